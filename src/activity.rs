@@ -0,0 +1,399 @@
+// Activity Module - Live feed of the tool calls OpenCode is making
+//
+// OpenCode sends an update for every state change of a tool call (pending,
+// running, completed). Updates carrying the same call ID replace the earlier
+// line, so each call shows up once with its latest status. Tools other than
+// OpenCode's own are labeled and described by `[[tools]]` rules when one
+// matches, and generically otherwise.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::diff::{self, DiffLine};
+use crate::tools::{self, ToolRule};
+use crate::transport::ToolEvent;
+
+/// Tool calls kept in the feed.
+pub const FEED_LIMIT: usize = 100;
+
+/// Longest target shown before it is cut with an ellipsis.
+const MAX_TARGET_CHARS: usize = 60;
+
+/// Diff lines kept for a completed edit or write.
+pub const MAX_DIFF_LINES: usize = 20;
+
+/// Where a tool call is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivityStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl ActivityStatus {
+    /// Map an OpenCode tool state string.
+    pub fn from_state(state: &str) -> Self {
+        match state {
+            "running" => ActivityStatus::Running,
+            "completed" => ActivityStatus::Completed,
+            "error" => ActivityStatus::Failed,
+            _ => ActivityStatus::Pending,
+        }
+    }
+
+    /// Whether the call has finished, successfully or not.
+    pub fn is_done(self) -> bool {
+        matches!(self, ActivityStatus::Completed | ActivityStatus::Failed)
+    }
+}
+
+/// One tool call in the feed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Activity {
+    pub call_id: Option<String>,
+    /// The assistant message that made the call.
+    pub message_id: Option<String>,
+    pub tool: String,
+    /// Name shown in the feed: a rule's label, else the tool name.
+    pub label: String,
+    /// Whether the tool is an MCP or custom tool rather than a built-in one.
+    pub custom: bool,
+    /// What the call acts on: a path, a command, a pattern, ...
+    pub target: String,
+    pub status: ActivityStatus,
+    /// The change a completed edit or write made, cut to
+    /// [`MAX_DIFF_LINES`].
+    pub diff: Vec<DiffLine>,
+}
+
+impl Activity {
+    pub fn from_event(event: &ToolEvent, rules: &[ToolRule]) -> Self {
+        let rule = tools::rule_for(rules, &event.tool);
+        Self {
+            call_id: event.call_id.clone(),
+            message_id: event.message_id.clone(),
+            tool: event.tool.clone(),
+            label: rule
+                .and_then(|r| r.label.clone())
+                .unwrap_or_else(|| event.tool.clone()),
+            custom: !tools::is_builtin(&event.tool),
+            target: describe_target(event, rule.and_then(|r| r.target.as_deref())),
+            status: ActivityStatus::from_state(&event.state),
+            diff: diff::from_event(event)
+                .unwrap_or_default()
+                .into_iter()
+                .take(MAX_DIFF_LINES)
+                .collect(),
+        }
+    }
+
+    /// Status marker shown after the target.
+    pub fn status_suffix(&self) -> &'static str {
+        match self.status {
+            ActivityStatus::Pending => " \u{2026} pending",
+            ActivityStatus::Running => " \u{2026} running",
+            ActivityStatus::Completed => " \u{2713}",
+            ActivityStatus::Failed => " \u{2717} failed",
+        }
+    }
+}
+
+impl fmt::Display for Activity {
+    /// "read src/main.rs ✓", "bash cargo test … running"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label)?;
+        if !self.target.is_empty() {
+            write!(f, " {}", self.target)?;
+        }
+        write!(f, "{}", self.status_suffix())
+    }
+}
+
+/// The most telling input of a tool call, on one line: the `key` field
+/// when given, else a well-known field, else the first string field.
+pub fn describe_target(event: &ToolEvent, key: Option<&str>) -> String {
+    const KEYS: [&str; 6] = [
+        "filePath",
+        "command",
+        "path",
+        "pattern",
+        "url",
+        "description",
+    ];
+    let value = match key {
+        Some(key) => tools::input_str(event, key),
+        None => KEYS
+            .iter()
+            .find_map(|key| tools::input_str(event, key))
+            .or_else(|| {
+                event
+                    .input
+                    .as_object()?
+                    .values()
+                    .find_map(|v| v.as_str().filter(|s| !s.trim().is_empty()))
+            }),
+    };
+    let Some(value) = value else {
+        return String::new();
+    };
+    let line = value.lines().next().unwrap_or("").trim();
+    if line.chars().count() > MAX_TARGET_CHARS || value.lines().nth(1).is_some() {
+        let cut: String = line.chars().take(MAX_TARGET_CHARS).collect();
+        format!("{}\u{2026}", cut)
+    } else {
+        line.to_string()
+    }
+}
+
+/// Bounded feed of recent tool calls, oldest first.
+#[derive(Debug, Default)]
+pub struct ActivityFeed {
+    entries: VecDeque<Activity>,
+    /// `[[tools]]` rules for non-built-in tools.
+    rules: Vec<ToolRule>,
+}
+
+impl ActivityFeed {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A feed that describes non-built-in tools with `rules`.
+    pub fn with_rules(rules: Vec<ToolRule>) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            rules,
+        }
+    }
+
+    /// Describe non-built-in tools with `rules` from now on.
+    pub fn set_rules(&mut self, rules: Vec<ToolRule>) {
+        self.rules = rules;
+    }
+
+    /// Add a tool event, updating the line of the same call if it is
+    /// already in the feed. Returns the call as it now stands.
+    pub fn record(&mut self, event: &ToolEvent) -> &Activity {
+        let activity = Activity::from_event(event, &self.rules);
+        let existing = activity.call_id.as_ref().and_then(|id| {
+            self.entries
+                .iter()
+                .rposition(|a| a.call_id.as_ref() == Some(id))
+        });
+        if let Some(index) = existing {
+            let existing = &mut self.entries[index];
+            // Later updates can arrive with a fuller input
+            if activity.target.is_empty() {
+                existing.status = activity.status;
+                if !activity.diff.is_empty() {
+                    existing.diff = activity.diff;
+                }
+            } else {
+                *existing = activity;
+            }
+            return existing;
+        }
+        if self.entries.len() == FEED_LIMIT {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(activity);
+        &self.entries[self.entries.len() - 1]
+    }
+
+    /// Feed entries, oldest first.
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &Activity> + ExactSizeIterator {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop the calls made by message `message_id`, which was removed.
+    /// Returns the number of calls dropped.
+    pub fn remove_message(&mut self, message_id: &str) -> usize {
+        let before = self.entries.len();
+        self.entries
+            .retain(|a| a.message_id.as_deref() != Some(message_id));
+        before - self.entries.len()
+    }
+
+    /// Calls that haven't finished yet.
+    pub fn in_flight(&self) -> usize {
+        self.entries.iter().filter(|a| !a.status.is_done()).count()
+    }
+
+    /// The latest call that hasn't finished yet.
+    pub fn current(&self) -> Option<&Activity> {
+        self.entries.iter().rev().find(|a| !a.status.is_done())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: Option<&str>, tool: &str, state: &str, input: serde_json::Value) -> ToolEvent {
+        ToolEvent {
+            call_id: id.map(str::to_string),
+            session_id: None,
+            message_id: None,
+            tool: tool.into(),
+            input,
+            state: state.into(),
+            metadata: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let read = event(
+            None,
+            "read",
+            "completed",
+            serde_json::json!({"filePath": "src/main.rs"}),
+        );
+        assert_eq!(
+            Activity::from_event(&read, &[]).to_string(),
+            "read src/main.rs \u{2713}"
+        );
+        let bash = event(
+            None,
+            "bash",
+            "running",
+            serde_json::json!({"command": "cargo test", "description": "run tests"}),
+        );
+        assert_eq!(
+            Activity::from_event(&bash, &[]).to_string(),
+            "bash cargo test \u{2026} running"
+        );
+        let todo = event(None, "todowrite", "error", serde_json::json!({}));
+        assert_eq!(
+            Activity::from_event(&todo, &[]).to_string(),
+            "todowrite \u{2717} failed"
+        );
+    }
+
+    #[test]
+    fn test_updates_replace_the_same_call() {
+        let mut feed = ActivityFeed::new();
+        let input = serde_json::json!({"command": "cargo test"});
+        feed.record(&event(Some("c1"), "bash", "pending", serde_json::json!({})));
+        feed.record(&event(Some("c1"), "bash", "running", input.clone()));
+        feed.record(&event(
+            Some("c2"),
+            "read",
+            "completed",
+            serde_json::json!({"filePath": "a.rs"}),
+        ));
+        assert_eq!(feed.len(), 2);
+        assert_eq!(feed.in_flight(), 1);
+        assert_eq!(feed.current().unwrap().tool, "bash");
+        feed.record(&event(Some("c1"), "bash", "completed", input));
+        let lines: Vec<String> = feed.entries().map(|a| a.to_string()).collect();
+        assert_eq!(
+            lines,
+            vec!["bash cargo test \u{2713}", "read a.rs \u{2713}"]
+        );
+        assert_eq!(feed.in_flight(), 0);
+        assert!(feed.current().is_none());
+    }
+
+    #[test]
+    fn test_events_without_id_are_appended() {
+        let mut feed = ActivityFeed::new();
+        let input = serde_json::json!({"path": "src"});
+        feed.record(&event(None, "list", "completed", input.clone()));
+        feed.record(&event(None, "list", "completed", input));
+        assert_eq!(feed.len(), 2);
+    }
+
+    #[test]
+    fn test_feed_is_bounded() {
+        let mut feed = ActivityFeed::new();
+        for i in 0..FEED_LIMIT + 3 {
+            let id = format!("c{i}");
+            feed.record(&event(
+                Some(&id),
+                "read",
+                "completed",
+                serde_json::json!({}),
+            ));
+        }
+        assert_eq!(feed.len(), FEED_LIMIT);
+        assert_eq!(
+            feed.entries().next().unwrap().call_id.as_deref(),
+            Some("c3")
+        );
+    }
+
+    #[test]
+    fn test_remove_message() {
+        let mut feed = ActivityFeed::new();
+        for (id, message) in [("c1", "m1"), ("c2", "m2"), ("c3", "m1")] {
+            let mut call = event(Some(id), "read", "completed", serde_json::json!({}));
+            call.message_id = Some(message.into());
+            feed.record(&call);
+        }
+        assert_eq!(feed.remove_message("m1"), 2);
+        let left: Vec<_> = feed.entries().map(|a| a.call_id.as_deref()).collect();
+        assert_eq!(left, vec![Some("c2")]);
+        assert_eq!(feed.remove_message("m1"), 0);
+    }
+
+    #[test]
+    fn test_long_and_multiline_targets_are_cut() {
+        let long = "x".repeat(100);
+        let bash = event(
+            None,
+            "bash",
+            "running",
+            serde_json::json!({"command": long}),
+        );
+        let target = describe_target(&bash, None);
+        assert_eq!(target.chars().count(), MAX_TARGET_CHARS + 1);
+        assert!(target.ends_with('\u{2026}'));
+        let script = event(
+            None,
+            "bash",
+            "running",
+            serde_json::json!({"command": "set -e\nmake"}),
+        );
+        assert_eq!(describe_target(&script, None), "set -e\u{2026}");
+    }
+
+    #[test]
+    fn test_custom_tools() {
+        let input = serde_json::json!({"owner": "rshigg", "repo": "conch", "count": 3});
+        // Without a rule: shown under its own name with the first string input
+        let generic = Activity::from_event(
+            &event(None, "github_list_issues", "completed", input.clone()),
+            &[],
+        );
+        assert!(generic.custom);
+        assert_eq!(generic.to_string(), "github_list_issues rshigg \u{2713}");
+        // A rule picks the label and the input to show
+        let rules = vec![ToolRule {
+            name: "github_*".into(),
+            label: Some("github".into()),
+            target: Some("repo".into()),
+            focus: None,
+            focus_key: None,
+        }];
+        let mut feed = ActivityFeed::with_rules(rules);
+        feed.record(&event(None, "github_list_issues", "running", input));
+        let activity = feed.entries().next().unwrap();
+        assert_eq!(activity.to_string(), "github conch \u{2026} running");
+        assert_eq!(activity.tool, "github_list_issues");
+        let read = Activity::from_event(
+            &event(None, "read", "completed", serde_json::json!({})),
+            &[],
+        );
+        assert!(!read.custom);
+    }
+}
@@ -0,0 +1,137 @@
+// Agent Module - Which OpenCode agent prompts go to
+//
+// OpenCode runs each prompt with an agent: `build` makes changes, `plan`
+// only looks and proposes, and projects can define their own. Prompts name
+// the agent in their body; without one OpenCode uses its default. The
+// `[agent]` section picks the agent conch starts with and lists the agents
+// to switch between, by voice ("use the plan agent") or with a key.
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+
+use crate::send::normalize_words;
+
+/// Spoken forms of a switch, already normalized, around the agent's name.
+const SWITCH_COMMANDS: &[(&str, &str)] = &[
+    ("use the", "agent"),
+    ("use", "agent"),
+    ("switch to the", "agent"),
+    ("switch to", "agent"),
+    ("use agent", ""),
+    ("switch to agent", ""),
+];
+
+/// `[agent]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AgentConfig {
+    /// Agent prompts go to at startup. Unset leaves it to OpenCode.
+    pub default: Option<String>,
+    /// Agents to switch between, in the order the key cycles through them.
+    pub names: Vec<String>,
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            default: None,
+            names: vec!["build".into(), "plan".into()],
+        }
+    }
+}
+
+impl AgentConfig {
+    /// The agents to switch between, the default included.
+    pub fn agents(&self) -> Vec<String> {
+        let mut agents = self.names.clone();
+        if let Some(default) = &self.default
+            && !agents.contains(default)
+        {
+            agents.insert(0, default.clone());
+        }
+        agents
+    }
+}
+
+/// The agent after `current` in `agents`, wrapping around; the first when
+/// `current` isn't one of them.
+pub fn next<'a>(agents: &'a [String], current: Option<&str>) -> Option<&'a str> {
+    let index = match agents.iter().position(|a| Some(a.as_str()) == current) {
+        Some(i) => (i + 1) % agents.len(),
+        None => 0,
+    };
+    agents.get(index).map(String::as_str)
+}
+
+/// If `transcript` is an agent switch command, the agent among `agents` to
+/// switch to, or why it couldn't be switched. `None` for ordinary
+/// transcripts. Names match ignoring case and punctuation, so "docs writer"
+/// finds `docs-writer`.
+pub fn voice_switch(transcript: &str, agents: &[String]) -> Option<Result<String>> {
+    let words = normalize_words(transcript);
+    SWITCH_COMMANDS.iter().find_map(|(before, after)| {
+        let rest = words.strip_prefix(before)?.strip_prefix(' ')?;
+        let name = match *after {
+            "" => rest,
+            after => rest.strip_suffix(after)?.strip_suffix(' ')?,
+        };
+        // "use the" alone isn't a name
+        if name.is_empty() || name == "the" {
+            return None;
+        }
+        Some(
+            agents
+                .iter()
+                .find(|a| normalize_words(a) == name)
+                .cloned()
+                .ok_or_else(|| anyhow!("unknown agent '{}' ({})", name, agents.join(", "))),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agents() -> Vec<String> {
+        ["build", "plan", "docs-writer"].map(String::from).to_vec()
+    }
+
+    #[test]
+    fn test_voice_switch() {
+        let agents = agents();
+        let switch = |t| voice_switch(t, &agents).map(|r| r.map_err(|e| e.to_string()));
+        assert_eq!(switch("Use the plan agent."), Some(Ok("plan".into())));
+        assert_eq!(switch("switch to build agent"), Some(Ok("build".into())));
+        assert_eq!(switch("use agent plan"), Some(Ok("plan".into())));
+        assert_eq!(
+            switch("Switch to the docs writer agent"),
+            Some(Ok("docs-writer".into()))
+        );
+        let err = switch("use the review agent").unwrap().unwrap_err();
+        assert!(err.contains("unknown agent 'review' (build, plan, docs-writer)"));
+        assert_eq!(switch("use the plan agent to fix it"), None);
+        assert_eq!(switch("use the agent"), None);
+        assert_eq!(switch("fix the agent config"), None);
+    }
+
+    #[test]
+    fn test_next_wraps_around() {
+        let agents = agents();
+        assert_eq!(next(&agents, None), Some("build"));
+        assert_eq!(next(&agents, Some("build")), Some("plan"));
+        assert_eq!(next(&agents, Some("docs-writer")), Some("build"));
+        assert_eq!(next(&agents, Some("custom")), Some("build"));
+        assert_eq!(next(&[], None), None);
+    }
+
+    #[test]
+    fn test_default_joins_the_agents() {
+        let config = AgentConfig {
+            default: Some("review".into()),
+            ..AgentConfig::default()
+        };
+        assert_eq!(config.agents(), vec!["review", "build", "plan"]);
+        assert_eq!(AgentConfig::default().agents(), vec!["build", "plan"]);
+    }
+}
@@ -0,0 +1,106 @@
+// Answer Module - Short spoken answers to the questions conch asks
+//
+// While a prompt is pending, conch is asking "send this?". A recording made
+// then is an answer rather than the next prompt: it is capped at a few
+// seconds, transcribed with Whisper primed for yes/no/cancel, and only
+// accepted if the whole transcript is one of the words below. Anything else
+// is reported, never sent. Asking to try again runs the prompt's recording
+// through the bigger `[stt] retry_model`, and naming a target ("send to
+// notes") sends the prompt there instead of to OpenCode.
+
+use std::time::Duration;
+
+use crate::route::{self, Target};
+use crate::send::normalize_words;
+
+/// Whisper's initial prompt for answers, biasing it toward the grammar.
+pub const VOCABULARY: &str = "Yes. No. Cancel. Try again. Send to notes. Copy it. Type it.";
+/// Longest answer recording; it stops by itself after this.
+pub const MAX_RECORDING: Duration = Duration::from_secs(3);
+
+const YES: &[&str] = &[
+    "yes", "yeah", "yep", "yup", "sure", "ok", "okay", "send", "send it", "go", "go ahead",
+    "do it", "confirm",
+];
+const NO: &[&str] = &[
+    "no",
+    "nope",
+    "nah",
+    "discard",
+    "discard it",
+    "scrap it",
+    "drop it",
+];
+const CANCEL: &[&str] = &[
+    "cancel",
+    "wait",
+    "hold on",
+    "stop",
+    "never mind",
+    "not yet",
+    "keep it",
+];
+const RETRY: &[&str] = &[
+    "try again",
+    "again",
+    "retry",
+    "redo",
+    "use the big model",
+    "try again with the big model",
+    "try again with the bigger model",
+    "try the big model",
+];
+
+/// An answer to "send this?".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Answer {
+    /// Send the pending prompt.
+    Yes,
+    /// Discard the pending prompt.
+    No,
+    /// Keep the prompt pending, stopping an auto-send countdown.
+    Cancel,
+    /// Transcribe the prompt's recording again with the bigger model.
+    Retry,
+    /// Send the pending prompt to a target other than OpenCode.
+    Route(Target),
+}
+
+/// The answer `transcript` gives, if it is nothing but an answer.
+pub fn parse(transcript: &str) -> Option<Answer> {
+    let words = normalize_words(transcript);
+    let is = |list: &[&str]| list.contains(&words.as_str());
+    if is(YES) {
+        Some(Answer::Yes)
+    } else if is(NO) {
+        Some(Answer::No)
+    } else if is(CANCEL) {
+        Some(Answer::Cancel)
+    } else if is(RETRY) {
+        Some(Answer::Retry)
+    } else {
+        route::spoken(transcript).map(Answer::Route)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_answers() {
+        assert_eq!(parse("Yes."), Some(Answer::Yes));
+        assert_eq!(parse(" Go ahead!"), Some(Answer::Yes));
+        assert_eq!(parse("No."), Some(Answer::No));
+        assert_eq!(parse("Never mind."), Some(Answer::Cancel));
+        assert_eq!(parse("Try again with the big model."), Some(Answer::Retry));
+        assert_eq!(parse("Send to notes."), Some(Answer::Route(Target::Notes)));
+    }
+
+    #[test]
+    fn test_anything_else_is_not_an_answer() {
+        assert_eq!(parse("yes and also run the tests"), None);
+        assert_eq!(parse("no wait"), None);
+        assert_eq!(parse(""), None);
+    }
+}
@@ -1,10 +1,187 @@
 // Audio Module - Captures mic input via cpal, manages ring buffer, provides PCM data
 
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 use anyhow::{Result, anyhow};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{FromSample, Sample, SizedSample};
+use serde::Deserialize;
+
+use crate::mixer::{MixMode, MixSource};
+use crate::remote_mic::NetSource;
+
+/// Seconds of audio a recording can hold before its beginning is overwritten.
+pub const BUFFER_SECS: usize = 60;
+/// Seconds of idle audio kept for wake word detection.
+const MONITOR_SECS: usize = crate::wake::WAKE_WINDOW_SECS as usize;
+
+/// Restarts of a failed stream tried in a row before giving up.
+pub const MAX_RESTARTS: u32 = 5;
+/// Wait before the first restart; each next one waits twice as long.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+/// A restarted stream that runs this long without errors starts the count
+/// of restarts over.
+const STABLE: Duration = Duration::from_secs(60);
+
+/// Whisper works at 16 kHz; below that, speech loses detail it needs.
+/// Bluetooth headsets in hands-free (HFP) mode deliver 8 kHz.
+pub const MIN_SPEECH_SAMPLE_RATE: u32 = 16_000;
+
+/// Why a mic at `sample_rate` will give poor transcripts and what to do
+/// about it, or `None` if the rate is fine.
+pub fn low_rate_warning(sample_rate: u32) -> Option<String> {
+    (sample_rate < MIN_SPEECH_SAMPLE_RATE).then(|| {
+        format!(
+            "Mic runs at {} Hz, too low for accurate transcripts. A Bluetooth headset \
+             is likely in hands-free (HFP) mode: switch it to its high-quality (A2DP) \
+             profile, or record from another mic (device under [audio])",
+            sample_rate
+        )
+    })
+}
+
+/// `[audio]` section of the config file: which backend and device to capture
+/// from. Everything defaults to what cpal picks for the platform.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AudioConfig {
+    /// Audio host by name ("alsa", "jack", "wasapi", "asio", "coreaudio").
+    /// Only hosts cpal was built with are available; `conch devices` lists them.
+    pub host: Option<String>,
+    /// Input device by name, or a unique part of it ("pipewire", "USB").
+    pub device: Option<String>,
+    /// Several input devices to record from at once, combined as `mix`
+    /// says. Overrides `device`.
+    pub devices: Vec<String>,
+    /// How the `devices` are combined.
+    pub mix: MixMode,
+    /// Fixed buffer size in frames, for drivers that misbehave with the default.
+    pub buffer_frames: Option<u32>,
+    /// Refuse to record from a mic below [`MIN_SPEECH_SAMPLE_RATE`] instead
+    /// of only warning.
+    pub refuse_low_rate: bool,
+    /// Take the audio `conch mic` streams from another machine, listening
+    /// at this address ("127.0.0.1:4101"), instead of opening a device.
+    pub listen: Option<String>,
+}
+
+/// Index of the name in `names` that `wanted` picks: an exact match ignoring
+/// case, else the only name containing it.
+pub fn match_name<S: AsRef<str>>(names: &[S], wanted: &str) -> Option<usize> {
+    let wanted = wanted.to_lowercase();
+    let lower: Vec<String> = names.iter().map(|n| n.as_ref().to_lowercase()).collect();
+    if let Some(i) = lower.iter().position(|n| *n == wanted) {
+        return Some(i);
+    }
+    let mut containing = lower
+        .iter()
+        .enumerate()
+        .filter(|(_, n)| n.contains(&wanted));
+    match (containing.next(), containing.next()) {
+        (Some((i, _)), None) => Some(i),
+        _ => None,
+    }
+}
+
+/// Names of the audio hosts available on this platform.
+pub fn host_names() -> Vec<&'static str> {
+    cpal::available_hosts().iter().map(|id| id.name()).collect()
+}
+
+/// Name of the host cpal uses when none is configured.
+pub fn default_host_name() -> &'static str {
+    cpal::default_host().id().name()
+}
+
+/// The host named `name`, or the platform default.
+fn select_host(name: Option<&str>) -> Result<cpal::Host> {
+    let Some(name) = name else {
+        return Ok(cpal::default_host());
+    };
+    let hosts = cpal::available_hosts();
+    let names: Vec<&str> = hosts.iter().map(|id| id.name()).collect();
+    let index = match_name(&names, name).ok_or_else(|| {
+        anyhow!(
+            "Audio host '{}' not available (available: {})",
+            name,
+            names.join(", ")
+        )
+    })?;
+    Ok(cpal::host_from_id(hosts[index])?)
+}
+
+/// The input device chosen in `audio`, or a mix of them when `devices`
+/// names more than one.
+pub fn open_mics(audio: &AudioConfig) -> Result<Box<dyn AudioSource>> {
+    if audio.devices.len() > 1 {
+        return Ok(Box::new(MixSource::open(audio)?));
+    }
+    Ok(Box::new(MicSource::new(audio)?))
+}
+
+/// What `conch devices` prints: the audio `hosts`, `chosen` marked with
+/// `*`, then the chosen host's input `devices`.
+pub fn device_listing(hosts: &[&str], chosen: &str, devices: &[String]) -> String {
+    let mut out = String::from("Audio hosts:\n");
+    for name in hosts {
+        let marker = if chosen.eq_ignore_ascii_case(name) {
+            "*"
+        } else {
+            " "
+        };
+        out.push_str(&format!("{} {}\n", marker, name));
+    }
+    out.push_str("\nInput devices:\n");
+    if devices.is_empty() {
+        out.push_str("  (none)\n");
+    }
+    for name in devices {
+        out.push_str(&format!("  {}\n", name));
+    }
+    out
+}
+
+/// `conch devices`: the [`device_listing`] of this machine, for `host` or
+/// the platform default.
+pub fn list_devices(host: Option<&str>) -> Result<String> {
+    let chosen = host.unwrap_or(default_host_name());
+    let devices = input_device_names(host)?;
+    Ok(device_listing(&host_names(), chosen, &devices))
+}
+
+/// Names of the input devices of `host` (the default host when `None`).
+pub fn input_device_names(host: Option<&str>) -> Result<Vec<String>> {
+    let host = select_host(host)?;
+    Ok(host
+        .input_devices()?
+        .filter_map(|device| device.name().ok())
+        .collect())
+}
+
+/// The input device named `name` on `host`, or the host's default.
+fn select_device(host: &cpal::Host, name: Option<&str>) -> Result<cpal::Device> {
+    let Some(name) = name else {
+        return host
+            .default_input_device()
+            .ok_or_else(|| anyhow!("No audio input device found"));
+    };
+    let devices: Vec<cpal::Device> = host.input_devices()?.collect();
+    let names: Vec<String> = devices
+        .iter()
+        .map(|d| d.name().unwrap_or_default())
+        .collect();
+    let index = match_name(&names, name).ok_or_else(|| {
+        anyhow!(
+            "No single input device matches '{}' on {} (devices: {})",
+            name,
+            host.id().name(),
+            names.join(", ")
+        )
+    })?;
+    Ok(devices.into_iter().nth(index).expect("index from names"))
+}
 
 /// Recording state machine
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -73,11 +250,19 @@ impl RingBuffer {
 
     /// Read the last `n` samples (most recent). Used for FFT visualization.
     pub fn read_last(&self, n: usize) -> Vec<f32> {
-        let n = n.min(self.count);
+        let mut result = vec![0.0; n.min(self.count)];
+        self.read_last_into(&mut result);
+        result
+    }
+
+    /// Copy the most recent samples into `out`, oldest first, as many as
+    /// fit. Returns how many were copied: fewer than `out.len()` when the
+    /// buffer holds fewer.
+    pub fn read_last_into(&self, out: &mut [f32]) -> usize {
+        let n = out.len().min(self.count);
         if n == 0 {
-            return Vec::new();
+            return 0;
         }
-        let mut result = Vec::with_capacity(n);
         // Start position is n samples before write_pos
         let start = if self.write_pos >= n {
             self.write_pos - n
@@ -85,13 +270,13 @@ impl RingBuffer {
             self.capacity - (n - self.write_pos)
         };
         if start + n <= self.capacity {
-            result.extend_from_slice(&self.data[start..start + n]);
+            out[..n].copy_from_slice(&self.data[start..start + n]);
         } else {
-            result.extend_from_slice(&self.data[start..]);
-            let remaining = n - (self.capacity - start);
-            result.extend_from_slice(&self.data[..remaining]);
+            let first = self.capacity - start;
+            out[..first].copy_from_slice(&self.data[start..]);
+            out[first..n].copy_from_slice(&self.data[..n - first]);
         }
-        result
+        n
     }
 
     /// Clear all data from the buffer.
@@ -120,79 +305,444 @@ impl RingBuffer {
     pub fn capacity(&self) -> usize {
         self.capacity
     }
+
+    /// Samples written since the last clear, as a fraction of the capacity.
+    /// Above 1.0 the oldest samples have been overwritten.
+    pub fn usage(&self) -> f32 {
+        self.total_written as f32 / self.capacity as f32
+    }
+}
+
+/// How much a sample must differ from the one before for the signal to be
+/// alive. Well under one step of 16-bit audio.
+const LIVE_DELTA: f32 = 1e-6;
+
+/// Watches a recording for a mic that delivers nothing: only zeros, or one
+/// constant level. A hardware mute switch, or a mic the OS hasn't given
+/// conch permission to, looks like this; a live mic never holds still.
+#[derive(Debug, Clone, Default)]
+pub struct SignalHealth {
+    /// Samples seen since the recording started.
+    seen: usize,
+    /// `seen` when the signal last moved.
+    last_live: usize,
+    previous: f32,
+}
+
+impl SignalHealth {
+    pub fn feed(&mut self, sample: f32) {
+        if (sample - self.previous).abs() > LIVE_DELTA {
+            self.last_live = self.seen;
+        }
+        self.previous = sample;
+        self.seen += 1;
+    }
+
+    /// Samples since the signal last moved, or since the recording started.
+    pub fn dead_samples(&self) -> usize {
+        self.seen - self.last_live
+    }
 }
 
 /// Shared state between the audio callback thread and the main thread.
 struct SharedAudioState {
     recording: bool,
     buffer: RingBuffer,
+    /// Liveness of the recording's signal.
+    health: SignalHealth,
+    /// Keep capturing into `monitor` while not recording (wake word).
+    monitoring: bool,
+    /// Short rolling window of idle audio, written only while monitoring.
+    monitor: RingBuffer,
+}
+
+/// Where captured samples go: the recording buffer while recording, the
+/// monitor window while monitoring, nowhere otherwise. A sink can also hand
+/// them to something else on the way, like the [`Mixer`](crate::mixer::Mixer)
+/// of several mics. Sources report errors of their stream to it too.
+#[derive(Clone)]
+pub struct SampleSink {
+    target: SinkTarget,
+    errors: StreamErrors,
+}
+
+/// Takes the errors a sink is given.
+type Report = dyn Fn(String) + Send + Sync;
+
+/// Who hears of stream errors, shared by a sink's clones so a listener set
+/// after the stream started still hears from it. An error with nobody
+/// listening yet is kept for the first listener.
+#[derive(Clone, Default)]
+struct StreamErrors(Arc<Mutex<ErrorListener>>);
+
+#[derive(Default)]
+struct ErrorListener {
+    report: Option<Box<Report>>,
+    unheard: Option<String>,
+}
+
+impl StreamErrors {
+    fn report(&self, error: String) {
+        let mut listener = self.0.lock().unwrap();
+        match &listener.report {
+            Some(report) => report(error),
+            None => listener.unheard = Some(error),
+        }
+    }
+
+    fn listen(&self, report: impl Fn(String) + Send + Sync + 'static) {
+        let mut listener = self.0.lock().unwrap();
+        if let Some(error) = listener.unheard.take() {
+            report(error);
+        }
+        listener.report = Some(Box::new(report));
+    }
+}
+
+/// Takes the samples a forwarding sink is given.
+type Forward = dyn Fn(&mut dyn Iterator<Item = f32>) + Send + Sync;
+
+#[derive(Clone)]
+enum SinkTarget {
+    Capture(Arc<Mutex<SharedAudioState>>),
+    Forward(Arc<Forward>),
+}
+
+impl SampleSink {
+    /// A sink passing samples to `forward` instead of the capture buffers.
+    pub fn forward(
+        forward: impl Fn(&mut dyn Iterator<Item = f32>) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            target: SinkTarget::Forward(Arc::new(forward)),
+            errors: StreamErrors::default(),
+        }
+    }
+
+    /// Report stream errors to whoever hears those of `sink`, for a sink
+    /// forwarding to it.
+    pub fn sharing_errors(mut self, sink: &SampleSink) -> Self {
+        self.errors = sink.errors.clone();
+        self
+    }
+
+    /// Hand stream errors to `report`.
+    pub fn on_error(self, report: impl Fn(String) + Send + Sync + 'static) -> Self {
+        self.errors.listen(report);
+        self
+    }
+
+    /// The stream feeding this sink failed with `error`. Called from the
+    /// audio thread; the listener decides what to do about it.
+    pub fn report_error(&self, error: String) {
+        self.errors.report(error);
+    }
+
+    /// Write mono samples. Never blocks: if the state is locked, the samples
+    /// are dropped, which is acceptable for a voice input client.
+    pub fn push(&self, samples: impl IntoIterator<Item = f32>) {
+        let shared = match &self.target {
+            SinkTarget::Capture(shared) => shared,
+            SinkTarget::Forward(forward) => {
+                forward(&mut samples.into_iter());
+                return;
+            }
+        };
+        let _span = tracing::trace_span!("audio_push").entered();
+        let Ok(mut state) = shared.try_lock() else {
+            return;
+        };
+        if state.recording {
+            for sample in samples {
+                state.buffer.write(&[sample]);
+                state.health.feed(sample);
+            }
+        } else if state.monitoring {
+            for sample in samples {
+                state.monitor.write(&[sample]);
+            }
+        }
+    }
+}
+
+/// Something that produces mono audio: the microphone, or a fake one for
+/// development and CI (see [`crate::fake_audio`]). An [`AudioCapture`] can
+/// move between threads, so its source must be `Send`; one whose audio API
+/// isn't keeps that on a thread of its own, as [`MicSource`] does.
+pub trait AudioSource: Send {
+    /// Samples per second of the audio passed to the sink.
+    fn sample_rate(&self) -> u32;
+
+    /// Start feeding `sink`. Audio keeps flowing until the source is dropped.
+    fn start(&mut self, sink: SampleSink) -> Result<()>;
+
+    /// Why the source has no audio to give right now, if it can tell.
+    fn unavailable(&self) -> Option<String> {
+        None
+    }
+
+    /// Build the stream again after it reported an error, feeding the same
+    /// sink. Sources whose stream can't fail have nothing to do.
+    fn restart(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The input device of an audio host, via cpal. Its stream lives on a
+/// [`StreamThread`].
+pub struct MicSource {
+    sample_rate: u32,
+    /// Moved to the stream's thread once started.
+    device: Option<MicDevice>,
+    stream: Option<StreamThread>,
+}
+
+/// An opened input device and the stream config chosen for it.
+struct MicDevice {
+    /// Host and device as configured, to find the device again on restart.
+    host: Option<String>,
+    name: Option<String>,
+    device: cpal::Device,
+    config: cpal::StreamConfig,
+    sample_format: cpal::SampleFormat,
+}
+
+impl MicSource {
+    /// Open the host and device chosen in `audio`.
+    pub fn new(audio: &AudioConfig) -> Result<Self> {
+        let device = audio.devices.first().or(audio.device.as_ref());
+        Self::open(audio, device.map(String::as_str), None)
+    }
+
+    /// Open input device `name` (the default one when `None`) on the host
+    /// chosen in `audio`, at `sample_rate` when given rather than the
+    /// device's default rate.
+    pub fn open(audio: &AudioConfig, name: Option<&str>, sample_rate: Option<u32>) -> Result<Self> {
+        let host = select_host(audio.host.as_deref())?;
+        let device = select_device(&host, name)?;
+
+        let supported_config = match sample_rate {
+            None => device.default_input_config()?,
+            Some(rate) => device
+                .supported_input_configs()?
+                .filter(|c| {
+                    use cpal::SampleFormat::{F32, I16, U16};
+                    matches!(c.sample_format(), F32 | I16 | U16)
+                })
+                .find(|c| (c.min_sample_rate().0..=c.max_sample_rate().0).contains(&rate))
+                .map(|c| c.with_sample_rate(cpal::SampleRate(rate)))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Input device '{}' can't record at {} Hz",
+                        device.name().unwrap_or_default(),
+                        rate
+                    )
+                })?,
+        };
+        let sample_format = supported_config.sample_format();
+        let mut config: cpal::StreamConfig = supported_config.into();
+        if let Some(frames) = audio.buffer_frames {
+            config.buffer_size = cpal::BufferSize::Fixed(frames);
+        }
+        Ok(Self {
+            sample_rate: config.sample_rate.0,
+            device: Some(MicDevice {
+                host: audio.host.clone(),
+                name: name.map(str::to_string),
+                device,
+                config,
+                sample_format,
+            }),
+            stream: None,
+        })
+    }
+}
+
+impl AudioSource for MicSource {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn start(&mut self, sink: SampleSink) -> Result<()> {
+        let device = self
+            .device
+            .take()
+            .ok_or_else(|| anyhow!("The mic is already started"))?;
+        self.stream = Some(StreamThread::spawn(device, sink)?);
+        Ok(())
+    }
+
+    fn restart(&mut self) -> Result<()> {
+        self.stream
+            .as_ref()
+            .ok_or_else(|| anyhow!("The mic was never started"))?
+            .restart()
+    }
+}
+
+impl MicDevice {
+    /// Build a stream feeding `sink` and start it.
+    fn play(&self, sink: &SampleSink) -> Result<cpal::Stream> {
+        let (device, config, sink) = (&self.device, &self.config, sink.clone());
+        let stream = match self.sample_format {
+            cpal::SampleFormat::F32 => build_input_stream::<f32>(device, config, sink)?,
+            cpal::SampleFormat::I16 => build_input_stream::<i16>(device, config, sink)?,
+            cpal::SampleFormat::U16 => build_input_stream::<u16>(device, config, sink)?,
+            format => return Err(anyhow!("Unsupported sample format: {:?}", format)),
+        };
+        stream.play()?;
+        Ok(stream)
+    }
+
+    /// Find the device again: one unplugged and plugged back in is a new
+    /// device. If it's gone, the old one gets another try.
+    fn reopen(&mut self) {
+        let host = select_host(self.host.as_deref());
+        if let Ok(device) = host.and_then(|host| select_device(&host, self.name.as_deref())) {
+            self.device = device;
+        }
+    }
+}
+
+/// A request to the thread owning a cpal stream.
+enum StreamCommand {
+    /// Build the stream again, answering how that went.
+    Restart(mpsc::SyncSender<Result<()>>),
+}
+
+/// The thread a cpal stream lives on. cpal streams can't move between
+/// threads on every platform, so this thread builds the stream, keeps it
+/// playing, and builds it again when asked to; what holds the handle is
+/// free to move. Dropping the handle stops the stream.
+struct StreamThread {
+    commands: Option<mpsc::Sender<StreamCommand>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl StreamThread {
+    /// Start the thread, once `device` plays into `sink`.
+    fn spawn(mut device: MicDevice, sink: SampleSink) -> Result<Self> {
+        let (commands, received) = mpsc::channel();
+        let (started, start) = mpsc::sync_channel(1);
+        // Named so the resources view counts it as audio capture
+        let thread = std::thread::Builder::new()
+            .name("cpal-stream".into())
+            .spawn(move || {
+                let mut stream = None;
+                let _ = started.send(device.play(&sink).map(|s| stream = Some(s)));
+                while let Ok(StreamCommand::Restart(reply)) = received.recv() {
+                    // Closed first; a device may not open twice
+                    drop(stream.take());
+                    device.reopen();
+                    let _ = reply.send(device.play(&sink).map(|s| stream = Some(s)));
+                }
+            })?;
+        let stream = Self {
+            commands: Some(commands),
+            thread: Some(thread),
+        };
+        start
+            .recv()
+            .map_err(|_| anyhow!("The audio stream thread stopped"))??;
+        Ok(stream)
+    }
+
+    fn restart(&self) -> Result<()> {
+        let (reply, result) = mpsc::sync_channel(1);
+        let stopped = || anyhow!("The audio stream thread stopped");
+        let commands = self.commands.as_ref().ok_or_else(stopped)?;
+        commands
+            .send(StreamCommand::Restart(reply))
+            .map_err(|_| stopped())?;
+        result.recv().map_err(|_| stopped())?
+    }
 }
 
-/// Audio capture system using cpal.
+impl Drop for StreamThread {
+    fn drop(&mut self) {
+        // Ends the thread's wait for commands, and with it the stream
+        self.commands = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Audio capture system.
 ///
-/// Manages the microphone input stream and a ring buffer for recorded audio.
-/// Supports push-to-talk: call `start_recording()` to begin capturing and
-/// `stop_recording()` to stop and extract the recorded samples.
+/// Runs an [`AudioSource`] (normally the microphone) into a ring buffer for
+/// recorded audio. Supports push-to-talk: call `start_recording()` to begin
+/// capturing and `stop_recording()` to stop and extract the recorded
+/// samples. With `set_monitoring(true)`, idle audio also flows into a short
+/// monitor window for wake word detection.
 pub struct AudioCapture {
     shared: Arc<Mutex<SharedAudioState>>,
-    _stream: cpal::Stream,
+    source: Mutex<Box<dyn AudioSource>>,
     sample_rate: u32,
+    errors: StreamErrors,
 }
 
-// cpal::Stream is not Send, but we ensure it's only accessed from the thread
-// that created it. The Arc<Mutex<SharedAudioState>> handles cross-thread access.
-unsafe impl Send for AudioCapture {}
-
 impl AudioCapture {
     /// Create a new AudioCapture using the default input device.
     ///
-    /// The ring buffer is sized for 60 seconds of audio at the device's sample rate.
+    /// The ring buffer is sized for [`BUFFER_SECS`] of audio at the device's sample rate.
     pub fn new() -> Result<Self> {
-        let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .ok_or_else(|| anyhow!("No audio input device found"))?;
+        Self::with_config(&AudioConfig::default())
+    }
 
-        let supported_config = device.default_input_config()?;
-        let sample_rate = supported_config.sample_rate().0;
-        let sample_format = supported_config.sample_format();
-        let channels = supported_config.channels() as usize;
-        let config: cpal::StreamConfig = supported_config.into();
+    /// Create an AudioCapture on the host and device chosen in `audio`,
+    /// mixing several devices when `devices` names more than one, or
+    /// taking `conch mic`'s audio when `listen` is set.
+    pub fn with_config(audio: &AudioConfig) -> Result<Self> {
+        if let Some(addr) = &audio.listen {
+            return Self::with_source(Box::new(NetSource::listen(addr)?));
+        }
+        Self::with_source(open_mics(audio)?)
+    }
 
-        // 60 seconds of mono audio at the device's sample rate
-        let buffer_capacity = sample_rate as usize * 60;
+    /// Create an AudioCapture fed by `source`.
+    pub fn with_source(mut source: Box<dyn AudioSource>) -> Result<Self> {
+        let sample_rate = source.sample_rate();
+        // BUFFER_SECS of mono audio at the source's sample rate
+        let buffer_capacity = sample_rate as usize * BUFFER_SECS;
         let shared = Arc::new(Mutex::new(SharedAudioState {
             recording: false,
             buffer: RingBuffer::new(buffer_capacity),
+            health: SignalHealth::default(),
+            monitoring: false,
+            monitor: RingBuffer::new(sample_rate as usize * MONITOR_SECS),
         }));
-
-        let shared_clone = Arc::clone(&shared);
-        let stream = match sample_format {
-            cpal::SampleFormat::F32 => {
-                build_input_stream::<f32>(&device, &config, shared_clone, channels)?
-            }
-            cpal::SampleFormat::I16 => {
-                build_input_stream::<i16>(&device, &config, shared_clone, channels)?
-            }
-            cpal::SampleFormat::U16 => {
-                build_input_stream::<u16>(&device, &config, shared_clone, channels)?
-            }
-            format => return Err(anyhow!("Unsupported sample format: {:?}", format)),
-        };
-
-        stream.play()?;
+        let errors = StreamErrors::default();
+        source.start(SampleSink {
+            target: SinkTarget::Capture(Arc::clone(&shared)),
+            errors: errors.clone(),
+        })?;
 
         Ok(Self {
             shared,
-            _stream: stream,
+            source: Mutex::new(source),
             sample_rate,
+            errors,
         })
     }
 
+    /// Hand errors of the source's stream to `report`, from the audio
+    /// thread. One that happened before is handed over at once.
+    pub fn on_stream_error(&self, report: impl Fn(String) + Send + Sync + 'static) {
+        self.errors.listen(report);
+    }
+
+    /// Build the source's stream again after an error. The recording, if
+    /// any, goes on with what the new stream captures.
+    pub fn restart(&self) -> Result<()> {
+        self.source.lock().unwrap().restart()
+    }
+
     /// Begin recording audio. Clears any previous buffer contents.
     pub fn start_recording(&self) {
         let mut state = self.shared.lock().unwrap();
         state.buffer.clear();
+        state.health = SignalHealth::default();
         state.recording = true;
     }
 
@@ -203,6 +753,14 @@ impl AudioCapture {
         state.buffer.read_all()
     }
 
+    /// Return the samples captured so far and clear them, recording on.
+    pub fn take_recording(&self) -> Vec<f32> {
+        let mut state = self.shared.lock().unwrap();
+        let samples = state.buffer.read_all();
+        state.buffer.clear();
+        samples
+    }
+
     /// Returns true if currently recording.
     pub fn is_recording(&self) -> bool {
         self.shared.lock().unwrap().recording
@@ -220,54 +778,157 @@ impl AudioCapture {
         }
     }
 
+    /// Copy the most recent samples into `out`, as
+    /// [`RingBuffer::read_last_into`] does, without allocating. Copies
+    /// nothing when not recording.
+    pub fn read_last_into(&self, out: &mut [f32]) -> usize {
+        let state = self.shared.lock().unwrap();
+        if state.recording {
+            state.buffer.read_last_into(out)
+        } else {
+            0
+        }
+    }
+
     /// Total samples written since recording started (monotonically increasing).
     pub fn total_samples_written(&self) -> usize {
         self.shared.lock().unwrap().buffer.total_written()
     }
 
+    /// How long the recording's signal has been dead: only zeros, or a
+    /// constant level. See [`SignalHealth`].
+    pub fn dead_for(&self) -> Duration {
+        let samples = self.shared.lock().unwrap().health.dead_samples();
+        Duration::from_secs_f64(samples as f64 / self.sample_rate as f64)
+    }
+
+    /// How full the recording buffer is; see [`RingBuffer::usage`].
+    pub fn buffer_usage(&self) -> f32 {
+        self.shared.lock().unwrap().buffer.usage()
+    }
+
     /// The sample rate of the audio input device in Hz.
     pub fn sample_rate(&self) -> u32 {
         self.sample_rate
     }
+
+    /// Why there's no audio to record right now, if the source can tell.
+    pub fn unavailable(&self) -> Option<String> {
+        self.source.lock().unwrap().unavailable()
+    }
+
+    /// Start or stop capturing idle audio into the monitor window.
+    /// Turning monitoring off discards the window.
+    pub fn set_monitoring(&self, on: bool) {
+        let mut state = self.shared.lock().unwrap();
+        state.monitoring = on;
+        if !on {
+            state.monitor.clear();
+        }
+    }
+
+    /// Returns true if idle audio is being monitored (the mic is hot).
+    pub fn is_monitoring(&self) -> bool {
+        self.shared.lock().unwrap().monitoring
+    }
+
+    /// Read the monitor window (oldest first).
+    pub fn read_monitor(&self) -> Vec<f32> {
+        self.shared.lock().unwrap().monitor.read_all()
+    }
+
+    /// Discard the monitor window, e.g. once an utterance has been judged.
+    pub fn clear_monitor(&self) {
+        self.shared.lock().unwrap().monitor.clear();
+    }
 }
 
-/// Build a cpal input stream that writes samples to the shared ring buffer.
+/// Build a cpal input stream that writes samples to `sink`.
 /// Handles mono conversion from multi-channel audio.
 fn build_input_stream<T>(
     device: &cpal::Device,
     config: &cpal::StreamConfig,
-    shared: Arc<Mutex<SharedAudioState>>,
-    channels: usize,
+    sink: SampleSink,
 ) -> Result<cpal::Stream>
 where
     T: SizedSample + Send + 'static,
     f32: FromSample<T>,
 {
+    let channels = config.channels as usize;
+    let errors = sink.clone();
     let stream = device.build_input_stream(
         config,
         move |data: &[T], _: &cpal::InputCallbackInfo| {
-            if let Ok(mut state) = shared.try_lock() {
-                if state.recording {
-                    // Convert to mono f32
-                    for chunk in data.chunks(channels) {
-                        let mono: f32 = chunk
-                            .iter()
-                            .map(|s| f32::from_sample(*s))
-                            .sum::<f32>()
-                            / channels as f32;
-                        state.buffer.write(&[mono]);
-                    }
-                }
-            }
-            // If lock fails (contention), drop the audio frame.
-            // This is acceptable for a voice input client.
+            // Convert to mono f32
+            sink.push(data.chunks(channels).map(|chunk| {
+                chunk.iter().map(|s| f32::from_sample(*s)).sum::<f32>() / channels as f32
+            }));
         },
-        |err| eprintln!("Audio stream error: {}", err),
+        move |err| errors.report_error(err.to_string()),
         None,
     )?;
     Ok(stream)
 }
 
+/// What to do about an error of the audio stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// Restart the stream after this long.
+    RestartIn(Duration),
+    /// A restart is already due, or recovery was given up.
+    Pending,
+    /// [`MAX_RESTARTS`] restarts in a row failed; leave the stream be.
+    GiveUp,
+}
+
+/// Restarts the audio stream after errors, waiting longer before each
+/// restart, up to [`MAX_RESTARTS`] in a row. A burst of errors from one
+/// failure makes one restart.
+#[derive(Debug, Clone, Default)]
+pub struct StreamRecovery {
+    restarts: u32,
+    due: Option<Instant>,
+    last_restart: Option<Instant>,
+    given_up: bool,
+}
+
+impl StreamRecovery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The stream failed at `now`, or a restart of it did.
+    pub fn failed(&mut self, now: Instant) -> Recovery {
+        if self.due.is_some() || self.given_up {
+            return Recovery::Pending;
+        }
+        if self
+            .last_restart
+            .is_some_and(|at| now.duration_since(at) >= STABLE)
+        {
+            self.restarts = 0;
+        }
+        if self.restarts >= MAX_RESTARTS {
+            self.given_up = true;
+            return Recovery::GiveUp;
+        }
+        let delay = RESTART_DELAY * 2u32.pow(self.restarts);
+        self.due = Some(now + delay);
+        Recovery::RestartIn(delay)
+    }
+
+    /// Whether it's time to restart the stream; counts the restart if so.
+    pub fn restart_due(&mut self, now: Instant) -> bool {
+        if self.due.is_none_or(|due| now < due) {
+            return false;
+        }
+        self.due = None;
+        self.restarts += 1;
+        self.last_restart = Some(now);
+        true
+    }
+}
+
 /// Resample audio from one sample rate to another using linear interpolation.
 /// Whisper requires 16kHz mono f32 audio.
 pub fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
@@ -288,10 +949,103 @@ pub fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
         .collect()
 }
 
+/// Read a WAV file as mono f32 samples, returning them with the sample rate.
+/// Supports 16-bit PCM and 32-bit float; channels are averaged.
+pub fn read_wav(path: &std::path::Path) -> Result<(Vec<f32>, u32)> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| anyhow!("Failed to read '{}': {}", path.display(), e))?;
+    parse_wav(&bytes).map_err(|e| anyhow!("Invalid WAV '{}': {}", path.display(), e))
+}
+
+/// Parse WAV bytes; see [`read_wav`].
+pub fn parse_wav(bytes: &[u8]) -> Result<(Vec<f32>, u32)> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(anyhow!("not a RIFF/WAVE file"));
+    }
+    let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+    let u32_at = |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+
+    let mut format = None;
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let len = u32_at(pos + 4) as usize;
+        let body = pos + 8;
+        let end = (body + len).min(bytes.len());
+        if id == b"fmt " && len >= 16 {
+            // (format tag, channels, sample rate, bits per sample)
+            format = Some((u16_at(body), u16_at(body + 2), u32_at(body + 4), u16_at(body + 14)));
+        } else if id == b"data" {
+            let (tag, channels, rate, bits) = format.ok_or_else(|| anyhow!("data before fmt"))?;
+            let channels = channels.max(1) as usize;
+            let data = &bytes[body..end];
+            let frames: Vec<f32> = match (tag, bits) {
+                (1, 16) => data
+                    .chunks_exact(2)
+                    .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+                    .collect(),
+                (3, 32) => data
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect(),
+                _ => return Err(anyhow!("unsupported format {} with {} bits", tag, bits)),
+            };
+            let mono = frames
+                .chunks_exact(channels)
+                .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+                .collect();
+            return Ok((mono, rate));
+        }
+        // Chunks are padded to an even length
+        pos = body + len + (len & 1);
+    }
+    Err(anyhow!("no data chunk"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn wav_bytes(channels: u16, rate: u32, samples: &[i16]) -> Vec<u8> {
+        let data_len = (samples.len() * 2) as u32;
+        let mut out = Vec::new();
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(36 + data_len).to_le_bytes());
+        out.extend_from_slice(b"WAVEfmt ");
+        out.extend_from_slice(&16u32.to_le_bytes());
+        out.extend_from_slice(&1u16.to_le_bytes());
+        out.extend_from_slice(&channels.to_le_bytes());
+        out.extend_from_slice(&rate.to_le_bytes());
+        out.extend_from_slice(&(rate * channels as u32 * 2).to_le_bytes());
+        out.extend_from_slice(&(channels * 2).to_le_bytes());
+        out.extend_from_slice(&16u16.to_le_bytes());
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&data_len.to_le_bytes());
+        for s in samples {
+            out.extend_from_slice(&s.to_le_bytes());
+        }
+        out
+    }
+
+    #[test]
+    fn test_parse_wav_mono() {
+        let (samples, rate) = parse_wav(&wav_bytes(1, 16000, &[0, 16384, -32768])).unwrap();
+        assert_eq!(rate, 16000);
+        assert_eq!(samples, vec![0.0, 0.5, -1.0]);
+    }
+
+    #[test]
+    fn test_parse_wav_downmixes_stereo() {
+        let (samples, rate) = parse_wav(&wav_bytes(2, 44100, &[16384, 0, -16384, -16384])).unwrap();
+        assert_eq!(rate, 44100);
+        assert_eq!(samples, vec![0.25, -0.5]);
+    }
+
+    #[test]
+    fn test_parse_wav_rejects_garbage() {
+        assert!(parse_wav(b"not a wav file at all").is_err());
+    }
+
     #[test]
     fn test_ring_buffer_creation() {
         let buf = RingBuffer::new(1024);
@@ -355,6 +1109,21 @@ mod tests {
         assert_eq!(buf.read_last(4), vec![3.0, 4.0, 5.0, 6.0]);
     }
 
+    #[test]
+    fn test_ring_buffer_read_last_into() {
+        let mut buf = RingBuffer::new(4);
+        let mut out = [0.0; 3];
+        assert_eq!(buf.read_last_into(&mut out), 0);
+        buf.write(&[1.0, 2.0]);
+        // Fewer samples than asked for fill the front
+        assert_eq!(buf.read_last_into(&mut out), 2);
+        assert_eq!(out[..2], [1.0, 2.0]);
+        // Across the wraparound
+        buf.write(&[3.0, 4.0, 5.0]);
+        assert_eq!(buf.read_last_into(&mut out), 3);
+        assert_eq!(out, [3.0, 4.0, 5.0]);
+    }
+
     #[test]
     fn test_ring_buffer_clear() {
         let mut buf = RingBuffer::new(1024);
@@ -365,6 +1134,17 @@ mod tests {
         assert_eq!(buf.read_all(), Vec::<f32>::new());
     }
 
+    #[test]
+    fn test_ring_buffer_usage() {
+        let mut buf = RingBuffer::new(4);
+        buf.write(&[1.0]);
+        assert_eq!(buf.usage(), 0.25);
+        buf.write(&[2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(buf.usage(), 1.5);
+        buf.clear();
+        assert_eq!(buf.usage(), 0.0);
+    }
+
     #[test]
     fn test_push_to_talk_start() {
         // Verify the state transition to Recording
@@ -463,4 +1243,133 @@ mod tests {
         let duration_secs = buf.len() as f64 / 16000.0;
         assert!((duration_secs - 1.0).abs() < 0.001);
     }
+
+    #[test]
+    fn test_signal_health() {
+        let mut health = SignalHealth::default();
+        for _ in 0..100 {
+            health.feed(0.0);
+        }
+        assert_eq!(health.dead_samples(), 100, "digital silence");
+        health.feed(0.001);
+        health.feed(-0.002);
+        assert_eq!(health.dead_samples(), 1);
+        // Stuck at a DC offset
+        for _ in 0..50 {
+            health.feed(0.2);
+        }
+        assert_eq!(health.dead_samples(), 50);
+    }
+
+    #[test]
+    fn test_low_rate_warning() {
+        let warning = low_rate_warning(8000).unwrap();
+        assert!(warning.contains("8000 Hz"));
+        assert!(warning.contains("A2DP"));
+        assert!(low_rate_warning(16000).is_none());
+        assert!(low_rate_warning(48000).is_none());
+    }
+
+    #[test]
+    fn test_match_name() {
+        let names = ["default", "pipewire", "pulse", "USB Audio Device"];
+        assert_eq!(match_name(&names, "PipeWire"), Some(1));
+        assert_eq!(match_name(&names, "usb"), Some(3));
+        // "p" is in both pipewire and pulse
+        assert_eq!(match_name(&names, "p"), None);
+        assert_eq!(match_name(&names, "jack"), None);
+        // An exact match wins over a longer name containing it
+        assert_eq!(match_name(&["pulse", "pulse-mono"], "pulse"), Some(0));
+    }
+
+    #[test]
+    fn test_device_listing() {
+        let listing = device_listing(&["ALSA", "JACK"], "alsa", &["USB Mic".into()]);
+        assert_eq!(
+            listing,
+            "Audio hosts:\n* ALSA\n  JACK\n\nInput devices:\n  USB Mic\n"
+        );
+        let none = device_listing(&["ALSA"], "ALSA", &[]);
+        assert!(none.ends_with("Input devices:\n  (none)\n"));
+    }
+
+    /// A source whose stream fails as soon as it's started.
+    struct Failing {
+        starts: usize,
+        sink: Option<SampleSink>,
+    }
+
+    impl AudioSource for Failing {
+        fn sample_rate(&self) -> u32 {
+            16_000
+        }
+
+        fn start(&mut self, sink: SampleSink) -> Result<()> {
+            self.starts += 1;
+            sink.report_error(format!("device unplugged ({})", self.starts));
+            self.sink = Some(sink);
+            Ok(())
+        }
+
+        fn restart(&mut self) -> Result<()> {
+            let sink = self.sink.take().unwrap();
+            self.start(sink)
+        }
+    }
+
+    #[test]
+    fn test_stream_errors_reach_listener() {
+        let source = Failing {
+            starts: 0,
+            sink: None,
+        };
+        let audio = AudioCapture::with_source(Box::new(source)).unwrap();
+        let heard = Arc::new(Mutex::new(Vec::new()));
+        let errors = Arc::clone(&heard);
+        // The error from before anyone listened is handed over at once
+        audio.on_stream_error(move |error| errors.lock().unwrap().push(error));
+        assert_eq!(*heard.lock().unwrap(), ["device unplugged (1)"]);
+        audio.restart().unwrap();
+        assert_eq!(heard.lock().unwrap().len(), 2);
+        assert_eq!(heard.lock().unwrap()[1], "device unplugged (2)");
+    }
+
+    #[test]
+    fn test_capture_moves_between_threads() {
+        fn shareable<T: Send + Sync>() {}
+        shareable::<AudioCapture>();
+    }
+
+    #[test]
+    fn test_stream_recovery() {
+        let mut recovery = StreamRecovery::new();
+        let t0 = Instant::now();
+        let secs = |s: u64| t0 + Duration::from_secs(s);
+        assert_eq!(recovery.failed(t0), Recovery::RestartIn(RESTART_DELAY));
+        // A burst of errors makes one restart
+        assert_eq!(recovery.failed(t0), Recovery::Pending);
+        assert!(!recovery.restart_due(t0));
+        assert!(recovery.restart_due(secs(1)));
+        assert!(!recovery.restart_due(secs(1)));
+
+        // Each restart in a row waits twice as long
+        let mut now = secs(1);
+        for restarts in 1..MAX_RESTARTS {
+            let delay = RESTART_DELAY * 2u32.pow(restarts);
+            assert_eq!(recovery.failed(now), Recovery::RestartIn(delay));
+            now += delay;
+            assert!(recovery.restart_due(now));
+        }
+        assert_eq!(recovery.failed(now), Recovery::GiveUp);
+        assert_eq!(recovery.failed(now + STABLE), Recovery::Pending);
+
+        // A stream that stayed up for a while starts the count over
+        let mut recovery = StreamRecovery::new();
+        recovery.failed(t0);
+        assert!(recovery.restart_due(secs(1)));
+        assert_eq!(
+            recovery.failed(secs(1) + STABLE),
+            Recovery::RestartIn(RESTART_DELAY)
+        );
+    }
 }
@@ -0,0 +1,221 @@
+// AutoStop Module - Stops a forgotten recording after silence or a hard time limit
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// How long before the hard limit the TUI starts counting down.
+pub const WARNING_WINDOW: Duration = Duration::from_secs(5);
+
+/// `[recording]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AutoStopConfig {
+    /// Hard limit on a single recording. Kept under the 60s ring buffer so
+    /// nothing is overwritten. 0 disables.
+    pub max_secs: f32,
+    /// Stop after this much continuous silence. 0 disables.
+    pub silence_secs: f32,
+    /// RMS level below which audio counts as silence.
+    pub silence_rms: f32,
+    /// A second Space within this many milliseconds of the one that stops a
+    /// recording discards it instead. 0 disables, and Space stops at once.
+    pub double_tap_ms: u64,
+}
+
+impl Default for AutoStopConfig {
+    fn default() -> Self {
+        Self {
+            max_secs: 55.0,
+            silence_secs: 8.0,
+            silence_rms: 0.01,
+            double_tap_ms: 300,
+        }
+    }
+}
+
+/// Why a recording was stopped automatically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    MaxDuration,
+    Silence,
+}
+
+impl std::fmt::Display for StopReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StopReason::MaxDuration => write!(f, "maximum duration reached"),
+            StopReason::Silence => write!(f, "silence"),
+        }
+    }
+}
+
+/// What the recording loop should do this frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoStopStatus {
+    Continue,
+    /// The hard limit is close; `remaining` until it stops.
+    Warning {
+        remaining: Duration,
+    },
+    Stop(StopReason),
+}
+
+/// Tracks one recording against the configured limits.
+pub struct AutoStop {
+    config: AutoStopConfig,
+    /// Recording time at which the current stretch of silence began.
+    silent_since: Option<Duration>,
+}
+
+impl AutoStop {
+    pub fn new(config: AutoStopConfig) -> Self {
+        Self {
+            config,
+            silent_since: Some(Duration::ZERO),
+        }
+    }
+
+    /// Start tracking a new recording. Silence counts from the start, so a
+    /// recording nobody speaks into stops too.
+    pub fn reset(&mut self) {
+        self.silent_since = Some(Duration::ZERO);
+    }
+
+    /// How long a stop waits for a second Space that discards the
+    /// recording, if the gesture is on.
+    pub fn double_tap(&self) -> Option<Duration> {
+        (self.config.double_tap_ms > 0).then(|| Duration::from_millis(self.config.double_tap_ms))
+    }
+
+    /// Feed the time since recording started and the RMS of the latest audio.
+    pub fn check(&mut self, elapsed: Duration, rms: f32) -> AutoStopStatus {
+        if rms >= self.config.silence_rms {
+            self.silent_since = None;
+        } else if self.silent_since.is_none() {
+            self.silent_since = Some(elapsed);
+        }
+
+        let max =
+            (self.config.max_secs > 0.0).then(|| Duration::from_secs_f32(self.config.max_secs));
+        if max.is_some_and(|max| elapsed >= max) {
+            return AutoStopStatus::Stop(StopReason::MaxDuration);
+        }
+        if self.config.silence_secs > 0.0 {
+            let limit = Duration::from_secs_f32(self.config.silence_secs);
+            if self
+                .silent_since
+                .is_some_and(|since| elapsed - since >= limit)
+            {
+                return AutoStopStatus::Stop(StopReason::Silence);
+            }
+        }
+        if let Some(remaining) = max.map(|max| max - elapsed)
+            && remaining <= WARNING_WINDOW
+        {
+            return AutoStopStatus::Warning { remaining };
+        }
+        AutoStopStatus::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(s: f32) -> Duration {
+        Duration::from_secs_f32(s)
+    }
+
+    fn limits(max_secs: f32, silence_secs: f32) -> AutoStop {
+        AutoStop::new(AutoStopConfig {
+            max_secs,
+            silence_secs,
+            silence_rms: 0.01,
+            ..AutoStopConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_speech_keeps_recording() {
+        let mut stop = limits(55.0, 8.0);
+        for t in 0..40 {
+            assert_eq!(stop.check(secs(t as f32), 0.1), AutoStopStatus::Continue);
+        }
+    }
+
+    #[test]
+    fn test_silence_stops() {
+        let mut stop = limits(55.0, 8.0);
+        assert_eq!(stop.check(secs(1.0), 0.1), AutoStopStatus::Continue);
+        assert_eq!(stop.check(secs(2.0), 0.001), AutoStopStatus::Continue);
+        assert_eq!(stop.check(secs(9.0), 0.001), AutoStopStatus::Continue);
+        assert_eq!(
+            stop.check(secs(10.0), 0.001),
+            AutoStopStatus::Stop(StopReason::Silence)
+        );
+    }
+
+    #[test]
+    fn test_silence_from_start_stops() {
+        let mut stop = limits(55.0, 8.0);
+        assert_eq!(
+            stop.check(secs(8.0), 0.0),
+            AutoStopStatus::Stop(StopReason::Silence)
+        );
+    }
+
+    #[test]
+    fn test_speech_resets_silence() {
+        let mut stop = limits(55.0, 8.0);
+        stop.check(secs(1.0), 0.0);
+        stop.check(secs(7.0), 0.2);
+        assert_eq!(stop.check(secs(14.0), 0.0), AutoStopStatus::Continue);
+    }
+
+    #[test]
+    fn test_max_duration_warns_then_stops() {
+        let mut stop = limits(20.0, 0.0);
+        assert_eq!(stop.check(secs(14.0), 0.1), AutoStopStatus::Continue);
+        assert_eq!(
+            stop.check(secs(16.0), 0.1),
+            AutoStopStatus::Warning {
+                remaining: secs(4.0)
+            }
+        );
+        assert_eq!(
+            stop.check(secs(20.0), 0.1),
+            AutoStopStatus::Stop(StopReason::MaxDuration)
+        );
+    }
+
+    #[test]
+    fn test_disabled_limits_never_stop() {
+        let mut stop = limits(0.0, 0.0);
+        assert_eq!(stop.check(secs(500.0), 0.0), AutoStopStatus::Continue);
+    }
+
+    #[test]
+    fn test_double_tap_window() {
+        assert_eq!(
+            limits(55.0, 8.0).double_tap(),
+            Some(Duration::from_millis(300))
+        );
+        let off = AutoStop::new(AutoStopConfig {
+            double_tap_ms: 0,
+            ..AutoStopConfig::default()
+        });
+        assert_eq!(off.double_tap(), None);
+    }
+
+    #[test]
+    fn test_reset_restarts_tracking() {
+        let mut stop = limits(55.0, 8.0);
+        stop.check(secs(5.0), 0.2);
+        stop.reset();
+        assert_eq!(
+            stop.check(secs(8.0), 0.0),
+            AutoStopStatus::Stop(StopReason::Silence)
+        );
+    }
+}
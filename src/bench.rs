@@ -0,0 +1,340 @@
+// Bench Module - Throughput measurements behind `conch bench`
+//
+// Transcription is timed on a reference clip built into the binary, so
+// results are comparable across machines without shipping an audio file.
+// The clip is synthesized speech-like audio (voiced syllables with vowel
+// formants and pauses); it exercises the full encoder/decoder path, but the
+// text Whisper makes of it is meaningless.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::audio::{self, RingBuffer};
+use crate::models::{self, ModelsConfig};
+use crate::stt::Transcriber;
+
+/// Sample rate of the reference clip (what Whisper consumes).
+pub const CLIP_SAMPLE_RATE: u32 = 16_000;
+/// Length of the reference clip, about one spoken command.
+pub const CLIP_SECS: f32 = 8.0;
+
+/// Columns of the model table.
+const MODEL_COLUMNS: [&str; 5] = ["model", "load", "transcribe", "speed", "error"];
+
+/// Arguments of `conch bench [--clip <wav>] [model...]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BenchArgs {
+    /// WAV file transcribed instead of the reference clip.
+    pub clip: Option<PathBuf>,
+    /// Models to time, by name or path; every installed one when empty.
+    pub models: Vec<String>,
+}
+
+impl BenchArgs {
+    pub fn from_args(args: &[String]) -> Self {
+        let clip = args
+            .windows(2)
+            .find_map(|w| (w[0] == "--clip").then(|| PathBuf::from(&w[1])));
+        let models = args
+            .iter()
+            .enumerate()
+            .filter(|(i, a)| !a.starts_with("--") && (*i == 0 || args[i - 1] != "--clip"))
+            .map(|(_, a)| a.clone())
+            .collect();
+        Self { clip, models }
+    }
+}
+
+/// `conch bench [--clip <wav>] [model...]`: time transcription for each
+/// installed model (or the ones named), then the audio plumbing.
+pub fn run(args: &[String], config: &ModelsConfig) -> Result<()> {
+    let args = BenchArgs::from_args(args);
+    let (clip, clip_rate) = match &args.clip {
+        Some(path) => audio::read_wav(path)?,
+        None => (reference_clip(), CLIP_SAMPLE_RATE),
+    };
+    let clip_secs = clip.len() as f32 / clip_rate as f32;
+
+    let dir = config.dir();
+    let model_paths: Vec<PathBuf> = if args.models.is_empty() {
+        models::installed(&dir)?
+            .into_iter()
+            .map(|m| m.path)
+            .collect()
+    } else {
+        args.models
+            .iter()
+            .map(|m| models::resolve(&dir, m))
+            .collect()
+    };
+    if model_paths.is_empty() {
+        eprintln!(
+            "No models in {}; download one with: conch models download base.en",
+            dir.display()
+        );
+    }
+
+    eprintln!(
+        "Transcribing {} ({:.1}s) with {} model(s)...",
+        args.clip
+            .as_ref()
+            .map_or("reference clip".into(), |p| p.display().to_string()),
+        clip_secs,
+        model_paths.len()
+    );
+    let mut rows = Vec::new();
+    for path in &model_paths {
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |f| models::model_name(&f.to_string_lossy()).to_string(),
+        );
+        eprintln!("  {}...", name);
+        rows.push(model_row(name, path, &clip, clip_rate));
+    }
+    if !rows.is_empty() {
+        println!();
+        print!("{}", format_table(&MODEL_COLUMNS, &rows));
+    }
+
+    println!();
+    print!("{}", audio_table());
+    Ok(())
+}
+
+/// A row of the model table: `name`, then how long the model at `path`
+/// takes to load and to transcribe `clip`, or why it couldn't.
+pub fn model_row(name: String, path: &Path, clip: &[f32], clip_rate: u32) -> Vec<String> {
+    let clip_secs = clip.len() as f32 / clip_rate as f32;
+    let (transcriber, load) = time(|| Transcriber::new(&path.to_string_lossy()));
+    let transcriber = match transcriber {
+        Ok(t) => t,
+        Err(e) => return vec![name, "-".into(), "-".into(), "-".into(), format!("{e}")],
+    };
+    let load = format!("{:.2}s", load.as_secs_f32());
+    // First run warms caches and allocations; time the second
+    let _ = transcriber.transcribe(clip, clip_rate);
+    let (result, elapsed) = time(|| transcriber.transcribe(clip, clip_rate));
+    match result {
+        Ok(_) => vec![
+            name,
+            load,
+            format!("{:.2}s", elapsed.as_secs_f32()),
+            realtime_factor(clip_secs, elapsed),
+            String::new(),
+        ],
+        Err(e) => vec![name, load, "-".into(), "-".into(), format!("{e}")],
+    }
+}
+
+/// The audio path's throughputs, on ten seconds of 48 kHz audio, about what
+/// the mic delivers per command.
+pub fn audio_table() -> String {
+    let samples = 48_000 * 10;
+    let rows = vec![
+        vec![
+            "ring buffer write+read".to_string(),
+            format!("{:.0}M samples/s", ring_buffer_throughput(samples) / 1e6),
+        ],
+        vec![
+            "resample 48k -> 16k".to_string(),
+            format!("{:.0}M samples/s", resample_throughput(samples) / 1e6),
+        ],
+    ];
+    format_table(&["audio path", "throughput"], &rows)
+}
+
+/// Formant pairs (Hz) for a handful of vowels.
+const VOWELS: [(f32, f32); 5] = [
+    (730.0, 1090.0),
+    (270.0, 2290.0),
+    (530.0, 1840.0),
+    (570.0, 840.0),
+    (300.0, 870.0),
+];
+
+/// Build the reference clip at [`CLIP_SAMPLE_RATE`]. Deterministic.
+pub fn reference_clip() -> Vec<f32> {
+    let rate = CLIP_SAMPLE_RATE as f32;
+    let n = (CLIP_SECS * rate) as usize;
+    let syllable = (0.22 * rate) as usize;
+    let pause_every = 5;
+    let mut out = Vec::with_capacity(n);
+    let mut phase = 0.0f32;
+    for i in 0..n {
+        let index = i / syllable;
+        let t_in = (i % syllable) as f32 / syllable as f32;
+        // A short pause after every few syllables, like word boundaries
+        if index % (pause_every + 1) == pause_every {
+            out.push(0.0);
+            continue;
+        }
+        let t = i as f32 / rate;
+        let pitch = 120.0 + 20.0 * (t * 1.3).sin();
+        phase = (phase + pitch / rate).fract();
+        let (f1, f2) = VOWELS[index % VOWELS.len()];
+        let envelope = (std::f32::consts::PI * t_in).sin();
+        // Harmonics of the pitch, weighted by closeness to the formants
+        let mut sample = 0.0;
+        for h in 1..=30 {
+            let freq = pitch * h as f32;
+            let weight = formant_gain(freq, f1) + 0.5 * formant_gain(freq, f2);
+            sample += weight * (2.0 * std::f32::consts::PI * phase * h as f32).sin() / h as f32;
+        }
+        out.push(0.3 * envelope * sample);
+    }
+    out
+}
+
+/// Resonance-shaped gain for a harmonic at `freq` near formant `center`.
+fn formant_gain(freq: f32, center: f32) -> f32 {
+    let bandwidth = 100.0;
+    1.0 / (1.0 + ((freq - center) / bandwidth).powi(2))
+}
+
+/// Time `f`, returning its result and the elapsed wall time.
+pub fn time<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+/// Samples per second pushed through the ring buffer in 512-sample writes
+/// (a typical cpal callback size), including the final read for Whisper.
+pub fn ring_buffer_throughput(total_samples: usize) -> f64 {
+    let chunk = vec![0.1f32; 512];
+    let mut buffer = RingBuffer::new(60 * 48_000);
+    let ((), elapsed) = time(|| {
+        let mut written = 0;
+        while written < total_samples {
+            buffer.write(&chunk);
+            written += chunk.len();
+        }
+        std::hint::black_box(buffer.read_all());
+    });
+    total_samples as f64 / elapsed.as_secs_f64().max(1e-9)
+}
+
+/// Input samples per second resampled from 48 kHz to 16 kHz.
+pub fn resample_throughput(total_samples: usize) -> f64 {
+    let input: Vec<f32> = (0..total_samples)
+        .map(|i| (i as f32 * 0.01).sin())
+        .collect();
+    let (output, elapsed) = time(|| audio::resample(&input, 48_000, 16_000));
+    std::hint::black_box(output);
+    total_samples as f64 / elapsed.as_secs_f64().max(1e-9)
+}
+
+/// Render rows as a left-aligned text table with a header underline.
+pub fn format_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+    let line = |cells: &[&str]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(c, w)| format!("{:<w$}", c, w = *w))
+            .collect();
+        padded.join("  ").trim_end().to_string()
+    };
+    let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+    let mut out = String::new();
+    for cells in std::iter::once(header.to_vec())
+        .chain(std::iter::once(rule.iter().map(String::as_str).collect()))
+        .chain(
+            rows.iter()
+                .map(|row| row.iter().map(String::as_str).collect()),
+        )
+    {
+        out.push_str(&line(&cells));
+        out.push('\n');
+    }
+    out
+}
+
+/// Speed relative to realtime, e.g. "12.3x", for `audio_secs` processed in `elapsed`.
+pub fn realtime_factor(audio_secs: f32, elapsed: Duration) -> String {
+    format!(
+        "{:.1}x",
+        audio_secs as f64 / elapsed.as_secs_f64().max(1e-9)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_clip_shape() {
+        let clip = reference_clip();
+        assert_eq!(clip.len(), (CLIP_SECS * CLIP_SAMPLE_RATE as f32) as usize);
+        assert!(clip.iter().all(|s| s.is_finite() && s.abs() <= 1.0));
+        // Voiced audio with pauses, not silence
+        let rms = (clip.iter().map(|s| s * s).sum::<f32>() / clip.len() as f32).sqrt();
+        assert!(rms > 0.01, "rms {rms}");
+        assert!(clip.iter().filter(|&&s| s == 0.0).count() > CLIP_SAMPLE_RATE as usize / 2);
+    }
+
+    #[test]
+    fn test_reference_clip_is_deterministic() {
+        assert_eq!(reference_clip(), reference_clip());
+    }
+
+    #[test]
+    fn test_throughputs_positive() {
+        assert!(ring_buffer_throughput(48_000) > 0.0);
+        assert!(resample_throughput(48_000) > 0.0);
+    }
+
+    #[test]
+    fn test_format_table() {
+        let table = format_table(
+            &["model", "speed"],
+            &[
+                vec!["base.en".into(), "9.5x".into()],
+                vec!["tiny".into(), "31.0x".into()],
+            ],
+        );
+        assert_eq!(
+            table,
+            "model    speed\n-------  -----\nbase.en  9.5x\ntiny     31.0x\n"
+        );
+    }
+
+    #[test]
+    fn test_bench_args() {
+        let args = |words: &[&str]| {
+            let words: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+            BenchArgs::from_args(&words)
+        };
+        assert_eq!(args(&[]), BenchArgs::default());
+        let named = args(&["base.en", "--clip", "take.wav", "tiny"]);
+        assert_eq!(named.clip, Some(PathBuf::from("take.wav")));
+        assert_eq!(named.models, vec!["base.en", "tiny"]);
+    }
+
+    #[test]
+    fn test_missing_model_row() {
+        let path = Path::new("/nonexistent/ggml-base.en.bin");
+        let row = model_row("base.en".into(), path, &reference_clip(), CLIP_SAMPLE_RATE);
+        assert_eq!(row[..4], ["base.en", "-", "-", "-"]);
+        assert!(!row[4].is_empty());
+    }
+
+    #[test]
+    fn test_audio_table() {
+        let table = audio_table();
+        assert!(table.starts_with("audio path "));
+        assert!(table.contains("resample 48k -> 16k"));
+    }
+
+    #[test]
+    fn test_realtime_factor() {
+        assert_eq!(realtime_factor(8.0, Duration::from_secs(2)), "4.0x");
+    }
+}
@@ -0,0 +1,160 @@
+// Bus Module - Typed publish/subscribe event bus with topics
+//
+// Producers publish events without knowing who listens; each subscriber gets
+// its own channel carrying only the topics it asked for. Events are cloned
+// per subscriber, so they should be cheap to clone.
+
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::mpsc;
+
+/// Broad category of an event, used to filter subscriptions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Topic {
+    /// Microphone and recording lifecycle.
+    Audio,
+    /// Transcription results.
+    Stt,
+    /// OpenCode connection, server events, and prompt sends.
+    Transport,
+    /// Focus stack changes.
+    Focus,
+    /// User interface actions.
+    Ui,
+}
+
+impl Topic {
+    /// Every topic, for subscribers that want everything.
+    pub const ALL: [Topic; 5] = [
+        Topic::Audio,
+        Topic::Stt,
+        Topic::Transport,
+        Topic::Focus,
+        Topic::Ui,
+    ];
+}
+
+/// An event that belongs to a topic.
+pub trait Topical {
+    fn topic(&self) -> Topic;
+}
+
+struct Subscriber<E> {
+    topics: Vec<Topic>,
+    tx: mpsc::UnboundedSender<E>,
+}
+
+/// Cloneable handle to a shared event bus.
+pub struct EventBus<E> {
+    subscribers: Arc<Mutex<Vec<Subscriber<E>>>>,
+}
+
+impl<E> Clone for EventBus<E> {
+    fn clone(&self) -> Self {
+        Self {
+            subscribers: Arc::clone(&self.subscribers),
+        }
+    }
+}
+
+impl<E: Topical + Clone> Default for EventBus<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: Topical + Clone> EventBus<E> {
+    pub fn new() -> Self {
+        Self {
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Receive every event published on `topics` from now on. Dropping the
+    /// receiver unsubscribes.
+    pub fn subscribe(&self, topics: &[Topic]) -> mpsc::UnboundedReceiver<E> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.subscribers.lock().unwrap().push(Subscriber {
+            topics: topics.to_vec(),
+            tx,
+        });
+        rx
+    }
+
+    /// Deliver `event` to every subscriber of its topic, returning how many
+    /// received it. Subscribers whose receiver was dropped are removed.
+    pub fn publish(&self, event: E) -> usize {
+        let topic = event.topic();
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|s| !s.tx.is_closed());
+        let mut delivered = 0;
+        for subscriber in subscribers.iter().filter(|s| s.topics.contains(&topic)) {
+            if subscriber.tx.send(event.clone()).is_ok() {
+                delivered += 1;
+            }
+        }
+        delivered
+    }
+
+    /// Number of live subscribers.
+    pub fn subscriber_count(&self) -> usize {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|s| !s.tx.is_closed());
+        subscribers.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Event {
+        Heard(String),
+        Focused(String),
+    }
+
+    impl Topical for Event {
+        fn topic(&self) -> Topic {
+            match self {
+                Event::Heard(_) => Topic::Stt,
+                Event::Focused(_) => Topic::Focus,
+            }
+        }
+    }
+
+    #[test]
+    fn test_subscribers_get_their_topics() {
+        let bus = EventBus::new();
+        let mut all = bus.subscribe(&Topic::ALL);
+        let mut stt = bus.subscribe(&[Topic::Stt]);
+
+        assert_eq!(bus.publish(Event::Heard("hello".into())), 2);
+        assert_eq!(bus.publish(Event::Focused("main.rs".into())), 1);
+
+        assert_eq!(all.try_recv().unwrap(), Event::Heard("hello".into()));
+        assert_eq!(all.try_recv().unwrap(), Event::Focused("main.rs".into()));
+        assert_eq!(stt.try_recv().unwrap(), Event::Heard("hello".into()));
+        assert!(stt.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_dropped_subscriber_is_removed() {
+        let bus = EventBus::new();
+        let rx = bus.subscribe(&[Topic::Stt]);
+        let _keep = bus.subscribe(&[Topic::Focus]);
+        assert_eq!(bus.subscriber_count(), 2);
+        drop(rx);
+        assert_eq!(bus.publish(Event::Heard("x".into())), 0);
+        assert_eq!(bus.subscriber_count(), 1);
+    }
+
+    #[test]
+    fn test_clones_share_subscribers() {
+        let bus = EventBus::new();
+        let publisher = bus.clone();
+        let mut rx = bus.subscribe(&[Topic::Focus]);
+        publisher.publish(Event::Focused("a".into()));
+        assert_eq!(rx.try_recv().unwrap(), Event::Focused("a".into()));
+    }
+}
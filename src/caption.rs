@@ -0,0 +1,173 @@
+// Caption Module - Live captions of what's being said
+//
+// While recording, the last few seconds of audio are transcribed again and
+// again on a thread of their own, and the partial text is shown in a
+// high-contrast strip at the top of the screen, so people watching a shared
+// screen can follow along. Partial results never reach the pending prompt:
+// the recording is still transcribed as a whole once it stops, and that
+// final text replaces the caption for a few seconds.
+
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+/// `[caption]` section of the config file. Off unless explicitly enabled.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CaptionConfig {
+    /// Show the caption strip.
+    pub enabled: bool,
+    /// Milliseconds between partial transcriptions.
+    pub interval_ms: u64,
+    /// Seconds of the latest audio each partial transcription covers.
+    pub window_secs: f32,
+    /// Seconds the final transcript stays up after recording stops.
+    pub hold_secs: f32,
+}
+
+impl Default for CaptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_ms: 1000,
+            window_secs: 8.0,
+            hold_secs: 5.0,
+        }
+    }
+}
+
+/// What the caption strip shows.
+#[derive(Debug, Clone, Default)]
+pub struct Captions {
+    /// Counts recordings, so a partial from an earlier one is dropped.
+    recording: u64,
+    text: String,
+    /// When the text became final; `None` while it's partial.
+    finished: Option<Instant>,
+    /// When a partial transcription was last asked for.
+    requested: Option<Instant>,
+}
+
+impl Captions {
+    /// A recording started. Returns its number, for [`partial`](Self::partial).
+    pub fn start(&mut self) -> u64 {
+        self.recording += 1;
+        self.text.clear();
+        self.finished = None;
+        self.requested = None;
+        self.recording
+    }
+
+    /// Whether to transcribe the latest audio again, every `interval`.
+    /// Returns the recording's number when it is time.
+    pub fn due(&mut self, now: Instant, interval: Duration) -> Option<u64> {
+        if self.finished.is_some() || self.requested.is_some_and(|t| now - t < interval) {
+            return None;
+        }
+        self.requested = Some(now);
+        Some(self.recording)
+    }
+
+    /// Partial text of recording number `recording`.
+    pub fn partial(&mut self, recording: u64, text: &str) {
+        if recording == self.recording && self.finished.is_none() {
+            self.text = text.trim().to_string();
+        }
+    }
+
+    /// Recording stopped: the last partial stays up for a while, until the
+    /// whole recording's transcript replaces it.
+    pub fn stop(&mut self, now: Instant) {
+        self.finished.get_or_insert(now);
+    }
+
+    /// Whether a recording is being captioned.
+    pub fn is_live(&self) -> bool {
+        self.recording > 0 && self.finished.is_none()
+    }
+
+    /// The stopped recording's transcript arrived. Ignored once another
+    /// recording has started.
+    pub fn finish(&mut self, text: &str, now: Instant) {
+        if self.finished.is_some() {
+            self.text = text.trim().to_string();
+            self.finished = Some(now);
+        }
+    }
+
+    /// The caption to show, if any: the partial text, or the final text
+    /// for `hold` after it arrived.
+    pub fn visible(&self, now: Instant, hold: Duration) -> Option<&str> {
+        if self.text.is_empty() || self.finished.is_some_and(|t| now - t >= hold) {
+            return None;
+        }
+        Some(&self.text)
+    }
+}
+
+/// The end of `text` that fits in `width` characters, with an ellipsis in
+/// front when it's cut: captions follow the speaker.
+pub fn fit_tail(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    if len <= width {
+        return text.to_string();
+    }
+    let keep = width.saturating_sub(1);
+    let tail: String = text.chars().skip(len - keep).collect();
+    format!("\u{2026}{tail}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partials_then_final() {
+        let now = Instant::now();
+        let hold = Duration::from_secs(5);
+        let mut captions = Captions::default();
+        let first = captions.start();
+        captions.partial(first, " open the ");
+        assert_eq!(captions.visible(now, hold), Some("open the"));
+
+        let second = captions.start();
+        assert_eq!(captions.visible(now, hold), None);
+        captions.partial(first, "stale");
+        assert_eq!(captions.visible(now, hold), None, "earlier recording");
+        captions.partial(second, "run the");
+        assert!(captions.is_live());
+        captions.stop(now);
+        assert!(!captions.is_live());
+        captions.partial(second, "run the te");
+        assert_eq!(captions.visible(now, hold), Some("run the"));
+        captions.finish("Run the tests.", now);
+        assert_eq!(captions.visible(now, hold), Some("Run the tests."));
+        assert_eq!(captions.visible(now + hold, hold), None);
+
+        captions.start();
+        captions.finish("Late transcript.", now);
+        assert_eq!(captions.visible(now, hold), None, "new recording");
+    }
+
+    #[test]
+    fn test_due_every_interval() {
+        let now = Instant::now();
+        let interval = Duration::from_secs(1);
+        let mut captions = Captions::default();
+        let recording = captions.start();
+        assert_eq!(captions.due(now, interval), Some(recording));
+        assert_eq!(
+            captions.due(now + Duration::from_millis(500), interval),
+            None
+        );
+        assert_eq!(captions.due(now + interval, interval), Some(recording));
+        captions.stop(now);
+        assert_eq!(captions.due(now + interval * 3, interval), None);
+    }
+
+    #[test]
+    fn test_fit_tail() {
+        assert_eq!(fit_tail("short", 10), "short");
+        assert_eq!(fit_tail("open the main file", 10), "\u{2026}main file");
+    }
+}
@@ -0,0 +1,186 @@
+// Casing Module - Spoken casing commands turned into identifiers
+//
+// Whisper writes "fetch user profile" however the phrase is meant, so an
+// identifier is dictated with its casing in front: "camel case fetch user
+// profile" becomes `fetchUserProfile` and "screaming snake max retry count"
+// `MAX_RETRY_COUNT`. The phrase runs to the next punctuation mark, the next
+// casing command or the end of the transcript. Whisper may write the command
+// in one word or with a hyphen ("camelcase", "camel-case"), and capitalizes
+// the words of the phrase as it likes; neither matters.
+
+/// How the words of a phrase are joined into an identifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    /// `fetchUserProfile`
+    Camel,
+    /// `FetchUserProfile`
+    Pascal,
+    /// `fetch_user_profile`
+    Snake,
+    /// `MAX_RETRY_COUNT`
+    ScreamingSnake,
+    /// `fetch-user-profile`
+    Kebab,
+}
+
+/// The spoken commands, each without spaces or hyphens.
+const COMMANDS: &[(&str, Case)] = &[
+    ("camelcase", Case::Camel),
+    ("pascalcase", Case::Pascal),
+    ("snakecase", Case::Snake),
+    ("screamingsnakecase", Case::ScreamingSnake),
+    ("screamingsnake", Case::ScreamingSnake),
+    ("constantcase", Case::ScreamingSnake),
+    ("kebabcase", Case::Kebab),
+];
+/// Most words a command is spoken in.
+const COMMAND_WORDS: usize = 3;
+
+impl Case {
+    /// `words`, lowercase, joined in this case.
+    pub fn join(self, words: &[String]) -> String {
+        let capitalized = || words.iter().map(|w| capitalize(w)).collect::<String>();
+        match self {
+            Case::Camel => match words.split_first() {
+                Some((first, rest)) => {
+                    let rest: String = rest.iter().map(|w| capitalize(w)).collect();
+                    format!("{first}{rest}")
+                }
+                None => String::new(),
+            },
+            Case::Pascal => capitalized(),
+            Case::Snake => words.join("_"),
+            Case::ScreamingSnake => words.join("_").to_uppercase(),
+            Case::Kebab => words.join("-"),
+        }
+    }
+}
+
+/// `text` with every casing command and the phrase after it replaced by
+/// the identifier. A command with no words after it is left as spoken.
+pub fn apply(text: &str) -> String {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let mut out: Vec<String> = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        let Some((case, len)) = command_at(&tokens[i..]) else {
+            out.push(tokens[i].to_string());
+            i += 1;
+            continue;
+        };
+        let (words, taken, end) = phrase(&tokens[i + len..]);
+        if words.is_empty() {
+            out.extend(tokens[i..i + len].iter().map(|t| t.to_string()));
+            i += len;
+            continue;
+        }
+        out.push(format!("{}{end}", case.join(&words)));
+        i += len + taken;
+    }
+    out.join(" ")
+}
+
+/// The casing command `tokens` start with, and how many tokens it takes.
+fn command_at(tokens: &[&str]) -> Option<(Case, usize)> {
+    let mut spoken = String::new();
+    let mut found = None;
+    for (n, token) in tokens.iter().take(COMMAND_WORDS).enumerate() {
+        // Punctuation inside a command ends it: "snake, case" isn't one
+        if token.contains(|c: char| !c.is_alphabetic() && c != '-') {
+            break;
+        }
+        spoken.extend(
+            token
+                .chars()
+                .filter(|&c| c != '-')
+                .flat_map(char::to_lowercase),
+        );
+        if let Some(&(_, case)) = COMMANDS.iter().find(|(name, _)| *name == spoken) {
+            found = Some((case, n + 1));
+        }
+    }
+    found
+}
+
+/// The words of the phrase `tokens` start with, lowercase, how many tokens
+/// they took, and the punctuation that ended the phrase.
+fn phrase(tokens: &[&str]) -> (Vec<String>, usize, String) {
+    let mut words = Vec::new();
+    for (n, token) in tokens.iter().enumerate() {
+        if command_at(&tokens[n..]).is_some() {
+            return (words, n, String::new());
+        }
+        let word = token.trim_end_matches(|c: char| !c.is_alphanumeric());
+        let end = &token[word.len()..];
+        words.extend(
+            word.split(|c: char| !c.is_alphanumeric())
+                .filter(|w| !w.is_empty())
+                .map(str::to_lowercase),
+        );
+        if !end.is_empty() {
+            return (words, n + 1, end.to_string());
+        }
+    }
+    (words, tokens.len(), String::new())
+}
+
+/// `word` with its first letter uppercase.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What Whisper writes, and what the prompt should say.
+    const CORPUS: &[(&str, &str)] = &[
+        ("camel case fetch user profile", "fetchUserProfile"),
+        ("Camel case fetch user profile.", "fetchUserProfile."),
+        ("camelcase fetch user profile", "fetchUserProfile"),
+        ("Camel-case Fetch User Profile", "fetchUserProfile"),
+        ("pascal case http client", "HttpClient"),
+        ("snake case parse args", "parse_args"),
+        ("screaming snake max retry count", "MAX_RETRY_COUNT"),
+        ("screaming snake case max retry count", "MAX_RETRY_COUNT"),
+        ("constant case default port", "DEFAULT_PORT"),
+        ("kebab case dry run", "dry-run"),
+        ("snake case retry 3 times", "retry_3_times"),
+        ("snake case user-id", "user_id"),
+        (
+            "rename camel case fetch user, to camel case load user profile",
+            "rename fetchUser, to loadUserProfile",
+        ),
+        (
+            "add a snake case max depth pascal case tree walker",
+            "add a max_depth TreeWalker",
+        ),
+        ("what is camel case?", "what is camel case?"),
+        ("explain camel case", "explain camel case"),
+        ("the snake, case closed", "the snake, case closed"),
+        ("open main.rs", "open main.rs"),
+        ("", ""),
+    ];
+
+    #[test]
+    fn test_corpus() {
+        for &(heard, expected) in CORPUS {
+            assert_eq!(apply(heard), expected, "heard {heard:?}");
+        }
+    }
+
+    #[test]
+    fn test_join() {
+        let words: Vec<String> = ["max", "retry", "count"].map(String::from).to_vec();
+        assert_eq!(Case::Camel.join(&words), "maxRetryCount");
+        assert_eq!(Case::Pascal.join(&words), "MaxRetryCount");
+        assert_eq!(Case::Snake.join(&words), "max_retry_count");
+        assert_eq!(Case::ScreamingSnake.join(&words), "MAX_RETRY_COUNT");
+        assert_eq!(Case::Kebab.join(&words), "max-retry-count");
+        assert_eq!(Case::Camel.join(&[]), "");
+    }
+}
@@ -0,0 +1,223 @@
+// Clipboard Module - Copies text to the system clipboard via the platform's copy tool
+//
+// Like speech, this is delegated to whatever is installed rather than linking
+// a windowing library: `pbcopy` on macOS, `wl-copy` under Wayland, and
+// `xclip`/`xsel` under X11. Over SSH those reach the remote machine's
+// clipboard, if any, so there the text goes to the terminal instead, in an
+// OSC 52 escape sequence that the local terminal puts on its clipboard.
+// Inside tmux the sequence is wrapped so tmux passes it on.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+
+/// Longest text sent in an OSC 52 sequence, before encoding: just under
+/// 100,000 bytes in base64, which is where xterm stops. Terminals drop
+/// longer ones, some silently.
+pub const MAX_OSC52_BYTES: usize = 74_994;
+
+/// How text reaches the clipboard.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// The terminal over SSH, otherwise the platform's copy tool, falling
+    /// back to the terminal when no tool works.
+    #[default]
+    Auto,
+    /// The platform's copy tool.
+    Command,
+    /// The terminal, through an OSC 52 escape sequence.
+    Osc52,
+}
+
+/// `[clipboard]` section of the config file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ClipboardConfig {
+    pub backend: Backend,
+}
+
+/// Whether conch runs in an SSH session, judging by what sshd sets.
+pub fn is_remote() -> bool {
+    ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"]
+        .iter()
+        .any(|var| std::env::var_os(var).is_some())
+}
+
+/// The backends `backend` tries, in order, in a session that is `remote`
+/// or not.
+pub fn backends(backend: Backend, remote: bool) -> &'static [Backend] {
+    match backend {
+        Backend::Auto if remote => &[Backend::Osc52],
+        Backend::Auto => &[Backend::Command, Backend::Osc52],
+        Backend::Command => &[Backend::Command],
+        Backend::Osc52 => &[Backend::Osc52],
+    }
+}
+
+/// Copy commands to try, in order, for the current platform and session.
+pub fn copy_commands() -> Vec<&'static str> {
+    if cfg!(target_os = "macos") {
+        return vec!["pbcopy"];
+    }
+    let mut commands = Vec::new();
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        commands.push("wl-copy");
+    }
+    commands.extend(["xclip -selection clipboard", "xsel --clipboard --input"]);
+    commands
+}
+
+/// Copy `text` with the first command in `commands` that runs successfully.
+pub fn copy_with(commands: &[&str], text: &str) -> Result<()> {
+    let mut errors = Vec::new();
+    for command in commands {
+        match pipe_to(command, text) {
+            Ok(()) => return Ok(()),
+            Err(e) => errors.push(format!("{}: {}", command, e)),
+        }
+    }
+    Err(anyhow!("No clipboard tool worked ({})", errors.join("; ")))
+}
+
+/// Copy `text` to the system clipboard with `backend`, returning the
+/// backend that did it.
+pub fn copy(text: &str, backend: Backend) -> Result<Backend> {
+    let mut errors = Vec::new();
+    for &backend in backends(backend, is_remote()) {
+        let copied = match backend {
+            Backend::Osc52 => copy_osc52(&mut std::io::stdout(), text, in_tmux()),
+            _ => copy_with(&copy_commands(), text),
+        };
+        match copied {
+            Ok(()) => return Ok(backend),
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+    Err(anyhow!("{}", errors.join("; ")))
+}
+
+/// Whether the terminal is a tmux pane.
+fn in_tmux() -> bool {
+    std::env::var_os("TMUX").is_some()
+}
+
+/// Write `text` to `out` as an OSC 52 sequence setting the clipboard,
+/// wrapped for tmux's passthrough when `tmux` is set.
+pub fn copy_osc52(out: &mut impl Write, text: &str, tmux: bool) -> Result<()> {
+    if text.len() > MAX_OSC52_BYTES {
+        return Err(anyhow!(
+            "Too long to copy through the terminal ({} KB, at most {} KB)",
+            text.len() / 1024,
+            MAX_OSC52_BYTES / 1024
+        ));
+    }
+    let sequence = format!("\x1b]52;c;{}\x07", base64(text.as_bytes()));
+    let sequence = if tmux {
+        // Escapes inside the passthrough are doubled
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    };
+    out.write_all(sequence.as_bytes())?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Standard base64 with padding, as OSC 52 expects.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Run `command` via `sh -c` with `text` on stdin, e.g. a copy command.
+pub(crate) fn pipe_to(command: &str, text: &str) -> Result<()> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!("exited with {}", status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_uses_first_working_command() {
+        let out = std::env::temp_dir().join(format!("conch_clip_{}.txt", std::process::id()));
+        let good = format!("cat > '{}'", out.display());
+        copy_with(&["exit 1", &good], "commit message").unwrap();
+        let written = std::fs::read_to_string(&out).unwrap();
+        let _ = std::fs::remove_file(&out);
+        assert_eq!(written, "commit message");
+    }
+
+    #[test]
+    fn test_copy_fails_when_nothing_works() {
+        let err = copy_with(&["exit 1", "exit 2"], "text").unwrap_err();
+        assert!(err.to_string().contains("No clipboard tool worked"));
+    }
+
+    #[test]
+    fn test_copy_commands_not_empty() {
+        assert!(!copy_commands().is_empty());
+    }
+
+    #[test]
+    fn test_backends() {
+        assert_eq!(backends(Backend::Auto, true), &[Backend::Osc52]);
+        assert_eq!(
+            backends(Backend::Auto, false),
+            &[Backend::Command, Backend::Osc52]
+        );
+        assert_eq!(backends(Backend::Command, true), &[Backend::Command]);
+        assert_eq!(backends(Backend::Osc52, false), &[Backend::Osc52]);
+    }
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+
+        let mut out = Vec::new();
+        copy_osc52(&mut out, "fix the build", false).unwrap();
+        assert_eq!(out, b"\x1b]52;c;Zml4IHRoZSBidWlsZA==\x07");
+
+        let mut out = Vec::new();
+        copy_osc52(&mut out, "fo", true).unwrap();
+        assert_eq!(out, b"\x1bPtmux;\x1b\x1b]52;c;Zm8=\x07\x1b\\");
+
+        let long = "x".repeat(MAX_OSC52_BYTES + 1);
+        assert!(copy_osc52(&mut Vec::new(), &long, false).is_err());
+    }
+}
@@ -0,0 +1,481 @@
+// Config Module - Loads user settings from a TOML file (conch.toml)
+
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+
+use crate::agent::AgentConfig;
+use crate::audio::AudioConfig;
+use crate::autostop::AutoStopConfig;
+use crate::caption::CaptionConfig;
+use crate::clipboard::ClipboardConfig;
+use crate::control::ControlConfig;
+use crate::conversation::ContextConfig;
+use crate::cues::CueConfig;
+use crate::dictation::DictationConfig;
+use crate::git::GitConfig;
+use crate::glyphs::DisplayConfig;
+use crate::hallucination::HallucinationConfig;
+use crate::handsfree::HandsFreeConfig;
+use crate::history::HistoryConfig;
+use crate::hooks::HookConfig;
+use crate::keymap::KeysConfig;
+use crate::layout::LayoutConfig;
+use crate::logs::LogConfig;
+use crate::models::ModelsConfig;
+use crate::numbers::NumbersConfig;
+use crate::preview::PreviewConfig;
+use crate::route::RouteConfig;
+use crate::send::SendConfig;
+use crate::snippets::Snippets;
+use crate::stt::SttConfig;
+use crate::theme::ThemeConfig;
+use crate::tools::ToolRule;
+use crate::transport::ConnectionConfig;
+use crate::trigger::TriggerConfig;
+use crate::tts::TtsConfig;
+use crate::viz::WaveformConfig;
+use crate::vocabulary::VocabularyConfig;
+use crate::wake::WakeConfig;
+
+/// User configuration. Every field has a default, so an empty or missing
+/// file yields a working setup.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Shell commands fired on pipeline events (`[[hooks]]` tables).
+    pub hooks: Vec<HookConfig>,
+    /// Wake word listening (`[wake]`), disabled by default.
+    pub wake: WakeConfig,
+    /// How transcripts are sent to OpenCode (`[send]`).
+    pub send: SendConfig,
+    /// Transcript history store (`[history]`).
+    pub history: HistoryConfig,
+    /// Spoken readback of replies (`[tts]`), disabled by default.
+    pub tts: TtsConfig,
+    /// Audible recording cues (`[cues]`).
+    pub cues: CueConfig,
+    /// Recent conversation turns in the context block (`[context]`).
+    pub context: ContextConfig,
+    /// Speech-to-text settings (`[stt]`).
+    pub stt: SttConfig,
+    /// Audio host and input device (`[audio]`).
+    pub audio: AudioConfig,
+    /// Waveform sensitivity (`[waveform]`).
+    pub waveform: WaveformConfig,
+    /// Automatic recording stop on silence or time limit (`[recording]`).
+    pub recording: AutoStopConfig,
+    /// Model directory and default model (`[models]`).
+    pub models: ModelsConfig,
+    /// Named prompt templates (`[snippets]`).
+    pub snippets: Snippets,
+    /// Display and focus rules for MCP and custom tools (`[[tools]]` tables).
+    pub tools: Vec<ToolRule>,
+    /// Debug log rotation and retention (`[log]`).
+    pub log: LogConfig,
+    /// TUI colors (`[theme]`).
+    pub theme: ThemeConfig,
+    /// Characters the TUI draws with (`[display]`).
+    pub display: DisplayConfig,
+    /// Pane arrangement and sizes (`[layout]`).
+    pub layout: LayoutConfig,
+    /// Key preset (`[keys]`).
+    pub keys: KeysConfig,
+    /// File preview pane (`[preview]`).
+    pub preview: PreviewConfig,
+    /// Git status in the title bar (`[git]`).
+    pub git: GitConfig,
+    /// Re-arming the mic after each reply (`[handsfree]`), disabled by default.
+    pub handsfree: HandsFreeConfig,
+    /// Push-to-talk from a MIDI controller or HID pedal (`[trigger]`).
+    pub trigger: TriggerConfig,
+    /// Long-form dictation segmenting (`[dictation]`).
+    pub dictation: DictationConfig,
+    /// Live caption strip (`[caption]`), disabled by default.
+    pub caption: CaptionConfig,
+    /// Control socket for scripts and key bindings (`[control]`).
+    pub control: ControlConfig,
+    /// Project names and symbols Whisper is primed with (`[vocabulary]`).
+    pub vocabulary: VocabularyConfig,
+    /// How copied transcripts reach the clipboard (`[clipboard]`).
+    pub clipboard: ClipboardConfig,
+    /// The event stream from OpenCode (`[connection]`).
+    pub connection: ConnectionConfig,
+    /// The OpenCode agent prompts go to (`[agent]`).
+    pub agent: AgentConfig,
+    /// Where transcripts go besides OpenCode (`[route]`).
+    pub route: RouteConfig,
+    /// Whisper artifacts caught before confirmation (`[hallucinations]`).
+    pub hallucinations: HallucinationConfig,
+    /// Spoken numbers written as digits (`[numbers]`).
+    pub numbers: NumbersConfig,
+}
+
+impl Config {
+    /// Load config from `path`. A missing file returns the defaults;
+    /// a file that exists but fails to parse is an error.
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::from_toml_str(&text)
+                .map_err(|e| anyhow!("Invalid config '{}': {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(anyhow!("Failed to read config '{}': {}", path.display(), e)),
+        }
+    }
+
+    /// Parse config from TOML text.
+    pub fn from_toml_str(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glyphs::Charset;
+    use crate::hallucination::Action;
+    use crate::hooks::HookEvent;
+    use crate::keymap::Keymap;
+    use crate::layout::Arrangement;
+    use crate::mixer::MixMode;
+    use crate::trigger::TriggerMode;
+
+    #[test]
+    fn test_empty_config_is_default() {
+        let config = Config::from_toml_str("").unwrap();
+        assert!(config.hooks.is_empty());
+        assert!(config.history.enabled);
+    }
+
+    #[test]
+    fn test_missing_file_is_default() {
+        let config = Config::load(Path::new("/nonexistent/conch.toml")).unwrap();
+        assert!(config.hooks.is_empty());
+    }
+
+    #[test]
+    fn test_parse_hooks() {
+        let toml = r#"
+            [[hooks]]
+            event = "prompt-sent"
+            command = "cat >> ~/voice-journal.jsonl"
+
+            [[hooks]]
+            event = "opencode-idle"
+            command = "tmux display-message done"
+        "#;
+        let config = Config::from_toml_str(toml).unwrap();
+        assert_eq!(config.hooks.len(), 2);
+        assert_eq!(config.hooks[0].event, HookEvent::PromptSent);
+        assert_eq!(config.hooks[1].command, "tmux display-message done");
+    }
+
+    #[test]
+    fn test_parse_wake() {
+        let config = Config::from_toml_str("[wake]\nenabled = true\nthreshold = 0.8").unwrap();
+        assert!(config.wake.enabled);
+        assert_eq!(config.wake.threshold, Some(0.8));
+        assert_eq!(config.wake.phrase, "hey conch");
+        assert!(!Config::default().wake.enabled);
+    }
+
+    #[test]
+    fn test_parse_send() {
+        let config = Config::from_toml_str("[send]\nauto = true\ncancel_secs = 1.5").unwrap();
+        assert!(config.send.auto);
+        assert_eq!(config.send.cancel_secs, 1.5);
+        assert!(!Config::default().send.auto);
+        assert_eq!(config.send.retries, 3, "default");
+
+        let config = Config::from_toml_str("[send]\nretries = 1\nretry_backoff_ms = 200").unwrap();
+        let retry = config.send.retry_policy();
+        assert_eq!(retry.retries, 1);
+        assert_eq!(retry.backoff, std::time::Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_parse_cues() {
+        let toml = "[cues]\nvolume = 0.1\nstart = [[440, 100], [0, 20], [880, 100]]";
+        let config = Config::from_toml_str(toml).unwrap();
+        assert_eq!(config.cues.volume, 0.1);
+        assert_eq!(config.cues.start, vec![(440.0, 100), (0.0, 20), (880.0, 100)]);
+        // Unset cues keep their defaults
+        assert_eq!(config.cues.done, CueConfig::default().done);
+    }
+
+    #[test]
+    fn test_parse_recording_limits() {
+        let config = Config::from_toml_str("[recording]\nsilence_secs = 0").unwrap();
+        assert_eq!(config.recording.silence_secs, 0.0);
+        assert_eq!(config.recording.max_secs, 55.0);
+    }
+
+    #[test]
+    fn test_parse_models() {
+        let toml = "[models]\ndir = \"/m\"\ndefault = \"small.en\"";
+        let config = Config::from_toml_str(toml).unwrap();
+        assert_eq!(
+            config.models.default_path(),
+            Some(std::path::PathBuf::from("/m/ggml-small.en.bin"))
+        );
+    }
+
+    #[test]
+    fn test_parse_snippets() {
+        let toml = "[snippets]\nreview = \"Review {file}\"\ncode-review = \"Review {focus}\"";
+        let config = Config::from_toml_str(toml).unwrap();
+        assert_eq!(config.snippets.len(), 2);
+        assert_eq!(config.snippets["review"], "Review {file}");
+        assert!(Config::default().snippets.is_empty());
+    }
+
+    #[test]
+    fn test_parse_tool_rules() {
+        let toml = r#"
+            [[tools]]
+            name = "github_*"
+            label = "github"
+            target = "repo"
+            focus = "directory"
+        "#;
+        let config = Config::from_toml_str(toml).unwrap();
+        assert_eq!(config.tools.len(), 1);
+        assert_eq!(config.tools[0].label.as_deref(), Some("github"));
+        assert_eq!(
+            config.tools[0].focus,
+            Some(crate::tools::FocusKind::Directory)
+        );
+        assert!(Config::from_toml_str("[[tools]]\nname = \"x\"\nfocus = \"tab\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_audio() {
+        let toml = r#"
+            [audio]
+            host = "alsa"
+            device = "pipewire"
+            buffer_frames = 512
+            refuse_low_rate = true
+            listen = "127.0.0.1:4101"
+        "#;
+        let config = Config::from_toml_str(toml).unwrap();
+        assert_eq!(config.audio.host.as_deref(), Some("alsa"));
+        assert_eq!(config.audio.device.as_deref(), Some("pipewire"));
+        assert_eq!(config.audio.buffer_frames, Some(512));
+        assert!(config.audio.refuse_low_rate);
+        assert_eq!(config.audio.listen.as_deref(), Some("127.0.0.1:4101"));
+        assert_eq!(Config::default().audio.host, None);
+        assert_eq!(Config::default().audio.mix, MixMode::Loudest);
+
+        let toml = "[audio]\ndevices = [\"Headset\", \"Yeti\"]\nmix = \"mix\"";
+        let config = Config::from_toml_str(toml).unwrap();
+        assert_eq!(config.audio.devices, vec!["Headset", "Yeti"]);
+        assert_eq!(config.audio.mix, MixMode::Mix);
+    }
+
+    #[test]
+    fn test_parse_waveform() {
+        let config = Config::from_toml_str("[waveform]\nfull_scale = 0.01").unwrap();
+        assert_eq!(config.waveform.full_scale, 0.01);
+        assert_eq!(config.waveform.noise_floor, 0.001);
+    }
+
+    #[test]
+    fn test_parse_context() {
+        let config = Config::from_toml_str("[context]\nrecent_prompts = 3").unwrap();
+        assert_eq!(config.context.recent_prompts, 3);
+        assert!(!config.context.last_reply);
+        assert!(config.context.enabled());
+        assert_eq!(config.context.max_chars, 2000);
+    }
+
+    #[test]
+    fn test_parse_stt() {
+        assert_eq!(Config::default().stt.language, "en");
+        let config = Config::from_toml_str("[stt]\nlanguage = \"Spanish\"").unwrap();
+        assert_eq!(config.stt.language, "Spanish");
+        assert_eq!(config.stt.retry_model, "large-v3-turbo");
+        let config = Config::from_toml_str("[stt]\nretry_model = \"medium\"").unwrap();
+        assert_eq!(config.stt.retry_model, "medium");
+        assert!(config.stt.server.is_none());
+        let config = Config::from_toml_str("[stt]\nserver = \"http://gpu-box:4100\"").unwrap();
+        assert_eq!(config.stt.server.as_deref(), Some("http://gpu-box:4100"));
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let config = Config::from_toml_str("[log]\nmax_mb = 1\nkeep = 5").unwrap();
+        assert_eq!(config.log.max_mb, 1);
+        assert_eq!(config.log.keep, 5);
+        assert_eq!(config.log.max_age_days, 14);
+    }
+
+    #[test]
+    fn test_parse_theme() {
+        let config = Config::from_toml_str(
+            "[theme]\nname = \"high-contrast\"\n\n[theme.colors]\naccent = \"#ffaa00\"",
+        )
+        .unwrap();
+        assert_eq!(config.theme.name, "high-contrast");
+        assert_eq!(config.theme.colors["accent"], "#ffaa00");
+        assert_eq!(Config::default().theme.name, "default");
+    }
+
+    #[test]
+    fn test_parse_display() {
+        let config = Config::from_toml_str("[display]\nunicode = \"ascii\"").unwrap();
+        assert_eq!(config.display.unicode, Charset::Ascii);
+        assert!(Config::from_toml_str("[display]\nunicode = \"emoji\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_layout() {
+        let toml = r#"
+[layout]
+arrangement = "side-by-side"
+waveform = false
+transcript_width = 35
+"#;
+        let config = Config::from_toml_str(toml).unwrap();
+        assert_eq!(config.layout.arrangement, Arrangement::SideBySide);
+        assert!(!config.layout.waveform);
+        assert_eq!(config.layout.transcript_width, 35);
+        assert_eq!(config.layout.waveform_height, 10);
+    }
+
+    #[test]
+    fn test_parse_keymap() {
+        let config = Config::from_toml_str("[keys]\nkeymap = \"vim\"").unwrap();
+        assert_eq!(config.keys.keymap, Keymap::Vim);
+        assert_eq!(Config::default().keys.keymap, Keymap::Default);
+    }
+
+    #[test]
+    fn test_parse_preview() {
+        let config = Config::from_toml_str("[preview]\nlines = 12").unwrap();
+        assert!(config.preview.enabled);
+        assert_eq!(config.preview.lines, 12);
+    }
+
+    #[test]
+    fn test_parse_git() {
+        let config = Config::from_toml_str("[git]\nenabled = false").unwrap();
+        assert!(!config.git.enabled);
+        assert_eq!(config.git.refresh_secs, 5);
+    }
+
+    #[test]
+    fn test_parse_handsfree() {
+        let config = Config::from_toml_str("[handsfree]\nenabled = true").unwrap();
+        assert!(config.handsfree.enabled);
+        assert_eq!(config.handsfree.delay_ms, 500);
+        assert!(!Config::default().handsfree.enabled);
+    }
+
+    #[test]
+    fn test_parse_trigger() {
+        let config = Config::from_toml_str(
+            "[trigger]\nmode = \"hold\"\n[trigger.hid]\nvendor_id = 0x05f3\nproduct_id = 0x00ff\nmask = 2",
+        )
+        .unwrap();
+        assert_eq!(config.trigger.mode, TriggerMode::Hold);
+        let hid = config.trigger.hid.unwrap();
+        assert_eq!(
+            (hid.vendor_id, hid.product_id, hid.mask),
+            (0x05f3, 0x00ff, 2)
+        );
+        assert_eq!(config.trigger.midi, None);
+    }
+
+    #[test]
+    fn test_parse_dictation() {
+        let config = Config::from_toml_str("[dictation]\npause_secs = 1.5").unwrap();
+        assert_eq!(config.dictation.pause_secs, 1.5);
+        assert_eq!(config.dictation.max_segment_secs, 25.0);
+    }
+
+    #[test]
+    fn test_parse_caption() {
+        assert!(!Config::default().caption.enabled);
+        let config = Config::from_toml_str("[caption]\nenabled = true\ninterval_ms = 800").unwrap();
+        assert!(config.caption.enabled);
+        assert_eq!(config.caption.interval_ms, 800);
+        assert_eq!(config.caption.window_secs, 8.0);
+    }
+
+    #[test]
+    fn test_parse_control() {
+        assert!(Config::default().control.enabled);
+        let config = Config::from_toml_str("[control]\nsocket = \"/tmp/conch.sock\"").unwrap();
+        assert_eq!(config.control.socket_path(), Path::new("/tmp/conch.sock"));
+        let config = Config::from_toml_str("[control]\nenabled = false").unwrap();
+        assert!(!config.control.enabled);
+    }
+
+    #[test]
+    fn test_parse_clipboard() {
+        use crate::clipboard::Backend;
+        assert_eq!(Config::default().clipboard.backend, Backend::Auto);
+        let config = Config::from_toml_str("[clipboard]\nbackend = \"osc52\"").unwrap();
+        assert_eq!(config.clipboard.backend, Backend::Osc52);
+        assert!(Config::from_toml_str("[clipboard]\nbackend = \"arboard\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_connection() {
+        assert_eq!(Config::default().connection.idle_timeout_secs, 60);
+        let config = Config::from_toml_str("[connection]\nidle_timeout_secs = 180").unwrap();
+        assert_eq!(config.connection.idle_timeout_secs, 180);
+        assert_eq!(config.connection.directory, None);
+        let config = Config::from_toml_str("[connection]\ndirectory = '~/src/site'").unwrap();
+        let directory = config.connection.directory.as_deref();
+        assert_eq!(directory, Some(Path::new("~/src/site")));
+    }
+
+    #[test]
+    fn test_parse_agent() {
+        assert_eq!(Config::default().agent.default, None);
+        let config = Config::from_toml_str("[agent]\ndefault = \"plan\"").unwrap();
+        assert_eq!(config.agent.default.as_deref(), Some("plan"));
+        assert_eq!(config.agent.names, vec!["build", "plan"]);
+    }
+
+    #[test]
+    fn test_parse_hallucinations() {
+        let config = Config::from_toml_str("[hallucinations]\naction = \"flag\"").unwrap();
+        assert_eq!(config.hallucinations.action, Action::Flag);
+        assert!(config.hallucinations.matches("Thanks for watching!"));
+    }
+
+    #[test]
+    fn test_parse_numbers() {
+        assert!(Config::default().numbers.enabled);
+        let config = Config::from_toml_str("[numbers]\nenabled = false").unwrap();
+        assert!(!config.numbers.enabled);
+    }
+
+    #[test]
+    fn test_parse_route() {
+        assert_eq!(Config::default().route.type_command, None);
+        let config = Config::from_toml_str("[route]\nnotes = '/tmp/ideas.md'").unwrap();
+        assert_eq!(config.route.notes, Path::new("/tmp/ideas.md"));
+    }
+
+    #[test]
+    fn test_parse_vocabulary() {
+        assert!(Config::default().vocabulary.enabled);
+        let config = Config::from_toml_str("[vocabulary]\nmax_terms = 50").unwrap();
+        assert_eq!(config.vocabulary.max_terms, 50);
+        assert!(config.vocabulary.enabled);
+        assert!(config.vocabulary.correct);
+        let config = Config::from_toml_str("[vocabulary]\ncorrect = false").unwrap();
+        assert!(!config.vocabulary.correct);
+    }
+
+    #[test]
+    fn test_invalid_config_is_error() {
+        let result = Config::from_toml_str("[[hooks]]\nevent = \"not-an-event\"\ncommand = \"x\"");
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,566 @@
+// Control Module - Driving a running conch from outside
+//
+// The TUI listens on a local socket (a Unix socket, or a named pipe on
+// Windows) so window manager bindings, Stream Deck buttons and scripts can
+// record, send and read the focus stack without the terminal in front. Each
+// line a client writes is one JSON command, and each gets one JSON line back:
+//
+//   {"command":"record","action":"toggle"}     {"ok":true}
+//   {"command":"send","text":"run the tests"}  {"ok":true}
+//   {"command":"focus","action":"list"}        {"ok":true,"result":[...]}
+//   {"command":"record","action":"stop"}       {"ok":false,"error":"Not recording"}
+//
+// Commands are carried out by the TUI's event loop, exactly as the keys
+// would; this module only moves them in and the answers out.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use crate::paths;
+
+/// `[control]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ControlConfig {
+    /// Listen for commands.
+    pub enabled: bool,
+    /// Socket to listen on, or a pipe name like `\\.\pipe\conch` on
+    /// Windows. [`paths::control_socket`] when unset.
+    pub socket: Option<PathBuf>,
+}
+
+impl Default for ControlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            socket: None,
+        }
+    }
+}
+
+impl ControlConfig {
+    /// The socket to listen on.
+    pub fn socket_path(&self) -> PathBuf {
+        self.socket.clone().unwrap_or_else(paths::control_socket)
+    }
+}
+
+/// What a `record` command does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordAction {
+    Start,
+    Stop,
+    /// Start or stop, like Space.
+    Toggle,
+}
+
+/// What a `focus` command does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FocusAction {
+    /// The focus stack, newest first.
+    #[default]
+    List,
+}
+
+/// A command, as a client sends it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum Command {
+    Record {
+        action: RecordAction,
+    },
+    /// Send `text` as a prompt, or without it the pending prompt.
+    Send {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+    },
+    /// Drop the pending prompt.
+    Discard,
+    Focus {
+        #[serde(default)]
+        action: FocusAction,
+    },
+    /// Recording state, session and pending prompt.
+    Status,
+    /// Transcribe the last recording again, optionally with another model
+    /// (a name or path) or language.
+    Retranscribe {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        language: Option<String>,
+    },
+}
+
+impl Command {
+    /// Usage of [`from_words`](Self::from_words), for `conch ctl`.
+    pub const USAGE: &str = "Usage: conch ctl record [start | stop | toggle] | send [text] | discard \
+                             | focus [list] | status \
+                             | retranscribe [model NAME] [language NAME]";
+
+    /// Parse a command written out as words, as in `conch ctl record toggle`.
+    /// The words after `send` are the prompt; `retranscribe` takes its
+    /// options as `model NAME` and `language NAME` pairs.
+    pub fn from_words(words: &[String]) -> Result<Self> {
+        let rest: Vec<&str> = words.iter().skip(1).map(String::as_str).collect();
+        let command = match (words.first().map(String::as_str), rest.as_slice()) {
+            (Some("record"), []) | (Some("record"), ["toggle"]) => Command::Record {
+                action: RecordAction::Toggle,
+            },
+            (Some("record"), ["start"]) => Command::Record {
+                action: RecordAction::Start,
+            },
+            (Some("record"), ["stop"]) => Command::Record {
+                action: RecordAction::Stop,
+            },
+            (Some("send"), []) => Command::Send { text: None },
+            (Some("send"), text) => Command::Send {
+                text: Some(text.join(" ")),
+            },
+            (Some("discard"), []) => Command::Discard,
+            (Some("focus"), []) | (Some("focus"), ["list"]) => Command::Focus {
+                action: FocusAction::List,
+            },
+            (Some("status"), []) => Command::Status,
+            (Some("retranscribe"), options) if options.len() % 2 == 0 => {
+                let (mut model, mut language) = (None, None);
+                for pair in options.chunks(2) {
+                    let slot = match pair[0] {
+                        "model" => &mut model,
+                        "language" => &mut language,
+                        _ => return Err(anyhow!(Self::USAGE)),
+                    };
+                    *slot = Some(pair[1].to_string());
+                }
+                Command::Retranscribe { model, language }
+            }
+            _ => return Err(anyhow!(Self::USAGE)),
+        };
+        Ok(command)
+    }
+
+    /// Parse the arguments of `conch ctl`: the command's words, up to the
+    /// first option such as `--config`.
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let end = args
+            .iter()
+            .position(|a| a.starts_with("--"))
+            .unwrap_or(args.len());
+        Self::from_words(&args[..end])
+    }
+}
+
+/// The answer to a command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Response {
+    /// Done, nothing to report.
+    pub fn ok() -> Self {
+        Self {
+            ok: true,
+            result: None,
+            error: None,
+        }
+    }
+
+    /// Done, with `result`.
+    pub fn with(result: Value) -> Self {
+        Self {
+            result: Some(result),
+            ..Self::ok()
+        }
+    }
+
+    /// Not done, because of `message`.
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            result: None,
+            error: Some(message.into()),
+        }
+    }
+
+    /// The result of a command that was done; a refused one is an error.
+    pub fn into_result(self) -> Result<Option<Value>> {
+        if !self.ok {
+            return Err(anyhow!(
+                self.error.unwrap_or_else(|| "Command failed".into())
+            ));
+        }
+        Ok(self.result)
+    }
+}
+
+/// A command from a client, and where its answer goes. Cloned like any bus
+/// event; the first [`reply`](Self::reply) is the one the client gets.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub command: Command,
+    reply: Arc<Mutex<Option<oneshot::Sender<Response>>>>,
+}
+
+impl Request {
+    pub fn new(command: Command) -> (Self, oneshot::Receiver<Response>) {
+        let (tx, rx) = oneshot::channel();
+        let request = Self {
+            command,
+            reply: Arc::new(Mutex::new(Some(tx))),
+        };
+        (request, rx)
+    }
+
+    /// Answer the client.
+    pub fn reply(&self, response: Response) {
+        if let Some(tx) = self.reply.lock().unwrap().take() {
+            let _ = tx.send(response);
+        }
+    }
+}
+
+/// A socket being listened on. Dropping it stops listening, and removes a
+/// Unix socket's file.
+#[derive(Debug)]
+pub struct ControlSocket {
+    path: PathBuf,
+    task: JoinHandle<()>,
+}
+
+impl ControlSocket {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        self.task.abort();
+        #[cfg(unix)]
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Listen on the Unix socket at `path`, passing each command to
+/// `on_request`. A socket left behind by a conch that didn't exit cleanly
+/// is replaced; one another conch is still listening on is an error. Must
+/// be called on the tokio runtime.
+#[cfg(unix)]
+pub fn listen(
+    path: &Path,
+    on_request: impl Fn(Request) + Send + Sync + 'static,
+) -> Result<ControlSocket> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+    use tokio::net::UnixListener;
+
+    if let Ok(meta) = std::fs::symlink_metadata(path) {
+        if !meta.file_type().is_socket() {
+            return Err(anyhow!("{} exists and isn't a socket", path.display()));
+        }
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            return Err(anyhow!("another conch is listening on {}", path.display()));
+        }
+        std::fs::remove_file(path)
+            .map_err(|e| anyhow!("Failed to remove stale {}: {e}", path.display()))?;
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let listener = UnixListener::bind(path)
+        .map_err(|e| anyhow!("Failed to listen on {}: {e}", path.display()))?;
+    // Commands can send prompts: only the user running conch gets to
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    let on_request = Arc::new(on_request);
+    let task = tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(serve(stream, Arc::clone(&on_request)));
+                }
+                // Out of file descriptors, most likely: wait for some
+                Err(_) => tokio::time::sleep(std::time::Duration::from_millis(100)).await,
+            }
+        }
+    });
+    Ok(ControlSocket {
+        path: path.to_path_buf(),
+        task,
+    })
+}
+
+/// Listen on the named pipe `path`, passing each command to `on_request`.
+/// A pipe another conch already created is an error. Must be called on the
+/// tokio runtime.
+#[cfg(windows)]
+pub fn listen(
+    path: &Path,
+    on_request: impl Fn(Request) + Send + Sync + 'static,
+) -> Result<ControlSocket> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let name = path.as_os_str().to_owned();
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(&name)
+        .map_err(|e| anyhow!("Failed to listen on {}: {e}", path.display()))?;
+    let on_request = Arc::new(on_request);
+    let task = tokio::spawn(async move {
+        loop {
+            if server.connect().await.is_err() {
+                return;
+            }
+            // The next client connects to a new instance of the pipe
+            let Ok(next) = ServerOptions::new().create(&name) else {
+                return;
+            };
+            let client = std::mem::replace(&mut server, next);
+            tokio::spawn(serve(client, Arc::clone(&on_request)));
+        }
+    });
+    Ok(ControlSocket {
+        path: path.to_path_buf(),
+        task,
+    })
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn listen(
+    _path: &Path,
+    _on_request: impl Fn(Request) + Send + Sync + 'static,
+) -> Result<ControlSocket> {
+    Err(anyhow!(
+        "The control socket isn't supported on this platform"
+    ))
+}
+
+/// Send `command` to the conch listening on `path` and wait for its answer.
+pub async fn request(path: &Path, command: &Command) -> Result<Response> {
+    let stream = connect(path).await.map_err(|e| {
+        anyhow!(
+            "No conch is listening on {} ({e}); is it running?",
+            path.display()
+        )
+    })?;
+    let (read, mut write) = tokio::io::split(stream);
+    let mut json = serde_json::to_string(command)?;
+    json.push('\n');
+    write.write_all(json.as_bytes()).await?;
+    let line = BufReader::new(read)
+        .lines()
+        .next_line()
+        .await?
+        .ok_or_else(|| anyhow!("conch hung up without answering"))?;
+    Ok(serde_json::from_str(&line)?)
+}
+
+#[cfg(unix)]
+async fn connect(path: &Path) -> std::io::Result<tokio::net::UnixStream> {
+    tokio::net::UnixStream::connect(path).await
+}
+
+#[cfg(windows)]
+async fn connect(path: &Path) -> std::io::Result<tokio::net::windows::named_pipe::NamedPipeClient> {
+    tokio::net::windows::named_pipe::ClientOptions::new().open(path)
+}
+
+#[cfg(not(any(unix, windows)))]
+async fn connect(_path: &Path) -> std::io::Result<tokio::io::DuplexStream> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+/// Answer one client's commands until it hangs up.
+async fn serve<S, F>(stream: S, on_request: Arc<F>)
+where
+    S: AsyncRead + AsyncWrite,
+    F: Fn(Request),
+{
+    let (read, mut write) = tokio::io::split(stream);
+    let mut lines = BufReader::new(read).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str(&line) {
+            Ok(command) => {
+                let (request, answer) = Request::new(command);
+                on_request(request);
+                answer
+                    .await
+                    .unwrap_or_else(|_| Response::error("conch is shutting down"))
+            }
+            Err(e) => Response::error(format!("Invalid command: {e}")),
+        };
+        let mut json = serde_json::to_string(&response).unwrap_or_default();
+        json.push('\n');
+        if write.write_all(json.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        let parse = |json: &str| serde_json::from_str::<Command>(json).unwrap();
+        assert_eq!(
+            parse(r#"{"command":"record","action":"toggle"}"#),
+            Command::Record {
+                action: RecordAction::Toggle
+            }
+        );
+        assert_eq!(
+            parse(r#"{"command":"send","text":"run the tests"}"#),
+            Command::Send {
+                text: Some("run the tests".into())
+            }
+        );
+        assert_eq!(parse(r#"{"command":"send"}"#), Command::Send { text: None });
+        assert_eq!(
+            parse(r#"{"command":"focus"}"#),
+            Command::Focus {
+                action: FocusAction::List
+            }
+        );
+        assert_eq!(
+            parse(r#"{"command":"retranscribe","language":"es"}"#),
+            Command::Retranscribe {
+                model: None,
+                language: Some("es".into())
+            }
+        );
+        assert!(serde_json::from_str::<Command>(r#"{"command":"record"}"#).is_err());
+        assert!(serde_json::from_str::<Command>(r#"{"command":"dance"}"#).is_err());
+
+        let json = serde_json::to_string(&Response::error("Not recording")).unwrap();
+        assert_eq!(json, r#"{"ok":false,"error":"Not recording"}"#);
+        assert_eq!(
+            serde_json::to_string(&Response::ok()).unwrap(),
+            r#"{"ok":true}"#
+        );
+    }
+
+    #[test]
+    fn test_from_words() {
+        let words = |line: &str| -> Vec<String> { line.split(' ').map(String::from).collect() };
+        let parse = |line: &str| Command::from_words(&words(line)).unwrap();
+        assert_eq!(
+            parse("record"),
+            Command::Record {
+                action: RecordAction::Toggle
+            }
+        );
+        assert_eq!(
+            parse("record stop"),
+            Command::Record {
+                action: RecordAction::Stop
+            }
+        );
+        assert_eq!(
+            parse("send run the tests"),
+            Command::Send {
+                text: Some("run the tests".into())
+            }
+        );
+        assert_eq!(parse("send"), Command::Send { text: None });
+        assert_eq!(parse("status"), Command::Status);
+        assert_eq!(
+            parse("retranscribe language de model large-v3"),
+            Command::Retranscribe {
+                model: Some("large-v3".into()),
+                language: Some("de".into())
+            }
+        );
+        assert_eq!(
+            parse("retranscribe"),
+            Command::Retranscribe {
+                model: None,
+                language: None
+            }
+        );
+        assert!(Command::from_words(&words("record louder")).is_err());
+        assert!(Command::from_words(&words("retranscribe model")).is_err());
+        assert!(Command::from_words(&words("retranscribe speed 2")).is_err());
+        assert!(Command::from_words(&[]).is_err());
+
+        // `conch ctl` options after the command aren't part of it
+        let args = words("send ship it --config conch.toml");
+        let text = Some("ship it".to_string());
+        assert_eq!(Command::from_args(&args).unwrap(), Command::Send { text });
+        assert!(Command::from_args(&words("--config conch.toml")).is_err());
+    }
+
+    #[test]
+    fn test_response_into_result() {
+        let status = serde_json::json!("idle");
+        let done = Response::with(status.clone()).into_result().unwrap();
+        assert_eq!(done, Some(status));
+        assert_eq!(Response::ok().into_result().unwrap(), None);
+        let refused = Response::error("Not recording").into_result().unwrap_err();
+        assert_eq!(refused.to_string(), "Not recording");
+        let silent = Response {
+            error: None,
+            ..Response::error("")
+        };
+        let err = silent.into_result().unwrap_err();
+        assert_eq!(err.to_string(), "Command failed");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_socket_round_trip() {
+        use tokio::net::UnixStream;
+
+        let dir = std::env::temp_dir().join(format!("conch_control_{}", std::process::id()));
+        let path = dir.join("conch.sock");
+        std::fs::create_dir_all(&dir).unwrap();
+        // Left behind by a conch that crashed
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        let socket = listen(&path, |request| match request.command {
+            Command::Status => request.reply(Response::with(serde_json::json!("idle"))),
+            _ => request.reply(Response::error("Not recording")),
+        })
+        .unwrap();
+        assert!(listen(&path, |_| {}).is_err(), "already listening");
+
+        let (read, mut write) = UnixStream::connect(&path).await.unwrap().into_split();
+        let mut lines = BufReader::new(read).lines();
+        write
+            .write_all(b"{\"command\":\"status\"}\n\nnonsense\n{\"command\":\"discard\"}\n")
+            .await
+            .unwrap();
+        let answer = lines.next_line().await.unwrap().unwrap();
+        assert_eq!(answer, r#"{"ok":true,"result":"idle"}"#);
+        let answer = lines.next_line().await.unwrap().unwrap();
+        assert!(answer.starts_with(r#"{"ok":false,"error":"Invalid command"#));
+        let answer = lines.next_line().await.unwrap().unwrap();
+        assert_eq!(answer, r#"{"ok":false,"error":"Not recording"}"#);
+
+        let response = request(&path, &Command::Status).await.unwrap();
+        assert_eq!(response.result, Some(serde_json::json!("idle")));
+
+        drop(socket);
+        assert!(!path.exists());
+        let gone = request(&path, &Command::Status).await.unwrap_err();
+        assert!(gone.to_string().starts_with("No conch is listening"));
+        std::fs::write(&path, "notes").unwrap();
+        assert!(listen(&path, |_| {}).is_err(), "not a socket");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,236 @@
+// Conversation Module - Recent turns added to the context block
+//
+// The focus stack says which files are in play, but "do that but for the
+// tests" refers to the conversation. With `[context]` enabled, the context
+// block sent before each prompt also lists the last few prompts sent in the
+// session and a short summary of the assistant's last reply.
+//
+// The whole block is kept under a character budget. Over it, the oldest
+// prompts are dropped first, then the reply summary, then the focus details,
+// so what's left is always the current focus.
+
+use serde::Deserialize;
+
+use crate::focus::{self, FocusState};
+use crate::history::HistoryEntry;
+use crate::tts::speakable_text;
+
+/// `[context]` section of the config file. Off by default, since every
+/// prompt gets longer.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ContextConfig {
+    /// How many earlier prompts of the session to include (0 = none).
+    pub recent_prompts: usize,
+    /// Include a summary of the assistant's last reply.
+    pub last_reply: bool,
+    /// Longest reply summary, in characters.
+    pub reply_chars: usize,
+    /// Longest context block, focus and conversation together, in
+    /// characters (0 = no limit).
+    pub max_chars: usize,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            recent_prompts: 0,
+            last_reply: false,
+            reply_chars: 200,
+            max_chars: 2000,
+        }
+    }
+}
+
+impl ContextConfig {
+    /// Whether anything about the conversation goes into the context.
+    pub fn enabled(&self) -> bool {
+        self.recent_prompts > 0 || self.last_reply
+    }
+
+    /// The context block sent before a prompt: the `focus`, then the last
+    /// sent prompts among `transcripts` (oldest first) and a summary of
+    /// `reply`, as far as enabled. `None` when there is nothing to say.
+    ///
+    /// Over `max_chars`, the oldest prompts go first, then the reply
+    /// summary, then the branch, file and directory. The current focus
+    /// stays, and is only cut short if it is over the budget by itself.
+    pub fn context_block(
+        &self,
+        focus: &FocusState,
+        transcripts: &[HistoryEntry],
+        reply: Option<&str>,
+    ) -> Option<String> {
+        let mut parts = focus.context_parts();
+        let current = usize::from(focus.current_entry().is_some());
+        let mut prompts = self.recent_prompts(transcripts);
+        let mut reply = self.reply_summary(reply);
+        loop {
+            let block = join_block(&parts, &prompts, reply.as_deref());
+            if block.is_empty() {
+                return None;
+            }
+            if self.max_chars == 0 || block.chars().count() <= self.max_chars {
+                return Some(block);
+            }
+            if !prompts.is_empty() {
+                prompts.remove(0);
+            } else if reply.is_some() {
+                reply = None;
+            } else if parts.len() > current {
+                parts.pop();
+            } else {
+                let cut: String = block
+                    .chars()
+                    .take(self.max_chars.saturating_sub(1))
+                    .collect();
+                return Some(format!("{cut}\u{2026}"));
+            }
+        }
+    }
+
+    /// The last sent prompts among `transcripts`, oldest first.
+    fn recent_prompts(&self, transcripts: &[HistoryEntry]) -> Vec<String> {
+        let mut prompts: Vec<String> = transcripts
+            .iter()
+            .rev()
+            .filter(|e| e.status.was_sent())
+            .take(self.recent_prompts)
+            .map(|e| e.text.clone())
+            .collect();
+        prompts.reverse();
+        prompts
+    }
+
+    fn reply_summary(&self, reply: Option<&str>) -> Option<String> {
+        if !self.last_reply {
+            return None;
+        }
+        reply
+            .map(|r| speakable_text(r, self.reply_chars))
+            .filter(|s| !s.is_empty())
+    }
+}
+
+/// The context block's lines: focus, recent prompts, last reply.
+fn join_block(focus: &[String], prompts: &[String], reply: Option<&str>) -> String {
+    let mut lines: Vec<String> = focus::context_line(focus).into_iter().collect();
+    if !prompts.is_empty() {
+        let quoted: Vec<String> = prompts.iter().map(|p| format!("\"{p}\"")).collect();
+        lines.push(format!("[Recent prompts: {}]", quoted.join(" | ")));
+    }
+    if let Some(summary) = reply {
+        lines.push(format!("[Last reply: {}]", summary));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::focus::FocusEntry;
+    use crate::history::TranscriptStatus;
+
+    fn entry(text: &str, status: TranscriptStatus) -> HistoryEntry {
+        HistoryEntry {
+            status,
+            ..HistoryEntry::new(text, None)
+        }
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let config = ContextConfig::default();
+        assert!(!config.enabled());
+        let transcripts = vec![entry("add a parser test", TranscriptStatus::Sent)];
+        assert_eq!(
+            config.context_block(&FocusState::new(), &transcripts, Some("Done.")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_recent_prompts_skip_unsent() {
+        let config = ContextConfig {
+            recent_prompts: 2,
+            ..ContextConfig::default()
+        };
+        let transcripts = vec![
+            entry("open the parser", TranscriptStatus::Sent),
+            entry("add a parser test", TranscriptStatus::Sent),
+            entry("never mind", TranscriptStatus::Discarded),
+            entry("run it", TranscriptStatus::Sent),
+            entry("do that but for the tests", TranscriptStatus::Pending),
+        ];
+        assert_eq!(
+            config
+                .context_block(&FocusState::new(), &transcripts, None)
+                .unwrap(),
+            "[Recent prompts: \"add a parser test\" | \"run it\"]"
+        );
+    }
+
+    #[test]
+    fn test_last_reply_summary() {
+        let config = ContextConfig {
+            last_reply: true,
+            reply_chars: 30,
+            ..ContextConfig::default()
+        };
+        let reply = "I added **three** tests. They all pass now.\n```rust\nfn x() {}\n```";
+        assert_eq!(
+            config
+                .context_block(&FocusState::new(), &[], Some(reply))
+                .unwrap(),
+            "[Last reply: I added three tests.]"
+        );
+        assert_eq!(config.context_block(&FocusState::new(), &[], None), None);
+    }
+
+    #[test]
+    fn test_budget_keeps_current_focus() {
+        let mut focus = FocusState::new();
+        focus.append(FocusEntry::Branch("main".into()));
+        focus.append(FocusEntry::Directory("crates/core".into()));
+        focus.append(FocusEntry::File("src/lib.rs".into()));
+        let transcripts = vec![
+            entry("open the parser", TranscriptStatus::Sent),
+            entry("add a test", TranscriptStatus::Sent),
+        ];
+        let reply = Some("I added the test.");
+        let mut config = ContextConfig {
+            recent_prompts: 2,
+            last_reply: true,
+            max_chars: 0,
+            ..ContextConfig::default()
+        };
+        let block = |config: &ContextConfig| config.context_block(&focus, &transcripts, reply);
+        let full = block(&config).unwrap();
+        assert_eq!(full.lines().count(), 3);
+
+        // The oldest prompt goes first
+        config.max_chars = full.chars().count() - 1;
+        let trimmed = block(&config).unwrap();
+        assert!(!trimmed.contains("open the parser"));
+        assert!(trimmed.contains("[Recent prompts: \"add a test\"]"));
+        assert!(trimmed.contains("[Last reply: I added the test.]"));
+
+        // Then the conversation, then the branch and directory
+        let focus_line = focus.to_context_string().unwrap();
+        config.max_chars = focus_line.chars().count();
+        assert_eq!(block(&config).unwrap(), focus_line);
+        config.max_chars -= 1;
+        assert_eq!(
+            block(&config).unwrap(),
+            "[Context: Currently focused on file src/lib.rs, in directory crates/core]"
+        );
+        config.max_chars = 50;
+        assert_eq!(
+            block(&config).unwrap(),
+            "[Context: Currently focused on file src/lib.rs]"
+        );
+        // Only the current focus left over budget: cut short
+        config.max_chars = 20;
+        assert_eq!(block(&config).unwrap(), "[Context: Currently\u{2026}");
+    }
+}
@@ -0,0 +1,209 @@
+// Cues Module - Short audible tones for record start/stop and transcript ready
+//
+// Plays through the default output device via a small cpal output stream that
+// drains a shared sample queue and outputs silence otherwise. Errors of the
+// stream go to the caller's error sink, never to the terminal the TUI owns.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Result, anyhow};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SizedSample};
+use serde::Deserialize;
+
+/// Fade in/out applied to every note so tones don't click.
+const FADE_MS: f32 = 5.0;
+
+/// A note: (frequency in Hz, duration in ms). Frequency 0 is a rest.
+pub type Note = (f32, u32);
+
+/// Pipeline moments that get a cue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cue {
+    RecordStart,
+    RecordStop,
+    TranscriptReady,
+}
+
+/// `[cues]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CueConfig {
+    pub enabled: bool,
+    /// Peak amplitude, 0.0–1.0.
+    pub volume: f32,
+    /// Notes played when recording starts (default: rising pair).
+    pub start: Vec<Note>,
+    /// Notes played when recording stops (default: falling pair).
+    pub stop: Vec<Note>,
+    /// Notes played when a transcript is ready (default: one high blip).
+    pub done: Vec<Note>,
+}
+
+impl Default for CueConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            volume: 0.25,
+            start: vec![(660.0, 60), (880.0, 60)],
+            stop: vec![(880.0, 60), (660.0, 60)],
+            done: vec![(1320.0, 50)],
+        }
+    }
+}
+
+impl CueConfig {
+    /// The notes for `cue`.
+    pub fn notes(&self, cue: Cue) -> &[Note] {
+        match cue {
+            Cue::RecordStart => &self.start,
+            Cue::RecordStop => &self.stop,
+            Cue::TranscriptReady => &self.done,
+        }
+    }
+}
+
+/// Render `notes` as mono f32 samples at `sample_rate`.
+pub fn synthesize(notes: &[Note], volume: f32, sample_rate: u32) -> Vec<f32> {
+    let volume = volume.clamp(0.0, 1.0);
+    let rate = sample_rate as f32;
+    let fade = ((FADE_MS / 1000.0) * rate) as usize;
+    let mut out = Vec::new();
+    for &(freq, ms) in notes {
+        let n = (ms as f32 / 1000.0 * rate) as usize;
+        for i in 0..n {
+            if freq <= 0.0 {
+                out.push(0.0);
+                continue;
+            }
+            let envelope = (i.min(n - 1 - i) as f32 / fade.max(1) as f32).min(1.0);
+            let phase = 2.0 * std::f32::consts::PI * freq * i as f32 / rate;
+            out.push(volume * envelope * phase.sin());
+        }
+    }
+    out
+}
+
+/// Plays cues on the default output device.
+pub struct CuePlayer {
+    config: CueConfig,
+    queue: Arc<Mutex<VecDeque<f32>>>,
+    _stream: cpal::Stream,
+    sample_rate: u32,
+}
+
+impl CuePlayer {
+    /// Open the default output device, handing errors of its stream to
+    /// `on_error`.
+    pub fn new(config: CueConfig, on_error: impl Fn(String) + Send + 'static) -> Result<Self> {
+        let host = cpal::default_host();
+        let device = host
+            .default_output_device()
+            .ok_or_else(|| anyhow!("No audio output device found"))?;
+        let supported_config = device.default_output_config()?;
+        let sample_rate = supported_config.sample_rate().0;
+        let sample_format = supported_config.sample_format();
+        let channels = supported_config.channels() as usize;
+        let stream_config: cpal::StreamConfig = supported_config.into();
+
+        let queue = Arc::new(Mutex::new(VecDeque::new()));
+        let q = Arc::clone(&queue);
+        let stream = match sample_format {
+            cpal::SampleFormat::F32 => {
+                build_output_stream::<f32>(&device, &stream_config, q, channels, on_error)?
+            }
+            cpal::SampleFormat::I16 => {
+                build_output_stream::<i16>(&device, &stream_config, q, channels, on_error)?
+            }
+            cpal::SampleFormat::U16 => {
+                build_output_stream::<u16>(&device, &stream_config, q, channels, on_error)?
+            }
+            format => return Err(anyhow!("Unsupported sample format: {:?}", format)),
+        };
+        stream.play()?;
+
+        Ok(Self {
+            config,
+            queue,
+            _stream: stream,
+            sample_rate,
+        })
+    }
+
+    /// Queue the tone for `cue`, replacing any cue still playing.
+    pub fn play(&self, cue: Cue) {
+        let samples = synthesize(self.config.notes(cue), self.config.volume, self.sample_rate);
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.clear();
+            queue.extend(samples);
+        }
+    }
+}
+
+/// Build a cpal output stream that drains the shared queue, duplicating
+/// each mono sample across channels, and reports its errors to `on_error`.
+fn build_output_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    queue: Arc<Mutex<VecDeque<f32>>>,
+    channels: usize,
+    on_error: impl Fn(String) + Send + 'static,
+) -> Result<cpal::Stream>
+where
+    T: SizedSample + FromSample<f32> + Send + 'static,
+{
+    let stream = device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+            let mut queue = queue.try_lock().ok();
+            for frame in data.chunks_mut(channels) {
+                let sample = queue.as_mut().and_then(|q| q.pop_front()).unwrap_or(0.0);
+                for out in frame.iter_mut() {
+                    *out = T::from_sample(sample);
+                }
+            }
+        },
+        move |err| on_error(err.to_string()),
+        None,
+    )?;
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthesize_length() {
+        let samples = synthesize(&[(440.0, 100), (0.0, 50)], 0.5, 16000);
+        assert_eq!(samples.len(), 1600 + 800);
+    }
+
+    #[test]
+    fn test_synthesize_respects_volume_and_fades() {
+        let samples = synthesize(&[(440.0, 100)], 0.3, 16000);
+        assert!(samples.iter().all(|s| s.abs() <= 0.3 + 1e-6));
+        assert_eq!(samples[0], 0.0);
+        assert!(samples.last().unwrap().abs() < 1e-3);
+        assert!(samples.iter().any(|s| s.abs() > 0.25));
+    }
+
+    #[test]
+    fn test_rest_is_silent() {
+        assert!(synthesize(&[(0.0, 20)], 1.0, 16000).iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_volume_clamped() {
+        let samples = synthesize(&[(440.0, 50)], 5.0, 16000);
+        assert!(samples.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_default_cues_differ() {
+        let config = CueConfig::default();
+        assert_ne!(config.notes(Cue::RecordStart), config.notes(Cue::RecordStop));
+        assert!(!config.notes(Cue::TranscriptReady).is_empty());
+    }
+}
@@ -0,0 +1,455 @@
+// Dictation Module - Long-form dictation, cut into segments on pauses
+//
+// A dictation keeps the mic open until it's finished. A voice activity
+// check on the live level cuts the recording at each pause, so every segment
+// is transcribed while the next one is spoken and the recording buffer never
+// fills. The segments' text adds up to one growing document, tidied into
+// prose as it grows: a pause ends a sentence, a longer pause starts a new
+// paragraph, and each sentence starts with a capital.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// `[dictation]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DictationConfig {
+    /// Seconds of silence after speech that end a segment.
+    pub pause_secs: f32,
+    /// Seconds of silence before speech that start a new paragraph.
+    pub paragraph_secs: f32,
+    /// Longest segment, cut even mid-sentence. Kept well under the 60s
+    /// recording buffer.
+    pub max_segment_secs: f32,
+    /// RMS level at and above which audio counts as speech.
+    pub speech_rms: f32,
+}
+
+impl Default for DictationConfig {
+    fn default() -> Self {
+        Self {
+            pause_secs: 0.8,
+            paragraph_secs: 3.0,
+            max_segment_secs: 25.0,
+            speech_rms: 0.01,
+        }
+    }
+}
+
+/// Common abbreviations whose period doesn't end a sentence.
+const ABBREVIATIONS: &[&str] = &[
+    "e.g.", "i.e.", "etc.", "vs.", "mr.", "mrs.", "dr.", "approx.",
+];
+
+/// How a segment goes on from the text before it, judged by the pause
+/// between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Join {
+    /// Cut mid-speech at the longest segment: the same sentence goes on.
+    Continue,
+    /// After a pause: a new sentence.
+    Sentence,
+    /// After a longer pause: a new paragraph.
+    Paragraph,
+}
+
+/// What to do with the audio recorded since the last cut.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cut {
+    /// Keep recording.
+    None,
+    /// Transcribe it; `join` says how it goes on from the segment before.
+    Segment { join: Join },
+    /// Throw it away: it's all silence.
+    Discard,
+}
+
+/// Finds segment boundaries in a running recording from its level.
+#[derive(Debug, Clone)]
+pub struct Segmenter {
+    config: DictationConfig,
+    /// Recording time the current segment started at.
+    start: Duration,
+    /// Whether the current segment has speech in it.
+    heard: bool,
+    /// Whether the current segment starts a new paragraph.
+    paragraph: bool,
+    /// Whether the current segment goes on from one cut mid-speech.
+    continues: bool,
+    /// Recording time speech was last heard.
+    last_speech: Option<Duration>,
+}
+
+impl Segmenter {
+    pub fn new(config: DictationConfig) -> Self {
+        Self {
+            config,
+            start: Duration::ZERO,
+            heard: false,
+            paragraph: false,
+            continues: false,
+            last_speech: None,
+        }
+    }
+
+    /// Feed the time since recording started and the RMS of the latest audio.
+    pub fn feed(&mut self, elapsed: Duration, rms: f32) -> Cut {
+        let speaking = rms >= self.config.speech_rms;
+        if speaking {
+            if !self.heard {
+                self.heard = true;
+                let paragraph = Duration::from_secs_f32(self.config.paragraph_secs.max(0.0));
+                self.paragraph = self
+                    .last_speech
+                    .is_some_and(|last| elapsed - last >= paragraph);
+            }
+            self.last_speech = Some(elapsed);
+        }
+        let length = elapsed.saturating_sub(self.start);
+        let max = Duration::from_secs_f32(self.config.max_segment_secs.max(1.0));
+        if !self.heard {
+            // Silence only: drop it now and then so it doesn't pile up
+            let discard = Duration::from_secs_f32(self.config.paragraph_secs.max(1.0));
+            if length >= discard {
+                self.start = elapsed;
+                return Cut::Discard;
+            }
+            return Cut::None;
+        }
+        let pause = Duration::from_secs_f32(self.config.pause_secs.max(0.1));
+        let paused = self.last_speech.is_some_and(|last| elapsed - last >= pause);
+        if paused || length >= max {
+            let cut = Cut::Segment { join: self.join() };
+            self.paragraph = false;
+            self.start = elapsed;
+            // Cut mid-speech, the next segment goes straight on
+            self.heard = !paused;
+            self.continues = !paused;
+            return cut;
+        }
+        Cut::None
+    }
+
+    /// Whether the audio since the last cut has speech in it.
+    pub fn has_speech(&self) -> bool {
+        self.heard
+    }
+
+    /// How the audio since the last cut goes on from the segment before.
+    pub fn join(&self) -> Join {
+        if self.paragraph {
+            Join::Paragraph
+        } else if self.continues {
+            Join::Continue
+        } else {
+            Join::Sentence
+        }
+    }
+}
+
+/// A dictation in progress: the document so far and the segments still
+/// being transcribed.
+#[derive(Debug, Clone)]
+pub struct Dictation {
+    pub segmenter: Segmenter,
+    text: String,
+    /// How each segment being transcribed joins the text, oldest first.
+    /// Segments are transcribed in order.
+    pending: VecDeque<Join>,
+    /// Segments transcribed so far.
+    segments: usize,
+    /// Whether recording has ended, so the last segments are all there is.
+    finishing: bool,
+}
+
+impl Dictation {
+    pub fn new(config: DictationConfig) -> Self {
+        Self {
+            segmenter: Segmenter::new(config),
+            text: String::new(),
+            pending: VecDeque::new(),
+            segments: 0,
+            finishing: false,
+        }
+    }
+
+    /// The document so far. Its last sentence is left open until the
+    /// dictation is done.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The finished document, its last sentence ended.
+    pub fn document(&self) -> String {
+        let mut text = self.text.clone();
+        end_sentence(&mut text);
+        text
+    }
+
+    /// Segments transcribed so far.
+    pub fn segments(&self) -> usize {
+        self.segments
+    }
+
+    /// Segments sent off and not transcribed yet.
+    pub fn transcribing(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// A segment was sent to be transcribed.
+    pub fn segment_sent(&mut self, join: Join) {
+        self.pending.push_back(join);
+    }
+
+    /// The oldest segment came back as `text`; `None` if it failed.
+    pub fn segment_done(&mut self, text: Option<&str>) {
+        let join = self.pending.pop_front().unwrap_or(Join::Sentence);
+        let Some(text) = text.map(str::trim).filter(|t| !t.is_empty()) else {
+            return;
+        };
+        self.segments += 1;
+        let starts_sentence = match join {
+            _ if self.text.is_empty() => true,
+            Join::Continue => {
+                self.text.push(' ');
+                false
+            }
+            Join::Sentence => {
+                // A trailing comma means the speaker only drew breath
+                let open = self.text.ends_with([',', ';', '-']);
+                if !open {
+                    end_sentence(&mut self.text);
+                }
+                self.text.push(' ');
+                !open
+            }
+            Join::Paragraph => {
+                end_sentence(&mut self.text);
+                self.text.push_str("\n\n");
+                true
+            }
+        };
+        self.text.push_str(&sentences(text, starts_sentence));
+    }
+
+    /// Recording has stopped; no more segments will be sent.
+    pub fn finish(&mut self) {
+        self.finishing = true;
+    }
+
+    pub fn is_finishing(&self) -> bool {
+        self.finishing
+    }
+
+    /// Whether recording has stopped and every segment is in.
+    pub fn is_done(&self) -> bool {
+        self.finishing && self.pending.is_empty()
+    }
+
+    /// The last `rows` lines of the document wrapped at `width` columns.
+    pub fn tail(&self, width: usize, rows: usize) -> Vec<String> {
+        let lines = wrap(&self.text, width.max(1));
+        lines[lines.len().saturating_sub(rows)..].to_vec()
+    }
+}
+
+/// `text` with runs of whitespace made single spaces and each sentence
+/// starting with a capital, the first one too if `starts_sentence`.
+fn sentences(text: &str, starts_sentence: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut capitalize = starts_sentence;
+    for word in text.split_whitespace() {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        if capitalize {
+            out.push_str(&capitalized(word));
+        } else {
+            out.push_str(word);
+        }
+        capitalize = ends_sentence(word);
+    }
+    out
+}
+
+/// Whether `word` is the last of a sentence.
+fn ends_sentence(word: &str) -> bool {
+    let word = word.trim_end_matches(['"', '\'', ')']);
+    word.ends_with(['.', '!', '?']) && !ABBREVIATIONS.contains(&word.to_lowercase().as_str())
+}
+
+/// `word` with a capital, unless it already has one or looks like code:
+/// `main.rs` and `snake_case` are left as they are.
+fn capitalized(word: &str) -> String {
+    let bare = word.trim_end_matches(|c: char| c.is_ascii_punctuation());
+    let code = bare.contains(['.', '_', '/'])
+        || word.chars().any(|c| c.is_uppercase() || c.is_ascii_digit());
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) if !code => first.to_uppercase().chain(chars).collect(),
+        _ => word.to_string(),
+    }
+}
+
+/// End the last sentence of `text` with a full stop, unless it has its own
+/// punctuation already.
+fn end_sentence(text: &mut String) {
+    if text.ends_with(|c: char| c.is_alphanumeric()) {
+        text.push('.');
+    }
+}
+
+/// Word-wrap `text` at `width` characters, keeping blank lines between
+/// paragraphs. Words longer than a line are split.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+            let used = line.chars().count();
+            if used > 0 && used + 1 + word.len() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            while word.len() > width {
+                let rest = word.split_off(width);
+                lines.push(word.into_iter().collect());
+                word = rest;
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.extend(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(s: f32) -> Duration {
+        Duration::from_secs_f32(s)
+    }
+
+    /// Feed `level` every 100ms from `from` to `to` seconds, collecting cuts.
+    fn run(segmenter: &mut Segmenter, from: f32, to: f32, level: f32) -> Vec<Cut> {
+        let mut cuts = Vec::new();
+        let mut t = from;
+        while t < to - 1e-3 {
+            match segmenter.feed(secs(t), level) {
+                Cut::None => {}
+                cut => cuts.push(cut),
+            }
+            t += 0.1;
+        }
+        cuts
+    }
+
+    #[test]
+    fn test_pauses_cut_segments() {
+        let mut segmenter = Segmenter::new(DictationConfig::default());
+        assert!(run(&mut segmenter, 0.0, 2.0, 0.1).is_empty());
+        // A short breath doesn't cut
+        assert!(run(&mut segmenter, 2.0, 2.5, 0.0).is_empty());
+        assert!(run(&mut segmenter, 2.5, 4.0, 0.1).is_empty());
+        assert_eq!(
+            run(&mut segmenter, 4.0, 5.0, 0.0),
+            vec![Cut::Segment {
+                join: Join::Sentence
+            }]
+        );
+        // A long pause starts a paragraph and drops the silence
+        assert_eq!(run(&mut segmenter, 5.0, 8.5, 0.0), vec![Cut::Discard]);
+        assert!(run(&mut segmenter, 8.5, 9.0, 0.1).is_empty());
+        assert_eq!(
+            run(&mut segmenter, 9.0, 10.0, 0.0),
+            vec![Cut::Segment {
+                join: Join::Paragraph
+            }]
+        );
+    }
+
+    #[test]
+    fn test_long_speech_is_cut() {
+        let config = DictationConfig {
+            max_segment_secs: 10.0,
+            ..DictationConfig::default()
+        };
+        let mut segmenter = Segmenter::new(config);
+        let cuts = run(&mut segmenter, 0.0, 25.0, 0.1);
+        let join = |join| Cut::Segment { join };
+        assert_eq!(cuts, vec![join(Join::Sentence), join(Join::Continue)]);
+        assert_eq!(segmenter.join(), Join::Continue);
+    }
+
+    #[test]
+    fn test_document_grows() {
+        let mut dictation = Dictation::new(DictationConfig::default());
+        dictation.segment_sent(Join::Sentence);
+        dictation.segment_sent(Join::Sentence);
+        dictation.segment_sent(Join::Paragraph);
+        dictation.segment_done(Some(" The parser reads tokens."));
+        dictation.segment_done(Some("It never backtracks. "));
+        dictation.finish();
+        assert!(!dictation.is_done());
+        dictation.segment_done(Some("Errors carry spans."));
+        assert!(dictation.is_done());
+        assert_eq!(dictation.segments(), 3);
+        assert_eq!(
+            dictation.text(),
+            "The parser reads tokens. It never backtracks.\n\nErrors carry spans."
+        );
+        assert_eq!(
+            dictation.tail(20, 3),
+            vec!["backtracks.", "", "Errors carry spans."]
+        );
+    }
+
+    #[test]
+    fn test_segments_become_prose() {
+        let mut dictation = Dictation::new(DictationConfig::default());
+        for join in [
+            Join::Sentence,
+            Join::Continue,
+            Join::Sentence,
+            Join::Paragraph,
+        ] {
+            dictation.segment_sent(join);
+        }
+        dictation.segment_done(Some("the cache  is keyed by path"));
+        dictation.segment_done(Some("and by mtime. it lives in main.rs,"));
+        dictation.segment_done(Some("e.g. the config. why not sqlite?"));
+        dictation.segment_done(Some("version 1.2 drops it"));
+        dictation.finish();
+        assert_eq!(
+            dictation.document(),
+            "The cache is keyed by path and by mtime. It lives in main.rs, e.g. the \
+             config. Why not sqlite?\n\nVersion 1.2 drops it."
+        );
+    }
+
+    #[test]
+    fn test_code_is_not_capitalized() {
+        assert_eq!(
+            sentences("main.rs is broken. parse_args too", true),
+            "main.rs is broken. parse_args too"
+        );
+        assert_eq!(
+            sentences("read main.rs. then stop", false),
+            "read main.rs. Then stop"
+        );
+        assert_eq!(sentences("iPhone. x", true), "iPhone. X");
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("aa bb cc", 5), vec!["aa bb", "cc"]);
+        assert_eq!(wrap("abcdefgh", 3), vec!["abc", "def", "gh"]);
+        assert_eq!(wrap("", 5), vec![""]);
+    }
+}
@@ -0,0 +1,250 @@
+// Diff Module - Unified diffs of the edits OpenCode makes
+//
+// A completed `edit` carries the change twice over: as a unified diff in the
+// tool's metadata, and as the `oldString`/`newString` it replaced. The diff
+// is used when it's there; otherwise the two strings are diffed line by line
+// here. A `write` without a diff shows the written content as added lines.
+
+use crate::transport::ToolEvent;
+
+/// Unchanged lines shown around each change.
+pub const CONTEXT_LINES: usize = 3;
+
+/// Largest old × new line count diffed exactly; bigger edits show as all
+/// lines removed, then all added.
+const MAX_DIFF_CELLS: usize = 250_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    /// An `@@ -a,b +c,d @@` hunk header.
+    Hunk,
+    Context,
+    Added,
+    Removed,
+}
+
+/// One line of a unified diff, without its `+`/`-`/` ` prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+impl DiffLine {
+    fn new(kind: DiffKind, text: &str) -> Self {
+        Self {
+            kind,
+            text: text.to_string(),
+        }
+    }
+
+    /// The line as a unified diff prints it.
+    pub fn prefixed(&self) -> String {
+        let prefix = match self.kind {
+            DiffKind::Hunk => "",
+            DiffKind::Context => " ",
+            DiffKind::Added => "+",
+            DiffKind::Removed => "-",
+        };
+        format!("{prefix}{}", self.text)
+    }
+}
+
+/// The diff of a completed `edit` or `write`, if `event` is one.
+pub fn from_event(event: &ToolEvent) -> Option<Vec<DiffLine>> {
+    if event.state != "completed" {
+        return None;
+    }
+    if let Some(diff) = event.metadata["diff"].as_str() {
+        return Some(parse_unified(diff));
+    }
+    let input = |key: &str| event.input[key].as_str();
+    match event.tool.as_str() {
+        "edit" => Some(unified(input("oldString")?, input("newString")?)),
+        "write" => Some(unified("", input("content")?)),
+        _ => None,
+    }
+}
+
+/// Parse unified diff text, dropping the file headers.
+pub fn parse_unified(text: &str) -> Vec<DiffLine> {
+    let mut in_hunk = false;
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        if line.starts_with("@@") {
+            in_hunk = true;
+            lines.push(DiffLine::new(DiffKind::Hunk, line));
+        } else if !in_hunk {
+            // "Index:", "===", "---" and "+++" before the first hunk
+        } else if let Some(rest) = line.strip_prefix('+') {
+            lines.push(DiffLine::new(DiffKind::Added, rest));
+        } else if let Some(rest) = line.strip_prefix('-') {
+            lines.push(DiffLine::new(DiffKind::Removed, rest));
+        } else if let Some(rest) = line.strip_prefix(' ') {
+            lines.push(DiffLine::new(DiffKind::Context, rest));
+        } else if line.is_empty() {
+            lines.push(DiffLine::new(DiffKind::Context, ""));
+        }
+    }
+    lines
+}
+
+/// Line diff of `old` and `new` with [`CONTEXT_LINES`] of context.
+pub fn unified(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let script = edit_script(&old, &new);
+
+    // Keep changes and the context around them, split into hunks where
+    // the unchanged run between two changes is too long to show
+    let changed: Vec<usize> = (0..script.len())
+        .filter(|&i| script[i].0 != DiffKind::Context)
+        .collect();
+    let mut lines = Vec::new();
+    let mut i = 0;
+    while let Some(&first) = changed.iter().find(|&&c| c >= i) {
+        let start = first.saturating_sub(CONTEXT_LINES).max(i);
+        let mut end = first;
+        for &c in changed.iter().filter(|&&c| c > first) {
+            if c - end > 2 * CONTEXT_LINES {
+                break;
+            }
+            end = c;
+        }
+        let end = (end + CONTEXT_LINES + 1).min(script.len());
+        let hunk = &script[start..end];
+        let (old_start, new_start) = (script[start].1, script[start].2);
+        let old_count = hunk.iter().filter(|op| op.0 != DiffKind::Added).count();
+        let new_count = hunk.iter().filter(|op| op.0 != DiffKind::Removed).count();
+        lines.push(DiffLine::new(
+            DiffKind::Hunk,
+            &format!(
+                "@@ -{},{old_count} +{},{new_count} @@",
+                old_start + 1,
+                new_start + 1
+            ),
+        ));
+        lines.extend(
+            hunk.iter()
+                .map(|(kind, _, _, text)| DiffLine::new(*kind, text)),
+        );
+        i = end;
+    }
+    lines
+}
+
+/// Each line of the diff with its kind and the old and new line indexes
+/// it sits at.
+pub(crate) fn edit_script<'a>(
+    old: &[&'a str],
+    new: &[&'a str],
+) -> Vec<(DiffKind, usize, usize, &'a str)> {
+    let (n, m) = (old.len(), new.len());
+    let mut script = Vec::with_capacity(n + m);
+    if n * m > MAX_DIFF_CELLS {
+        script.extend(
+            old.iter()
+                .enumerate()
+                .map(|(i, l)| (DiffKind::Removed, i, 0, *l)),
+        );
+        script.extend(
+            new.iter()
+                .enumerate()
+                .map(|(j, l)| (DiffKind::Added, n, j, *l)),
+        );
+        return script;
+    }
+    // Longest common subsequence lengths of every pair of suffixes
+    let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[at(i, j)] = if old[i] == new[j] {
+                lcs[at(i + 1, j + 1)] + 1
+            } else {
+                lcs[at(i + 1, j)].max(lcs[at(i, j + 1)])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            script.push((DiffKind::Context, i, j, old[i]));
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[at(i, j + 1)] > lcs[at(i + 1, j)]) {
+            script.push((DiffKind::Added, i, j, new[j]));
+            j += 1;
+        } else {
+            script.push((DiffKind::Removed, i, j, old[i]));
+            i += 1;
+        }
+    }
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(lines: &[DiffLine]) -> Vec<String> {
+        lines.iter().map(DiffLine::prefixed).collect()
+    }
+
+    #[test]
+    fn test_unified_line_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\n";
+        assert_eq!(
+            render(&unified(old, new)),
+            vec![
+                "@@ -1,5 +1,5 @@",
+                " a",
+                "-b",
+                "+B",
+                " c",
+                " d",
+                " e",
+                "@@ -10,3 +10,4 @@",
+                " j",
+                " k",
+                " l",
+                "+m",
+            ]
+        );
+        assert!(unified("same\n", "same\n").is_empty());
+    }
+
+    #[test]
+    fn test_parse_unified() {
+        let text = "Index: src/lib.rs\n===\n--- src/lib.rs\n+++ src/lib.rs\n@@ -1,2 +1,2 @@\n fn a() {\n-    old()\n+    new()\n";
+        let lines = parse_unified(text);
+        assert_eq!(lines[0].kind, DiffKind::Hunk);
+        assert_eq!(
+            render(&lines[1..]),
+            vec![" fn a() {", "-    old()", "+    new()"]
+        );
+    }
+
+    #[test]
+    fn test_from_event() {
+        let mut event = ToolEvent {
+            call_id: None,
+            session_id: None,
+            message_id: None,
+            tool: "edit".into(),
+            input: serde_json::json!({ "oldString": "x = 1", "newString": "x = 2" }),
+            state: "completed".into(),
+            metadata: serde_json::Value::Null,
+        };
+        let lines = from_event(&event).unwrap();
+        assert_eq!(render(&lines[1..]), vec!["-x = 1", "+x = 2"]);
+
+        // OpenCode's own diff wins
+        event.metadata = serde_json::json!({ "diff": "@@ -1 +1 @@\n-a\n+b\n" });
+        assert_eq!(render(&from_event(&event).unwrap()[1..]), vec!["-a", "+b"]);
+
+        event.state = "running".into();
+        assert_eq!(from_event(&event), None);
+    }
+}
@@ -0,0 +1,447 @@
+// Export Module - Renders a session's conversation as Markdown or JSON notes
+//
+// `conch export` picks the session here too: the one named with `--session`,
+// or else the one working in the project directory.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+
+use crate::paths;
+use crate::transport::{self, MessagePart, SessionInfo, SessionMessage, ToolEvent};
+
+/// What `conch export [file] [--session <id>] [--dir <dir>]` was asked for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportArgs {
+    /// Where to write the export; named after the session when `None`.
+    pub path: Option<PathBuf>,
+    /// Session to export, rather than the project directory's.
+    pub session: Option<String>,
+    /// Project directory, rather than the working directory.
+    pub dir: Option<PathBuf>,
+}
+
+impl ExportArgs {
+    pub fn from_args(args: &[String]) -> Self {
+        let flag = |name: &str| {
+            args.windows(2)
+                .find_map(|w| (w[0] == name).then(|| w[1].clone()))
+        };
+        Self {
+            path: args
+                .first()
+                .filter(|a| !a.starts_with("--"))
+                .map(PathBuf::from),
+            session: flag("--session"),
+            dir: flag("--dir").map(PathBuf::from),
+        }
+    }
+
+    /// The session to export out of `sessions`. One named with `--session`
+    /// that isn't listed is still tried, as OpenCode may know it anyway.
+    pub fn pick_session(&self, sessions: &[SessionInfo]) -> Result<SessionInfo> {
+        if let Some(id) = &self.session {
+            let listed = sessions.iter().find(|s| &s.id == id).cloned();
+            return Ok(listed.unwrap_or_else(|| SessionInfo {
+                id: id.clone(),
+                slug: None,
+                directory: None,
+                title: None,
+            }));
+        }
+        let dir = paths::project_dir(self.dir.as_deref())?;
+        transport::directory_session(sessions, &dir)
+            .cloned()
+            .ok_or_else(|| {
+                anyhow!(
+                    "No OpenCode session for {}; pass --session <id>",
+                    paths::home_relative(&dir)
+                )
+            })
+    }
+
+    /// Where the export of `session` goes.
+    pub fn out_path(&self, session: &SessionInfo) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| {
+            PathBuf::from(format!(
+                "conch-{}.md",
+                session.slug.as_deref().unwrap_or(&session.id)
+            ))
+        })
+    }
+}
+
+/// Output format, picked from the file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+}
+
+impl ExportFormat {
+    /// `.json` exports JSON; anything else is Markdown.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => ExportFormat::Json,
+            _ => ExportFormat::Markdown,
+        }
+    }
+}
+
+/// One step of the exported conversation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ConversationItem {
+    /// Something the user sent, with conch's focus context split off.
+    Prompt {
+        created: Option<i64>,
+        context: Option<String>,
+        text: String,
+    },
+    /// Assistant text.
+    Reply { created: Option<i64>, text: String },
+    /// A tool call worth noting (edits, writes, commands).
+    Tool {
+        created: Option<i64>,
+        tool: String,
+        status: String,
+        summary: String,
+    },
+}
+
+/// Flatten session messages into an interleaved conversation.
+pub fn conversation(messages: &[SessionMessage]) -> Vec<ConversationItem> {
+    let mut items = Vec::new();
+    for message in messages {
+        let created = message.created;
+        for part in &message.parts {
+            match part {
+                MessagePart::Text { text } if message.role == "user" => {
+                    let (context, text) = split_context(text);
+                    items.push(ConversationItem::Prompt {
+                        created,
+                        context: context.map(str::to_string),
+                        text: text.to_string(),
+                    });
+                }
+                MessagePart::Text { text } => items.push(ConversationItem::Reply {
+                    created,
+                    text: text.clone(),
+                }),
+                MessagePart::Tool(event) => {
+                    if let Some(summary) = notable_tool(event) {
+                        items.push(ConversationItem::Tool {
+                            created,
+                            tool: event.tool.clone(),
+                            status: event.state.clone(),
+                            summary,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    items
+}
+
+/// Split a leading `[Context: ...]` line (added by conch from the focus
+/// stack) off a prompt.
+pub fn split_context(prompt: &str) -> (Option<&str>, &str) {
+    if let Some(rest) = prompt.strip_prefix("[Context: ")
+        && let Some((context, text)) = rest.split_once("]\n")
+    {
+        return (Some(context), text.trim());
+    }
+    (None, prompt.trim())
+}
+
+/// One-line summary of a tool call that changed something. Reads and
+/// searches are noise in notes, so they return `None`, as do calls that
+/// haven't finished.
+pub fn notable_tool(event: &ToolEvent) -> Option<String> {
+    if event.state != "completed" && event.state != "error" {
+        return None;
+    }
+    let input = |key: &str| event.input.get(key).and_then(|v| v.as_str());
+    let summary = match event.tool.as_str() {
+        "write" => format!("wrote `{}`", input("filePath")?),
+        "edit" => format!("edited `{}`", input("filePath")?),
+        "bash" => format!("ran `{}`", input("command")?),
+        "task" => format!("delegated: {}", input("description")?),
+        "webfetch" => format!("fetched {}", input("url")?),
+        _ => return None,
+    };
+    if event.state == "error" {
+        Some(format!("{} (failed)", summary))
+    } else {
+        Some(summary)
+    }
+}
+
+/// Heading for the export: session title, else slug, else ID.
+fn session_heading(session: &SessionInfo) -> &str {
+    session
+        .title
+        .as_deref()
+        .or(session.slug.as_deref())
+        .unwrap_or(&session.id)
+}
+
+/// Render the conversation as Markdown notes.
+pub fn to_markdown(session: &SessionInfo, messages: &[SessionMessage]) -> String {
+    let mut out = format!("# {}\n\n", session_heading(session));
+    out.push_str(&format!("_Session `{}`", session.id));
+    if let Some(dir) = &session.directory {
+        out.push_str(&format!(" in `{}`", dir));
+    }
+    out.push_str("_\n");
+
+    // Consecutive replies and tool calls share one "Assistant" section;
+    // consecutive tool calls form one list.
+    let mut in_reply = false;
+    let mut in_tool_list = false;
+    for item in conversation(messages) {
+        match item {
+            ConversationItem::Prompt { context, text, .. } => {
+                out.push_str("\n## You\n\n");
+                if let Some(context) = context {
+                    out.push_str(&format!("_{}_\n\n", context));
+                }
+                out.push_str(&text);
+                out.push('\n');
+                in_reply = false;
+                in_tool_list = false;
+            }
+            ConversationItem::Reply { text, .. } => {
+                if !in_reply {
+                    out.push_str("\n## Assistant\n");
+                }
+                out.push('\n');
+                out.push_str(&text);
+                out.push('\n');
+                in_reply = true;
+                in_tool_list = false;
+            }
+            ConversationItem::Tool { summary, .. } => {
+                if !in_reply {
+                    out.push_str("\n## Assistant\n");
+                }
+                if !in_tool_list {
+                    out.push('\n');
+                }
+                out.push_str(&format!("- {}\n", summary));
+                in_reply = true;
+                in_tool_list = true;
+            }
+        }
+    }
+    out
+}
+
+/// Render the conversation as pretty-printed JSON.
+pub fn to_json(session: &SessionInfo, messages: &[SessionMessage]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&serde_json::json!({
+        "session": session,
+        "conversation": conversation(messages),
+    }))?)
+}
+
+/// Write the conversation to `path`, in the format its extension asks for.
+pub fn write(path: &Path, session: &SessionInfo, messages: &[SessionMessage]) -> Result<()> {
+    let text = match ExportFormat::from_path(path) {
+        ExportFormat::Markdown => to_markdown(session, messages),
+        ExportFormat::Json => to_json(session, messages)?,
+    };
+    std::fs::write(path, text).map_err(|e| anyhow!("Failed to write '{}': {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> SessionInfo {
+        SessionInfo {
+            id: "ses_abc".into(),
+            slug: Some("brave-otter".into()),
+            directory: Some("/home/me/proj".into()),
+            title: None,
+        }
+    }
+
+    fn tool(tool: &str, state: &str, input: serde_json::Value) -> MessagePart {
+        MessagePart::Tool(ToolEvent {
+            call_id: None,
+            session_id: None,
+            message_id: None,
+            tool: tool.into(),
+            input,
+            state: state.into(),
+            metadata: serde_json::Value::Null,
+        })
+    }
+
+    fn messages() -> Vec<SessionMessage> {
+        vec![
+            SessionMessage {
+                id: "msg_1".into(),
+                role: "user".into(),
+                created: Some(1),
+                parts: vec![MessagePart::Text {
+                    text: "[Context: Currently focused on file src/main.rs]\nfix the warning".into(),
+                }],
+            },
+            SessionMessage {
+                id: "msg_2".into(),
+                role: "assistant".into(),
+                created: Some(2),
+                parts: vec![
+                    tool("read", "completed", serde_json::json!({"filePath": "src/main.rs"})),
+                    MessagePart::Text {
+                        text: "The import is unused.".into(),
+                    },
+                    tool("edit", "completed", serde_json::json!({"filePath": "src/main.rs"})),
+                    tool("bash", "error", serde_json::json!({"command": "cargo build"})),
+                    MessagePart::Text {
+                        text: "Removed it.".into(),
+                    },
+                ],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(ExportFormat::from_path(Path::new("notes.json")), ExportFormat::Json);
+        assert_eq!(ExportFormat::from_path(Path::new("notes.md")), ExportFormat::Markdown);
+        assert_eq!(ExportFormat::from_path(Path::new("notes")), ExportFormat::Markdown);
+    }
+
+    #[test]
+    fn test_split_context() {
+        let (ctx, text) = split_context("[Context: on branch main]\nship it");
+        assert_eq!(ctx, Some("on branch main"));
+        assert_eq!(text, "ship it");
+        assert_eq!(split_context("just text"), (None, "just text"));
+    }
+
+    #[test]
+    fn test_notable_tools() {
+        let event = |tool: &str, state: &str, input| ToolEvent {
+            call_id: None,
+            session_id: None,
+            message_id: None,
+            tool: tool.into(),
+            input,
+            state: state.into(),
+            metadata: serde_json::Value::Null,
+        };
+        let path = serde_json::json!({"filePath": "a.rs"});
+        assert_eq!(
+            notable_tool(&event("write", "completed", path.clone())),
+            Some("wrote `a.rs`".into())
+        );
+        assert_eq!(notable_tool(&event("read", "completed", path.clone())), None);
+        assert_eq!(notable_tool(&event("edit", "running", path)), None);
+    }
+
+    #[test]
+    fn test_conversation_interleaves() {
+        let items = conversation(&messages());
+        assert_eq!(items.len(), 5);
+        assert!(matches!(
+            &items[0],
+            ConversationItem::Prompt { context: Some(_), text, .. } if text == "fix the warning"
+        ));
+        assert!(matches!(&items[1], ConversationItem::Reply { .. }));
+        assert!(matches!(&items[3], ConversationItem::Tool { summary, .. } if summary.ends_with("(failed)")));
+    }
+
+    #[test]
+    fn test_markdown_export() {
+        let md = to_markdown(&session(), &messages());
+        let expected = "\
+# brave-otter
+
+_Session `ses_abc` in `/home/me/proj`_
+
+## You
+
+_Currently focused on file src/main.rs_
+
+fix the warning
+
+## Assistant
+
+The import is unused.
+
+- edited `src/main.rs`
+- ran `cargo build` (failed)
+
+Removed it.
+";
+        assert_eq!(md, expected);
+    }
+
+    #[test]
+    fn test_json_export() {
+        let json: serde_json::Value =
+            serde_json::from_str(&to_json(&session(), &messages()).unwrap()).unwrap();
+        assert_eq!(json["session"]["id"], "ses_abc");
+        let conv = json["conversation"].as_array().unwrap();
+        assert_eq!(conv[0]["kind"], "prompt");
+        assert_eq!(conv[0]["text"], "fix the warning");
+        assert_eq!(conv[2]["kind"], "tool");
+        assert_eq!(conv[2]["tool"], "edit");
+    }
+
+    #[test]
+    fn test_export_args() {
+        let args = |words: &[&str]| {
+            let words: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+            ExportArgs::from_args(&words)
+        };
+        assert_eq!(args(&[]), ExportArgs::default());
+        let named = args(&["notes.json", "--session", "ses_abc"]);
+        assert_eq!(named.path, Some(PathBuf::from("notes.json")));
+        assert_eq!(named.session.as_deref(), Some("ses_abc"));
+        assert_eq!(args(&["--dir", "/tmp"]).dir, Some(PathBuf::from("/tmp")));
+
+        // Named sessions come from the list, or are taken on trust
+        let picked = named.pick_session(&[session()]).unwrap();
+        assert_eq!(picked.slug.as_deref(), Some("brave-otter"));
+        let unlisted = named.pick_session(&[]).unwrap();
+        assert_eq!((unlisted.id.as_str(), unlisted.slug), ("ses_abc", None));
+        assert_eq!(named.out_path(&picked), PathBuf::from("notes.json"));
+        let default = args(&[]).out_path(&picked);
+        assert_eq!(default, PathBuf::from("conch-brave-otter.md"));
+    }
+
+    #[test]
+    fn test_session_of_project_dir() {
+        let dir = std::env::temp_dir().join(format!("conch_export_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+        let here = SessionInfo {
+            directory: Some(dir.display().to_string()),
+            ..session()
+        };
+        let args = ExportArgs {
+            dir: Some(dir.clone()),
+            ..ExportArgs::default()
+        };
+        assert_eq!(args.pick_session(&[here]).unwrap().id, "ses_abc");
+        let err = args.pick_session(&[session()]).unwrap_err();
+        assert!(err.to_string().ends_with("; pass --session <id>"), "{err}");
+
+        // Written in the format the extension asks for
+        let path = dir.join("notes.json");
+        write(&path, &session(), &messages()).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["session"]["id"], "ses_abc");
+        let err = write(&dir.join("missing/notes.md"), &session(), &messages()).unwrap_err();
+        assert!(err.to_string().starts_with("Failed to write"), "{err}");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
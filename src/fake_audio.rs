@@ -0,0 +1,181 @@
+// Fake Audio Module - A stand-in microphone for development and CI
+//
+// `--fake-audio <file.wav>` plays a WAV file into the capture buffers in
+// real time, as if it were being spoken into the mic: over and over, with a
+// second of silence in between. `--fake-audio tone` (or `tone:<hz>`) plays a
+// generated tone instead. Machines without a microphone can run the whole
+// record → transcribe → send flow this way.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+
+use crate::audio::{AudioSource, SampleSink, read_wav};
+
+/// How often samples are delivered, like a mic's callback period.
+pub const CHUNK: Duration = Duration::from_millis(20);
+/// Silence between repeats of the clip.
+pub const GAP: Duration = Duration::from_secs(1);
+/// Sample rate of generated tones.
+pub const TONE_SAMPLE_RATE: u32 = 16_000;
+
+/// Plays a clip on a loop into the capture buffers.
+pub struct FakeSource {
+    clip: Arc<[f32]>,
+    sample_rate: u32,
+    stop: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl FakeSource {
+    /// Play `clip` at `sample_rate`.
+    pub fn new(clip: Vec<f32>, sample_rate: u32) -> Self {
+        Self {
+            clip: clip.into(),
+            sample_rate,
+            stop: Arc::new(AtomicBool::new(false)),
+            worker: None,
+        }
+    }
+
+    /// Play a 16-bit or float WAV file.
+    pub fn from_wav(path: &Path) -> Result<Self> {
+        let (clip, sample_rate) = read_wav(path)?;
+        Ok(Self::new(clip, sample_rate))
+    }
+
+    /// Play a one-second sine tone at `hz`.
+    pub fn tone(hz: f32) -> Self {
+        let rate = TONE_SAMPLE_RATE as f32;
+        let clip = (0..TONE_SAMPLE_RATE)
+            .map(|i| 0.3 * (std::f32::consts::TAU * hz * i as f32 / rate).sin())
+            .collect();
+        Self::new(clip, TONE_SAMPLE_RATE)
+    }
+
+    /// The source a `--fake-audio` value names: `tone`, `tone:<hz>`, or a
+    /// WAV file.
+    pub fn parse(spec: &str) -> Result<Self> {
+        match spec.strip_prefix("tone") {
+            Some("") => Ok(Self::tone(440.0)),
+            Some(hz) => {
+                let hz = hz
+                    .strip_prefix(':')
+                    .and_then(|hz| hz.parse::<f32>().ok())
+                    .filter(|hz| *hz > 0.0)
+                    .ok_or_else(|| anyhow!("--fake-audio tone needs a frequency, e.g. tone:440"))?;
+                Ok(Self::tone(hz))
+            }
+            None => Self::from_wav(Path::new(spec)),
+        }
+    }
+
+    /// The clip being played.
+    pub fn clip(&self) -> &[f32] {
+        &self.clip
+    }
+}
+
+impl AudioSource for FakeSource {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn start(&mut self, sink: SampleSink) -> Result<()> {
+        let gap = (self.sample_rate as f32 * GAP.as_secs_f32()) as usize;
+        let mut samples = Looped::new(Arc::clone(&self.clip), gap);
+        let (stop, rate) = (Arc::clone(&self.stop), self.sample_rate as f64);
+        let worker = std::thread::Builder::new()
+            .name("fake-audio".into())
+            .spawn(move || {
+                let started = Instant::now();
+                let mut written = 0usize;
+                while !stop.load(Ordering::Relaxed) {
+                    std::thread::sleep(CHUNK);
+                    // Catch up to the clock so timing doesn't drift
+                    let due = (started.elapsed().as_secs_f64() * rate) as usize;
+                    sink.push(samples.by_ref().take(due - written));
+                    written = due;
+                }
+            })?;
+        self.worker = Some(worker);
+        Ok(())
+    }
+}
+
+impl Drop for FakeSource {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// A clip followed by `gap` samples of silence, forever.
+struct Looped {
+    clip: Arc<[f32]>,
+    gap: usize,
+    position: usize,
+}
+
+impl Looped {
+    fn new(clip: Arc<[f32]>, gap: usize) -> Self {
+        Self {
+            clip,
+            gap,
+            position: 0,
+        }
+    }
+}
+
+impl Iterator for Looped {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let period = self.clip.len() + self.gap;
+        if period == 0 {
+            return Some(0.0);
+        }
+        let sample = self.clip.get(self.position).copied().unwrap_or(0.0);
+        self.position = (self.position + 1) % period;
+        Some(sample)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::AudioCapture;
+
+    #[test]
+    fn test_parse_spec() {
+        let tone = FakeSource::parse("tone").unwrap();
+        assert_eq!(tone.sample_rate(), TONE_SAMPLE_RATE);
+        assert_eq!(tone.clip().len(), TONE_SAMPLE_RATE as usize);
+        assert!(FakeSource::parse("tone:1000").is_ok());
+        assert!(FakeSource::parse("tone:loud").is_err());
+        assert!(FakeSource::parse("/nonexistent/clip.wav").is_err());
+    }
+
+    #[test]
+    fn test_looped_clip_with_gap() {
+        let played: Vec<f32> = Looped::new(vec![1.0, 2.0].into(), 2).take(7).collect();
+        assert_eq!(played, vec![1.0, 2.0, 0.0, 0.0, 1.0, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn test_records_from_fake_source() {
+        let audio = AudioCapture::with_source(Box::new(FakeSource::tone(440.0))).unwrap();
+        assert_eq!(audio.sample_rate(), TONE_SAMPLE_RATE);
+        audio.start_recording();
+        std::thread::sleep(Duration::from_millis(200));
+        let samples = audio.stop_recording();
+        assert!(!samples.is_empty());
+        assert!(samples.iter().any(|s| s.abs() > 0.1));
+    }
+}
@@ -1,12 +1,27 @@
 // Focus Module - Maintains focus stack derived from OpenCode session log
-
+//
+// Entries can be pinned, keeping them in the prompt context however far
+// down the stack they sink, or forgotten, dropping them from the stack and
+// ignoring them when OpenCode touches them again. Pins and forgotten entries
+// are kept per session in a JSON file, so they outlive a restart. The pointer
+// and follow mode can also be moved by voice: "focus up", "focus on the
+// transport file", "follow mode off".
+
+use std::collections::BTreeMap;
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
 
+use crate::glyphs::Glyphs;
+use crate::send::normalize_words;
+use crate::tools;
 use crate::transport::ToolEvent;
 
 /// A typed focus entry derived from an OpenCode tool execution event.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
 pub enum FocusEntry {
     File(PathBuf),
     Directory(PathBuf),
@@ -16,19 +31,24 @@ pub enum FocusEntry {
 
 impl fmt::Display for FocusEntry {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.label(&Glyphs::FULL))
+    }
+}
+
+impl FocusEntry {
+    /// The entry as the focus stack shows it, with its icon from `glyphs`.
+    pub fn label(&self, glyphs: &Glyphs) -> String {
         match self {
             FocusEntry::File(p) => {
                 let name = p.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
-                write!(f, "\u{1F4C4} {}", name)
+                format!("{} {}", glyphs.file, name)
             }
-            FocusEntry::Directory(p) => write!(f, "\u{1F4C1} {}", p.display()),
-            FocusEntry::Branch(name) => write!(f, "\u{1F33F} {}", name),
-            FocusEntry::Commit(hash) => write!(f, "\u{1F4E6} {}", hash),
+            FocusEntry::Directory(p) => format!("{} {}", glyphs.directory, p.display()),
+            FocusEntry::Branch(name) => format!("{} {}", glyphs.branch, name),
+            FocusEntry::Commit(hash) => format!("{} {}", glyphs.commit, hash),
         }
     }
-}
 
-impl FocusEntry {
     /// Type name for context JSON.
     pub fn type_name(&self) -> &str {
         match self {
@@ -54,18 +74,48 @@ impl FocusEntry {
 pub struct FocusState {
     /// Entries ordered newest-first (index 0 = most recent).
     entries: Vec<FocusEntry>,
+    /// ID of the message each entry came from, parallel to `entries`.
+    origins: Vec<Option<String>>,
     /// Current pointer position into entries.
     pointer: usize,
     /// When true, pointer auto-advances to index 0 on new entries.
     follow_mode: bool,
+    /// Entries kept in the context wherever they are in the stack.
+    pinned: Vec<FocusEntry>,
+    /// Entries dropped from the stack and not added again.
+    forgotten: Vec<FocusEntry>,
+}
+
+/// The pinned and forgotten entries of one session, as stored.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusMarks {
+    pub pinned: Vec<FocusEntry>,
+    pub forgotten: Vec<FocusEntry>,
+}
+
+impl FocusMarks {
+    pub fn is_empty(&self) -> bool {
+        self.pinned.is_empty() && self.forgotten.is_empty()
+    }
+}
+
+impl Default for FocusState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl FocusState {
+    /// Create an empty focus history with follow mode on.
     pub fn new() -> Self {
         Self {
             entries: Vec::new(),
+            origins: Vec::new(),
             pointer: 0,
             follow_mode: true,
+            pinned: Vec::new(),
+            forgotten: Vec::new(),
         }
     }
 
@@ -73,7 +123,18 @@ impl FocusState {
     /// In follow mode, pointer stays at 0. Otherwise, pointer increments
     /// to continue pointing at the same entry.
     pub fn append(&mut self, entry: FocusEntry) {
+        self.append_from(entry, None);
+    }
+
+    /// Prepend an entry that came from message `message_id`, so it can be
+    /// dropped again if the message is removed.
+    /// Forgotten entries are ignored.
+    pub fn append_from(&mut self, entry: FocusEntry, message_id: Option<String>) {
+        if self.forgotten.contains(&entry) {
+            return;
+        }
         self.entries.insert(0, entry);
+        self.origins.insert(0, message_id);
         if !self.follow_mode {
             // Keep pointer on the same entry it was on before
             self.pointer += 1;
@@ -81,6 +142,110 @@ impl FocusState {
         // In follow mode, pointer stays at 0 (newest)
     }
 
+    /// Drop the entries that came from message `message_id` (undone or
+    /// reverted in OpenCode). The pointer stays on the entry it was on, or
+    /// moves to the next older one if that entry was dropped. Returns the
+    /// number of entries dropped.
+    pub fn remove_message(&mut self, message_id: &str) -> usize {
+        self.remove_where(|_, origin| origin == Some(message_id))
+    }
+
+    /// Drop the entries `remove` picks, given each entry and the message it
+    /// came from, keeping the pointer on its entry or the next older one.
+    fn remove_where(&mut self, remove: impl Fn(&FocusEntry, Option<&str>) -> bool) -> usize {
+        let before = self.entries.len();
+        let mut index = 0;
+        while index < self.entries.len() {
+            if remove(&self.entries[index], self.origins[index].as_deref()) {
+                self.entries.remove(index);
+                self.origins.remove(index);
+                if index < self.pointer {
+                    self.pointer -= 1;
+                }
+            } else {
+                index += 1;
+            }
+        }
+        self.pointer = self.pointer.min(self.entries.len().saturating_sub(1));
+        before - self.entries.len()
+    }
+
+    /// Pin the entry under the pointer, or unpin it if it is pinned.
+    /// Returns whether it is pinned now, or None with no entry.
+    pub fn toggle_pin(&mut self) -> Option<bool> {
+        let entry = self.current_entry()?.clone();
+        if let Some(index) = self.pinned.iter().position(|p| *p == entry) {
+            self.pinned.remove(index);
+            Some(false)
+        } else {
+            self.pinned.push(entry);
+            Some(true)
+        }
+    }
+
+    /// Index of the newest entry named `name`, already normalized, and of
+    /// type `kind` if given. A file is named by its path, its file name or
+    /// its stem, so "transport" finds `src/transport.rs`.
+    pub fn find(&self, name: &str, kind: Option<&str>) -> Option<usize> {
+        self.entries.iter().position(|entry| {
+            if kind.is_some_and(|k| k != entry.type_name()) {
+                return false;
+            }
+            let names = match entry {
+                FocusEntry::File(p) | FocusEntry::Directory(p) => vec![
+                    p.to_string_lossy().to_string(),
+                    p.file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    p.file_stem()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                ],
+                FocusEntry::Branch(s) | FocusEntry::Commit(s) => vec![s.clone()],
+            };
+            names.iter().any(|n| normalize_words(n) == name)
+        })
+    }
+
+    /// Whether `entry` is pinned.
+    pub fn is_pinned(&self, entry: &FocusEntry) -> bool {
+        self.pinned.contains(entry)
+    }
+
+    /// Drop the entry under the pointer from the stack, every time it
+    /// appears, unpin it and ignore it from now on. Returns the entry.
+    pub fn forget(&mut self) -> Option<FocusEntry> {
+        let entry = self.current_entry()?.clone();
+        self.remove_where(|e, _| *e == entry);
+        self.pinned.retain(|p| *p != entry);
+        self.forgotten.push(entry.clone());
+        Some(entry)
+    }
+
+    /// The pinned and forgotten entries, to be stored.
+    pub fn marks(&self) -> FocusMarks {
+        FocusMarks {
+            pinned: self.pinned.clone(),
+            forgotten: self.forgotten.clone(),
+        }
+    }
+
+    /// Take on stored `marks`: forgotten entries leave the stack, and pinned
+    /// ones not in it are added at the oldest end, where they can be
+    /// unpinned.
+    pub fn set_marks(&mut self, marks: FocusMarks) {
+        let forgotten = marks.forgotten;
+        self.remove_where(|e, _| forgotten.contains(e));
+        for entry in &marks.pinned {
+            if !self.entries.contains(entry) {
+                self.entries.push(entry.clone());
+                self.origins.push(None);
+            }
+        }
+        self.pinned = marks.pinned;
+        self.forgotten = forgotten;
+    }
+
     /// Move pointer toward newer entries (lower index).
     pub fn move_up(&mut self) {
         if self.pointer > 0 {
@@ -97,6 +262,44 @@ impl FocusState {
         }
     }
 
+    /// Move the pointer to entry `index`, as if browsing to it.
+    pub fn move_to(&mut self, index: usize) {
+        if index < self.entries.len() {
+            self.pointer = index;
+            self.follow_mode = false;
+        }
+    }
+
+    /// Carry out a spoken focus command, like the arrow keys and **f**
+    /// would. `None`, leaving the transcript to be a prompt, for "focus on
+    /// ..." naming nothing in the stack without saying what kind of entry it
+    /// is, as in "focus on the failing tests".
+    pub fn apply_voice(&mut self, command: VoiceCommand) -> Option<VoiceOutcome> {
+        match command {
+            VoiceCommand::Up => self.move_up(),
+            VoiceCommand::Down => self.move_down(),
+            VoiceCommand::Follow(on) => {
+                self.set_follow_mode(on);
+                return Some(VoiceOutcome::Follow(on));
+            }
+            VoiceCommand::On { name, kind } => match self.find(&name, kind) {
+                Some(index) => self.move_to(index),
+                None if kind.is_some() => return Some(VoiceOutcome::NotFound(name)),
+                None => return None,
+            },
+        }
+        Some(VoiceOutcome::Focused(self.current_entry().cloned()))
+    }
+
+    /// Turn follow mode on or off. Turning it on snaps the pointer to the
+    /// newest entry.
+    pub fn set_follow_mode(&mut self, on: bool) {
+        if on != self.follow_mode {
+            self.toggle_follow_mode();
+        }
+    }
+
+    /// Toggle follow mode. Turning it on snaps the pointer to the newest entry.
     pub fn toggle_follow_mode(&mut self) {
         self.follow_mode = !self.follow_mode;
         if self.follow_mode {
@@ -104,26 +307,36 @@ impl FocusState {
         }
     }
 
+    /// The entry under the pointer, if any.
     pub fn current_entry(&self) -> Option<&FocusEntry> {
         self.entries.get(self.pointer)
     }
 
+    /// All entries, newest first.
     pub fn entries(&self) -> &[FocusEntry] {
         &self.entries
     }
 
+    /// Index of the entry under the pointer.
     pub fn pointer(&self) -> usize {
         self.pointer
     }
 
+    /// Whether the pointer tracks the newest entry.
     pub fn follow_mode(&self) -> bool {
         self.follow_mode
     }
 
+    /// Number of entries in the history.
     pub fn len(&self) -> usize {
         self.entries.len()
     }
 
+    /// Whether the history has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
     /// Find the most recent entry of each type.
     /// Returns (File, Directory, Branch, Commit) — each Option.
     pub fn recent_by_type(
@@ -188,12 +401,24 @@ impl FocusState {
 
     /// Generate a human-readable context string for prompt prepending.
     pub fn to_context_string(&self) -> Option<String> {
+        context_line(&self.context_parts())
+    }
+
+    /// The parts of the context string, most important first: the current
+    /// focus, when there is one, then the directory, file and branch.
+    pub fn context_parts(&self) -> Vec<String> {
         let (file, dir, branch, _commit) = self.recent_by_type();
         let mut parts = Vec::new();
 
         if let Some(entry) = self.current_entry() {
             parts.push(format!("Currently focused on {} {}", entry.type_name(), entry.value_str()));
         }
+        for entry in &self.pinned {
+            if Some(entry) != self.current_entry() {
+                let (kind, value) = (entry.type_name(), entry.value_str());
+                parts.push(format!("pinned {kind} {value}"));
+            }
+        }
         if let Some(FocusEntry::Directory(p)) = dir {
             let dir_str = p.to_string_lossy();
             // Only add if not already the current focus
@@ -210,12 +435,7 @@ impl FocusState {
         if let Some(FocusEntry::Branch(b)) = branch {
             parts.push(format!("on branch {}", b));
         }
-
-        if parts.is_empty() {
-            None
-        } else {
-            Some(format!("[Context: {}]", parts.join(", ")))
-        }
+        parts
     }
 
     /// Rebuild focus history from a sequence of tool events (chronological order).
@@ -230,34 +450,150 @@ impl FocusState {
     }
 }
 
-/// Map a tool event to a focus entry. Returns None for irrelevant events.
-/// Only maps events with state == "completed".
-pub fn map_tool_event(event: &ToolEvent) -> Option<FocusEntry> {
-    if event.state != "completed" {
-        return None;
+/// Pinned and forgotten entries of every session, in one JSON file keyed
+/// by session ID.
+pub struct FocusStore {
+    path: PathBuf,
+}
+
+impl FocusStore {
+    /// Use the store at `path`. The file is created on first write.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The file backing this store.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The marks of every session.
+    fn load_all(&self) -> Result<BTreeMap<String, FocusMarks>> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|e| anyhow!("Invalid focus marks '{}': {}", self.path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(anyhow!(
+                "Failed to read focus marks '{}': {}",
+                self.path.display(),
+                e
+            )),
+        }
+    }
+
+    /// The marks of session `session_id`, empty if it has none.
+    pub fn load(&self, session_id: &str) -> Result<FocusMarks> {
+        Ok(self.load_all()?.remove(session_id).unwrap_or_default())
+    }
+
+    /// Replace the marks of session `session_id`.
+    pub fn save(&self, session_id: &str, marks: &FocusMarks) -> Result<()> {
+        let mut all = self.load_all()?;
+        if marks.is_empty() {
+            all.remove(session_id);
+        } else {
+            all.insert(session_id.to_string(), marks.clone());
+        }
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let path = self.path.display();
+        std::fs::write(&self.path, serde_json::to_string_pretty(&all)?)
+            .map_err(|e| anyhow!("Failed to write focus marks '{path}': {e}"))
     }
+}
+
+/// A focus stack command spoken instead of a prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VoiceCommand {
+    /// "focus up": the next newer entry.
+    Up,
+    /// "focus down": the next older entry.
+    Down,
+    /// "follow mode on", "follow mode off".
+    Follow(bool),
+    /// "focus on the transport file": the newest entry named `name`, of
+    /// type `kind` if one was said.
+    On {
+        name: String,
+        kind: Option<&'static str>,
+    },
+}
 
-    match event.tool.as_str() {
-        "read" | "write" | "edit" => {
-            // All file tools use "filePath" in OpenCode's SDK
-            let path = event.input.get("filePath")?.as_str()?;
-            Some(FocusEntry::File(PathBuf::from(path)))
+/// What a spoken focus command did, to tell the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VoiceOutcome {
+    /// The entry under the pointer now, `None` with the stack empty.
+    Focused(Option<FocusEntry>),
+    /// Follow mode is on, or off.
+    Follow(bool),
+    /// Nothing of the kind said is called `name`.
+    NotFound(String),
+}
+
+/// Spoken entry types, after the name: "focus on the src folder".
+const KINDS: &[(&str, &str)] = &[
+    ("file", "file"),
+    ("directory", "directory"),
+    ("folder", "directory"),
+    ("branch", "branch"),
+    ("commit", "commit"),
+];
+
+/// The focus command `transcript` is, if any.
+pub fn voice_command(transcript: &str) -> Option<VoiceCommand> {
+    let words = normalize_words(transcript);
+    match words.as_str() {
+        "focus up" | "focus newer" | "move focus up" | "move the focus up" => {
+            return Some(VoiceCommand::Up);
+        }
+        "focus down" | "focus older" | "focus back" | "move focus down" | "move the focus down" => {
+            return Some(VoiceCommand::Down);
         }
-        "bash" => {
-            let command = event.input.get("command")?.as_str()?;
-            parse_bash_command(command)
+        "follow mode on" | "follow on" | "turn follow mode on" | "turn on follow mode" => {
+            return Some(VoiceCommand::Follow(true));
         }
-        "list" => {
-            let path = event.input.get("path")?.as_str()?;
-            Some(FocusEntry::Directory(PathBuf::from(path)))
+        "follow mode off" | "follow off" | "turn follow mode off" | "turn off follow mode" => {
+            return Some(VoiceCommand::Follow(false));
         }
-        "grep" => None,
-        _ => None,
+        _ => {}
+    }
+    let rest = words.strip_prefix("focus on ")?;
+    let rest = rest.strip_prefix("the ").unwrap_or(rest);
+    let (name, kind) = KINDS
+        .iter()
+        .find_map(|(word, kind)| {
+            let name = rest.strip_suffix(word)?.strip_suffix(' ')?;
+            Some((name, Some(*kind)))
+        })
+        .unwrap_or((rest, None));
+    // "main dot rs" is main.rs, normalized "main rs"
+    let name = name
+        .split(' ')
+        .filter(|w| *w != "dot")
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!name.is_empty()).then_some(VoiceCommand::On { name, kind })
+}
+
+/// The context line for `parts` from [`FocusState::context_parts`].
+pub fn context_line(parts: &[String]) -> Option<String> {
+    if parts.is_empty() {
+        None
+    } else {
+        Some(format!("[Context: {}]", parts.join(", ")))
     }
 }
 
+/// Map a tool event to a focus entry with the built-in rules only, see
+/// [`tools::map_focus`]. Returns None for irrelevant events.
+/// Only maps events with state == "completed".
+pub fn map_tool_event(event: &ToolEvent) -> Option<FocusEntry> {
+    tools::map_focus(&[], event)
+}
+
 /// Parse a bash command string into a focus entry, if relevant.
-fn parse_bash_command(command: &str) -> Option<FocusEntry> {
+pub(crate) fn parse_bash_command(command: &str) -> Option<FocusEntry> {
     let trimmed = command.trim();
 
     // cd <path>
@@ -341,6 +677,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_focus_entry_label() {
+        let entry = FocusEntry::File(PathBuf::from("src/main.rs"));
+        assert_eq!(entry.to_string(), "\u{1F4C4} main.rs");
+        assert_eq!(entry.label(&Glyphs::ASCII), "[F] main.rs");
+        let dir = FocusEntry::Directory(PathBuf::from("src"));
+        assert_eq!(dir.label(&Glyphs::BASIC), "[D] src");
+    }
+
     #[test]
     fn test_focus_entry_equality() {
         let a = FocusEntry::File(PathBuf::from("src/main.rs"));
@@ -543,13 +888,193 @@ mod tests {
         assert!(!state.follow_mode());
     }
 
+    #[test]
+    fn test_remove_message_entries() {
+        let a = FocusEntry::File(PathBuf::from("a.rs"));
+        let main = FocusEntry::Branch("main".into());
+        let mut state = FocusState::new();
+        state.append_from(a.clone(), Some("m1".into()));
+        state.append(main.clone());
+        state.append_from(FocusEntry::File(PathBuf::from("b.rs")), Some("m2".into()));
+        state.append_from(FocusEntry::File(PathBuf::from("c.rs")), Some("m2".into()));
+        state.move_down();
+        state.move_down();
+        assert_eq!(state.current_entry(), Some(&main));
+
+        assert_eq!(state.remove_message("m2"), 2);
+        assert_eq!(state.len(), 2);
+        assert_eq!(state.current_entry(), Some(&main));
+        assert_eq!(state.remove_message("m3"), 0);
+
+        // The entry under the pointer goes, and it was the oldest
+        state.move_down();
+        assert_eq!(state.current_entry(), Some(&a));
+        assert_eq!(state.remove_message("m1"), 1);
+        assert_eq!(state.current_entry(), Some(&main));
+    }
+
+    #[test]
+    fn test_pin_and_forget() {
+        let a = FocusEntry::File(PathBuf::from("src/a.rs"));
+        let b = FocusEntry::File(PathBuf::from("src/b.rs"));
+        let mut state = FocusState::new();
+        assert_eq!(state.toggle_pin(), None);
+        state.append(a.clone());
+        assert_eq!(state.toggle_pin(), Some(true));
+        state.append(b.clone());
+        state.append(a.clone());
+        state.append(FocusEntry::Branch("main".into()));
+        // The pinned file stays in the context under newer entries
+        let parts = state.context_parts();
+        assert!(parts.contains(&"pinned file src/a.rs".to_string()));
+
+        state.move_down();
+        assert_eq!(state.current_entry(), Some(&a));
+        assert_eq!(state.forget(), Some(a.clone()));
+        assert_eq!(state.len(), 2);
+        assert_eq!(state.current_entry(), Some(&b));
+        assert!(!state.is_pinned(&a));
+        state.append(a.clone());
+        assert_eq!(state.len(), 2);
+        let context = state.to_context_string().unwrap();
+        assert!(!context.contains("a.rs"), "{context}");
+        assert_eq!(state.toggle_pin(), Some(true));
+        assert_eq!(state.toggle_pin(), Some(false));
+    }
+
+    #[test]
+    fn test_marks_restored() {
+        let a = FocusEntry::File(PathBuf::from("src/a.rs"));
+        let b = FocusEntry::File(PathBuf::from("src/b.rs"));
+        let marks = FocusMarks {
+            pinned: vec![b.clone()],
+            forgotten: vec![a.clone()],
+        };
+        let mut state = FocusState::new();
+        state.append(a.clone());
+        state.append(FocusEntry::Branch("main".into()));
+        state.set_marks(marks.clone());
+        assert_eq!(
+            state.entries(),
+            &[FocusEntry::Branch("main".into()), b.clone()]
+        );
+        assert!(state.is_pinned(&b));
+        assert_eq!(state.marks(), marks);
+
+        let dir = std::env::temp_dir().join(format!("conch_focus_{}", std::process::id()));
+        let store = FocusStore::new(dir.join("focus.json"));
+        assert_eq!(store.load("ses_a").unwrap(), FocusMarks::default());
+        store.save("ses_a", &marks).unwrap();
+        store.save("ses_b", &marks).unwrap();
+        store.save("ses_b", &FocusMarks::default()).unwrap();
+        let text = std::fs::read_to_string(store.path()).unwrap();
+        assert_eq!(store.load("ses_a").unwrap(), marks);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(text.contains(r#""type": "file""#), "{text}");
+        assert!(!text.contains("ses_b"));
+    }
+
+    #[test]
+    fn test_voice_commands() {
+        let on = |name: &str, kind| {
+            Some(VoiceCommand::On {
+                name: name.into(),
+                kind,
+            })
+        };
+        assert_eq!(voice_command("Focus up."), Some(VoiceCommand::Up));
+        assert_eq!(
+            voice_command("move the focus down"),
+            Some(VoiceCommand::Down)
+        );
+        assert_eq!(
+            voice_command("Follow mode off."),
+            Some(VoiceCommand::Follow(false))
+        );
+        assert_eq!(
+            voice_command("turn on follow mode"),
+            Some(VoiceCommand::Follow(true))
+        );
+        assert_eq!(
+            voice_command("Focus on the transport file."),
+            on("transport", Some("file"))
+        );
+        assert_eq!(voice_command("focus on main dot rs"), on("main rs", None));
+        assert_eq!(
+            voice_command("focus on the src folder"),
+            on("src", Some("directory"))
+        );
+        assert_eq!(voice_command("Focus on."), None);
+        assert_eq!(voice_command("focus up the parser"), None);
+        assert_eq!(voice_command("open the transport file"), None);
+    }
+
+    #[test]
+    fn test_find_by_spoken_name() {
+        let mut state = FocusState::new();
+        state.append(FocusEntry::File(PathBuf::from("src/transport.rs")));
+        state.append(FocusEntry::Directory(PathBuf::from("src")));
+        state.append(FocusEntry::File(PathBuf::from("src/main.rs")));
+        state.append(FocusEntry::Branch("fix/reconnect".into()));
+        assert_eq!(state.find("transport", None), Some(3));
+        assert_eq!(state.find("main rs", Some("file")), Some(1));
+        assert_eq!(state.find("src", None), Some(2));
+        assert_eq!(state.find("src", Some("file")), None);
+        assert_eq!(state.find("fix reconnect", Some("branch")), Some(0));
+        assert_eq!(state.find("lib", None), None);
+
+        state.move_to(3);
+        assert!(!state.follow_mode());
+        assert_eq!(state.pointer(), 3);
+        state.set_follow_mode(true);
+        assert_eq!(state.pointer(), 0);
+        state.set_follow_mode(true);
+        assert!(state.follow_mode());
+        state.set_follow_mode(false);
+        assert!(!state.follow_mode());
+    }
+
+    #[test]
+    fn test_apply_voice() {
+        let mut state = FocusState::new();
+        assert_eq!(
+            state.apply_voice(VoiceCommand::Down),
+            Some(VoiceOutcome::Focused(None))
+        );
+        let transport = FocusEntry::File(PathBuf::from("src/transport.rs"));
+        let main = FocusEntry::File(PathBuf::from("src/main.rs"));
+        state.append(transport.clone());
+        state.append(main.clone());
+        let focused = |entry: &FocusEntry| Some(VoiceOutcome::Focused(Some(entry.clone())));
+        assert_eq!(state.apply_voice(VoiceCommand::Down), focused(&transport));
+        assert_eq!(state.apply_voice(VoiceCommand::Up), focused(&main));
+        let on = |name: &str, kind| VoiceCommand::On {
+            name: name.into(),
+            kind,
+        };
+        let file = Some("file");
+        let found = state.apply_voice(on("transport", file));
+        assert_eq!(found, focused(&transport));
+        let missing = state.apply_voice(on("parser", file));
+        assert_eq!(missing, Some(VoiceOutcome::NotFound("parser".into())));
+        assert_eq!(state.apply_voice(on("failing tests", None)), None);
+        assert_eq!(state.current_entry(), Some(&transport));
+        let follow = state.apply_voice(VoiceCommand::Follow(true));
+        assert_eq!(follow, Some(VoiceOutcome::Follow(true)));
+        assert_eq!(state.current_entry(), Some(&main));
+    }
+
     // ===== Event-to-Focus Mapping Tests =====
 
     fn make_tool_event(tool: &str, input: serde_json::Value, state: &str) -> ToolEvent {
         ToolEvent {
+            call_id: None,
+            session_id: None,
+            message_id: None,
             tool: tool.to_string(),
             input,
             state: state.to_string(),
+            metadata: serde_json::Value::Null,
         }
     }
 
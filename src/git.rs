@@ -0,0 +1,167 @@
+// Git Module - Branch and working tree status of the session directory
+//
+// Branch context matters for prompts like "commit this", so the title bar
+// shows the current branch, how many files are changed, and how far the
+// branch is ahead of or behind its upstream. The status comes from
+// `git status --porcelain=v2 --branch`, re-read every few seconds.
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+
+/// `[git]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct GitConfig {
+    /// Show the git status in the title bar.
+    pub enabled: bool,
+    /// Seconds between refreshes.
+    pub refresh_secs: u64,
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            refresh_secs: 5,
+        }
+    }
+}
+
+/// Status of a git working tree.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitStatus {
+    /// Checked-out branch; `None` when detached.
+    pub branch: Option<String>,
+    /// Abbreviated commit checked out; `None` before the first commit.
+    pub commit: Option<String>,
+    /// Changed, conflicted and untracked files.
+    pub changed: usize,
+    /// Commits ahead of and behind the upstream, when there is one.
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+impl GitStatus {
+    /// Status of the working tree at `dir`, or `None` if it isn't one.
+    /// Fails only if git can't be run.
+    pub fn read(dir: &Path) -> Result<Option<Self>> {
+        let output = Command::new("git")
+            // Don't take the index lock away from OpenCode's own git commands
+            .arg("--no-optional-locks")
+            .arg("-C")
+            .arg(dir)
+            .args(["status", "--porcelain=v2", "--branch"])
+            .output()
+            .map_err(|e| anyhow!("Failed to run git: {e}"))?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        Ok(Some(Self::parse(&String::from_utf8_lossy(&output.stdout))))
+    }
+
+    /// Parse `git status --porcelain=v2 --branch` output.
+    pub fn parse(text: &str) -> Self {
+        let mut status = Self::default();
+        for line in text.lines() {
+            if let Some(header) = line.strip_prefix("# branch.") {
+                let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+                match key {
+                    "oid" if value != "(initial)" => {
+                        status.commit = Some(value.chars().take(7).collect());
+                    }
+                    "head" if value != "(detached)" => status.branch = Some(value.to_string()),
+                    "ab" => {
+                        for count in value.split_whitespace() {
+                            if let Some(n) = count.strip_prefix('+') {
+                                status.ahead = n.parse().unwrap_or(0);
+                            } else if let Some(n) = count.strip_prefix('-') {
+                                status.behind = n.parse().unwrap_or(0);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            } else if ["1 ", "2 ", "u ", "? "].iter().any(|p| line.starts_with(p)) {
+                status.changed += 1;
+            }
+        }
+        status
+    }
+
+    /// Whether nothing is changed and the branch is in sync.
+    pub fn is_clean(&self) -> bool {
+        self.changed == 0 && self.ahead == 0 && self.behind == 0
+    }
+
+    /// One-line summary, e.g. "main · 3 changed · 1 ahead, 2 behind", with
+    /// `separator` between the parts.
+    pub fn summary(&self, separator: &str) -> String {
+        let mut parts = vec![match (&self.branch, &self.commit) {
+            (Some(branch), _) => branch.clone(),
+            (None, Some(commit)) => format!("detached at {commit}"),
+            (None, None) => "detached".to_string(),
+        }];
+        if self.changed > 0 {
+            parts.push(format!("{} changed", self.changed));
+        }
+        let sync: Vec<String> = [(self.ahead, "ahead"), (self.behind, "behind")]
+            .into_iter()
+            .filter(|(n, _)| *n > 0)
+            .map(|(n, word)| format!("{n} {word}"))
+            .collect();
+        if !sync.is_empty() {
+            parts.push(sync.join(", "));
+        }
+        parts.join(&format!(" {separator} "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_porcelain() {
+        let text = "\
+# branch.oid 4f2a9c1e0b7d3a5f6e8c9d0a1b2c3d4e5f6a7b8c
+# branch.head feature/voice
+# branch.upstream origin/feature/voice
+# branch.ab +1 -2
+1 .M N... 100644 100644 100644 aaa bbb src/main.rs
+2 R. N... 100644 100644 100644 aaa bbb R100 new.rs\told.rs
+? notes.txt
+! target/
+";
+        let status = GitStatus::parse(text);
+        assert_eq!(status.branch.as_deref(), Some("feature/voice"));
+        assert_eq!(status.commit.as_deref(), Some("4f2a9c1"));
+        assert_eq!((status.changed, status.ahead, status.behind), (3, 1, 2));
+        assert_eq!(
+            status.summary("\u{b7}"),
+            "feature/voice \u{b7} 3 changed \u{b7} 1 ahead, 2 behind"
+        );
+    }
+
+    #[test]
+    fn test_detached_and_clean() {
+        let status = GitStatus::parse(
+            "# branch.oid 4f2a9c1e0b7d3a5f6e8c9d0a1b2c3d4e5f6a7b8c\n# branch.head (detached)\n",
+        );
+        assert!(status.is_clean());
+        assert_eq!(status.summary("|"), "detached at 4f2a9c1");
+    }
+
+    #[test]
+    fn test_not_a_repository() {
+        let dir = std::env::temp_dir().join(format!("conch_git_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // Without git installed there is nothing to check
+        if let Ok(status) = GitStatus::read(&dir) {
+            assert_eq!(status, None);
+        }
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,213 @@
+// Glyphs Module - Icons and markers for fonts without emoji or braille
+//
+// Focus entries are marked with emoji and the waveform is drawn in braille,
+// and both turn into boxes in many fonts and terminals (and over SSH to older
+// systems). `[display] unicode` picks what the TUI draws with: "full" (emoji
+// and braille), "basic" (symbols nearly every font has, and block bars), or
+// "ascii" (plain-text markers and ASCII bars).
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// How long each frame of the busy spinner shows, the pacer's idle tick.
+pub const SPINNER_STEP: Duration = Duration::from_millis(250);
+
+/// Which characters the TUI may draw with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Charset {
+    /// Emoji, braille and symbols.
+    #[default]
+    Full,
+    /// Common symbols and block elements only.
+    Basic,
+    /// Printable ASCII only.
+    Ascii,
+}
+
+impl Charset {
+    /// The markers drawn in this charset.
+    pub fn glyphs(self) -> Glyphs {
+        match self {
+            Charset::Full => Glyphs::FULL,
+            Charset::Basic => Glyphs::BASIC,
+            Charset::Ascii => Glyphs::ASCII,
+        }
+    }
+}
+
+/// `[display]` section of the config file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DisplayConfig {
+    /// Characters the TUI draws with: "full", "basic" or "ascii".
+    pub unicode: Charset,
+}
+
+/// Every icon and marker the TUI draws, in one charset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glyphs {
+    pub charset: Charset,
+    /// Focus entry icons.
+    pub file: &'static str,
+    pub directory: &'static str,
+    pub branch: &'static str,
+    pub commit: &'static str,
+    /// The selected focus entry or palette item.
+    pub pointer: &'static str,
+    /// A pinned focus entry.
+    pub pin: &'static str,
+    /// The pending prompt.
+    pub pending: &'static str,
+    /// Prompts held for review, and other warnings.
+    pub warning: &'static str,
+    /// Typing a prompt, spelling.
+    pub typing: &'static str,
+    /// Recording, and the hot mic.
+    pub recording: &'static str,
+    pub transcribing: &'static str,
+    /// Reading a reply aloud.
+    pub speaking: &'static str,
+    /// Events conch handled, or ignored.
+    pub handled: &'static str,
+    pub unhandled: &'static str,
+    /// Sets off a clause in status text.
+    pub dash: &'static str,
+    /// Separates items in a title.
+    pub separator: &'static str,
+    /// Key hints for the arrow keys.
+    pub up_down: &'static str,
+    pub left_right: &'static str,
+    /// Frames of the spinner shown while OpenCode is busy.
+    pub spinner: &'static [&'static str],
+}
+
+impl Glyphs {
+    pub const FULL: Glyphs = Glyphs {
+        charset: Charset::Full,
+        file: "\u{1F4C4}",
+        directory: "\u{1F4C1}",
+        branch: "\u{1F33F}",
+        commit: "\u{1F4E6}",
+        pointer: "\u{25B8}",
+        pin: "\u{1F4CC}",
+        pending: "\u{25B6}",
+        warning: "\u{26A0}",
+        typing: "\u{270E}",
+        recording: "\u{25CF}",
+        transcribing: "\u{23F3}",
+        speaking: "\u{1F50A}",
+        handled: "\u{2713}",
+        unhandled: "\u{00B7}",
+        dash: "\u{2014}",
+        separator: "\u{00B7}",
+        up_down: "\u{2191}\u{2193}",
+        left_right: "\u{2190}\u{2192}",
+        spinner: &[
+            "\u{280B}", "\u{2819}", "\u{2839}", "\u{2838}", "\u{283C}", "\u{2834}", "\u{2826}",
+            "\u{2827}", "\u{2807}", "\u{280F}",
+        ],
+    };
+
+    pub const BASIC: Glyphs = Glyphs {
+        charset: Charset::Basic,
+        file: "[F]",
+        directory: "[D]",
+        branch: "[B]",
+        commit: "[C]",
+        pointer: "\u{25BA}",
+        pin: "\u{2020}",
+        pending: "\u{25BA}",
+        warning: "!",
+        typing: "\u{00BB}",
+        recording: "\u{25CF}",
+        transcribing: "\u{25CB}",
+        speaking: "\u{266A}",
+        handled: "\u{221A}",
+        unhandled: "\u{00B7}",
+        dash: "\u{2014}",
+        separator: "\u{00B7}",
+        up_down: "\u{2191}\u{2193}",
+        left_right: "\u{2190}\u{2192}",
+        spinner: &["\u{25D0}", "\u{25D3}", "\u{25D1}", "\u{25D2}"],
+    };
+
+    pub const ASCII: Glyphs = Glyphs {
+        charset: Charset::Ascii,
+        file: "[F]",
+        directory: "[D]",
+        branch: "[B]",
+        commit: "[C]",
+        pointer: ">",
+        pin: "*",
+        pending: ">",
+        warning: "!",
+        typing: ">",
+        recording: "*",
+        transcribing: "~",
+        speaking: ")))",
+        handled: "+",
+        unhandled: ".",
+        dash: "-",
+        separator: "|",
+        up_down: "^v",
+        left_right: "<>",
+        spinner: &["|", "/", "-", "\\"],
+    };
+
+    /// The spinner frame to show `elapsed` into a busy spell.
+    pub fn spinner_frame(&self, elapsed: Duration) -> &'static str {
+        let step = (elapsed.as_millis() / SPINNER_STEP.as_millis()) as usize;
+        self.spinner[step % self.spinner.len()]
+    }
+}
+
+impl Default for Glyphs {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_glyphs_are_ascii() {
+        let g = Charset::Ascii.glyphs();
+        let all = [
+            g.file,
+            g.directory,
+            g.branch,
+            g.commit,
+            g.pointer,
+            g.pin,
+            g.pending,
+            g.warning,
+            g.typing,
+            g.recording,
+            g.transcribing,
+            g.speaking,
+            g.handled,
+            g.unhandled,
+            g.dash,
+            g.separator,
+            g.up_down,
+            g.left_right,
+        ];
+        assert!(all.iter().all(|s| !s.is_empty() && s.is_ascii()));
+        assert!(g.spinner.iter().all(|s| s.is_ascii()));
+        // Basic never reaches for emoji
+        assert!(Glyphs::BASIC.file.is_ascii());
+        assert!(Glyphs::BASIC.speaking.chars().all(|c| (c as u32) < 0x2700));
+    }
+
+    #[test]
+    fn test_spinner_turns_each_step() {
+        let g = Glyphs::ASCII;
+        assert_eq!(g.spinner_frame(Duration::ZERO), "|");
+        assert_eq!(g.spinner_frame(SPINNER_STEP), "/");
+        assert_eq!(g.spinner_frame(SPINNER_STEP * 5 + SPINNER_STEP / 2), "/");
+    }
+}
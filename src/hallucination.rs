@@ -0,0 +1,158 @@
+// Hallucination Module - Whisper artifacts caught before confirmation
+//
+// Whisper was trained on subtitled video, and when a recording has little
+// or no speech in it, it writes what subtitles say over the silence:
+// "Thanks for watching!", "Subtitles by the Amara.org community", a lone
+// "you". A transcript made up of nothing but such phrases is dropped before
+// it becomes the pending prompt, or, if the `[hallucinations]` action says
+// so, held for review like a low-confidence one. A phrase is only caught on
+// its own: "thank you, now run the tests" is a prompt.
+
+use serde::Deserialize;
+
+use crate::send::normalize_words;
+
+/// Phrases Whisper is known to make up. A trailing `*` matches whatever
+/// follows, as in the credits lines.
+const DEFAULT_PHRASES: &[&str] = &[
+    "thank you",
+    "thanks for watching",
+    "thank you for watching",
+    "thank you so much for watching",
+    "please subscribe",
+    "like and subscribe",
+    "don't forget to like and subscribe",
+    "see you in the next video",
+    "subtitles by *",
+    "subtitles made by *",
+    "captions by *",
+    "transcription by *",
+    "translated by *",
+    "you",
+    "bye",
+    "blank audio",
+    "music",
+    "applause",
+    "silence",
+];
+
+/// What happens to a transcript that is only hallucinations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    /// Dropped with a notice; it never becomes the pending prompt.
+    #[default]
+    Drop,
+    /// Kept as the pending prompt, held for review.
+    Flag,
+}
+
+/// `[hallucinations]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct HallucinationConfig {
+    pub action: Action,
+    /// Phrases to catch, matched ignoring case and punctuation. Replaces the
+    /// built-in list; empty turns the filter off.
+    pub phrases: Vec<String>,
+}
+
+impl Default for HallucinationConfig {
+    fn default() -> Self {
+        Self {
+            action: Action::Drop,
+            phrases: DEFAULT_PHRASES.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+}
+
+impl HallucinationConfig {
+    /// Whether every sentence of `transcript` is one of the phrases. An
+    /// empty transcript isn't.
+    pub fn matches(&self, transcript: &str) -> bool {
+        let sentences: Vec<String> = sentences(transcript)
+            .map(normalize_words)
+            .filter(|s| !s.is_empty())
+            .collect();
+        !sentences.is_empty() && sentences.iter().all(|s| self.is_phrase(s))
+    }
+
+    /// Whether the normalized `sentence` is one of the phrases.
+    fn is_phrase(&self, sentence: &str) -> bool {
+        self.phrases.iter().any(|phrase| {
+            let words = normalize_words(phrase);
+            if words.is_empty() {
+                return false;
+            }
+            if phrase.trim_end().ends_with('*') {
+                sentence
+                    .strip_prefix(&words)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+            } else {
+                sentence == words
+            }
+        })
+    }
+}
+
+/// The sentences of `text`, cut after sentence punctuation followed by a
+/// space, so "Amara.org" stays whole.
+fn sentences(text: &str) -> impl Iterator<Item = &str> {
+    let mut start = 0;
+    let mut cuts = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let at_end = chars.peek().is_none_or(|(_, next)| next.is_whitespace());
+        if matches!(c, '.' | '!' | '?' | '\u{2026}' | '\n') && at_end {
+            let end = i + c.len_utf8();
+            cuts.push(&text[start..end]);
+            start = end;
+        }
+    }
+    cuts.push(&text[start..]);
+    cuts.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_artifacts() {
+        let config = HallucinationConfig::default();
+        assert!(config.matches("Thanks for watching!"));
+        assert!(config.matches(" Thank you."));
+        assert!(config.matches("you"));
+        assert!(config.matches("Subtitles by the Amara.org community"));
+        assert!(config.matches("[BLANK_AUDIO]"));
+        assert!(config.matches("Thank you. Thank you for watching!"));
+        assert!(config.matches("Thanks for watching… Please subscribe."));
+    }
+
+    #[test]
+    fn test_prompts_pass() {
+        let config = HallucinationConfig::default();
+        assert!(!config.matches("Thank you, now run the tests."));
+        assert!(!config.matches("Thanks for watching. Now open main.rs"));
+        assert!(!config.matches("you should fix the parser"));
+        assert!(!config.matches("Subtitles"));
+        assert!(!config.matches("subtitles bypass the cache"));
+        assert!(!config.matches(""));
+        assert!(!config.matches("..."));
+    }
+
+    #[test]
+    fn test_custom_phrases() {
+        let config = HallucinationConfig {
+            phrases: vec!["Untertitel im Auftrag des ZDF *".into()],
+            ..HallucinationConfig::default()
+        };
+        assert!(config.matches("Untertitel im Auftrag des ZDF, 2021"));
+        assert!(!config.matches("Thank you."));
+        let off = HallucinationConfig {
+            phrases: Vec::new(),
+            ..HallucinationConfig::default()
+        };
+        assert!(!off.matches("Thanks for watching!"));
+    }
+}
@@ -0,0 +1,177 @@
+// Handsfree Module - Re-arming the mic after each reply
+//
+// In hands-free mode conch takes the next turn by itself. Once OpenCode goes
+// from busy to idle and its reply has been rendered (and read aloud, when
+// TTS is on), the mic opens again, so the conversation goes on without a
+// key press. With a wake word the hot mic already listens while idle, so
+// the turn waits for the wake word instead.
+
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+/// How long to wait for the reply after OpenCode goes idle before
+/// re-arming without it.
+pub const REPLY_WAIT: Duration = Duration::from_secs(5);
+
+/// `[handsfree]` section of the config file. Off unless explicitly enabled.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct HandsFreeConfig {
+    /// Re-arm the mic after every reply.
+    pub enabled: bool,
+    /// Milliseconds between the reply (or its readback) ending and the mic
+    /// opening, so the tail of the readback isn't recorded.
+    pub delay_ms: u64,
+}
+
+impl Default for HandsFreeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            delay_ms: 500,
+        }
+    }
+}
+
+/// A reply the mic is waiting on.
+#[derive(Debug, Clone, Copy)]
+struct Turn {
+    /// When OpenCode went idle.
+    idle_at: Instant,
+    /// Whether the reply has been rendered.
+    reply: bool,
+    /// Since when nothing else has been going on.
+    ready_since: Option<Instant>,
+}
+
+/// Decides when to re-arm the mic.
+#[derive(Debug, Clone, Default)]
+pub struct HandsFree {
+    enabled: bool,
+    delay: Duration,
+    turn: Option<Turn>,
+}
+
+impl HandsFree {
+    pub fn new(config: &HandsFreeConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            delay: Duration::from_millis(config.delay_ms),
+            turn: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.turn = None;
+    }
+
+    /// OpenCode went from busy to idle.
+    pub fn opencode_idle(&mut self, now: Instant) {
+        if self.enabled {
+            self.turn = Some(Turn {
+                idle_at: now,
+                reply: false,
+                ready_since: None,
+            });
+        }
+    }
+
+    /// The reply to the last prompt was rendered.
+    pub fn reply_rendered(&mut self) {
+        if let Some(turn) = &mut self.turn {
+            turn.reply = true;
+        }
+    }
+
+    /// Drop the pending turn, e.g. because a recording was started anyway.
+    pub fn cancel(&mut self) {
+        self.turn = None;
+    }
+
+    /// Whether the mic should open now. `ready` says nothing else is going
+    /// on: no recording, transcription, pending prompt or readback.
+    pub fn poll(&mut self, now: Instant, ready: bool) -> bool {
+        let Some(turn) = &mut self.turn else {
+            return false;
+        };
+        if !ready {
+            turn.ready_since = None;
+            return false;
+        }
+        if !turn.reply && now.duration_since(turn.idle_at) < REPLY_WAIT {
+            return false;
+        }
+        let since = *turn.ready_since.get_or_insert(now);
+        if now.duration_since(since) < self.delay {
+            return false;
+        }
+        self.turn = None;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled() -> HandsFree {
+        HandsFree::new(&HandsFreeConfig {
+            enabled: true,
+            delay_ms: 500,
+        })
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_rearms_after_reply_and_delay() {
+        let start = Instant::now();
+        let mut hands_free = enabled();
+        assert!(!hands_free.poll(start, true), "no turn yet");
+
+        hands_free.opencode_idle(start);
+        assert!(
+            !hands_free.poll(start + ms(100), true),
+            "reply not rendered"
+        );
+        hands_free.reply_rendered();
+        assert!(!hands_free.poll(start + ms(200), true));
+        // Still reading the reply aloud: the delay starts over
+        assert!(!hands_free.poll(start + ms(800), false));
+        assert!(!hands_free.poll(start + ms(900), true));
+        assert!(hands_free.poll(start + ms(1400), true));
+        assert!(!hands_free.poll(start + ms(2000), true), "once per turn");
+    }
+
+    #[test]
+    fn test_rearms_without_reply_after_wait() {
+        let start = Instant::now();
+        let mut hands_free = enabled();
+        hands_free.opencode_idle(start);
+        assert!(!hands_free.poll(start + ms(1000), true));
+        assert!(!hands_free.poll(start + REPLY_WAIT, true));
+        assert!(hands_free.poll(start + REPLY_WAIT + ms(500), true));
+    }
+
+    #[test]
+    fn test_disabled_and_cancelled() {
+        let start = Instant::now();
+        let mut hands_free = HandsFree::new(&HandsFreeConfig::default());
+        hands_free.opencode_idle(start);
+        hands_free.reply_rendered();
+        assert!(!hands_free.poll(start + REPLY_WAIT * 2, true));
+
+        hands_free.set_enabled(true);
+        hands_free.opencode_idle(start);
+        hands_free.cancel();
+        assert!(!hands_free.poll(start + REPLY_WAIT * 2, true));
+    }
+}
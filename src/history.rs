@@ -0,0 +1,365 @@
+// History Module - Persists transcripts to a local JSONL store across runs
+//
+// The store is append-only: every status change appends the full entry again,
+// and loading keeps the last record per id. A crash loses at most the line
+// being written.
+
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+
+/// `[history]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Save transcripts and reload them when reconnecting to the same session.
+    pub enabled: bool,
+    /// JSONL file the transcripts are appended to.
+    pub path: PathBuf,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: paths::history_file(),
+        }
+    }
+}
+
+/// What happened to a transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptStatus {
+    /// Waiting for the user to send or discard it.
+    Pending,
+    /// Accepted by OpenCode, which hasn't finished answering it yet.
+    Sent,
+    /// OpenCode went idle again after it was sent.
+    Answered,
+    /// Sending was attempted and failed, or OpenCode reported an error
+    /// while working on it.
+    Failed,
+    /// Discarded without sending.
+    Discarded,
+    /// Copied to the clipboard instead of being sent.
+    Copied,
+    /// Typed into another window instead of being sent.
+    Typed,
+    /// Appended to the notes file instead of being sent.
+    Noted,
+}
+
+impl TranscriptStatus {
+    /// Whether the transcript went to OpenCode as a prompt.
+    pub fn was_sent(self) -> bool {
+        matches!(self, TranscriptStatus::Sent | TranscriptStatus::Answered)
+    }
+}
+
+/// One transcript and its fate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Unique per transcript; later records with the same id supersede earlier ones.
+    pub id: u64,
+    /// Seconds since the Unix epoch when the transcript was produced.
+    pub timestamp: f64,
+    /// OpenCode session the transcript belongs to, if one was known.
+    pub session_id: Option<String>,
+    pub text: String,
+    pub status: TranscriptStatus,
+    /// Seconds from sending to OpenCode going idle, once answered.
+    #[serde(default)]
+    pub response_secs: Option<f64>,
+}
+
+impl HistoryEntry {
+    /// A new pending transcript stamped with the current time.
+    pub fn new(text: impl Into<String>, session_id: Option<String>) -> Self {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        Self {
+            id: now.as_nanos() as u64,
+            timestamp: now.as_secs_f64(),
+            session_id,
+            text: text.into(),
+            status: TranscriptStatus::Pending,
+            response_secs: None,
+        }
+    }
+}
+
+/// Prompts OpenCode accepted and hasn't answered yet, to time its
+/// responses. A session answers everything sent to it once it goes from
+/// busy to idle.
+#[derive(Debug, Default)]
+pub struct Unanswered {
+    sent: Vec<(u64, String, Instant)>,
+}
+
+impl Unanswered {
+    /// Transcript `id` was accepted by session `session_id` at `at`.
+    pub fn sent(&mut self, id: u64, session_id: String, at: Instant) {
+        self.sent.push((id, session_id, at));
+    }
+
+    /// Session `session_id` went idle at `now`. Returns the transcripts it
+    /// answered with their response times, oldest first.
+    pub fn answered(&mut self, session_id: &str, now: Instant) -> Vec<(u64, Duration)> {
+        let mut answered = Vec::new();
+        self.sent.retain(|(id, session, at)| {
+            if session != session_id {
+                return true;
+            }
+            answered.push((*id, now.saturating_duration_since(*at)));
+            false
+        });
+        answered
+    }
+}
+
+/// Response time for the transcript strip: "4.2s", "1m05s".
+pub fn format_response(secs: f64) -> String {
+    if secs < 60.0 {
+        format!("{secs:.1}s")
+    } else {
+        let secs = secs.round() as u64;
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    }
+}
+
+/// Append-only JSONL transcript store.
+pub struct TranscriptStore {
+    path: PathBuf,
+}
+
+impl TranscriptStore {
+    /// Use the store at `path`. The file is created on first write.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The file backing this store.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append the current state of `entry`.
+    pub fn record(&self, entry: &HistoryEntry) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| anyhow!("Failed to open history '{}': {}", self.path.display(), e))?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+
+    /// All entries, latest record per id, in the order they were first written.
+    /// Unparseable lines (e.g. a torn final write) are skipped.
+    pub fn load(&self) -> Result<Vec<HistoryEntry>> {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(anyhow!(
+                    "Failed to read history '{}': {}",
+                    self.path.display(),
+                    e
+                ));
+            }
+        };
+
+        let mut entries: Vec<HistoryEntry> = Vec::new();
+        let mut index: HashMap<u64, usize> = HashMap::new();
+        for line in text.lines() {
+            let Ok(entry) = serde_json::from_str::<HistoryEntry>(line) else {
+                continue;
+            };
+            match index.get(&entry.id) {
+                Some(&i) => entries[i] = entry,
+                None => {
+                    index.insert(entry.id, entries.len());
+                    entries.push(entry);
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Entries for one OpenCode session, oldest first.
+    pub fn load_session(&self, session_id: &str) -> Result<Vec<HistoryEntry>> {
+        Ok(self
+            .load()?
+            .into_iter()
+            .filter(|e| e.session_id.as_deref() == Some(session_id))
+            .collect())
+    }
+}
+
+/// Texts of the sent entries, oldest first, with repeats of the same prompt
+/// in a row kept once, like a shell's history.
+pub fn sent_prompts(entries: &[HistoryEntry]) -> Vec<String> {
+    let mut prompts: Vec<String> = Vec::new();
+    for entry in entries {
+        if entry.status.was_sent() && prompts.last() != Some(&entry.text) {
+            prompts.push(entry.text.clone());
+        }
+    }
+    prompts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store(name: &str) -> TranscriptStore {
+        let path = std::env::temp_dir().join(format!(
+            "conch_history_{}_{}.jsonl",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        TranscriptStore::new(path)
+    }
+
+    fn entry(id: u64, session: &str, text: &str, status: TranscriptStatus) -> HistoryEntry {
+        HistoryEntry {
+            id,
+            timestamp: id as f64,
+            session_id: Some(session.into()),
+            text: text.into(),
+            status,
+            response_secs: None,
+        }
+    }
+
+    #[test]
+    fn test_missing_store_is_empty() {
+        let store = temp_store("missing");
+        assert!(store.load().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_record_and_load_roundtrip() {
+        let store = temp_store("roundtrip");
+        let e = entry(1, "ses_a", "open the readme", TranscriptStatus::Sent);
+        store.record(&e).unwrap();
+        let loaded = store.load().unwrap();
+        let _ = std::fs::remove_file(store.path());
+        assert_eq!(loaded, vec![e]);
+    }
+
+    #[test]
+    fn test_latest_status_wins() {
+        let store = temp_store("status");
+        let mut e = entry(1, "ses_a", "run the tests", TranscriptStatus::Pending);
+        store.record(&e).unwrap();
+        store
+            .record(&entry(2, "ses_a", "second", TranscriptStatus::Sent))
+            .unwrap();
+        e.status = TranscriptStatus::Discarded;
+        store.record(&e).unwrap();
+
+        let loaded = store.load().unwrap();
+        let _ = std::fs::remove_file(store.path());
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].text, "run the tests");
+        assert_eq!(loaded[0].status, TranscriptStatus::Discarded);
+        assert_eq!(loaded[1].text, "second");
+    }
+
+    #[test]
+    fn test_load_session_filters() {
+        let store = temp_store("session");
+        store
+            .record(&entry(1, "ses_a", "one", TranscriptStatus::Sent))
+            .unwrap();
+        store
+            .record(&entry(2, "ses_b", "two", TranscriptStatus::Sent))
+            .unwrap();
+        store
+            .record(&entry(3, "ses_a", "three", TranscriptStatus::Failed))
+            .unwrap();
+        let texts: Vec<String> = store
+            .load_session("ses_a")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.text)
+            .collect();
+        let _ = std::fs::remove_file(store.path());
+        assert_eq!(texts, vec!["one", "three"]);
+    }
+
+    #[test]
+    fn test_torn_line_is_skipped() {
+        let store = temp_store("torn");
+        store
+            .record(&entry(1, "ses_a", "kept", TranscriptStatus::Sent))
+            .unwrap();
+        let mut f = OpenOptions::new().append(true).open(store.path()).unwrap();
+        write!(f, "{{\"id\": 2, \"text\": \"cut o").unwrap();
+        let loaded = store.load().unwrap();
+        let _ = std::fs::remove_file(store.path());
+        assert_eq!(loaded.len(), 1);
+    }
+
+    #[test]
+    fn test_sent_prompts() {
+        let entries = vec![
+            entry(1, "ses_a", "run the tests", TranscriptStatus::Sent),
+            entry(2, "ses_b", "run the tests", TranscriptStatus::Sent),
+            entry(3, "ses_a", "never mind", TranscriptStatus::Discarded),
+            entry(4, "ses_a", "open the readme", TranscriptStatus::Answered),
+            entry(5, "ses_a", "run the tests", TranscriptStatus::Sent),
+        ];
+        assert_eq!(
+            sent_prompts(&entries),
+            vec!["run the tests", "open the readme", "run the tests"]
+        );
+    }
+
+    #[test]
+    fn test_status_serializes_lowercase() {
+        let json = serde_json::to_string(&TranscriptStatus::Discarded).unwrap();
+        assert_eq!(json, "\"discarded\"");
+    }
+
+    #[test]
+    fn test_records_without_response_time_load() {
+        let line = r#"{"id":1,"timestamp":1.0,"session_id":null,"text":"hi","status":"sent"}"#;
+        let entry: HistoryEntry = serde_json::from_str(line).unwrap();
+        assert_eq!(entry.response_secs, None);
+    }
+
+    #[test]
+    fn test_unanswered_until_idle() {
+        let start = Instant::now();
+        let mut unanswered = Unanswered::default();
+        unanswered.sent(1, "ses_a".into(), start);
+        unanswered.sent(2, "ses_b".into(), start);
+        unanswered.sent(3, "ses_a".into(), start + Duration::from_secs(2));
+
+        let answered = unanswered.answered("ses_a", start + Duration::from_secs(5));
+        assert_eq!(
+            answered,
+            vec![(1, Duration::from_secs(5)), (3, Duration::from_secs(3))]
+        );
+        assert!(unanswered.answered("ses_a", start).is_empty());
+        assert_eq!(unanswered.answered("ses_b", start).len(), 1);
+    }
+
+    #[test]
+    fn test_format_response() {
+        assert_eq!(format_response(4.24), "4.2s");
+        assert_eq!(format_response(65.4), "1m05s");
+    }
+}
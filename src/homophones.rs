@@ -0,0 +1,536 @@
+// Homophones Module - Words Whisper heard right but wrote wrong
+//
+// "Open mane dot rs" is what Whisper writes for "open main.rs" when it has
+// no reason to prefer the file name. After transcription, runs of words are
+// looked up in the focus stack and the project vocabulary, first as heard and
+// then with one word swapped for a word that sounds the same, so "mane dot
+// rs" becomes `main.rs` and "audio capture" `AudioCapture`. Next to a file
+// name, "right" is "write" and "two" is "to". Each change is remembered so
+// the pending prompt can show it and put the heard words back; changes made
+// before this, by numbers and casing, are found by diffing the words.
+
+use std::ops::Range;
+
+use crate::diff::{self, DiffKind};
+use crate::focus::{FocusEntry, FocusState};
+use crate::vocabulary::Vocabulary;
+
+/// Most words looked up as one name.
+const MAX_WORDS: usize = 4;
+/// How many words after "right" or "two" a file name may be.
+const NEAR_FILE: usize = 4;
+/// Punctuation around a word that isn't part of it.
+const PUNCTUATION: &[char] = &[',', '.', '!', '?', ';', ':', '"', '\'', '(', ')'];
+
+/// Words that sound alike, tried in place of each other in names.
+const SOUNDS_ALIKE: &[&[&str]] = &[
+    &["main", "mane", "maine"],
+    &["to", "two", "too", "2"],
+    &["for", "four", "4"],
+    &["one", "won", "1"],
+    &["write", "right", "rite"],
+    &["read", "red", "reed"],
+    &["lib", "lip"],
+    &["toml", "tamil", "tommel"],
+    &["json", "jason"],
+    &["yaml", "yamel"],
+];
+
+/// Words that mean another word in front of a file name.
+const BEFORE_FILE: &[(&str, &str)] = &[
+    ("right", "write"),
+    ("rite", "write"),
+    ("red", "read"),
+    ("reed", "read"),
+    ("two", "to"),
+    ("too", "to"),
+];
+
+/// One change made to a transcript.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Correction {
+    /// Where the new words are in the corrected text, in bytes.
+    pub range: Range<usize>,
+    /// The words as Whisper wrote them.
+    pub original: String,
+}
+
+/// A transcript after correction, and what was changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Corrected {
+    pub text: String,
+    /// In order of where they are in the text.
+    pub corrections: Vec<Correction>,
+}
+
+impl Corrected {
+    /// The corrections, if they are still of `text`; none once it has
+    /// been edited or replaced.
+    pub fn of(&self, text: &str) -> &[Correction] {
+        if self.text == text {
+            &self.corrections
+        } else {
+            &[]
+        }
+    }
+
+    /// `text` with every run of words or punctuation that differs from
+    /// `heard` as a correction, whatever changed it.
+    pub fn between(heard: &str, text: &str) -> Self {
+        let old = token_ranges(heard);
+        let new = token_ranges(text);
+        let old_words: Vec<&str> = old.iter().map(|r| &heard[r.clone()]).collect();
+        let new_words: Vec<&str> = new.iter().map(|r| &text[r.clone()]).collect();
+        let script = diff::edit_script(&old_words, &new_words);
+        let mut corrections = Vec::new();
+        let mut k = 0;
+        while k < script.len() {
+            if script[k].0 == DiffKind::Context {
+                k += 1;
+                continue;
+            }
+            let (_, i, j, _) = script[k];
+            let (mut removed, mut added) = (0, 0);
+            while k < script.len() && script[k].0 != DiffKind::Context {
+                match script[k].0 {
+                    DiffKind::Removed => removed += 1,
+                    _ => added += 1,
+                }
+                k += 1;
+            }
+            let original = match removed {
+                0 => String::new(),
+                n => heard[old[i].start..old[i + n - 1].end].to_string(),
+            };
+            let range = match added {
+                // Words dropped: mark where they were
+                0 => {
+                    let at = new.get(j).map_or(text.len(), |r| r.start);
+                    at..at
+                }
+                n => new[j].start..new[j + n - 1].end,
+            };
+            corrections.push(Correction { range, original });
+        }
+        Self {
+            text: text.to_string(),
+            corrections,
+        }
+    }
+
+    /// These corrections, then `later` made to `self.text`: the changes from
+    /// the first text to `later.text`. One of these inside a later
+    /// correction becomes part of what that one replaced.
+    pub fn then(self, later: Corrected) -> Corrected {
+        let mut corrections = Vec::new();
+        let mut earlier = self.corrections.into_iter().peekable();
+        // How far the text after the last later correction moved
+        let mut shift = 0isize;
+        let moved = |range: Range<usize>, shift: isize| {
+            range.start.saturating_add_signed(shift)..range.end.saturating_add_signed(shift)
+        };
+        for mut correction in later.corrections {
+            let start = correction.range.start.saturating_add_signed(-shift);
+            let end = start + correction.original.len();
+            while let Some(e) = earlier.next_if(|e| e.range.end <= start) {
+                let range = moved(e.range, shift);
+                corrections.push(Correction { range, ..e });
+            }
+            let mut original = String::new();
+            let mut at = start;
+            while let Some(e) = earlier.next_if(|e| e.range.start < end) {
+                // One straddling an edge can't be told apart; it's dropped
+                if e.range.start >= at && e.range.end <= end {
+                    original.push_str(&self.text[at..e.range.start]);
+                    original.push_str(&e.original);
+                    at = e.range.end;
+                }
+            }
+            if at > start {
+                original.push_str(&self.text[at..end]);
+                correction.original = original;
+            }
+            shift += correction.range.len() as isize - (end - start) as isize;
+            corrections.push(correction);
+        }
+        corrections.extend(earlier.map(|e| Correction {
+            range: moved(e.range.clone(), shift),
+            ..e
+        }));
+        Corrected {
+            text: later.text,
+            corrections,
+        }
+    }
+
+    /// Put the words of the last correction back as they were heard.
+    pub fn revert_last(&mut self) -> Option<Correction> {
+        let correction = self.corrections.pop()?;
+        self.text
+            .replace_range(correction.range.clone(), &correction.original);
+        Some(correction)
+    }
+}
+
+/// The names in the focus stack, to look up before the project vocabulary:
+/// file names, directory names and branches.
+pub fn focus_vocabulary(focus: &FocusState) -> Vocabulary {
+    let mut files = Vec::new();
+    let mut names = Vec::new();
+    for entry in focus.entries() {
+        match entry {
+            FocusEntry::File(path) => {
+                files.extend(path.file_name().map(|n| n.to_string_lossy().into_owned()));
+            }
+            FocusEntry::Directory(path) => {
+                names.extend(path.file_name().map(|n| n.to_string_lossy().into_owned()));
+            }
+            FocusEntry::Branch(name) => names.push(name.clone()),
+            FocusEntry::Commit(_) => {}
+        }
+    }
+    Vocabulary::new(files, names)
+}
+
+/// A piece of the transcript: a word, or what's between words.
+#[derive(Debug, Clone)]
+struct Piece {
+    text: String,
+    word: bool,
+    /// What was heard, if this word was corrected.
+    original: Option<String>,
+}
+
+impl Piece {
+    fn new(text: &str, word: bool) -> Self {
+        Self {
+            text: text.to_string(),
+            word,
+            original: None,
+        }
+    }
+}
+
+/// Correct names and homophones in `text`, looking names up in
+/// `vocabularies` in order.
+pub fn correct(text: &str, vocabularies: &[&Vocabulary]) -> Corrected {
+    let pieces = correct_names(split(text), vocabularies);
+    let pieces = correct_before_files(pieces, vocabularies);
+    let mut corrected = Corrected::default();
+    for piece in pieces {
+        let start = corrected.text.len();
+        corrected.text.push_str(&piece.text);
+        if let Some(original) = piece.original {
+            let range = start..corrected.text.len();
+            corrected.corrections.push(Correction { range, original });
+        }
+    }
+    corrected
+}
+
+/// Where the words and punctuation of `text` are, in bytes.
+fn token_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut at = 0;
+    split(text)
+        .into_iter()
+        .filter_map(|piece| {
+            let range = at..at + piece.text.len();
+            at = range.end;
+            (!piece.text.trim().is_empty()).then_some(range)
+        })
+        .collect()
+}
+
+/// Split `text` into words and the spaces and punctuation around them.
+fn split(text: &str) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let space = rest
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(rest.len());
+        if space > 0 {
+            pieces.push(Piece::new(&rest[..space], false));
+            rest = &rest[space..];
+            continue;
+        }
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let token = &rest[..end];
+        let core = token.trim_matches(PUNCTUATION);
+        if core.is_empty() {
+            pieces.push(Piece::new(token, false));
+        } else {
+            let start = token.find(core).unwrap_or(0);
+            if start > 0 {
+                pieces.push(Piece::new(&token[..start], false));
+            }
+            pieces.push(Piece::new(core, true));
+            if start + core.len() < token.len() {
+                pieces.push(Piece::new(&token[start + core.len()..], false));
+            }
+        }
+        rest = &rest[end..];
+    }
+    pieces
+}
+
+/// The word `word` may have been heard for, other than itself.
+fn sounds_alike(word: &str) -> impl Iterator<Item = &'static str> {
+    let lower = word.to_lowercase();
+    SOUNDS_ALIKE
+        .iter()
+        .filter(move |group| group.contains(&lower.as_str()))
+        .flat_map(|group| group.iter().copied())
+        .filter(move |other| !other.eq_ignore_ascii_case(word))
+}
+
+/// The name `words` stand for, as heard or with one word swapped for one
+/// that sounds alike. A name that is only the words in another case ("the
+/// license", `LICENSE`) isn't a correction.
+fn lookup(words: &[&str], vocabularies: &[&Vocabulary]) -> Option<String> {
+    let heard = words.join(" ");
+    let resolve = |spoken: &str| vocabularies.iter().find_map(|v| v.resolve(spoken));
+    if let Some(term) = resolve(&heard) {
+        return (!term.eq_ignore_ascii_case(&heard)).then(|| term.to_string());
+    }
+    for (i, word) in words.iter().enumerate() {
+        for other in sounds_alike(word) {
+            let mut swapped = words.to_vec();
+            swapped[i] = other;
+            if let Some(term) = resolve(&swapped.join(" ")) {
+                return Some(term.to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Replace runs of words that name something in `vocabularies` with the
+/// name, the longest run first.
+fn correct_names(pieces: Vec<Piece>, vocabularies: &[&Vocabulary]) -> Vec<Piece> {
+    let words: Vec<usize> = (0..pieces.len()).filter(|&i| pieces[i].word).collect();
+    let mut out = Vec::new();
+    let mut next = 0;
+    let mut k = 0;
+    while k < words.len() {
+        let first = words[k];
+        out.extend(pieces[next..first].iter().cloned());
+        let longest = (1..=MAX_WORDS.min(words.len() - k))
+            .take_while(|&n| {
+                // Only spaces between the words of a name
+                let last = words[k + n - 1];
+                pieces[first..last]
+                    .iter()
+                    .all(|p| p.word || p.text.trim().is_empty())
+            })
+            .last()
+            .unwrap_or(1);
+        let found = (1..=longest).rev().find_map(|n| {
+            let run: Vec<&str> = words[k..k + n]
+                .iter()
+                .map(|&i| pieces[i].text.as_str())
+                .collect();
+            lookup(&run, vocabularies).map(|term| (n, term))
+        });
+        match found {
+            Some((n, term)) => {
+                let last = words[k + n - 1];
+                let original: String = pieces[first..=last]
+                    .iter()
+                    .map(|p| p.text.as_str())
+                    .collect();
+                out.push(Piece {
+                    text: term,
+                    word: true,
+                    original: Some(original),
+                });
+                next = last + 1;
+                k += n;
+            }
+            None => {
+                out.push(pieces[first].clone());
+                next = first + 1;
+                k += 1;
+            }
+        }
+    }
+    out.extend(pieces[next..].iter().cloned());
+    out
+}
+
+/// Whether `word` is a file name: one in `vocabularies`, or a name with a
+/// short extension.
+fn is_file(word: &str, vocabularies: &[&Vocabulary]) -> bool {
+    if vocabularies
+        .iter()
+        .any(|v| v.files.iter().any(|f| f == word))
+    {
+        return true;
+    }
+    word.rsplit_once('.').is_some_and(|(stem, extension)| {
+        !stem.is_empty()
+            && (1..=4).contains(&extension.len())
+            && extension.chars().all(|c| c.is_ascii_alphanumeric())
+            && extension.chars().any(|c| c.is_ascii_alphabetic())
+    })
+}
+
+/// Swap "right" for "write" and the like when a file name follows closely.
+fn correct_before_files(mut pieces: Vec<Piece>, vocabularies: &[&Vocabulary]) -> Vec<Piece> {
+    let words: Vec<usize> = (0..pieces.len()).filter(|&i| pieces[i].word).collect();
+    for (k, &i) in words.iter().enumerate() {
+        if pieces[i].original.is_some() {
+            continue;
+        }
+        let heard = pieces[i].text.to_lowercase();
+        let Some((_, meant)) = BEFORE_FILE.iter().find(|(h, _)| *h == heard) else {
+            continue;
+        };
+        let near = words[k + 1..].iter().take(NEAR_FILE);
+        if !near
+            .into_iter()
+            .any(|&j| is_file(&pieces[j].text, vocabularies))
+        {
+            continue;
+        }
+        let capitalized = pieces[i].text.starts_with(char::is_uppercase);
+        let mut text = meant.to_string();
+        if capitalized {
+            text[..1].make_ascii_uppercase();
+        }
+        let original = std::mem::replace(&mut pieces[i].text, text);
+        pieces[i].original = Some(original);
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn project() -> Vocabulary {
+        Vocabulary::new(
+            vec!["main.rs".into(), "Cargo.toml".into()],
+            vec!["AudioCapture".into(), "step2".into()],
+        )
+    }
+
+    fn fix(text: &str) -> Corrected {
+        correct(text, &[&project()])
+    }
+
+    #[test]
+    fn test_names_and_homophones() {
+        let fixed = fix("Open mane dot rs and the audio capture.");
+        assert_eq!(fixed.text, "Open main.rs and the AudioCapture.");
+        let originals: Vec<&str> = fixed
+            .corrections
+            .iter()
+            .map(|c| c.original.as_str())
+            .collect();
+        assert_eq!(originals, vec!["mane dot rs", "audio capture"]);
+        assert_eq!(&fixed.text[fixed.corrections[0].range.clone()], "main.rs");
+
+        assert_eq!(fix("bump cargo dot tamil").text, "bump Cargo.toml");
+        assert_eq!(fix("rename step two").text, "rename step2");
+        assert_eq!(
+            fix("right the tests to main.rs").text,
+            "write the tests to main.rs"
+        );
+        assert_eq!(fix("Right two main.rs").text, "Write to main.rs");
+    }
+
+    #[test]
+    fn test_ordinary_words_are_kept() {
+        let text = [
+            "the main point",
+            "turn right",
+            "two tests",
+            "it's main",
+            "cargo.toml",
+        ];
+        for text in text {
+            let fixed = fix(text);
+            assert_eq!(fixed.text, text);
+            assert!(fixed.corrections.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_revert_last() {
+        let mut fixed = fix("right audio capture in mane dot rs");
+        assert_eq!(fixed.text, "write AudioCapture in main.rs");
+        assert_eq!(fixed.of("write AudioCapture in main.rs").len(), 3);
+        assert_eq!(fixed.revert_last().unwrap().original, "mane dot rs");
+        assert_eq!(fixed.text, "write AudioCapture in mane dot rs");
+        fixed.revert_last();
+        fixed.revert_last();
+        assert_eq!(fixed.text, "right audio capture in mane dot rs");
+        assert!(fixed.revert_last().is_none());
+        assert!(fixed.of("something else").is_empty());
+    }
+
+    #[test]
+    fn test_between_finds_every_change() {
+        let heard = "Port forty ninety six in camel case fetch user, mane dot rs.";
+        let text = "Port 4096 in fetchUser, main.rs.";
+        let mut changed = Corrected::between(heard, text);
+        let shown: Vec<(&str, &str)> = changed
+            .corrections
+            .iter()
+            .map(|c| (c.original.as_str(), &text[c.range.clone()]))
+            .collect();
+        assert_eq!(
+            shown,
+            vec![
+                ("forty ninety six", "4096"),
+                ("camel case fetch user", "fetchUser"),
+                ("mane dot rs", "main.rs")
+            ]
+        );
+        while changed.revert_last().is_some() {}
+        assert_eq!(changed.text, heard);
+        assert!(Corrected::between(text, text).corrections.is_empty());
+    }
+
+    #[test]
+    fn test_then_keeps_both_changes() {
+        let heard = "two mane dot rs on port forty ninety six";
+        let numbers = Corrected::between(heard, "two mane dot rs on port 4096");
+        let mut fixed = numbers.then(fix("two mane dot rs on port 4096"));
+        assert_eq!(fixed.text, "to main.rs on port 4096");
+        let shown: Vec<(&str, &str)> = fixed
+            .corrections
+            .iter()
+            .map(|c| (c.original.as_str(), &fixed.text[c.range.clone()]))
+            .collect();
+        assert_eq!(
+            shown,
+            vec![
+                ("two", "to"),
+                ("mane dot rs", "main.rs"),
+                ("forty ninety six", "4096")
+            ]
+        );
+        while fixed.revert_last().is_some() {}
+        assert_eq!(fixed.text, heard);
+
+        // An earlier change inside a later one is part of what it replaced
+        let cased = Corrected::between("use audio kapture", "use audio capture");
+        let fixed = cased.then(fix("use audio capture"));
+        assert_eq!(fixed.text, "use AudioCapture");
+        assert_eq!(fixed.corrections.len(), 1);
+        assert_eq!(fixed.corrections[0].original, "audio kapture");
+    }
+
+    #[test]
+    fn test_focus_names_come_first() {
+        let mut focus = FocusState::new();
+        focus.append(FocusEntry::File(PathBuf::from("src/lib.rs")));
+        focus.append(FocusEntry::Branch("feature_x".into()));
+        let names = focus_vocabulary(&focus);
+        let fixed = correct("check lip dot rs on feature x", &[&names, &project()]);
+        assert_eq!(fixed.text, "check lib.rs on feature_x");
+    }
+}
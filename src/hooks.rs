@@ -0,0 +1,327 @@
+// Hooks Module - Runs user-configured shell commands on pipeline events
+//
+// Each hook receives a JSON payload on stdin, so scripts can log prompts,
+// poke tmux, etc. without conch knowing anything about them. The app hands
+// what it sees on its bus to a `HookObserver`, which decides what fires.
+
+use std::collections::HashSet;
+use std::io::Write as _;
+use std::process::{Command, Stdio};
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+/// Pipeline events hooks can subscribe to. Names are kebab-case in config
+/// and in the payload's `event` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookEvent {
+    /// Whisper produced a non-empty transcript.
+    TranscriptReady,
+    /// A prompt was accepted by OpenCode.
+    PromptSent,
+    /// OpenCode went from busy to idle.
+    OpencodeIdle,
+    /// The entry under the focus pointer changed.
+    FocusChanged,
+}
+
+impl HookEvent {
+    /// The kebab-case name used in config and payloads.
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::TranscriptReady => "transcript-ready",
+            HookEvent::PromptSent => "prompt-sent",
+            HookEvent::OpencodeIdle => "opencode-idle",
+            HookEvent::FocusChanged => "focus-changed",
+        }
+    }
+}
+
+/// One `[[hooks]]` table from the config file.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct HookConfig {
+    pub event: HookEvent,
+    /// Run via `sh -c`, with the event payload on stdin.
+    pub command: String,
+}
+
+/// Receives hook failures (non-zero exit, spawn errors) for logging.
+pub type ErrorSink = fn(&str);
+
+/// The configured hooks, ready to fire.
+pub struct Hooks {
+    hooks: Vec<HookConfig>,
+    on_error: Option<ErrorSink>,
+}
+
+impl Hooks {
+    /// Wrap the hooks from config. Failures are dropped until a sink is set.
+    pub fn new(hooks: Vec<HookConfig>) -> Self {
+        Self {
+            hooks,
+            on_error: None,
+        }
+    }
+
+    /// Report hook failures to `sink` instead of dropping them.
+    pub fn with_error_sink(mut self, sink: ErrorSink) -> Self {
+        self.on_error = Some(sink);
+        self
+    }
+
+    /// Commands subscribed to `event`, in config order.
+    pub fn commands_for(&self, event: HookEvent) -> impl Iterator<Item = &str> {
+        self.hooks
+            .iter()
+            .filter(move |h| h.event == event)
+            .map(|h| h.command.as_str())
+    }
+
+    /// Run every hook subscribed to `event` on a background thread.
+    ///
+    /// Hooks run sequentially in config order so their side effects don't
+    /// interleave; the caller never waits on them.
+    pub fn fire(&self, event: HookEvent, data: serde_json::Value) {
+        let commands: Vec<String> = self.commands_for(event).map(str::to_string).collect();
+        if commands.is_empty() {
+            return;
+        }
+        let payload = hook_payload(event, data).to_string();
+        let on_error = self.on_error;
+        std::thread::spawn(move || {
+            for command in commands {
+                if let Err(e) = run_hook(&command, &payload)
+                    && let Some(sink) = on_error
+                {
+                    let name = event.name();
+                    sink(&format!("hook '{command}' ({name}) failed: {e}"));
+                }
+            }
+        });
+    }
+}
+
+/// The sessions OpenCode is working in, to tell when one goes idle. Each
+/// session is tracked on its own, so one finishing doesn't hide another.
+#[derive(Debug, Default)]
+pub struct BusySessions {
+    busy: HashSet<String>,
+}
+
+impl BusySessions {
+    /// Record whether `session_id` is busy. Returns true when it just went
+    /// from busy to idle.
+    pub fn status(&mut self, session_id: &str, busy: bool) -> bool {
+        if busy {
+            self.busy.insert(session_id.to_string());
+            false
+        } else {
+            self.busy.remove(session_id)
+        }
+    }
+}
+
+/// A pipeline event as the hook observer is told of it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Observed {
+    /// Whisper finished a transcript, empty or not.
+    Transcript { text: String, confidence: f32 },
+    /// OpenCode accepted a prompt.
+    PromptSent { prompt: String },
+    /// OpenCode reported whether a session is working.
+    SessionStatus { session_id: String, busy: bool },
+    /// The entry under the focus pointer changed.
+    FocusChanged { type_name: String, value: String },
+}
+
+/// Fires the hooks for the pipeline events it is told of.
+pub struct HookObserver {
+    hooks: Hooks,
+    sessions: BusySessions,
+}
+
+impl HookObserver {
+    pub fn new(hooks: Hooks) -> Self {
+        Self {
+            hooks,
+            sessions: BusySessions::default(),
+        }
+    }
+
+    /// Fire the hooks subscribed to what `observed` means, if anything.
+    pub fn observe(&mut self, observed: Observed) {
+        if let Some((event, data)) = self.hook_for(observed) {
+            self.hooks.fire(event, data);
+        }
+    }
+
+    /// The hook event `observed` means and its payload data, if any.
+    pub fn hook_for(&mut self, observed: Observed) -> Option<(HookEvent, serde_json::Value)> {
+        match observed {
+            Observed::Transcript { text, confidence } => (!text.is_empty()).then(|| {
+                let data = serde_json::json!({ "text": text, "confidence": confidence });
+                (HookEvent::TranscriptReady, data)
+            }),
+            Observed::PromptSent { prompt } => {
+                let data = serde_json::json!({ "prompt": prompt });
+                Some((HookEvent::PromptSent, data))
+            }
+            Observed::SessionStatus { session_id, busy } => {
+                let idle = self.sessions.status(&session_id, busy);
+                idle.then(|| {
+                    let data = serde_json::json!({ "session_id": session_id });
+                    (HookEvent::OpencodeIdle, data)
+                })
+            }
+            Observed::FocusChanged { type_name, value } => {
+                let data = serde_json::json!({ "type": type_name, "value": value });
+                Some((HookEvent::FocusChanged, data))
+            }
+        }
+    }
+}
+
+/// Build the JSON document passed to hooks on stdin.
+pub fn hook_payload(event: HookEvent, data: serde_json::Value) -> serde_json::Value {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    serde_json::json!({
+        "event": event.name(),
+        "timestamp": timestamp,
+        "data": data,
+    })
+}
+
+/// Run a single hook command with `stdin_json` on stdin, blocking until it exits.
+pub fn run_hook(command: &str, stdin_json: &str) -> Result<()> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that ignores stdin may exit before we finish writing.
+        let _ = stdin.write_all(stdin_json.as_bytes());
+    }
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!("exited with {}", status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook(event: HookEvent, command: &str) -> HookConfig {
+        HookConfig {
+            event,
+            command: command.into(),
+        }
+    }
+
+    #[test]
+    fn test_event_names_match_serde() {
+        for event in [
+            HookEvent::TranscriptReady,
+            HookEvent::PromptSent,
+            HookEvent::OpencodeIdle,
+            HookEvent::FocusChanged,
+        ] {
+            let json = serde_json::to_value(event).unwrap();
+            assert_eq!(json, event.name());
+        }
+    }
+
+    #[test]
+    fn test_commands_for_filters_by_event() {
+        let hooks = Hooks::new(vec![
+            hook(HookEvent::PromptSent, "a"),
+            hook(HookEvent::OpencodeIdle, "b"),
+            hook(HookEvent::PromptSent, "c"),
+        ]);
+        let cmds: Vec<&str> = hooks.commands_for(HookEvent::PromptSent).collect();
+        assert_eq!(cmds, vec!["a", "c"]);
+        assert_eq!(hooks.commands_for(HookEvent::FocusChanged).count(), 0);
+    }
+
+    #[test]
+    fn test_idle_per_session() {
+        let mut sessions = BusySessions::default();
+        assert!(!sessions.status("ses_a", false));
+        assert!(!sessions.status("ses_a", true));
+        assert!(!sessions.status("ses_b", true));
+        // ses_b finishing leaves ses_a busy
+        assert!(sessions.status("ses_b", false));
+        assert!(!sessions.status("ses_b", false));
+        assert!(sessions.status("ses_a", false));
+    }
+
+    #[test]
+    fn test_observer_maps_events() {
+        let mut observer = HookObserver::new(Hooks::new(Vec::new()));
+        let mut fired = |observed| observer.hook_for(observed);
+        let transcript = |text: &str| Observed::Transcript {
+            text: text.into(),
+            confidence: 0.5,
+        };
+        let (event, data) = fired(transcript("open it")).unwrap();
+        assert_eq!(event, HookEvent::TranscriptReady);
+        let expected = serde_json::json!({"text": "open it", "confidence": 0.5});
+        assert_eq!(data, expected);
+        assert_eq!(fired(transcript("")), None);
+        let focus = Observed::FocusChanged {
+            type_name: "file".into(),
+            value: "src/main.rs".into(),
+        };
+        let (event, data) = fired(focus).unwrap();
+        assert_eq!(event, HookEvent::FocusChanged);
+        assert_eq!(data["type"], "file");
+
+        // Idle fires once per session going from busy to idle
+        let status = |session_id: &str, busy| Observed::SessionStatus {
+            session_id: session_id.into(),
+            busy,
+        };
+        assert_eq!(fired(status("ses_a", true)), None);
+        assert_eq!(fired(status("ses_b", true)), None);
+        let (event, data) = fired(status("ses_a", false)).unwrap();
+        assert_eq!(event, HookEvent::OpencodeIdle);
+        assert_eq!(data["session_id"], "ses_a");
+        assert_eq!(fired(status("ses_a", false)), None);
+        assert!(fired(status("ses_b", false)).is_some());
+    }
+
+    #[test]
+    fn test_hook_payload_shape() {
+        let payload = hook_payload(
+            HookEvent::TranscriptReady,
+            serde_json::json!({"text": "open it"}),
+        );
+        assert_eq!(payload["event"], "transcript-ready");
+        assert_eq!(payload["data"]["text"], "open it");
+        assert!(payload["timestamp"].as_f64().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_run_hook_receives_stdin() {
+        let out = std::env::temp_dir().join(format!("conch_hook_{}.json", std::process::id()));
+        let command = format!("cat > '{}'", out.display());
+        run_hook(&command, r#"{"event":"prompt-sent"}"#).unwrap();
+        let written = std::fs::read_to_string(&out).unwrap();
+        let _ = std::fs::remove_file(&out);
+        assert_eq!(written, r#"{"event":"prompt-sent"}"#);
+    }
+
+    #[test]
+    fn test_run_hook_failure_is_error() {
+        assert!(run_hook("exit 3", "{}").is_err());
+    }
+}
@@ -0,0 +1,244 @@
+// Input Module - Single-line text editing for typed prompts
+//
+// The buffer is edited by character, not byte, so the cursor never lands
+// inside a multi-byte character. Up and Down recall earlier prompts, like a
+// shell's history.
+
+/// A line of text with a cursor.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextInput {
+    text: String,
+    /// Cursor position in characters, `0..=len`.
+    cursor: usize,
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Cursor position in characters.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Replace the text, with the cursor at its end.
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = self.char_len();
+    }
+
+    /// Byte offset of character position `pos`.
+    fn byte_at(&self, pos: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(pos)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+
+    fn char_len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    /// Insert `c` at the cursor.
+    pub fn insert(&mut self, c: char) {
+        let at = self.byte_at(self.cursor);
+        self.text.insert(at, c);
+        self.cursor += 1;
+    }
+
+    /// Delete the character before the cursor.
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let at = self.byte_at(self.cursor);
+            self.text.remove(at);
+        }
+    }
+
+    /// Delete the character under the cursor.
+    pub fn delete(&mut self) {
+        if self.cursor < self.char_len() {
+            let at = self.byte_at(self.cursor);
+            self.text.remove(at);
+        }
+    }
+
+    /// Delete the word before the cursor, and the spaces after it (Ctrl+W).
+    pub fn delete_word(&mut self) {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut start = self.cursor;
+        while start > 0 && chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        while start > 0 && !chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        let (from, to) = (self.byte_at(start), self.byte_at(self.cursor));
+        self.text.replace_range(from..to, "");
+        self.cursor = start;
+    }
+
+    /// Delete everything before the cursor (Ctrl+U).
+    pub fn delete_to_start(&mut self) {
+        let to = self.byte_at(self.cursor);
+        self.text.replace_range(..to, "");
+        self.cursor = 0;
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.char_len());
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.char_len();
+    }
+
+    /// The text before and from the cursor, for rendering a cursor between them.
+    pub fn split_at_cursor(&self) -> (&str, &str) {
+        self.text.split_at(self.byte_at(self.cursor))
+    }
+}
+
+/// Stepping through earlier prompts from the input line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recall {
+    /// Oldest first.
+    prompts: Vec<String>,
+    /// The prompt shown; `None` while on the draft.
+    at: Option<usize>,
+    /// What was in the line before recalling, given back past the newest.
+    draft: String,
+}
+
+impl Recall {
+    pub fn new(prompts: Vec<String>) -> Self {
+        Self {
+            prompts,
+            at: None,
+            draft: String::new(),
+        }
+    }
+
+    /// The prompt before the one shown. Leaving the draft keeps `current`
+    /// as the draft.
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        let at = self.at.unwrap_or(self.prompts.len()).checked_sub(1)?;
+        if self.at.is_none() {
+            self.draft = current.to_string();
+        }
+        self.at = Some(at);
+        Some(&self.prompts[at])
+    }
+
+    /// The prompt after the one shown, or the draft after the newest.
+    pub fn newer(&mut self) -> Option<&str> {
+        let at = self.at? + 1;
+        if at < self.prompts.len() {
+            self.at = Some(at);
+            Some(&self.prompts[at])
+        } else {
+            self.at = None;
+            Some(&self.draft)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(s: &str) -> TextInput {
+        let mut input = TextInput::new();
+        s.chars().for_each(|c| input.insert(c));
+        input
+    }
+
+    #[test]
+    fn test_insert_and_backspace() {
+        let mut input = typed("fix thw");
+        input.backspace();
+        input.insert('e');
+        assert_eq!(input.text(), "fix the");
+        assert_eq!(input.cursor(), 7);
+    }
+
+    #[test]
+    fn test_edit_in_the_middle() {
+        let mut input = typed("run tests");
+        input.move_home();
+        (0..4).for_each(|_| input.move_right());
+        "the ".chars().for_each(|c| input.insert(c));
+        assert_eq!(input.text(), "run the tests");
+        input.move_end();
+        input.move_left();
+        input.delete();
+        assert_eq!(input.text(), "run the test");
+        assert_eq!(input.split_at_cursor(), ("run the test", ""));
+    }
+
+    #[test]
+    fn test_multibyte_characters() {
+        let mut input = typed("café ok");
+        (0..3).for_each(|_| input.move_left());
+        input.backspace();
+        assert_eq!(input.text(), "caf ok");
+        assert_eq!(input.split_at_cursor(), ("caf", " ok"));
+    }
+
+    #[test]
+    fn test_delete_word_and_line() {
+        let mut input = typed("open the  config");
+        input.delete_word();
+        assert_eq!(input.text(), "open the  ");
+        input.delete_word();
+        assert_eq!(input.text(), "open ");
+        input.delete_to_start();
+        assert!(input.is_empty());
+        assert_eq!(input.cursor(), 0);
+    }
+
+    #[test]
+    fn test_recall() {
+        let mut recall = Recall::new(vec!["first".into(), "second".into()]);
+        assert_eq!(recall.newer(), None);
+        assert_eq!(recall.older("draft"), Some("second"));
+        assert_eq!(recall.older("second"), Some("first"));
+        assert_eq!(recall.older("first"), None);
+        assert_eq!(recall.newer(), Some("second"));
+        assert_eq!(recall.newer(), Some("draft"));
+        assert_eq!(recall.newer(), None);
+        assert!(Recall::default().older("").is_none());
+
+        let mut input = typed("abc");
+        input.set_text("open it");
+        assert_eq!(input.split_at_cursor(), ("open it", ""));
+    }
+
+    #[test]
+    fn test_cursor_stays_in_bounds() {
+        let mut input = typed("ab");
+        (0..5).for_each(|_| input.move_right());
+        assert_eq!(input.cursor(), 2);
+        (0..5).for_each(|_| input.move_left());
+        assert_eq!(input.cursor(), 0);
+        input.backspace();
+        assert_eq!(input.text(), "ab");
+    }
+}
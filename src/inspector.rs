@@ -0,0 +1,163 @@
+// Inspector Module - Raw SSE events, kept for the debug pane
+//
+// When a new OpenCode version renames a field, conch quietly stops reacting
+// to its events. The inspector keeps the last events exactly as they arrived
+// (whether or not conch understood them) so the debug pane can show them as
+// pretty-printed JSON, filtered by event type.
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Events kept; older ones are dropped.
+pub const CAPACITY: usize = 200;
+
+/// One SSE `data:` line as received.
+#[derive(Debug, Clone)]
+pub struct RawEvent {
+    /// The event's `type`, or "(invalid)" for data that isn't JSON.
+    pub event_type: String,
+    /// The data line, unparsed.
+    pub data: String,
+    /// Whether conch turned it into a server event.
+    pub handled: bool,
+    pub at: Instant,
+}
+
+impl RawEvent {
+    /// The data as indented JSON, or as received if it isn't JSON.
+    pub fn pretty(&self) -> String {
+        serde_json::from_str::<serde_json::Value>(&self.data)
+            .ok()
+            .and_then(|v| serde_json::to_string_pretty(&v).ok())
+            .unwrap_or_else(|| self.data.clone())
+    }
+}
+
+/// The most recent raw events, with a type filter.
+#[derive(Debug, Default)]
+pub struct Inspector {
+    events: VecDeque<RawEvent>,
+    /// Only events of this type are shown.
+    filter: Option<String>,
+}
+
+impl Inspector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep a data line, noting whether it was handled.
+    pub fn record(&mut self, data: &str, handled: bool) {
+        self.record_at(data, handled, Instant::now());
+    }
+
+    /// [`Inspector::record`] with an explicit arrival time.
+    pub fn record_at(&mut self, data: &str, handled: bool, at: Instant) {
+        let event_type = serde_json::from_str::<serde_json::Value>(data)
+            .ok()
+            .map(|v| v["type"].as_str().unwrap_or("(untyped)").to_string())
+            .unwrap_or_else(|| "(invalid)".into());
+        if self.events.len() == CAPACITY {
+            self.events.pop_front();
+        }
+        self.events.push_back(RawEvent {
+            event_type,
+            data: data.to_string(),
+            handled,
+            at,
+        });
+    }
+
+    /// Events passing the filter, newest first.
+    pub fn visible(&self) -> impl Iterator<Item = &RawEvent> {
+        self.events
+            .iter()
+            .rev()
+            .filter(|e| self.filter.as_ref().is_none_or(|t| e.event_type == *t))
+    }
+
+    /// Every event type seen, sorted.
+    pub fn types(&self) -> Vec<&str> {
+        let mut types: Vec<&str> = self.events.iter().map(|e| e.event_type.as_str()).collect();
+        types.sort_unstable();
+        types.dedup();
+        types
+    }
+
+    /// The type being shown, if filtered.
+    pub fn filter(&self) -> Option<&str> {
+        self.filter.as_deref()
+    }
+
+    /// Step the filter through "all" and each type seen: forward with
+    /// `step` 1, back with -1.
+    pub fn cycle_filter(&mut self, step: isize) {
+        let types = self.types();
+        // Position 0 is "all", then each type
+        let current = self
+            .filter
+            .as_deref()
+            .and_then(|t| types.iter().position(|x| *x == t))
+            .map_or(0, |i| i + 1);
+        let len = types.len() as isize + 1;
+        let next = (current as isize + step).rem_euclid(len) as usize;
+        self.filter = next.checked_sub(1).map(|i| types[i].to_string());
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_keeps_latest() {
+        let mut inspector = Inspector::new();
+        for i in 0..CAPACITY + 5 {
+            inspector.record(&format!(r#"{{"type":"t{}"}}"#, i), true);
+        }
+        assert_eq!(inspector.len(), CAPACITY);
+        let newest = inspector.visible().next().unwrap();
+        assert_eq!(newest.event_type, format!("t{}", CAPACITY + 4));
+        inspector.record("not json", false);
+        assert_eq!(inspector.visible().next().unwrap().event_type, "(invalid)");
+    }
+
+    #[test]
+    fn test_cycle_filter() {
+        let mut inspector = Inspector::new();
+        inspector.record(r#"{"type":"server.heartbeat"}"#, true);
+        inspector.record(r#"{"type":"session.status"}"#, true);
+        inspector.record(r#"{"type":"server.heartbeat"}"#, true);
+        assert_eq!(
+            inspector.types(),
+            vec!["server.heartbeat", "session.status"]
+        );
+
+        inspector.cycle_filter(1);
+        assert_eq!(inspector.filter(), Some("server.heartbeat"));
+        assert_eq!(inspector.visible().count(), 2);
+        inspector.cycle_filter(1);
+        assert_eq!(inspector.filter(), Some("session.status"));
+        inspector.cycle_filter(1);
+        assert_eq!(inspector.filter(), None);
+        assert_eq!(inspector.visible().count(), 3);
+        inspector.cycle_filter(-1);
+        assert_eq!(inspector.filter(), Some("session.status"));
+    }
+
+    #[test]
+    fn test_pretty() {
+        let mut inspector = Inspector::new();
+        inspector.record(r#"{"type":"x","properties":{"a":1}}"#, false);
+        let pretty = inspector.visible().next().unwrap().pretty();
+        assert!(pretty.contains("\n  \"properties\": {\n    \"a\": 1\n  }"));
+    }
+}
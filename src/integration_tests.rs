@@ -2,26 +2,137 @@
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, mpsc};
+    use std::time::{Duration, Instant};
+
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use ratatui::style::Color;
+
+    use conch::answer::{self, Answer};
+    use conch::audio::{AudioCapture, RecordingState};
+    use conch::autostop::{AutoStop, AutoStopConfig};
+    use conch::bus::Topic;
+    use conch::config::Config;
+    use conch::control::{Command, FocusAction, RecordAction};
+    use conch::conversation::ContextConfig;
+    use conch::dictation::{Dictation, DictationConfig, Join};
+    use conch::fake_audio::FakeSource;
+    use conch::focus::{FocusEntry, FocusStore};
+    use conch::git::GitStatus;
+    use conch::glyphs::Glyphs;
+    use conch::hallucination;
+    use conch::history::{HistoryEntry, TranscriptStatus, TranscriptStore};
+    use conch::input::TextInput;
+    use conch::keymap::Keymap;
+    use conch::language;
+    use conch::layout::{Arrangement, Panel};
+    use conch::hooks::HookEvent;
+    use conch::mock::{self, MockServer};
+    use conch::resources::Snapshot;
+    use conch::route::Target;
+    use conch::send::ReviewReason;
+    use conch::session::SessionHandle;
+    use conch::stt::{Transcriber, Transcript};
+    use conch::transport::{ConnectionStatus, OpenCodeClient, ServerEvent, SessionInfo, ToolEvent};
+    use conch::vocabulary::Vocabulary;
+
+    use crate::test_utils::snapshots::{assert_snapshot, buffer_to_string};
+    use crate::{
+        App, AppMessage, EventSource, JobKind, Keys, Screen, accept_queued_transcripts,
+        answer_prompt, cancel_queued, change_directory, check_sessions, confirm_prompt,
+        forget_focus, handle_input_key, handle_palette_key, handle_search_key, handle_space,
+        handle_vim_key, hold_prompt, open_input, open_palette, pin_focus, prompt_context,
+        reload_config, render, route_prompt, run_app, run_control, session_lost, settle_dictation,
+        space_pressed, stop_tap_due, task_ended, undo_correction,
+    };
 
     // ===== Full Utterance Flow Tests =====
 
-    #[test]
-    fn test_complete_utterance_flow() {
-        // Test: Complete flow from audio capture to focus update
+    /// Wait up to five seconds for `done`.
+    async fn wait_for(done: impl Fn() -> bool) {
+        for _ in 0..100 {
+            if done() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("timed out waiting for the app");
+    }
+
+    fn press(c: char) -> Event {
+        Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_complete_utterance_flow() {
+        // Test: Complete flow from audio capture to focus update, driving the
+        // whole app: fake audio, the mock OpenCode server, a test terminal
         // Plan requirement: "Event Flow for a Single Utterance" (steps 1-11)
-        // 1. Push-to-talk activates
-        // 2. Audio flows to ring buffer
-        // 3. FFT reads for visualization
-        // 4. Audio accumulates for Whisper
-        // 5. Push-to-talk releases
-        // 6. Whisper transcribes
-        // 7. Transcript displayed
-        // 8. Focus context retrieved
-        // 9. Sent to OpenCode
-        // 10. Event received via SSE
-        // 11. Focus updated
-        todo!("Simulate complete utterance flow through all modules");
+        let server = MockServer::start().await.unwrap();
+        let audio = AudioCapture::with_source(Box::new(FakeSource::tone(440.0))).unwrap();
+        let transcriber = Arc::new(Transcriber::fixed(mock::PHRASE));
+        let mut config = Config::default();
+        config.history.enabled = false;
+        config.cues.enabled = false;
+        config.send.auto = true;
+        config.send.cancel_secs = 0.0;
+        config.control.enabled = false;
+        let events = EventSource::Live {
+            url: server.url().to_string(),
+            sessions: Vec::new(),
+            directory: std::env::current_dir().unwrap(),
+            capture: None,
+        };
+        let (keys, rx) = mpsc::channel();
+        let mut screen = Screen {
+            terminal: Terminal::new(TestBackend::new(100, 30)).unwrap(),
+            keys: Keys::Scripted(rx),
+        };
+
+        let driver = {
+            let server = server.clone();
+            tokio::spawn(async move {
+                // 1-5. Push-to-talk once the session is up, released half a
+                // second of tone later
+                wait_for(|| server.subscribers() > 0).await;
+                keys.send(press(' ')).unwrap();
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                keys.send(press(' ')).unwrap();
+                // 6-9. Transcribed, then auto-sent with the focus context
+                wait_for(|| !server.prompts().is_empty()).await;
+                // 10. The scripted turn arrives over SSE
+                wait_for(|| server.turns() > 0).await;
+                tokio::time::sleep(mock::STEP * 5).await;
+                keys.send(press('q')).unwrap();
+            })
+        };
+        let config_path = Path::new("/nonexistent/conch.toml");
+        run_app(
+            &mut screen,
+            &audio,
+            &transcriber,
+            events,
+            &config,
+            config_path,
+            None,
+        )
+        .await
+        .unwrap();
+        driver.await.unwrap();
+
+        let prompts = server.prompts();
+        assert_eq!(prompts.len(), 1);
+        assert!(prompts[0].ends_with(mock::PHRASE), "{:?}", prompts[0]);
+        // 7. Transcript displayed, 11. focus updated
+        let shown = buffer_to_string(screen.terminal.backend().buffer());
+        assert!(shown.contains(mock::PHRASE));
+        assert!(shown.contains(mock::REPLY_FILE));
+        // OpenCode went idle, so the prompt is marked answered, with its time
+        let line = shown.lines().find(|l| l.contains(mock::PHRASE)).unwrap();
+        assert!(line.contains(&format!("{} \u{2713} ", mock::PHRASE)), "{line}");
     }
 
     #[test]
@@ -239,32 +350,1928 @@ mod tests {
 
     // ===== TUI Rendering Integration Tests =====
 
+    /// Fixed terminal size used for all TUI snapshots.
+    const SNAPSHOT_WIDTH: u16 = 80;
+    const SNAPSHOT_HEIGHT: u16 = 28;
+
+    /// Render `app` into an off-screen terminal and return the screen as text.
+    fn render_to_string(app: &App) -> String {
+        let backend = TestBackend::new(SNAPSHOT_WIDTH, SNAPSHOT_HEIGHT);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal.draw(|f| render(f, app)).unwrap();
+        buffer_to_string(terminal.backend().buffer())
+    }
+
+    /// An app connected to OpenCode with a short session history.
+    fn scripted_app() -> App {
+        let mut app = App::new(16000);
+        app.connection_status = ConnectionStatus::Connected;
+        app.session_slug = Some("brave-otter".into());
+        app.transcripts
+            .push(HistoryEntry::new("show me what's in here", None));
+        app.focus
+            .append(FocusEntry::Directory(PathBuf::from("src")));
+        app.focus
+            .append(FocusEntry::File(PathBuf::from("src/main.rs")));
+        app
+    }
+
     #[test]
     fn test_tui_displays_all_components() {
         // Test: TUI shows spectrogram, transcript, and focus stack
         // Plan requirement: Complete TUI layout
-        todo!("Verify TUI renders all three main components simultaneously");
+        let app = scripted_app();
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Conch Voice Client"));
+        assert!(screen.contains("show me what's in here"));
+        assert!(screen.contains("Status"));
+        assert!(screen.contains("Focus Stack"));
+        assert!(screen.contains("main.rs"));
+        assert_snapshot("tui_idle_with_focus", &screen);
     }
 
     #[test]
     fn test_tui_updates_on_state_changes() {
         // Test: TUI re-renders when state changes
         // Plan requirement: Reactive UI
-        todo!("Change focus, verify TUI updates; start recording, verify spectrogram appears");
+        let mut app = scripted_app();
+        let before = render_to_string(&app);
+
+        app.focus.move_down();
+        let after_focus = render_to_string(&app);
+        assert_ne!(before, after_focus);
+        assert!(after_focus.contains("\u{25B8} \u{1F4C1} src"));
+
+        app.state = RecordingState::Recording;
+        app.waveform_bars = vec![0.5; SNAPSHOT_WIDTH as usize];
+        let recording = render_to_string(&app);
+        assert_ne!(after_focus, recording);
+        assert!(recording.contains('\u{28FF}'), "waveform should fill braille cells");
+    }
+
+    #[test]
+    fn test_configurable_layout() {
+        // Test: [layout] can hide the waveform, grow the transcript strip,
+        // and put the transcripts beside the focus stack
+        let mut app = scripted_app();
+        app.transcripts
+            .push(HistoryEntry::new("now open the config", None));
+        app.state = RecordingState::Recording;
+        app.waveform_bars = vec![0.5; SNAPSHOT_WIDTH as usize];
+        let screen = render_to_string(&app);
+        assert!(!screen.contains("show me what's in here"));
+
+        app.layout.transcript_height = 3;
+        let screen = render_to_string(&app);
+        assert!(screen.contains("show me what's in here"));
+        assert!(screen.contains("now open the config"));
+
+        app.show_waveform = false;
+        let screen = render_to_string(&app);
+        assert!(!screen.contains('\u{28FF}'), "waveform should be hidden");
+
+        app.layout.arrangement = Arrangement::SideBySide;
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Transcripts"));
+        assert!(screen.contains("show me what's in here"));
+        assert!(screen.contains("Focus Stack"));
+        assert_snapshot("tui_side_by_side", &screen);
+    }
+
+    #[test]
+    fn test_file_preview() {
+        // Test: A File entry under the pointer gets a preview, starting at
+        // the region OpenCode last edited
+        let dir = std::env::temp_dir().join(format!("conch_tui_preview_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("lib.rs");
+        let text: String = (1..=30).map(|i| format!("// line {i}\n")).collect();
+        std::fs::write(&path, text).unwrap();
+        let mut app = scripted_app();
+        app.show_waveform = false;
+        app.focus.append(FocusEntry::File(path.clone()));
+        app.refresh_preview();
+        let screen = render_to_string(&app);
+        assert!(screen.contains("1 // line 1"), "{screen}");
+
+        let edit = ToolEvent {
+            call_id: None,
+            session_id: None,
+            message_id: None,
+            tool: "edit".into(),
+            input: serde_json::json!({ "filePath": path, "newString": "// line 20" }),
+            state: "completed".into(),
+            metadata: serde_json::Value::Null,
+        };
+        app.last_edits.record(&edit);
+        app.refresh_preview();
+        let screen = render_to_string(&app);
+        assert!(screen.contains("(last edit)"));
+        assert!(screen.contains("20 // line 20"));
+        assert!(!screen.contains("1 // line 1 "));
+
+        // Other entries have no preview
+        app.focus.move_down();
+        app.focus.move_down();
+        assert_eq!(
+            app.focus.current_entry(),
+            Some(&FocusEntry::Directory(PathBuf::from("src")))
+        );
+        app.refresh_preview();
+        assert!(app.preview.is_none());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_ascii_display() {
+        // Test: With `unicode = "ascii"` the whole screen is plain ASCII
+        let mut app = scripted_app();
+        app.glyphs = Glyphs::ASCII;
+        app.state = RecordingState::Recording;
+        app.waveform_bars = vec![0.5; SNAPSHOT_WIDTH as usize];
+        let screen = render_to_string(&app);
+        assert!(screen.contains("> [F] main.rs"));
+        assert!(screen.contains("* Recording... press [Space] to stop"));
+        // Only the borders are drawn outside ASCII, in box-drawing characters
+        let box_drawing = '\u{2500}'..='\u{257F}';
+        assert!(
+            screen
+                .chars()
+                .all(|c| c.is_ascii() || box_drawing.contains(&c)),
+            "{screen}"
+        );
     }
 
     #[test]
     fn test_recording_state_indicator() {
         // Test: TUI shows recording state clearly
         // Plan requirement: "Add status indicators (recording state...)"
-        todo!("Start recording, verify 'Recording' or 'Listening' indicator appears");
+        let mut app = scripted_app();
+        app.state = RecordingState::Recording;
+        app.waveform_bars = (0..SNAPSHOT_WIDTH)
+            .map(|i| (i % 10) as f32 / 10.0)
+            .collect();
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Recording... press [Space] to stop"));
+        // Esc aborts the recording instead of quitting
+        assert!(screen.contains("[Esc] Abort"));
+        assert!(!screen.contains("[q/Esc] Quit"));
+        assert_snapshot("tui_recording", &screen);
+    }
+
+    #[test]
+    fn test_recording_timer_and_buffer_usage() {
+        // Test: While recording, the status title shows the elapsed time and
+        // how much of the 60s buffer is used, warning once it wraps
+        let mut app = scripted_app();
+        app.state = RecordingState::Recording;
+        app.recording_started = Some(std::time::Instant::now() - Duration::from_secs(72));
+        app.buffer_usage = 0.45;
+        let screen = render_to_string(&app);
+        assert!(screen.contains("1:12 \u{b7} buffer 45%"));
+
+        app.buffer_usage = 1.2;
+        let screen = render_to_string(&app);
+        assert!(screen.contains("1:12 \u{b7} buffer full, start lost"));
+        assert_snapshot("tui_recording_buffer_full", &screen);
+
+        app.state = RecordingState::Idle;
+        assert!(!render_to_string(&app).contains("buffer"));
+
+        app.state = RecordingState::Processing;
+        app.waveform_bars.clear();
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Transcribing..."));
+    }
+
+    #[test]
+    fn test_dead_mic_is_reported() {
+        // Test: A mic delivering only zeros is seen by the capture, and the
+        // status title says so while recording
+        let silence = FakeSource::new(vec![0.0; 1600], 16000);
+        let audio = AudioCapture::with_source(Box::new(silence)).unwrap();
+        audio.start_recording();
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(audio.dead_for() >= Duration::from_millis(100));
+
+        let mut app = scripted_app();
+        app.state = RecordingState::Recording;
+        app.recording_started = Some(Instant::now() - Duration::from_secs(5));
+        app.mic_dead = true;
+        assert!(render_to_string(&app).contains("0:05 \u{b7} \u{26A0} mic silent"));
+    }
+
+    #[test]
+    fn test_waveform_held_after_recording() {
+        // Test: The recording's waveform stays up while it's transcribed and
+        // pending, and goes once the prompt is sent
+        let mut app = scripted_app();
+        app.state = RecordingState::Processing;
+        let blank = render_to_string(&app);
+        let speech: Vec<f32> = (0..1024).map(|i| (i % 64) as f32 / 64.0).collect();
+        app.utterance = Some(speech);
+        let held = render_to_string(&app);
+        assert_ne!(held, blank);
+
+        app.state = RecordingState::Idle;
+        app.prompt_pending = Some("run the tests".into());
+        assert_ne!(render_to_string(&app), blank);
+        confirm_prompt(&mut app);
+        assert!(app.utterance.is_none());
     }
 
     #[test]
     fn test_connection_status_indicator() {
         // Test: TUI shows OpenCode connection status
         // Plan requirement: "OpenCode connection status" indicator
-        todo!("Disconnect from OpenCode, verify TUI shows disconnected status");
+        let mut app = scripted_app();
+        app.connection_status = ConnectionStatus::Disconnected;
+        app.session_slug = None;
+        let screen = render_to_string(&app);
+        assert!(screen.contains("[OC: disconnected]"));
+        assert_snapshot("tui_disconnected", &screen);
+
+        app.connection_status = ConnectionStatus::Reconnecting;
+        assert!(render_to_string(&app).contains("[OC: reconnecting]"));
+
+        app.connection_status = ConnectionStatus::Connected;
+        app.opencode_busy = true;
+        assert!(render_to_string(&app).contains("[OC: busy]"));
+    }
+
+    #[test]
+    fn test_busy_spinner() {
+        // Test: A busy session shows how long it has been busy and the tool it's running
+        let mut app = scripted_app();
+        app.glyphs = Glyphs::ASCII;
+        app.opencode_busy = true;
+        app.busy_since = Some(Instant::now() - Duration::from_secs(65));
+        let screen = render_to_string(&app);
+        assert!(screen.contains(" busy 1:05]"), "{screen}");
+
+        app.activity.record(&ToolEvent {
+            call_id: Some("c1".into()),
+            session_id: None,
+            message_id: None,
+            tool: "bash".into(),
+            input: serde_json::json!({"command": "cargo test"}),
+            state: "running".into(),
+            metadata: serde_json::Value::Null,
+        });
+        let screen = render_to_string(&app);
+        assert!(screen.contains(" busy 1:05 | bash]"));
+    }
+
+    #[test]
+    fn test_connection_health_indicator() {
+        // Test: Heartbeat age and reconnects show next to the status, yellow once overdue
+        let mut app = scripted_app();
+        app.connection_status = ConnectionStatus::Connected;
+        let ago = |secs| Instant::now() - Duration::from_secs(secs);
+        app.heartbeats.connected(ago(200));
+        app.heartbeats.connected(ago(100));
+        app.heartbeats.heartbeat(ago(8));
+        app.heartbeats.heartbeat(ago(3));
+        let (screen, color) = indicator(&app);
+        assert!(screen.contains("[OC: connected \u{00B7} 3s ago \u{00B7} 1 reconnect]"));
+        assert_eq!(color, app.theme.success);
+
+        // Heartbeats every 5s, none for 30s
+        app.heartbeats.connected(ago(40));
+        app.heartbeats.heartbeat(ago(35));
+        app.heartbeats.heartbeat(ago(30));
+        let (screen, color) = indicator(&app);
+        assert!(screen.contains("[OC: connected \u{00B7} 30s ago \u{00B7} 2 reconnects]"));
+        assert_eq!(color, app.theme.warning);
+    }
+
+    /// The rendered screen and the color of the connection indicator.
+    fn indicator(app: &App) -> (String, Color) {
+        let backend = TestBackend::new(SNAPSHOT_WIDTH, SNAPSHOT_HEIGHT);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal.draw(|f| render(f, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let color = buffer
+            .content()
+            .windows(4)
+            .find(|cells| cells.iter().map(|c| c.symbol()).collect::<String>() == "[OC:")
+            .map(|cells| cells[0].fg)
+            .unwrap();
+        (buffer_to_string(buffer), color)
+    }
+
+    #[test]
+    fn test_git_status_widget() {
+        // Test: The title bar shows the branch and working tree status
+        let mut app = scripted_app();
+        app.session_slug = None;
+        app.git = Some(GitStatus {
+            branch: Some("main".into()),
+            changed: 2,
+            ahead: 1,
+            ..GitStatus::default()
+        });
+        let screen = render_to_string(&app);
+        assert!(screen.contains("main \u{b7} 2 changed \u{b7} 1 ahead"));
+
+        app.glyphs = Glyphs::ASCII;
+        app.git = Some(GitStatus::parse("# branch.head main\n"));
+        assert!(render_to_string(&app).contains("[[B] main]"));
+    }
+
+    #[test]
+    fn test_edit_diff_in_activity() {
+        // Test: A completed edit shows its diff under its activity line
+        let mut app = scripted_app();
+        let mut edit = ToolEvent {
+            call_id: Some("e1".into()),
+            session_id: None,
+            message_id: None,
+            tool: "edit".into(),
+            input: serde_json::json!({
+                "filePath": "src/lib.rs",
+                "oldString": "x = 1",
+                "newString": "x = 2",
+            }),
+            state: "running".into(),
+            metadata: serde_json::Value::Null,
+        };
+        app.activity.record(&edit);
+        assert!(!render_to_string(&app).contains("+x = 2"));
+
+        edit.state = "completed".into();
+        app.activity.record(&edit);
+        let screen = render_to_string(&app);
+        assert!(screen.contains("edit src/lib.rs \u{2713}"));
+        assert!(screen.contains("-x = 1"));
+        assert!(screen.contains("+x = 2"));
+    }
+
+    #[test]
+    fn test_dictation_document() {
+        // Test: A dictation shows its growing document, then becomes the
+        // pending prompt, its sentences capitalized and ended
+        let mut app = scripted_app();
+        let mut dictation = Dictation::new(DictationConfig::default());
+        dictation.segment_sent(Join::Sentence);
+        dictation.segment_sent(Join::Sentence);
+        dictation.segment_done(Some("The cache is keyed by path."));
+        app.state = RecordingState::Recording;
+        app.dictation = Some(dictation);
+        let screen = render_to_string(&app);
+        assert!(screen.contains("The cache is keyed by path."));
+        assert!(screen.contains("Dictating"));
+        assert!(screen.contains("2 segments"));
+
+        let dictation = app.dictation.as_mut().unwrap();
+        dictation.finish();
+        dictation.segment_done(Some("entries expire after an hour"));
+        settle_dictation(&mut app);
+        assert!(app.dictation.is_none());
+        assert_eq!(
+            app.prompt_pending.as_deref(),
+            Some("The cache is keyed by path. Entries expire after an hour.")
+        );
+        assert_eq!(app.send_deadline, None, "never auto-sent");
+    }
+
+    #[test]
+    fn test_caption_strip() {
+        // Test: The caption strip shows the partial transcript above the panes
+        let mut app = scripted_app();
+        let recording = app.captions.start();
+        app.captions.partial(recording, "rename the parser module");
+        assert!(!render_to_string(&app).contains("rename the parser"));
+
+        app.caption.enabled = true;
+        let screen = render_to_string(&app);
+        let lines: Vec<&str> = screen.lines().collect();
+        assert!(lines[1].contains("rename the parser module"));
+        assert!(!lines[0].contains("rename"));
+    }
+
+    #[test]
+    fn test_hands_free_mode() {
+        // Test: Hands-free mode shows in the status pane
+        let mut app = scripted_app();
+        assert!(!render_to_string(&app).contains("hands-free"));
+
+        app.hands_free.set_enabled(true);
+        assert!(render_to_string(&app).contains("Status (hands-free)"));
+    }
+
+    #[test]
+    fn test_hot_mic_indicator() {
+        // Test: An open mic is always visible while wake word listening is on
+        let mut app = scripted_app();
+        assert!(!render_to_string(&app).contains("hot mic"));
+
+        app.wake_phrase = Some("hey conch".into());
+        app.hot_mic = true;
+        let screen = render_to_string(&app);
+        assert!(screen.contains("hot mic"));
+        assert!(screen.contains("say \"hey conch\" to record"));
+        assert_snapshot("tui_hot_mic", &screen);
+    }
+
+    #[test]
+    fn test_recording_stop_countdown() {
+        // Test: The last seconds before the max-duration stop are counted down
+        let mut app = scripted_app();
+        app.state = RecordingState::Recording;
+        assert!(!render_to_string(&app).contains("stopping in"));
+
+        app.stop_countdown = Some(4);
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Recording... stopping in 4s"));
+    }
+
+    #[test]
+    fn test_typed_prompt_input() {
+        // Test: A typed prompt goes through the same pending/confirm flow as speech
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut app = scripted_app();
+        let before = app.transcripts.len();
+        app.input = Some(TextInput::new());
+        for c in "run the testz".chars() {
+            handle_input_key(&mut app, key(KeyCode::Char(c)));
+        }
+        handle_input_key(&mut app, key(KeyCode::Backspace));
+        handle_input_key(&mut app, key(KeyCode::Char('s')));
+        let screen = render_to_string(&app);
+        assert!(screen.contains("run the tests"));
+        assert!(screen.contains("Typing a prompt"));
+        assert!(screen.contains("[Esc] Cancel"));
+        assert_snapshot("tui_typed_input", &screen);
+
+        handle_input_key(&mut app, key(KeyCode::Enter));
+        assert!(app.input.is_none());
+        assert_eq!(app.prompt_pending.as_deref(), Some("run the tests"));
+        assert_eq!(app.transcripts.len(), before + 1);
+        assert!(render_to_string(&app).contains("run the tests [pending]"));
+
+        // Esc and empty input leave nothing pending
+        app.prompt_pending = None;
+        app.input = Some(TextInput::new());
+        handle_input_key(&mut app, key(KeyCode::Char('x')));
+        handle_input_key(&mut app, key(KeyCode::Esc));
+        assert!(app.input.is_none());
+        app.input = Some(TextInput::new());
+        handle_input_key(&mut app, key(KeyCode::Enter));
+        assert!(app.prompt_pending.is_none());
+        assert_eq!(app.transcripts.len(), before + 1);
+    }
+
+    #[test]
+    fn test_prompt_recall() {
+        // Test: Up recalls sent prompts into the input line, also from a pending prompt
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut app = scripted_app();
+        for text in ["run the tests", "open the config"] {
+            let mut entry = HistoryEntry::new(text, None);
+            entry.status = TranscriptStatus::Sent;
+            app.transcripts.push(entry);
+        }
+        open_input(&mut app, "");
+        handle_input_key(&mut app, key(KeyCode::Char('x')));
+        handle_input_key(&mut app, key(KeyCode::Up));
+        handle_input_key(&mut app, key(KeyCode::Up));
+        assert_eq!(app.input.as_ref().unwrap().text(), "run the tests");
+        handle_input_key(&mut app, key(KeyCode::Down));
+        handle_input_key(&mut app, key(KeyCode::Down));
+        assert_eq!(app.input.as_ref().unwrap().text(), "x", "draft is kept");
+
+        // Editing a recalled prompt replaces the pending one
+        handle_input_key(&mut app, key(KeyCode::Esc));
+        app.prompt_pending = Some("fix the bug".into());
+        app.transcripts.push(HistoryEntry::new("fix the bug", None));
+        open_input(&mut app, "fix the bug");
+        handle_input_key(&mut app, key(KeyCode::Up));
+        for c in " again".chars() {
+            handle_input_key(&mut app, key(KeyCode::Char(c)));
+        }
+        handle_input_key(&mut app, key(KeyCode::Enter));
+        assert_eq!(app.prompt_pending.as_deref(), Some("open the config again"));
+        let statuses: Vec<TranscriptStatus> = app.transcripts.iter().map(|e| e.status).collect();
+        assert_eq!(
+            statuses[statuses.len() - 2..],
+            [TranscriptStatus::Discarded, TranscriptStatus::Pending]
+        );
+    }
+
+    #[test]
+    fn test_vim_keymap() {
+        // Test: The vim preset browses, searches and selects transcripts
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut app = scripted_app();
+        for text in ["run the tests", "open the config"] {
+            app.transcripts.push(HistoryEntry::new(text, None));
+        }
+        app.keymap = Keymap::Vim;
+        for c in "kk".chars() {
+            assert!(handle_vim_key(&mut app, key(KeyCode::Char(c))));
+        }
+        let screen = render_to_string(&app);
+        assert!(screen.contains("\u{25B8} run the tests"));
+        assert!(screen.contains("Transcript 2/3"));
+        assert!(screen.contains("[q] Quit"));
+
+        for c in "ggv".chars() {
+            handle_vim_key(&mut app, key(KeyCode::Char(c)));
+        }
+        handle_vim_key(&mut app, key(KeyCode::Char('j')));
+        assert_eq!(app.transcript_cursor.selection(3), Some(0..=1));
+        assert!(render_to_string(&app).contains("2 selected"));
+        handle_vim_key(&mut app, key(KeyCode::Esc));
+        assert!(!app.transcript_cursor.is_selecting());
+
+        handle_vim_key(&mut app, key(KeyCode::Char('G')));
+        assert_eq!(app.transcript_cursor.position(3), None);
+        handle_vim_key(&mut app, key(KeyCode::Char('/')));
+        for c in "SHOW".chars() {
+            handle_search_key(&mut app, key(KeyCode::Char(c)));
+        }
+        assert!(render_to_string(&app).contains("/SHOW"));
+        handle_search_key(&mut app, key(KeyCode::Enter));
+        assert_eq!(app.transcript_cursor.position(3), Some(0));
+        handle_vim_key(&mut app, key(KeyCode::Char('n')));
+        assert_eq!(
+            app.notices.toast().unwrap().message,
+            "No transcript matches \"SHOW\""
+        );
+
+        // Esc goes back to the live view instead of quitting
+        assert!(handle_vim_key(&mut app, key(KeyCode::Esc)));
+        assert_eq!(app.transcript_cursor.position(3), None);
+    }
+
+    #[test]
+    fn test_snippet_palette() {
+        // Test: A snippet picked from the palette becomes the pending prompt,
+        // with its placeholders filled from the focus stack
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut app = scripted_app();
+        app.snippets.insert("review".into(), "Review {file} for bugs".into());
+        app.snippets.insert("explain".into(), "Explain {focus}".into());
+        app.snippets.insert("blame".into(), "Who changed {commit}?".into());
+        assert!(render_to_string(&app).contains("[p] Palette"));
+
+        open_palette(&mut app);
+        handle_palette_key(&mut app, key(KeyCode::Char('i')));
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Command Palette"));
+        assert!(screen.contains("explain  Explain {focus}"));
+        assert!(screen.contains("review  Review {file} for bugs"));
+        assert!(!screen.contains("blame"));
+        assert_snapshot("tui_palette", &screen);
+
+        handle_palette_key(&mut app, key(KeyCode::Down));
+        handle_palette_key(&mut app, key(KeyCode::Enter));
+        assert!(app.palette.is_none());
+        assert_eq!(
+            app.prompt_pending.as_deref(),
+            Some("Review src/main.rs for bugs")
+        );
+
+        // A placeholder the focus stack can't fill is reported, not sent
+        app.prompt_pending = None;
+        open_palette(&mut app);
+        handle_palette_key(&mut app, key(KeyCode::Enter));
+        assert!(app.prompt_pending.is_none());
+        assert!(render_to_string(&app).contains("Snippet 'blame': nothing in focus for {commit}"));
+    }
+
+    #[test]
+    fn test_tool_activity_feed() {
+        // Test: Tool calls show up once each, updated in place as they progress
+        let mut app = scripted_app();
+        let tool = |id: &str, tool: &str, state: &str, input| ToolEvent {
+            call_id: Some(id.into()),
+            session_id: None,
+            message_id: None,
+            tool: tool.into(),
+            input,
+            state: state.into(),
+            metadata: serde_json::Value::Null,
+        };
+        let test = serde_json::json!({"command": "cargo test"});
+        app.activity.record(&tool(
+            "c1",
+            "read",
+            "completed",
+            serde_json::json!({"filePath": "src/main.rs"}),
+        ));
+        app.activity.record(&tool("c2", "bash", "running", test.clone()));
+        let screen = render_to_string(&app);
+        assert!(screen.contains("read src/main.rs \u{2713}"));
+        assert!(screen.contains("bash cargo test \u{2026} running"));
+        assert!(screen.contains("Activity (1 running)"));
+        assert_snapshot("tui_activity_feed", &screen);
+
+        app.activity.record(&tool("c2", "bash", "completed", test));
+        let screen = render_to_string(&app);
+        assert!(screen.contains("bash cargo test \u{2713}"));
+        assert!(!screen.contains("running"));
+
+        // The newest calls stay visible once the pane is full
+        for i in 0..10 {
+            app.activity.record(&tool(
+                &format!("r{i}"),
+                "read",
+                "completed",
+                serde_json::json!({"filePath": format!("file{i}.rs")}),
+            ));
+        }
+        let screen = render_to_string(&app);
+        assert!(screen.contains("file9.rs"));
+        assert!(!screen.contains("file0.rs"));
+    }
+
+    #[test]
+    fn test_low_sample_rate_warning() {
+        // Test: A mic in Bluetooth hands-free mode is flagged in the title bar
+        let mut app = App::new(8000);
+        app.connection_status = ConnectionStatus::Connected;
+        let screen = render_to_string(&app);
+        assert!(screen.contains("8000 Hz mic"));
+        assert!(!render_to_string(&scripted_app()).contains("Hz mic"));
+    }
+
+    #[test]
+    fn test_record_while_transcribing() {
+        // Test: A new recording can start while earlier ones transcribe, and
+        // their transcripts become the pending prompt one at a time, in order
+        let mut app = scripted_app();
+        app.state = RecordingState::Recording;
+        app.transcribing = 1;
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Recording... press [Space] to stop (1 still transcribing)"));
+
+        let config = Config::default();
+        for text in ["run the tests", "then fix the failures"] {
+            app.transcript_queue.push_back(Transcript {
+                text: text.into(),
+                confidence: 0.9,
+            });
+        }
+        accept_queued_transcripts(&mut app, &config, Duration::ZERO);
+        assert_eq!(app.prompt_pending.as_deref(), Some("run the tests"));
+        assert_eq!(app.transcript_queue.len(), 1);
+        app.prompt_pending = None;
+        accept_queued_transcripts(&mut app, &config, Duration::ZERO);
+        assert_eq!(app.prompt_pending.as_deref(), Some("then fix the failures"));
+        assert_eq!(
+            app.transcripts.last().unwrap().text,
+            "then fix the failures"
+        );
+
+        // Processing lasts until the last recording is transcribed
+        app.settle_state();
+        assert_eq!(app.state, RecordingState::Recording);
+        app.state = RecordingState::Processing;
+        app.settle_state();
+        assert_eq!(app.state, RecordingState::Processing);
+        app.transcribing = 0;
+        app.settle_state();
+        assert_eq!(app.state, RecordingState::Idle);
+    }
+
+    #[test]
+    fn test_session_tabs() {
+        // Test: Events are routed to their session's tab, and switching tabs
+        // swaps transcripts, focus and activity
+        let mut app = scripted_app();
+        app.attach_session("ses_a".into(), Some("brave-otter".into()), true, &[]);
+        app.attach_session("ses_b".into(), Some("quiet-heron".into()), false, &[]);
+        assert_eq!(app.tabs.len(), 2);
+        assert_eq!(app.session_id.as_deref(), Some("ses_a"));
+
+        let read = ToolEvent {
+            call_id: Some("c1".into()),
+            session_id: Some("ses_b".into()),
+            message_id: None,
+            tool: "read".into(),
+            input: serde_json::json!({"filePath": "docs/notes.md"}),
+            state: "completed".into(),
+            metadata: serde_json::Value::Null,
+        };
+        app.update_background_tab(&ServerEvent::Tool(read), &[]);
+        app.update_background_tab(
+            &ServerEvent::SessionStatus {
+                session_id: "ses_b".into(),
+                busy: true,
+            },
+            &[],
+        );
+        assert!(app.activity.is_empty());
+        assert!(!app.opencode_busy);
+        let screen = render_to_string(&app);
+        assert!(screen.contains("1:brave-otter"));
+        assert!(screen.contains("2:quiet-heron*"));
+
+        app.switch_tab(1);
+        assert_eq!(app.session_id.as_deref(), Some("ses_b"));
+        assert!(app.opencode_busy);
+        assert!(app.busy_since.is_some());
+        assert!(app.transcripts.is_empty());
+        // Keep the spinner and busy time out of the snapshot
+        app.busy_since = None;
+        let screen = render_to_string(&app);
+        assert!(screen.contains("notes.md"));
+        assert!(!screen.contains("show me what's in here"));
+        assert_snapshot("tui_session_tabs", &screen);
+
+        // A pending prompt belongs to the session in front
+        app.prompt_pending = Some("summarize the notes".into());
+        app.switch_tab(0);
+        assert_eq!(app.tabs.active(), 1);
+        app.prompt_pending = None;
+
+        app.switch_tab(0);
+        assert_eq!(app.transcripts[0].text, "show me what's in here");
+        assert!(app.focus.current_entry().is_some());
+        app.close_tab();
+        assert_eq!(app.tabs.len(), 1);
+        assert_eq!(app.session_id.as_deref(), Some("ses_b"));
+        assert!(!render_to_string(&app).contains("1:quiet-heron"));
+    }
+
+    #[test]
+    fn test_pin_and_forget_focus() {
+        // Test: A pinned entry is marked and stays in the context; a
+        // forgotten one leaves the stack, isn't brought back by OpenCode, and
+        // is still forgotten when the session is attached again
+        let dir = std::env::temp_dir().join(format!("conch_pins_{}", std::process::id()));
+        let store = || Some(FocusStore::new(dir.join("focus.json")));
+        let main_rs = FocusEntry::File(PathBuf::from("src/main.rs"));
+        let src = FocusEntry::Directory(PathBuf::from("src"));
+        let mut app = scripted_app();
+        app.focus_marks = store();
+        app.attach_session("ses_a".into(), Some("brave-otter".into()), true, &[]);
+        app.focus.move_down();
+        pin_focus(&mut app);
+        assert_eq!(app.notices.toast().unwrap().message, "Pinned src");
+        assert!(render_to_string(&app).contains('\u{1F4CC}'));
+
+        app.focus.move_up();
+        forget_focus(&mut app);
+        assert_eq!(app.notices.toast().unwrap().message, "Forgot src/main.rs");
+        app.focus.append(main_rs.clone());
+        assert_eq!(app.focus.entries(), vec![src.clone()]);
+        let context = prompt_context(&app).unwrap();
+        assert!(!context.contains("main.rs"), "{context}");
+
+        let mut later = scripted_app();
+        later.focus_marks = store();
+        later.attach_session("ses_a".into(), None, true, &[]);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(!later.focus.entries().contains(&main_rs));
+        assert!(later.focus.is_pinned(&src));
+    }
+
+    #[test]
+    fn test_deleted_sessions() {
+        // Test: A deleted background session loses its tab, the pending
+        // prompt follows the front tab to its neighbour, and deleting the
+        // last session holds prompts until a new one is attached
+        let mut app = scripted_app();
+        app.attach_session("ses_a".into(), Some("brave-otter".into()), true, &[]);
+        app.attach_session("ses_b".into(), Some("quiet-heron".into()), false, &[]);
+        app.attach_session("ses_c".into(), Some("calm-lynx".into()), false, &[]);
+        app.session_deleted("ses_c");
+        assert_eq!(app.tabs.len(), 2);
+        assert_eq!(app.session_id.as_deref(), Some("ses_a"));
+
+        app.transcripts
+            .push(HistoryEntry::new("run the tests", Some("ses_a".into())));
+        app.prompt_pending = Some("run the tests".into());
+        app.session_deleted("ses_a");
+        assert_eq!(app.tabs.len(), 1);
+        assert_eq!(app.session_id.as_deref(), Some("ses_b"));
+        assert_eq!(app.prompt_pending.as_deref(), Some("run the tests"));
+        let last = app.transcripts.last().unwrap();
+        assert_eq!(last.text, "run the tests");
+        assert_eq!(last.session_id.as_deref(), Some("ses_b"));
+
+        app.session_deleted("ses_b");
+        assert!(app.tabs.is_empty());
+        assert_eq!(app.session_id, None);
+        assert_eq!(app.transcripts.len(), 1);
+        let screen = render_to_string(&app);
+        assert!(screen.contains("(no session)"));
+        assert!(screen.contains("press [t]"));
+        confirm_prompt(&mut app);
+        assert_eq!(app.prompt_pending.as_deref(), Some("run the tests"));
+
+        app.attach_session("ses_d".into(), None, true, &[]);
+        assert_eq!(app.session_id.as_deref(), Some("ses_d"));
+        assert!(!render_to_string(&app).contains("(no session)"));
+        assert_eq!(app.transcripts.last().unwrap().text, "run the tests");
+    }
+
+    #[test]
+    fn test_session_error_fails_prompt() {
+        // Test: An error in a background session is shown with the session's
+        // name and fails the prompt it was working on
+        let mut app = scripted_app();
+        app.attach_session("ses_a".into(), Some("brave-otter".into()), true, &[]);
+        app.attach_session("ses_b".into(), Some("quiet-heron".into()), false, &[]);
+        app.switch_tab(1);
+        let mut entry = HistoryEntry::new("run the tests", Some("ses_b".into()));
+        entry.status = TranscriptStatus::Sent;
+        app.unanswered
+            .sent(entry.id, "ses_b".into(), Instant::now());
+        app.transcripts.push(entry);
+        app.switch_tab(0);
+
+        app.update_background_tab(
+            &ServerEvent::Error {
+                session_id: Some("ses_b".into()),
+                message: "Authentication with anthropic failed".into(),
+            },
+            &[],
+        );
+        assert_eq!(
+            app.notices.toast().unwrap().message,
+            "quiet-heron: Authentication with anthropic failed"
+        );
+        app.switch_tab(1);
+        assert_eq!(app.transcripts[0].status, TranscriptStatus::Failed);
+        assert!(render_to_string(&app).contains("run the tests \u{26A0} failed"));
+    }
+
+    #[test]
+    fn test_control_commands() {
+        // Test: Commands from the control socket act like the keys, and
+        // refuse what the keys couldn't do
+        let mut app = scripted_app();
+        let audio = AudioCapture::with_source(Box::new(FakeSource::tone(440.0))).unwrap();
+        let (transcribe, _jobs) = mpsc::channel();
+        let run = |app: &mut App, command: Command| {
+            run_control(app, &command, &audio, &transcribe).unwrap()
+        };
+        let record = |action| Command::Record { action };
+
+        let stop = run(&mut app, record(RecordAction::Stop));
+        assert_eq!(stop.error.as_deref(), Some("Not recording"));
+        assert!(run(&mut app, record(RecordAction::Start)).ok);
+        assert_eq!(app.state, RecordingState::Recording);
+        assert!(!run(&mut app, record(RecordAction::Start)).ok);
+        assert!(run(&mut app, record(RecordAction::Toggle)).ok);
+        assert_ne!(app.state, RecordingState::Recording);
+
+        let status = run(&mut app, Command::Status).result.unwrap();
+        assert_eq!(status["slug"], "brave-otter");
+        assert_eq!(status["pending"], serde_json::Value::Null);
+        let focus = Command::Focus {
+            action: FocusAction::List,
+        };
+        let entries = run(&mut app, focus).result.unwrap();
+        assert_eq!(entries[0]["value"], "src/main.rs");
+        assert_eq!(entries[0]["current"], true);
+
+        assert!(!run(&mut app, Command::Send { text: None }).ok);
+        let send = |text: &str| Command::Send {
+            text: Some(text.into()),
+        };
+        assert!(run(&mut app, send("run the tests")).ok);
+        assert_eq!(app.transcripts.last().unwrap().text, "run the tests");
+        app.prompt_pending = Some("fix the bug".into());
+        assert!(!run(&mut app, send("run the lints")).ok);
+        assert!(run(&mut app, Command::Discard).ok);
+        assert!(app.prompt_pending.is_none());
+    }
+
+    #[test]
+    fn test_double_tap_cancels_recording() {
+        // Test: Space stops a recording once the double-tap window has
+        // passed; a second Space within it discards the recording instead
+        let mut app = scripted_app();
+        let audio = AudioCapture::with_source(Box::new(FakeSource::tone(440.0))).unwrap();
+        let (transcribe, jobs) = mpsc::channel();
+        let ms = Duration::from_millis;
+        let t0 = Instant::now();
+
+        app.start_recording(&audio);
+        std::thread::sleep(ms(100));
+        space_pressed(&mut app, &audio, &transcribe, t0).unwrap();
+        assert!(!stop_tap_due(&mut app, &audio, &transcribe, t0 + ms(100)).unwrap());
+        assert_eq!(app.state, RecordingState::Recording);
+        space_pressed(&mut app, &audio, &transcribe, t0 + ms(200)).unwrap();
+        assert_eq!(app.state, RecordingState::Idle);
+        assert!(!audio.is_recording());
+        assert!(jobs.try_recv().is_err());
+        assert_eq!(app.notices.toast().unwrap().message, "Recording discarded");
+
+        app.start_recording(&audio);
+        std::thread::sleep(ms(100));
+        space_pressed(&mut app, &audio, &transcribe, t0).unwrap();
+        assert!(stop_tap_due(&mut app, &audio, &transcribe, t0 + ms(300)).unwrap());
+        assert_eq!(app.state, RecordingState::Processing);
+        assert!(jobs.try_recv().is_ok());
+
+        // Without the gesture, Space stops at once
+        app.autostop = AutoStop::new(AutoStopConfig {
+            double_tap_ms: 0,
+            ..AutoStopConfig::default()
+        });
+        app.start_recording(&audio);
+        std::thread::sleep(ms(100));
+        space_pressed(&mut app, &audio, &transcribe, t0).unwrap();
+        assert!(jobs.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_retranscribe_last_recording() {
+        // Test: The last recording is kept after it's sent, and can be
+        // transcribed again in another language; a missing model is refused
+        let mut app = scripted_app();
+        let audio = AudioCapture::with_source(Box::new(FakeSource::tone(440.0))).unwrap();
+        let (transcribe, jobs) = mpsc::channel();
+        let retranscribe = |model: Option<&str>, language: Option<&str>| Command::Retranscribe {
+            model: model.map(String::from),
+            language: language.map(String::from),
+        };
+        let none = run_control(&mut app, &retranscribe(None, None), &audio, &transcribe).unwrap();
+        let error = none.error.unwrap();
+        assert_eq!(error, "No recording to transcribe again");
+
+        app.start_recording(&audio);
+        std::thread::sleep(Duration::from_millis(100));
+        handle_space(&mut app, &audio, &transcribe).unwrap();
+        let first = jobs.try_recv().unwrap();
+        app.state = RecordingState::Idle;
+        app.prompt_pending = Some("run the test".into());
+        confirm_prompt(&mut app);
+
+        let spanish = retranscribe(None, Some("spanish"));
+        let queued = run_control(&mut app, &spanish, &audio, &transcribe).unwrap();
+        assert!(queued.ok);
+        let again = jobs.try_recv().unwrap();
+        assert_eq!(again.samples, first.samples);
+        assert_eq!(again.language, "es");
+        assert!(again.model.is_none());
+        assert_eq!(app.state, RecordingState::Processing);
+
+        app.models_dir = std::env::temp_dir().join("conch_no_models");
+        let missing = retranscribe(Some("large-v3"), None);
+        let refused = run_control(&mut app, &missing, &audio, &transcribe).unwrap();
+        assert!(refused.error.unwrap().starts_with("No model at"));
+        assert!(jobs.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_vocabulary_primes_prompts() {
+        // Test: Prompt recordings take the project vocabulary to Whisper and
+        // answers don't; the vocabulary follows the focus directory
+        let mut app = scripted_app();
+        let cwd = Path::new("/work/conch");
+        assert_eq!(app.focus_directory(cwd), cwd.join("src"));
+        app.focus
+            .append(FocusEntry::Directory(PathBuf::from("/work/other")));
+        assert_eq!(app.focus_directory(cwd), Path::new("/work/other"));
+
+        let audio = AudioCapture::with_source(Box::new(FakeSource::tone(440.0))).unwrap();
+        let (transcribe, jobs) = mpsc::channel();
+        let record = |app: &mut App| {
+            app.start_recording(&audio);
+            std::thread::sleep(Duration::from_millis(50));
+            handle_space(app, &audio, &transcribe).unwrap();
+            jobs.try_recv().unwrap()
+        };
+        assert!(record(&mut app).prompt.is_none(), "nothing harvested yet");
+
+        let files = vec!["main.rs".into()];
+        app.vocabulary = Arc::new(Vocabulary::new(files, vec!["AudioCapture".into()]));
+        let job = record(&mut app);
+        assert_eq!(job.prompt.as_deref(), Some("main.rs, AudioCapture"));
+
+        app.prompt_pending = Some("run the tests".into());
+        app.state = RecordingState::Idle;
+        assert!(record(&mut app).prompt.is_none(), "answers have their own");
+    }
+
+    #[test]
+    fn test_transcript_corrections_can_be_undone() {
+        // Test: Names from the focus stack and homophones next to them are
+        // fixed in the pending prompt, shown, and put back one at a time
+        let mut app = scripted_app();
+        let mut config = Config::default();
+        let heard = "right the tests two mane dot rs";
+        app.transcript_queue.push_back(Transcript {
+            text: heard.into(),
+            confidence: 0.9,
+        });
+        accept_queued_transcripts(&mut app, &config, Duration::ZERO);
+        let fixed = "write the tests to main.rs";
+        assert_eq!(app.prompt_pending.as_deref(), Some(fixed));
+        assert_eq!(app.transcripts.last().unwrap().text, fixed);
+        let screen = render_to_string(&app);
+        assert!(screen.contains("[u] Undo fix"));
+        // Shown as a diff: what was heard, then what replaced it
+        assert!(screen.contains("right write the tests two to mane dot rs main.rs"));
+
+        undo_correction(&mut app);
+        let pending = "write the tests to mane dot rs";
+        assert_eq!(app.prompt_pending.as_deref(), Some(pending));
+        assert_eq!(app.transcripts.last().unwrap().text, pending);
+        assert!(app.notices.toast().unwrap().message.contains("mane dot rs"));
+        undo_correction(&mut app);
+        undo_correction(&mut app);
+        assert_eq!(app.prompt_pending.as_deref(), Some(heard));
+        assert!(!render_to_string(&app).contains("[u] Undo fix"));
+
+        // Numbers show the words they were said as
+        app.prompt_pending = None;
+        app.transcript_queue.push_back(Transcript {
+            text: "Use port forty ninety six".into(),
+            confidence: 0.9,
+        });
+        accept_queued_transcripts(&mut app, &config, Duration::ZERO);
+        assert_eq!(app.prompt_pending.as_deref(), Some("Use port 4096"));
+        assert!(render_to_string(&app).contains("forty ninety six 4096"));
+
+        // With correcting off, the transcript stays as heard
+        app.prompt_pending = None;
+        config.vocabulary.correct = false;
+        app.transcript_queue.push_back(Transcript {
+            text: heard.into(),
+            confidence: 0.9,
+        });
+        accept_queued_transcripts(&mut app, &config, Duration::ZERO);
+        assert_eq!(app.prompt_pending.as_deref(), Some(heard));
+    }
+
+    #[test]
+    fn test_casing_commands_in_prompts() {
+        // Test: A casing command turns the phrase after it into an
+        // identifier before the prompt is shown
+        let mut app = scripted_app();
+        app.transcript_queue.push_back(Transcript {
+            text: "Rename camel case fetch user, to screaming snake max users.".into(),
+            confidence: 0.9,
+        });
+        accept_queued_transcripts(&mut app, &Config::default(), Duration::ZERO);
+        assert_eq!(
+            app.prompt_pending.as_deref(),
+            Some("Rename fetchUser, to MAX_USERS.")
+        );
+        // Shown as a diff like the name corrections, and undone the same way
+        let screen = render_to_string(&app);
+        assert!(screen.contains("camel case fetch user fetchUser"));
+        undo_correction(&mut app);
+        let pending = "Rename fetchUser, to screaming snake max users.";
+        assert_eq!(app.prompt_pending.as_deref(), Some(pending));
+    }
+
+    #[test]
+    fn test_prompt_in_flight_is_not_sent_twice() {
+        // Test: While a prompt is being sent, the same text can't be sent
+        // again, and the help bar says so
+        let mut app = scripted_app();
+        app.prompt_pending = Some("run the tests".into());
+        confirm_prompt(&mut app);
+        assert!(app.prompt_pending.is_none());
+        assert_eq!(app.sending.len(), 1);
+
+        app.prompt_pending = Some("run the tests".into());
+        assert!(render_to_string(&app).contains("[Enter] Sending\u{2026}"));
+        confirm_prompt(&mut app);
+        assert_eq!(app.prompt_pending.as_deref(), Some("run the tests"));
+        assert_eq!(app.sending.len(), 1);
+        let toast = app.notices.toast().unwrap();
+        assert!(toast.message.starts_with("Already sending"));
+
+        // Other text goes straight out
+        app.prompt_pending = Some("and the lints".into());
+        assert!(render_to_string(&app).contains("[Enter] Send  [S-Enter/y]"));
+        confirm_prompt(&mut app);
+        assert!(app.prompt_pending.is_none());
+        assert_eq!(app.sending.len(), 2);
+    }
+
+    #[test]
+    fn test_conversation_view() {
+        // Test: [c] shows prompts, tool calls and replies in one pane, with
+        // long replies folded until [z]
+        let mut app = scripted_app();
+        app.prompt_pending = Some("fix the parser".into());
+        confirm_prompt(&mut app);
+        let edit = ToolEvent {
+            call_id: Some("c1".into()),
+            session_id: None,
+            message_id: Some("msg_1".into()),
+            tool: "edit".into(),
+            input: serde_json::json!({"filePath": "src/parser.rs"}),
+            state: "completed".into(),
+            metadata: serde_json::Value::Null,
+        };
+        app.timeline.tool(app.activity.record(&edit));
+        let reply: Vec<String> = (1..=6).map(|i| format!("Step {i} done.")).collect();
+        app.timeline.reply(&reply.join("\n"));
+
+        app.show_waveform = false;
+        assert!(!render_to_string(&app).contains("Conversation"));
+        app.show_conversation = true;
+        let screen = render_to_string(&app);
+        assert!(screen.contains(" Conversation "));
+        assert!(screen.contains("fix the parser"));
+        assert!(screen.contains("edit src/parser.rs"));
+        assert!(screen.contains("Step 4 done."));
+        assert!(!screen.contains("Step 5 done."));
+        assert!(screen.contains("+2 more lines [z]"));
+
+        app.timeline.toggle_fold();
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Step 6 done."));
+        assert!(!screen.contains("more lines"));
+
+        // A removed message takes its tool calls along
+        app.message_removed("msg_1");
+        assert!(!render_to_string(&app).contains("edit src/parser.rs"));
+    }
+
+    #[test]
+    fn test_agent_switch_by_voice() {
+        // Test: "Use the plan agent" switches agents instead of prompting,
+        // and the title bar names the agent
+        let mut app = scripted_app();
+        app.agents = conch::agent::AgentConfig::default().agents();
+        assert!(!render_to_string(&app).contains("[agent:"));
+        app.transcript_queue.push_back(Transcript {
+            text: "Use the plan agent.".into(),
+            confidence: 0.9,
+        });
+        accept_queued_transcripts(&mut app, &Config::default(), Duration::ZERO);
+        assert!(app.prompt_pending.is_none());
+        assert_eq!(app.agent.as_deref(), Some("plan"));
+        assert!(render_to_string(&app).contains("[agent: plan]"));
+
+        app.transcript_queue.push_back(Transcript {
+            text: "Switch to the review agent".into(),
+            confidence: 0.9,
+        });
+        accept_queued_transcripts(&mut app, &Config::default(), Duration::ZERO);
+        assert_eq!(app.agent.as_deref(), Some("plan"));
+        let toast = app.notices.toast().unwrap();
+        assert!(toast.message.contains("unknown agent 'review'"));
+    }
+
+    #[test]
+    fn test_focus_navigation_by_voice() {
+        // Test: Spoken focus commands move the pointer and follow mode
+        // instead of prompting; "focus on" naming nothing stays a prompt
+        let mut app = scripted_app();
+        app.focus
+            .append(FocusEntry::File(PathBuf::from("src/transport.rs")));
+        let mut say = |text: &str| {
+            app.transcript_queue.push_back(Transcript {
+                text: text.into(),
+                confidence: 0.9,
+            });
+            accept_queued_transcripts(&mut app, &Config::default(), Duration::ZERO);
+            app.focus.current_entry().map(|e| e.value_str())
+        };
+        assert_eq!(say("Focus down.").as_deref(), Some("src/main.rs"));
+        let transport = Some("src/transport.rs");
+        assert_eq!(say("Focus on the transport file.").as_deref(), transport);
+        assert_eq!(say("focus on src").as_deref(), Some("src"));
+        assert_eq!(say("Follow mode on.").as_deref(), transport);
+        assert_eq!(say("Focus on the parser file").as_deref(), transport);
+        assert!(app.focus.follow_mode());
+        assert!(app.prompt_pending.is_none());
+        let toast = app.notices.toast().unwrap();
+        let missing = "Nothing in the focus stack is called \"parser\"";
+        assert_eq!(toast.message, missing);
+
+        app.focus.toggle_follow_mode();
+        assert!(!render_to_string(&app).contains("(follow)"));
+        app.transcript_queue.push_back(Transcript {
+            text: "Focus on the failing tests".into(),
+            confidence: 0.9,
+        });
+        accept_queued_transcripts(&mut app, &Config::default(), Duration::ZERO);
+        let pending = app.prompt_pending.as_deref();
+        assert_eq!(pending, Some("Focus on the failing tests"));
+    }
+
+    #[test]
+    fn test_stats_view() {
+        // Test: [S] sums up the session in front, and each tab keeps its own
+        let mut app = scripted_app();
+        app.attach_session("ses_a".into(), Some("brave-otter".into()), true, &[]);
+        app.transcripts.last_mut().unwrap().status = TranscriptStatus::Answered;
+        app.transcripts.last_mut().unwrap().response_secs = Some(12.5);
+        app.stats.transcribed(Duration::from_millis(900));
+        for state in ["running", "completed"] {
+            app.stats.tool(&ToolEvent {
+                call_id: Some("c1".into()),
+                session_id: None,
+                message_id: None,
+                tool: "edit".into(),
+                input: serde_json::json!({"filePath": "src/parser.rs"}),
+                state: state.into(),
+                metadata: serde_json::Value::Null,
+            });
+        }
+        app.panel = Some(Panel::Stats);
+        app.show_waveform = false;
+        let screen = render_to_string(&app);
+        assert!(screen.contains(" Session Stats "));
+        assert!(screen.contains("Transcription   0.9s"));
+        assert!(screen.contains("Response time   12.5s"));
+        assert!(screen.contains("Tool calls (1)"));
+        assert!(screen.contains("edit    1 \u{2588}"));
+        assert!(screen.contains("src/parser.rs"));
+
+        app.attach_session("ses_b".into(), None, true, &[]);
+        assert!(render_to_string(&app).contains("Tool calls (0)"));
+        app.switch_tab(0);
+        assert!(render_to_string(&app).contains("Tool calls (1)"));
+    }
+
+    #[test]
+    fn test_payload_preview() {
+        // Test: [P] shows the request the pending prompt goes out as, focus
+        // context and agent included
+        let mut app = scripted_app();
+        app.panel = Some(Panel::Payload);
+        app.show_waveform = false;
+        assert!(render_to_string(&app).contains("No prompt pending"));
+
+        app.prompt_pending = Some("fix the parser".into());
+        app.agent = Some("plan".into());
+        let screen = render_to_string(&app);
+        assert!(screen.contains(" Payload "));
+        assert!(screen.contains("POST /session/<new session>/prompt_async"));
+        assert!(screen.contains("\"agent\": \"plan\""));
+        assert!(screen.contains("main.rs"));
+        assert!(screen.contains("fix the"));
+
+        app.attach_session("ses_a".into(), Some("brave-otter".into()), true, &[]);
+        assert!(render_to_string(&app).contains("POST /session/ses_a/prompt_async"));
+    }
+
+    #[test]
+    fn test_removed_message_prunes_focus() {
+        // Test: Focus entries and tool calls of a removed message go away
+        let mut app = scripted_app();
+        let mut edit = ToolEvent {
+            call_id: Some("c1".into()),
+            session_id: None,
+            message_id: Some("msg_1".into()),
+            tool: "edit".into(),
+            input: serde_json::json!({"filePath": "src/lib.rs"}),
+            state: "completed".into(),
+            metadata: serde_json::Value::Null,
+        };
+        app.activity.record(&edit);
+        app.focus.append_from(
+            FocusEntry::File(PathBuf::from("src/lib.rs")),
+            edit.message_id.clone(),
+        );
+        edit.call_id = Some("c2".into());
+        edit.message_id = Some("msg_2".into());
+        app.activity.record(&edit);
+
+        app.message_removed("msg_1");
+        assert_eq!(app.activity.len(), 1);
+        assert_eq!(
+            app.focus.current_entry(),
+            Some(&FocusEntry::File(PathBuf::from("src/main.rs")))
+        );
+    }
+
+    #[test]
+    fn test_language_per_session() {
+        // Test: "Switch to Spanish" changes the language of the session in
+        // front only, and Spanish destructive words hold auto-send
+        let mut app = scripted_app();
+        app.attach_session("ses_a".into(), Some("brave-otter".into()), true, &[]);
+        app.attach_session("ses_b".into(), Some("quiet-heron".into()), false, &[]);
+        let config = Config::default();
+        app.transcript_queue.push_back(Transcript {
+            text: "Switch to Spanish.".into(),
+            confidence: 0.9,
+        });
+        accept_queued_transcripts(&mut app, &config, Duration::ZERO);
+        assert_eq!(app.language().code, "es");
+        assert!(app.prompt_pending.is_none());
+        assert!(render_to_string(&app).contains("Status (Spanish)"));
+
+        app.auto_send = true;
+        app.transcript_queue.push_back(Transcript {
+            text: "Borra la rama vieja".into(),
+            confidence: 0.95,
+        });
+        accept_queued_transcripts(&mut app, &config, Duration::ZERO);
+        assert_eq!(app.review, Some(ReviewReason::Keyword("borra".into())));
+        app.prompt_pending = None;
+
+        app.switch_tab(1);
+        assert_eq!(app.language().code, "en");
+        app.switch_tab(0);
+        assert_eq!(app.language().code, "es");
+
+        // An English-only model still switches, with a warning
+        app.english_only_model = true;
+        app.transcript_queue.push_back(Transcript {
+            text: "change language to German".into(),
+            confidence: 0.9,
+        });
+        accept_queued_transcripts(&mut app, &config, Duration::ZERO);
+        assert_eq!(app.language().code, "de");
+        assert!(render_to_string(&app).contains("English-only"));
+    }
+
+    #[test]
+    fn test_spelling_mode() {
+        // Test: Spelled text is added to the pending prompt instead of
+        // waiting behind it, and spelling mode joins what is spelled
+        let mut app = scripted_app();
+        let config = Config::default();
+        for text in [
+            "Rename the helper to",
+            "Spelling mode.",
+            "Victor echo charlie.",
+            "Underscore, two.",
+            "Stop spelling.",
+            "Spell delta.",
+        ] {
+            app.transcript_queue.push_back(Transcript {
+                text: text.into(),
+                confidence: 0.9,
+            });
+        }
+        accept_queued_transcripts(&mut app, &config, Duration::ZERO);
+        assert!(app.transcript_queue.is_empty());
+        assert!(!app.spelling);
+        assert_eq!(
+            app.prompt_pending.as_deref(),
+            Some("Rename the helper to vec_2 d")
+        );
+        assert_eq!(
+            app.transcripts.last().unwrap().text,
+            "Rename the helper to vec_2 d"
+        );
+
+        app.prompt_pending = None;
+        app.set_spelling(true);
+        assert!(render_to_string(&app).contains("Status (spelling)"));
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Spelling \u{2014} say letters"));
+        assert_snapshot("tui_spelling_mode", &screen);
+
+        // Mangled spelling is reported rather than sent
+        app.transcript_queue.push_back(Transcript {
+            text: "victor banana".into(),
+            confidence: 0.9,
+        });
+        accept_queued_transcripts(&mut app, &config, Duration::ZERO);
+        assert!(app.prompt_pending.is_none());
+        assert!(render_to_string(&app).contains("can't spell 'banana'"));
+
+        app.transcript_queue.push_back(Transcript {
+            text: "Stop spelling.".into(),
+            confidence: 0.9,
+        });
+        accept_queued_transcripts(&mut app, &config, Duration::ZERO);
+        assert!(!app.spelling);
+    }
+
+    #[test]
+    fn test_voice_answers() {
+        // Test: A recording made while a prompt is pending answers it
+        let mut app = scripted_app();
+        let (transcribe, jobs) = mpsc::channel();
+        app.prompt_pending = Some("show me what's in here".into());
+        app.answering = true;
+        app.state = RecordingState::Recording;
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Listening for an answer \u{2014} say yes, no or cancel"));
+        assert_snapshot("tui_voice_answer", &screen);
+        app.state = RecordingState::Idle;
+
+        // "Cancel" keeps the prompt but stops the countdown
+        app.send_deadline = Some(std::time::Instant::now() + Duration::from_secs(2));
+        answer_prompt(&mut app, answer::parse("Wait.").unwrap(), &transcribe).unwrap();
+        assert!(app.send_deadline.is_none());
+        assert!(app.prompt_pending.is_some());
+
+        answer_prompt(&mut app, answer::parse("No.").unwrap(), &transcribe).unwrap();
+        assert!(app.prompt_pending.is_none());
+        assert_eq!(app.transcripts[0].status, TranscriptStatus::Discarded);
+
+        app.prompt_pending = Some("show me what's in here".into());
+        answer_prompt(&mut app, Answer::Yes, &transcribe).unwrap();
+        assert!(app.prompt_pending.is_none());
+        // Answers arriving after the prompt is gone do nothing
+        answer_prompt(&mut app, Answer::No, &transcribe).unwrap();
+        assert!(app.prompt_pending.is_none());
+
+        // "Try again" runs the prompt's recording through the retry model
+        let model = std::env::temp_dir().join(format!("conch_retry_{}.bin", std::process::id()));
+        std::fs::write(&model, b"").unwrap();
+        app.retry_model = model.to_string_lossy().into_owned();
+        app.last_recording = Some(vec![0.5; 1600]);
+        app.prompt_pending = Some("show me what's in hear".into());
+        assert!(render_to_string(&app).contains("[r] Retry"));
+        answer_prompt(&mut app, answer::parse("Try again.").unwrap(), &transcribe).unwrap();
+        assert!(app.prompt_pending.is_none());
+        assert_eq!(app.state, RecordingState::Processing);
+        let job = jobs.try_recv().unwrap();
+        assert_eq!(job.model.as_deref(), Some(model.as_path()));
+        std::fs::remove_file(&model).ok();
+    }
+
+    #[test]
+    fn test_route_to_notes() {
+        // Test: A spoken target sends the pending prompt to the notes file
+        // instead of OpenCode; a failed route keeps it pending
+        let mut app = scripted_app();
+        let (transcribe, _jobs) = mpsc::channel();
+        let dir = std::env::temp_dir().join(format!("conch_route_{}", std::process::id()));
+        app.route.notes = dir.join("notes.md");
+        app.prompt_pending = Some("look into the reconnect backoff".into());
+        assert!(render_to_string(&app).contains("[1-4] Route"));
+
+        let answer = answer::parse("Send to notes.").unwrap();
+        answer_prompt(&mut app, answer, &transcribe).unwrap();
+        assert!(app.prompt_pending.is_none());
+        assert_eq!(app.transcripts[0].status, TranscriptStatus::Noted);
+        assert_eq!(app.notices.toast().unwrap().message, "Added to notes");
+        let notes = std::fs::read_to_string(&app.route.notes).unwrap();
+        assert_eq!(notes, "- look into the reconnect backoff\n");
+        std::fs::remove_dir_all(&dir).ok();
+
+        app.route.type_command = Some("false".into());
+        app.prompt_pending = Some("hello from conch".into());
+        route_prompt(&mut app, Target::Type);
+        assert_eq!(app.prompt_pending.as_deref(), Some("hello from conch"));
+        let message = &app.notices.toast().unwrap().message;
+        assert!(message.starts_with("Type failed"), "{message}");
+    }
+
+    #[test]
+    fn test_conversation_context() {
+        // Test: With [context] enabled, the context block carries the recent
+        // prompts and the last reply after the focus
+        let mut app = scripted_app();
+        let focus_only = prompt_context(&app).unwrap();
+        app.transcripts[0].status = TranscriptStatus::Sent;
+        app.transcripts
+            .push(HistoryEntry::new("do that but for the tests", None));
+        app.last_reply = Some("I listed `src/main.rs` and `src/lib.rs`.".into());
+        assert_eq!(prompt_context(&app).unwrap(), focus_only);
+
+        app.context = ContextConfig {
+            recent_prompts: 3,
+            last_reply: true,
+            ..ContextConfig::default()
+        };
+        let context = prompt_context(&app).unwrap();
+        let lines: Vec<&str> = context.lines().collect();
+        assert_eq!(lines[0], focus_only);
+        assert_eq!(lines[1], "[Recent prompts: \"show me what's in here\"]");
+        assert_eq!(lines[2], "[Last reply: I listed src/main.rs and src/lib.rs.]");
+
+        // Each session tab keeps its own last reply
+        app.attach_session("ses_a".into(), None, true, &[]);
+        app.attach_session("ses_b".into(), None, true, &[]);
+        assert!(app.last_reply.is_none());
+        app.switch_tab(0);
+        assert!(app.last_reply.is_some());
+    }
+
+    #[test]
+    fn test_event_inspector() {
+        // Test: Raw SSE events are shown as JSON, filtered by type
+        let mut app = scripted_app();
+        app.inspector
+            .record(r#"{"type":"server.connected","properties":{}}"#, true);
+        app.inspector.record(
+            r#"{"type":"session.renamed","properties":{"sessionID":"s1","title":"Parser"}}"#,
+            false,
+        );
+        app.panel = Some(Panel::Inspector);
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Events (2) \u{2014} all types"));
+        assert!(screen.contains("session.renamed"));
+        assert!(screen.contains("\"sessionID\": \"s1\","));
+        assert!(screen.contains("[d] Close"));
+        assert!(!screen.contains("Focus Stack"));
+        assert_snapshot("tui_event_inspector", &screen);
+
+        app.inspector.cycle_filter(1);
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Events (2) \u{2014} server.connected"));
+        assert!(!screen.contains("session.renamed"));
+    }
+
+    #[test]
+    fn test_resources_view() {
+        // Test: CPU use is shown per part of conch, with the last transcription's speed
+        let mut app = scripted_app();
+        app.panel = Some(Panel::Resources);
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Resources"));
+        assert!(screen.contains("[R] Close"));
+        assert!(!screen.contains("Focus Stack"));
+
+        let snapshot = |ticks: u64, whisper: u64| Snapshot {
+            process_ticks: ticks,
+            threads: [
+                ("conch".to_string(), (ticks - whisper, 1)),
+                ("whisper".to_string(), (whisper, 1)),
+            ]
+            .into(),
+            rss_bytes: 300 * 1024 * 1024,
+        };
+        let t0 = Instant::now();
+        app.resources.update(t0, snapshot(10, 0), 0);
+        app.resources
+            .update(t0 + Duration::from_secs(1), snapshot(60, 40), 30);
+        app.resources
+            .transcribed(Duration::from_secs(6), Duration::from_secs(2));
+        let screen = render_to_string(&app);
+        assert!(screen.contains("CPU  50.0% of a core"));
+        assert!(screen.contains("300.0 MB"));
+        assert!(screen.contains("30.0 frames/s"));
+        assert!(screen.contains("40.0%  Whisper  1 thread"));
+        assert!(screen.contains("6.0s of audio in 2.00s (3.0\u{00d7} realtime)"));
+    }
+
+    #[test]
+    fn test_transcription_queue() {
+        // Test: Recordings waiting for Whisper show as a badge and can be cancelled
+        let mut app = scripted_app();
+        let running = app.queue_ticket(JobKind::Prompt, &[0.0; 16000]);
+        let _guard = running.start().unwrap();
+        app.queue_ticket(JobKind::Prompt, &[0.0; 48000]);
+        app.queue_ticket(JobKind::Dictation, &[0.0; 32000]);
+        app.transcribing = 3;
+        let screen = render_to_string(&app);
+        assert!(screen.contains("2 queued"));
+
+        app.panel = Some(Panel::Queue);
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Transcription Queue (2)"));
+        assert!(screen.contains("Prompt \u{2014} 3.0s"));
+        assert!(screen.contains("[Del] Cancel"));
+
+        // Dictation needs every segment
+        app.queue_cursor = 1;
+        cancel_queued(&mut app);
+        assert_eq!(app.queue.waiting().count(), 2);
+        app.queue_cursor = 0;
+        cancel_queued(&mut app);
+        assert_eq!(app.queue.waiting().count(), 1);
+        assert_eq!(app.transcribing, 2);
+        let screen = render_to_string(&app);
+        assert!(screen.contains("1 queued"));
+        assert!(screen.contains("Prompt of 3.0s cancelled"));
+        assert!(!screen.contains("Prompt \u{2014} 3.0s"));
+    }
+
+    #[tokio::test]
+    async fn test_crashed_task_is_reported() {
+        // Test: A background task that panics shows up in the TUI instead of vanishing
+        let mut app = scripted_app();
+        app.tasks
+            .spawn("Reply fetch", async { panic!("unexpected reply") });
+        let mut reports = Vec::new();
+        while reports.is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            reports = app.tasks.reap();
+        }
+        for report in &reports {
+            task_ended(&mut app, report);
+        }
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Reply fetch crashed: unexpected reply"));
+    }
+
+    #[tokio::test]
+    async fn test_change_directory_from_palette() {
+        // Test: The palette lists the other directories OpenCode has sessions
+        // in; picking one shows it in the title bar and attaches its session
+        let server = MockServer::start().await.unwrap();
+        let mut app = scripted_app();
+        let mut rx = app.bus.subscribe(&[Topic::Transport]);
+        app.directory = Some(PathBuf::from("/work/conch"));
+        let session = |id: &str, dir: &str| SessionInfo {
+            id: id.into(),
+            slug: None,
+            directory: Some(dir.into()),
+            title: None,
+        };
+        app.sessions = vec![
+            session("ses_a", "/work/site"),
+            session("ses_b", "/work/conch"),
+            session("ses_c", "/work/site"),
+        ];
+        assert!(render_to_string(&app).contains("[/work/conch]"));
+
+        open_palette(&mut app);
+        let labels: Vec<String> = (app.palette.as_ref().unwrap().filtered())
+            .iter()
+            .map(|item| item.label.clone())
+            .collect();
+        assert_eq!(labels, vec!["/work/site"]);
+        handle_palette_key(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        let toast = app.notices.toast().unwrap();
+        assert_eq!(toast.message, "Not connected to OpenCode");
+        assert_eq!(app.directory, Some(PathBuf::from("/work/conch")));
+
+        // The mock server has no session there, so one is created
+        app.opencode = Some(SessionHandle::spawn(OpenCodeClient::new(server.url())));
+        change_directory(&mut app, PathBuf::from("/work/site"));
+        assert_eq!(app.directory, Some(PathBuf::from("/work/site")));
+        assert!(render_to_string(&app).contains("[/work/site]"));
+        loop {
+            match rx.recv().await.unwrap() {
+                AppMessage::SessionReady { id, activate, .. } => {
+                    assert_eq!(id, mock::SESSION_ID);
+                    assert!(activate);
+                    break;
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lost_session_after_reconnect() {
+        // Test: A session gone from the server after a reconnect loses its
+        // tab, and with none left the picker offers a new session or the
+        // ones OpenCode still has
+        let server = MockServer::start().await.unwrap();
+        let mut client = OpenCodeClient::new(server.url());
+        let site = Path::new("/work/site");
+        client.create_session(Some(site)).await.unwrap();
+        let mut app = scripted_app();
+        let mut rx = app.bus.subscribe(&[Topic::Transport]);
+        app.opencode = Some(SessionHandle::spawn(client));
+        app.directory = Some(PathBuf::from("/work/conch"));
+        app.attach_session("ses_gone".into(), Some("brave-otter".into()), true, &[]);
+
+        check_sessions(&app);
+        loop {
+            match rx.recv().await.unwrap() {
+                AppMessage::SessionsListed(sessions) => app.sessions = sessions,
+                AppMessage::SessionLost { id, why } => {
+                    assert_eq!(why, "no longer exists");
+                    session_lost(&mut app, &id, &why);
+                    break;
+                }
+                _ => continue,
+            }
+        }
+        assert!(app.tabs.is_empty());
+        assert!(app.detached);
+        let toast = app.notices.toast().unwrap();
+        assert!(toast.message.starts_with("brave-otter no longer exists"));
+        let labels: Vec<String> = (app.palette.as_ref().unwrap().filtered())
+            .iter()
+            .map(|item| item.label.clone())
+            .collect();
+        assert_eq!(labels, vec!["New session", "mock-session"]);
+
+        handle_palette_key(&mut app, KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        handle_palette_key(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        loop {
+            match rx.recv().await.unwrap() {
+                AppMessage::SessionReady { id, directory, .. } => {
+                    assert_eq!(id, mock::SESSION_ID);
+                    assert_eq!(directory, Some(PathBuf::from("/work/site")));
+                    break;
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    #[test]
+    fn test_config_reload() {
+        // Test: Edited settings apply at once; device changes ask for a restart
+        let mut app = scripted_app();
+        let old = Config::default();
+        let new =
+            Config::from_toml_str("[send]\nauto = true\n[snippets]\nreview = \"Review {file}\"")
+                .unwrap();
+        reload_config(&mut app, &old, &new).unwrap();
+        assert!(app.auto_send);
+        assert_eq!(app.snippets["review"], "Review {file}");
+        assert_eq!(app.notices.toast().unwrap().message, "Config reloaded");
+
+        let moved =
+            Config::from_toml_str("[audio]\ndevice = \"USB\"\n[models]\ndefault = \"small\"")
+                .unwrap();
+        reload_config(&mut app, &old, &moved).unwrap();
+        let screen = render_to_string(&app);
+        assert!(screen.contains("restart conch to apply [audio], [models]"));
+
+        // An invalid file changes nothing
+        let bad =
+            Config::from_toml_str("[stt]\nlanguage = \"klingon\"\n[send]\nauto = false").unwrap();
+        assert!(reload_config(&mut app, &new, &bad).is_err());
+        assert!(app.auto_send);
+    }
+
+    #[test]
+    fn test_notification_history() {
+        // Test: A later error becomes the toast without losing the earlier warning
+        let mut app = scripted_app();
+        app.notices.warn("Audible cues disabled: no output device");
+        app.notices.error("Send failed: connection refused");
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Send failed: connection refused"));
+        assert!(!screen.contains("Audible cues disabled"));
+        assert!(screen.contains("[e] Notices (2)"));
+
+        app.panel = Some(Panel::Notices);
+        app.notices.mark_seen();
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Notifications (2)"));
+        assert!(screen.contains("Audible cues disabled: no output device"));
+        assert!(screen.contains("[e] Close"));
+        assert!(!screen.contains("Focus Stack"));
+        assert_snapshot("tui_notifications", &screen);
+
+        // A new recording clears the toast but keeps the history
+        app.notices.clear_toast();
+        app.panel = None;
+        let screen = render_to_string(&app);
+        assert!(!screen.contains("Send failed"));
+        assert_eq!(app.notices.history().len(), 2);
+    }
+
+    #[test]
+    fn test_tts_speaking_indicator() {
+        // Test: Readback in progress is shown with a way to stop it
+        let mut app = scripted_app();
+        app.speaking = true;
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Reading reply aloud... press [s] to stop"));
+        assert!(screen.contains("[s] Stop"));
+    }
+
+    #[test]
+    fn test_tui_pending_prompt() {
+        // Test: Pending transcript is highlighted with confirmation hints
+        // Plan requirement: Prompt confirmation flow (Enter to send, Backspace to discard)
+        let mut app = scripted_app();
+        app.prompt_pending = Some("open the config file".into());
+        let screen = render_to_string(&app);
+        assert!(screen.contains("open the config file [pending]"));
+        assert!(screen.contains("[S-Enter/y] Copy"));
+        assert!(screen.contains("[Enter] Send  [S-Enter/y]"));
+        assert_snapshot("tui_pending_prompt", &screen);
+    }
+
+    #[test]
+    fn test_auto_send_countdown() {
+        // Test: Auto-send shows its mode and a cancel countdown for the pending
+        // prompt, which Esc stops rather than quitting, vim preset or not
+        let mut app = scripted_app();
+        app.auto_send = true;
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Status (auto-send)"));
+        assert!(screen.contains("[a] Auto"));
+
+        app.prompt_pending = Some("run the tests".into());
+        app.send_deadline = Some(std::time::Instant::now() + std::time::Duration::from_secs(3));
+        let screen = render_to_string(&app);
+        assert!(screen.contains("Sending in"));
+        assert!(screen.contains("[Esc] to hold, [Backspace] to discard"));
+
+        let esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        app.keymap = Keymap::Vim;
+        assert!(!handle_vim_key(&mut app, esc), "left to the countdown");
+        hold_prompt(&mut app);
+        assert!(app.send_deadline.is_none());
+        assert_eq!(app.prompt_pending.as_deref(), Some("run the tests"));
+        assert!(handle_vim_key(&mut app, esc));
+    }
+
+    #[test]
+    fn test_auto_send_review_highlight() {
+        // Test: A transcript held back by auto-send is highlighted with the reason
+        let mut app = scripted_app();
+        app.auto_send = true;
+        app.prompt_pending = Some("delete the build dir".into());
+        app.review = Some(ReviewReason::Keyword("delete".into()));
+        let screen = render_to_string(&app);
+        assert!(screen.contains("delete the build dir [review: mentions \"delete\"]"));
+        assert!(screen.contains("Auto-send held"));
+        assert_snapshot("tui_review_pending", &screen);
+    }
+
+    #[test]
+    fn test_hallucinations_never_pend() {
+        // Test: "Thanks for watching!" is dropped before it becomes the
+        // pending prompt, or held for review when flagged
+        let mut app = scripted_app();
+        let mut config = Config::default();
+        let before = app.transcripts.len();
+        app.transcript_queue.push_back(Transcript {
+            text: "Thanks for watching!".into(),
+            confidence: 0.9,
+        });
+        accept_queued_transcripts(&mut app, &config, Duration::ZERO);
+        assert!(app.prompt_pending.is_none());
+        assert_eq!(app.transcripts.len(), before);
+        assert!(app.notices.toast().unwrap().message.starts_with("Dropped"));
+
+        config.hallucinations.action = hallucination::Action::Flag;
+        app.auto_send = true;
+        app.transcript_queue.push_back(Transcript {
+            text: "Thank you.".into(),
+            confidence: 0.9,
+        });
+        accept_queued_transcripts(&mut app, &config, Duration::ZERO);
+        assert_eq!(app.prompt_pending.as_deref(), Some("Thank you."));
+        assert_eq!(app.review, Some(ReviewReason::Hallucination));
+        assert!(render_to_string(&app).contains("[review: likely made up by Whisper]"));
+    }
+
+    #[test]
+    fn test_spoken_numbers_in_prompts() {
+        // Test: Number words in a prompt become digits, by the rules of the
+        // session's language, unless [numbers] is off
+        let mut app = scripted_app();
+        let mut config = Config::default();
+        let accept = |app: &mut App, config: &Config, text: &str| {
+            app.transcript_queue.push_back(Transcript {
+                text: text.into(),
+                confidence: 0.9,
+            });
+            accept_queued_transcripts(app, config, Duration::ZERO);
+            app.prompt_pending.take()
+        };
+        assert_eq!(
+            accept(&mut app, &config, "Listen on port forty ninety six.").as_deref(),
+            Some("Listen on port 4096.")
+        );
+        app.language = language::find("es");
+        let prompt = accept(&mut app, &config, "Sube a la versión uno punto setenta");
+        assert_eq!(prompt.as_deref(), Some("Sube a la versión 1.70"));
+        config.numbers.enabled = false;
+        assert_eq!(
+            accept(&mut app, &config, "puerto ocho mil ochenta").as_deref(),
+            Some("puerto ocho mil ochenta")
+        );
+    }
+
+    #[test]
+    fn test_tui_long_transcript() {
+        // Test: Long transcripts and deep focus history don't break the layout
+        // Plan requirement: Edge case handling for long dictations
+        let mut app = scripted_app();
+        app.transcripts.push(HistoryEntry::new(
+            "refactor the transport module so that the session handling lives in its own \
+             struct and the retry logic is shared between health checks and prompt sends",
+            None,
+        ));
+        for i in 0..30 {
+            app.focus
+                .append(FocusEntry::File(PathBuf::from(format!("src/file_{}.rs", i))));
+        }
+        let screen = render_to_string(&app);
+        assert_eq!(screen.lines().count(), SNAPSHOT_HEIGHT as usize);
+        assert!(screen.contains("[q/Esc] Quit"), "help bar must stay visible");
+        assert_snapshot("tui_long_transcript", &screen);
+    }
+
+    #[test]
+    fn test_history_restored_for_session() {
+        // Test: Restarting conch restores the session's transcripts ahead of new ones
+        let path = std::env::temp_dir().join(format!("conch_tui_history_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = TranscriptStore::new(&path);
+        let mut old = HistoryEntry::new("from last run", Some("ses_x".into()));
+        old.status = TranscriptStatus::Sent;
+        store.record(&old).unwrap();
+        store
+            .record(&HistoryEntry::new("other session", Some("ses_y".into())))
+            .unwrap();
+
+        let mut app = App::new(16000);
+        app.history = Some(store);
+        app.transcripts.push(HistoryEntry::new("said before connecting", None));
+        app.restore_history("ses_x");
+
+        let texts: Vec<&str> = app.transcripts.iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(texts, vec!["from last run", "said before connecting"]);
+        assert_eq!(app.transcripts[0].status, TranscriptStatus::Sent);
+        // The early transcript now belongs to the session in the store too
+        let stored = TranscriptStore::new(&path).load_session("ses_x").unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(stored.len(), 2);
     }
 
     // ===== Configuration Tests =====
@@ -273,7 +2280,19 @@ mod tests {
     fn test_configuration_file_loading() {
         // Test: Configuration file can be loaded
         // Plan requirement: "Configuration file for keybindings, Whisper model path, OpenCode server URL"
-        todo!("Load config file and verify settings are applied");
+        let path = std::env::temp_dir().join(format!("conch_config_{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "[[hooks]]\nevent = \"opencode-idle\"\ncommand = \"notify-send done\"\n",
+        )
+        .unwrap();
+        let config = Config::load(&path);
+        let _ = std::fs::remove_file(&path);
+
+        let config = config.expect("config should parse");
+        assert_eq!(config.hooks.len(), 1);
+        assert_eq!(config.hooks[0].event, HookEvent::OpencodeIdle);
+        assert_eq!(config.hooks[0].command, "notify-send done");
     }
 
     #[test]
@@ -301,6 +2320,8 @@ mod tests {
     fn test_default_configuration() {
         // Test: System works with default config if file missing
         // Plan requirement: Sensible defaults
-        todo!("Run without config file, verify default values are used");
+        let path = std::env::temp_dir().join("conch_no_such_config.toml");
+        let config = Config::load(&path).expect("missing config falls back to defaults");
+        assert!(config.hooks.is_empty());
     }
 }
@@ -0,0 +1,497 @@
+// Keymap Module - Key presets, and the transcript cursor vim keys move
+//
+// `[keys] keymap = "vim"` adds vim-style keys on top of the defaults:
+// `j`/`k` move through the transcripts, `gg`/`G` jump to the oldest and back
+// to the live view, `/` searches (`n`/`N` repeat it), `:` opens the command
+// palette, and `v` starts selecting transcripts for `y` to copy. Esc leaves
+// browsing or selecting instead of quitting.
+//
+// The default keys are resolved to an [`Action`] against what the TUI is
+// doing, since several keys mean different things with a prompt pending, a
+// view open or a recording running.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use crate::audio::RecordingState;
+use crate::layout::Panel;
+use crate::route::Target;
+
+/// Key preset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Keymap {
+    #[default]
+    Default,
+    Vim,
+}
+
+/// `[keys]` section of the config file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct KeysConfig {
+    /// "default" or "vim".
+    pub keymap: Keymap,
+}
+
+/// What a vim key does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VimAction {
+    /// `j`: the next newer transcript.
+    Down,
+    /// `k`: the next older transcript.
+    Up,
+    /// `gg`: the oldest transcript.
+    Top,
+    /// `G`: back to the live view.
+    Bottom,
+    /// `/`: type a search.
+    Search,
+    /// `n`: the previous search, further back.
+    SearchNext,
+    /// `N`: the previous search, forward.
+    SearchPrevious,
+    /// `:`: the command palette.
+    Palette,
+    /// `v`: start or stop selecting.
+    Select,
+}
+
+/// Turns vim keys into actions, remembering a pending `g`.
+#[derive(Debug, Clone, Default)]
+pub struct VimKeys {
+    pending_g: bool,
+}
+
+impl VimKeys {
+    /// The action `key` completes, if any. Keys that aren't vim keys
+    /// return `None` and drop a pending `g`.
+    pub fn press(&mut self, key: char) -> Option<VimAction> {
+        let pending_g = std::mem::take(&mut self.pending_g);
+        Some(match key {
+            'j' => VimAction::Down,
+            'k' => VimAction::Up,
+            'g' if pending_g => VimAction::Top,
+            'g' => {
+                self.pending_g = true;
+                return None;
+            }
+            'G' => VimAction::Bottom,
+            '/' => VimAction::Search,
+            'n' => VimAction::SearchNext,
+            'N' => VimAction::SearchPrevious,
+            ':' => VimAction::Palette,
+            'v' => VimAction::Select,
+            _ => return None,
+        })
+    }
+}
+
+/// What the TUI is doing, as far as what a key does depends on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyContext {
+    pub state: RecordingState,
+    /// A transcript is waiting to be confirmed.
+    pub prompt_pending: bool,
+    /// The pending prompt is counting down to an auto-send.
+    pub counting_down: bool,
+    /// The view shown in place of the focus stack.
+    pub panel: Option<Panel>,
+    /// The conversation is shown.
+    pub conversation: bool,
+    /// The focus stack is shown, not replaced by a view or the palette.
+    pub focus_shown: bool,
+}
+
+/// What a key does in the main view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    /// Esc while recording: throw the recording away.
+    AbortRecording,
+    /// Esc during an auto-send countdown: stop it, keeping the prompt.
+    HoldPrompt,
+    /// Space: start or stop recording, or answer the pending prompt.
+    Record,
+    /// Shift+Enter or `y`: copy the pending prompt instead of sending it.
+    CopyPrompt,
+    /// Enter: send the pending prompt.
+    ConfirmPrompt,
+    /// A number key: send the pending prompt somewhere else.
+    Route(Target),
+    /// Delete in the queue: cancel the selected recording.
+    CancelQueued,
+    /// Delete with no prompt pending: forget the focus entry.
+    ForgetFocus,
+    DiscardPrompt,
+    /// `r`: transcribe the last recording again.
+    RetryPrompt,
+    /// `u`: put back the last correction made to the pending prompt.
+    UndoCorrection,
+    /// `s`: stop reading a reply aloud.
+    StopSpeaking,
+    ToggleAutoSend,
+    /// `i`: type a prompt.
+    OpenInput,
+    OpenPalette,
+    NewTab,
+    NextTab,
+    PreviousTab,
+    CloseTab,
+    ToggleSpelling,
+    /// `D`: start or finish a dictation.
+    Dictation,
+    ToggleCaptions,
+    ToggleHandsFree,
+    /// `]` (more) or `[` (less): the waveform's sensitivity.
+    Sensitivity {
+        more: bool,
+    },
+    TogglePanel(Panel),
+    ToggleWaveform,
+    NextAgent,
+    ToggleConversation,
+    /// PageUp: the previous turn of the conversation.
+    PreviousTurn,
+    NextTurn,
+    /// `z`: fold or unfold the selected turn.
+    ToggleFold,
+    /// Up or Down in the view that's open.
+    Scroll {
+        down: bool,
+    },
+    /// Left or Right in the event inspector: the next event type shown.
+    CycleFilter(isize),
+    /// Up with a prompt pending: edit it.
+    EditPrompt,
+    FocusUp,
+    FocusDown,
+    /// `m`: pin the focus entry.
+    PinFocus,
+    ToggleFollow,
+}
+
+impl Action {
+    /// What `key` does in `context`, if anything.
+    pub fn for_key(key: KeyEvent, context: &KeyContext) -> Option<Action> {
+        let recording = context.state == RecordingState::Recording;
+        let idle = context.state == RecordingState::Idle;
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        let scrolls = matches!(
+            context.panel,
+            Some(Panel::Inspector | Panel::Queue | Panel::Notices)
+        );
+        Some(match key.code {
+            KeyCode::Esc if recording => Action::AbortRecording,
+            // Stops an auto-send countdown rather than quitting under it
+            KeyCode::Esc if context.counting_down => Action::HoldPrompt,
+            KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+            KeyCode::Char(' ') => Action::Record,
+            KeyCode::Enter if key.modifiers.contains(KeyModifiers::SHIFT) => Action::CopyPrompt,
+            KeyCode::Enter => Action::ConfirmPrompt,
+            // Fallback for terminals that can't report Shift+Enter
+            KeyCode::Char('y') => Action::CopyPrompt,
+            KeyCode::Char(c) if context.prompt_pending && Target::from_key(c).is_some() => {
+                Action::Route(Target::from_key(c)?)
+            }
+            KeyCode::Backspace | KeyCode::Delete if context.panel == Some(Panel::Queue) => {
+                Action::CancelQueued
+            }
+            // Without a prompt to discard, Delete forgets the focus entry
+            KeyCode::Delete if !context.prompt_pending && context.focus_shown => {
+                Action::ForgetFocus
+            }
+            KeyCode::Backspace | KeyCode::Delete => Action::DiscardPrompt,
+            KeyCode::Char('r') if !recording => Action::RetryPrompt,
+            KeyCode::Char('u') => Action::UndoCorrection,
+            KeyCode::Char('s') => Action::StopSpeaking,
+            KeyCode::Char('a') => Action::ToggleAutoSend,
+            KeyCode::Char('i') if !context.prompt_pending && idle => Action::OpenInput,
+            KeyCode::Char('p') if !context.prompt_pending && idle => Action::OpenPalette,
+            KeyCode::Char('t') => Action::NewTab,
+            KeyCode::Tab => Action::NextTab,
+            KeyCode::BackTab => Action::PreviousTab,
+            KeyCode::Char('x') if !context.prompt_pending => Action::CloseTab,
+            KeyCode::Char('l') => Action::ToggleSpelling,
+            KeyCode::Char('D') => Action::Dictation,
+            KeyCode::Char('C') => Action::ToggleCaptions,
+            KeyCode::Char('h') => Action::ToggleHandsFree,
+            KeyCode::Char('[') => Action::Sensitivity { more: false },
+            KeyCode::Char(']') => Action::Sensitivity { more: true },
+            KeyCode::Char('e') => Action::TogglePanel(Panel::Notices),
+            KeyCode::Char('d') => Action::TogglePanel(Panel::Inspector),
+            KeyCode::Char('R') => Action::TogglePanel(Panel::Resources),
+            KeyCode::Char('J') => Action::TogglePanel(Panel::Queue),
+            KeyCode::Char('S') => Action::TogglePanel(Panel::Stats),
+            KeyCode::Char('P') => Action::TogglePanel(Panel::Payload),
+            KeyCode::Char('w') => Action::ToggleWaveform,
+            KeyCode::Char('A') => Action::NextAgent,
+            KeyCode::Char('c') if !control => Action::ToggleConversation,
+            KeyCode::PageUp if context.conversation => Action::PreviousTurn,
+            KeyCode::PageDown if context.conversation => Action::NextTurn,
+            KeyCode::Char('z') if context.conversation => Action::ToggleFold,
+            KeyCode::Up | KeyCode::Down if scrolls => Action::Scroll {
+                down: key.code == KeyCode::Down,
+            },
+            KeyCode::Left | KeyCode::Right if context.panel == Some(Panel::Inspector) => {
+                Action::CycleFilter(if key.code == KeyCode::Right { 1 } else { -1 })
+            }
+            // Edit the pending prompt, starting from the last one sent
+            KeyCode::Up if context.prompt_pending && !recording => Action::EditPrompt,
+            KeyCode::Up => Action::FocusUp,
+            KeyCode::Down => Action::FocusDown,
+            KeyCode::Char('m') if context.focus_shown => Action::PinFocus,
+            KeyCode::Char('f') if !context.prompt_pending => Action::ToggleFollow,
+            KeyCode::Char('c') => Action::Quit,
+            _ => return None,
+        })
+    }
+}
+
+/// Which transcript is under the cursor, and the selection, as indexes
+/// into the transcript list (oldest first). No cursor means the live view.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TranscriptCursor {
+    cursor: Option<usize>,
+    anchor: Option<usize>,
+}
+
+impl TranscriptCursor {
+    /// The transcript under the cursor, kept within `len` transcripts.
+    pub fn position(&self, len: usize) -> Option<usize> {
+        self.cursor.filter(|_| len > 0).map(|i| i.min(len - 1))
+    }
+
+    /// The selected transcripts, if selecting.
+    pub fn selection(&self, len: usize) -> Option<std::ops::RangeInclusive<usize>> {
+        let cursor = self.position(len)?;
+        let anchor = self.anchor?.min(len - 1);
+        Some(anchor.min(cursor)..=anchor.max(cursor))
+    }
+
+    pub fn is_selecting(&self) -> bool {
+        self.anchor.is_some()
+    }
+
+    /// Move to an older transcript, starting from the newest.
+    pub fn up(&mut self, len: usize) {
+        if len > 0 {
+            self.cursor = Some(self.position(len).map_or(len - 1, |i| i.saturating_sub(1)));
+        }
+    }
+
+    /// Move to a newer transcript. Past the newest, back to the live view
+    /// unless selecting.
+    pub fn down(&mut self, len: usize) {
+        match self.position(len) {
+            Some(i) if i + 1 < len => self.cursor = Some(i + 1),
+            Some(_) if self.is_selecting() => {}
+            _ => self.reset(),
+        }
+    }
+
+    pub fn top(&mut self, len: usize) {
+        if len > 0 {
+            self.cursor = Some(0);
+        }
+    }
+
+    /// Back to the live view.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Start selecting at the cursor (the newest transcript if there's
+    /// none), or stop.
+    pub fn toggle_select(&mut self, len: usize) {
+        if self.anchor.take().is_some() || len == 0 {
+            return;
+        }
+        let cursor = self.position(len).unwrap_or(len - 1);
+        self.cursor = Some(cursor);
+        self.anchor = Some(cursor);
+    }
+
+    /// Move to the nearest transcript matching `query` (case-insensitive),
+    /// older than the cursor when `backward`, newer otherwise. Returns
+    /// whether one was found.
+    pub fn search<'a>(
+        &mut self,
+        texts: impl DoubleEndedIterator<Item = &'a str> + ExactSizeIterator,
+        query: &str,
+        backward: bool,
+    ) -> bool {
+        let query = query.to_lowercase();
+        let len = texts.len();
+        let from = self.position(len).unwrap_or(len);
+        let matches = |(_, text): &(usize, &str)| text.to_lowercase().contains(&query);
+        let texts = texts.enumerate();
+        let found = if backward {
+            texts.rev().filter(|(i, _)| *i < from).find(matches)
+        } else {
+            texts.filter(|(i, _)| *i > from).find(matches)
+        };
+        if let Some((i, _)) = found {
+            self.cursor = Some(i);
+        }
+        found.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vim_keys() {
+        let mut keys = VimKeys::default();
+        assert_eq!(keys.press('j'), Some(VimAction::Down));
+        assert_eq!(keys.press('g'), None);
+        assert_eq!(keys.press('g'), Some(VimAction::Top));
+        // A different key drops the pending g
+        assert_eq!(keys.press('g'), None);
+        assert_eq!(keys.press('x'), None);
+        assert_eq!(keys.press('g'), None);
+        assert_eq!(keys.press('G'), Some(VimAction::Bottom));
+        assert_eq!(keys.press(':'), Some(VimAction::Palette));
+    }
+
+    fn idle() -> KeyContext {
+        KeyContext {
+            state: RecordingState::Idle,
+            prompt_pending: false,
+            counting_down: false,
+            panel: None,
+            conversation: false,
+            focus_shown: true,
+        }
+    }
+
+    fn press(code: KeyCode, context: &KeyContext) -> Option<Action> {
+        Action::for_key(KeyEvent::new(code, KeyModifiers::NONE), context)
+    }
+
+    #[test]
+    fn test_esc_depends_on_state() {
+        assert_eq!(press(KeyCode::Esc, &idle()), Some(Action::Quit));
+        let recording = KeyContext {
+            state: RecordingState::Recording,
+            ..idle()
+        };
+        assert_eq!(
+            press(KeyCode::Esc, &recording),
+            Some(Action::AbortRecording)
+        );
+        let counting = KeyContext {
+            prompt_pending: true,
+            counting_down: true,
+            ..idle()
+        };
+        assert_eq!(press(KeyCode::Esc, &counting), Some(Action::HoldPrompt));
+    }
+
+    #[test]
+    fn test_keys_with_prompt_pending() {
+        let pending = KeyContext {
+            prompt_pending: true,
+            ..idle()
+        };
+        assert_eq!(
+            press(KeyCode::Char('3'), &pending),
+            Some(Action::Route(Target::Type))
+        );
+        assert_eq!(press(KeyCode::Char('3'), &idle()), None);
+        assert_eq!(press(KeyCode::Up, &pending), Some(Action::EditPrompt));
+        assert_eq!(press(KeyCode::Up, &idle()), Some(Action::FocusUp));
+        assert_eq!(
+            press(KeyCode::Delete, &pending),
+            Some(Action::DiscardPrompt)
+        );
+        assert_eq!(press(KeyCode::Delete, &idle()), Some(Action::ForgetFocus));
+        assert_eq!(press(KeyCode::Char('i'), &pending), None);
+        assert_eq!(press(KeyCode::Char('f'), &pending), None);
+        assert_eq!(
+            press(KeyCode::Char('f'), &idle()),
+            Some(Action::ToggleFollow)
+        );
+    }
+
+    #[test]
+    fn test_keys_in_panels() {
+        let queue = KeyContext {
+            panel: Some(Panel::Queue),
+            focus_shown: false,
+            ..idle()
+        };
+        assert_eq!(press(KeyCode::Delete, &queue), Some(Action::CancelQueued));
+        assert_eq!(
+            press(KeyCode::Down, &queue),
+            Some(Action::Scroll { down: true })
+        );
+        assert_eq!(press(KeyCode::Left, &queue), None);
+        let inspector = KeyContext {
+            panel: Some(Panel::Inspector),
+            ..queue
+        };
+        assert_eq!(
+            press(KeyCode::Left, &inspector),
+            Some(Action::CycleFilter(-1))
+        );
+        assert_eq!(
+            press(KeyCode::Char('J'), &inspector),
+            Some(Action::TogglePanel(Panel::Queue))
+        );
+        let stats = KeyContext {
+            panel: Some(Panel::Stats),
+            ..queue
+        };
+        assert_eq!(press(KeyCode::Up, &stats), Some(Action::FocusUp));
+        assert_eq!(press(KeyCode::Char('m'), &stats), None);
+    }
+
+    #[test]
+    fn test_control_c_quits() {
+        let key = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(Action::for_key(key, &idle()), Some(Action::Quit));
+        assert_eq!(
+            press(KeyCode::Char('c'), &idle()),
+            Some(Action::ToggleConversation)
+        );
+    }
+
+    #[test]
+    fn test_cursor_moves_and_selects() {
+        let mut cursor = TranscriptCursor::default();
+        assert_eq!(cursor.position(3), None);
+        cursor.up(3);
+        assert_eq!(cursor.position(3), Some(2));
+        cursor.up(3);
+        cursor.up(3);
+        cursor.up(3);
+        assert_eq!(cursor.position(3), Some(0));
+
+        cursor.toggle_select(3);
+        cursor.down(3);
+        cursor.down(3);
+        cursor.down(3);
+        assert_eq!(cursor.selection(3), Some(0..=2));
+        cursor.toggle_select(3);
+        assert_eq!(cursor.selection(3), None);
+
+        cursor.down(3);
+        assert_eq!(cursor.position(3), None, "past the newest is the live view");
+    }
+
+    #[test]
+    fn test_search() {
+        let texts = ["open main", "run the tests", "open the config"];
+        let mut cursor = TranscriptCursor::default();
+        assert!(cursor.search(texts.iter().copied(), "OPEN", true));
+        assert_eq!(cursor.position(3), Some(2));
+        assert!(cursor.search(texts.iter().copied(), "open", true));
+        assert_eq!(cursor.position(3), Some(0));
+        assert!(!cursor.search(texts.iter().copied(), "open", true));
+        assert!(cursor.search(texts.iter().copied(), "open", false));
+        assert_eq!(cursor.position(3), Some(2));
+    }
+}
@@ -0,0 +1,204 @@
+// Language Module - The spoken language of a session
+//
+// Each session starts in the language from `[stt]` and can be switched by
+// saying "switch to Spanish". The language is passed to Whisper as its
+// language hint and brings its own review keywords for auto-send, so
+// "borra la rama" is held for confirmation just like "delete the branch".
+
+use anyhow::{Result, anyhow};
+
+use crate::send::normalize_words;
+
+/// A language Whisper can be told to expect.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Language {
+    /// Whisper language code, or "auto" to let Whisper detect it.
+    pub code: &'static str,
+    /// English name, as said in "switch to Spanish".
+    pub name: &'static str,
+    /// Name in the language itself, as said in "switch to español".
+    pub native: &'static str,
+    /// Destructive words that hold an auto-sent transcript for review, on
+    /// top of `[send] review_keywords`.
+    pub review_keywords: &'static [&'static str],
+}
+
+/// Languages conch knows by name.
+pub const LANGUAGES: &[Language] = &[
+    Language {
+        code: "auto",
+        name: "Auto-detect",
+        native: "auto",
+        review_keywords: &[],
+    },
+    Language {
+        code: "en",
+        name: "English",
+        native: "English",
+        review_keywords: &[],
+    },
+    Language {
+        code: "es",
+        name: "Spanish",
+        native: "español",
+        review_keywords: &["borra", "borrar", "elimina", "eliminar", "sobrescribe"],
+    },
+    Language {
+        code: "fr",
+        name: "French",
+        native: "français",
+        review_keywords: &["supprime", "supprimer", "efface", "effacer", "écrase"],
+    },
+    Language {
+        code: "de",
+        name: "German",
+        native: "Deutsch",
+        review_keywords: &["lösche", "löschen", "entferne", "entfernen", "überschreibe"],
+    },
+    Language {
+        code: "it",
+        name: "Italian",
+        native: "italiano",
+        review_keywords: &["cancella", "elimina", "rimuovi", "sovrascrivi"],
+    },
+    Language {
+        code: "pt",
+        name: "Portuguese",
+        native: "português",
+        review_keywords: &["apaga", "apagar", "exclui", "excluir", "remove", "remover"],
+    },
+    Language {
+        code: "nl",
+        name: "Dutch",
+        native: "Nederlands",
+        review_keywords: &["verwijder", "verwijderen", "wis", "overschrijf"],
+    },
+    Language {
+        code: "pl",
+        name: "Polish",
+        native: "polski",
+        review_keywords: &[],
+    },
+    Language {
+        code: "ru",
+        name: "Russian",
+        native: "русский",
+        review_keywords: &[],
+    },
+    Language {
+        code: "ja",
+        name: "Japanese",
+        native: "日本語",
+        review_keywords: &[],
+    },
+    Language {
+        code: "zh",
+        name: "Chinese",
+        native: "中文",
+        review_keywords: &[],
+    },
+    Language {
+        code: "ko",
+        name: "Korean",
+        native: "한국어",
+        review_keywords: &[],
+    },
+];
+
+/// The default language, and the only one `.en` models understand.
+pub const ENGLISH: &Language = &LANGUAGES[1];
+
+/// Spoken prefixes that switch language whatever follows, already normalized.
+const SWITCH_COMMANDS: &[&str] = &["change language to", "switch language to", "language"];
+/// Spoken prefix that switches language only when a known language follows,
+/// so "switch to the main branch" stays a prompt.
+const SWITCH_TO: &str = "switch to";
+
+/// The language with this code, English name, or native name (any case).
+pub fn find(name: &str) -> Option<&'static Language> {
+    let name = normalize_words(name);
+    LANGUAGES.iter().find(|lang| {
+        lang.code == name
+            || normalize_words(lang.name) == name
+            || normalize_words(lang.native) == name
+    })
+}
+
+/// Look up a configured language, with a helpful error for typos.
+pub fn parse(name: &str) -> Result<&'static Language> {
+    find(name).ok_or_else(|| {
+        let codes: Vec<&str> = LANGUAGES.iter().map(|l| l.code).collect();
+        anyhow!("unknown language '{}' (known: {})", name, codes.join(", "))
+    })
+}
+
+/// If `transcript` is a language switch command, the language to switch to,
+/// or why it couldn't be switched. `None` for ordinary transcripts.
+pub fn voice_switch(transcript: &str) -> Option<Result<&'static Language>> {
+    let words = normalize_words(transcript);
+    if let Some(rest) = words
+        .strip_prefix(SWITCH_TO)
+        .and_then(|r| r.strip_prefix(' '))
+    {
+        return find(rest).map(Ok);
+    }
+    SWITCH_COMMANDS.iter().find_map(|command| {
+        let rest = words.strip_prefix(command)?.strip_prefix(' ')?;
+        Some(find(rest).ok_or_else(|| anyhow!("unknown language '{}'", rest)))
+    })
+}
+
+impl Language {
+    /// Whether an English-only (`.en`) model can transcribe this language.
+    pub fn english(&self) -> bool {
+        self == ENGLISH
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_by_code_or_name() {
+        assert_eq!(find("es").unwrap().name, "Spanish");
+        assert_eq!(find("spanish").unwrap().code, "es");
+        assert_eq!(find("Español").unwrap().code, "es");
+        assert_eq!(find("deutsch").unwrap().code, "de");
+        assert!(find("klingon").is_none());
+        assert_eq!(find("EN"), Some(ENGLISH));
+    }
+
+    #[test]
+    fn test_parse_error_lists_codes() {
+        let err = parse("sp").unwrap_err().to_string();
+        assert!(err.contains("unknown language 'sp'"));
+        assert!(err.contains("en, es"));
+    }
+
+    #[test]
+    fn test_voice_switch() {
+        assert_eq!(
+            voice_switch("Switch to Spanish.").unwrap().unwrap().code,
+            "es"
+        );
+        assert_eq!(
+            voice_switch("change language to français")
+                .unwrap()
+                .unwrap()
+                .code,
+            "fr"
+        );
+        assert_eq!(
+            voice_switch("switch to English").unwrap().unwrap().code,
+            "en"
+        );
+        // "switch to" only counts when a language follows
+        assert!(voice_switch("switch to the main branch").is_none());
+        assert!(voice_switch("fix the language parser").is_none());
+        let err = voice_switch("change language to klingon")
+            .unwrap()
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown language 'klingon'"));
+    }
+}
@@ -0,0 +1,192 @@
+// Layout Module - Where the TUI's panes go
+//
+// The screen is a title bar, the waveform, the latest transcript, the
+// status line, the focus stack beside the tool activity, and the help bar,
+// stacked top to bottom. `[layout]` can hide the waveform (or start it
+// collapsed, since `w` toggles it), give the transcript more rows so earlier
+// transcripts show above the latest, or move the transcripts into a column
+// beside the focus stack and activity ("side-by-side"). One view at a time
+// (notifications, the event inspector, ...) can take the focus stack's place.
+
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use serde::Deserialize;
+
+/// How the transcripts and the focus stack share the screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Arrangement {
+    /// Transcripts in a strip under the waveform, above the status line.
+    #[default]
+    Stacked,
+    /// Transcripts in a column left of the focus stack and activity.
+    SideBySide,
+}
+
+/// `[layout]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// "stacked" or "side-by-side".
+    pub arrangement: Arrangement,
+    /// Show the waveform at startup.
+    pub waveform: bool,
+    /// Rows the waveform takes.
+    pub waveform_height: u16,
+    /// Rows the transcript strip takes when stacked, including the blank
+    /// row under it.
+    pub transcript_height: u16,
+    /// Percent of the width the transcript column takes side by side.
+    pub transcript_width: u16,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            arrangement: Arrangement::Stacked,
+            waveform: true,
+            waveform_height: 10,
+            transcript_height: 2,
+            transcript_width: 40,
+        }
+    }
+}
+
+/// A view shown in place of the focus stack pane, one at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Panel {
+    /// The notification history.
+    Notices,
+    /// Raw SSE events.
+    Inspector,
+    /// conch's own CPU and memory use.
+    Resources,
+    /// Recordings waiting to be transcribed.
+    Queue,
+    /// The session's stats.
+    Stats,
+    /// The request the pending prompt will be sent as.
+    Payload,
+}
+
+impl Panel {
+    /// What's shown once `self` is toggled while `shown` is: `self` in
+    /// place of any other view, or nothing if it was `self` already.
+    pub fn toggle(self, shown: Option<Panel>) -> Option<Panel> {
+        (shown != Some(self)).then_some(self)
+    }
+}
+
+/// Screen areas for each pane. Overlays (palette, notices, inspector) draw
+/// over `main`, which holds the transcripts when side by side.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Panes {
+    pub title: Rect,
+    /// `None` while the waveform is hidden.
+    pub waveform: Option<Rect>,
+    pub transcript: Rect,
+    pub status: Rect,
+    pub main: Rect,
+    pub focus: Rect,
+    pub activity: Rect,
+    pub help: Rect,
+}
+
+impl LayoutConfig {
+    /// Split `area` into panes, with or without the waveform.
+    pub fn split(&self, area: Rect, show_waveform: bool) -> Panes {
+        let waveform_height = if show_waveform {
+            self.waveform_height
+        } else {
+            0
+        };
+        let stacked = self.arrangement == Arrangement::Stacked;
+        let transcript_height = if stacked {
+            self.transcript_height.max(1)
+        } else {
+            0
+        };
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),                 // Title
+                Constraint::Length(waveform_height),   // Waveform
+                Constraint::Length(transcript_height), // Transcript (stacked)
+                Constraint::Length(3),                 // Status
+                Constraint::Min(6),                    // Focus stack and activity
+                Constraint::Length(3),                 // Help bar
+            ])
+            .split(area);
+        let main = rows[4];
+        let (transcript, focus, activity) = if stacked {
+            let columns = halves(Direction::Horizontal, main);
+            (rows[2], columns[0], columns[1])
+        } else {
+            let width = self.transcript_width.clamp(10, 90);
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(width),
+                    Constraint::Percentage(100 - width),
+                ])
+                .split(main);
+            let right = halves(Direction::Vertical, columns[1]);
+            (columns[0], right[0], right[1])
+        };
+        Panes {
+            title: rows[0],
+            waveform: (waveform_height > 0).then_some(rows[1]),
+            transcript,
+            status: rows[3],
+            main,
+            focus,
+            activity,
+            help: rows[5],
+        }
+    }
+}
+
+fn halves(direction: Direction, area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
+        .direction(direction)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_layout() {
+        let panes = LayoutConfig::default().split(Rect::new(0, 0, 100, 40), true);
+        assert_eq!(panes.waveform, Some(Rect::new(0, 3, 100, 10)));
+        assert_eq!(panes.transcript, Rect::new(0, 13, 100, 2));
+        assert_eq!(panes.status.y, 15);
+        assert_eq!(panes.focus, Rect::new(0, 18, 50, 19));
+        assert_eq!(panes.activity, Rect::new(50, 18, 50, 19));
+        assert_eq!(panes.help.y, 37);
+    }
+
+    #[test]
+    fn test_panel_toggle() {
+        assert_eq!(Panel::Stats.toggle(None), Some(Panel::Stats));
+        assert_eq!(Panel::Stats.toggle(Some(Panel::Stats)), None);
+        assert_eq!(Panel::Stats.toggle(Some(Panel::Queue)), Some(Panel::Stats));
+    }
+
+    #[test]
+    fn test_hidden_waveform_and_side_by_side() {
+        let config = LayoutConfig {
+            arrangement: Arrangement::SideBySide,
+            transcript_width: 30,
+            ..LayoutConfig::default()
+        };
+        let panes = config.split(Rect::new(0, 0, 100, 40), false);
+        assert_eq!(panes.waveform, None);
+        assert_eq!(panes.status.y, 3);
+        assert_eq!(panes.transcript, Rect::new(0, 6, 30, 31));
+        assert_eq!(panes.focus.x, 30);
+        assert_eq!(panes.activity.y, panes.focus.bottom());
+        assert_eq!(panes.main, Rect::new(0, 6, 100, 31));
+    }
+}
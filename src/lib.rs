@@ -0,0 +1,172 @@
+//! Conch — voice input pipeline for OpenCode.
+//!
+//! This crate holds everything the `conch` TUI is built from, so other tools
+//! can embed the same capture → transcribe → send flow without the terminal
+//! frontend:
+//!
+//! - [`audio`] — microphone capture via cpal and the shared [`audio::RingBuffer`]
+//! - [`mixer`] — several mics at once, following the loudest or mixed
+//! - [`fake_audio`] — a WAV file or tone standing in for the microphone
+//! - [`stt`] — local Whisper transcription ([`stt::Transcriber`])
+//! - [`queue`] — recordings waiting to be transcribed, each cancellable
+//! - [`vocabulary`] — the project's file names and symbols, priming Whisper
+//! - [`language`] — per-session spoken language and its transcript rules
+//! - [`bench`] — throughput measurements for `conch bench`
+//! - [`viz`] — RMS waveform data and the braille [`viz::WaveformWidget`]
+//! - [`focus`] — the focus stack derived from OpenCode tool events
+//! - [`git`] — branch and working tree status of the session directory
+//! - [`preview`] — lines of the focused file, around its last edit
+//! - [`conversation`] — recent prompts and the last reply in the context block
+//! - [`activity`] — live feed of OpenCode tool calls
+//! - [`timeline`] — prompts, replies and tool calls as one conversation
+//! - [`stats`] — prompts, latencies, tool calls and files touched in a session
+//! - [`diff`] — unified diffs of the edits OpenCode makes
+//! - [`tools`] — display and focus rules for MCP and custom tools
+//! - [`transport`] — HTTP/SSE client for the OpenCode server
+//! - [`session`] — task owning the OpenCode client and current session
+//! - [`agent`] — which OpenCode agent prompts go to, switched by voice or key
+//! - [`inspector`] — raw SSE events for the debug pane
+//! - [`replay`] — capturing SSE event logs and replaying them without a server
+//! - [`resources`] — conch's own CPU and memory use, for the diagnostics view
+//! - [`profiling`] — flamegraphs of the hot paths' tracing spans (`--profile`)
+//! - [`mock`] — a stand-in OpenCode server for `--mock` and end-to-end tests
+//! - [`tabs`] — several sessions attached at once, events routed per tab
+//! - [`bus`] — typed publish/subscribe event bus with topics
+//! - [`supervisor`] — background tasks and threads, reported when they die and restarted
+//! - [`notify`] — user-facing notifications with severity and history
+//! - [`pacer`] — when the TUI redraws, so an idle client stays cheap
+//! - [`theme`] — the TUI's colors by role, with high-contrast and colorblind-safe palettes
+//! - [`glyphs`] — icons and markers for fonts without emoji or braille
+//! - [`layout`] — where the TUI's panes go, stacked or side by side
+//! - [`keymap`] — key presets, and the vim keys' transcript cursor
+//! - [`config`] — user settings loaded from `conch.toml`
+//! - [`reload`] — watching `conch.toml` and applying edits live
+//! - [`remote_mic`] — `conch mic`, streaming a mic to a TUI on another machine
+//! - [`remote_stt`] — `conch serve-stt` and transcribing on it from another machine
+//! - [`paths`] — platform config, data, state and cache directories
+//! - [`logs`] — the debug log, with size-based rotation and retention
+//! - [`autostop`] — stops forgotten recordings on silence or a time limit
+//! - [`clipboard`] — copying transcripts to the system clipboard
+//! - [`cues`] — short tones for record start/stop and transcript ready
+//! - [`hooks`] — shell commands fired on pipeline events
+//! - [`history`] — transcript history persisted across runs
+//! - [`homophones`] — project names and homophones fixed in transcripts
+//! - [`input`] — line editing for typed prompts
+//! - [`palette`] — filterable command palette
+//! - [`export`] — session conversation export to Markdown or JSON
+//! - [`models`] — download and management of ggml Whisper models
+//! - [`send`] — auto-send policy (confidence and keyword gating)
+//! - [`hallucination`] — phrases Whisper makes up from silence, dropped or held for review
+//! - [`numbers`] — spoken numbers and versions written as digits
+//! - [`rewrite`] — numbers, casing and names rewritten in a transcript, each change kept
+//! - [`answer`] — spoken yes/no/cancel answers to the pending prompt
+//! - [`route`] — sending a transcript to the clipboard, another app or notes instead
+//! - [`snippets`] — named prompt templates with focus placeholders and spoken parameters
+//! - [`dictation`] — long-form dictation, cut into segments on pauses
+//! - [`spelling`] — spelled-out identifiers ("victor echo charlie" → `vec`)
+//! - [`casing`] — spoken casing commands ("camel case fetch user" → `fetchUser`)
+//! - [`wake`] — opt-in wake word detection on an always-open mic
+//! - [`tts`] — optional spoken readback of assistant replies
+//! - [`handsfree`] — re-arming the mic after each reply for a spoken back-and-forth
+//! - [`trigger`] — push-to-talk from a MIDI controller or HID foot pedal
+//! - [`caption`] — live captions of the recording for a shared screen
+//! - [`control`] — a local socket for driving a running conch from scripts
+//!
+//! A minimal embedding records a buffer, transcribes it, and sends the text
+//! along with the current focus context:
+//!
+//! ```no_run
+//! use conch::audio::AudioCapture;
+//! use conch::focus::FocusState;
+//! use conch::stt::Transcriber;
+//! use conch::transport::OpenCodeClient;
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let audio = AudioCapture::new()?;
+//! let transcriber = Transcriber::new("ggml-base.en.bin")?;
+//!
+//! audio.start_recording();
+//! // ... user speaks ...
+//! let samples = audio.stop_recording();
+//! let text = transcriber.transcribe(&samples, audio.sample_rate())?;
+//!
+//! let focus = FocusState::new();
+//! let prompt = match focus.to_context_string() {
+//!     Some(ctx) => format!("{}\n{}", ctx, text),
+//!     None => text,
+//! };
+//!
+//! let mut client = OpenCodeClient::new("http://127.0.0.1:4096");
+//! client.create_session(None).await?;
+//! client.send_prompt(&prompt).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod activity;
+pub mod agent;
+pub mod answer;
+pub mod audio;
+pub mod autostop;
+pub mod bench;
+pub mod bus;
+pub mod caption;
+pub mod casing;
+pub mod clipboard;
+pub mod config;
+pub mod control;
+pub mod conversation;
+pub mod cues;
+pub mod dictation;
+pub mod diff;
+pub mod export;
+pub mod fake_audio;
+pub mod focus;
+pub mod git;
+pub mod glyphs;
+pub mod hallucination;
+pub mod handsfree;
+pub mod history;
+pub mod homophones;
+pub mod hooks;
+pub mod input;
+pub mod inspector;
+pub mod keymap;
+pub mod language;
+pub mod layout;
+pub mod logs;
+pub mod mixer;
+pub mod mock;
+pub mod models;
+pub mod notify;
+pub mod numbers;
+pub mod pacer;
+pub mod palette;
+pub mod paths;
+pub mod preview;
+pub mod profiling;
+pub mod queue;
+pub mod reload;
+pub mod remote_mic;
+pub mod remote_stt;
+pub mod replay;
+pub mod resources;
+pub mod rewrite;
+pub mod route;
+pub mod send;
+pub mod session;
+pub mod snippets;
+pub mod spelling;
+pub mod stats;
+pub mod stt;
+pub mod supervisor;
+pub mod tabs;
+pub mod theme;
+pub mod timeline;
+pub mod tools;
+pub mod transport;
+pub mod trigger;
+pub mod tts;
+pub mod viz;
+pub mod vocabulary;
+pub mod wake;
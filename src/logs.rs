@@ -0,0 +1,311 @@
+// Logs Module - The debug log, with rotation and retention
+//
+// Every line goes to `conch.log` in the state directory. The SSE stream is
+// chatty, so once the log passes `[log] max_mb` it is rotated: `conch.log`
+// becomes `conch.log.1`, the older ones shift up, and only `keep` of them
+// are kept. Rotated logs older than `max_age_days` are deleted at startup.
+// `conch logs` shows or follows the current log; its arguments are parsed
+// here.
+
+use std::fs::OpenOptions;
+use std::io::{Read as _, Seek as _, SeekFrom, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+
+/// `[log]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    /// Rotate the log once it reaches this many megabytes (0 = never).
+    pub max_mb: u64,
+    /// Rotated logs to keep (`conch.log.1` is the newest).
+    pub keep: usize,
+    /// Delete rotated logs older than this many days (0 = keep them).
+    pub max_age_days: u64,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            max_mb: 5,
+            keep: 3,
+            max_age_days: 14,
+        }
+    }
+}
+
+impl LogConfig {
+    fn max_bytes(&self) -> u64 {
+        self.max_mb.saturating_mul(1024 * 1024)
+    }
+}
+
+/// Rotation settings for [`append`]; the defaults until [`configure`] is called.
+static CONFIG: Mutex<Option<LogConfig>> = Mutex::new(None);
+
+/// Use `config` for rotation from now on.
+pub fn configure(config: LogConfig) {
+    if let Ok(mut current) = CONFIG.lock() {
+        *current = Some(config);
+    }
+}
+
+/// Append `line` to the log at `path`, rotating it first if it is full.
+/// Logging must never get in the way, so failures are ignored.
+pub fn append(path: &Path, line: &str) {
+    let config = CONFIG
+        .lock()
+        .ok()
+        .and_then(|c| c.clone())
+        .unwrap_or_default();
+    let full = std::fs::metadata(path)
+        .is_ok_and(|m| config.max_bytes() > 0 && m.len() >= config.max_bytes());
+    if full {
+        let _ = rotate(path, config.keep);
+    }
+    if let Ok(mut f) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(f, "{line}");
+    }
+}
+
+/// `path` with a rotation number: `conch.log.2`.
+pub fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+/// Shift `path` to `path.1`, `path.1` to `path.2` and so on, dropping
+/// whatever would land past `keep`. With `keep` 0 the log is just deleted.
+pub fn rotate(path: &Path, keep: usize) -> Result<()> {
+    if keep == 0 {
+        return std::fs::remove_file(path).map_err(Into::into);
+    }
+    let _ = std::fs::remove_file(rotated_path(path, keep));
+    for n in (1..keep).rev() {
+        let from = rotated_path(path, n);
+        if from.exists() {
+            std::fs::rename(&from, rotated_path(path, n + 1))?;
+        }
+    }
+    std::fs::rename(path, rotated_path(path, 1))
+        .map_err(|e| anyhow!("Failed to rotate '{}': {}", path.display(), e))
+}
+
+/// Delete rotated logs past `keep` or older than `max_age_days`. Returns how
+/// many were deleted.
+pub fn prune(path: &Path, config: &LogConfig, now: SystemTime) -> Result<usize> {
+    let max_age = Duration::from_secs(config.max_age_days.saturating_mul(24 * 60 * 60));
+    let mut deleted = 0;
+    for n in 1.. {
+        let old = rotated_path(path, n);
+        let Ok(meta) = std::fs::metadata(&old) else {
+            break;
+        };
+        let age = meta
+            .modified()
+            .ok()
+            .and_then(|m| now.duration_since(m).ok())
+            .unwrap_or_default();
+        if n > config.keep || (config.max_age_days > 0 && age > max_age) {
+            std::fs::remove_file(&old)?;
+            deleted += 1;
+        }
+    }
+    Ok(deleted)
+}
+
+/// What `conch logs` was asked to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogsCommand {
+    /// Print where the log is.
+    Path,
+    /// Open the log in a pager.
+    Open,
+    /// Print the last `count` lines, then keep printing new ones if `follow`.
+    Tail { count: usize, follow: bool },
+}
+
+impl LogsCommand {
+    /// Lines printed without `-n`.
+    pub const DEFAULT_LINES: usize = 50;
+
+    /// Parse the arguments of `conch logs [-n <lines>] [--follow | --open |
+    /// --path]`.
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let has = |flag: &str| args.iter().any(|a| a == flag);
+        if has("--path") {
+            return Ok(LogsCommand::Path);
+        }
+        if has("--open") {
+            return Ok(LogsCommand::Open);
+        }
+        let count = match args.windows(2).find(|w| w[0] == "-n") {
+            Some(w) => w[1]
+                .parse()
+                .map_err(|_| anyhow!("-n needs a number of lines, got '{}'", w[1]))?,
+            None => Self::DEFAULT_LINES,
+        };
+        let follow = has("--follow") || has("-f");
+        Ok(LogsCommand::Tail { count, follow })
+    }
+}
+
+/// Open the log at `path` in `pager`, a shell command, and wait for it to
+/// be closed.
+pub fn open_in_pager(path: &Path, pager: &str) -> Result<()> {
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{pager} \"$1\""))
+        .arg("sh")
+        .arg(path)
+        .status()?;
+    if !status.success() {
+        return Err(anyhow!("{pager} exited with {status}"));
+    }
+    Ok(())
+}
+
+/// The last `count` lines of the log at `path`.
+pub fn tail(path: &Path, count: usize) -> Result<Vec<String>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read log '{}': {}", path.display(), e))?;
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.len().saturating_sub(count);
+    Ok(lines[start..].iter().map(|l| l.to_string()).collect())
+}
+
+/// Follows a log as it grows, starting over when it is rotated.
+#[derive(Debug)]
+pub struct Follower {
+    path: PathBuf,
+    offset: u64,
+}
+
+impl Follower {
+    /// Follow `path` from its current end.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let offset = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        Self { path, offset }
+    }
+
+    /// Text appended since the last call.
+    pub fn read_new(&mut self) -> Result<String> {
+        let Ok(mut file) = std::fs::File::open(&self.path) else {
+            // Between rotation and the next line being written
+            self.offset = 0;
+            return Ok(String::new());
+        };
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            self.offset = 0;
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        self.offset += bytes.len() as u64;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("conch_logs_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("conch.log")
+    }
+
+    #[test]
+    fn test_rotate_keeps_newest() {
+        let path = temp_log("rotate");
+        for text in ["first", "second", "third"] {
+            std::fs::write(&path, text).unwrap();
+            rotate(&path, 2).unwrap();
+        }
+        assert!(!path.exists());
+        assert_eq!(
+            std::fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+            "third"
+        );
+        assert_eq!(
+            std::fs::read_to_string(rotated_path(&path, 2)).unwrap(),
+            "second"
+        );
+        assert!(!rotated_path(&path, 3).exists());
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_prune_by_count_and_age() {
+        let path = temp_log("prune");
+        for n in 1..=4 {
+            std::fs::write(rotated_path(&path, n), "old").unwrap();
+        }
+        let config = LogConfig {
+            keep: 3,
+            max_age_days: 14,
+            ..LogConfig::default()
+        };
+        assert_eq!(prune(&path, &config, SystemTime::now()).unwrap(), 1);
+        assert!(!rotated_path(&path, 4).exists());
+
+        // A month from now, all of them are too old
+        let later = SystemTime::now() + Duration::from_secs(30 * 24 * 60 * 60);
+        assert_eq!(prune(&path, &config, later).unwrap(), 3);
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_tail_and_follow() {
+        let path = temp_log("tail");
+        std::fs::write(&path, "one\ntwo\nthree\n").unwrap();
+        assert_eq!(tail(&path, 2).unwrap(), vec!["two", "three"]);
+        assert_eq!(tail(&path, 10).unwrap().len(), 3);
+
+        let mut follower = Follower::new(&path);
+        assert_eq!(follower.read_new().unwrap(), "");
+        std::fs::write(&path, "one\ntwo\nthree\nfour\n").unwrap();
+        assert_eq!(follower.read_new().unwrap(), "four\n");
+        // Rotated: start over from the top of the new file
+        std::fs::write(&path, "five\n").unwrap();
+        assert_eq!(follower.read_new().unwrap(), "five\n");
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_logs_command_from_args() {
+        let parse = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+            LogsCommand::from_args(&args)
+        };
+        let tail = |count, follow| LogsCommand::Tail { count, follow };
+        assert_eq!(parse(&[]).unwrap(), tail(50, false));
+        assert_eq!(parse(&["-n", "200", "-f"]).unwrap(), tail(200, true));
+        assert_eq!(parse(&["--follow"]).unwrap(), tail(50, true));
+        assert_eq!(parse(&["--path"]).unwrap(), LogsCommand::Path);
+        assert_eq!(parse(&["-n", "5", "--open"]).unwrap(), LogsCommand::Open);
+        let err = parse(&["-n", "lots"]).unwrap_err();
+        assert_eq!(err.to_string(), "-n needs a number of lines, got 'lots'");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_in_pager() {
+        let path = temp_log("pager");
+        std::fs::write(&path, "one\n").unwrap();
+        let copy = path.with_extension("seen");
+        open_in_pager(&path, &format!("cp -- \"$1\" {} && test -f", copy.display())).unwrap();
+        assert_eq!(std::fs::read_to_string(&copy).unwrap(), "one\n");
+        let err = open_in_pager(&path, "false").unwrap_err();
+        assert!(err.to_string().starts_with("false exited with"), "{err}");
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -1,604 +1,5040 @@
 // Conch — Voice input client for OpenCode
 //
-// Phase 1: Audio capture + Whisper STT + Minimal TUI
-// Phase 2: Spectrogram visualization
-// Phase 3: OpenCode transport (HTTP/SSE)
-
-mod audio;
-mod focus;
-mod stt;
-mod transport;
-mod viz;
+// TUI frontend over the `conch` library: parses arguments, hands the
+// subcommands to their library modules, and runs the ratatui event loop that
+// wires audio capture, Whisper, the focus stack, and the OpenCode transport
+// together. Keys resolve to actions in `keymap`, panels live in `layout`, and
+// the wake word is polled through `wake::HotMic`.
 
 #[cfg(test)]
 mod integration_tests;
 #[cfg(test)]
 mod test_utils;
 
-use std::fs::OpenOptions;
-use std::io::{self, Stdout, Write as _};
-use std::sync::Arc;
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, mpsc};
+use std::time::{Duration, Instant};
 
 use anyhow::{Result, anyhow};
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+    PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
 use crossterm::execute;
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
 use ratatui::Terminal;
-use ratatui::backend::CrosstermBackend;
-use ratatui::layout::{Alignment, Constraint, Direction, Layout};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 
-use audio::{AudioCapture, RecordingState};
-use focus::FocusState;
-use stt::Transcriber;
-use transport::{
-    ConnectionStatus, OpenCodeClient, ServerEvent, extract_sse_data_lines, parse_sse_event,
+use conch::activity::{ActivityFeed, ActivityStatus};
+use conch::agent;
+use conch::answer::{self, Answer};
+use conch::audio::{self, AudioCapture, RecordingState, Recovery, StreamRecovery};
+use conch::autostop::{AutoStop, AutoStopConfig, AutoStopStatus};
+use conch::bench;
+use conch::bus::{EventBus, Topic, Topical};
+use conch::caption::{self, CaptionConfig, Captions};
+use conch::casing;
+use conch::clipboard;
+use conch::config::Config;
+use conch::control::{self, ControlSocket, FocusAction, RecordAction, Response};
+use conch::conversation::ContextConfig;
+use conch::cues::{Cue, CueConfig, CuePlayer};
+use conch::dictation::{Cut, Dictation, Join};
+use conch::diff::DiffKind;
+use conch::export::{self, ExportArgs};
+use conch::fake_audio::FakeSource;
+use conch::focus::{
+    self, FocusEntry, FocusMarks, FocusState, FocusStore, VoiceCommand, VoiceOutcome,
 };
-use viz::{WaveformData, WaveformWidget};
+use conch::git::GitStatus;
+use conch::glyphs::{Charset, Glyphs};
+use conch::hallucination;
+use conch::handsfree::HandsFree;
+use conch::history::{self, HistoryEntry, TranscriptStatus, TranscriptStore, Unanswered};
+use conch::homophones::{self, Corrected};
+use conch::hooks::{HookObserver, Hooks, Observed};
+use conch::input::{Recall, TextInput};
+use conch::inspector::Inspector;
+use conch::keymap::{Action, KeyContext, Keymap, TranscriptCursor, VimAction, VimKeys};
+use conch::language::{self, Language};
+use conch::layout::{Arrangement, LayoutConfig, Panel};
+use conch::logs::{self, LogsCommand};
+use conch::mock::{self, MockServer};
+use conch::models;
+use conch::notify::{self, Notifications, Severity};
+use conch::pacer::FramePacer;
+use conch::palette::{Palette, PaletteAction, PaletteItem};
+use conch::paths;
+use conch::preview::{LastEdits, Preview, PreviewConfig};
+use conch::profiling;
+use conch::queue::{Ticket, TranscriptionQueue};
+use conch::reload::{self, ConfigWatcher};
+use conch::remote_mic;
+use conch::remote_stt;
+use conch::replay::{Capture, Replay};
+use conch::resources::{self, ResourceMonitor};
+use conch::rewrite::Rewrites;
+use conch::route::{self, RouteConfig, Target};
+use conch::send::ReviewReason;
+use conch::session::{self, RetryPolicy, SendOrder, SendTurn, SessionHandle};
+use conch::snippets::{self, Snippets};
+use conch::spelling;
+use conch::stats::SessionStats;
+use conch::stt::{SpeechGate, SttConfig, Transcriber, Transcript};
+use conch::supervisor::{self, Exit, Report, Restart, Supervisor};
+use conch::tabs::{Route, SessionTab, TabSet};
+use conch::theme::Theme;
+use conch::timeline::{Timeline, Turn};
+use conch::tools::{self, ToolRule};
+use conch::transport::{
+    self, ConnectionConfig, ConnectionStatus, Heartbeats, MessagePart, OpenCodeClient, ServerEvent,
+    SessionInfo, extract_sse_data_lines, parse_sse_event,
+};
+use conch::trigger::{self, TriggerEvent};
+use conch::tts::{self, Speaker};
+use conch::viz::{self, WaveformConfig, WaveformWidget};
+use conch::vocabulary::Vocabulary;
+use conch::wake::{self, HotMic, KeywordModel, WakeCheck, WakeDetector};
 
-/// Noise floor threshold for RMS normalization.
-const NOISE_FLOOR: f32 = 0.001;
 /// OpenCode server base URL.
 const OPENCODE_URL: &str = "http://127.0.0.1:4096";
+/// How long a recording's signal can be dead before the mic is reported
+/// silent.
+const DEAD_MIC: Duration = Duration::from_secs(3);
+/// Windows a finished recording's waveform is kept in, enough for any
+/// terminal width.
+const UTTERANCE_WINDOWS: usize = 1024;
 
 /// Application state for the TUI.
 struct App {
     /// Current recording state.
     state: RecordingState,
-    /// History of transcripts (newest last).
-    transcripts: Vec<String>,
-    /// Error message to display, if any.
-    error: Option<String>,
-    /// Whether we're waiting for a background transcription.
-    pending_transcript: bool,
+    /// History of transcripts (newest last), including ones restored from the store.
+    transcripts: Vec<HistoryEntry>,
+    /// Notifications: the toast in the status area plus their history.
+    notices: Notifications,
+    /// The view shown in place of the focus stack pane, if any.
+    panel: Option<Panel>,
+    /// Lines scrolled down in the notification history, newest first.
+    notice_scroll: usize,
+    /// Raw SSE events as received, for the debug pane.
+    inspector: Inspector,
+    /// CPU and memory use, measured while it's shown.
+    resources: ResourceMonitor,
+    /// Frames drawn since startup.
+    frames: u64,
+    /// Lines scrolled down in the event inspector, newest first.
+    inspector_scroll: usize,
+    /// Recordings handed to the transcription thread and not yet back.
+    transcribing: usize,
+    /// The same recordings, for listing and cancelling the waiting ones.
+    queue: TranscriptionQueue<JobKind>,
+    /// Selected line in the list of waiting recordings.
+    queue_cursor: usize,
+    /// Transcripts that arrived while another prompt was pending, oldest first.
+    transcript_queue: VecDeque<Transcript>,
     /// Waveform amplitudes for current frame, one per display column.
     waveform_bars: Vec<f32>,
+    /// RMS levels of the last recording, shown still while it's transcribed
+    /// and confirmed.
+    utterance: Option<Vec<f32>>,
+    /// Samples of the last prompt recording, kept after it's sent so it
+    /// can be transcribed again.
+    last_recording: Option<Vec<f32>>,
+    /// Waveform sensitivity, adjusted live with `[` and `]`.
+    waveform: WaveformConfig,
+    /// Pane arrangement and sizes (`[layout]`).
+    layout: LayoutConfig,
+    /// Whether the waveform pane is shown, toggled with `w`.
+    show_waveform: bool,
+    /// File preview settings (`[preview]`).
+    preview_config: PreviewConfig,
+    /// Preview of the File entry under the focus pointer.
+    preview: Option<Preview>,
+    /// Latest edit of each file, for the preview to start at.
+    last_edits: LastEdits,
+    /// Colors everything is drawn in (`[theme]`).
+    theme: Theme,
+    /// Icons and markers the terminal can show (`[display] unicode`).
+    glyphs: Glyphs,
     /// Transcript pending user confirmation before sending to OpenCode.
     prompt_pending: Option<String>,
+    /// Prompt being typed, while in text-input mode.
+    input: Option<TextInput>,
+    /// Earlier prompts, recalled into the input line with Up and Down.
+    recall: Recall,
+    /// Command palette, while open.
+    palette: Option<Palette>,
+    /// Key preset (`[keys] keymap`).
+    keymap: Keymap,
+    /// A pending `g` in the vim preset.
+    vim_keys: VimKeys,
+    /// Transcript being browsed or selected with the vim keys.
+    transcript_cursor: TranscriptCursor,
+    /// Transcript search being typed after `/`.
+    search: Option<TextInput>,
+    /// Last transcript search, repeated with `n` and `N`.
+    search_query: String,
+    /// Prompt templates from `[snippets]`.
+    snippets: Snippets,
+    /// The OpenCode agent prompts go to; `None` leaves it to OpenCode.
+    agent: Option<String>,
+    /// Agents to switch between (`[agent]`).
+    agents: Vec<String>,
+    /// Where the number keys and spoken targets send a prompt (`[route]`).
+    route: RouteConfig,
     /// OpenCode connection status.
     connection_status: ConnectionStatus,
+    /// Heartbeats and reconnects of the event stream, for the title bar.
+    heartbeats: Heartbeats,
+    /// Current OpenCode session ID, once connected.
+    session_id: Option<String>,
     /// Current session slug for display.
     session_slug: Option<String>,
+    /// The session in front was deleted and no tab was left to switch to:
+    /// prompts wait until [t] starts a new session.
+    detached: bool,
+    /// Project directory sessions are looked up for, when connected live.
+    directory: Option<PathBuf>,
+    /// The sessions OpenCode has, as last listed, for the palette.
+    sessions: Vec<SessionInfo>,
     /// Whether OpenCode is currently busy processing.
     opencode_busy: bool,
+    /// When OpenCode became busy, while it is.
+    busy_since: Option<Instant>,
     /// Focus stack state.
     focus: FocusState,
+    /// Recent tool calls, shown next to the focus stack.
+    activity: ActivityFeed,
+    /// Text of the assistant's last reply in the session in front.
+    last_reply: Option<String>,
+    /// Prompts, tool calls and replies of the session in front.
+    timeline: Timeline,
+    /// Whether the conversation replaces the transcript list (`c`).
+    show_conversation: bool,
+    /// Tool calls and transcription times of the session in front.
+    stats: SessionStats,
+    /// Which conversation turns go into the context block (`[context]`).
+    context: ContextConfig,
+    /// Attached sessions. The active tab's transcripts, focus, activity and
+    /// busy state live in the fields above; the others are kept in their tabs.
+    tabs: TabSet,
+    /// Pipeline events, for observers such as hooks.
+    bus: EventBus<AppMessage>,
+    /// Background tasks and threads, reported when they die.
+    tasks: Supervisor,
+    /// Wake phrase, when wake word listening is enabled.
+    wake_phrase: Option<String>,
+    /// Whether the mic is open listening for the wake word.
+    hot_mic: bool,
+    /// Git status of the working directory, when it is a repository.
+    git: Option<GitStatus>,
+    /// Send transcripts without waiting for Enter.
+    auto_send: bool,
+    /// How failed sends are retried.
+    retry: RetryPolicy,
+    /// The send being retried: which retry, out of how many.
+    send_retry: Option<(u32, u32)>,
+    /// Turns of the prompt sends, so one being retried holds back those
+    /// confirmed after it.
+    send_order: SendOrder,
+    /// Prompts handed to OpenCode and not yet accepted or refused, by
+    /// transcript and text, so a double Enter can't send one twice.
+    sending: Vec<(Option<u64>, String)>,
+    /// Spelling mode: transcripts are spelled out and added to the prompt.
+    spelling: bool,
+    /// The pending prompt ends with text spelled in this spelling mode, so
+    /// the next spelled text joins it without a space.
+    spelled_tail: bool,
+    /// When auto-sending with a cancel window, the pending prompt goes out at this instant.
+    send_deadline: Option<Instant>,
+    /// Why auto-send held the pending prompt for confirmation.
+    review: Option<ReviewReason>,
+    /// Where transcripts are persisted, when history is enabled.
+    history: Option<TranscriptStore>,
+    /// Where pinned and forgotten focus entries are kept, with the history.
+    focus_marks: Option<FocusStore>,
+    /// Sent prompts waiting for their session to go idle.
+    unanswered: Unanswered,
+    /// Whether a reply is being read aloud (the mic is ducked meanwhile).
+    speaking: bool,
+    /// Re-arms the mic after each reply in hands-free mode.
+    hands_free: HandsFree,
+    /// The long-form dictation being recorded or finished.
+    dictation: Option<Dictation>,
+    /// Caption strip settings; `C` switches it on and off.
+    caption: CaptionConfig,
+    /// What the caption strip shows, when it's on.
+    captions: Captions,
+    /// Audible cue output, when enabled and an output device is available.
+    cues: Option<CuePlayer>,
+    /// When the current recording started.
+    recording_started: Option<Instant>,
+    /// Fraction of the recording buffer used by the current recording.
+    buffer_usage: f32,
+    /// The mic has delivered only zeros or a constant level for
+    /// [`DEAD_MIC`] of the current recording.
+    mic_dead: bool,
+    /// Restarts of the audio stream after it failed.
+    audio_recovery: StreamRecovery,
+    /// The current recording answers the pending prompt rather than
+    /// dictating a new one.
+    answering: bool,
+    /// Silence and max-duration limits for the current recording.
+    autostop: AutoStop,
+    /// Seconds left before the max-duration limit stops the recording.
+    stop_countdown: Option<u64>,
+    /// When Space asked to stop the recording, while a second press can
+    /// still discard it instead.
+    stop_tapped: Option<Instant>,
+    /// Owner of the OpenCode client and current session, once running.
+    opencode: Option<SessionHandle>,
+    /// Language the session in front is dictated in, once switched by voice.
+    language: Option<&'static Language>,
+    /// Language from `[stt]`, for sessions that haven't switched.
+    default_language: &'static Language,
+    /// Whether the Whisper model only understands English.
+    english_only_model: bool,
+    /// Where models named in a re-transcription are looked up.
+    models_dir: PathBuf,
+    /// Model a pending prompt is transcribed again with, from `[stt]`.
+    retry_model: String,
+    /// How copies reach the clipboard, from `[clipboard]`.
+    clipboard: clipboard::Backend,
+    /// File names and symbols of the project, priming Whisper.
+    vocabulary: Arc<Vocabulary>,
+    /// Directory the vocabulary is built from, once asked for.
+    vocabulary_dir: Option<PathBuf>,
+    /// Names and homophones fixed in the newest transcript, to undo.
+    corrected: Corrected,
+    /// Sample rate of the input device, in Hz.
+    sample_rate: u32,
+    /// Refuse to record when the mic rate is too low for speech.
+    refuse_low_rate: bool,
 }
 
 impl App {
-    fn new(_sample_rate: u32) -> Self {
+    fn new(sample_rate: u32) -> Self {
         Self {
             state: RecordingState::Idle,
             transcripts: Vec::new(),
-            error: None,
-            pending_transcript: false,
+            notices: Notifications::new(),
+            panel: None,
+            notice_scroll: 0,
+            inspector: Inspector::new(),
+            resources: ResourceMonitor::new(),
+            frames: 0,
+            inspector_scroll: 0,
+            transcribing: 0,
+            queue: TranscriptionQueue::new(),
+            queue_cursor: 0,
+            transcript_queue: VecDeque::new(),
             waveform_bars: Vec::new(),
+            utterance: None,
+            last_recording: None,
+            waveform: WaveformConfig::default(),
+            theme: Theme::default(),
+            glyphs: Glyphs::default(),
+            layout: LayoutConfig::default(),
+            show_waveform: true,
+            preview_config: PreviewConfig::default(),
+            preview: None,
+            last_edits: LastEdits::default(),
+            git: None,
             prompt_pending: None,
+            input: None,
+            recall: Recall::default(),
+            keymap: Keymap::default(),
+            vim_keys: VimKeys::default(),
+            transcript_cursor: TranscriptCursor::default(),
+            search: None,
+            search_query: String::new(),
+            palette: None,
+            snippets: Snippets::new(),
+            agent: None,
+            agents: Vec::new(),
+            route: RouteConfig::default(),
             connection_status: ConnectionStatus::Disconnected,
+            heartbeats: Heartbeats::new(&ConnectionConfig::default()),
+            session_id: None,
             session_slug: None,
+            detached: false,
+            directory: None,
+            sessions: Vec::new(),
             opencode_busy: false,
+            busy_since: None,
             focus: FocusState::new(),
+            activity: ActivityFeed::new(),
+            last_reply: None,
+            timeline: Timeline::new(),
+            show_conversation: false,
+            stats: SessionStats::new(),
+            context: ContextConfig::default(),
+            tabs: TabSet::new(),
+            bus: EventBus::new(),
+            tasks: Supervisor::new(),
+            wake_phrase: None,
+            hot_mic: false,
+            auto_send: false,
+            retry: RetryPolicy::NONE,
+            send_retry: None,
+            send_order: SendOrder::default(),
+            sending: Vec::new(),
+            spelling: false,
+            spelled_tail: false,
+            send_deadline: None,
+            review: None,
+            history: None,
+            focus_marks: None,
+            unanswered: Unanswered::default(),
+            speaking: false,
+            hands_free: HandsFree::default(),
+            dictation: None,
+            caption: CaptionConfig::default(),
+            captions: Captions::default(),
+            cues: None,
+            recording_started: None,
+            answering: false,
+            buffer_usage: 0.0,
+            mic_dead: false,
+            audio_recovery: StreamRecovery::new(),
+            autostop: AutoStop::new(AutoStopConfig::default()),
+            stop_countdown: None,
+            stop_tapped: None,
+            opencode: None,
+            language: None,
+            default_language: language::ENGLISH,
+            english_only_model: false,
+            models_dir: models::default_dir(),
+            retry_model: SttConfig::default().retry_model,
+            clipboard: clipboard::Backend::default(),
+            vocabulary: Arc::default(),
+            vocabulary_dir: None,
+            corrected: Corrected::default(),
+            sample_rate,
+            refuse_low_rate: false,
         }
     }
-}
 
-/// Messages sent from background tasks to the main TUI loop.
-enum AppMessage {
-    TranscriptReady(Result<String>),
-    ServerEvent(ServerEvent),
-    PromptSent(Result<()>),
-    SessionReady { _id: String, slug: Option<String> },
-    ConnectionChanged(ConnectionStatus),
-}
+    /// A new app set up as `config` says.
+    fn with_config(sample_rate: u32, config: &Config) -> Result<Self> {
+        let mut app = Self::new(sample_rate);
+        app.default_language = language::parse(&config.stt.language)?;
+        app.theme = Theme::from_config(&config.theme)?;
+        app.glyphs = config.display.unicode.glyphs();
+        app.layout = config.layout.clone();
+        app.show_waveform = config.layout.waveform;
+        app.keymap = config.keys.keymap;
+        app.preview_config = config.preview.clone();
+        app.models_dir = config.models.dir();
+        app.retry_model = config.stt.retry_model.clone();
+        app.clipboard = config.clipboard.backend;
+        app.heartbeats = Heartbeats::new(&config.connection);
+        app.hands_free = HandsFree::new(&config.handsfree);
+        app.caption = config.caption.clone();
+        app.auto_send = config.send.auto;
+        app.retry = config.send.retry_policy();
+        app.snippets = config.snippets.clone();
+        app.agents = config.agent.agents();
+        app.agent = config.agent.default.clone();
+        app.route = config.route.clone();
+        app.activity = ActivityFeed::with_rules(config.tools.clone());
+        app.autostop = AutoStop::new(config.recording.clone());
+        app.waveform = config.waveform.clone();
+        app.context = config.context.clone();
+        app.refuse_low_rate = config.audio.refuse_low_rate;
+        app.history = config
+            .history
+            .enabled
+            .then(|| TranscriptStore::new(&config.history.path));
+        app.focus_marks = config
+            .history
+            .enabled
+            .then(|| FocusStore::new(paths::focus_file()));
+        Ok(app)
+    }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Parse command-line arguments
-    let args: Vec<String> = std::env::args().collect();
-    let model_path = args
-        .get(1)
-        .map(|s| s.as_str())
-        .unwrap_or("ggml-base.en.bin");
-
-    // Check for --session flag
-    let session_flag = args.windows(2).find_map(|w| {
-        if w[0] == "--session" {
-            Some(w[1].clone())
+    /// Language the session in front is dictated in.
+    fn language(&self) -> &'static Language {
+        self.language.unwrap_or(self.default_language)
+    }
+
+    /// Switch the session in front to `lang`, warning when the model can't
+    /// transcribe it.
+    fn set_language(&mut self, lang: &'static Language) {
+        log(&format!("tui: language {}", lang.code));
+        self.language = Some(lang);
+        if self.english_only_model && !lang.english() {
+            self.notices.warn(format!(
+                "Language: {} (the model is English-only; use a multilingual model)",
+                lang.name
+            ));
         } else {
-            None
+            self.notices.info(format!("Language: {}", lang.name));
         }
-    });
+    }
 
-    // Load Whisper model
-    eprintln!("Loading Whisper model from '{}'...", model_path);
-    let transcriber = match Transcriber::new(model_path) {
-        Ok(t) => Arc::new(t),
-        Err(e) => {
-            eprintln!("Error: {}", e);
-            eprintln!();
-            eprintln!("To use Conch, you need a Whisper model file.");
-            eprintln!("Download one with:");
-            eprintln!(
-                "  curl -L -o ggml-base.en.bin \
-                 https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.en.bin"
-            );
-            eprintln!();
-            eprintln!("Then run: conch ggml-base.en.bin");
-            return Err(e);
+    /// Send later prompts to `agent`, or OpenCode's default when `None`.
+    fn set_agent(&mut self, agent: Option<String>) {
+        log(&format!(
+            "tui: agent {}",
+            agent.as_deref().unwrap_or("default")
+        ));
+        if let Some(opencode) = &self.opencode {
+            opencode.set_agent(agent.clone());
         }
-    };
+        match &agent {
+            Some(name) => self.notices.info(format!("Agent: {name}")),
+            None => self.notices.info("Agent: OpenCode's default"),
+        }
+        self.agent = agent;
+    }
 
-    // Initialize audio capture
-    let audio = AudioCapture::new()?;
-    eprintln!(
-        "Audio device ready ({}Hz). Starting TUI...",
-        audio.sample_rate()
-    );
+    /// Turn spelling mode on or off. The status bar shows the mode, so an
+    /// older toast shouldn't hide it.
+    fn set_spelling(&mut self, on: bool) {
+        log(&format!("tui: spelling {}", if on { "on" } else { "off" }));
+        self.spelling = on;
+        self.spelled_tail = false;
+        self.notices.clear_toast();
+    }
 
-    // Set up terminal
-    terminal::enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    /// Play `cue` if cues are enabled.
+    fn cue(&self, cue: Cue) {
+        if let Some(player) = &self.cues {
+            player.play(cue);
+        }
+    }
 
-    // Run the app, ensuring we restore the terminal on exit
-    let result = run_app(&mut terminal, &audio, &transcriber, session_flag).await;
+    /// Start a new recording.
+    fn start_recording(&mut self, audio: &AudioCapture) {
+        if self.refuse_low_rate
+            && let Some(warning) = audio::low_rate_warning(self.sample_rate)
+        {
+            self.notices.error(warning);
+            return;
+        }
+        self.cue(Cue::RecordStart);
+        audio.start_recording();
+        self.state = RecordingState::Recording;
+        self.recording_started = Some(Instant::now());
+        self.buffer_usage = 0.0;
+        self.mic_dead = false;
+        self.utterance = None;
+        self.answering = self.prompt_pending.is_some();
+        if self.answering {
+            // Hold an auto-send countdown while the answer is given
+            self.send_deadline = None;
+        }
+        self.autostop.reset();
+        self.stop_countdown = None;
+        self.stop_tapped = None;
+        self.hands_free.cancel();
+        self.captions.start();
+        self.notices.clear_toast();
+        // Recorded anyway: a remote mic may connect while it runs
+        if let Some(reason) = audio.unavailable() {
+            self.notices.warn(reason);
+        }
+        self.bus.publish(AppMessage::RecordingStarted);
+    }
 
-    // Restore terminal
-    terminal::disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
+    /// A ticket for transcribing `samples`, queued behind the recordings
+    /// handed over before.
+    fn queue_ticket(&mut self, kind: JobKind, samples: &[f32]) -> Ticket {
+        let audio = samples.len() as f32 / self.sample_rate.max(1) as f32;
+        self.queue.push(kind, Duration::from_secs_f32(audio))
+    }
 
-    result
-}
+    /// Leave Processing once every recording has been transcribed. A new
+    /// recording started meanwhile keeps its Recording state.
+    fn settle_state(&mut self) {
+        if self.state == RecordingState::Processing && self.transcribing == 0 {
+            self.state = RecordingState::Idle;
+        }
+    }
 
-/// Main event loop.
-async fn run_app(
-    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
-    audio: &AudioCapture,
-    transcriber: &Arc<Transcriber>,
-    session_flag: Option<String>,
-) -> Result<()> {
-    let mut app = App::new(audio.sample_rate());
+    /// Persist the current state of `entry`, if history is enabled.
+    fn save_transcript(&self, entry: &HistoryEntry) {
+        if let Some(store) = &self.history
+            && let Err(e) = store.record(entry)
+        {
+            log(&format!("history: {e}"));
+        }
+    }
 
-    // Channel for all messages to the TUI
-    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<AppMessage>();
+    /// Persist the pinned and forgotten focus entries of the session in
+    /// front, if history is enabled.
+    fn save_focus_marks(&self) {
+        if let (Some(store), Some(id)) = (&self.focus_marks, &self.session_id)
+            && let Err(e) = store.save(id, &self.focus.marks())
+        {
+            log(&format!("focus: {e}"));
+        }
+    }
 
-    // Start OpenCode connection in background
-    let tx_oc = tx.clone();
-    let session_flag_clone = session_flag.clone();
-    tokio::spawn(async move {
-        connect_opencode(tx_oc, session_flag_clone).await;
-    });
+    /// Update the status of transcript `id` and persist the change.
+    fn set_transcript_status(&mut self, id: u64, status: TranscriptStatus) {
+        self.update_transcript(id, |entry| entry.status = status);
+    }
 
-    loop {
-        // Drain all pending messages (non-blocking)
-        while let Ok(msg) = rx.try_recv() {
-            match msg {
-                AppMessage::TranscriptReady(result) => {
-                    app.pending_transcript = false;
-                    match result {
-                        Ok(text) if !text.is_empty() => {
-                            app.transcripts.push(text.clone());
-                            app.prompt_pending = Some(text);
-                            app.error = None;
-                        }
-                        Ok(_) => {
-                            app.error = Some("No speech detected".into());
-                        }
-                        Err(e) => {
-                            app.error = Some(format!("Transcription error: {}", e));
-                        }
-                    }
-                    app.state = RecordingState::Idle;
-                }
-                AppMessage::ServerEvent(event) => match event {
-                    ServerEvent::Connected => {
-                        log("tui: SSE connected event");
-                        app.connection_status = ConnectionStatus::Connected;
-                    }
-                    ServerEvent::SessionStatus { session_id, busy } => {
-                        log(&format!(
-                            "tui: session {} status: {}",
-                            session_id,
-                            if busy { "busy" } else { "idle" }
-                        ));
-                        app.opencode_busy = busy;
-                    }
-                    ServerEvent::Tool(ref te) => {
-                        log(&format!(
-                            "tui: tool event: {} (state: {})",
-                            te.tool, te.state
-                        ));
-                        if let Some(entry) = focus::map_tool_event(te) {
-                            app.focus.append(entry);
-                        }
-                    }
-                    ServerEvent::Heartbeat => {}
-                },
-                AppMessage::PromptSent(result) => {
-                    match &result {
-                        Ok(()) => log("tui: prompt sent successfully"),
-                        Err(e) => log(&format!("tui: prompt send failed: {e}")),
-                    }
-                    if let Err(e) = result {
-                        app.error = Some(format!("Send failed: {}", e));
-                    }
-                }
-                AppMessage::SessionReady { slug, .. } => {
-                    app.session_slug = slug;
-                }
-                AppMessage::ConnectionChanged(status) => {
-                    app.connection_status = status;
-                }
+    /// Change transcript `id` with `change` and persist it. The transcript
+    /// may belong to a tab that has since gone to the background.
+    fn update_transcript(&mut self, id: u64, change: impl FnOnce(&mut HistoryEntry)) {
+        if let Some(i) = self.transcripts.iter().position(|e| e.id == id) {
+            change(&mut self.transcripts[i]);
+            self.save_transcript(&self.transcripts[i]);
+            return;
+        }
+        for index in 0..self.tabs.len() {
+            let Some(tab) = self.tabs.get_mut(index) else {
+                continue;
+            };
+            if let Some(entry) = tab.transcripts.iter_mut().find(|e| e.id == id) {
+                change(entry);
+                let entry = entry.clone();
+                self.save_transcript(&entry);
+                return;
             }
         }
+    }
 
-        // Snapshot waveform from ring buffer each frame
-        let num_columns = terminal.size()?.width as usize;
-        if app.state == RecordingState::Recording {
-            // Read ~100ms of recent audio for the snapshot
-            let snapshot_samples = audio.sample_rate() as usize / 10;
-            let samples = audio.read_last_samples(snapshot_samples);
-            if !samples.is_empty() {
-                let rms = viz::compute_rms_windows(&samples, num_columns);
-                app.waveform_bars = rms
-                    .into_iter()
-                    .map(|v| {
-                        // Boost: divide by a low reference so moderate speech fills the display
-                        let boosted = (v / 0.04).clamp(0.0, 1.0);
-                        if boosted < NOISE_FLOOR { 0.0 } else { boosted }
-                    })
-                    .collect();
+    /// Whether a prompt reading `text` is being sent right now.
+    fn is_sending(&self, text: &str) -> bool {
+        self.sending.iter().any(|(_, sent)| sent == text)
+    }
+
+    /// OpenCode reported an error in session `session_id`: the prompts it
+    /// was working on failed.
+    fn mark_failed(&mut self, session_id: &str) {
+        for (id, _) in self.unanswered.answered(session_id, Instant::now()) {
+            self.set_transcript_status(id, TranscriptStatus::Failed);
+        }
+    }
+
+    /// Session `session_id` went from busy to idle: the prompts sent to it
+    /// have been answered.
+    fn mark_answered(&mut self, session_id: &str) {
+        for (id, elapsed) in self.unanswered.answered(session_id, Instant::now()) {
+            log(&format!(
+                "tui: transcript {id} answered in {:.1}s",
+                elapsed.as_secs_f64()
+            ));
+            self.update_transcript(id, |entry| {
+                entry.status = TranscriptStatus::Answered;
+                entry.response_secs = Some(elapsed.as_secs_f64());
+            });
+        }
+    }
+
+    /// Stored transcripts of `session_id`, if history is enabled.
+    fn stored_history(&self, session_id: &str) -> Vec<HistoryEntry> {
+        let Some(store) = &self.history else {
+            return Vec::new();
+        };
+        match store.load_session(session_id) {
+            Ok(restored) => {
+                log(&format!("history: restored {} transcripts", restored.len()));
+                restored
+            }
+            Err(e) => {
+                log(&format!("history: {e}"));
+                Vec::new()
             }
-        } else if !app.waveform_bars.is_empty() {
-            app.waveform_bars.clear();
         }
+    }
 
-        // Draw UI
-        terminal.draw(|f| render(f, &app))?;
+    /// Stored focus marks of `session_id`, if history is enabled.
+    fn stored_focus_marks(&self, session_id: &str) -> FocusMarks {
+        let Some(store) = &self.focus_marks else {
+            return FocusMarks::default();
+        };
+        store.load(session_id).unwrap_or_else(|e| {
+            log(&format!("focus: {e}"));
+            FocusMarks::default()
+        })
+    }
 
-        // Poll for keyboard events
-        if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind != KeyEventKind::Press {
-                    continue;
-                }
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
-                    KeyCode::Char(' ') => {
-                        if app.prompt_pending.is_none() {
-                            handle_space(&mut app, audio, transcriber, &tx)?;
-                        }
-                    }
-                    KeyCode::Enter => {
-                        if let Some(text) = app.prompt_pending.take() {
-                            app.error = None;
-                            let prompt = if let Some(ctx) = app.focus.to_context_string() {
-                                format!("{}\n{}", ctx, text)
-                            } else {
-                                text
-                            };
-                            send_prompt_to_opencode(&prompt, &tx);
-                        }
-                    }
-                    KeyCode::Backspace | KeyCode::Delete => {
-                        if app.prompt_pending.take().is_some() {
-                            app.error = Some("Prompt discarded".into());
-                        }
-                    }
-                    KeyCode::Up => {
-                        app.focus.move_up();
-                    }
-                    KeyCode::Down => {
-                        app.focus.move_down();
-                    }
-                    KeyCode::Char('f') => {
-                        if app.prompt_pending.is_none() {
-                            app.focus.toggle_follow_mode();
-                        }
-                    }
-                    KeyCode::Char('c')
-                        if key
-                            .modifiers
-                            .contains(crossterm::event::KeyModifiers::CONTROL) =>
-                    {
-                        return Ok(());
-                    }
-                    _ => {}
-                }
+    /// Adopt `session_id`: claim transcripts recorded before it was known and
+    /// put the session's stored history in front of this run's transcripts.
+    fn restore_history(&mut self, session_id: &str) {
+        for i in 0..self.transcripts.len() {
+            if self.transcripts[i].session_id.is_none() {
+                self.transcripts[i].session_id = Some(session_id.to_string());
+                self.save_transcript(&self.transcripts[i]);
             }
         }
+        let mut restored = self.stored_history(session_id);
+        restored.retain(|e| !self.transcripts.iter().any(|t| t.id == e.id));
+        restored.append(&mut self.transcripts);
+        self.transcripts = restored;
+        self.timeline = Timeline::from_history(&self.transcripts);
     }
-}
 
-/// Write a timestamped line to conch.log for debugging.
-fn log(msg: &str) {
-    if let Ok(mut f) = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("conch.log")
-    {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default();
-        let _ = writeln!(f, "[{:.3}] {}", now.as_secs_f64(), msg);
+    /// Attach a session: the first becomes the only tab, later ones open a
+    /// new tab, brought to the front if `activate` is set.
+    fn attach_session(
+        &mut self,
+        id: String,
+        slug: Option<String>,
+        activate: bool,
+        rules: &[ToolRule],
+    ) {
+        if let Some(index) = self.tabs.position(&id) {
+            if activate {
+                self.switch_tab(index);
+            }
+            return;
+        }
+        if self.tabs.is_empty() {
+            self.tabs.add(SessionTab::new(id.clone(), slug.clone()));
+            self.session_slug = slug;
+            self.restore_history(&id);
+            let marks = self.stored_focus_marks(&id);
+            self.focus.set_marks(marks);
+            // After a session was lost, prompts went nowhere until now
+            if let Some(opencode) = &self.opencode {
+                opencode.set_session(id.clone());
+            }
+            self.session_id = Some(id);
+            self.detached = false;
+            return;
+        }
+        log(&format!("tui: attached session {id} in a new tab"));
+        let mut tab = SessionTab::new(id.clone(), slug);
+        tab.activity = ActivityFeed::with_rules(rules.to_vec());
+        tab.transcripts = self.stored_history(&id);
+        tab.timeline = Timeline::from_history(&tab.transcripts);
+        tab.focus.set_marks(self.stored_focus_marks(&id));
+        let index = self.tabs.add(tab);
+        if activate {
+            self.switch_tab(index);
+        }
     }
-}
 
-/// Shared state for the OpenCode client, accessible from the send path.
-static OPENCODE_SESSION_ID: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
+    /// The directories OpenCode has sessions in, in path order.
+    fn session_directories(&self) -> Vec<PathBuf> {
+        let mut directories: Vec<PathBuf> = self
+            .sessions
+            .iter()
+            .filter_map(|s| s.directory.as_deref().map(PathBuf::from))
+            .collect();
+        directories.sort();
+        directories.dedup();
+        directories
+    }
 
-fn send_prompt_to_opencode(text: &str, tx: &tokio::sync::mpsc::UnboundedSender<AppMessage>) {
-    let text = text.to_string();
-    let tx = tx.clone();
-    log(&format!(
-        "send_prompt: queuing prompt ({} chars)",
-        text.len()
-    ));
-    tokio::spawn(async move {
-        let session_id = OPENCODE_SESSION_ID.lock().unwrap().clone();
-        let Some(session_id) = session_id else {
-            log("send_prompt: ERROR no session ID set");
-            let _ = tx.send(AppMessage::PromptSent(Err(anyhow!("no session"))));
+    /// Remember that session `id` was looked up or created for `directory`,
+    /// which becomes the project directory while the session is in front.
+    fn set_session_directory(&mut self, id: &str, directory: Option<PathBuf>) {
+        let Some(index) = self.tabs.position(id) else {
             return;
         };
-        log(&format!("send_prompt: sending to session {session_id}"));
-        let mut client = OpenCodeClient::new(OPENCODE_URL);
-        client.set_session(session_id);
-        let result = client.send_prompt(&text).await;
-        match &result {
-            Ok(()) => log("send_prompt: success"),
-            Err(e) => log(&format!("send_prompt: ERROR {e}")),
-        }
-        let _ = tx.send(AppMessage::PromptSent(result));
-    });
-}
+        if index == self.tabs.active() && directory.is_some() {
+            self.directory = directory.clone();
+        }
+        if let Some(tab) = self.tabs.get_mut(index) {
+            tab.directory = directory;
+        }
+    }
 
-/// Background task: connect to OpenCode, establish session, listen for SSE events.
-async fn connect_opencode(
-    tx: tokio::sync::mpsc::UnboundedSender<AppMessage>,
-    session_flag: Option<String>,
-) {
-    let mut client = OpenCodeClient::new(OPENCODE_URL);
+    /// Bring tab `index` to the front: park the current session's state in
+    /// its tab, take the target's out of its tab, and point prompts at it.
+    fn switch_tab(&mut self, index: usize) {
+        if index == self.tabs.active() || index >= self.tabs.len() {
+            return;
+        }
+        // The pending prompt was spoken for the session in front
+        if self.prompt_pending.is_some() {
+            self.notices
+                .warn("Send or discard the pending prompt before switching sessions");
+            return;
+        }
+        let before = self.focus.current_entry().cloned();
+        self.swap_tab_state(self.tabs.active());
+        self.tabs.set_active(index);
+        self.swap_tab_state(index);
+        let tab = &self.tabs.tabs()[index];
+        log(&format!("tui: switched to session {}", tab.session_id));
+        self.session_id = Some(tab.session_id.clone());
+        self.session_slug = tab.slug.clone();
+        if tab.directory.is_some() {
+            self.directory = tab.directory.clone();
+        }
+        if let Some(opencode) = &self.opencode {
+            opencode.set_session(tab.session_id.clone());
+        }
+        self.notify_focus_change(before);
+    }
 
-    // Health check with retry
-    log("connect_opencode: starting health check loop");
-    loop {
-        match client.health_check().await {
-            Ok(true) => {
-                log("connect_opencode: health check passed");
-                break;
-            }
-            Ok(false) => {
-                log("connect_opencode: health check returned false, retrying...");
-                let _ = tx.send(AppMessage::ConnectionChanged(
-                    ConnectionStatus::Reconnecting,
-                ));
-                tokio::time::sleep(Duration::from_secs(2)).await;
-            }
-            Err(e) => {
-                log(&format!(
-                    "connect_opencode: health check error: {e}, retrying..."
-                ));
-                let _ = tx.send(AppMessage::ConnectionChanged(
-                    ConnectionStatus::Reconnecting,
-                ));
-                tokio::time::sleep(Duration::from_secs(2)).await;
+    /// Exchange the per-session fields with the state kept in tab `index`.
+    fn swap_tab_state(&mut self, index: usize) {
+        let Some(tab) = self.tabs.get_mut(index) else {
+            return;
+        };
+        std::mem::swap(&mut self.transcripts, &mut tab.transcripts);
+        std::mem::swap(&mut self.focus, &mut tab.focus);
+        std::mem::swap(&mut self.activity, &mut tab.activity);
+        std::mem::swap(&mut self.opencode_busy, &mut tab.busy);
+        std::mem::swap(&mut self.busy_since, &mut tab.busy_since);
+        std::mem::swap(&mut self.language, &mut tab.language);
+        std::mem::swap(&mut self.last_reply, &mut tab.last_reply);
+        std::mem::swap(&mut self.timeline, &mut tab.timeline);
+        std::mem::swap(&mut self.stats, &mut tab.stats);
+    }
+
+    /// Detach the session in front and switch to its neighbour. The last
+    /// tab can't be closed.
+    fn close_tab(&mut self) {
+        if self.tabs.len() < 2 {
+            return;
+        }
+        let closing = self.tabs.active();
+        let next = if closing + 1 < self.tabs.len() {
+            closing + 1
+        } else {
+            closing - 1
+        };
+        self.switch_tab(next);
+        if self.tabs.active() == next
+            && let Some(tab) = self.tabs.remove(closing)
+        {
+            log(&format!("tui: closed session tab {}", tab.session_id));
+        }
+    }
+
+    /// Session `id` was deleted in OpenCode: close its tab.
+    fn session_deleted(&mut self, id: &str) {
+        self.session_gone(id, "was deleted");
+    }
+
+    /// Session `id` can't take prompts any more, for the reason `why`
+    /// ("was deleted"): close its tab. When it was the session in front the
+    /// next tab comes to the front, taking the pending prompt along, or with
+    /// no tab left conch waits for a new session instead of failing every
+    /// send.
+    fn session_gone(&mut self, id: &str, why: &str) {
+        let Some(index) = self.tabs.position(id) else {
+            return;
+        };
+        log(&format!("tui: session {id} {why}"));
+        let title = self.tabs.tabs()[index].title();
+        if index != self.tabs.active() {
+            self.tabs.remove(index);
+            self.notices
+                .warn(format!("{title} {why}; its tab was closed"));
+            return;
+        }
+        // Whatever happens, a pending prompt now waits for [Enter]
+        self.send_deadline = None;
+        if self.tabs.len() < 2 {
+            self.tabs.remove(index);
+            self.session_id = None;
+            self.session_slug = None;
+            self.opencode_busy = false;
+            self.busy_since = None;
+            self.last_reply = None;
+            self.timeline = Timeline::new();
+            self.stats = SessionStats::new();
+            self.detached = true;
+            // Only the pending prompt carries over to the next session
+            let pending = self.prompt_pending.is_some();
+            let kept = self.transcripts.pop().filter(|_| pending);
+            self.transcripts.clear();
+            self.transcript_cursor.reset();
+            self.transcripts.extend(kept.map(|mut entry| {
+                entry.session_id = None;
+                entry
+            }));
+            if let Some(opencode) = &self.opencode {
+                opencode.clear_session();
             }
+            self.notices.error(format!(
+                "{title} {why} \u{2014} press [t] to start a new session"
+            ));
+            return;
+        }
+        let next = if index + 1 < self.tabs.len() {
+            index + 1
+        } else {
+            index - 1
+        };
+        let pending = self.prompt_pending.take();
+        self.switch_tab(next);
+        let Some(mut tab) = self.tabs.remove(index) else {
+            return;
+        };
+        // The pending prompt is the newest transcript of the deleted session
+        if let (Some(_), Some(mut entry)) = (&pending, tab.transcripts.pop()) {
+            entry.session_id = self.session_id.clone();
+            self.save_transcript(&entry);
+            self.transcripts.push(entry);
         }
+        self.prompt_pending = pending;
+        self.notices.warn(format!(
+            "{title} {why}; switched to {}",
+            self.tabs.tabs()[self.tabs.active()].title()
+        ));
     }
 
-    // Session management
-    log("connect_opencode: resolving session");
-    let session_id = if let Some(id) = session_flag {
-        log(&format!("connect_opencode: using --session flag: {id}"));
-        client.set_session(id.clone());
-        id
-    } else {
-        // Try to reuse existing session for this directory
-        match client.list_sessions().await {
-            Ok(sessions) => {
-                log(&format!(
-                    "connect_opencode: found {} sessions",
-                    sessions.len()
-                ));
-                let cwd = std::env::current_dir()
-                    .ok()
-                    .map(|p| p.to_string_lossy().to_string());
-                let existing = sessions
-                    .iter()
-                    .find(|s| s.directory.as_ref() == cwd.as_ref());
-                if let Some(s) = existing {
-                    log(&format!(
-                        "connect_opencode: reusing session {} ({})",
-                        s.id,
-                        s.slug.as_deref().unwrap_or("?")
-                    ));
-                    client.set_session(s.id.clone());
-                    let _ = tx.send(AppMessage::SessionReady {
-                        _id: s.id.clone(),
-                        slug: s.slug.clone(),
-                    });
-                    s.id.clone()
-                } else {
-                    match client.create_session().await {
-                        Ok(id) => {
-                            let _ = tx.send(AppMessage::SessionReady {
-                                _id: id.clone(),
-                                slug: None,
-                            });
-                            id
-                        }
-                        Err(e) => {
-                            let _ = tx.send(AppMessage::ConnectionChanged(
-                                ConnectionStatus::Disconnected,
-                            ));
-                            eprintln!("Failed to create session: {}", e);
-                            return;
-                        }
+    /// Drop the focus entries and tool calls of message `message_id`, which
+    /// was removed (undone or reverted) in the session in front.
+    fn message_removed(&mut self, message_id: &str) {
+        let before = self.focus.current_entry().cloned();
+        let dropped =
+            self.focus.remove_message(message_id) + self.activity.remove_message(message_id);
+        self.timeline.remove_message(message_id);
+        if dropped > 0 {
+            log(&format!(
+                "tui: message {message_id} removed, dropped {dropped} entries"
+            ));
+        }
+        self.notify_focus_change(before);
+    }
+
+    /// Apply an event for a session that isn't in front to its tab, or drop
+    /// it if no tab is attached to the session.
+    fn update_background_tab(&mut self, event: &ServerEvent, rules: &[ToolRule]) {
+        let Route::Background(index) = self.tabs.route(event.session_id()) else {
+            log(&format!(
+                "tui: ignoring event for unattached session {}",
+                event.session_id().unwrap_or("?")
+            ));
+            return;
+        };
+        let Some(tab) = self.tabs.get_mut(index) else {
+            return;
+        };
+        match event {
+            ServerEvent::SessionStatus { session_id, busy } => {
+                let done = tab.busy && !busy;
+                if *busy != tab.busy {
+                    tab.busy_since = busy.then(Instant::now);
+                }
+                tab.busy = *busy;
+                if done {
+                    self.notices.info(format!("{} is done", tab.title()));
+                    if let Some(opencode) = &self.opencode {
+                        fetch_last_reply(opencode, session_id, &self.bus, &self.tasks);
                     }
+                    self.mark_answered(session_id);
                 }
             }
-            Err(_) => match client.create_session().await {
-                Ok(id) => {
-                    let _ = tx.send(AppMessage::SessionReady {
-                        _id: id.clone(),
-                        slug: None,
-                    });
-                    id
+            ServerEvent::Tool(te) => {
+                tab.timeline.tool(tab.activity.record(te));
+                tab.stats.tool(te);
+                self.last_edits.record(te);
+                if let Some(entry) = tools::map_focus(rules, te) {
+                    tab.focus.append_from(entry, te.message_id.clone());
                 }
-                Err(e) => {
-                    let _ = tx.send(AppMessage::ConnectionChanged(
-                        ConnectionStatus::Disconnected,
-                    ));
-                    eprintln!("Failed to create session: {}", e);
-                    return;
+            }
+            ServerEvent::MessageRemoved { message_id, .. } => {
+                tab.focus.remove_message(message_id);
+                tab.activity.remove_message(message_id);
+                tab.timeline.remove_message(message_id);
+            }
+            ServerEvent::Error {
+                session_id,
+                message,
+            } => {
+                log(&format!(
+                    "tui: OpenCode error in {}: {message}",
+                    tab.session_id
+                ));
+                self.notices.error(format!("{}: {message}", tab.title()));
+                if let Some(id) = session_id {
+                    self.mark_failed(id);
                 }
-            },
+            }
+            // Deletions are handled before events are routed to tabs
+            ServerEvent::SessionDeleted { .. } => {}
+            ServerEvent::Connected | ServerEvent::Heartbeat => {}
         }
-    };
+    }
+
+    /// Load the preview of the File entry under the focus pointer, unless
+    /// the one shown is still current.
+    fn refresh_preview(&mut self) {
+        let path = match self.focus.current_entry() {
+            Some(FocusEntry::File(path)) if self.preview_config.enabled => path,
+            _ => {
+                self.preview = None;
+                return;
+            }
+        };
+        if let Some(preview) = &self.preview
+            && preview.is_current(path, &self.last_edits)
+        {
+            return;
+        }
+        self.preview = Some(Preview::load(
+            path,
+            &self.last_edits,
+            self.preview_config.lines,
+        ));
+    }
+
+    /// The directory the session is working in: the newest Directory entry
+    /// of the focus stack, relative to `cwd`, or `cwd` itself.
+    fn focus_directory(&self, cwd: &Path) -> PathBuf {
+        match self.focus.recent_by_type().1 {
+            Some(FocusEntry::Directory(dir)) if dir != Path::new("~") => cwd.join(dir),
+            _ => cwd.to_path_buf(),
+        }
+    }
+
+    /// Whisper's initial prompt for prompts and dictation, from the
+    /// vocabulary.
+    fn whisper_prompt(&self) -> Option<String> {
+        Some(self.vocabulary.prompt()).filter(|prompt| !prompt.is_empty())
+    }
+
+    /// Whether the focus stack pane is showing, not replaced by a view.
+    fn focus_shown(&self) -> bool {
+        self.palette.is_none() && self.panel.is_none()
+    }
+
+    /// What the main view's keys depend on.
+    fn key_context(&self) -> KeyContext {
+        KeyContext {
+            state: self.state,
+            prompt_pending: self.prompt_pending.is_some(),
+            counting_down: self.send_deadline.is_some(),
+            panel: self.panel,
+            conversation: self.show_conversation,
+            focus_shown: self.focus_shown(),
+        }
+    }
+
+    /// Show `panel` in place of the focus stack pane, or close it if it's
+    /// shown.
+    fn toggle_panel(&mut self, panel: Panel) {
+        self.panel = panel.toggle(self.panel);
+        match panel {
+            Panel::Notices => {
+                self.notice_scroll = 0;
+                self.notices.mark_seen();
+            }
+            Panel::Inspector => self.inspector_scroll = 0,
+            Panel::Resources => self.resources.reset(),
+            Panel::Queue => self.queue_cursor = 0,
+            Panel::Stats | Panel::Payload => {}
+        }
+    }
+
+    /// Publish a focus change if the entry under the pointer differs from `before`.
+    fn notify_focus_change(&self, before: Option<FocusEntry>) {
+        let current = self.focus.current_entry();
+        if current == before.as_ref() {
+            return;
+        }
+        if let Some(entry) = current {
+            self.bus.publish(AppMessage::FocusChanged {
+                type_name: entry.type_name().to_string(),
+                value: entry.value_str().to_string(),
+            });
+        }
+    }
+}
+
+/// Events published on the bus by background tasks and the TUI. Errors are
+/// carried as strings so every subscriber can get its own copy.
+#[derive(Clone)]
+enum AppMessage {
+    RecordingStarted,
+    RecordingStopped,
+    /// The audio stream failed, from the audio thread.
+    AudioError(String),
+    /// The cue player's output stream failed, from the audio thread.
+    CueError(String),
+    TranscriptReady(Result<Transcript, String>),
+    /// Transcript of a spoken answer to the pending prompt.
+    AnswerReady(Result<Transcript, String>),
+    /// Transcript of one segment of a dictation.
+    DictationReady(Result<Transcript, String>),
+    /// `audio` of a recording was transcribed in `took`.
+    Transcribed {
+        audio: Duration,
+        took: Duration,
+    },
+    /// Partial transcript of the latest audio of recording number
+    /// `recording`, for the caption strip.
+    Caption {
+        recording: u64,
+        text: String,
+    },
+    ServerEvent(ServerEvent),
+    /// An SSE data line as received, for the event inspector.
+    RawEvent {
+        data: String,
+        handled: bool,
+    },
+    /// A send failed and is about to be retried.
+    SendRetrying {
+        attempt: u32,
+        of: u32,
+    },
+    /// A prompt was sent; `result` holds the session it went to.
+    PromptSent {
+        transcript_id: Option<u64>,
+        prompt: String,
+        result: Result<String, String>,
+    },
+    /// A session to attach, looked up or created for `directory` if known;
+    /// `activate` brings its tab to the front.
+    SessionReady {
+        id: String,
+        slug: Option<String>,
+        directory: Option<PathBuf>,
+        activate: bool,
+    },
+    /// The sessions OpenCode has, as listed when looking one up.
+    SessionsListed(Vec<SessionInfo>),
+    /// Session `id` was found gone or moved after a reconnect; `why` says
+    /// which.
+    SessionLost {
+        id: String,
+        why: String,
+    },
+    /// The assistant's final reply text, fetched for readback and context.
+    ReplyReady {
+        session_id: String,
+        text: String,
+    },
+    ConnectionChanged(ConnectionStatus),
+    FocusChanged { type_name: String, value: String },
+    GitStatus(Option<GitStatus>),
+    /// The vocabulary of `dir` was built.
+    VocabularyReady {
+        dir: PathBuf,
+        vocabulary: Arc<Vocabulary>,
+    },
+    /// A MIDI controller or HID pedal was pressed or released.
+    Trigger(TriggerEvent),
+    /// A command from the control socket, to be answered.
+    Control(control::Request),
+}
+
+impl Topical for AppMessage {
+    fn topic(&self) -> Topic {
+        match self {
+            AppMessage::RecordingStarted
+            | AppMessage::RecordingStopped
+            | AppMessage::AudioError(_)
+            | AppMessage::CueError(_) => Topic::Audio,
+            AppMessage::TranscriptReady(_)
+            | AppMessage::AnswerReady(_)
+            | AppMessage::DictationReady(_)
+            | AppMessage::Transcribed { .. }
+            | AppMessage::Caption { .. } => Topic::Stt,
+            AppMessage::ServerEvent(_)
+            | AppMessage::RawEvent { .. }
+            | AppMessage::PromptSent { .. }
+            | AppMessage::SendRetrying { .. }
+            | AppMessage::SessionReady { .. }
+            | AppMessage::SessionsListed(_)
+            | AppMessage::SessionLost { .. }
+            | AppMessage::ReplyReady { .. }
+            | AppMessage::ConnectionChanged(_) => Topic::Transport,
+            AppMessage::FocusChanged { .. }
+            | AppMessage::GitStatus(_)
+            | AppMessage::VocabularyReady { .. } => Topic::Focus,
+            AppMessage::Trigger(_) | AppMessage::Control(_) => Topic::Ui,
+        }
+    }
+}
+
+/// Where server events come from.
+enum EventSource {
+    /// The OpenCode server at `url`, attaching `sessions` (`--session`) or
+    /// else the session of `directory` (`--dir`), optionally writing what
+    /// arrives to an event log (`--capture-events`).
+    Live {
+        url: String,
+        sessions: Vec<String>,
+        directory: PathBuf,
+        capture: Option<Capture>,
+    },
+    /// A captured event log (`--replay`).
+    Replay(Replay),
+}
+
+/// The terminal the TUI draws on, and where its key presses come from.
+struct Screen<B: Backend> {
+    terminal: Terminal<B>,
+    keys: Keys,
+}
+
+/// Where the TUI reads key presses.
+enum Keys {
+    /// The terminal it runs in.
+    Terminal,
+    /// Events sent down a channel, to drive the app from a test. Dropping
+    /// the sender quits the app.
+    #[cfg(test)]
+    Scripted(mpsc::Receiver<Event>),
+}
+
+impl Keys {
+    /// The next terminal event, waiting up to `timeout` for one.
+    fn next(&self, timeout: Duration) -> Result<Option<Event>> {
+        match self {
+            Keys::Terminal => Ok(if event::poll(timeout)? {
+                Some(event::read()?)
+            } else {
+                None
+            }),
+            #[cfg(test)]
+            Keys::Scripted(rx) => match rx.recv_timeout(timeout) {
+                Ok(event) => Ok(Some(event)),
+                Err(mpsc::RecvTimeoutError::Timeout) => Ok(None),
+                Err(mpsc::RecvTimeoutError::Disconnected) => Ok(Some(Event::Key(KeyEvent::new(
+                    KeyCode::Char('c'),
+                    KeyModifiers::CONTROL,
+                )))),
+            },
+        }
+    }
+}
+
+/// Append every raw SSE event to an event log, on a task of its own.
+fn spawn_event_capture(bus: &EventBus<AppMessage>, tasks: &Supervisor, mut capture: Capture) {
+    let mut rx = bus.subscribe(&[Topic::Transport]);
+    tasks.spawn("Event capture", async move {
+        while let Some(msg) = rx.recv().await {
+            if let AppMessage::RawEvent { data, .. } = msg {
+                capture.record(&data, Instant::now())?;
+            }
+        }
+        Ok(())
+    });
+}
+
+/// Re-read the git status of `dir` every `interval`, publishing it when it
+/// changes.
+fn spawn_git_watcher(
+    bus: &EventBus<AppMessage>,
+    tasks: &Supervisor,
+    dir: PathBuf,
+    interval: Duration,
+) {
+    let bus = bus.clone();
+    tasks.spawn("Git status", async move {
+        let mut last = None;
+        loop {
+            let path = dir.clone();
+            let status = tokio::task::spawn_blocking(move || GitStatus::read(&path)).await??;
+            if status != last {
+                last = status.clone();
+                bus.publish(AppMessage::GitStatus(status));
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+/// Build the vocabulary of `dir` on a blocking thread and publish it.
+fn spawn_vocabulary(
+    bus: &EventBus<AppMessage>,
+    tasks: &Supervisor,
+    dir: PathBuf,
+    max_terms: usize,
+) {
+    let bus = bus.clone();
+    tasks.spawn_blocking("Vocabulary", move || {
+        match Vocabulary::harvest(&dir, max_terms) {
+            Ok(vocabulary) => {
+                log(&format!(
+                    "vocabulary: {} files and {} symbols from {}",
+                    vocabulary.files.len(),
+                    vocabulary.symbols.len(),
+                    dir.display()
+                ));
+                let vocabulary = Arc::new(vocabulary);
+                bus.publish(AppMessage::VocabularyReady { dir, vocabulary });
+            }
+            Err(e) => log(&format!("vocabulary: not built, {e}")),
+        }
+    });
+}
+
+/// Publish a captured event log as if it came from the server, keeping its
+/// timing (scaled by the replay speed).
+async fn replay_events(bus: EventBus<AppMessage>, replay: Replay) {
+    log(&format!("replay: {} events", replay.events.len()));
+    bus.publish(AppMessage::ConnectionChanged(ConnectionStatus::Connected));
+    for (wait, data) in replay.schedule() {
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        let event = parse_sse_event(data);
+        bus.publish(AppMessage::RawEvent {
+            data: data.to_string(),
+            handled: event.is_some(),
+        });
+        if let Some(event) = event {
+            bus.publish(AppMessage::ServerEvent(event));
+        }
+    }
+    log("replay: done");
+}
+
+/// Fire user hooks from bus events, on a task of its own.
+fn spawn_hook_observer(bus: &EventBus<AppMessage>, tasks: &Supervisor, hooks: Hooks) {
+    let mut rx = bus.subscribe(&[Topic::Stt, Topic::Transport, Topic::Focus]);
+    tasks.spawn("Hooks", async move {
+        let mut observer = HookObserver::new(hooks);
+        while let Some(event) = rx.recv().await {
+            let observed = match event {
+                AppMessage::TranscriptReady(Ok(Transcript { text, confidence })) => {
+                    Observed::Transcript { text, confidence }
+                }
+                AppMessage::PromptSent {
+                    prompt,
+                    result: Ok(_),
+                    ..
+                } => Observed::PromptSent { prompt },
+                AppMessage::ServerEvent(ServerEvent::SessionStatus { session_id, busy }) => {
+                    Observed::SessionStatus { session_id, busy }
+                }
+                AppMessage::FocusChanged { type_name, value } => {
+                    Observed::FocusChanged { type_name, value }
+                }
+                _ => continue,
+            };
+            observer.observe(observed);
+        }
+        Ok(())
+    });
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Parse command-line arguments
+    let args: Vec<String> = std::env::args().collect();
+
+    // Subcommands that don't need the model, audio, or TUI
+    if args.get(1).map(String::as_str) == Some("export") {
+        return run_export(&args[2..]).await;
+    }
+
+    // Check for --session flags; each one attaches a session in its own tab
+    let session_flags: Vec<String> = args
+        .windows(2)
+        .filter(|w| w[0] == "--session")
+        .map(|w| w[1].clone())
+        .collect();
+
+    // --data-dir and --log-file move files from their platform locations,
+    // so they're read before anything resolves a default path
+    let path_flag = |flag: &str| {
+        args.windows(2)
+            .find_map(|w| (w[0] == flag).then(|| PathBuf::from(&w[1])))
+    };
+    if let Some(dir) = path_flag("--data-dir") {
+        paths::set_data_dir(dir);
+    }
+    if let Some(file) = path_flag("--log-file") {
+        paths::set_log_file(file);
+    }
+    // --profile records the tracing spans until conch exits
+    let _profile = path_flag("--profile")
+        .map(|path| profiling::start(&path))
+        .transpose()?;
+
+    // Check for --config flag, falling back to the usual config file
+    let config_path = path_flag("--config").unwrap_or_else(paths::config_file);
+    let config = Config::load(&config_path)?;
+
+    if args.get(1).map(String::as_str) == Some("ctl") {
+        return run_ctl(&args[2..], &config).await;
+    }
+
+    // --mock stands a local server in for OpenCode, a tone in for the mic
+    // and a fixed phrase in for Whisper, so the app runs without any of them
+    let mock_server = if args.iter().any(|a| a == "--mock") {
+        Some(MockServer::start().await?)
+    } else {
+        None
+    };
+
+    // --replay feeds a captured event log to the TUI instead of connecting
+    let events = match path_flag("--replay") {
+        Some(path) => {
+            let speed = match args.windows(2).find(|w| w[0] == "--replay-speed") {
+                Some(w) => w[1]
+                    .parse()
+                    .map_err(|_| anyhow!("--replay-speed needs a number, got '{}'", w[1]))?,
+                None => 1.0,
+            };
+            EventSource::Replay(Replay::load(&path, speed)?)
+        }
+        None => EventSource::Live {
+            url: mock_server
+                .as_ref()
+                .map_or(OPENCODE_URL, MockServer::url)
+                .to_string(),
+            sessions: session_flags,
+            directory: paths::project_dir(
+                path_flag("--dir")
+                    .as_deref()
+                    .or(config.connection.directory.as_deref()),
+            )?,
+            capture: path_flag("--capture-events").map(Capture::new),
+        },
+    };
+    paths::create_dirs()?;
+    logs::configure(config.log.clone());
+    let log_file = paths::log_file();
+    if let Err(e) = logs::prune(&log_file, &config.log, std::time::SystemTime::now()) {
+        eprintln!("Warning: old logs not cleaned up: {e}");
+    }
+
+    if args.get(1).map(String::as_str) == Some("models") {
+        return models::run(&args[2..], &config.models, &config_path).await;
+    }
+    if args.get(1).map(String::as_str) == Some("bench") {
+        return bench::run(&args[2..], &config.models);
+    }
+    if args.get(1).map(String::as_str) == Some("devices") {
+        print!("{}", audio::list_devices(config.audio.host.as_deref())?);
+        return Ok(());
+    }
+    if args.get(1).map(String::as_str) == Some("logs") {
+        return run_logs(&args[2..]);
+    }
+    if args.get(1).map(String::as_str) == Some("serve-stt") {
+        return remote_stt::serve(&args[2..], &config.models, config.stt.speech_gate());
+    }
+    if args.get(1).map(String::as_str) == Some("mic") {
+        return remote_mic::run(&args[2..], &config.audio);
+    }
+
+    for moved in paths::migrate(&std::env::current_dir()?, &config)? {
+        eprintln!("{moved}");
+    }
+    let model_path = config.models.chosen(args.get(1).map(String::as_str));
+    let model_path = model_path.to_string_lossy();
+    let default_language = language::parse(&config.stt.language)
+        .map_err(|e| anyhow!("Invalid [stt] language: {e}"))?;
+    Theme::from_config(&config.theme).map_err(|e| anyhow!("Invalid [theme]: {e}"))?;
+
+    if args.iter().any(|a| a == "--enroll-wake") {
+        return wake::enroll(&config.wake, &config.audio);
+    }
+
+    // Wake word is opt-in and needs an enrolled keyword model
+    let wake_detector = if config.wake.enabled {
+        let model = KeywordModel::load(&config.wake.model).map_err(|e| {
+            anyhow!("{e}\nRecord the wake word first with: conch --enroll-wake")
+        })?;
+        Some(WakeDetector::new(model, config.wake.threshold))
+    } else {
+        None
+    };
+
+    // Load Whisper model
+    let gate = config.stt.speech_gate();
+    let transcriber = if mock_server.is_some() {
+        Arc::new(Transcriber::fixed(mock::PHRASE).with_gate(gate))
+    } else if let Some(server) = &config.stt.server {
+        // A thin client: the recordings are transcribed elsewhere
+        eprintln!("Connecting to the transcription server at {server}...");
+        Arc::new(Transcriber::remote(server)?.with_gate(gate))
+    } else {
+        eprintln!("Loading Whisper model from '{}'...", model_path);
+        match Transcriber::new(&model_path) {
+            Ok(t) => Arc::new(t.with_gate(gate)),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                eprintln!();
+                eprintln!("To use Conch, you need a Whisper model file.");
+                eprintln!("Download one and make it the default with:");
+                eprintln!("  conch models download base.en");
+                eprintln!("  conch models default base.en");
+                return Err(e);
+            }
+        }
+    };
+    if transcriber.english_only() && !default_language.english() {
+        eprintln!(
+            "Warning: {} is English-only; [stt] language = \"{}\" needs a multilingual model",
+            config.stt.server.as_deref().unwrap_or(&model_path),
+            config.stt.language
+        );
+    }
+
+    // Initialize audio capture, from a clip instead of the mic with --fake-audio
+    let audio = match args.windows(2).find(|w| w[0] == "--fake-audio") {
+        Some(w) => AudioCapture::with_source(Box::new(FakeSource::parse(&w[1])?))?,
+        None if mock_server.is_some() => {
+            AudioCapture::with_source(Box::new(FakeSource::tone(440.0)))?
+        }
+        None => AudioCapture::with_config(&config.audio)?,
+    };
+    if let Some(warning) = audio::low_rate_warning(audio.sample_rate()) {
+        eprintln!("Warning: {warning}");
+    }
+    eprintln!(
+        "Audio device ready ({}Hz). Starting TUI...",
+        audio.sample_rate()
+    );
+
+    // Set up terminal
+    terminal::enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    // A background task's panic is reported in the TUI; printed, it would
+    // garble the screen
+    let print_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| match std::thread::current().name() {
+        Some("main") => print_panic(info),
+        _ => log(&format!("panic: {info}")),
+    }));
+    // Needed for the terminal to report Shift+Enter distinctly from Enter
+    let enhanced_keys = terminal::supports_keyboard_enhancement().unwrap_or(false);
+    if enhanced_keys {
+        execute!(
+            stdout,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        )?;
+    }
+    let backend = CrosstermBackend::new(stdout);
+    let mut screen = Screen {
+        terminal: Terminal::new(backend)?,
+        keys: Keys::Terminal,
+    };
+
+    // Run the app, ensuring we restore the terminal on exit
+    let result = run_app(
+        &mut screen,
+        &audio,
+        &transcriber,
+        events,
+        &config,
+        &config_path,
+        wake_detector,
+    )
+    .await;
+
+    // Restore terminal
+    let terminal = &mut screen.terminal;
+    if enhanced_keys {
+        execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)?;
+    }
+    terminal::disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    result
+}
+
+/// Main event loop.
+async fn run_app<B: Backend>(
+    screen: &mut Screen<B>,
+    audio: &AudioCapture,
+    transcriber: &Arc<Transcriber>,
+    events: EventSource,
+    config: &Config,
+    config_path: &Path,
+    wake: Option<WakeDetector>,
+) -> Result<()> {
+    let mut config = config.clone();
+    let mut app = App::with_config(audio.sample_rate(), &config)?;
+    app.english_only_model = transcriber.english_only();
+    spawn_hook_observer(
+        &app.bus,
+        &app.tasks,
+        Hooks::new(config.hooks.clone()).with_error_sink(log),
+    );
+    app.wake_phrase = wake.as_ref().map(|w| w.phrase().to_string());
+    if let Some(warning) = audio::low_rate_warning(app.sample_rate) {
+        log(&format!("audio: {warning}"));
+        app.notices.error(warning);
+    }
+    let mut cancel_window = Duration::from_secs_f32(config.send.cancel_secs.max(0.0));
+    let mut watcher = ConfigWatcher::new(config_path);
+    if config.cues.enabled {
+        open_cues(&mut app, &config.cues);
+    }
+    let mut speaker = config
+        .tts
+        .enabled
+        .then(|| Speaker::new(config.tts.command()));
+    let mut hot_mic = wake.map(HotMic::new);
+    let mut pacer = FramePacer::new();
+    // The recent audio the waveform is drawn from, reused every frame
+    let mut snapshot: Vec<f32> = Vec::new();
+
+    // The TUI observes every topic
+    let bus = app.bus.clone();
+    let mut rx = bus.subscribe(&Topic::ALL);
+
+    // Recordings are transcribed one at a time, in the order they were made
+    let rate = audio.sample_rate();
+    let transcribe = spawn_transcriber(Arc::clone(transcriber), rate, &bus, &app.tasks);
+    let captioner = spawn_captioner(Arc::clone(transcriber), rate, &bus, &app.tasks);
+
+    let errors = bus.clone();
+    audio.on_stream_error(move |error| {
+        errors.publish(AppMessage::AudioError(error));
+    });
+    spawn_triggers(&mut app, &config);
+    // Listening until the TUI exits
+    let _control = spawn_control(&mut app, &config);
+
+    // Git status and the vocabulary are read for the project directory
+    let cwd = match &events {
+        EventSource::Live { directory, .. } => directory.clone(),
+        EventSource::Replay(_) => std::env::current_dir().unwrap_or_default(),
+    };
+    if config.git.enabled {
+        let interval = Duration::from_secs(config.git.refresh_secs.max(1));
+        spawn_git_watcher(&bus, &app.tasks, cwd.clone(), interval);
+    }
+    // Read at startup, like [git]
+    let vocabulary = config.vocabulary.clone();
+
+    match events {
+        // Start OpenCode connection in background
+        EventSource::Live {
+            url,
+            sessions,
+            directory,
+            capture,
+        } => {
+            if let Some(capture) = capture {
+                spawn_event_capture(&bus, &app.tasks, capture);
+            }
+            let opencode = SessionHandle::spawn(OpenCodeClient::new(&url));
+            opencode.set_agent(app.agent.clone());
+            app.opencode = Some(opencode.clone());
+            let bus_oc = bus.clone();
+            let heartbeats = Heartbeats::new(&config.connection);
+            app.directory = Some(directory.clone());
+            // Started over, session lookup included, if it dies
+            app.tasks.supervise("OpenCode connection", move || {
+                let (bus, sessions) = (bus_oc.clone(), sessions.clone());
+                let (directory, heartbeats) = (directory.clone(), heartbeats.clone());
+                connect_opencode(bus, sessions, directory, opencode.clone(), heartbeats)
+            });
+        }
+        // Without a server, prompts fail to send as if disconnected
+        EventSource::Replay(replay) => {
+            let bus_replay = bus.clone();
+            app.tasks.spawn("Replay", async move {
+                replay_events(bus_replay, replay).await;
+                Ok(())
+            });
+        }
+    }
+
+    loop {
+        // Drain all pending messages (non-blocking)
+        while let Ok(msg) = rx.try_recv() {
+            pacer.mark_dirty();
+            handle_message(&mut app, msg, audio, &transcribe, speaker.as_mut(), &config)?;
+        }
+
+        // Edits to conch.toml apply without a restart where they can
+        if let Some(result) = watcher.poll(Instant::now()) {
+            pacer.mark_dirty();
+            match result.and_then(|new| reload_config(&mut app, &config, &new).map(|()| new)) {
+                Ok(new) => {
+                    cancel_window = Duration::from_secs_f32(new.send.cancel_secs.max(0.0));
+                    config = new;
+                }
+                Err(e) => {
+                    log(&format!("config: not reloaded, {e}"));
+                    let reason = e.to_string();
+                    let reason = reason.lines().next().unwrap_or_default();
+                    app.notices.error(format!("Config not reloaded: {reason}"));
+                }
+            }
+        }
+
+        accept_queued_transcripts(&mut app, &config, cancel_window);
+
+        // The vocabulary follows the directory the session works in
+        if vocabulary.enabled {
+            let dir = app.focus_directory(app.directory.as_deref().unwrap_or(&cwd));
+            if app.vocabulary_dir.as_ref() != Some(&dir) {
+                spawn_vocabulary(&bus, &app.tasks, dir.clone(), vocabulary.max_terms);
+                app.vocabulary_dir = Some(dir);
+            }
+        }
+
+        app.speaking = speaker.as_mut().is_some_and(|s| s.is_speaking());
+
+        // Auto-send once the cancel window runs out
+        if app.send_deadline.is_some_and(|d| Instant::now() >= d) {
+            confirm_prompt(&mut app);
+        }
+
+        // Hands-free: take the next turn once the reply is in
+        let ready = app.state == RecordingState::Idle
+            && app.prompt_pending.is_none()
+            && app.transcript_queue.is_empty()
+            && app.input.is_none()
+            && app.palette.is_none()
+            && !app.speaking;
+        if app.hands_free.poll(Instant::now(), ready) {
+            pacer.mark_dirty();
+            // The hot mic is already listening for the wake word
+            if hot_mic.is_none() {
+                log("tui: hands-free, re-arming the mic");
+                app.start_recording(audio);
+            }
+        }
+
+        // Wake word: keep the mic hot while idle, also with a prompt pending
+        // so it can be answered hands-free
+        if let Some(hot_mic) = &mut hot_mic {
+            // Ducked while speaking so the readback can't trigger it
+            let listen = app.state == RecordingState::Idle
+                && app.input.is_none()
+                && app.palette.is_none()
+                && !app.speaking;
+            if listen != app.hot_mic {
+                pacer.mark_dirty();
+            }
+            let check = hot_mic.poll(audio, listen, Instant::now());
+            app.hot_mic = hot_mic.is_listening();
+            match check {
+                Some(WakeCheck::Detected { distance }) => {
+                    log(&format!("wake: detected (distance {distance:.3})"));
+                    app.start_recording(audio);
+                }
+                Some(WakeCheck::Rejected {
+                    distance: Some(distance),
+                }) => log(&format!("wake: rejected (distance {distance:.3})")),
+                _ => {}
+            }
+        }
+
+        for report in app.tasks.reap() {
+            task_ended(&mut app, &report);
+            pacer.mark_dirty();
+        }
+
+        if stop_tap_due(&mut app, audio, &transcribe, Instant::now())? {
+            pacer.mark_dirty();
+        }
+
+        if app.audio_recovery.restart_due(Instant::now()) {
+            match audio.restart() {
+                Ok(()) => {
+                    log("audio: stream restarted");
+                    app.notices.info("Audio stream restarted");
+                }
+                Err(e) => audio_stream_failed(&mut app, &format!("restart failed: {e}")),
+            }
+            pacer.mark_dirty();
+        }
+
+        // Snapshot waveform from ring buffer each frame
+        if app.state == RecordingState::Recording {
+            let num_columns = screen.terminal.size()?.width as usize;
+            // Read ~100ms of recent audio for the snapshot
+            snapshot.resize(audio.sample_rate() as usize / 10, 0.0);
+            let read = audio.read_last_into(&mut snapshot);
+            let samples = &snapshot[..read];
+
+            if watch_recording(&mut app, audio, &transcribe, samples)? {
+                continue;
+            }
+
+            // Captions transcribe the latest audio again every interval
+            if app.caption.enabled {
+                let interval = Duration::from_millis(app.caption.interval_ms.max(200));
+                if let Some(recording) = app.captions.due(Instant::now(), interval) {
+                    let window = app.caption.window_secs.max(1.0) * audio.sample_rate() as f32;
+                    let job = CaptionJob {
+                        recording,
+                        samples: audio.read_last_samples(window as usize),
+                        language: app.language().code,
+                    };
+                    // Dropped while the last one is still transcribing
+                    let _ = captioner.try_send(job);
+                }
+            }
+
+            if !samples.is_empty() {
+                app.waveform_bars.resize(num_columns, 0.0);
+                viz::compute_rms_windows_into(samples, &mut app.waveform_bars);
+                app.waveform.scale_in_place(&mut app.waveform_bars);
+            }
+        } else if !app.waveform_bars.is_empty() {
+            app.waveform_bars.clear();
+        }
+        if app.state != RecordingState::Recording && app.captions.is_live() {
+            app.captions.stop(Instant::now());
+        }
+
+        // Draw UI when something changed, every tick while something moves
+        let animating = app.state == RecordingState::Recording
+            || app.send_deadline.is_some()
+            || app.speaking
+            || (app.caption.enabled && caption_visible(&app).is_some());
+        if app.panel == Some(Panel::Resources) && app.resources.tick(Instant::now(), app.frames) {
+            pacer.mark_dirty();
+        }
+        // The busy spinner turns a step every idle tick
+        if app.busy_since.is_some() {
+            pacer.mark_dirty();
+        }
+        if pacer.should_draw(animating, Instant::now()) {
+            app.refresh_preview();
+            screen.terminal.draw(|f| render(f, &app))?;
+            app.frames += 1;
+            pacer.drawn(animating, Instant::now());
+        }
+
+        // Poll for keyboard events
+        if let Some(event) = screen.keys.next(FramePacer::tick(animating))? {
+            if let Event::Resize(..) = event {
+                pacer.mark_dirty();
+            }
+            if let Event::Key(key) = event {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                pacer.mark_dirty();
+                if app.input.is_some() {
+                    if key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL)
+                    {
+                        return Ok(());
+                    }
+                    handle_input_key(&mut app, key);
+                    continue;
+                }
+                if app.palette.is_some() {
+                    if key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL)
+                    {
+                        return Ok(());
+                    }
+                    handle_palette_key(&mut app, key);
+                    continue;
+                }
+                if app.search.is_some() {
+                    if key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL)
+                    {
+                        return Ok(());
+                    }
+                    handle_search_key(&mut app, key);
+                    continue;
+                }
+                if app.keymap == Keymap::Vim && handle_vim_key(&mut app, key) {
+                    continue;
+                }
+                let Some(action) = Action::for_key(key, &app.key_context()) else {
+                    continue;
+                };
+                if action == Action::Quit {
+                    return Ok(());
+                }
+                let speaker = speaker.as_mut();
+                apply_action(&mut app, action, key, audio, &transcribe, speaker, &config)?;
+            }
+        }
+    }
+}
+
+/// Update `app` with a message from the bus.
+fn handle_message(
+    app: &mut App,
+    msg: AppMessage,
+    audio: &AudioCapture,
+    transcribe: &mpsc::Sender<TranscribeJob>,
+    speaker: Option<&mut Speaker>,
+    config: &Config,
+) -> Result<()> {
+    match msg {
+        AppMessage::TranscriptReady(result) => {
+            app.transcribing = app.transcribing.saturating_sub(1);
+            app.settle_state();
+            match result {
+                Ok(transcript) if !transcript.text.is_empty() => {
+                    app.captions.finish(&transcript.text, Instant::now());
+                    app.transcript_queue.push_back(transcript);
+                }
+                Ok(_) => {
+                    app.notices.warn("No speech detected");
+                }
+                Err(e) => {
+                    app.notices.error(format!("Transcription error: {}", e));
+                }
+            }
+        }
+        AppMessage::DictationReady(result) => {
+            app.transcribing = app.transcribing.saturating_sub(1);
+            app.settle_state();
+            let text = match &result {
+                // A segment of silence turns into "Thank you." as often as not
+                Ok(transcript)
+                    if config.hallucinations.action == hallucination::Action::Drop
+                        && config.hallucinations.matches(&transcript.text) =>
+                {
+                    log(&format!("tui: dropped hallucination {:?}", transcript.text));
+                    None
+                }
+                Ok(transcript) => Some(casing::apply(&transcript.text)),
+                Err(e) => {
+                    app.notices.error(format!("Transcription error: {}", e));
+                    None
+                }
+            };
+            if let Some(dictation) = &mut app.dictation {
+                dictation.segment_done(text.as_deref());
+            }
+            settle_dictation(app);
+        }
+        AppMessage::Caption { recording, text } => {
+            app.captions.partial(recording, &text);
+        }
+        AppMessage::Transcribed { audio, took } => {
+            app.resources.transcribed(audio, took);
+            app.stats.transcribed(took);
+        }
+        AppMessage::AnswerReady(result) => {
+            app.transcribing = app.transcribing.saturating_sub(1);
+            app.settle_state();
+            match result {
+                Ok(transcript) => match answer::parse(&transcript.text) {
+                    Some(answer) => answer_prompt(app, answer, transcribe)?,
+                    None => {
+                        log(&format!("tui: not an answer: {:?}", transcript.text));
+                        app.notices
+                            .warn("Didn't catch that \u{2014} say yes, no or cancel");
+                    }
+                },
+                Err(e) => {
+                    app.notices.error(format!("Transcription error: {}", e));
+                }
+            }
+        }
+        AppMessage::ServerEvent(ServerEvent::SessionDeleted { session_id }) => {
+            app.session_deleted(&session_id);
+        }
+        AppMessage::ServerEvent(event) if app.tabs.route(event.session_id()) != Route::Active => {
+            app.update_background_tab(&event, &config.tools);
+        }
+        AppMessage::ServerEvent(event) => match event {
+            ServerEvent::Connected => {
+                log("tui: SSE connected event");
+                app.connection_status = ConnectionStatus::Connected;
+            }
+            ServerEvent::SessionStatus { session_id, busy } => {
+                log(&format!(
+                    "tui: session {} status: {}",
+                    session_id,
+                    if busy { "busy" } else { "idle" }
+                ));
+                if app.opencode_busy && !busy {
+                    app.mark_answered(&session_id);
+                    app.hands_free.opencode_idle(Instant::now());
+                    if let Some(opencode) = &app.opencode {
+                        fetch_last_reply(opencode, &session_id, &app.bus, &app.tasks);
+                    }
+                }
+                if busy != app.opencode_busy {
+                    app.busy_since = busy.then(Instant::now);
+                }
+                app.opencode_busy = busy;
+            }
+            ServerEvent::Tool(ref te) => {
+                log(&format!(
+                    "tui: tool event: {} (state: {})",
+                    te.tool, te.state
+                ));
+                app.timeline.tool(app.activity.record(te));
+                app.stats.tool(te);
+                app.last_edits.record(te);
+                if let Some(entry) = tools::map_focus(&config.tools, te) {
+                    let before = app.focus.current_entry().cloned();
+                    app.focus.append_from(entry, te.message_id.clone());
+                    app.notify_focus_change(before);
+                }
+            }
+            ServerEvent::MessageRemoved { message_id, .. } => {
+                app.message_removed(&message_id);
+            }
+            ServerEvent::Error {
+                session_id,
+                message,
+            } => {
+                log(&format!("tui: OpenCode error: {message}"));
+                app.notices.error(format!("OpenCode: {message}"));
+                if let Some(id) = session_id {
+                    app.mark_failed(&id);
+                }
+            }
+            ServerEvent::Heartbeat => app.heartbeats.heartbeat(Instant::now()),
+            ServerEvent::SessionDeleted { .. } => {}
+        },
+        AppMessage::SendRetrying { attempt, of } => {
+            app.send_retry = Some((attempt, of));
+        }
+        AppMessage::PromptSent {
+            transcript_id,
+            result,
+            ..
+        } => {
+            app.send_retry = None;
+            if let Some(i) = app.sending.iter().position(|(id, _)| *id == transcript_id) {
+                app.sending.remove(i);
+            }
+            if let Some(id) = transcript_id {
+                let status = match &result {
+                    Ok(session_id) => {
+                        app.unanswered.sent(id, session_id.clone(), Instant::now());
+                        TranscriptStatus::Sent
+                    }
+                    Err(_) => TranscriptStatus::Failed,
+                };
+                app.set_transcript_status(id, status);
+            }
+            match &result {
+                Ok(_) => log("tui: prompt sent successfully"),
+                Err(e) => log(&format!("tui: prompt send failed: {e}")),
+            }
+            if let Err(e) = result {
+                app.notices.error(format!("Send failed: {}", e));
+            }
+        }
+        AppMessage::SessionReady {
+            id,
+            slug,
+            directory,
+            activate,
+        } => {
+            app.attach_session(id.clone(), slug, activate, &config.tools);
+            app.set_session_directory(&id, directory);
+        }
+        AppMessage::SessionsListed(sessions) => app.sessions = sessions,
+        AppMessage::SessionLost { id, why } => session_lost(app, &id, &why),
+        AppMessage::ConnectionChanged(status) => {
+            if status == ConnectionStatus::Connected {
+                app.heartbeats.connected(Instant::now());
+                // Sessions may have gone while the stream was down
+                if app.heartbeats.reconnects() > 0 {
+                    check_sessions(app);
+                }
+            }
+            app.connection_status = status;
+        }
+        AppMessage::RawEvent { data, handled } => {
+            app.inspector.record(&data, handled);
+        }
+        AppMessage::GitStatus(status) => app.git = status,
+        AppMessage::AudioError(error) => audio_stream_failed(app, &error),
+        AppMessage::CueError(error) => {
+            log(&format!("cues: output stream error: {error}"));
+            app.notices.warn(format!("Audible cue error: {error}"));
+        }
+        // Dropped if the focus moved on while it was built
+        AppMessage::VocabularyReady { dir, vocabulary } => {
+            if app.vocabulary_dir.as_ref() == Some(&dir) {
+                app.vocabulary = vocabulary;
+            }
+        }
+        AppMessage::Trigger(event) => {
+            let recording = app.state == RecordingState::Recording;
+            if config.trigger.mode.toggles(event, recording) {
+                // A pedal press is a Space press, barge-in included
+                if let Some(speaker) = speaker {
+                    speaker.stop();
+                    app.speaking = false;
+                }
+                handle_space(app, audio, transcribe)?;
+            }
+        }
+        AppMessage::Control(request) => {
+            if let (control::Command::Record { .. }, Some(speaker)) = (&request.command, speaker) {
+                speaker.stop();
+                app.speaking = false;
+            }
+            let response = run_control(app, &request.command, audio, transcribe)?;
+            request.reply(response);
+        }
+        // Published by the TUI itself, for other observers
+        AppMessage::RecordingStarted
+        | AppMessage::RecordingStopped
+        | AppMessage::FocusChanged { .. } => {}
+        AppMessage::ReplyReady { session_id, text } => {
+            let route = app.tabs.route(Some(&session_id));
+            if let Route::Background(index) = route {
+                if let Some(tab) = app.tabs.get_mut(index) {
+                    tab.timeline.reply(&text);
+                    tab.last_reply = Some(text);
+                }
+                return Ok(());
+            }
+            app.timeline.reply(&text);
+            app.last_reply = Some(text.clone());
+            app.hands_free.reply_rendered();
+            // Don't talk over the user
+            if let (Some(speaker), RecordingState::Idle) = (speaker, app.state) {
+                let text = tts::speakable_text(&text, config.tts.max_chars);
+                if let Err(e) = speaker.speak(&text) {
+                    log(&format!("tts: {e}"));
+                    app.notices.error(format!("Speech failed: {}", e));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Safeguards against a forgotten recording, given its latest `samples`:
+/// warnings when the buffer is full or the mic dead, and the stops for a
+/// dictation's segments, a spoken answer and auto-stop. Returns whether the
+/// recording was stopped.
+fn watch_recording(
+    app: &mut App,
+    audio: &AudioCapture,
+    transcribe: &mpsc::Sender<TranscribeJob>,
+    samples: &[f32],
+) -> Result<bool> {
+    if let Some(started) = app.recording_started {
+        let elapsed = started.elapsed();
+        let level = viz::rms(samples);
+        let usage = audio.buffer_usage();
+        if usage >= 1.0 && app.buffer_usage < 1.0 {
+            app.notices.warn(format!(
+                "Recording is longer than {}s; its beginning is being overwritten",
+                audio::BUFFER_SECS
+            ));
+        }
+        app.buffer_usage = usage;
+        let dead = audio.dead_for() >= DEAD_MIC;
+        if dead && !app.mic_dead {
+            log("audio: mic is delivering a dead signal");
+            app.notices
+                .warn("Mic appears silent \u{2014} check the mute switch and permissions");
+        }
+        app.mic_dead = dead;
+        if app.answering && elapsed >= answer::MAX_RECORDING {
+            handle_space(app, audio, transcribe)?;
+            return Ok(true);
+        }
+        // A dictation runs until it's finished, a segment at a time
+        let dictation = app.dictation.as_mut().filter(|d| !d.is_finishing());
+        let status = match dictation.map(|d| d.segmenter.feed(elapsed, level)) {
+            Some(Cut::Segment { join }) => {
+                let samples = audio.take_recording();
+                send_segment(app, samples, join, transcribe)?;
+                AutoStopStatus::Continue
+            }
+            Some(Cut::Discard) => {
+                audio.take_recording();
+                AutoStopStatus::Continue
+            }
+            Some(Cut::None) => AutoStopStatus::Continue,
+            None => app.autostop.check(elapsed, level),
+        };
+        match status {
+            AutoStopStatus::Continue => app.stop_countdown = None,
+            AutoStopStatus::Warning { remaining } => {
+                app.stop_countdown = Some(remaining.as_secs_f32().ceil() as u64);
+            }
+            AutoStopStatus::Stop(reason) => {
+                log(&format!(
+                    "tui: recording auto-stopped after {:.1}s ({reason})",
+                    elapsed.as_secs_f32()
+                ));
+                handle_space(app, audio, transcribe)?;
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Do what a key in the main view, resolved to `action`, asks for.
+fn apply_action(
+    app: &mut App,
+    action: Action,
+    key: KeyEvent,
+    audio: &AudioCapture,
+    transcribe: &mpsc::Sender<TranscribeJob>,
+    speaker: Option<&mut Speaker>,
+    config: &Config,
+) -> Result<()> {
+    match action {
+        Action::Quit => {}
+        Action::AbortRecording => abort_recording(app, audio),
+        Action::HoldPrompt => hold_prompt(app),
+        Action::Record => {
+            // Barge in: talking over the readback stops it
+            if let Some(speaker) = speaker {
+                speaker.stop();
+                app.speaking = false;
+            }
+            // With a prompt pending, the recording is a spoken answer to it
+            space_pressed(app, audio, transcribe, Instant::now())?;
+        }
+        Action::CopyPrompt => copy_prompt(app),
+        Action::ConfirmPrompt => confirm_prompt(app),
+        Action::Route(target) => route_prompt(app, target),
+        Action::CancelQueued => cancel_queued(app),
+        Action::ForgetFocus => forget_focus(app),
+        Action::DiscardPrompt => discard_prompt(app),
+        Action::RetryPrompt => retry_prompt(app, transcribe)?,
+        Action::UndoCorrection => undo_correction(app),
+        Action::StopSpeaking => {
+            if let Some(speaker) = speaker {
+                speaker.stop();
+                app.speaking = false;
+            }
+        }
+        Action::ToggleAutoSend => {
+            app.auto_send = !app.auto_send;
+            if !app.auto_send {
+                // Leave a counting-down prompt for manual confirmation
+                app.send_deadline = None;
+            }
+            log(&format!(
+                "tui: auto-send {}",
+                if app.auto_send { "on" } else { "off" }
+            ));
+        }
+        Action::OpenInput => open_input(app, ""),
+        Action::OpenPalette => open_palette(app),
+        // Before the first session is resolved there is nothing to tab from;
+        // without a session, the pending prompt is for the new one
+        Action::NewTab => {
+            if let Some(opencode) = &app.opencode
+                && (app.session_id.is_some() || app.detached)
+                && (app.prompt_pending.is_none() || app.detached)
+            {
+                let directory = app.directory.clone();
+                open_session_tab(opencode, directory, &app.bus, &app.tasks);
+            }
+        }
+        Action::NextTab => app.switch_tab(app.tabs.cycle(1)),
+        Action::PreviousTab => app.switch_tab(app.tabs.cycle(-1)),
+        Action::CloseTab => app.close_tab(),
+        Action::ToggleSpelling => app.set_spelling(!app.spelling),
+        Action::Dictation => match &app.dictation {
+            Some(dictation) if !dictation.is_finishing() => {
+                handle_space(app, audio, transcribe)?;
+            }
+            Some(_) => {}
+            None => {
+                if app.prompt_pending.is_none() && app.state == RecordingState::Idle {
+                    app.start_recording(audio);
+                    if app.state == RecordingState::Recording {
+                        log("tui: dictation started");
+                        app.dictation = Some(Dictation::new(config.dictation.clone()));
+                    }
+                }
+            }
+        },
+        Action::ToggleCaptions => {
+            app.caption.enabled = !app.caption.enabled;
+            log(&format!(
+                "tui: captions {}",
+                if app.caption.enabled { "on" } else { "off" }
+            ));
+        }
+        Action::ToggleHandsFree => {
+            let on = !app.hands_free.is_enabled();
+            app.hands_free.set_enabled(on);
+            log(&format!(
+                "tui: hands-free {}",
+                if on { "on" } else { "off" }
+            ));
+        }
+        Action::Sensitivity { more } => {
+            if more {
+                app.waveform.more_sensitive();
+            } else {
+                app.waveform.less_sensitive();
+            }
+            app.notices.info(format!(
+                "Waveform full scale at RMS {:.3}",
+                app.waveform.full_scale
+            ));
+        }
+        Action::TogglePanel(panel) => app.toggle_panel(panel),
+        Action::ToggleWaveform => app.show_waveform = !app.show_waveform,
+        Action::NextAgent => {
+            if let Some(next) = agent::next(&app.agents, app.agent.as_deref()) {
+                app.set_agent(Some(next.to_string()));
+            }
+        }
+        Action::ToggleConversation => app.show_conversation = !app.show_conversation,
+        Action::PreviousTurn => app.timeline.select_previous(),
+        Action::NextTurn => app.timeline.select_next(),
+        Action::ToggleFold => app.timeline.toggle_fold(),
+        Action::Scroll { down } => match app.panel {
+            Some(Panel::Inspector) if down => app.inspector_scroll += 1,
+            Some(Panel::Inspector) => {
+                app.inspector_scroll = app.inspector_scroll.saturating_sub(1);
+            }
+            Some(Panel::Queue) if down => {
+                let last = app.queue.waiting().count().saturating_sub(1);
+                app.queue_cursor = (app.queue_cursor + 1).min(last);
+            }
+            Some(Panel::Queue) => app.queue_cursor = app.queue_cursor.saturating_sub(1),
+            Some(Panel::Notices) if down => {
+                let last = app.notices.history().len().saturating_sub(1);
+                app.notice_scroll = (app.notice_scroll + 1).min(last);
+            }
+            Some(Panel::Notices) => app.notice_scroll = app.notice_scroll.saturating_sub(1),
+            _ => {}
+        },
+        Action::CycleFilter(step) => {
+            app.inspector.cycle_filter(step);
+            app.inspector_scroll = 0;
+        }
+        Action::EditPrompt => {
+            let pending = app.prompt_pending.clone().unwrap_or_default();
+            open_input(app, &pending);
+            handle_input_key(app, key);
+        }
+        Action::FocusUp => {
+            let before = app.focus.current_entry().cloned();
+            app.focus.move_up();
+            app.notify_focus_change(before);
+        }
+        Action::FocusDown => {
+            let before = app.focus.current_entry().cloned();
+            app.focus.move_down();
+            app.notify_focus_change(before);
+        }
+        Action::PinFocus => pin_focus(app),
+        Action::ToggleFollow => {
+            let before = app.focus.current_entry().cloned();
+            app.focus.toggle_follow_mode();
+            app.notify_focus_change(before);
+        }
+    }
+    Ok(())
+}
+
+/// Work in `dir` from now on: bring its session to the front, in a new tab
+/// unless one is attached already, or create one for it.
+fn change_directory(app: &mut App, dir: PathBuf) {
+    if app.directory.as_ref() == Some(&dir) {
+        return;
+    }
+    let Some(opencode) = &app.opencode else {
+        app.notices.warn("Not connected to OpenCode");
+        return;
+    };
+    // The pending prompt was spoken for the session in front
+    if app.prompt_pending.is_some() {
+        app.notices
+            .warn("Send or discard the pending prompt before changing directories");
+        return;
+    }
+    log(&format!("tui: directory {}", dir.display()));
+    app.notices
+        .info(format!("Working in {}", paths::home_relative(&dir)));
+    let (opencode, bus) = (opencode.clone(), app.bus.clone());
+    app.directory = Some(dir.clone());
+    app.tasks.spawn("Session lookup", async move {
+        let sessions = opencode.list_sessions().await?;
+        bus.publish(AppMessage::SessionsListed(sessions.clone()));
+        let session = match transport::directory_session(&sessions, &dir) {
+            Some(s) => s.clone(),
+            None => opencode.create_session(Some(dir.clone())).await?,
+        };
+        bus.publish(AppMessage::SessionReady {
+            id: session.id,
+            slug: session.slug,
+            directory: Some(dir),
+            activate: true,
+        });
+        Ok(())
+    });
+}
+
+/// After a reconnect, fetch each attached session again: one deleted or
+/// moved to another directory while the stream was down is reported lost,
+/// with the sessions OpenCode has now listed for the picker.
+fn check_sessions(app: &App) {
+    let Some(opencode) = &app.opencode else {
+        return;
+    };
+    for tab in app.tabs.tabs() {
+        let (opencode, bus) = (opencode.clone(), app.bus.clone());
+        let (id, expected) = (tab.session_id.clone(), tab.directory.clone());
+        app.tasks.spawn("Session check", async move {
+            let why = match opencode.get_session(id.clone()).await? {
+                None => "no longer exists".to_string(),
+                Some(session) => match (session.directory, expected) {
+                    (Some(got), Some(want)) if Path::new(&got) != want => {
+                        format!("now works in {}", paths::home_relative(Path::new(&got)))
+                    }
+                    _ => return Ok(()),
+                },
+            };
+            bus.publish(AppMessage::SessionsListed(opencode.list_sessions().await?));
+            bus.publish(AppMessage::SessionLost { id, why });
+            Ok(())
+        });
+    }
+}
+
+/// Session `id` was found lost after a reconnect: close its tab, and with
+/// no session left, offer the others to pick from.
+fn session_lost(app: &mut App, id: &str, why: &str) {
+    app.session_gone(id, why);
+    if app.detached {
+        open_session_picker(app);
+    }
+}
+
+/// Open the palette on the sessions OpenCode has, after a new session in
+/// the project directory.
+fn open_session_picker(app: &mut App) {
+    let new = PaletteItem {
+        label: "New session".to_string(),
+        detail: app
+            .directory
+            .as_deref()
+            .map(paths::home_relative)
+            .unwrap_or_default(),
+        action: PaletteAction::NewSession,
+    };
+    let sessions = app.sessions.iter().map(|session| PaletteItem {
+        label: session
+            .slug
+            .clone()
+            .or_else(|| session.title.clone())
+            .unwrap_or_else(|| session.id.clone()),
+        detail: session
+            .directory
+            .as_deref()
+            .map(|d| paths::home_relative(Path::new(d)))
+            .unwrap_or_default(),
+        action: PaletteAction::Session {
+            id: session.id.clone(),
+            slug: session.slug.clone(),
+            directory: session.directory.as_deref().map(PathBuf::from),
+        },
+    });
+    app.palette = Some(Palette::new(std::iter::once(new).chain(sessions).collect()));
+}
+
+/// Create a session working in `directory` and publish it to be attached
+/// in a new tab.
+fn open_session_tab(
+    opencode: &SessionHandle,
+    directory: Option<PathBuf>,
+    bus: &EventBus<AppMessage>,
+    tasks: &Supervisor,
+) {
+    let created = opencode.create_session(directory.clone());
+    let bus = bus.clone();
+    tasks.spawn("New session", async move {
+        match created.await {
+            Ok(session) => {
+                bus.publish(AppMessage::SessionReady {
+                    id: session.id,
+                    slug: session.slug,
+                    directory,
+                    activate: true,
+                });
+            }
+            Err(e) => log(&format!("tui: failed to create session: {e}")),
+        }
+        Ok(())
+    });
+}
+
+/// Fetch the last assistant message of `session_id` and send its text back
+/// for the conversation view, readback and the conversation context.
+fn fetch_last_reply(
+    opencode: &SessionHandle,
+    session_id: &str,
+    bus: &EventBus<AppMessage>,
+    tasks: &Supervisor,
+) {
+    let session_id = session_id.to_string();
+    let messages = opencode.list_messages(Some(session_id.clone()));
+    let bus = bus.clone();
+    tasks.spawn("Reply fetch", async move {
+        let messages = match messages.await {
+            Ok(messages) => messages,
+            Err(e) => {
+                log(&format!("tui: failed to fetch reply: {e}"));
+                return Ok(());
+            }
+        };
+        let Some(reply) = messages.iter().rev().find(|m| m.role == "assistant") else {
+            return Ok(());
+        };
+        let text: Vec<&str> = reply
+            .parts
+            .iter()
+            .filter_map(|p| match p {
+                MessagePart::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        if !text.is_empty() {
+            bus.publish(AppMessage::ReplyReady {
+                session_id,
+                text: text.join("\n\n"),
+            });
+        }
+        Ok(())
+    });
+}
+
+/// `conch ctl <command> [--config <file>]`: send a command to the running
+/// conch over its control socket and print the result, for key bindings and
+/// scripts. A refused command is an error, so the exit status says so.
+async fn run_ctl(args: &[String], config: &Config) -> Result<()> {
+    let command = control::Command::from_args(args)?;
+    let response = control::request(&config.control.socket_path(), &command).await?;
+    if let Some(result) = response.into_result()? {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    }
+    Ok(())
+}
+
+/// `conch logs [-n <lines>] [--follow | --open | --path]`: print the end of
+/// the current log, keep printing as it grows, open it in `$PAGER`, or print
+/// where it is.
+fn run_logs(args: &[String]) -> Result<()> {
+    let path = paths::log_file();
+    let (count, follow) = match LogsCommand::from_args(args)? {
+        LogsCommand::Path => {
+            println!("{}", path.display());
+            return Ok(());
+        }
+        LogsCommand::Open => {
+            let pager = std::env::var("PAGER").unwrap_or_else(|_| "less".into());
+            return logs::open_in_pager(&path, &pager);
+        }
+        LogsCommand::Tail { count, follow } => (count, follow),
+    };
+    if path.exists() {
+        for line in logs::tail(&path, count)? {
+            println!("{line}");
+        }
+    } else {
+        eprintln!("No log yet at {}", path.display());
+    }
+    if follow {
+        let mut follower = logs::Follower::new(&path);
+        loop {
+            std::thread::sleep(Duration::from_millis(250));
+            print!("{}", follower.read_new()?);
+            io::stdout().flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// `conch export [file] [--session <id>]`: write the session's conversation
+/// to Markdown (default) or JSON, picked by the file extension.
+async fn run_export(args: &[String]) -> Result<()> {
+    let args = ExportArgs::from_args(args);
+    let mut client = OpenCodeClient::new(OPENCODE_URL);
+    let sessions = client
+        .list_sessions()
+        .await
+        .map_err(|e| anyhow!("Could not reach OpenCode at {OPENCODE_URL}: {e}"))?;
+    let session = args.pick_session(&sessions)?;
+    client.set_session(session.id.clone());
+    let messages = client.list_messages().await?;
+
+    let path = args.out_path(&session);
+    export::write(&path, &session, &messages)?;
+    eprintln!(
+        "Exported {} messages from session {} to '{}'.",
+        messages.len(),
+        session.id,
+        path.display()
+    );
+    Ok(())
+}
+
+/// Report a background task that panicked or failed. The recording being
+/// transcribed when the transcription thread crashed is lost.
+fn task_ended(app: &mut App, report: &Report) {
+    log(&format!(
+        "tasks: {} ended, {:?}, {:?}",
+        report.name, report.exit, report.restart
+    ));
+    let (what, why) = match &report.exit {
+        Exit::Panicked(message) => ("crashed", message),
+        Exit::Failed(error) => ("stopped", error),
+        Exit::Finished => return,
+    };
+    if report.name == TRANSCRIPTION && what == "crashed" {
+        app.transcribing = app.transcribing.saturating_sub(1);
+        app.settle_state();
+    }
+    let then = match report.restart {
+        Restart::No => String::new(),
+        Restart::In(delay) => format!(" \u{2014} restarting in {}s", delay.as_secs()),
+        Restart::GaveUp => format!(
+            " \u{2014} gave up after {} restarts",
+            supervisor::MAX_RESTARTS
+        ),
+    };
+    app.notices
+        .error(format!("{} {what}: {why}{then}", report.name));
+}
+
+/// Open the cue player for `config`, reporting errors of its output stream
+/// on the bus like those of the input stream.
+fn open_cues(app: &mut App, config: &CueConfig) {
+    let errors = app.bus.clone();
+    let on_error = move |error| {
+        errors.publish(AppMessage::CueError(error));
+    };
+    match CuePlayer::new(config.clone(), on_error) {
+        Ok(player) => app.cues = Some(player),
+        Err(e) => {
+            log(&format!("cues: disabled, {e}"));
+            app.notices.warn(format!("Audible cues disabled: {e}"));
+        }
+    }
+}
+
+/// The audio stream, or a restart of it, failed with `error`: restart it
+/// after a while, unless that was tried often enough.
+fn audio_stream_failed(app: &mut App, error: &str) {
+    log(&format!("audio: stream error: {error}"));
+    match app.audio_recovery.failed(Instant::now()) {
+        Recovery::RestartIn(delay) => app.notices.warn(format!(
+            "Audio stream error: {error} \u{2014} restarting in {}s",
+            delay.as_secs()
+        )),
+        Recovery::GiveUp => app.notices.error(format!(
+            "Audio stream error: {error} \u{2014} gave up after {} restarts; restart conch",
+            audio::MAX_RESTARTS
+        )),
+        Recovery::Pending => {}
+    }
+}
+
+/// Start listening to the MIDI controller and HID pedal in `[trigger]`. One
+/// that can't be opened is reported and left out.
+fn spawn_triggers(app: &mut App, config: &Config) {
+    if let Some(midi) = &config.trigger.midi {
+        let bus = app.bus.clone();
+        let publish = move |event| {
+            bus.publish(AppMessage::Trigger(event));
+        };
+        if let Err(e) = trigger::spawn_midi(midi, publish) {
+            log(&format!("trigger: {e}"));
+            app.notices.error(format!("MIDI trigger disabled: {e}"));
+        }
+    }
+    if let Some(hid) = &config.trigger.hid {
+        let bus = app.bus.clone();
+        let publish = move |event| {
+            bus.publish(AppMessage::Trigger(event));
+        };
+        if let Err(e) = trigger::spawn_hid(hid, publish, log) {
+            log(&format!("trigger: {e}"));
+            app.notices.error(format!("Pedal disabled: {e}"));
+        }
+    }
+}
+
+/// Listen on the control socket in `[control]`, passing its commands to the
+/// TUI over the bus. One that can't be listened on is reported.
+fn spawn_control(app: &mut App, config: &Config) -> Option<ControlSocket> {
+    if !config.control.enabled {
+        return None;
+    }
+    let bus = app.bus.clone();
+    let publish = move |request| {
+        bus.publish(AppMessage::Control(request));
+    };
+    match control::listen(&config.control.socket_path(), publish) {
+        Ok(socket) => {
+            log(&format!(
+                "control: listening on {}",
+                socket.path().display()
+            ));
+            Some(socket)
+        }
+        Err(e) => {
+            log(&format!("control: {e}"));
+            app.notices.warn(format!("Control socket disabled: {e}"));
+            None
+        }
+    }
+}
+
+/// Write a timestamped line to the debug log (`conch.log`).
+fn log(msg: &str) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    logs::append(
+        &paths::log_file(),
+        &format!("[{:.3}] {}", now.as_secs_f64(), msg),
+    );
+}
+
+/// Edit the typed prompt. Enter turns it into the pending prompt, exactly
+/// like a transcript; Esc throws it away.
+fn handle_input_key(app: &mut App, key: KeyEvent) {
+    let Some(input) = &mut app.input else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc => app.input = None,
+        KeyCode::Enter => {
+            let text = input.text().trim().to_string();
+            app.input = None;
+            if text.is_empty() || app.prompt_pending.as_deref() == Some(text.as_str()) {
+                return;
+            }
+            // An edited or recalled prompt replaces the one pending
+            discard_prompt(app);
+            propose_prompt(app, text);
+        }
+        KeyCode::Up => {
+            if let Some(text) = app.recall.older(input.text()) {
+                input.set_text(text);
+            }
+        }
+        KeyCode::Down => {
+            if let Some(text) = app.recall.newer() {
+                input.set_text(text);
+            }
+        }
+        _ => edit_text(input, key),
+    }
+}
+
+/// Open the input line holding `draft`, with the prompts sent so far ready
+/// to recall. They come from the transcript store when history is on, so
+/// they outlast a restart. A pending prompt's auto-send countdown is held.
+fn open_input(app: &mut App, draft: &str) {
+    let entries = match &app.history {
+        Some(store) => store.load().unwrap_or_else(|e| {
+            log(&format!("history: {e}"));
+            app.transcripts.clone()
+        }),
+        None => app.transcripts.clone(),
+    };
+    app.recall = Recall::new(history::sent_prompts(&entries));
+    let mut input = TextInput::new();
+    input.set_text(draft);
+    app.input = Some(input);
+    app.send_deadline = None;
+}
+
+/// Keys of the vim preset, on top of the defaults. Returns whether `key`
+/// was one.
+fn handle_vim_key(app: &mut App, key: KeyEvent) -> bool {
+    let len = app.transcripts.len();
+    let cursor = &mut app.transcript_cursor;
+    let c = match key.code {
+        KeyCode::Esc if app.state == RecordingState::Recording => return false,
+        KeyCode::Esc if app.send_deadline.is_some() => return false,
+        KeyCode::Esc if cursor.is_selecting() => {
+            cursor.toggle_select(len);
+            return true;
+        }
+        // Esc never quits, vim users press it out of habit
+        KeyCode::Esc => {
+            cursor.reset();
+            return true;
+        }
+        KeyCode::Char('y') if cursor.is_selecting() => {
+            copy_selection(app);
+            return true;
+        }
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => c,
+        _ => return false,
+    };
+    let Some(action) = app.vim_keys.press(c) else {
+        // The first half of `gg`
+        return c == 'g';
+    };
+    match action {
+        VimAction::Down => cursor.down(len),
+        VimAction::Up => cursor.up(len),
+        VimAction::Top => cursor.top(len),
+        VimAction::Bottom => cursor.reset(),
+        VimAction::Search => app.search = Some(TextInput::new()),
+        VimAction::SearchNext => search_transcripts(app, true),
+        VimAction::SearchPrevious => search_transcripts(app, false),
+        VimAction::Palette => {
+            if app.prompt_pending.is_none() && app.state == RecordingState::Idle {
+                open_palette(app);
+            }
+        }
+        VimAction::Select => cursor.toggle_select(len),
+    }
+    true
+}
+
+/// Edit the transcript search. Enter moves to the newest match.
+fn handle_search_key(app: &mut App, key: KeyEvent) {
+    let Some(search) = &mut app.search else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc => app.search = None,
+        KeyCode::Enter => {
+            let query = search.text().trim().to_string();
+            app.search = None;
+            if !query.is_empty() {
+                app.search_query = query;
+                app.transcript_cursor.reset();
+                search_transcripts(app, true);
+            }
+        }
+        _ => edit_text(search, key),
+    }
+}
+
+/// Move to the next match of the last search, older when `backward`.
+fn search_transcripts(app: &mut App, backward: bool) {
+    if app.search_query.is_empty() {
+        return;
+    }
+    let texts = app.transcripts.iter().map(|e| e.text.as_str());
+    if !app
+        .transcript_cursor
+        .search(texts, &app.search_query, backward)
+    {
+        app.notices
+            .info(format!("No transcript matches \"{}\"", app.search_query));
+    }
+}
+
+/// Copy the selected transcripts, one per line, and stop selecting.
+fn copy_selection(app: &mut App) {
+    let len = app.transcripts.len();
+    let Some(range) = app.transcript_cursor.selection(len) else {
+        return;
+    };
+    let count = range.clone().count();
+    let text: Vec<&str> = app.transcripts[range]
+        .iter()
+        .map(|e| e.text.as_str())
+        .collect();
+    match clipboard::copy(&text.join("\n"), app.clipboard) {
+        Ok(backend) => {
+            log(&format!(
+                "tui: {count} transcripts copied to clipboard ({backend:?})"
+            ));
+            let noun = if count == 1 {
+                "transcript"
+            } else {
+                "transcripts"
+            };
+            app.notices.info(format!("Copied {count} {noun}"));
+        }
+        Err(e) => {
+            log(&format!("tui: clipboard copy failed: {e}"));
+            app.notices.error(format!("Copy failed: {}", e));
+        }
+    }
+    app.transcript_cursor.toggle_select(len);
+}
+
+/// Line-editing keys shared by the typed prompt and the palette filter.
+fn edit_text(input: &mut TextInput, key: KeyEvent) {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Backspace => input.backspace(),
+        KeyCode::Delete => input.delete(),
+        KeyCode::Left => input.move_left(),
+        KeyCode::Right => input.move_right(),
+        KeyCode::Home => input.move_home(),
+        KeyCode::End => input.move_end(),
+        KeyCode::Char('w') if ctrl => input.delete_word(),
+        KeyCode::Char('u') if ctrl => input.delete_to_start(),
+        KeyCode::Char('a') if ctrl => input.move_home(),
+        KeyCode::Char('e') if ctrl => input.move_end(),
+        KeyCode::Char(c) if !ctrl => input.insert(c),
+        _ => {}
+    }
+}
+
+/// Make a finished transcript the pending prompt, or send it right away
+/// when auto-send lets it through.
+fn accept_transcript(
+    app: &mut App,
+    transcript: Transcript,
+    config: &Config,
+    cancel_window: Duration,
+) {
+    let Transcript { text, confidence } = transcript;
+    // "Thanks for watching!" from a silent recording never becomes a prompt
+    let hallucinated = config.hallucinations.matches(&text);
+    if hallucinated && config.hallucinations.action == hallucination::Action::Drop {
+        log(&format!("tui: dropped hallucination {text:?}"));
+        app.notices.warn(format!(
+            "Dropped \"{}\" \u{2014} Whisper makes this up from silence",
+            text.trim()
+        ));
+        return;
+    }
+    match spelling::command(&text) {
+        Some(spelling::Command::Start) => return app.set_spelling(true),
+        Some(spelling::Command::Stop) => return app.set_spelling(false),
+        Some(spelling::Command::Spell(spelled)) => return add_spelled(app, spelled),
+        None if app.spelling => {
+            match spelling::spell(&text) {
+                Ok(spelled) => add_spelled(app, spelled),
+                Err(e) => app.notices.error(format!("Spelling: {e}")),
+            }
+            return;
+        }
+        None => {}
+    }
+    // "Switch to Spanish" changes the session's language instead of prompting
+    if let Some(result) = language::voice_switch(&text) {
+        match result {
+            Ok(lang) => app.set_language(lang),
+            Err(e) => app.notices.error(format!("Language not changed: {e}")),
+        }
+        return;
+    }
+    // "Use the plan agent" sends later prompts to that agent
+    if let Some(result) = agent::voice_switch(&text, &app.agents) {
+        match result {
+            Ok(name) => app.set_agent(Some(name)),
+            Err(e) => app.notices.error(format!("Agent not changed: {e}")),
+        }
+        return;
+    }
+    // "Focus on the transport file" moves the focus pointer
+    if let Some(command) = focus::voice_command(&text)
+        && voice_focus(app, command)
+    {
+        return;
+    }
+    // "Run snippet review" becomes the snippet's prompt
+    let text = match snippets::from_voice(&app.snippets, &app.focus, &text) {
+        Some(Ok(prompt)) => {
+            log(&format!("tui: voice snippet: {text}"));
+            prompt
+        }
+        Some(Err(e)) => {
+            app.notices.error(format!("Snippet failed: {}", e));
+            return;
+        }
+        None => text,
+    };
+    // Numbers, casing and names; the focus names come before the project's
+    let focus = homophones::focus_vocabulary(&app.focus);
+    let vocabularies = [&focus, &app.vocabulary];
+    let rewrites = Rewrites {
+        numbers: config.numbers.enabled.then_some(app.language().code),
+        vocabularies: config.vocabulary.correct.then_some(&vocabularies[..]),
+    };
+    app.corrected = rewrites.apply(&text);
+    let heard = text;
+    let text = app.corrected.text.clone();
+    if !app.corrected.corrections.is_empty() {
+        log(&format!("tui: corrected {heard:?} to {text:?}"));
+    }
+    let entry = HistoryEntry::new(text.clone(), app.session_id.clone());
+    app.save_transcript(&entry);
+    app.transcripts.push(entry);
+    app.cue(Cue::TranscriptReady);
+    app.prompt_pending = Some(text.clone());
+    app.notices.clear_toast();
+    app.review = None;
+    if hallucinated {
+        // Flagged: never sent without a look, auto-send or not
+        log(&format!("tui: flagged hallucination {text:?}"));
+        app.review = Some(ReviewReason::Hallucination);
+    } else if app.auto_send {
+        let keywords = app.language().review_keywords;
+        match config.send.review_reason_with(&text, confidence, keywords) {
+            Some(reason) => {
+                log(&format!("tui: auto-send held: {reason}"));
+                app.review = Some(reason);
+            }
+            None if cancel_window.is_zero() => confirm_prompt(app),
+            None => app.send_deadline = Some(Instant::now() + cancel_window),
+        }
+    }
+}
+
+/// Apply the settings in `new` that changed since `old` and can change while
+/// running. Sections read only at startup are listed in a restart notice.
+/// Nothing is applied if `new` is invalid.
+fn reload_config(app: &mut App, old: &Config, new: &Config) -> Result<()> {
+    let default_language = language::parse(&new.stt.language)?;
+    let theme = Theme::from_config(&new.theme)?;
+    log("config: reloaded");
+    app.default_language = default_language;
+    app.retry_model = new.stt.retry_model.clone();
+    app.clipboard = new.clipboard.backend;
+    app.theme = theme;
+    app.glyphs = new.display.unicode.glyphs();
+    app.keymap = new.keys.keymap;
+    if new.preview != old.preview {
+        app.preview_config = new.preview.clone();
+        app.preview = None;
+    }
+    if new.layout != old.layout {
+        app.layout = new.layout.clone();
+        app.show_waveform = new.layout.waveform;
+    }
+    if new.send.auto != old.send.auto {
+        app.auto_send = new.send.auto;
+    }
+    app.retry = new.send.retry_policy();
+    if new.handsfree != old.handsfree {
+        app.hands_free = HandsFree::new(&new.handsfree);
+    }
+    if new.snippets != old.snippets {
+        app.snippets = new.snippets.clone();
+    }
+    if new.tools != old.tools {
+        app.activity.set_rules(new.tools.clone());
+        for tab in app.tabs.tabs_mut() {
+            tab.activity.set_rules(new.tools.clone());
+        }
+    }
+    if new.recording != old.recording {
+        app.autostop = AutoStop::new(new.recording.clone());
+    }
+    app.waveform = new.waveform.clone();
+    app.context = new.context.clone();
+    app.caption = new.caption.clone();
+    app.agents = new.agent.agents();
+    if new.agent.default != old.agent.default {
+        app.set_agent(new.agent.default.clone());
+    }
+    app.route = new.route.clone();
+    if new.history != old.history {
+        app.history = new
+            .history
+            .enabled
+            .then(|| TranscriptStore::new(&new.history.path));
+        app.focus_marks = new
+            .history
+            .enabled
+            .then(|| FocusStore::new(paths::focus_file()));
+    }
+    if new.log != old.log {
+        logs::configure(new.log.clone());
+    }
+    if new.cues != old.cues {
+        app.cues = None;
+        if new.cues.enabled {
+            open_cues(app, &new.cues);
+        }
+    }
+    let restart = reload::restart_sections(old, new);
+    if restart.is_empty() {
+        app.notices.info("Config reloaded");
+    } else {
+        app.notices.warn(format!(
+            "Config reloaded \u{2014} restart conch to apply {}",
+            restart.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Take queued transcripts in order while no prompt is pending. Transcripts
+/// wait their turn behind the pending prompt, except spelling, which adds to it.
+fn accept_queued_transcripts(app: &mut App, config: &Config, cancel_window: Duration) {
+    while let Some(next) = app.transcript_queue.front() {
+        if app.prompt_pending.is_some() && !app.spelling && spelling::command(&next.text).is_none()
+        {
+            break;
+        }
+        let Some(transcript) = app.transcript_queue.pop_front() else {
+            break;
+        };
+        accept_transcript(app, transcript, config, cancel_window);
+    }
+}
+
+/// Append spelled-out text to the pending prompt, or make it the pending
+/// prompt. Spelled text is never auto-sent: more usually follows.
+fn add_spelled(app: &mut App, spelled: String) {
+    log(&format!("tui: spelled {spelled}"));
+    match &mut app.prompt_pending {
+        Some(pending) => {
+            if !app.spelled_tail {
+                pending.push(' ');
+            }
+            pending.push_str(&spelled);
+            // The pending prompt is always the newest transcript
+            let text = pending.clone();
+            if let Some(entry) = app.transcripts.last_mut() {
+                entry.text = text;
+            }
+            if let Some(entry) = app.transcripts.last() {
+                app.save_transcript(entry);
+            }
+        }
+        None => {
+            let entry = HistoryEntry::new(spelled.clone(), app.session_id.clone());
+            app.save_transcript(&entry);
+            app.transcripts.push(entry);
+            app.prompt_pending = Some(spelled);
+        }
+    }
+    app.spelled_tail = app.spelling;
+    app.cue(Cue::TranscriptReady);
+    app.send_deadline = None;
+    app.review = None;
+}
+
+/// Make `text`, which the user wrote or picked rather than spoke, the
+/// pending prompt. It is recorded like a transcript; with auto-send on it
+/// goes out at once, since there is nothing misheard to review.
+fn propose_prompt(app: &mut App, text: String) {
+    let entry = HistoryEntry::new(text.clone(), app.session_id.clone());
+    app.save_transcript(&entry);
+    app.transcripts.push(entry);
+    app.prompt_pending = Some(text);
+    app.notices.clear_toast();
+    app.review = None;
+    if app.auto_send {
+        confirm_prompt(app);
+    }
+}
+
+/// Open the command palette with the configured snippets, then the other
+/// directories OpenCode has sessions in.
+fn open_palette(app: &mut App) {
+    let snippets = app.snippets.iter().map(|(name, template)| PaletteItem {
+        label: name.clone(),
+        detail: template.clone(),
+        action: PaletteAction::Snippet(name.clone()),
+    });
+    let directories = app
+        .session_directories()
+        .into_iter()
+        .filter(|dir| app.directory.as_ref() != Some(dir))
+        .map(|dir| PaletteItem {
+            label: paths::home_relative(&dir),
+            detail: "work in this directory".to_string(),
+            action: PaletteAction::Directory(dir),
+        });
+    app.palette = Some(Palette::new(snippets.chain(directories).collect()));
+}
+
+/// Filter, move through, and pick from the command palette.
+fn handle_palette_key(app: &mut App, key: KeyEvent) {
+    let Some(palette) = &mut app.palette else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc => app.palette = None,
+        KeyCode::Up => palette.move_up(),
+        KeyCode::Down => palette.move_down(),
+        KeyCode::Enter => {
+            let action = palette.selected_item().map(|item| item.action.clone());
+            app.palette = None;
+            match action {
+                Some(PaletteAction::Snippet(name)) => use_snippet(app, &name),
+                Some(PaletteAction::Directory(dir)) => change_directory(app, dir),
+                Some(PaletteAction::NewSession) => {
+                    if let Some(opencode) = &app.opencode {
+                        let directory = app.directory.clone();
+                        open_session_tab(opencode, directory, &app.bus, &app.tasks);
+                    }
+                }
+                Some(PaletteAction::Session {
+                    id,
+                    slug,
+                    directory,
+                }) => {
+                    app.bus.publish(AppMessage::SessionReady {
+                        id,
+                        slug,
+                        directory,
+                        activate: true,
+                    });
+                }
+                None => {}
+            }
+        }
+        _ => edit_text(&mut palette.query, key),
+    }
+}
+
+/// Expand snippet `name` against the focus stack into the pending prompt.
+fn use_snippet(app: &mut App, name: &str) {
+    let Some(template) = app.snippets.get(name) else {
+        return;
+    };
+    match snippets::expand(template, &app.focus) {
+        Ok(prompt) => {
+            log(&format!("tui: snippet '{name}' used"));
+            propose_prompt(app, prompt);
+        }
+        Err(e) => app.notices.error(format!("Snippet '{}': {}", name, e)),
+    }
+}
+
+/// Copy the pending transcript to the clipboard instead of sending it.
+/// The focus context is left off; the text is meant to be pasted elsewhere.
+fn copy_prompt(app: &mut App) {
+    app.send_deadline = None;
+    app.review = None;
+    let Some(text) = app.prompt_pending.take() else {
+        return;
+    };
+    match clipboard::copy(&text, app.clipboard) {
+        Ok(backend) => {
+            log(&format!(
+                "tui: transcript copied to clipboard ({backend:?})"
+            ));
+            app.notices.info("Copied to clipboard");
+            if let Some(id) = app.transcripts.last().map(|e| e.id) {
+                app.set_transcript_status(id, TranscriptStatus::Copied);
+            }
+        }
+        Err(e) => {
+            log(&format!("tui: clipboard copy failed: {e}"));
+            app.notices.error(format!("Copy failed: {}", e));
+            // Keep it so it can still be sent or discarded
+            app.prompt_pending = Some(text);
+        }
+    }
+}
+
+/// Send the pending prompt to `target`: OpenCode, the clipboard, the
+/// focused window or the notes file.
+fn route_prompt(app: &mut App, target: Target) {
+    match target {
+        Target::Prompt => confirm_prompt(app),
+        Target::Clipboard => copy_prompt(app),
+        Target::Type | Target::Notes => hand_off_prompt(app, target),
+    }
+}
+
+/// Type the pending prompt into the focused window or append it to the
+/// notes file. On failure it stays pending.
+fn hand_off_prompt(app: &mut App, target: Target) {
+    app.send_deadline = None;
+    app.review = None;
+    let Some(text) = app.prompt_pending.take() else {
+        return;
+    };
+    let (result, done, status) = match target {
+        Target::Notes => (
+            route::append_note(&app.route.notes, &text),
+            "Added to notes",
+            TranscriptStatus::Noted,
+        ),
+        _ => (
+            route::type_text(&app.route.type_commands(), &text),
+            "Typed into the focused window",
+            TranscriptStatus::Typed,
+        ),
+    };
+    match result {
+        Ok(()) => {
+            log(&format!("tui: transcript routed to {}", target.label()));
+            app.notices.info(done);
+            if let Some(id) = app.transcripts.last().map(|e| e.id) {
+                app.set_transcript_status(id, status);
+            }
+        }
+        Err(e) => {
+            log(&format!("tui: routing to {} failed: {e}", target.label()));
+            app.notices
+                .error(format!("{} failed: {}", target.label(), e));
+            app.prompt_pending = Some(text);
+        }
+    }
+}
+
+/// Send the pending transcript, prefixed with the current focus context.
+fn confirm_prompt(app: &mut App) {
+    app.send_deadline = None;
+    app.review = None;
+    if app.detached && app.prompt_pending.is_some() {
+        app.notices
+            .warn("No session to send to \u{2014} press [t] to start a new one");
+        return;
+    }
+    // A key repeat, or the same words said twice: wait for the first send
+    if app
+        .prompt_pending
+        .as_deref()
+        .is_some_and(|text| app.is_sending(text))
+    {
+        app.notices
+            .info("Already sending this prompt \u{2014} press [Enter] again once it's through");
+        return;
+    }
+    if let Some(text) = app.prompt_pending.take() {
+        app.notices.clear_toast();
+        app.utterance = None;
+        // The pending prompt is always the newest transcript
+        let transcript_id = app.transcripts.last().map(|e| e.id);
+        app.sending.push((transcript_id, text.clone()));
+        app.timeline.prompt(transcript_id, &text);
+        let prompt = outgoing_prompt(app, &text);
+        send_prompt_to_opencode(
+            app.opencode.as_ref(),
+            &prompt,
+            transcript_id,
+            app.retry,
+            app.send_order.take_turn(),
+            &app.bus,
+            &app.tasks,
+        );
+    }
+}
+
+/// Apply a spoken focus command and say what it did. Returns false when
+/// it isn't one after all, leaving the transcript to be a prompt.
+fn voice_focus(app: &mut App, command: VoiceCommand) -> bool {
+    log(&format!("tui: voice focus {command:?}"));
+    let before = app.focus.current_entry().cloned();
+    let Some(outcome) = app.focus.apply_voice(command) else {
+        return false;
+    };
+    match outcome {
+        VoiceOutcome::Focused(Some(entry)) => {
+            let message = format!("Focused on {}", entry.value_str());
+            app.notices.info(message);
+        }
+        VoiceOutcome::Focused(None) => app.notices.warn("No focus entries yet"),
+        VoiceOutcome::Follow(true) => app.notices.info("Follow mode on"),
+        VoiceOutcome::Follow(false) => app.notices.info("Follow mode off"),
+        VoiceOutcome::NotFound(name) => {
+            app.notices
+                .warn(format!("Nothing in the focus stack is called \"{name}\""));
+        }
+    }
+    app.notify_focus_change(before);
+    true
+}
+
+/// Pin the focus entry under the pointer, keeping it in the prompt context,
+/// or unpin it.
+fn pin_focus(app: &mut App) {
+    let Some(pinned) = app.focus.toggle_pin() else {
+        return;
+    };
+    let value = app
+        .focus
+        .current_entry()
+        .map(FocusEntry::value_str)
+        .unwrap_or_default();
+    app.notices.info(if pinned {
+        format!("Pinned {value}")
+    } else {
+        format!("Unpinned {value}")
+    });
+    app.save_focus_marks();
+}
+
+/// Drop the focus entry under the pointer for good: the context stops
+/// mentioning it, and OpenCode touching it again doesn't bring it back.
+fn forget_focus(app: &mut App) {
+    let before = app.focus.current_entry().cloned();
+    let Some(entry) = app.focus.forget() else {
+        return;
+    };
+    log(&format!("tui: forgot {entry:?}"));
+    app.notices.info(format!("Forgot {}", entry.value_str()));
+    app.save_focus_marks();
+    app.notify_focus_change(before);
+}
+
+/// Drop the pending prompt without sending it.
+fn discard_prompt(app: &mut App) {
+    app.send_deadline = None;
+    app.review = None;
+    if app.prompt_pending.take().is_some() {
+        app.utterance = None;
+        app.notices.info("Prompt discarded");
+        if let Some(id) = app.transcripts.last().map(|e| e.id) {
+            app.set_transcript_status(id, TranscriptStatus::Discarded);
+        }
+    }
+}
+
+/// Act on a spoken answer to the pending prompt. The prompt may have been
+/// sent or discarded by key while the answer was transcribing.
+fn answer_prompt(
+    app: &mut App,
+    answer: Answer,
+    transcribe: &mpsc::Sender<TranscribeJob>,
+) -> Result<()> {
+    if app.prompt_pending.is_none() {
+        log(&format!("tui: answer {answer:?} with no prompt pending"));
+        return Ok(());
+    }
+    log(&format!("tui: answered {answer:?}"));
+    match answer {
+        Answer::Yes => confirm_prompt(app),
+        Answer::No => discard_prompt(app),
+        Answer::Cancel => hold_prompt(app),
+        Answer::Retry => retry_prompt(app, transcribe)?,
+        Answer::Route(target) => route_prompt(app, target),
+    }
+    Ok(())
+}
+
+/// Stop the auto-send countdown, keeping the prompt for Enter.
+fn hold_prompt(app: &mut App) {
+    app.send_deadline = None;
+    app.notices
+        .info("Prompt kept \u{2014} press [Enter] to send");
+}
+
+/// Transcribe the pending prompt's recording again with the `[stt]` retry
+/// model, replacing the pending text once it's in.
+fn retry_prompt(app: &mut App, transcribe: &mpsc::Sender<TranscribeJob>) -> Result<()> {
+    if app.prompt_pending.is_none() {
+        return Ok(());
+    }
+    let model = app.retry_model.clone();
+    if retranscribe(app, Some(&model), None, transcribe)? {
+        let name = models::model_name(&model);
+        app.notices.info(format!("Transcribing again with {name}"));
+    }
+    Ok(())
+}
+
+/// Put back the words of the last correction in the pending prompt, as
+/// Whisper heard them.
+fn undo_correction(app: &mut App) {
+    let Some(pending) = &app.prompt_pending else {
+        return;
+    };
+    if app.corrected.of(pending).is_empty() {
+        return;
+    }
+    let Some(correction) = app.corrected.revert_last() else {
+        return;
+    };
+    let text = app.corrected.text.clone();
+    // The pending prompt is always the newest transcript
+    if let Some(entry) = app.transcripts.last_mut() {
+        entry.text = text.clone();
+    }
+    if let Some(entry) = app.transcripts.last() {
+        app.save_transcript(entry);
+    }
+    app.prompt_pending = Some(text);
+    // Held for review, rather than sent while being looked at
+    app.send_deadline = None;
+    app.notices
+        .info(format!("Put back \"{}\"", correction.original));
+}
+
+/// The context block sent before a prompt: the focus, then the recent
+/// conversation when `[context]` asks for it, within its budget.
+fn prompt_context(app: &App) -> Option<String> {
+    app.context
+        .context_block(&app.focus, &app.transcripts, app.last_reply.as_deref())
+}
+
+/// The prompt `text` goes out as: the context block, if any, then the text.
+fn outgoing_prompt(app: &App, text: &str) -> String {
+    match prompt_context(app) {
+        Some(ctx) => format!("{}\n{}", ctx, text),
+        None => text.to_string(),
+    }
+}
+
+/// Queue `text` as a prompt for the session in front, reporting the outcome
+/// for transcript `transcript_id` as `PromptSent`. It goes out in `turn`,
+/// retried per `retry` from the send task.
+fn send_prompt_to_opencode(
+    opencode: Option<&SessionHandle>,
+    text: &str,
+    transcript_id: Option<u64>,
+    retry: RetryPolicy,
+    mut turn: SendTurn,
+    bus: &EventBus<AppMessage>,
+    tasks: &Supervisor,
+) {
+    let text = text.to_string();
+    let bus = bus.clone();
+    log(&format!(
+        "send_prompt: queuing prompt ({} chars)",
+        text.len()
+    ));
+    let Some(opencode) = opencode else {
+        log("send_prompt: ERROR not connected");
+        bus.publish(AppMessage::PromptSent {
+            transcript_id,
+            prompt: text,
+            result: Err("not connected".into()),
+        });
+        return;
+    };
+    // Asked now, so it goes to the session that is current at confirm time
+    let target = opencode.prompt_target();
+    let opencode = opencode.clone();
+    tasks.spawn("Prompt send", async move {
+        turn.wait().await;
+        let on_retry = |attempt| {
+            log(&format!("send_prompt: retry {attempt}/{}", retry.retries));
+            bus.publish(AppMessage::SendRetrying {
+                attempt,
+                of: retry.retries,
+            });
+        };
+        let sent = match target.await {
+            Ok(target) => session::send_with_retry(&opencode, target, &text, retry, on_retry).await,
+            Err(e) => Err(e),
+        };
+        let result = match sent {
+            Ok(session_id) => {
+                log(&format!("send_prompt: success (session {session_id})"));
+                Ok(session_id)
+            }
+            Err(e) => {
+                log(&format!("send_prompt: ERROR {e}"));
+                Err(e.to_string())
+            }
+        };
+        bus.publish(AppMessage::PromptSent {
+            transcript_id,
+            prompt: text,
+            result,
+        });
+        Ok(())
+    });
+}
+
+/// Background task: connect to OpenCode, establish session, listen for SSE events.
+async fn connect_opencode(
+    bus: EventBus<AppMessage>,
+    session_flags: Vec<String>,
+    directory: PathBuf,
+    client: SessionHandle,
+    mut heartbeats: Heartbeats,
+) -> Result<()> {
+
+    // Health check with retry
+    log("connect_opencode: starting health check loop");
+    loop {
+        match client.health_check().await {
+            Ok(true) => {
+                log("connect_opencode: health check passed");
+                break;
+            }
+            Ok(false) => {
+                log("connect_opencode: health check returned false, retrying...");
+                bus.publish(AppMessage::ConnectionChanged(
+                    ConnectionStatus::Reconnecting,
+                ));
+                tokio::time::sleep(Duration::from_secs(2)).await;
+            }
+            Err(e) => {
+                log(&format!(
+                    "connect_opencode: health check error: {e}, retrying..."
+                ));
+                bus.publish(AppMessage::ConnectionChanged(
+                    ConnectionStatus::Reconnecting,
+                ));
+                tokio::time::sleep(Duration::from_secs(2)).await;
+            }
+        }
+    }
+
+    // Session management
+    log("connect_opencode: resolving session");
+    let mut session_flags = session_flags.into_iter();
+    let session_id = if let Some(id) = session_flags.next() {
+        log(&format!("connect_opencode: using --session flag: {id}"));
+        client.set_session(id.clone());
+        bus.publish(AppMessage::SessionReady {
+            id: id.clone(),
+            slug: None,
+            directory: None,
+            activate: true,
+        });
+        id
+    } else {
+        // Try to reuse existing session for this directory
+        match client.list_sessions().await {
+            Ok(sessions) => {
+                log(&format!(
+                    "connect_opencode: found {} sessions",
+                    sessions.len()
+                ));
+                bus.publish(AppMessage::SessionsListed(sessions.clone()));
+                if let Some(s) = transport::directory_session(&sessions, &directory) {
+                    log(&format!(
+                        "connect_opencode: reusing session {} ({})",
+                        s.id,
+                        s.slug.as_deref().unwrap_or("?")
+                    ));
+                    client.set_session(s.id.clone());
+                    bus.publish(AppMessage::SessionReady {
+                        id: s.id.clone(),
+                        slug: s.slug.clone(),
+                        directory: Some(directory.clone()),
+                        activate: true,
+                    });
+                    s.id.clone()
+                } else {
+                    match client.create_session(Some(directory.clone())).await {
+                        Ok(session) => {
+                            bus.publish(AppMessage::SessionReady {
+                                id: session.id.clone(),
+                                slug: session.slug,
+                                directory: Some(directory.clone()),
+                                activate: true,
+                            });
+                            session.id
+                        }
+                        Err(e) => {
+                            bus.publish(AppMessage::ConnectionChanged(
+                                ConnectionStatus::Disconnected,
+                            ));
+                            return Err(anyhow!("Failed to create session: {e}"));
+                        }
+                    }
+                }
+            }
+            Err(_) => match client.create_session(Some(directory.clone())).await {
+                Ok(session) => {
+                    bus.publish(AppMessage::SessionReady {
+                        id: session.id.clone(),
+                        slug: session.slug,
+                        directory: Some(directory.clone()),
+                        activate: true,
+                    });
+                    session.id
+                }
+                Err(e) => {
+                    bus.publish(AppMessage::ConnectionChanged(
+                        ConnectionStatus::Disconnected,
+                    ));
+                    return Err(anyhow!("Failed to create session: {e}"));
+                }
+            },
+        }
+    };
+
+    log(&format!("connect_opencode: session {session_id} ready"));
+
+    // Further --session flags open in background tabs
+    for id in session_flags {
+        bus.publish(AppMessage::SessionReady {
+            id,
+            slug: None,
+            directory: None,
+            activate: false,
+        });
+    }
+
+    // SSE event loop with reconnection
+    log("connect_opencode: entering SSE loop");
+    loop {
+        match client.subscribe_events().await {
+            Ok(resp) => {
+                log("connect_opencode: SSE connected");
+                bus.publish(AppMessage::ConnectionChanged(ConnectionStatus::Connected));
+                heartbeats.connected(Instant::now());
+                if let Err(e) = stream_sse_events(resp, &bus, &mut heartbeats).await {
+                    log(&format!("connect_opencode: SSE stream ended: {e}"));
+                    bus.publish(AppMessage::ConnectionChanged(
+                        ConnectionStatus::Reconnecting,
+                    ));
+                }
+            }
+            Err(e) => {
+                log(&format!("connect_opencode: SSE connect failed: {e}"));
+                bus.publish(AppMessage::ConnectionChanged(
+                    ConnectionStatus::Reconnecting,
+                ));
+            }
+        }
+        log("connect_opencode: reconnecting in 2s...");
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+}
+
+/// Read SSE events from a streaming response and forward them, until it
+/// ends or stays silent for longer than `heartbeats` allow.
+async fn stream_sse_events(
+    mut resp: reqwest::Response,
+    bus: &EventBus<AppMessage>,
+    heartbeats: &mut Heartbeats,
+) -> Result<()> {
+    let mut buf = String::new();
+
+    // Use reqwest's chunk() method to read the streaming body piece by piece.
+    loop {
+        let timeout = heartbeats.timeout();
+        let chunk = tokio::time::timeout(timeout, resp.chunk()).await;
+
+        match chunk {
+            Ok(Ok(Some(bytes))) => {
+                buf.push_str(&String::from_utf8_lossy(&bytes));
+                // Process complete SSE events (terminated by \n\n)
+                while let Some(pos) = buf.find("\n\n") {
+                    let event_text = buf[..pos].to_string();
+                    buf = buf[pos + 2..].to_string();
+
+                    for line in extract_sse_data_lines(&event_text) {
+                        let event = parse_sse_event(line);
+                        bus.publish(AppMessage::RawEvent {
+                            data: line.to_string(),
+                            handled: event.is_some(),
+                        });
+                        if matches!(event, Some(ServerEvent::Heartbeat)) {
+                            heartbeats.heartbeat(Instant::now());
+                        }
+                        if let Some(event) = event {
+                            bus.publish(AppMessage::ServerEvent(event));
+                        }
+                    }
+                }
+            }
+            Ok(Ok(None)) => return Err(anyhow!("stream ended")),
+            Ok(Err(e)) => return Err(anyhow!("stream error: {}", e)),
+            Err(_) => {
+                return Err(anyhow!(
+                    "no data for {}s (heartbeats every {})",
+                    timeout.as_secs(),
+                    heartbeats
+                        .interval()
+                        .map_or("?".to_string(), |i| format!("{}s", i.as_secs()))
+                ));
+            }
+        }
+    }
+}
+
+/// Cancel the selected recording in the list of those waiting to be
+/// transcribed. Dictation segments stay, since the document needs them all.
+fn cancel_queued(app: &mut App) {
+    let Some(job) = app.queue.waiting().nth(app.queue_cursor) else {
+        return;
+    };
+    if job.kind == JobKind::Dictation {
+        app.notices
+            .warn("Dictation segments can't be cancelled \u{2014} stop the dictation instead");
+        return;
+    }
+    let id = job.ticket.id;
+    // The thread may have taken it up meanwhile
+    let Some(job) = app.queue.cancel(id) else {
+        app.notices.info("Already transcribing");
+        return;
+    };
+    log(&format!("tui: cancelled transcription job {id}"));
+    app.transcribing = app.transcribing.saturating_sub(1);
+    app.settle_state();
+    let last = app.queue.waiting().count().saturating_sub(1);
+    app.queue_cursor = app.queue_cursor.min(last);
+    app.notices.info(format!(
+        "{} of {:.1}s cancelled",
+        job.kind.label(),
+        job.audio.as_secs_f32()
+    ));
+}
+
+/// Stop the current recording and throw it away without transcribing it.
+fn abort_recording(app: &mut App, audio: &AudioCapture) {
+    let samples = audio.stop_recording();
+    log(&format!(
+        "tui: recording aborted, {} samples discarded",
+        samples.len()
+    ));
+    app.bus.publish(AppMessage::RecordingStopped);
+    app.recording_started = None;
+    app.stop_countdown = None;
+    app.cue(Cue::RecordStop);
+    // Earlier recordings may still be transcribing
+    app.state = RecordingState::Processing;
+    app.settle_state();
+    match app.dictation.as_mut().filter(|d| !d.is_finishing()) {
+        Some(dictation) => {
+            dictation.finish();
+            app.notices
+                .info("Dictation stopped, last segment discarded");
+            settle_dictation(app);
+        }
+        None => app.notices.info("Recording discarded"),
+    }
+}
+
+/// Space from the keyboard. A recording stops once the double-tap window
+/// has passed without a second press, which discards it instead.
+fn space_pressed(
+    app: &mut App,
+    audio: &AudioCapture,
+    transcribe: &mpsc::Sender<TranscribeJob>,
+    now: Instant,
+) -> Result<()> {
+    let window = app.autostop.double_tap();
+    match (app.state, window) {
+        (RecordingState::Recording, Some(window)) => match app.stop_tapped.take() {
+            Some(at) if now.duration_since(at) < window => {
+                log("tui: double-tap on Space");
+                abort_recording(app, audio);
+            }
+            _ => app.stop_tapped = Some(now),
+        },
+        _ => handle_space(app, audio, transcribe)?,
+    }
+    Ok(())
+}
+
+/// Stop the recording once Space was pressed a double-tap window ago.
+/// Returns whether it was stopped.
+fn stop_tap_due(
+    app: &mut App,
+    audio: &AudioCapture,
+    transcribe: &mpsc::Sender<TranscribeJob>,
+    now: Instant,
+) -> Result<bool> {
+    let Some(at) = app.stop_tapped else {
+        return Ok(false);
+    };
+    let window = app.autostop.double_tap().unwrap_or_default();
+    if now.duration_since(at) < window {
+        return Ok(false);
+    }
+    app.stop_tapped = None;
+    if app.state != RecordingState::Recording {
+        // Stopped some other way meanwhile
+        return Ok(false);
+    }
+    handle_space(app, audio, transcribe)?;
+    Ok(true)
+}
+
+/// Handle spacebar press: toggle between recording and stopping.
+fn handle_space(
+    app: &mut App,
+    audio: &AudioCapture,
+    transcribe: &mpsc::Sender<TranscribeJob>,
+) -> Result<()> {
+    match app.state {
+        // Earlier recordings keep transcribing while the next one is made
+        RecordingState::Idle | RecordingState::Processing => {
+            app.start_recording(audio);
+        }
+        RecordingState::Recording => {
+            let samples = audio.stop_recording();
+            app.bus.publish(AppMessage::RecordingStopped);
+            app.recording_started = None;
+            app.stop_countdown = None;
+            app.cue(Cue::RecordStop);
+
+            app.state = RecordingState::Processing;
+            if let Some(dictation) = app.dictation.as_mut().filter(|d| !d.is_finishing()) {
+                // What's left of a dictation is its last segment
+                dictation.finish();
+                let join = dictation.segmenter.join();
+                if dictation.segmenter.has_speech() && !samples.is_empty() {
+                    send_segment(app, samples, join, transcribe)?;
+                }
+                app.settle_state();
+                settle_dictation(app);
+                return Ok(());
+            }
+            if samples.is_empty() {
+                app.notices.warn("No audio captured");
+                app.settle_state();
+                return Ok(());
+            }
+            // An answer leaves the prompt's waveform up
+            if !app.answering {
+                app.utterance = Some(viz::compute_rms_windows(&samples, UTTERANCE_WINDOWS));
+                app.last_recording = Some(samples.clone());
+            }
+
+            let kind = if app.answering {
+                JobKind::Answer
+            } else {
+                JobKind::Prompt
+            };
+            let job = TranscribeJob {
+                ticket: app.queue_ticket(kind, &samples),
+                samples,
+                language: app.language().code,
+                kind,
+                model: None,
+                prompt: app.whisper_prompt().filter(|_| !app.answering),
+            };
+            if transcribe.send(job).is_err() {
+                return Err(anyhow!("Transcription thread stopped"));
+            }
+            app.transcribing += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Carry out a command from the control socket, as the keys would.
+fn run_control(
+    app: &mut App,
+    command: &control::Command,
+    audio: &AudioCapture,
+    transcribe: &mpsc::Sender<TranscribeJob>,
+) -> Result<Response> {
+    log(&format!("control: {command:?}"));
+    let recording = app.state == RecordingState::Recording;
+    let response = match command {
+        control::Command::Record { action } => match (action, recording) {
+            (RecordAction::Start, true) => Response::error("Already recording"),
+            (RecordAction::Stop, false) => Response::error("Not recording"),
+            _ => {
+                handle_space(app, audio, transcribe)?;
+                Response::ok()
+            }
+        },
+        control::Command::Send { text: Some(_) } if app.prompt_pending.is_some() => {
+            Response::error("A prompt is pending \u{2014} send or discard it first")
+        }
+        control::Command::Send { text } => {
+            match text.as_deref().map(str::trim) {
+                Some("") => return Ok(Response::error("Nothing to send")),
+                Some(text) => propose_prompt(app, text.to_string()),
+                None if app.prompt_pending.is_none() => {
+                    return Ok(Response::error("No prompt pending"));
+                }
+                None => {}
+            }
+            if app.prompt_pending.is_some() {
+                confirm_prompt(app);
+            }
+            // Refused: the toast says why
+            match (&app.prompt_pending, app.notices.toast()) {
+                (None, _) => Response::ok(),
+                (Some(_), Some(toast)) => Response::error(toast.message.clone()),
+                (Some(_), None) => Response::error("Not sent"),
+            }
+        }
+        control::Command::Discard if app.prompt_pending.is_none() => {
+            Response::error("No prompt pending")
+        }
+        control::Command::Discard => {
+            discard_prompt(app);
+            Response::ok()
+        }
+        control::Command::Focus {
+            action: FocusAction::List,
+        } => {
+            let pointer = app.focus.pointer();
+            let entries = app.focus.entries().iter().enumerate().map(|(i, entry)| {
+                serde_json::json!({
+                    "type": entry.type_name(),
+                    "value": entry.value_str(),
+                    "current": i == pointer,
+                })
+            });
+            Response::with(entries.collect())
+        }
+        control::Command::Retranscribe { model, language } => {
+            if retranscribe(app, model.as_deref(), language.as_deref(), transcribe)? {
+                Response::ok()
+            } else {
+                // Refused: the toast says why
+                let toast = app.notices.toast();
+                Response::error(toast.map_or("Not transcribed".into(), |t| t.message.clone()))
+            }
+        }
+        control::Command::Status => {
+            let state = match app.state {
+                RecordingState::Idle => "idle",
+                RecordingState::Recording => "recording",
+                RecordingState::Processing => "transcribing",
+            };
+            Response::with(serde_json::json!({
+                "state": state,
+                "session": app.session_id,
+                "slug": app.session_slug,
+                "busy": app.opencode_busy,
+                "pending": app.prompt_pending,
+            }))
+        }
+    };
+    Ok(response)
+}
+
+/// Transcribe the last recording again, with `model` (a name or path) and
+/// `language` instead of the current ones when given. The new transcript
+/// replaces a pending prompt. Returns whether the recording was queued.
+fn retranscribe(
+    app: &mut App,
+    model: Option<&str>,
+    language: Option<&str>,
+    transcribe: &mpsc::Sender<TranscribeJob>,
+) -> Result<bool> {
+    if app.state == RecordingState::Recording {
+        app.notices.warn("Stop recording first");
+        return Ok(false);
+    }
+    let Some(samples) = app.last_recording.clone() else {
+        app.notices.warn("No recording to transcribe again");
+        return Ok(false);
+    };
+    let language = match language.map(language::parse).transpose() {
+        Ok(language) => language.unwrap_or_else(|| app.language()),
+        Err(e) => {
+            app.notices.warn(e.to_string());
+            return Ok(false);
+        }
+    };
+    let model = model.map(|m| models::resolve(&app.models_dir, m));
+    if let Some(path) = model.as_ref().filter(|path| !path.exists()) {
+        app.notices.warn(format!(
+            "No model at {} \u{2014} get one with `conch models download`",
+            path.display()
+        ));
+        return Ok(false);
+    }
+    let model_name = model.as_deref().map(Path::display);
+    log(&format!(
+        "tui: transcribing the last recording again (model {}, language {})",
+        model_name.map_or("unchanged".into(), |m| m.to_string()),
+        language.code
+    ));
+    discard_prompt(app);
+    app.notices.info("Transcribing the last recording again");
+    app.utterance = Some(viz::compute_rms_windows(&samples, UTTERANCE_WINDOWS));
+    let job = TranscribeJob {
+        ticket: app.queue_ticket(JobKind::Prompt, &samples),
+        samples,
+        language: language.code,
+        kind: JobKind::Prompt,
+        model,
+        prompt: app.whisper_prompt(),
+    };
+    if transcribe.send(job).is_err() {
+        return Err(anyhow!("Transcription thread stopped"));
+    }
+    app.state = RecordingState::Processing;
+    app.transcribing += 1;
+    Ok(true)
+}
+
+/// Send a segment of the dictation to be transcribed.
+fn send_segment(
+    app: &mut App,
+    samples: Vec<f32>,
+    join: Join,
+    transcribe: &mpsc::Sender<TranscribeJob>,
+) -> Result<()> {
+    let job = TranscribeJob {
+        ticket: app.queue_ticket(JobKind::Dictation, &samples),
+        samples,
+        language: app.language().code,
+        kind: JobKind::Dictation,
+        model: None,
+        prompt: app.whisper_prompt(),
+    };
+    if transcribe.send(job).is_err() {
+        return Err(anyhow!("Transcription thread stopped"));
+    }
+    app.transcribing += 1;
+    if let Some(dictation) = &mut app.dictation {
+        dictation.segment_sent(join);
+    }
+    Ok(())
+}
+
+/// Once a finished dictation's last segment is in, make the document the
+/// pending prompt. Like spelled text, it is never auto-sent.
+fn settle_dictation(app: &mut App) {
+    if !app.dictation.as_ref().is_some_and(Dictation::is_done) {
+        return;
+    }
+    let Some(dictation) = app.dictation.take() else {
+        return;
+    };
+    log(&format!(
+        "tui: dictation finished, {} segments",
+        dictation.segments()
+    ));
+    let text = dictation.document();
+    if text.is_empty() {
+        app.notices.warn("Nothing was dictated");
+        return;
+    }
+    let entry = HistoryEntry::new(text.clone(), app.session_id.clone());
+    app.save_transcript(&entry);
+    app.transcripts.push(entry);
+    app.prompt_pending = Some(text);
+    app.cue(Cue::TranscriptReady);
+    app.send_deadline = None;
+    app.review = None;
+}
+
+/// What a recording is for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum JobKind {
+    Prompt,
+    /// An answer to the pending prompt.
+    Answer,
+    /// A segment of a dictation.
+    Dictation,
+}
+
+impl JobKind {
+    /// How the recording is listed in the queue.
+    fn label(self) -> &'static str {
+        match self {
+            JobKind::Prompt => "Prompt",
+            JobKind::Answer => "Answer",
+            JobKind::Dictation => "Dictation segment",
+        }
+    }
+}
+
+/// A finished recording waiting to be transcribed.
+struct TranscribeJob {
+    /// Its place in [`App::queue`]; skipped when cancelled there.
+    ticket: Ticket,
+    samples: Vec<f32>,
+    /// Language code of the session it was dictated for.
+    language: &'static str,
+    kind: JobKind,
+    /// Model to use instead of the loaded one, for a re-transcription.
+    model: Option<PathBuf>,
+    /// Whisper's initial prompt: the project vocabulary.
+    prompt: Option<String>,
+}
+
+/// Name of the transcription thread's task, in reports of it.
+const TRANSCRIPTION: &str = "Transcription";
+
+/// Start the thread that transcribes finished recordings. Recordings queue
+/// up while an earlier one is transcribing, and results are published in
+/// the order the recordings were made. The thread exits once the returned
+/// sender is dropped; if it panics, another takes the recordings still
+/// queued.
+fn spawn_transcriber(
+    transcriber: Arc<Transcriber>,
+    sample_rate: u32,
+    bus: &EventBus<AppMessage>,
+    tasks: &Supervisor,
+) -> mpsc::Sender<TranscribeJob> {
+    let (tx, rx) = mpsc::channel::<TranscribeJob>();
+    let rx = Arc::new(Mutex::new(rx));
+    let bus = bus.clone();
+    tasks.supervise_thread(TRANSCRIPTION, move || {
+        let (rx, bus, transcriber) = (Arc::clone(&rx), bus.clone(), Arc::clone(&transcriber));
+        // Named so the resources view can tell its threads, and the ones
+        // Whisper starts from it, apart
+        let thread = std::thread::Builder::new().name("whisper".into());
+        thread.spawn(move || transcribe_jobs(&transcriber, sample_rate, &rx, &bus))
+    });
+    tx
+}
+
+/// Transcribe the jobs from `rx` until its sender is dropped.
+fn transcribe_jobs(
+    transcriber: &Transcriber,
+    sample_rate: u32,
+    rx: &Mutex<mpsc::Receiver<TranscribeJob>>,
+    bus: &EventBus<AppMessage>,
+) {
+    // The model a re-transcription last asked for, kept loaded
+    let mut other = None;
+    loop {
+        // Not held while transcribing, so a panic doesn't poison it
+        let Ok(job) = rx.lock().unwrap().recv() else {
+            return;
+        };
+        let Some(_running) = job.ticket.start() else {
+            log(&format!("stt: job {} was cancelled", job.ticket.id));
+            continue;
+        };
+        let transcriber = match &job.model {
+            Some(path) => match load_model(&mut other, path, transcriber.gate()) {
+                Ok(transcriber) => transcriber,
+                Err(e) => {
+                    bus.publish(AppMessage::TranscriptReady(Err(e.to_string())));
+                    continue;
+                }
+            },
+            None => transcriber,
+        };
+        if let JobKind::Answer = job.kind {
+            let result = transcriber
+                .transcribe_answer(&job.samples, sample_rate, job.language, answer::VOCABULARY)
+                .map_err(|e| e.to_string());
+            bus.publish(AppMessage::AnswerReady(result));
+            continue;
+        }
+        if !transcriber.gate().hears_speech(&job.samples, sample_rate) {
+            // Not a transcription: it would make Whisper look instant
+            log(&format!("stt: no speech in job {}, skipped", job.ticket.id));
+            let empty = Transcript {
+                text: String::new(),
+                confidence: 1.0,
+            };
+            bus.publish(match job.kind {
+                JobKind::Dictation => AppMessage::DictationReady(Ok(empty)),
+                _ => AppMessage::TranscriptReady(Ok(empty)),
+            });
+            continue;
+        }
+        let started = Instant::now();
+        let result = match &job.prompt {
+            Some(prompt) => {
+                transcriber.transcribe_primed(&job.samples, sample_rate, job.language, prompt)
+            }
+            None => transcriber.transcribe_in(&job.samples, sample_rate, job.language),
+        };
+        let audio = job.samples.len() as f32 / sample_rate.max(1) as f32;
+        bus.publish(AppMessage::Transcribed {
+            audio: Duration::from_secs_f32(audio),
+            took: started.elapsed(),
+        });
+        let result = result.map_err(|e| e.to_string());
+        bus.publish(match job.kind {
+            JobKind::Dictation => AppMessage::DictationReady(result),
+            _ => AppMessage::TranscriptReady(result),
+        });
+    }
+}
+
+/// The model at `path`, loaded into `slot` in place of the one there unless
+/// it's the same, judging silence with `gate`.
+fn load_model<'a>(
+    slot: &'a mut Option<(PathBuf, Transcriber)>,
+    path: &Path,
+    gate: SpeechGate,
+) -> Result<&'a Transcriber> {
+    let (_, transcriber) = match slot.take().filter(|(loaded, _)| loaded == path) {
+        Some(loaded) => slot.insert(loaded),
+        None => {
+            log(&format!("stt: loading {}", path.display()));
+            let transcriber = Transcriber::new(&path.to_string_lossy())?.with_gate(gate);
+            slot.insert((path.to_path_buf(), transcriber))
+        }
+    };
+    Ok(transcriber)
+}
+
+/// The latest audio of a recording, to caption.
+struct CaptionJob {
+    /// Number of the recording, from [`Captions::start`].
+    recording: u64,
+    samples: Vec<f32>,
+    language: &'static str,
+}
+
+/// Start the thread that transcribes the latest audio for the caption
+/// strip, beside the one transcribing finished recordings. A job sent while
+/// it is busy is dropped rather than queued, so captions never fall behind.
+fn spawn_captioner(
+    transcriber: Arc<Transcriber>,
+    sample_rate: u32,
+    bus: &EventBus<AppMessage>,
+    tasks: &Supervisor,
+) -> mpsc::SyncSender<CaptionJob> {
+    let (tx, rx) = mpsc::sync_channel::<CaptionJob>(0);
+    let rx = Arc::new(Mutex::new(rx));
+    let bus = bus.clone();
+    tasks.supervise_thread("Live captions", move || {
+        let (rx, bus, transcriber) = (Arc::clone(&rx), bus.clone(), Arc::clone(&transcriber));
+        let thread = std::thread::Builder::new().name("captions".into());
+        thread.spawn(move || {
+            loop {
+                let Ok(job) = rx.lock().unwrap().recv() else {
+                    return;
+                };
+                // A missed caption is replaced by the next one
+                if let Ok(transcript) =
+                    transcriber.transcribe_in(&job.samples, sample_rate, job.language)
+                {
+                    bus.publish(AppMessage::Caption {
+                        recording: job.recording,
+                        text: transcript.text,
+                    });
+                }
+            }
+        })
+    });
+    tx
+}
+
+/// Status color for a notification.
+fn severity_color(theme: &Theme, severity: Severity) -> Color {
+    match severity {
+        Severity::Info => theme.info,
+        Severity::Warning => theme.warning,
+        Severity::Error => theme.error,
+    }
+}
+
+/// Elapsed time and recording buffer usage, right-aligned in the status
+/// title. Turns yellow as the buffer fills and red once it has wrapped, or
+/// while the mic is `dead`.
+fn recording_meter(
+    theme: &Theme,
+    glyphs: &Glyphs,
+    elapsed: Duration,
+    usage: f32,
+    dead: bool,
+) -> Line<'static> {
+    let secs = elapsed.as_secs();
+    let time = format!("{}:{:02}", secs / 60, secs % 60);
+    let dot = glyphs.separator;
+    let (text, color) = if dead {
+        let warning = glyphs.warning;
+        (format!(" {time} {dot} {warning} mic silent "), theme.error)
+    } else if usage >= 1.0 {
+        (
+            format!(" {time} {dot} buffer full, start lost "),
+            theme.error,
+        )
+    } else {
+        let percent = (usage * 100.0) as u32;
+        let color = if usage >= 0.8 {
+            theme.warning
+        } else {
+            theme.info
+        };
+        (format!(" {time} {dot} buffer {percent}% "), color)
+    };
+    Line::from(Span::styled(text, Style::default().fg(color))).right_aligned()
+}
+
+/// Render the command palette: the filter line, then the matching items.
+fn render_palette(f: &mut ratatui::Frame, app: &App, palette: &Palette, area: Rect) {
+    let theme = &app.theme;
+    let (before, after) = palette.query.split_at_cursor();
+    let mut lines = vec![Line::from(vec![
+        Span::styled("  > ", Style::default().fg(theme.accent)),
+        Span::raw(before.to_string()),
+        Span::styled(
+            after.chars().next().map_or(" ".to_string(), String::from),
+            Style::default().add_modifier(Modifier::REVERSED),
+        ),
+        Span::raw(after.chars().skip(1).collect::<String>()),
+    ])];
+    let items = palette.filtered();
+    if items.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  No matching snippets (add them under [snippets] in conch.toml)",
+            Style::default().fg(theme.muted),
+        )));
+    }
+    // Keep the selection in view when the list is taller than the pane
+    let visible = area.height.saturating_sub(3) as usize;
+    let selected = palette.selected();
+    let skip = (selected + 1).saturating_sub(visible.max(1));
+    for (i, item) in items.iter().enumerate().skip(skip).take(visible) {
+        let (indicator, style) = if i == selected {
+            (
+                format!("{} ", app.glyphs.pointer),
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            ("  ".into(), Style::default().fg(theme.text))
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}{}", indicator, item.label), style),
+            Span::styled(
+                format!("  {}", item.detail),
+                Style::default().fg(theme.muted),
+            ),
+        ]));
+    }
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Command Palette ")
+            .borders(Borders::ALL),
+    );
+    f.render_widget(widget, area);
+}
+
+/// Render the tool-activity feed, newest at the bottom.
+fn render_activity(f: &mut ratatui::Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let title = match app.activity.in_flight() {
+        0 => " Activity ".to_string(),
+        n => format!(" Activity ({} running) ", n),
+    };
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let visible = block.inner(area).height as usize;
+    let lines: Vec<Line> = if app.activity.is_empty() {
+        vec![Line::from(Span::styled(
+            "  No tool activity yet",
+            Style::default().fg(theme.muted),
+        ))]
+    } else {
+        // Only the latest edit's diff is shown, under its line
+        let diffed = app.activity.entries().rposition(|a| !a.diff.is_empty());
+        let mut lines = Vec::new();
+        for (i, activity) in app.activity.entries().enumerate() {
+            let color = match activity.status {
+                ActivityStatus::Pending => theme.muted,
+                ActivityStatus::Running => theme.warning,
+                ActivityStatus::Completed => theme.success,
+                ActivityStatus::Failed => theme.error,
+            };
+            // MCP and custom tools stand out from OpenCode's own
+            let label_style = if activity.custom {
+                Style::default().fg(theme.special)
+            } else {
+                Style::default().fg(color)
+            };
+            let target = if activity.target.is_empty() {
+                String::new()
+            } else {
+                format!(" {}", activity.target)
+            };
+            lines.push(Line::from(vec![
+                Span::raw("  "),
+                Span::styled(activity.label.clone(), label_style),
+                Span::styled(
+                    format!("{}{}", target, activity.status_suffix()),
+                    Style::default().fg(color),
+                ),
+            ]));
+            if diffed != Some(i) {
+                continue;
+            }
+            for line in &activity.diff {
+                let color = match line.kind {
+                    DiffKind::Hunk => theme.accent,
+                    DiffKind::Context => theme.muted,
+                    DiffKind::Added => theme.success,
+                    DiffKind::Removed => theme.error,
+                };
+                lines.push(Line::from(Span::styled(
+                    format!("    {}", line.prefixed()),
+                    Style::default().fg(color),
+                )));
+            }
+        }
+        let skip = lines.len().saturating_sub(visible);
+        lines.split_off(skip)
+    };
+    // No wrapping: one line per call keeps the feed's scroll position exact
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// The conversation: prompts sent, the tool calls made for them and the
+/// replies, newest at the bottom unless an entry is picked. `below` is the
+/// prompt being typed or waiting to be sent, if any.
+fn render_conversation(f: &mut ratatui::Frame, app: &App, area: Rect, below: Option<Line>) {
+    let (theme, glyphs) = (&app.theme, &app.glyphs);
+    let block = Block::default()
+        .title(" Conversation ")
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    let rows = (inner.height as usize).saturating_sub(below.is_some() as usize);
+    let width = (inner.width as usize).saturating_sub(4);
+    let mut lines: Vec<Line> = Vec::new();
+    // Where the picked entry's lines end, to keep it in view
+    let mut end = None;
+    for (i, entry) in app.timeline.entries().enumerate() {
+        let (text, hidden) = entry.lines(width);
+        let first = lines.len();
+        match &entry.turn {
+            Turn::Prompt { transcript_id, .. } => {
+                let style = Style::default().fg(theme.text).add_modifier(Modifier::BOLD);
+                for (n, line) in text.iter().enumerate() {
+                    let lead = if n == 0 { glyphs.pointer } else { " " };
+                    lines.push(Line::from(Span::styled(format!("{lead} {line}"), style)));
+                }
+                let outcome = app
+                    .transcripts
+                    .iter()
+                    .find(|t| Some(t.id) == *transcript_id)
+                    .and_then(|t| transcript_outcome(t, theme, glyphs));
+                if let (Some(outcome), Some(last)) = (outcome, lines.last_mut()) {
+                    last.spans.push(outcome);
+                }
+            }
+            Turn::Tool(activity) => {
+                let color = match activity.status {
+                    ActivityStatus::Pending => theme.muted,
+                    ActivityStatus::Running => theme.warning,
+                    ActivityStatus::Completed => theme.success,
+                    ActivityStatus::Failed => theme.error,
+                };
+                lines.extend(text.iter().map(|line| {
+                    Line::from(Span::styled(
+                        format!("  {line}"),
+                        Style::default().fg(color),
+                    ))
+                }));
+            }
+            Turn::Reply(_) => {
+                lines.extend(text.iter().map(|line| {
+                    Line::from(Span::styled(
+                        format!("    {line}"),
+                        Style::default().fg(theme.text),
+                    ))
+                }));
+            }
+        }
+        if hidden > 0 {
+            lines.push(Line::from(Span::styled(
+                format!("    +{hidden} more lines [z]"),
+                Style::default().fg(theme.muted),
+            )));
+        }
+        if app.timeline.selected() == Some(i) {
+            if let Some(line) = lines.get_mut(first) {
+                line.spans = std::mem::take(&mut line.spans)
+                    .into_iter()
+                    .map(|span| span.patch_style(Style::default().add_modifier(Modifier::REVERSED)))
+                    .collect();
+            }
+            end = Some(lines.len());
+        }
+    }
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  Nothing sent yet",
+            Style::default().fg(theme.muted),
+        )));
+    }
+    let end = end.unwrap_or(lines.len());
+    let mut shown: Vec<Line> = lines.drain(end.saturating_sub(rows)..end).collect();
+    shown.extend(below);
+    f.render_widget(Paragraph::new(shown).block(block), area);
+}
+
+/// Render the notification history, newest first, in place of the focus stack.
+fn render_notices(f: &mut ratatui::Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let now = Instant::now();
+    let lines: Vec<Line> = if app.notices.history().len() == 0 {
+        vec![Line::from(Span::styled(
+            "  No notifications yet",
+            Style::default().fg(theme.muted),
+        ))]
+    } else {
+        app.notices
+            .history()
+            .rev()
+            .skip(app.notice_scroll)
+            .map(|notice| {
+                Line::from(vec![
+                    Span::styled(
+                        format!(
+                            "  {:>4} ",
+                            notify::format_age(now.duration_since(notice.at))
+                        ),
+                        Style::default().fg(theme.muted),
+                    ),
+                    Span::styled(
+                        format!("{:<6}", notice.severity.label()),
+                        Style::default()
+                            .fg(severity_color(theme, notice.severity))
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(notice.message.clone(), Style::default().fg(theme.text)),
+                ])
+            })
+            .collect()
+    };
+    let title = format!(" Notifications ({}) ", app.notices.history().len());
+    let widget = Paragraph::new(lines)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .wrap(Wrap { trim: false });
+    f.render_widget(widget, area);
+}
+
+/// Recordings waiting to be transcribed, oldest first, to cancel one.
+fn render_queue(f: &mut ratatui::Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let lines: Vec<Line> = if app.queue.waiting().next().is_none() {
+        vec![Line::from(Span::styled(
+            "  Nothing waiting to be transcribed",
+            Style::default().fg(theme.muted),
+        ))]
+    } else {
+        app.queue
+            .waiting()
+            .enumerate()
+            .map(|(i, job)| {
+                let selected = i == app.queue_cursor;
+                let pointer = if selected {
+                    format!("{} ", app.glyphs.pointer)
+                } else {
+                    "  ".to_string()
+                };
+                let style = if selected {
+                    Style::default()
+                        .fg(theme.accent)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(theme.text)
+                };
+                Line::from(Span::styled(
+                    format!(
+                        "{pointer}{}. {} {} {:.1}s",
+                        i + 1,
+                        job.kind.label(),
+                        app.glyphs.dash,
+                        job.audio.as_secs_f32()
+                    ),
+                    style,
+                ))
+            })
+            .collect()
+    };
+    let title = format!(" Transcription Queue ({}) ", app.queue.waiting().count());
+    let widget = Paragraph::new(lines)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .wrap(Wrap { trim: false });
+    f.render_widget(widget, area);
+}
+
+/// conch's own CPU and memory use, and what each part of it costs.
+fn render_resources(f: &mut ratatui::Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let muted = Style::default().fg(theme.muted);
+    let text = Style::default().fg(theme.text);
+    let mut lines = Vec::new();
+    match app.resources.usage() {
+        Some(usage) => {
+            lines.push(Line::from(vec![
+                Span::styled("  CPU ", muted),
+                Span::styled(
+                    format!("{:5.1}%", usage.cpu),
+                    Style::default()
+                        .fg(theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(" of a core   Memory ", muted),
+                Span::styled(
+                    resources::format_mb(usage.rss_bytes),
+                    Style::default()
+                        .fg(theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(format!("   {:.1} frames/s", usage.frames_per_sec), muted),
+            ]));
+            lines.extend(transcription_speed(app));
+            lines.push(Line::from(""));
+            for part in &usage.parts {
+                let threads = match part.threads {
+                    0 => String::new(),
+                    1 => "  1 thread".to_string(),
+                    n => format!("  {n} threads"),
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(format!("  {:5.1}%  ", part.cpu), text),
+                    Span::styled(part.name.clone(), text),
+                    Span::styled(threads, muted),
+                ]));
+            }
+        }
+        None => {
+            let note = if cfg!(target_os = "linux") {
+                "  Measuring\u{2026}"
+            } else {
+                "  Resource use is only measured on Linux"
+            };
+            lines.push(Line::from(Span::styled(note, muted)));
+            lines.extend(transcription_speed(app));
+        }
+    }
+    let widget = Paragraph::new(lines)
+        .block(Block::default().title(" Resources ").borders(Borders::ALL))
+        .wrap(Wrap { trim: false });
+    f.render_widget(widget, area);
+}
 
-    // Store session ID for the prompt sender
-    log(&format!(
-        "connect_opencode: session ready, storing ID for prompt sender"
-    ));
-    *OPENCODE_SESSION_ID.lock().unwrap() = Some(session_id);
+/// The session in front summed up: prompts, words, average latencies, then
+/// tool calls by type as bars and the files changed.
+fn render_stats(f: &mut ratatui::Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let muted = Style::default().fg(theme.muted);
+    let value = Style::default()
+        .fg(theme.accent)
+        .add_modifier(Modifier::BOLD);
+    let summary = app.stats.summary(&app.transcripts);
+    let average = |d: Option<Duration>| match d {
+        Some(d) => format!("{:.1}s", d.as_secs_f32()),
+        None => app.glyphs.dash.to_string(),
+    };
+    let rows = [
+        ("Prompts sent", summary.prompts.to_string()),
+        ("Words dictated", summary.words.to_string()),
+        ("Transcription", average(summary.transcription)),
+        ("Response time", average(summary.response)),
+    ];
+    let mut lines: Vec<Line> = rows
+        .into_iter()
+        .map(|(label, n)| {
+            Line::from(vec![
+                Span::styled(format!("  {label:<16}"), muted),
+                Span::styled(n, value),
+            ])
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!("  Tool calls ({})", summary.calls()),
+        Style::default().fg(theme.text),
+    )));
+    let most = summary.tools.first().map_or(1, |(_, n)| *n);
+    let name_width = summary
+        .tools
+        .iter()
+        .map(|(t, _)| t.len())
+        .max()
+        .unwrap_or(0);
+    let bar_width = (area.width as usize).saturating_sub(name_width + 14).max(1);
+    let block = if app.glyphs.charset == Charset::Ascii {
+        "#"
+    } else {
+        "\u{2588}"
+    };
+    for (tool, n) in &summary.tools {
+        let bar = (n * bar_width).div_ceil(most);
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {tool:<name_width$} {n:>4} "), muted),
+            Span::styled(block.repeat(bar), Style::default().fg(theme.accent)),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!("  Files touched ({})", summary.files.len()),
+        Style::default().fg(theme.text),
+    )));
+    lines.extend(
+        summary
+            .files
+            .iter()
+            .map(|path| Line::from(Span::styled(format!("  {path}"), muted))),
+    );
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Session Stats ")
+            .borders(Borders::ALL),
+    );
+    f.render_widget(widget, area);
+}
 
-    // SSE event loop with reconnection
-    log("connect_opencode: entering SSE loop");
-    loop {
-        match client.subscribe_events().await {
-            Ok(resp) => {
-                log("connect_opencode: SSE connected");
-                let _ = tx.send(AppMessage::ConnectionChanged(ConnectionStatus::Connected));
-                if let Err(e) = stream_sse_events(resp, &tx).await {
-                    log(&format!("connect_opencode: SSE stream ended: {e}"));
-                    let _ = tx.send(AppMessage::ConnectionChanged(
-                        ConnectionStatus::Reconnecting,
-                    ));
-                }
+/// The request the pending prompt will be sent as: the endpoint, the JSON
+/// body with the context block and agent, and the words fixed in it.
+fn render_payload(f: &mut ratatui::Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let muted = Style::default().fg(theme.muted);
+    let mut lines = Vec::new();
+    match &app.prompt_pending {
+        Some(text) => {
+            let endpoint = if app.detached {
+                "  No session to send to".to_string()
+            } else {
+                // Before the first session is created, its ID isn't known
+                let id = app.session_id.as_deref().unwrap_or("<new session>");
+                format!("  POST {}", transport::prompt_path(id))
+            };
+            lines.push(Line::from(Span::styled(endpoint, muted)));
+            let body = transport::prompt_body(&outgoing_prompt(app, text), app.agent.as_deref());
+            let json = serde_json::to_string_pretty(&body).unwrap_or_default();
+            let body_style = Style::default().fg(theme.text);
+            lines.extend(
+                json.lines()
+                    .map(|l| Line::from(Span::styled(format!("  {l}"), body_style))),
+            );
+            let corrections = app.corrected.of(text);
+            if !corrections.is_empty() {
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled("  Fixed", muted)));
             }
-            Err(e) => {
-                log(&format!("connect_opencode: SSE connect failed: {e}"));
-                let _ = tx.send(AppMessage::ConnectionChanged(
-                    ConnectionStatus::Reconnecting,
-                ));
+            for correction in corrections {
+                lines.push(Line::from(vec![
+                    Span::raw("  "),
+                    Span::styled(
+                        correction.original.clone(),
+                        muted.add_modifier(Modifier::CROSSED_OUT),
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        text[correction.range.clone()].to_string(),
+                        Style::default().fg(theme.special),
+                    ),
+                ]));
             }
         }
-        log("connect_opencode: reconnecting in 2s...");
-        tokio::time::sleep(Duration::from_secs(2)).await;
+        None => lines.push(Line::from(Span::styled(
+            "  No prompt pending \u{2014} the next one shows here before it's sent",
+            muted,
+        ))),
     }
+    let widget = Paragraph::new(lines)
+        .block(Block::default().title(" Payload ").borders(Borders::ALL))
+        .wrap(Wrap { trim: false });
+    f.render_widget(widget, area);
 }
 
-/// Read SSE events from a streaming response and forward them.
-async fn stream_sse_events(
-    mut resp: reqwest::Response,
-    tx: &tokio::sync::mpsc::UnboundedSender<AppMessage>,
-) -> Result<()> {
-    let mut buf = String::new();
-
-    // Use reqwest's chunk() method to read the streaming body piece by piece.
-    loop {
-        let chunk = tokio::time::timeout(Duration::from_secs(60), resp.chunk()).await;
+/// How fast the last recording was transcribed, once there was one.
+fn transcription_speed(app: &App) -> Option<Line<'static>> {
+    let (audio, took) = app.resources.transcription()?;
+    let speed = audio.as_secs_f32() / took.as_secs_f32().max(0.001);
+    Some(Line::from(Span::styled(
+        format!(
+            "  Last transcription: {:.1}s of audio in {:.2}s ({speed:.1}\u{00d7} realtime)",
+            audio.as_secs_f32(),
+            took.as_secs_f32()
+        ),
+        Style::default().fg(app.theme.muted),
+    )))
+}
 
-        match chunk {
-            Ok(Ok(Some(bytes))) => {
-                buf.push_str(&String::from_utf8_lossy(&bytes));
-                // Process complete SSE events (terminated by \n\n)
-                while let Some(pos) = buf.find("\n\n") {
-                    let event_text = buf[..pos].to_string();
-                    buf = buf[pos + 2..].to_string();
+/// Raw SSE events, newest first, each a header line (age, type, and
+/// whether conch handled it) followed by its JSON.
+fn render_preview(f: &mut ratatui::Frame, app: &App, preview: &Preview, area: Rect) {
+    let theme = &app.theme;
+    let name = preview
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    let title = if preview.edited.is_some() {
+        format!(" {name} (last edit) ")
+    } else {
+        format!(" {name} ")
+    };
+    let lines: Vec<Line> = match &preview.error {
+        Some(error) => vec![Line::from(Span::styled(
+            format!("  {error}"),
+            Style::default().fg(theme.muted),
+        ))],
+        None => preview
+            .lines
+            .iter()
+            .map(|(number, text)| {
+                let edited = preview.edited.as_ref().is_some_and(|r| r.contains(number));
+                let color = if edited { theme.accent } else { theme.text };
+                Line::from(vec![
+                    Span::styled(format!("{number:>4} "), Style::default().fg(theme.muted)),
+                    Span::styled(text.clone(), Style::default().fg(color)),
+                ])
+            })
+            .collect(),
+    };
+    let widget = Paragraph::new(lines).block(Block::default().title(title).borders(Borders::ALL));
+    f.render_widget(widget, area);
+}
 
-                    for line in extract_sse_data_lines(&event_text) {
-                        if let Some(event) = parse_sse_event(line) {
-                            let _ = tx.send(AppMessage::ServerEvent(event));
-                        }
-                    }
-                }
-            }
-            Ok(Ok(None)) => return Err(anyhow!("stream ended")),
-            Ok(Err(e)) => return Err(anyhow!("stream error: {}", e)),
-            Err(_) => return Err(anyhow!("stream timeout")),
+fn render_inspector(f: &mut ratatui::Frame, app: &App, area: Rect) {
+    let (theme, glyphs) = (&app.theme, &app.glyphs);
+    let now = Instant::now();
+    let mut lines: Vec<Line> = Vec::new();
+    for event in app.inspector.visible() {
+        let (mark, color) = if event.handled {
+            (glyphs.handled, theme.success)
+        } else {
+            (glyphs.unhandled, theme.muted)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("  {:>4} ", notify::format_age(now.duration_since(event.at))),
+                Style::default().fg(theme.muted),
+            ),
+            Span::styled(format!("{mark} "), Style::default().fg(color)),
+            Span::styled(
+                event.event_type.clone(),
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]));
+        for json_line in event.pretty().lines() {
+            lines.push(Line::from(Span::styled(
+                format!("    {json_line}"),
+                Style::default().fg(theme.text),
+            )));
         }
     }
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  No events yet",
+            Style::default().fg(theme.muted),
+        )));
+    }
+    let scroll = app.inspector_scroll.min(lines.len().saturating_sub(1));
+    let lines: Vec<Line> = lines.into_iter().skip(scroll).collect();
+    let title = format!(
+        " Events ({}) {} {} ",
+        app.inspector.len(),
+        glyphs.dash,
+        app.inspector.filter().unwrap_or("all types")
+    );
+    let widget = Paragraph::new(lines)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .wrap(Wrap { trim: false });
+    f.render_widget(widget, area);
 }
 
-/// Handle spacebar press: toggle between recording and stopping.
-fn handle_space(
-    app: &mut App,
-    audio: &AudioCapture,
-    transcriber: &Arc<Transcriber>,
-    tx: &tokio::sync::mpsc::UnboundedSender<AppMessage>,
-) -> Result<()> {
-    match app.state {
-        RecordingState::Idle => {
-            audio.start_recording();
-            app.state = RecordingState::Recording;
-            app.error = None;
-        }
-        RecordingState::Recording => {
-            let samples = audio.stop_recording();
-            let sample_rate = audio.sample_rate();
+/// One span per session tab: number and name, the active one highlighted,
+/// busy sessions marked with `*`.
+fn tab_spans(app: &App) -> Vec<Span<'static>> {
+    let theme = &app.theme;
+    app.tabs
+        .tabs()
+        .iter()
+        .enumerate()
+        .map(|(i, tab)| {
+            let active = i == app.tabs.active();
+            let busy = if active { app.opencode_busy } else { tab.busy };
+            let label = format!(" {}:{}{} ", i + 1, tab.title(), if busy { "*" } else { "" });
+            let style = if active {
+                Style::default()
+                    .fg(theme.text)
+                    .add_modifier(Modifier::BOLD | Modifier::REVERSED)
+            } else {
+                Style::default().fg(theme.muted)
+            };
+            Span::styled(label, style)
+        })
+        .collect()
+}
 
-            if samples.is_empty() {
-                app.error = Some("No audio captured".into());
-                app.state = RecordingState::Idle;
-                return Ok(());
-            }
+/// Rows taken by the caption strip at the top of the screen.
+const CAPTION_ROWS: u16 = 3;
 
-            app.state = RecordingState::Processing;
-            app.pending_transcript = true;
-
-            // Run transcription in background thread
-            let tx = tx.clone();
-            let transcriber = Arc::clone(transcriber);
-            std::thread::spawn(move || {
-                let result = transcriber.transcribe(&samples, sample_rate);
-                let _ = tx.send(AppMessage::TranscriptReady(result));
-            });
-        }
-        RecordingState::Processing => {
-            // Ignore space while processing
-        }
+/// The caption to show now, if any.
+fn caption_visible(app: &App) -> Option<&str> {
+    let hold = Duration::from_secs_f32(app.caption.hold_secs.max(0.0));
+    app.captions.visible(Instant::now(), hold)
+}
+
+/// The caption strip: what's being said, bold and in reverse video, on the
+/// middle row of a solid band so it stands out on a shared screen.
+fn render_caption(f: &mut ratatui::Frame, app: &App, area: Rect) {
+    let style = Style::default()
+        .fg(app.theme.text)
+        .add_modifier(Modifier::BOLD | Modifier::REVERSED);
+    f.render_widget(Block::default().style(style), area);
+    let line = Rect {
+        x: area.x + 2,
+        y: area.y + area.height / 2,
+        width: area.width.saturating_sub(4),
+        height: area.height.min(1),
+    };
+    let text = caption_visible(app)
+        .map(|text| caption::fit_tail(text, line.width as usize))
+        .unwrap_or_default();
+    f.render_widget(Paragraph::new(text).style(style), line);
+}
+
+/// What became of a sent transcript, shown after its text: still waiting
+/// for OpenCode, answered (and how fast), or failed.
+fn transcript_outcome(
+    entry: &HistoryEntry,
+    theme: &Theme,
+    glyphs: &Glyphs,
+) -> Option<Span<'static>> {
+    let (text, color) = match entry.status {
+        TranscriptStatus::Sent => (format!(" {}", glyphs.transcribing), theme.muted),
+        TranscriptStatus::Answered => match entry.response_secs {
+            Some(secs) => (
+                format!(" {} {}", glyphs.handled, history::format_response(secs)),
+                theme.success,
+            ),
+            None => (format!(" {}", glyphs.handled), theme.success),
+        },
+        TranscriptStatus::Failed => (format!(" {} failed", glyphs.warning), theme.error),
+        _ => return None,
+    };
+    Some(Span::styled(text, Style::default().fg(color)))
+}
+
+/// A spinner, how long OpenCode has been busy, and the tool it's running,
+/// e.g. "⠹ busy 1:05 · bash".
+fn busy_state(app: &App, elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    let mut state = format!(
+        "{} busy {}:{:02}",
+        app.glyphs.spinner_frame(elapsed),
+        secs / 60,
+        secs % 60
+    );
+    if let Some(tool) = app.activity.current() {
+        state.push_str(&format!(" {} {}", app.glyphs.separator, tool.label));
     }
-    Ok(())
+    state
+}
+
+/// Heartbeat age and reconnect count for the connection indicator, e.g.
+/// " · 4s ago · 2 reconnects".
+fn connection_health(heartbeats: &Heartbeats, now: Instant, separator: &str) -> String {
+    let mut parts = Vec::new();
+    if let Some(since) = heartbeats.since_heartbeat(now) {
+        parts.push(format!("{}s ago", since.as_secs()));
+    }
+    match heartbeats.reconnects() {
+        0 => {}
+        1 => parts.push("1 reconnect".to_string()),
+        n => parts.push(format!("{n} reconnects")),
+    }
+    parts
+        .iter()
+        .map(|part| format!(" {separator} {part}"))
+        .collect()
 }
 
 /// Render the TUI.
+#[tracing::instrument(level = "debug", skip_all)]
 fn render(f: &mut ratatui::Frame, app: &App) {
-    let area = f.area();
-
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3),  // Title
-            Constraint::Length(10), // Waveform (8 content rows = 32 braille dots tall)
-            Constraint::Length(2),  // Transcript (borderless, compact)
-            Constraint::Length(3),  // Status
-            Constraint::Min(6),     // Focus Stack
-            Constraint::Length(3),  // Help bar
-        ])
-        .split(area);
+    let (theme, glyphs) = (&app.theme, &app.glyphs);
+    let dash = glyphs.dash;
+    let mut area = f.area();
+    if app.caption.enabled {
+        let split = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(CAPTION_ROWS), Constraint::Min(0)])
+            .split(area);
+        render_caption(f, app, split[0]);
+        area = split[1];
+    }
+    let panes = app.layout.split(area, app.show_waveform);
+    let stacked = app.layout.arrangement == Arrangement::Stacked;
+    let overlay = app.palette.is_some() || app.panel.is_some();
 
     // Title bar with connection status
     let conn_indicator = match app.connection_status {
         ConnectionStatus::Connected => {
-            if app.opencode_busy {
-                Span::styled(" [OC: busy] ", Style::default().fg(Color::Yellow))
+            let now = Instant::now();
+            let state = match app.busy_since.filter(|_| app.opencode_busy) {
+                Some(since) => busy_state(app, now.saturating_duration_since(since)),
+                None if app.opencode_busy => "busy".to_string(),
+                None => "connected".to_string(),
+            };
+            let health = connection_health(&app.heartbeats, now, glyphs.separator);
+            // A connection that's open but wedged looks like a busy one
+            let color = if app.opencode_busy || app.heartbeats.overdue(now) {
+                theme.warning
             } else {
-                Span::styled(" [OC: connected] ", Style::default().fg(Color::Green))
-            }
+                theme.success
+            };
+            Span::styled(
+                format!(" [OC: {state}{health}] "),
+                Style::default().fg(color),
+            )
         }
         ConnectionStatus::Disconnected => {
-            Span::styled(" [OC: disconnected] ", Style::default().fg(Color::Red))
+            Span::styled(" [OC: disconnected] ", Style::default().fg(theme.error))
         }
         ConnectionStatus::Reconnecting => {
-            Span::styled(" [OC: reconnecting] ", Style::default().fg(Color::Yellow))
+            Span::styled(" [OC: reconnecting] ", Style::default().fg(theme.warning))
         }
     };
     let session_info = app
@@ -607,82 +5043,460 @@ fn render(f: &mut ratatui::Frame, app: &App) {
         .map(|s| format!(" ({}) ", s))
         .unwrap_or_default();
 
-    let title = Paragraph::new(Line::from(vec![
-        Span::styled(
-            "Conch Voice Client",
+    let mut title_spans = vec![Span::styled(
+        "Conch Voice Client",
+        Style::default()
+            .fg(theme.accent)
+            .add_modifier(Modifier::BOLD),
+    )];
+    if app.tabs.len() > 1 {
+        title_spans.push(Span::raw(" "));
+        title_spans.extend(tab_spans(app));
+    } else if app.detached {
+        title_spans.push(Span::styled(
+            " (no session) ",
+            Style::default().fg(theme.warning),
+        ));
+    } else {
+        title_spans.push(Span::styled(session_info, Style::default().fg(theme.muted)));
+    }
+    title_spans.extend([
+        conn_indicator,
+        if app.hot_mic {
+            Span::styled(
+                format!(" [{} hot mic] ", glyphs.recording),
+                Style::default()
+                    .fg(theme.error)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Span::raw("")
+        },
+    ]);
+    if let Some(agent) = &app.agent {
+        title_spans.push(Span::styled(
+            format!(" [agent: {agent}] "),
+            Style::default().fg(theme.special),
+        ));
+    }
+    if let Some(dir) = &app.directory {
+        title_spans.push(Span::styled(
+            format!(" [{}] ", paths::home_relative(dir)),
+            Style::default().fg(theme.muted),
+        ));
+    }
+    if let Some(git) = &app.git {
+        let color = if git.is_clean() {
+            theme.success
+        } else {
+            theme.warning
+        };
+        title_spans.push(Span::styled(
+            format!(" [{} {}] ", glyphs.branch, git.summary(glyphs.separator)),
+            Style::default().fg(color),
+        ));
+    }
+    if audio::low_rate_warning(app.sample_rate).is_some() {
+        title_spans.push(Span::styled(
+            format!(" [{} {} Hz mic] ", glyphs.warning, app.sample_rate),
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme.error)
                 .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(session_info, Style::default().fg(Color::DarkGray)),
-        conn_indicator,
-    ]))
-    .alignment(Alignment::Center)
-    .block(Block::default().borders(Borders::ALL));
-    f.render_widget(title, chunks[0]);
-
-    // Waveform
+        ));
+    }
+    let title = Paragraph::new(Line::from(title_spans))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, panes.title);
 
-    let waveform_data = WaveformData {
-        bars: app.waveform_bars.clone(),
-    };
-    let wave_block = Block::default();
-    let wave_inner = wave_block.inner(chunks[1]);
-    f.render_widget(wave_block, chunks[1]);
-    let wave_widget = WaveformWidget::new(&waveform_data);
-    f.render_widget(wave_widget, wave_inner);
+    // Waveform: live while recording, then the recording, still and dimmed,
+    // until its prompt is sent or discarded
+    if let Some(wave_area) = panes.waveform {
+        let frozen = app.utterance.as_ref().filter(|_| {
+            app.state == RecordingState::Processing
+                || (app.state == RecordingState::Idle && app.prompt_pending.is_some())
+        });
+        let still: Vec<f32>;
+        let (bars, color) = match frozen {
+            Some(rms) => {
+                let rms = viz::compute_rms_windows(rms, wave_area.width as usize);
+                still = app.waveform.scale(&rms);
+                (still.as_slice(), theme.muted)
+            }
+            None => (app.waveform_bars.as_slice(), theme.waveform),
+        };
+        let wave_widget = WaveformWidget::from_bars(bars)
+            .colors(color, theme.muted)
+            .charset(glyphs.charset);
+        f.render_widget(wave_widget, wave_area);
+    }
 
-    // Transcript area (borderless, compact — just latest text below waveform)
-    let transcript_line = if let Some(pending) = &app.prompt_pending {
+    // Transcript area: the latest text (or the prompt being typed or
+    // reviewed), with earlier transcripts above it when there's room
+    let transcript_line = if let Some(input) = &app.input {
+        let (before, after) = input.split_at_cursor();
+        let mut rest = after.chars();
+        let under_cursor = rest.next().map_or(" ".to_string(), String::from);
+        let text_style = Style::default().fg(theme.text);
+        Line::from(vec![
+            Span::styled(
+                format!("  {} ", glyphs.typing),
+                Style::default().fg(theme.accent),
+            ),
+            Span::styled(before.to_string(), text_style),
+            Span::styled(under_cursor, text_style.add_modifier(Modifier::REVERSED)),
+            Span::styled(rest.as_str().to_string(), text_style),
+        ])
+    } else if let (Some(pending), Some(reason)) = (&app.prompt_pending, &app.review) {
         Line::from(vec![
-            Span::styled("  \u{25B6} ", Style::default().fg(Color::Cyan)),
+            Span::styled(
+                format!("  {} ", glyphs.warning),
+                Style::default().fg(theme.warning),
+            ),
             Span::styled(
                 pending.clone(),
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(theme.on_warning)
+                    .bg(theme.warning)
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::styled(" [pending]", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                format!(" [review: {}]", reason),
+                Style::default().fg(theme.warning),
+            ),
         ])
+    } else if let Some(pending) = &app.prompt_pending {
+        let style = Style::default()
+            .fg(theme.accent)
+            .add_modifier(Modifier::BOLD);
+        let mut spans = vec![Span::styled(
+            format!("  {} ", glyphs.pending),
+            Style::default().fg(theme.accent),
+        )];
+        // Corrected words show as a diff, the words heard struck through
+        // before what replaced them, until they're sent or undone
+        let mut at = 0;
+        for correction in app.corrected.of(pending) {
+            let range = correction.range.clone();
+            spans.push(Span::styled(pending[at..range.start].to_string(), style));
+            spans.push(Span::styled(
+                correction.original.clone(),
+                Style::default()
+                    .fg(theme.muted)
+                    .add_modifier(Modifier::CROSSED_OUT),
+            ));
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(
+                pending[range.clone()].to_string(),
+                style.fg(theme.special).add_modifier(Modifier::UNDERLINED),
+            ));
+            at = range.end;
+        }
+        spans.push(Span::styled(pending[at..].to_string(), style));
+        spans.push(Span::styled(" [pending]", Style::default().fg(theme.muted)));
+        Line::from(spans)
     } else if let Some(last) = app.transcripts.last() {
-        Line::from(vec![
+        let mut spans = vec![
             Span::styled("  ", Style::default()),
-            Span::styled(last.clone(), Style::default().fg(Color::White)),
-        ])
+            Span::styled(last.text.clone(), Style::default().fg(theme.text)),
+        ];
+        spans.extend(transcript_outcome(last, theme, glyphs));
+        Line::from(spans)
     } else {
         Line::from(Span::styled(
             "  No transcripts yet",
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme.muted),
         ))
     };
-    let transcript = Paragraph::new(transcript_line);
-    f.render_widget(transcript, chunks[2]);
+    let rows = if stacked {
+        // Stacked, the strip keeps a blank row under it
+        panes.transcript.height.saturating_sub(1)
+    } else {
+        panes.transcript.height.saturating_sub(2)
+    };
+    let len = app.transcripts.len();
+    // The conversation takes the list's place, unless dictating or browsing
+    let conversation = app.show_conversation
+        && app.dictation.is_none()
+        && app.transcript_cursor.position(len).is_none();
+    let below =
+        (app.input.is_some() || app.prompt_pending.is_some()).then(|| transcript_line.clone());
+    let transcript_lines: Vec<Line> = if let Some(dictation) = &app.dictation {
+        // Dictating: the end of the document so far
+        let width = panes
+            .transcript
+            .width
+            .saturating_sub(if stacked { 4 } else { 6 });
+        if dictation.text().is_empty() {
+            vec![Line::from(Span::styled(
+                "  Nothing dictated yet",
+                Style::default().fg(theme.muted),
+            ))]
+        } else {
+            dictation
+                .tail(width as usize, rows.max(1) as usize)
+                .into_iter()
+                .map(|line| {
+                    Line::from(Span::styled(
+                        format!("  {line}"),
+                        Style::default().fg(theme.text),
+                    ))
+                })
+                .collect()
+        }
+    } else if let Some(position) = app.transcript_cursor.position(len) {
+        // Browsing with the vim keys: the transcript under the cursor last
+        let selection = app.transcript_cursor.selection(len);
+        let first = (position + 1).saturating_sub(rows.max(1) as usize);
+        (first..=position)
+            .map(|i| {
+                let mut style = if i == position {
+                    Style::default()
+                        .fg(theme.accent)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(theme.muted)
+                };
+                if selection.as_ref().is_some_and(|s| s.contains(&i)) {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                let indicator = if i == position { glyphs.pointer } else { " " };
+                Line::from(Span::styled(
+                    format!("{indicator} {}", app.transcripts[i].text),
+                    style,
+                ))
+            })
+            .collect()
+    } else {
+        let latest_shown = app.input.is_none() as usize;
+        let earlier = &app.transcripts[..len.saturating_sub(latest_shown)];
+        let mut lines: Vec<Line> = earlier
+            .iter()
+            .rev()
+            .take(rows.saturating_sub(1) as usize)
+            .rev()
+            .map(|entry| {
+                let mut spans = vec![Span::styled(
+                    format!("  {}", entry.text),
+                    Style::default().fg(theme.muted),
+                )];
+                spans.extend(transcript_outcome(entry, theme, glyphs));
+                Line::from(spans)
+            })
+            .collect();
+        lines.push(transcript_line);
+        lines
+    };
+    if stacked {
+        f.render_widget(Paragraph::new(transcript_lines), panes.transcript);
+    } else if conversation && !overlay {
+        render_conversation(f, app, panes.transcript, below);
+    } else if !overlay {
+        let transcript = Paragraph::new(transcript_lines).block(
+            Block::default()
+                .title(" Transcripts ")
+                .borders(Borders::ALL),
+        );
+        f.render_widget(transcript, panes.transcript);
+    }
 
     // Status area
-    let (status_text, status_color) = if app.prompt_pending.is_some() {
+    let (status_text, status_color) = if app.palette.is_some() {
+        (
+            format!("  Command palette {dash} type to filter, [Enter] to use, [Esc] to close"),
+            theme.accent,
+        )
+    } else if app.input.is_some() {
+        (
+            format!("  Typing a prompt {dash} [Enter] to review, [Esc] to cancel"),
+            theme.accent,
+        )
+    } else if let Some(search) = &app.search {
+        (format!("  /{}", search.text()), theme.accent)
+    } else if let Some(dictation) = &app.dictation {
+        if dictation.is_finishing() {
+            (
+                format!(
+                    "  {} Finishing the dictation... {} segments to go",
+                    glyphs.transcribing,
+                    dictation.transcribing()
+                ),
+                theme.warning,
+            )
+        } else {
+            (
+                format!(
+                    "  {} Dictating {dash} {} segments, press [D] or [Space] to finish",
+                    glyphs.recording,
+                    dictation.segments() + dictation.transcribing()
+                ),
+                theme.error,
+            )
+        }
+    } else if app.answering && app.state == RecordingState::Recording {
+        (
+            format!(
+                "  {} Listening for an answer {dash} say yes, no or cancel",
+                glyphs.recording
+            ),
+            theme.error,
+        )
+    } else if let Some(deadline) = app.send_deadline {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        (
+            format!(
+                "  Sending in {:.1}s {dash} [Esc] to hold, [Backspace] to discard, [Enter] to send now",
+                remaining.as_secs_f32()
+            ),
+            theme.accent,
+        )
+    } else if let (Some(_), Some(reason)) = (&app.prompt_pending, &app.review) {
+        // Hallucinations are held with auto-send off too
+        let held = if app.auto_send {
+            "Auto-send held"
+        } else {
+            "Held for review"
+        };
+        (
+            format!("  {held} ({reason}) {dash} [Enter] to send, [Backspace] to discard"),
+            theme.warning,
+        )
+    } else if let (Some(_), Some(notice)) = (&app.prompt_pending, app.notices.toast()) {
+        (
+            format!(
+                "  {} {dash} [Enter] to send, [Backspace] to discard",
+                notice.message
+            ),
+            severity_color(theme, notice.severity),
+        )
+    } else if app.prompt_pending.is_some() {
         (
             "  Press [Enter] to send to OpenCode, [Backspace] to discard".into(),
-            Color::Cyan,
+            theme.accent,
+        )
+    } else if let Some(range) = app.transcript_cursor.selection(len) {
+        (
+            format!(
+                "  {} selected {dash} [j/k] extend, [y] copy, [Esc] stop selecting",
+                range.count()
+            ),
+            theme.accent,
+        )
+    } else if let Some(position) = app.transcript_cursor.position(len) {
+        (
+            format!(
+                "  Transcript {}/{len} {dash} [j/k] move, [/] search, [v] select, [G] back to live",
+                position + 1
+            ),
+            theme.accent,
         )
     } else {
         match app.state {
             RecordingState::Idle => {
-                if let Some(err) = &app.error {
-                    (format!("  {}", err), Color::Yellow)
+                if app.speaking {
+                    (
+                        format!(
+                            "  {} Reading reply aloud... press [s] to stop",
+                            glyphs.speaking
+                        ),
+                        theme.success,
+                    )
+                } else if let Some((attempt, of)) = app.send_retry {
+                    (
+                        format!("  Sending failed, retrying ({attempt}/{of})\u{2026}"),
+                        theme.warning,
+                    )
+                } else if let Some(notice) = app.notices.toast() {
+                    (
+                        format!("  {}", notice.message),
+                        severity_color(theme, notice.severity),
+                    )
+                } else if app.spelling {
+                    (
+                        format!(
+                            "  {} Spelling {dash} say letters, [l] or \"stop spelling\" to finish",
+                            glyphs.typing
+                        ),
+                        theme.special,
+                    )
+                } else if let (true, Some(phrase)) = (app.hot_mic, &app.wake_phrase) {
+                    (
+                        format!("  Ready {dash} say \"{}\" to record", phrase),
+                        theme.info,
+                    )
                 } else {
-                    ("  Ready".into(), Color::Gray)
+                    ("  Ready".into(), theme.info)
                 }
             }
-            RecordingState::Recording => {
-                ("  \u{25CF} Recording... press [Space] to stop".into(), Color::Red)
-            }
-            RecordingState::Processing => ("  \u{23F3} Transcribing...".into(), Color::Yellow),
+            RecordingState::Recording => match app.stop_countdown {
+                Some(secs) => (
+                    format!(
+                        "  {} Recording... stopping in {}s {dash} press [Space] to stop now",
+                        glyphs.recording, secs
+                    ),
+                    theme.error,
+                ),
+                None if app.transcribing > 0 => (
+                    format!(
+                        "  {} Recording... press [Space] to stop ({} still transcribing)",
+                        glyphs.recording, app.transcribing
+                    ),
+                    theme.error,
+                ),
+                None => (
+                    format!("  {} Recording... press [Space] to stop", glyphs.recording),
+                    theme.error,
+                ),
+            },
+            RecordingState::Processing => (
+                format!(
+                    "  {} Transcribing... press [Space] to record the next prompt",
+                    glyphs.transcribing
+                ),
+                theme.warning,
+            ),
         }
     };
+    let mut modes = Vec::new();
+    if app.auto_send {
+        modes.push("auto-send");
+    }
+    if !app.language().english() {
+        modes.push(app.language().name);
+    }
+    if app.spelling {
+        modes.push("spelling");
+    }
+    if app.hands_free.is_enabled() {
+        modes.push("hands-free");
+    }
+    let status_title = if modes.is_empty() {
+        " Status ".to_string()
+    } else {
+        format!(" Status ({}) ", modes.join(", "))
+    };
+    let mut status_block = Block::default().title(status_title).borders(Borders::ALL);
+    if let (RecordingState::Recording, Some(started)) = (app.state, app.recording_started) {
+        let meter = recording_meter(
+            theme,
+            glyphs,
+            started.elapsed(),
+            app.buffer_usage,
+            app.mic_dead,
+        );
+        status_block = status_block.title(meter);
+    }
+    let queued = app.queue.waiting().count();
+    if queued > 0 {
+        let badge = format!(" {} {queued} queued ", glyphs.transcribing);
+        status_block = status_block.title(
+            Line::from(Span::styled(badge, Style::default().fg(theme.warning))).right_aligned(),
+        );
+    }
     let status = Paragraph::new(status_text)
         .style(Style::default().fg(status_color))
-        .block(Block::default().title(" Status ").borders(Borders::ALL));
-    f.render_widget(status, chunks[3]);
+        .block(status_block);
+    f.render_widget(status, panes.status);
 
     // Focus Stack area
     let focus_title = if app.focus.follow_mode() {
@@ -690,10 +5504,10 @@ fn render(f: &mut ratatui::Frame, app: &App) {
     } else {
         " Focus Stack "
     };
-    let focus_lines: Vec<Line> = if app.focus.len() == 0 {
+    let focus_lines: Vec<Line> = if app.focus.is_empty() {
         vec![Line::from(Span::styled(
             "  No focus entries yet",
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme.muted),
         ))]
     } else {
         app.focus
@@ -702,44 +5516,258 @@ fn render(f: &mut ratatui::Frame, app: &App) {
             .enumerate()
             .map(|(i, entry)| {
                 let is_current = i == app.focus.pointer();
-                let indicator = if is_current { "\u{25B8} " } else { "  " };
+                let indicator = if is_current {
+                    format!("{} ", glyphs.pointer)
+                } else {
+                    "  ".to_string()
+                };
                 let style = if is_current {
                     Style::default()
-                        .fg(Color::Cyan)
+                        .fg(theme.accent)
                         .add_modifier(Modifier::BOLD)
                 } else {
-                    Style::default().fg(Color::White)
+                    Style::default().fg(theme.text)
+                };
+                let pin = if app.focus.is_pinned(entry) {
+                    format!(" {}", glyphs.pin)
+                } else {
+                    String::new()
                 };
-                Line::from(Span::styled(format!("{}{}", indicator, entry), style))
+                Line::from(Span::styled(
+                    format!("{}{}{}", indicator, entry.label(glyphs), pin),
+                    style,
+                ))
             })
             .collect()
     };
     let focus_widget = Paragraph::new(focus_lines)
         .block(Block::default().title(focus_title).borders(Borders::ALL))
         .wrap(Wrap { trim: false });
-    f.render_widget(focus_widget, chunks[4]);
+    if let Some(palette) = &app.palette {
+        render_palette(f, app, palette, panes.main);
+    } else if let Some(panel) = app.panel {
+        match panel {
+            Panel::Notices => render_notices(f, app, panes.main),
+            Panel::Inspector => render_inspector(f, app, panes.main),
+            Panel::Resources => render_resources(f, app, panes.main),
+            Panel::Queue => render_queue(f, app, panes.main),
+            Panel::Stats => render_stats(f, app, panes.main),
+            Panel::Payload => render_payload(f, app, panes.main),
+        }
+    } else if conversation && stacked {
+        // Stacked, the strip above already shows the prompt in the works
+        render_conversation(f, app, panes.main, None);
+    } else {
+        let mut focus_area = panes.focus;
+        // The preview takes up to half the focus pane, if that leaves it room
+        let preview_height = (app.preview_config.lines as u16 + 2).min(focus_area.height / 2);
+        if let (Some(preview), true) = (&app.preview, preview_height >= 3) {
+            let split = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(preview_height)])
+                .split(focus_area);
+            focus_area = split[0];
+            render_preview(f, app, preview, split[1]);
+        }
+        f.render_widget(focus_widget, focus_area);
+        render_activity(f, app, panes.activity);
+    }
 
     // Help bar
-    let mut help_spans = vec![
-        Span::styled(" [Space] ", Style::default().fg(Color::Cyan)),
-        Span::raw("Record  "),
-    ];
-    if app.prompt_pending.is_some() {
+    if app.palette.is_some() {
+        let help = Paragraph::new(Line::from(vec![
+            Span::styled(" [Enter] ", Style::default().fg(theme.accent)),
+            Span::raw("Use  "),
+            Span::styled(
+                format!("[{}] ", glyphs.up_down),
+                Style::default().fg(theme.accent),
+            ),
+            Span::raw("Select  "),
+            Span::styled("[Esc] ", Style::default().fg(theme.accent)),
+            Span::raw("Close"),
+        ]))
+        .block(Block::default().borders(Borders::ALL));
+        f.render_widget(help, panes.help);
+        return;
+    }
+    if app.input.is_some() {
+        let help = Paragraph::new(Line::from(vec![
+            Span::styled(" [Enter] ", Style::default().fg(theme.accent)),
+            Span::raw("Done  "),
+            Span::styled("[Esc] ", Style::default().fg(theme.accent)),
+            Span::raw("Cancel  "),
+            Span::styled("[Up/Down] ", Style::default().fg(theme.accent)),
+            Span::raw("History  "),
+            Span::styled("[C-w/C-u] ", Style::default().fg(theme.accent)),
+            Span::raw("Delete word/line"),
+        ]))
+        .block(Block::default().borders(Borders::ALL));
+        f.render_widget(help, panes.help);
+        return;
+    }
+    let recording = app.state == RecordingState::Recording;
+    let mut help_spans = vec![Span::styled(" [Space] ", Style::default().fg(theme.accent))];
+    if recording {
+        help_spans.extend([
+            Span::raw("Stop  "),
+            Span::styled("[Esc] ", Style::default().fg(theme.accent)),
+            Span::raw("Abort  "),
+        ]);
+    } else {
+        help_spans.push(Span::raw("Record  "));
+    }
+    if let Some(text) = &app.prompt_pending {
+        // The same text already on its way can't be sent until it's through
+        if app.is_sending(text) {
+            help_spans.push(Span::styled(
+                "[Enter] Sending\u{2026}  ",
+                Style::default().fg(theme.muted),
+            ));
+        } else {
+            help_spans.extend([
+                Span::styled("[Enter] ", Style::default().fg(theme.accent)),
+                Span::raw("Send  "),
+            ]);
+        }
         help_spans.extend([
-            Span::styled("[Enter] ", Style::default().fg(Color::Cyan)),
-            Span::raw("Send  "),
-            Span::styled("[Bksp] ", Style::default().fg(Color::Cyan)),
+            Span::styled("[S-Enter/y] ", Style::default().fg(theme.accent)),
+            Span::raw("Copy  "),
+            Span::styled("[Bksp] ", Style::default().fg(theme.accent)),
             Span::raw("Discard  "),
         ]);
+        if app.last_recording.is_some() && !recording {
+            help_spans.extend([
+                Span::styled("[r] ", Style::default().fg(theme.accent)),
+                Span::raw("Retry  "),
+            ]);
+        }
+        if !app.corrected.of(text).is_empty() {
+            help_spans.extend([
+                Span::styled("[u] ", Style::default().fg(theme.accent)),
+                Span::raw("Undo fix  "),
+            ]);
+        }
+        help_spans.extend([
+            Span::styled("[1-4] ", Style::default().fg(theme.accent)),
+            Span::raw("Route  "),
+            Span::styled("[P] ", Style::default().fg(theme.accent)),
+            Span::raw(if app.panel == Some(Panel::Payload) {
+                "Hide payload  "
+            } else {
+                "Payload  "
+            }),
+        ]);
+    } else if !recording && app.panel != Some(Panel::Inspector) {
+        if app.speaking {
+            help_spans.extend([
+                Span::styled("[s] ", Style::default().fg(theme.accent)),
+                Span::raw("Stop  "),
+            ]);
+        }
+        help_spans.extend([
+            Span::styled("[a] ", Style::default().fg(theme.accent)),
+            Span::raw("Auto  "),
+            Span::styled("[i] ", Style::default().fg(theme.accent)),
+            Span::raw("Type  "),
+        ]);
+        // The palette lists snippets and directories
+        let current = app.directory.as_ref();
+        let other_directory = app.session_directories().iter().any(|d| current != Some(d));
+        if !app.snippets.is_empty() || other_directory {
+            help_spans.extend([
+                Span::styled("[p] ", Style::default().fg(theme.accent)),
+                Span::raw("Palette  "),
+            ]);
+        }
+    }
+    if app.panel == Some(Panel::Inspector) {
+        help_spans.extend([
+            Span::styled(
+                format!("[{}] ", glyphs.up_down),
+                Style::default().fg(theme.accent),
+            ),
+            Span::raw("Scroll  "),
+            Span::styled(
+                format!("[{}] ", glyphs.left_right),
+                Style::default().fg(theme.accent),
+            ),
+            Span::raw("Filter  "),
+            Span::styled("[d] ", Style::default().fg(theme.accent)),
+            Span::raw("Close  "),
+        ]);
+    } else if app.panel == Some(Panel::Notices) {
+        help_spans.extend([
+            Span::styled(
+                format!("[{}] ", glyphs.up_down),
+                Style::default().fg(theme.accent),
+            ),
+            Span::raw("Scroll  "),
+            Span::styled("[e] ", Style::default().fg(theme.accent)),
+            Span::raw("Close  "),
+        ]);
+    } else if app.panel == Some(Panel::Resources) {
+        help_spans.extend([
+            Span::styled("[R] ", Style::default().fg(theme.accent)),
+            Span::raw("Close  "),
+        ]);
+    } else if app.panel == Some(Panel::Stats) {
+        help_spans.extend([
+            Span::styled("[S] ", Style::default().fg(theme.accent)),
+            Span::raw("Close  "),
+        ]);
+    } else if app.panel == Some(Panel::Payload) {
+        help_spans.extend([
+            Span::styled("[P] ", Style::default().fg(theme.accent)),
+            Span::raw("Close  "),
+        ]);
+    } else if app.panel == Some(Panel::Queue) {
+        help_spans.extend([
+            Span::styled(
+                format!("[{}] ", glyphs.up_down),
+                Style::default().fg(theme.accent),
+            ),
+            Span::raw("Select  "),
+            Span::styled("[Del] ", Style::default().fg(theme.accent)),
+            Span::raw("Cancel  "),
+            Span::styled("[J] ", Style::default().fg(theme.accent)),
+            Span::raw("Close  "),
+        ]);
+    } else {
+        if app.notices.unseen() > 0 {
+            help_spans.extend([
+                Span::styled("[e] ", Style::default().fg(theme.accent)),
+                Span::raw(format!("Notices ({})  ", app.notices.unseen())),
+            ]);
+        }
+        help_spans.extend([
+            Span::styled(
+                format!("[{}] ", glyphs.up_down),
+                Style::default().fg(theme.accent),
+            ),
+            Span::raw("Focus  "),
+            Span::styled("[f] ", Style::default().fg(theme.accent)),
+            Span::raw("Follow  "),
+        ]);
+        if app.show_conversation {
+            help_spans.extend([
+                Span::styled("[PgUp/PgDn] ", Style::default().fg(theme.accent)),
+                Span::raw("Pick  "),
+                Span::styled("[z] ", Style::default().fg(theme.accent)),
+                Span::raw("Fold  "),
+                Span::styled("[c] ", Style::default().fg(theme.accent)),
+                Span::raw("Transcripts  "),
+            ]);
+        }
     }
+    let quit = if recording || app.keymap == Keymap::Vim {
+        "[q] "
+    } else {
+        "[q/Esc] "
+    };
     help_spans.extend([
-        Span::styled("[\u{2191}\u{2193}] ", Style::default().fg(Color::Cyan)),
-        Span::raw("Focus  "),
-        Span::styled("[f] ", Style::default().fg(Color::Cyan)),
-        Span::raw("Follow  "),
-        Span::styled("[q/Esc] ", Style::default().fg(Color::Cyan)),
+        Span::styled(quit, Style::default().fg(theme.accent)),
         Span::raw("Quit"),
     ]);
     let help = Paragraph::new(Line::from(help_spans)).block(Block::default().borders(Borders::ALL));
-    f.render_widget(help, chunks[5]);
+    f.render_widget(help, panes.help);
 }
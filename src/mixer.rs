@@ -0,0 +1,263 @@
+// Mixer Module - Recording from several mics at once
+//
+// With `devices` set under `[audio]`, every device gets its own stream and
+// their audio meets in a mixer before it reaches the capture buffers. The
+// mixer lines the streams up in short windows and either follows the loudest
+// mic, window by window, so a headset and a desk mic both work as you move
+// around the room, or mixes them all together. A stream that stalls (a
+// device unplugged, or a clock running slow) is treated as silence rather
+// than holding the others up.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+
+use crate::audio::{AudioConfig, AudioSource, MicSource, SampleSink};
+
+/// Length of the windows the streams are lined up and compared in.
+pub const WINDOW_SECS: f32 = 0.02;
+/// How far one stream may run ahead before the others count as silent.
+pub const MAX_LAG_SECS: f32 = 0.2;
+/// How much louder another mic must be before the loudest-mic mode switches
+/// to it, so two mics at about the same level don't flap.
+const SWITCH_RATIO: f32 = 1.5;
+
+/// How the audio of several devices is combined.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MixMode {
+    /// Follow whichever mic is loudest.
+    #[default]
+    Loudest,
+    /// Average all the mics.
+    Mix,
+}
+
+/// Lines up the samples of several streams and combines them.
+#[derive(Debug, Clone)]
+pub struct Mixer {
+    mode: MixMode,
+    queues: Vec<VecDeque<f32>>,
+    window: usize,
+    max_lag: usize,
+    /// The input followed in [`MixMode::Loudest`].
+    current: usize,
+}
+
+impl Mixer {
+    /// A mixer of `inputs` streams at `sample_rate`.
+    pub fn new(inputs: usize, sample_rate: u32, mode: MixMode) -> Self {
+        let window = ((sample_rate as f32 * WINDOW_SECS) as usize).max(1);
+        Self {
+            mode,
+            queues: vec![VecDeque::new(); inputs],
+            window,
+            max_lag: ((sample_rate as f32 * MAX_LAG_SECS) as usize).max(window),
+            current: 0,
+        }
+    }
+
+    /// The input followed in [`MixMode::Loudest`].
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Add `samples` from input number `input`, returning the combined
+    /// audio that is ready.
+    pub fn push(&mut self, input: usize, samples: impl Iterator<Item = f32>) -> Vec<f32> {
+        self.queues[input].extend(samples);
+        let mut out = Vec::new();
+        loop {
+            let longest = self.queues.iter().map(VecDeque::len).max().unwrap_or(0);
+            let all_ready = self.queues.iter().all(|q| q.len() >= self.window);
+            if longest < self.window || !(all_ready || longest > self.max_lag) {
+                return out;
+            }
+            // Streams that fell behind are silent for this window
+            let windows: Vec<Vec<f32>> = self
+                .queues
+                .iter_mut()
+                .map(|queue| {
+                    let n = queue.len().min(self.window);
+                    let mut window: Vec<f32> = queue.drain(..n).collect();
+                    window.resize(self.window, 0.0);
+                    window
+                })
+                .collect();
+            self.combine(&windows, &mut out);
+        }
+    }
+
+    fn combine(&mut self, windows: &[Vec<f32>], out: &mut Vec<f32>) {
+        match self.mode {
+            MixMode::Mix => {
+                let n = windows.len() as f32;
+                out.extend((0..self.window).map(|i| windows.iter().map(|w| w[i]).sum::<f32>() / n));
+            }
+            MixMode::Loudest => {
+                let levels: Vec<f32> = windows.iter().map(|w| rms(w)).collect();
+                let loudest = (0..levels.len())
+                    .max_by(|&a, &b| levels[a].total_cmp(&levels[b]))
+                    .unwrap_or(0);
+                if levels[loudest] <= levels[self.current] * SWITCH_RATIO {
+                    out.extend_from_slice(&windows[self.current]);
+                    return;
+                }
+                // Fade over to the louder mic across the window, so the
+                // switch doesn't click
+                let (from, to) = (&windows[self.current], &windows[loudest]);
+                let len = self.window as f32;
+                out.extend((0..self.window).map(|i| {
+                    let t = (i + 1) as f32 / len;
+                    from[i] * (1.0 - t) + to[i] * t
+                }));
+                self.current = loudest;
+            }
+        }
+    }
+}
+
+#[tracing::instrument(level = "trace", skip_all)]
+fn rms(samples: &[f32]) -> f32 {
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32).sqrt()
+}
+
+/// Several sources recorded at once, through a [`Mixer`].
+pub struct MixSource {
+    sources: Vec<Box<dyn AudioSource>>,
+    mode: MixMode,
+}
+
+impl MixSource {
+    /// Combine `sources`, which must all run at the same sample rate.
+    pub fn new(sources: Vec<Box<dyn AudioSource>>, mode: MixMode) -> Result<Self> {
+        let Some(first) = sources.first() else {
+            return Err(anyhow!("No audio sources to mix"));
+        };
+        let rate = first.sample_rate();
+        if let Some(other) = sources.iter().find(|s| s.sample_rate() != rate) {
+            return Err(anyhow!(
+                "Can't mix audio at {} Hz with audio at {} Hz",
+                rate,
+                other.sample_rate()
+            ));
+        }
+        Ok(Self { sources, mode })
+    }
+
+    /// Open the `devices` in `audio`, the others at the first one's rate.
+    pub fn open(audio: &AudioConfig) -> Result<Self> {
+        let mut sources: Vec<Box<dyn AudioSource>> = Vec::new();
+        let mut rate = None;
+        for name in &audio.devices {
+            let mic = MicSource::open(audio, Some(name), rate)?;
+            rate = Some(mic.sample_rate());
+            sources.push(Box::new(mic));
+        }
+        Self::new(sources, audio.mix)
+    }
+}
+
+impl AudioSource for MixSource {
+    fn sample_rate(&self) -> u32 {
+        self.sources[0].sample_rate()
+    }
+
+    fn start(&mut self, sink: SampleSink) -> Result<()> {
+        let mixer = Mixer::new(self.sources.len(), self.sample_rate(), self.mode);
+        let mixer = Arc::new(Mutex::new(mixer));
+        for (input, source) in self.sources.iter_mut().enumerate() {
+            let mixer = Arc::clone(&mixer);
+            let mixed_sink = sink.clone();
+            // Held only while one callback queues its samples and mixes
+            // the windows that completes
+            let forward = SampleSink::forward(move |samples| {
+                let mixed = mixer.lock().unwrap().push(input, samples);
+                mixed_sink.push(mixed);
+            });
+            source.start(forward.sharing_errors(&sink))?;
+        }
+        Ok(())
+    }
+
+    fn restart(&mut self) -> Result<()> {
+        for source in &mut self.sources {
+            source.restart()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::AudioCapture;
+    use crate::fake_audio::FakeSource;
+
+    const RATE: u32 = 1000;
+
+    /// `n` windows of a constant `level`.
+    fn level(level: f32, n: usize) -> impl Iterator<Item = f32> {
+        std::iter::repeat_n(level, (RATE as f32 * WINDOW_SECS) as usize * n)
+    }
+
+    #[test]
+    fn test_loudest_mic_is_followed() {
+        let mut mixer = Mixer::new(2, RATE, MixMode::Loudest);
+        assert!(mixer.push(0, level(0.5, 3)).is_empty(), "waits for input 1");
+        let out = mixer.push(1, level(0.05, 3));
+        assert_eq!(out.len(), 60);
+        assert!(out.iter().all(|&s| s == 0.5));
+
+        // About as loud: no switch
+        mixer.push(0, level(0.4, 1));
+        let out = mixer.push(1, level(0.5, 1));
+        assert_eq!(mixer.current(), 0);
+        assert!(out.iter().all(|&s| s == 0.4));
+
+        // Walked over to the other mic: a fade, then the other mic
+        mixer.push(0, level(0.05, 2));
+        let out = mixer.push(1, level(0.5, 2));
+        assert_eq!(mixer.current(), 1);
+        assert!(out[0] < 0.1 && out[19] == 0.5);
+        assert!(out[20..].iter().all(|&s| s == 0.5));
+    }
+
+    #[test]
+    fn test_mix_averages() {
+        let mut mixer = Mixer::new(2, RATE, MixMode::Mix);
+        mixer.push(0, level(0.5, 1));
+        let out = mixer.push(1, level(-0.1, 1));
+        assert_eq!(out.len(), 20);
+        assert!(out.iter().all(|&s| (s - 0.2).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_stalled_input_is_silent() {
+        let mut mixer = Mixer::new(2, RATE, MixMode::Mix);
+        assert!(mixer.push(0, level(0.5, 10)).is_empty());
+        // Past the lag limit, input 1 counts as silent
+        let out = mixer.push(0, level(0.5, 1));
+        assert!(!out.is_empty());
+        assert!(out.iter().all(|&s| s == 0.25));
+    }
+
+    #[test]
+    fn test_mix_source() {
+        let tone: Box<dyn AudioSource> = Box::new(FakeSource::tone(440.0));
+        let silence = FakeSource::new(vec![0.0; 1600], crate::fake_audio::TONE_SAMPLE_RATE);
+        let source = MixSource::new(vec![tone, Box::new(silence)], MixMode::Loudest).unwrap();
+        let audio = AudioCapture::with_source(Box::new(source)).unwrap();
+        audio.start_recording();
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        let samples = audio.stop_recording();
+        assert!(rms(&samples) > 0.1, "the tone comes through");
+
+        let slow: Box<dyn AudioSource> = Box::new(FakeSource::new(vec![0.0; 100], 8000));
+        let tone: Box<dyn AudioSource> = Box::new(FakeSource::tone(440.0));
+        assert!(MixSource::new(vec![tone, slow], MixMode::Mix).is_err());
+        assert!(MixSource::new(Vec::new(), MixMode::Mix).is_err());
+    }
+}
@@ -0,0 +1,333 @@
+// Mock Module - A stand-in OpenCode server, for development and tests
+//
+// `conch --mock` runs the whole app with nothing else installed: this server
+// takes OpenCode's place on a local port, a tone takes the microphone's (see
+// fake_audio), and every recording is heard as [`PHRASE`]. The server speaks
+// just enough of OpenCode's HTTP API for conch (health, sessions,
+// `prompt_async`, messages and the `/event` stream) and answers each prompt
+// with a short scripted turn: busy, a `read` of [`REPLY_FILE`], a reply,
+// idle. The end-to-end tests drive the app against it.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+
+/// What every `--mock` recording is heard as.
+pub const PHRASE: &str = "open the main file";
+/// The file each scripted turn reads.
+pub const REPLY_FILE: &str = "src/main.rs";
+/// The session the server creates.
+pub const SESSION_ID: &str = "ses_mock";
+/// Pause between the events of a scripted turn.
+pub const STEP: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Default)]
+struct State {
+    /// The session, once created.
+    session: Option<Value>,
+    prompts: Vec<String>,
+    messages: Vec<Value>,
+    /// Scripted turns played to the end.
+    turns: usize,
+}
+
+/// A running mock server. Clones share it.
+#[derive(Debug, Clone)]
+pub struct MockServer {
+    url: String,
+    state: Arc<Mutex<State>>,
+    events: broadcast::Sender<String>,
+}
+
+impl MockServer {
+    /// Start serving on a free local port. The server runs until the tokio
+    /// runtime shuts down.
+    pub async fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let (events, _) = broadcast::channel(64);
+        let server = Self {
+            url: format!("http://{}", listener.local_addr()?),
+            state: Arc::default(),
+            events,
+        };
+        let accepting = server.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let server = accepting.clone();
+                tokio::spawn(async move {
+                    let _ = server.serve(stream).await;
+                });
+            }
+        });
+        Ok(server)
+    }
+
+    /// Base URL to point an [`OpenCodeClient`](crate::transport::OpenCodeClient) at.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Prompts received, oldest first.
+    pub fn prompts(&self) -> Vec<String> {
+        self.lock().prompts.clone()
+    }
+
+    /// Scripted turns played to the end, idle event included.
+    pub fn turns(&self) -> usize {
+        self.lock().turns
+    }
+
+    /// Clients connected to the event stream.
+    pub fn subscribers(&self) -> usize {
+        self.events.receiver_count()
+    }
+
+    /// Send `event` to every client on the event stream.
+    pub fn emit(&self, event: Value) {
+        let _ = self.events.send(event.to_string());
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Answer one request. Every response closes the connection.
+    async fn serve(self, stream: TcpStream) -> Result<()> {
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).await?;
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).await?;
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':')
+                && name.eq_ignore_ascii_case("content-length")
+            {
+                length = value.trim().parse().unwrap_or(0);
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).await?;
+        let mut stream = reader.into_inner();
+
+        let mut words = request_line.split_whitespace();
+        let (method, path) = (words.next().unwrap_or(""), words.next().unwrap_or(""));
+        if (method, path) == ("GET", "/event") {
+            return self.stream_events(stream).await;
+        }
+        let (status, body) = self.respond(method, path, &body);
+        let head = format!(
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(body.as_bytes()).await?;
+        Ok(())
+    }
+
+    fn respond(&self, method: &str, path: &str, body: &[u8]) -> (&'static str, String) {
+        let (path, query) = path.split_once('?').unwrap_or((path, ""));
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, segments.as_slice()) {
+            ("GET", ["global", "health"]) => ok(json!({ "healthy": true })),
+            ("GET", ["session"]) => ok(Value::Array(self.lock().session.iter().cloned().collect())),
+            // Created where the request says, as OpenCode does
+            ("POST", ["session"]) => {
+                let body: Value = serde_json::from_slice(body).unwrap_or_default();
+                let mut session = json!({ "id": SESSION_ID, "slug": "mock-session" });
+                session["title"] = body["title"].clone();
+                if let Some(dir) = query_param(query, "directory") {
+                    session["directory"] = dir.into();
+                }
+                self.lock().session = Some(session.clone());
+                ok(session)
+            }
+            ("POST", ["session", id, "prompt_async"]) => {
+                let body: Value = serde_json::from_slice(body).unwrap_or_default();
+                let text: Vec<&str> = body["parts"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|p| p["text"].as_str())
+                    .collect();
+                let text = text.join("\n");
+                {
+                    let mut state = self.lock();
+                    state.prompts.push(text.clone());
+                    let message = message(state.messages.len(), "user", &text);
+                    state.messages.push(message);
+                }
+                self.play_turn(id.to_string());
+                ("204 No Content", String::new())
+            }
+            ("GET", ["session", id]) => match &self.lock().session {
+                Some(session) if session["id"] == *id => ok(session.clone()),
+                _ => ("404 Not Found", json!({ "error": "not found" }).to_string()),
+            },
+            ("GET", ["session", _, "message"]) => ok(Value::Array(self.lock().messages.clone())),
+            _ => ("404 Not Found", json!({ "error": "not found" }).to_string()),
+        }
+    }
+
+    /// Hold the connection open, sending events as they're emitted.
+    async fn stream_events(&self, mut stream: TcpStream) -> Result<()> {
+        let mut rx = self.events.subscribe();
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n")
+            .await?;
+        let connected = json!({ "type": "server.connected", "properties": {} });
+        stream
+            .write_all(format!("data: {connected}\n\n").as_bytes())
+            .await?;
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    stream
+                        .write_all(format!("data: {event}\n\n").as_bytes())
+                        .await?
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+
+    /// Work on a prompt the way OpenCode would, an event at a time.
+    fn play_turn(&self, session_id: String) {
+        let server = self.clone();
+        tokio::spawn(async move {
+            let status = |kind: &str| {
+                json!({
+                    "type": "session.status",
+                    "properties": { "sessionID": session_id, "status": { "type": kind } },
+                })
+            };
+            let read = |state: &str| {
+                json!({
+                    "type": "message.part.updated",
+                    "properties": { "part": {
+                        "type": "tool",
+                        "id": "prt_mock_read",
+                        "callID": "call_mock_read",
+                        "sessionID": session_id,
+                        "tool": "read",
+                        "state": { "status": state, "input": { "filePath": REPLY_FILE } },
+                    } },
+                })
+            };
+            for event in [status("busy"), read("running"), read("completed")] {
+                server.emit(event);
+                tokio::time::sleep(STEP).await;
+            }
+            {
+                let mut state = server.lock();
+                let reply = format!("Opened {REPLY_FILE}.");
+                let message = message(state.messages.len(), "assistant", &reply);
+                state.messages.push(message);
+            }
+            server.emit(status("idle"));
+            server.lock().turns += 1;
+        });
+    }
+}
+
+fn ok(body: Value) -> (&'static str, String) {
+    ("200 OK", body.to_string())
+}
+
+/// The value of `name` in the query string `query`, percent-decoded.
+fn query_param(query: &str, name: &str) -> Option<String> {
+    let value = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))?;
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        let hex = tail.get(..2).and_then(|h| std::str::from_utf8(h).ok());
+        match (b, hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(decoded)) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            (b'+', _) => {
+                bytes.push(b' ');
+                rest = tail;
+            }
+            _ => {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// A stored message with one text part, as GET /session/{id}/message lists it.
+fn message(index: usize, role: &str, text: &str) -> Value {
+    let created = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64);
+    json!({
+        "info": { "id": format!("msg_mock_{index}"), "role": role, "time": { "created": created } },
+        "parts": [{ "type": "text", "text": text }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{OpenCodeClient, ServerEvent, extract_sse_data_lines, parse_sse_event};
+
+    #[tokio::test]
+    async fn test_scripted_turn() {
+        let server = MockServer::start().await.unwrap();
+        let mut client = OpenCodeClient::new(server.url());
+        assert!(client.health_check().await.unwrap());
+        assert!(client.list_sessions().await.unwrap().is_empty());
+        let dir = std::path::Path::new("/work/my project");
+        let session = client.create_session(Some(dir)).await.unwrap();
+        assert_eq!(session.id, SESSION_ID);
+        let sessions = client.list_sessions().await.unwrap();
+        assert_eq!(sessions[0].directory.as_deref(), Some("/work/my project"));
+        let title = sessions[0].title.as_deref();
+        assert_eq!(title, Some(crate::transport::SESSION_TITLE));
+        assert!(client.get_session(SESSION_ID).await.unwrap().is_some());
+        assert!(client.get_session("ses_gone").await.unwrap().is_none());
+
+        let mut stream = client.subscribe_events().await.unwrap();
+        client.send_prompt("hello").await.unwrap();
+        assert_eq!(server.prompts(), vec!["hello"]);
+
+        let mut text = String::new();
+        while !text.contains(r#""type":"idle""#) {
+            let chunk = tokio::time::timeout(Duration::from_secs(5), stream.chunk())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            text.push_str(&String::from_utf8_lossy(&chunk));
+        }
+        let events: Vec<ServerEvent> = extract_sse_data_lines(&text)
+            .into_iter()
+            .filter_map(parse_sse_event)
+            .collect();
+        assert!(matches!(events[0], ServerEvent::Connected));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            ServerEvent::Tool(t) if t.state == "completed" && t.input["filePath"] == REPLY_FILE
+        )));
+
+        let messages = client.list_messages().await.unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].role, "assistant");
+    }
+}
@@ -0,0 +1,614 @@
+// Models Module - Manages downloaded ggml Whisper models in a data directory
+//
+// Models are plain `ggml-<name>.bin` files named the way whisper.cpp publishes
+// them, so everything shown (size, language, quantization) is read off the
+// file name.
+
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+
+use crate::paths;
+
+/// Where whisper.cpp publishes its ggml models.
+pub const DOWNLOAD_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+
+/// Models offered by `conch models list` when not yet downloaded.
+pub const CATALOG: &[&str] = &[
+    "tiny.en",
+    "tiny",
+    "base.en",
+    "base",
+    "small.en",
+    "small",
+    "medium.en",
+    "medium",
+    "large-v3",
+    "large-v3-turbo",
+    "base.en-q5_1",
+    "small.en-q5_1",
+    "large-v3-turbo-q5_0",
+];
+
+/// `[models]` section of the config file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ModelsConfig {
+    /// Directory models are downloaded to. Defaults to [`default_dir`].
+    pub dir: Option<PathBuf>,
+    /// Model used when none is given on the command line, by name
+    /// (`base.en`) or path.
+    pub default: Option<String>,
+}
+
+impl ModelsConfig {
+    /// The configured model directory, or the default one.
+    pub fn dir(&self) -> PathBuf {
+        self.dir.clone().unwrap_or_else(default_dir)
+    }
+
+    /// Path of the default model, if one is configured.
+    pub fn default_path(&self) -> Option<PathBuf> {
+        self.default.as_deref().map(|m| resolve(&self.dir(), m))
+    }
+
+    /// The model named on the command line (`arg`, a name or path), else
+    /// the default one, else `base.en`.
+    pub fn chosen(&self, arg: Option<&str>) -> PathBuf {
+        match arg.filter(|a| !a.starts_with("--")) {
+            Some(model) => resolve(&self.dir(), model),
+            None => self
+                .default_path()
+                .unwrap_or_else(|| resolve(&self.dir(), "base.en")),
+        }
+    }
+}
+
+/// What `conch models` was asked to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelsCommand {
+    /// List installed models and the ones available to download.
+    List,
+    Download(String),
+    Remove(String),
+    /// Make a model the default in the config file.
+    Default(String),
+}
+
+impl ModelsCommand {
+    pub const USAGE: &str =
+        "Usage: conch models list | download <name> | remove <name> | default <name>";
+
+    /// Parse the arguments of `conch models`; none at all is `list`.
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let name = args.get(1).cloned();
+        match (args.first().map(String::as_str), name) {
+            (Some("list") | None, _) => Ok(ModelsCommand::List),
+            (Some("download"), Some(name)) => Ok(ModelsCommand::Download(name)),
+            (Some("remove"), Some(name)) => Ok(ModelsCommand::Remove(name)),
+            (Some("default"), Some(name)) => Ok(ModelsCommand::Default(name)),
+            _ => Err(anyhow!(Self::USAGE)),
+        }
+    }
+}
+
+/// `conch models list|download|remove|default`: manage models in the
+/// model directory, making one the default in the config file at
+/// `config_path`.
+pub async fn run(args: &[String], config: &ModelsConfig, config_path: &Path) -> Result<()> {
+    let dir = config.dir();
+    let default = config.default.as_deref().map(model_name);
+    match ModelsCommand::from_args(args)? {
+        ModelsCommand::List => {
+            print!("{}", listing(&dir, &installed(&dir)?, default));
+        }
+        ModelsCommand::Download(name) => {
+            let name = model_name(&name);
+            eprintln!("Downloading {} to {}...", download_url(name), dir.display());
+            let mut last_percent = None;
+            let path = download(&dir, name, |done, total| {
+                if let Some(total) = total.filter(|&t| t > 0) {
+                    let percent = done * 100 / total;
+                    if last_percent != Some(percent) {
+                        last_percent = Some(percent);
+                        eprint!("\r  {}% of {}", percent, format_size(total));
+                    }
+                }
+            })
+            .await?;
+            eprintln!("\nSaved {}.", path.display());
+            if default.is_none() {
+                eprintln!("Make it the default with: conch models default {}", name);
+            }
+        }
+        ModelsCommand::Remove(name) => {
+            let path = remove(&dir, &name)?;
+            eprintln!("Removed {}.", path.display());
+            if default == Some(model_name(&name)) {
+                eprintln!(
+                    "It was the default model; pick another with: conch models default <name>"
+                );
+            }
+        }
+        ModelsCommand::Default(name) => {
+            let name = model_name(&name);
+            if !resolve(&dir, name).exists() {
+                eprintln!(
+                    "Note: '{}' is not downloaded yet (conch models download {}).",
+                    name, name
+                );
+            }
+            set_default_in_file(config_path, name)?;
+            eprintln!(
+                "Default model set to {} in {}.",
+                name,
+                config_path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// What `conch models list` prints: the models in `dir`, `default` marked
+/// with `*`, then the [`CATALOG`] models not downloaded yet.
+pub fn listing(dir: &Path, installed: &[InstalledModel], default: Option<&str>) -> String {
+    let mut out = format!("Models in {}:\n", dir.display());
+    if installed.is_empty() {
+        out.push_str("  (none)\n");
+    }
+    for model in installed {
+        let marker = if default == Some(model.info.name.as_str()) {
+            "*"
+        } else {
+            " "
+        };
+        let _ = writeln!(
+            out,
+            "{} {:<22} {:>8}  {:<12} {}",
+            marker,
+            model.info.name,
+            format_size(model.bytes),
+            model.info.language(),
+            model.info.quantization()
+        );
+    }
+    let available: Vec<&str> = CATALOG
+        .iter()
+        .copied()
+        .filter(|name| !installed.iter().any(|m| m.info.name == *name))
+        .collect();
+    if !available.is_empty() {
+        out.push_str("\nAvailable to download:\n");
+        for name in available {
+            if let Some(info) = ModelInfo::parse(name) {
+                let _ = writeln!(
+                    out,
+                    "  {:<22} {:>8}  {:<12} {}",
+                    name,
+                    "",
+                    info.language(),
+                    info.quantization()
+                );
+            }
+        }
+    }
+    out
+}
+
+/// `models` in the data directory, e.g. `~/.local/share/conch/models`.
+pub fn default_dir() -> PathBuf {
+    paths::models_dir()
+}
+
+/// Normalize `base.en`, `ggml-base.en` or `ggml-base.en.bin` to `base.en`.
+pub fn model_name(input: &str) -> &str {
+    let name = input.strip_suffix(".bin").unwrap_or(input);
+    name.strip_prefix("ggml-").unwrap_or(name)
+}
+
+/// File name for a model name.
+pub fn file_name(name: &str) -> String {
+    format!("ggml-{}.bin", model_name(name))
+}
+
+/// Download URL for a model name.
+pub fn download_url(name: &str) -> String {
+    format!("{}/{}", DOWNLOAD_BASE_URL, file_name(name))
+}
+
+/// Resolve a model given by name or path. Anything that looks like a path
+/// is used as-is; bare names live in `dir`.
+pub fn resolve(dir: &Path, model: &str) -> PathBuf {
+    if model.contains('/') || Path::new(model).exists() {
+        PathBuf::from(model)
+    } else {
+        dir.join(file_name(model))
+    }
+}
+
+/// What a model file name says about the model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelInfo {
+    /// Name as used on the command line, e.g. `small.en-q5_1`.
+    pub name: String,
+    /// Model size class, e.g. `small` or `large-v3-turbo`.
+    pub size: String,
+    /// `.en` models are English-only.
+    pub english_only: bool,
+    /// Quantization type, e.g. `q5_1`; `None` for full precision.
+    pub quantization: Option<String>,
+}
+
+impl ModelInfo {
+    /// Parse a model name or file name. Returns `None` for names that don't
+    /// follow the whisper.cpp convention.
+    pub fn parse(input: &str) -> Option<Self> {
+        let name = model_name(input);
+        if name.is_empty() || !name.is_ascii() {
+            return None;
+        }
+        let (base, quantization) = match name.rsplit_once('-') {
+            Some((base, q)) if q.starts_with('q') && q.contains('_') => (base, Some(q.to_string())),
+            _ => (name, None),
+        };
+        let (size, english_only) = match base.strip_suffix(".en") {
+            Some(size) => (size, true),
+            None => (base, false),
+        };
+        Some(Self {
+            name: name.to_string(),
+            size: size.to_string(),
+            english_only,
+            quantization,
+        })
+    }
+
+    /// "English" or "multilingual".
+    pub fn language(&self) -> &'static str {
+        if self.english_only {
+            "English"
+        } else {
+            "multilingual"
+        }
+    }
+
+    /// Quantization for display, "f16" when unquantized.
+    pub fn quantization(&self) -> &str {
+        self.quantization.as_deref().unwrap_or("f16")
+    }
+}
+
+/// A model file present in the model directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledModel {
+    pub info: ModelInfo,
+    pub path: PathBuf,
+    /// File size in bytes.
+    pub bytes: u64,
+}
+
+/// Models downloaded to `dir`, sorted by name. A missing directory is empty.
+pub fn installed(dir: &Path) -> Result<Vec<InstalledModel>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(anyhow!("Failed to read '{}': {}", dir.display(), e)),
+    };
+    let mut models = Vec::new();
+    for entry in entries.flatten() {
+        let file = entry.file_name();
+        let Some(file) = file.to_str() else {
+            continue;
+        };
+        if !file.starts_with("ggml-") || !file.ends_with(".bin") {
+            continue;
+        }
+        if let Some(info) = ModelInfo::parse(file) {
+            let bytes = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            models.push(InstalledModel {
+                info,
+                path: entry.path(),
+                bytes,
+            });
+        }
+    }
+    models.sort_by(|a, b| a.info.name.cmp(&b.info.name));
+    Ok(models)
+}
+
+/// Human-readable size, e.g. "142 MB".
+pub fn format_size(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    let mb = bytes as f64 / MB;
+    if mb >= 1024.0 {
+        format!("{:.1} GB", mb / 1024.0)
+    } else {
+        format!("{:.0} MB", mb)
+    }
+}
+
+/// Download a model into `dir`, calling `progress(downloaded, total)` as
+/// bytes arrive. The file is written under a `.part` name and renamed once
+/// complete, so an interrupted download never looks installed.
+pub async fn download(
+    dir: &Path,
+    name: &str,
+    mut progress: impl FnMut(u64, Option<u64>),
+) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| anyhow!("Failed to create '{}': {}", dir.display(), e))?;
+    let url = download_url(name);
+    let mut response = reqwest::get(&url).await?;
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "Download of '{}' failed: HTTP {}",
+            url,
+            response.status()
+        ));
+    }
+    let total = response.content_length();
+    let path = dir.join(file_name(name));
+    let part = path.with_extension("bin.part");
+    let mut file = std::fs::File::create(&part)
+        .map_err(|e| anyhow!("Failed to create '{}': {}", part.display(), e))?;
+    let mut downloaded = 0;
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk)?;
+        downloaded += chunk.len() as u64;
+        progress(downloaded, total);
+    }
+    file.flush()?;
+    std::fs::rename(&part, &path)?;
+    Ok(path)
+}
+
+/// Delete a downloaded model.
+pub fn remove(dir: &Path, name: &str) -> Result<PathBuf> {
+    let path = dir.join(file_name(name));
+    std::fs::remove_file(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => anyhow!("Model '{}' is not installed", model_name(name)),
+        _ => anyhow!("Failed to remove '{}': {}", path.display(), e),
+    })?;
+    Ok(path)
+}
+
+/// Set `default` in the `[models]` table of a config file's text, keeping
+/// the rest of the file (comments included) as it was.
+pub fn set_default_in_toml(text: &str, name: &str) -> String {
+    let setting = format!(
+        "default = \"{}\"",
+        name.replace('\\', "\\\\").replace('"', "\\\"")
+    );
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+
+    let section = lines.iter().position(|l| l.trim() == "[models]");
+    match section {
+        Some(start) => {
+            let end = lines[start + 1..]
+                .iter()
+                .position(|l| l.trim_start().starts_with('['))
+                .map_or(lines.len(), |i| start + 1 + i);
+            let existing = lines[start + 1..end].iter().position(|l| {
+                l.split_once('=')
+                    .is_some_and(|(key, _)| key.trim() == "default")
+            });
+            match existing {
+                Some(i) => lines[start + 1 + i] = setting,
+                None => lines.insert(start + 1, setting),
+            }
+        }
+        None => {
+            if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push("[models]".into());
+            lines.push(setting);
+        }
+    }
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// Set the default model in the config file at `path`, which is created if
+/// missing.
+pub fn set_default_in_file(path: &Path, name: &str) -> Result<()> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(anyhow!("Failed to read '{}': {}", path.display(), e)),
+    };
+    std::fs::write(path, set_default_in_toml(&text, name))
+        .map_err(|e| anyhow!("Failed to write '{}': {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("conch_models_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_model_name_normalization() {
+        assert_eq!(model_name("base.en"), "base.en");
+        assert_eq!(model_name("ggml-base.en.bin"), "base.en");
+        assert_eq!(file_name("small"), "ggml-small.bin");
+        assert_eq!(
+            download_url("tiny.en"),
+            "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.en.bin"
+        );
+    }
+
+    #[test]
+    fn test_parse_model_info() {
+        let info = ModelInfo::parse("ggml-small.en-q5_1.bin").unwrap();
+        assert_eq!(info.size, "small");
+        assert!(info.english_only);
+        assert_eq!(info.quantization(), "q5_1");
+
+        let info = ModelInfo::parse("large-v3-turbo").unwrap();
+        assert_eq!(info.size, "large-v3-turbo");
+        assert_eq!(info.language(), "multilingual");
+        assert_eq!(info.quantization(), "f16");
+    }
+
+    #[test]
+    fn test_resolve_name_or_path() {
+        let dir = Path::new("/data/models");
+        assert_eq!(resolve(dir, "base.en"), dir.join("ggml-base.en.bin"));
+        assert_eq!(resolve(dir, "./my.bin"), PathBuf::from("./my.bin"));
+    }
+
+    #[test]
+    fn test_installed_lists_models() {
+        let dir = temp_dir("installed");
+        std::fs::write(dir.join("ggml-tiny.en.bin"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.join("ggml-base.bin.part"), b"partial").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"x").unwrap();
+        let models = installed(&dir).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].info.name, "tiny.en");
+        assert_eq!(models[0].bytes, 10);
+    }
+
+    #[test]
+    fn test_chosen_model() {
+        let config = ModelsConfig {
+            dir: Some(PathBuf::from("/m")),
+            default: None,
+        };
+        assert_eq!(config.chosen(None), Path::new("/m/ggml-base.en.bin"));
+        assert_eq!(
+            config.chosen(Some("--mock")),
+            Path::new("/m/ggml-base.en.bin")
+        );
+        assert_eq!(config.chosen(Some("small")), Path::new("/m/ggml-small.bin"));
+        let config = ModelsConfig {
+            default: Some("tiny.en".into()),
+            ..config
+        };
+        assert_eq!(config.chosen(None), Path::new("/m/ggml-tiny.en.bin"));
+    }
+
+    #[test]
+    fn test_models_command_from_args() {
+        let command = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+            ModelsCommand::from_args(&args)
+        };
+        assert_eq!(command(&[]).unwrap(), ModelsCommand::List);
+        assert_eq!(command(&["list"]).unwrap(), ModelsCommand::List);
+        assert_eq!(
+            command(&["download", "base.en"]).unwrap(),
+            ModelsCommand::Download("base.en".into())
+        );
+        assert_eq!(
+            command(&["default", "small"]).unwrap(),
+            ModelsCommand::Default("small".into())
+        );
+        let usage = command(&["remove"]).unwrap_err().to_string();
+        assert_eq!(usage, ModelsCommand::USAGE);
+        assert!(command(&["upgrade", "base"]).is_err());
+    }
+
+    #[test]
+    fn test_listing_marks_default() {
+        let dir = temp_dir("listing");
+        std::fs::write(dir.join("ggml-tiny.en.bin"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.join("ggml-base.bin"), vec![0u8; 10]).unwrap();
+        let models = installed(&dir).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        let out = listing(&dir, &models, Some("tiny.en"));
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], format!("Models in {}:", dir.display()));
+        assert!(lines[1].starts_with("  base "));
+        assert!(lines[2].starts_with("* tiny.en "));
+        assert_eq!(lines[4], "Available to download:");
+        assert!(!lines[5..].iter().any(|l| l.starts_with("  tiny.en ")));
+        assert!(lines[5..].iter().any(|l| l.starts_with("  base.en ")));
+
+        let empty = listing(&dir, &[], None);
+        assert_eq!(empty.lines().nth(1), Some("  (none)"));
+    }
+
+    #[test]
+    fn test_installed_missing_dir_is_empty() {
+        assert!(
+            installed(Path::new("/nonexistent/conch/models"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_remove_model() {
+        let dir = temp_dir("remove");
+        std::fs::write(dir.join("ggml-base.bin"), b"x").unwrap();
+        remove(&dir, "base").unwrap();
+        let again = remove(&dir, "base");
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(again.unwrap_err().to_string().contains("not installed"));
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(148_897_792), "142 MB");
+        assert_eq!(format_size(3_095_033_483), "2.9 GB");
+    }
+
+    #[test]
+    fn test_set_default_appends_section() {
+        let text = "[send]\nauto = true\n";
+        assert_eq!(
+            set_default_in_toml(text, "small.en"),
+            "[send]\nauto = true\n\n[models]\ndefault = \"small.en\"\n"
+        );
+        assert_eq!(
+            set_default_in_toml("", "base"),
+            "[models]\ndefault = \"base\"\n"
+        );
+    }
+
+    #[test]
+    fn test_set_default_replaces_existing() {
+        let text =
+            "# my config\n[models]\ndir = \"/m\"\ndefault = \"tiny\"\n\n[tts]\nenabled = true\n";
+        assert_eq!(
+            set_default_in_toml(text, "base.en"),
+            "# my config\n[models]\ndir = \"/m\"\ndefault = \"base.en\"\n\n[tts]\nenabled = true\n"
+        );
+    }
+
+    #[test]
+    fn test_set_default_in_file() {
+        let dir = temp_dir("set_default");
+        let path = dir.join("conch.toml");
+        set_default_in_file(&path, "tiny").unwrap();
+        let created = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(created, "[models]\ndefault = \"tiny\"\n");
+        std::fs::write(&path, "# mine\n[models]\ndefault = \"tiny\"\n").unwrap();
+        set_default_in_file(&path, "base.en").unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(text, "# mine\n[models]\ndefault = \"base.en\"\n");
+    }
+
+    #[test]
+    fn test_set_default_inserts_into_section() {
+        let text = "[models]\ndir = \"/m\"\n[tts]\ndefault_voice = 1\n";
+        assert_eq!(
+            set_default_in_toml(text, "base"),
+            "[models]\ndefault = \"base\"\ndir = \"/m\"\n[tts]\ndefault_voice = 1\n"
+        );
+    }
+}
@@ -0,0 +1,209 @@
+// Notify Module - User-facing notifications with severity, a toast, and history
+//
+// Every message is kept in a bounded history so a later failure can't hide
+// an earlier one. The newest message is also shown as a toast: info and
+// warnings expire on their own, errors stay until cleared.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Messages kept in the history.
+pub const HISTORY_LIMIT: usize = 200;
+
+/// How serious a notification is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// How long a toast of this severity stays up; `None` until cleared.
+    pub fn toast_duration(self) -> Option<Duration> {
+        match self {
+            Severity::Info => Some(Duration::from_secs(4)),
+            Severity::Warning => Some(Duration::from_secs(8)),
+            Severity::Error => None,
+        }
+    }
+
+    /// Short tag for the history view.
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warn",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// One notification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notice {
+    pub severity: Severity,
+    pub message: String,
+    pub at: Instant,
+}
+
+/// Notification history plus the current toast.
+#[derive(Debug, Default)]
+pub struct Notifications {
+    history: VecDeque<Notice>,
+    /// Index into `history` of the toast, while one is showing.
+    toast: Option<usize>,
+    /// Warnings and errors pushed since the history was last viewed.
+    unseen: usize,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a notification and show it as the toast.
+    pub fn push(&mut self, severity: Severity, message: impl Into<String>) {
+        self.push_at(severity, message, Instant::now());
+    }
+
+    /// [`push`](Self::push) with an explicit timestamp.
+    pub fn push_at(&mut self, severity: Severity, message: impl Into<String>, at: Instant) {
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(Notice {
+            severity,
+            message: message.into(),
+            at,
+        });
+        self.toast = Some(self.history.len() - 1);
+        if severity >= Severity::Warning {
+            self.unseen += 1;
+        }
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(Severity::Info, message);
+    }
+
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.push(Severity::Warning, message);
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(Severity::Error, message);
+    }
+
+    /// The toast to show at `now`, if it hasn't expired or been cleared.
+    pub fn toast_at(&self, now: Instant) -> Option<&Notice> {
+        let notice = self.history.get(self.toast?)?;
+        match notice.severity.toast_duration() {
+            Some(ttl) if now.duration_since(notice.at) >= ttl => None,
+            _ => Some(notice),
+        }
+    }
+
+    /// The toast to show now.
+    pub fn toast(&self) -> Option<&Notice> {
+        self.toast_at(Instant::now())
+    }
+
+    /// Hide the toast. The message stays in the history.
+    pub fn clear_toast(&mut self) {
+        self.toast = None;
+    }
+
+    /// Every notification, oldest first.
+    pub fn history(&self) -> impl DoubleEndedIterator<Item = &Notice> + ExactSizeIterator {
+        self.history.iter()
+    }
+
+    /// Warnings and errors not yet seen in the history view.
+    pub fn unseen(&self) -> usize {
+        self.unseen
+    }
+
+    /// The user has looked at the history.
+    pub fn mark_seen(&mut self) {
+        self.unseen = 0;
+    }
+}
+
+/// Compact age for the history view: "5s", "3m", "2h".
+pub fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}h", secs / 3600)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_keeps_every_message() {
+        let mut n = Notifications::new();
+        n.warn("model load warning");
+        n.error("Send failed: timeout");
+        let messages: Vec<&str> = n.history().map(|m| m.message.as_str()).collect();
+        assert_eq!(messages, vec!["model load warning", "Send failed: timeout"]);
+        assert_eq!(n.toast().unwrap().message, "Send failed: timeout");
+    }
+
+    #[test]
+    fn test_info_toast_expires() {
+        let mut n = Notifications::new();
+        let t0 = Instant::now();
+        n.push_at(Severity::Info, "Copied to clipboard", t0);
+        assert!(n.toast_at(t0 + Duration::from_secs(1)).is_some());
+        assert!(n.toast_at(t0 + Duration::from_secs(5)).is_none());
+    }
+
+    #[test]
+    fn test_error_toast_sticks_until_cleared() {
+        let mut n = Notifications::new();
+        let t0 = Instant::now();
+        n.push_at(Severity::Error, "Transcription error", t0);
+        assert!(n.toast_at(t0 + Duration::from_secs(3600)).is_some());
+        n.clear_toast();
+        assert!(n.toast().is_none());
+        assert_eq!(n.history().len(), 1);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut n = Notifications::new();
+        for i in 0..HISTORY_LIMIT + 5 {
+            n.info(format!("message {i}"));
+        }
+        assert_eq!(n.history().len(), HISTORY_LIMIT);
+        assert_eq!(n.history().next().unwrap().message, "message 5");
+        assert_eq!(
+            n.toast().unwrap().message,
+            format!("message {}", HISTORY_LIMIT + 4)
+        );
+    }
+
+    #[test]
+    fn test_unseen_counts_warnings_and_errors() {
+        let mut n = Notifications::new();
+        n.info("Prompt discarded");
+        n.warn("No speech detected");
+        n.error("Send failed");
+        assert_eq!(n.unseen(), 2);
+        n.mark_seen();
+        assert_eq!(n.unseen(), 0);
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(Duration::from_secs(5)), "5s");
+        assert_eq!(format_age(Duration::from_secs(125)), "2m");
+        assert_eq!(format_age(Duration::from_secs(7300)), "2h");
+    }
+}
@@ -0,0 +1,446 @@
+// Numbers Module - Spoken numbers and versions written as digits
+//
+// Whisper writes numbers the way they were said about as often as it
+// writes digits, and a prompt about "port forty ninety six" or "one point
+// seventy two" means `4096` and `1.72`. Number words are read the way
+// they're spoken in code: pairs run together ("nineteen ninety nine" is
+// 1999, "eighty eighty" 8080, "four oh four" 404), "point" or "dot" between
+// numbers makes a decimal or a version ("two point three point one" is
+// 2.3.1), and "hundred" and "thousand" multiply. A lone number under ten
+// stays a word in prose ("one of the tests") unless it follows a word like
+// "port" or "version". The words come from the session's language; ones
+// without rules here are left alone.
+
+use serde::Deserialize;
+
+/// `[numbers]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct NumbersConfig {
+    /// Write spoken numbers in prompts as digits.
+    pub enabled: bool,
+}
+
+impl Default for NumbersConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// A language's number words.
+struct Locale {
+    /// Whisper language codes the words are for.
+    codes: &'static [&'static str],
+    /// Numbers below the first of `tens`, each said in one word.
+    units: &'static [(&'static str, u64)],
+    tens: &'static [(&'static str, u64)],
+    /// Whole hundreds said in one word ("doscientos").
+    hundreds: &'static [(&'static str, u64)],
+    /// Words multiplying what's before them.
+    multipliers: &'static [(&'static str, u64)],
+    /// Multipliers that are a number on their own ("mil").
+    lone: &'static [&'static str],
+    /// Words that may join the parts of a number ("ninety and nine").
+    joiners: &'static [&'static str],
+    /// Words between the parts of a decimal or a version.
+    points: &'static [&'static str],
+    /// Zero, but only within a number ("four oh four").
+    inner_zeros: &'static [&'static str],
+    /// Words after which even a lone small number is written as digits.
+    context: &'static [&'static str],
+}
+
+const ENGLISH: Locale = Locale {
+    codes: &["en", "auto"],
+    units: &[
+        ("zero", 0),
+        ("one", 1),
+        ("two", 2),
+        ("three", 3),
+        ("four", 4),
+        ("five", 5),
+        ("six", 6),
+        ("seven", 7),
+        ("eight", 8),
+        ("nine", 9),
+        ("ten", 10),
+        ("eleven", 11),
+        ("twelve", 12),
+        ("thirteen", 13),
+        ("fourteen", 14),
+        ("fifteen", 15),
+        ("sixteen", 16),
+        ("seventeen", 17),
+        ("eighteen", 18),
+        ("nineteen", 19),
+    ],
+    tens: &[
+        ("twenty", 20),
+        ("thirty", 30),
+        ("forty", 40),
+        ("fifty", 50),
+        ("sixty", 60),
+        ("seventy", 70),
+        ("eighty", 80),
+        ("ninety", 90),
+    ],
+    hundreds: &[],
+    multipliers: &[("hundred", 100), ("thousand", 1000), ("million", 1_000_000)],
+    lone: &[],
+    joiners: &["and"],
+    points: &["point", "dot"],
+    inner_zeros: &["oh"],
+    context: &[
+        "port", "line", "lines", "column", "version", "issue", "number", "step", "page", "python",
+        "node", "java",
+    ],
+};
+
+const SPANISH: Locale = Locale {
+    codes: &["es"],
+    units: &[
+        ("cero", 0),
+        ("uno", 1),
+        ("un", 1),
+        ("una", 1),
+        ("dos", 2),
+        ("tres", 3),
+        ("cuatro", 4),
+        ("cinco", 5),
+        ("seis", 6),
+        ("siete", 7),
+        ("ocho", 8),
+        ("nueve", 9),
+        ("diez", 10),
+        ("once", 11),
+        ("doce", 12),
+        ("trece", 13),
+        ("catorce", 14),
+        ("quince", 15),
+        ("dieciséis", 16),
+        ("dieciseis", 16),
+        ("diecisiete", 17),
+        ("dieciocho", 18),
+        ("diecinueve", 19),
+        ("veinte", 20),
+        ("veintiuno", 21),
+        ("veintiún", 21),
+        ("veintidós", 22),
+        ("veintidos", 22),
+        ("veintitrés", 23),
+        ("veintitres", 23),
+        ("veinticuatro", 24),
+        ("veinticinco", 25),
+        ("veintiséis", 26),
+        ("veintiseis", 26),
+        ("veintisiete", 27),
+        ("veintiocho", 28),
+        ("veintinueve", 29),
+    ],
+    tens: &[
+        ("treinta", 30),
+        ("cuarenta", 40),
+        ("cincuenta", 50),
+        ("sesenta", 60),
+        ("setenta", 70),
+        ("ochenta", 80),
+        ("noventa", 90),
+    ],
+    hundreds: &[
+        ("cien", 100),
+        ("ciento", 100),
+        ("doscientos", 200),
+        ("trescientos", 300),
+        ("cuatrocientos", 400),
+        ("quinientos", 500),
+        ("seiscientos", 600),
+        ("setecientos", 700),
+        ("ochocientos", 800),
+        ("novecientos", 900),
+    ],
+    multipliers: &[
+        ("mil", 1000),
+        ("millón", 1_000_000),
+        ("millones", 1_000_000),
+    ],
+    lone: &["mil"],
+    joiners: &["y"],
+    points: &["punto", "coma"],
+    inner_zeros: &[],
+    context: &[
+        "puerto", "línea", "linea", "versión", "version", "número", "numero", "paso",
+    ],
+};
+
+const LOCALES: &[&Locale] = &[&ENGLISH, &SPANISH];
+
+/// What a number word is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Word {
+    Unit(u64),
+    Tens(u64),
+    Hundreds(u64),
+    Multiplier(u64),
+    Joiner,
+    Point,
+    InnerZero,
+}
+
+impl Locale {
+    fn word(&self, word: &str) -> Option<Word> {
+        let find = |table: &[(&str, u64)]| table.iter().find(|(w, _)| *w == word).map(|&(_, n)| n);
+        find(self.units)
+            .map(Word::Unit)
+            .or_else(|| find(self.tens).map(Word::Tens))
+            .or_else(|| find(self.hundreds).map(Word::Hundreds))
+            .or_else(|| find(self.multipliers).map(Word::Multiplier))
+            .or_else(|| self.joiners.contains(&word).then_some(Word::Joiner))
+            .or_else(|| self.points.contains(&word).then_some(Word::Point))
+            .or_else(|| self.inner_zeros.contains(&word).then_some(Word::InnerZero))
+    }
+}
+
+/// A token of the transcript: the word, and the punctuation around it.
+struct Token<'a> {
+    lead: &'a str,
+    /// The word lowercase, split at hyphens ("seventy-two").
+    words: Vec<String>,
+    trail: &'a str,
+}
+
+impl<'a> Token<'a> {
+    fn new(token: &'a str) -> Self {
+        let punctuation = |c: char| !c.is_alphanumeric();
+        let start = token.len() - token.trim_start_matches(punctuation).len();
+        let core = token[start..].trim_end_matches(punctuation);
+        let (lead, trail) = (&token[..start], &token[start + core.len()..]);
+        let words = core
+            .to_lowercase()
+            .split('-')
+            .filter(|w| !w.is_empty())
+            .map(str::to_string)
+            .collect();
+        Self { lead, words, trail }
+    }
+}
+
+/// `text` with the spoken numbers in it written as digits, by the rules of
+/// the language with Whisper code `language`.
+pub fn apply(text: &str, language: &str) -> String {
+    let Some(locale) = LOCALES.iter().find(|l| l.codes.contains(&language)) else {
+        return text.to_string();
+    };
+    // Where each token is in `text`, so the whitespace between stays as is
+    let spans: Vec<(usize, &str)> = text
+        .split_whitespace()
+        .map(|t| (t.as_ptr() as usize - text.as_ptr() as usize, t))
+        .collect();
+    let tokens: Vec<Token> = spans.iter().map(|(_, t)| Token::new(t)).collect();
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        let after_context = i > 0
+            && tokens[i - 1].words.len() == 1
+            && locale.context.contains(&tokens[i - 1].words[0].as_str());
+        match number_at(locale, &tokens[i..], after_context) {
+            Some((digits, taken)) => {
+                let (start, _) = spans[i];
+                let (last_start, last) = spans[i + taken - 1];
+                out.push_str(&text[copied..start]);
+                out.push_str(tokens[i].lead);
+                out.push_str(&digits);
+                out.push_str(tokens[i + taken - 1].trail);
+                copied = last_start + last.len();
+                i += taken;
+            }
+            None => i += 1,
+        }
+    }
+    out.push_str(&text[copied..]);
+    out
+}
+
+/// The number `tokens` start with, as digits, and how many tokens it takes.
+fn number_at(locale: &Locale, tokens: &[Token], after_context: bool) -> Option<(String, usize)> {
+    let (mut digits, mut taken, words) = integer_at(locale, tokens, false)?;
+    let mut decimal = false;
+    // "point seventy two", as many times as a version has parts
+    while taken < tokens.len() && tokens[taken - 1].trail.is_empty() {
+        let point = &tokens[taken];
+        let is_point = point.words.len() == 1 && locale.word(&point.words[0]) == Some(Word::Point);
+        if !is_point || !point.lead.is_empty() || !point.trail.is_empty() {
+            break;
+        }
+        let Some((fraction, n, _)) = integer_at(locale, &tokens[taken + 1..], true) else {
+            break;
+        };
+        digits = format!("{digits}.{fraction}");
+        taken += 1 + n;
+        decimal = true;
+    }
+    // A lone "one" is usually prose
+    let small = words == 1 && digits.parse::<u64>().is_ok_and(|n| n < 10);
+    (decimal || after_context || !small).then_some((digits, taken))
+}
+
+/// The whole number `tokens` start with, as digits, the tokens it takes and
+/// the number words in it. Parts said one after the other are written one
+/// after the other. `inner` allows an inner zero ("oh") first, as after a
+/// point.
+fn integer_at(locale: &Locale, tokens: &[Token], inner: bool) -> Option<(String, usize, usize)> {
+    // Parts already written, and the part being built with its last word
+    let mut digits = String::new();
+    let mut part: Option<(u64, Word)> = None;
+    // After a joiner, which the next word must add to the part
+    let mut joined = false;
+    let mut words = 0;
+    let mut number = None;
+    'tokens: for (t, token) in tokens.iter().enumerate() {
+        if token.words.is_empty() || (t > 0 && !token.lead.is_empty()) {
+            break;
+        }
+        for spoken in &token.words {
+            let Some(word) = locale.word(spoken) else {
+                break 'tokens;
+            };
+            match (word, part) {
+                (Word::Joiner, Some((_, last)))
+                    if !joined && !matches!(last, Word::Unit(_) | Word::InnerZero) =>
+                {
+                    joined = true;
+                    words += 1;
+                    continue;
+                }
+                (Word::Joiner | Word::Point, _) => break 'tokens,
+                (Word::InnerZero, _) if joined || (words == 0 && !inner) => break 'tokens,
+                (Word::InnerZero, _) => {
+                    push_part(&mut digits, part);
+                    part = Some((0, word));
+                }
+                (Word::Multiplier(m), Some((n, _))) if !joined => {
+                    // "two thousand five hundred": only the five is multiplied
+                    let (high, low) = (n - n % m, n % m);
+                    if low == 0 || (high != 0 && !high.is_multiple_of(m * 10)) {
+                        break 'tokens;
+                    }
+                    part = Some((high + low * m, word));
+                }
+                (Word::Multiplier(m), None) if locale.lone.contains(&spoken.as_str()) => {
+                    part = Some((m, word));
+                }
+                (Word::Multiplier(_), _) => break 'tokens,
+                (Word::Unit(v) | Word::Tens(v) | Word::Hundreds(v), Some((n, last)))
+                    if fits(n, last, word, v) =>
+                {
+                    part = Some((n + v, word));
+                }
+                (_, _) if joined => break 'tokens,
+                (Word::Unit(v) | Word::Tens(v) | Word::Hundreds(v), _) => {
+                    push_part(&mut digits, part);
+                    part = Some((v, word));
+                }
+            }
+            joined = false;
+            words += 1;
+        }
+        // A joiner must be followed by more of the number
+        if !joined {
+            let mut whole = digits.clone();
+            push_part(&mut whole, part);
+            number = Some((whole, t + 1, words));
+        }
+        if !token.trail.is_empty() {
+            break;
+        }
+    }
+    number
+}
+
+/// Write the finished `part`, if any, after `digits`.
+fn push_part(digits: &mut String, part: Option<(u64, Word)>) {
+    if let Some((n, _)) = part {
+        digits.push_str(&n.to_string());
+    }
+}
+
+/// Whether `word`, worth `value`, adds to the part so far, which ends in
+/// `last`, rather than starting a new one: "twenty" and "one", "two
+/// hundred" and "five", "mil" and "novecientos".
+fn fits(part: u64, last: Word, word: Word, value: u64) -> bool {
+    match (last, word) {
+        (Word::Tens(_), Word::Unit(u)) => part.is_multiple_of(10) && u < 10,
+        (Word::Hundreds(_), Word::Unit(_) | Word::Tens(_)) => part.is_multiple_of(100),
+        (Word::Multiplier(m), _) => part.is_multiple_of(m) && value < m,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What Whisper writes, and what the prompt should say.
+    const ENGLISH_CORPUS: &[(&str, &str)] = &[
+        ("bump it to one point seventy two", "bump it to 1.72"),
+        ("port forty ninety six", "port 4096"),
+        ("listen on port eighty eighty", "listen on port 8080"),
+        ("it returns four oh four", "it returns 404"),
+        (
+            "upgrade to version two point three point one.",
+            "upgrade to version 2.3.1.",
+        ),
+        (
+            "set the timeout to thirty seconds",
+            "set the timeout to 30 seconds",
+        ),
+        ("Twenty-one tests failed", "21 tests failed"),
+        ("since nineteen ninety nine", "since 1999"),
+        ("in twenty twenty four", "in 2024"),
+        ("retry two hundred and five times", "retry 205 times"),
+        ("limit it to three thousand", "limit it to 3000"),
+        ("use python three", "use python 3"),
+        ("go to line nine", "go to line 9"),
+        ("scale by zero point five", "scale by 0.5"),
+        ("a timeout of one point oh five", "a timeout of 1.05"),
+        ("fix one of the tests", "fix one of the tests"),
+        ("one or two files", "one or two files"),
+        ("open main dot rs", "open main dot rs"),
+        ("oh wait, the other one", "oh wait, the other one"),
+        ("this and that", "this and that"),
+        ("fifty and counting", "50 and counting"),
+        ("ten, twenty, thirty", "10, 20, 30"),
+        ("the hundred tests", "the hundred tests"),
+        ("already 4096", "already 4096"),
+        ("  run on\nport eighty eighty ", "  run on\nport 8080 "),
+        ("", ""),
+    ];
+
+    #[test]
+    fn test_english_corpus() {
+        for &(heard, expected) in ENGLISH_CORPUS {
+            assert_eq!(apply(heard, "en"), expected, "heard {heard:?}");
+        }
+    }
+
+    #[test]
+    fn test_spanish() {
+        assert_eq!(apply("puerto ocho mil ochenta", "es"), "puerto 8080");
+        assert_eq!(
+            apply("versión uno punto setenta y dos", "es"),
+            "versión 1.72"
+        );
+        assert_eq!(apply("mil novecientos noventa y nueve", "es"), "1999");
+        assert_eq!(apply("ciento veinte pruebas", "es"), "120 pruebas");
+        assert_eq!(apply("revisa una función", "es"), "revisa una función");
+    }
+
+    #[test]
+    fn test_other_languages_untouched() {
+        assert_eq!(
+            apply("port forty ninety six", "de"),
+            "port forty ninety six"
+        );
+        assert_eq!(apply("port forty ninety six", "auto"), "port 4096");
+    }
+}
@@ -0,0 +1,116 @@
+// Pacer Module - Decides when the TUI redraws and how long it waits for input
+//
+// Redrawing every frame keeps a core busy even when nothing on screen moves.
+// Instead the TUI redraws when something changed (a key, a bus message, a
+// resize), every tick while something animates (the waveform, a countdown),
+// and otherwise only once a second so relative times stay fresh.
+
+use std::time::{Duration, Instant};
+
+/// Input poll timeout while something animates.
+pub const ACTIVE_TICK: Duration = Duration::from_millis(50);
+/// Input poll timeout while idle. Bus messages wait at most this long.
+pub const IDLE_TICK: Duration = Duration::from_millis(250);
+/// Longest time between redraws while idle.
+pub const IDLE_REFRESH: Duration = Duration::from_secs(1);
+
+/// Tracks whether the screen is out of date.
+#[derive(Debug, Clone)]
+pub struct FramePacer {
+    dirty: bool,
+    /// Whether the last frame was drawn while animating, so the frame after
+    /// an animation ends is drawn too.
+    was_animating: bool,
+    last_draw: Option<Instant>,
+}
+
+impl Default for FramePacer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FramePacer {
+    /// A pacer that draws the first frame right away.
+    pub fn new() -> Self {
+        Self {
+            dirty: true,
+            was_animating: false,
+            last_draw: None,
+        }
+    }
+
+    /// Something on screen changed; draw the next frame.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Whether to draw a frame at `now`.
+    pub fn should_draw(&self, animating: bool, now: Instant) -> bool {
+        self.dirty
+            || animating
+            || self.was_animating
+            || self
+                .last_draw
+                .is_none_or(|at| now.duration_since(at) >= IDLE_REFRESH)
+    }
+
+    /// Record that a frame was drawn at `now`.
+    pub fn drawn(&mut self, animating: bool, now: Instant) {
+        self.dirty = false;
+        self.was_animating = animating;
+        self.last_draw = Some(now);
+    }
+
+    /// How long to wait for input before the next frame.
+    pub fn tick(animating: bool) -> Duration {
+        if animating { ACTIVE_TICK } else { IDLE_TICK }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_frame_is_drawn() {
+        assert!(FramePacer::new().should_draw(false, Instant::now()));
+    }
+
+    #[test]
+    fn test_idle_skips_frames_until_dirty() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new();
+        pacer.drawn(false, start);
+        assert!(!pacer.should_draw(false, start + IDLE_TICK));
+        pacer.mark_dirty();
+        assert!(pacer.should_draw(false, start + IDLE_TICK));
+    }
+
+    #[test]
+    fn test_idle_refresh() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new();
+        pacer.drawn(false, start);
+        assert!(!pacer.should_draw(false, start + IDLE_REFRESH / 2));
+        assert!(pacer.should_draw(false, start + IDLE_REFRESH));
+    }
+
+    #[test]
+    fn test_animation_draws_every_tick_and_once_after() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new();
+        pacer.drawn(true, start);
+        assert!(pacer.should_draw(true, start + ACTIVE_TICK));
+        // The animation stopped: one more frame clears it
+        assert!(pacer.should_draw(false, start + ACTIVE_TICK));
+        pacer.drawn(false, start + ACTIVE_TICK);
+        assert!(!pacer.should_draw(false, start + ACTIVE_TICK * 2));
+    }
+
+    #[test]
+    fn test_tick() {
+        assert_eq!(FramePacer::tick(true), ACTIVE_TICK);
+        assert_eq!(FramePacer::tick(false), IDLE_TICK);
+    }
+}
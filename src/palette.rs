@@ -0,0 +1,125 @@
+// Palette Module - Filterable command palette
+//
+// The palette holds a fixed list of items built when it opens. Typing
+// filters them by case-insensitive substring on the label; the selection
+// moves over the filtered list.
+
+use std::path::PathBuf;
+
+use crate::input::TextInput;
+
+/// What picking a palette item does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteAction {
+    /// Expand the named snippet into the pending prompt.
+    Snippet(String),
+    /// Work in another project directory, with its session.
+    Directory(PathBuf),
+    /// Start a session in the project directory.
+    NewSession,
+    /// Attach an existing session.
+    Session {
+        id: String,
+        slug: Option<String>,
+        directory: Option<PathBuf>,
+    },
+}
+
+/// One entry in the palette.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteItem {
+    pub label: String,
+    /// Shown dimmed after the label, e.g. the snippet template.
+    pub detail: String,
+    pub action: PaletteAction,
+}
+
+/// Palette state while it is open.
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    items: Vec<PaletteItem>,
+    pub query: TextInput,
+    /// Index into the filtered items.
+    selected: usize,
+}
+
+impl Palette {
+    pub fn new(items: Vec<PaletteItem>) -> Self {
+        Self {
+            items,
+            query: TextInput::new(),
+            selected: 0,
+        }
+    }
+
+    /// Items whose label contains the query, in their original order.
+    pub fn filtered(&self) -> Vec<&PaletteItem> {
+        let query = self.query.text().to_lowercase();
+        self.items
+            .iter()
+            .filter(|item| item.label.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    /// Index of the selection in [`filtered`](Self::filtered), kept in range
+    /// as the query changes.
+    pub fn selected(&self) -> usize {
+        self.selected.min(self.filtered().len().saturating_sub(1))
+    }
+
+    pub fn selected_item(&self) -> Option<&PaletteItem> {
+        self.filtered().get(self.selected()).copied()
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected().saturating_sub(1);
+    }
+
+    pub fn move_down(&mut self) {
+        let last = self.filtered().len().saturating_sub(1);
+        self.selected = (self.selected() + 1).min(last);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn palette() -> Palette {
+        let item = |label: &str| PaletteItem {
+            label: label.into(),
+            detail: String::new(),
+            action: PaletteAction::Snippet(label.into()),
+        };
+        Palette::new(vec![item("review"), item("explain"), item("code-review")])
+    }
+
+    #[test]
+    fn test_filter_by_query() {
+        let mut palette = palette();
+        assert_eq!(palette.filtered().len(), 3);
+        "REV".chars().for_each(|c| palette.query.insert(c));
+        let labels: Vec<&str> = palette
+            .filtered()
+            .iter()
+            .map(|i| i.label.as_str())
+            .collect();
+        assert_eq!(labels, vec!["review", "code-review"]);
+    }
+
+    #[test]
+    fn test_selection_follows_filter() {
+        let mut palette = palette();
+        palette.move_down();
+        palette.move_down();
+        palette.move_down();
+        assert_eq!(palette.selected_item().unwrap().label, "code-review");
+        // Narrowing the list keeps the selection on a visible item
+        "explain".chars().for_each(|c| palette.query.insert(c));
+        assert_eq!(palette.selected_item().unwrap().label, "explain");
+        palette.move_up();
+        assert_eq!(palette.selected(), 0);
+        palette.query.insert('x');
+        assert!(palette.selected_item().is_none());
+    }
+}
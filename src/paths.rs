@@ -0,0 +1,366 @@
+// Paths Module - Where conch keeps its config, data, state and cache
+//
+// Files live where the platform expects them (XDG directories on Linux,
+// ~/Library on macOS, %APPDATA% on Windows), resolved with the `directories`
+// crate:
+//
+//   config  conch.toml (a conch.toml in the working directory wins)
+//   data    models/, history.jsonl, focus.json, wake.json
+//   state   conch.log
+//   cache   scratch files that can be thrown away
+//   runtime conch.sock, the control socket (in state where there's no runtime)
+//
+// `--data-dir` puts data, state and cache under one directory and
+// `--log-file` moves the log. Older versions wrote their files, and looked
+// for the model, in the working directory; [`migrate`] moves them to their
+// new homes the first time the TUI starts.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{Result, anyhow};
+use directories::ProjectDirs;
+
+use crate::config::Config;
+
+/// Config file name, in the config directory or the working directory.
+pub const CONFIG_FILE: &str = "conch.toml";
+
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+static LOG_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Keep data, state and cache under `dir` (`--data-dir`). Takes effect for
+/// defaults resolved afterwards, so call it before loading the config.
+pub fn set_data_dir(dir: PathBuf) {
+    let _ = DATA_DIR.set(dir);
+}
+
+/// Write the debug log to `path` (`--log-file`).
+pub fn set_log_file(path: PathBuf) {
+    let _ = LOG_FILE.set(path);
+}
+
+fn project() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "", "conch")
+}
+
+/// Resolve a platform directory, or the working directory without a home.
+fn platform(dir: impl FnOnce(&ProjectDirs) -> PathBuf) -> PathBuf {
+    project()
+        .map(|p| dir(&p))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Directory holding `conch.toml`.
+pub fn config_dir() -> PathBuf {
+    platform(|p| p.config_dir().to_path_buf())
+}
+
+/// Directory for files worth keeping: models, transcript history, the wake
+/// word model.
+pub fn data_dir() -> PathBuf {
+    match DATA_DIR.get() {
+        Some(dir) => dir.clone(),
+        None => platform(|p| p.data_dir().to_path_buf()),
+    }
+}
+
+/// Directory for the log. Platforms without a state directory use the
+/// local data directory.
+pub fn state_dir() -> PathBuf {
+    match DATA_DIR.get() {
+        Some(dir) => dir.join("state"),
+        None => platform(|p| {
+            p.state_dir()
+                .unwrap_or_else(|| p.data_local_dir())
+                .to_path_buf()
+        }),
+    }
+}
+
+/// Directory for files that can be deleted at any time.
+pub fn cache_dir() -> PathBuf {
+    match DATA_DIR.get() {
+        Some(dir) => dir.join("cache"),
+        None => platform(|p| p.cache_dir().to_path_buf()),
+    }
+}
+
+/// The control socket: in the runtime directory, private to the user, or
+/// the state directory on platforms without one. A named pipe on Windows.
+pub fn control_socket() -> PathBuf {
+    if cfg!(windows) {
+        return PathBuf::from(r"\\.\pipe\conch");
+    }
+    let runtime = match DATA_DIR.get() {
+        Some(_) => None,
+        None => project().and_then(|p| p.runtime_dir().map(Path::to_path_buf)),
+    };
+    runtime.unwrap_or_else(state_dir).join("conch.sock")
+}
+
+/// The config file to use without `--config`: `conch.toml` in the working
+/// directory if there is one, so projects can carry their own, otherwise the
+/// one in the config directory.
+pub fn config_file() -> PathBuf {
+    let local = Path::new(CONFIG_FILE);
+    if local.exists() {
+        local.to_path_buf()
+    } else {
+        config_dir().join(CONFIG_FILE)
+    }
+}
+
+/// The debug log.
+pub fn log_file() -> PathBuf {
+    match LOG_FILE.get() {
+        Some(path) => path.clone(),
+        None => state_dir().join("conch.log"),
+    }
+}
+
+/// Where downloaded models go by default.
+pub fn models_dir() -> PathBuf {
+    data_dir().join("models")
+}
+
+/// Default transcript history file.
+pub fn history_file() -> PathBuf {
+    data_dir().join("history.jsonl")
+}
+
+/// Pinned and forgotten focus entries of each session.
+pub fn focus_file() -> PathBuf {
+    data_dir().join("focus.json")
+}
+
+/// Default wake word model file.
+pub fn wake_model_file() -> PathBuf {
+    data_dir().join("wake.json")
+}
+
+/// Left in the state directory once [`migrate`] has run.
+pub fn migration_marker() -> PathBuf {
+    state_dir().join("migrated")
+}
+
+/// The project directory sessions are looked up for: `dir` (`--dir` or
+/// `[connection] directory`), a leading `~` expanded, or else the working
+/// directory. Symlinks are resolved, as in the directory OpenCode records.
+pub fn project_dir(dir: Option<&Path>) -> Result<PathBuf> {
+    let dir = match dir {
+        Some(dir) => expand_home(dir),
+        None => std::env::current_dir()?,
+    };
+    let resolved = dir
+        .canonicalize()
+        .map_err(|e| anyhow!("Project directory '{}': {}", dir.display(), e))?;
+    if !resolved.is_dir() {
+        return Err(anyhow!(
+            "Project directory '{}' is not a directory",
+            dir.display()
+        ));
+    }
+    Ok(resolved)
+}
+
+/// `path` for display, with the home directory written as `~`.
+pub fn home_relative(path: &Path) -> String {
+    let rest = home().and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf));
+    match rest {
+        Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Some(rest) => format!("~/{}", rest.display()),
+        None => path.display().to_string(),
+    }
+}
+
+fn home() -> Option<PathBuf> {
+    directories::BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf())
+}
+
+/// `path` with a leading `~` replaced by the home directory.
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), home()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Create the data and state directories, and the log file's directory.
+pub fn create_dirs() -> Result<()> {
+    let log = log_file();
+    let dirs = [
+        Some(data_dir()),
+        Some(state_dir()),
+        log.parent().map(Path::to_path_buf),
+    ];
+    for dir in dirs
+        .into_iter()
+        .flatten()
+        .filter(|d| !d.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| anyhow!("Failed to create '{}': {}", dir.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Move files older versions wrote to `cwd` to where `config` expects them,
+/// Whisper models included. A file is only moved to a default location,
+/// never over an existing file. Runs once: later calls, from whatever
+/// directory, do nothing. Returns a line per file moved.
+pub fn migrate(cwd: &Path, config: &Config) -> Result<Vec<String>> {
+    let marker = migration_marker();
+    if marker.exists() {
+        return Ok(Vec::new());
+    }
+    let mut moves = vec![
+        (
+            cwd.join("conch-history.jsonl"),
+            &config.history.path,
+            history_file(),
+        ),
+        (
+            cwd.join("conch-wake.json"),
+            &config.wake.model,
+            wake_model_file(),
+        ),
+    ];
+    // A path set in the config is left alone
+    moves.retain(|(_, configured, default)| *configured == default);
+    let mut moves: Vec<(PathBuf, PathBuf)> =
+        moves.into_iter().map(|(from, _, to)| (from, to)).collect();
+    moves.push((cwd.join("conch.log"), log_file()));
+    if config.models.dir() == models_dir() {
+        moves.extend(model_moves(cwd, &models_dir())?);
+    }
+    let mut moved = Vec::new();
+    for (from, to) in moves {
+        if move_file(&from, &to)? {
+            moved.push(format!("Moved {} to {}", from.display(), to.display()));
+        }
+    }
+    std::fs::write(&marker, "")
+        .map_err(|e| anyhow!("Failed to write '{}': {}", marker.display(), e))?;
+    Ok(moved)
+}
+
+/// The Whisper models (`ggml-*.bin`) in `dir`, each with where it goes in
+/// `models`.
+fn model_moves(dir: &Path, models: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let mut moves = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let name = entry?.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if name.starts_with("ggml-") && name.ends_with(".bin") {
+            moves.push((dir.join(name), models.join(name)));
+        }
+    }
+    moves.sort();
+    Ok(moves)
+}
+
+/// Move `from` to `to` if `from` exists and `to` doesn't, creating `to`'s
+/// directory. Falls back to copying across filesystems. Whether it moved.
+fn move_file(from: &Path, to: &Path) -> Result<bool> {
+    if !from.is_file() || to.exists() {
+        return Ok(false);
+    }
+    if let Some(dir) = to.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    if std::fs::rename(from, to).is_err() {
+        std::fs::copy(from, to)
+            .map_err(|e| anyhow!("Failed to move '{}': {}", from.display(), e))?;
+        std::fs::remove_file(from)?;
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_under_data_dir() {
+        assert_eq!(models_dir(), data_dir().join("models"));
+        assert_eq!(history_file().parent(), Some(data_dir().as_path()));
+        assert!(log_file().ends_with("conch.log"));
+    }
+
+    #[test]
+    fn test_project_dir() {
+        let dir = std::env::temp_dir().join(format!("conch_project_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let resolved = project_dir(Some(&dir)).unwrap();
+        assert_eq!(resolved, dir.canonicalize().unwrap());
+        assert!(project_dir(Some(&dir.join("missing"))).is_err());
+        std::fs::write(dir.join("file"), "").unwrap();
+        let err = project_dir(Some(&dir.join("file"))).unwrap_err();
+        assert!(err.to_string().contains("is not a directory"));
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(project_dir(None).is_ok());
+    }
+
+    #[test]
+    fn test_home_relative() {
+        if let Some(home) = home() {
+            assert_eq!(home_relative(&home.join("src/conch")), "~/src/conch");
+            assert_eq!(home_relative(&home), "~");
+            assert_eq!(expand_home(Path::new("~/src")), home.join("src"));
+        }
+        assert_eq!(home_relative(Path::new("/srv/app")), "/srv/app");
+    }
+
+    #[test]
+    fn test_move_file_never_overwrites() {
+        let dir = std::env::temp_dir().join(format!("conch_paths_{}", std::process::id()));
+        let from = dir.join("conch-history.jsonl");
+        let to = dir.join("data").join("history.jsonl");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert!(!move_file(&from, &to).unwrap());
+        std::fs::write(&from, "old").unwrap();
+        assert!(move_file(&from, &to).unwrap());
+        assert!(!from.exists());
+        assert_eq!(std::fs::read_to_string(&to).unwrap(), "old");
+
+        std::fs::write(&from, "older").unwrap();
+        assert!(!move_file(&from, &to).unwrap());
+        assert_eq!(std::fs::read_to_string(&to).unwrap(), "old");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_models_move_to_models_dir() {
+        let dir = std::env::temp_dir().join(format!("conch_models_{}", std::process::id()));
+        let models = dir.join("data").join("models");
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "ggml-base.en.bin",
+            "ggml-small.bin",
+            "notes.bin",
+            "ggml-base.en.txt",
+        ] {
+            std::fs::write(dir.join(name), name).unwrap();
+        }
+        let moves = model_moves(&dir, &models).unwrap();
+        let names: Vec<&Path> = moves.iter().map(|(from, _)| from.as_path()).collect();
+        let base = dir.join("ggml-base.en.bin");
+        assert_eq!(
+            names,
+            vec![base.as_path(), dir.join("ggml-small.bin").as_path()]
+        );
+        assert_eq!(moves[0].1, models.join("ggml-base.en.bin"));
+        for (from, to) in &moves {
+            assert!(move_file(from, to).unwrap());
+        }
+        let moved = std::fs::read_to_string(models.join("ggml-base.en.bin")).unwrap();
+        assert_eq!(moved, "ggml-base.en.bin");
+        assert!(!base.exists());
+        assert!(dir.join("notes.bin").exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,205 @@
+// Preview Module - A few lines of the focused file
+//
+// When the focus pointer sits on a File entry the TUI shows a small preview
+// under the focus stack, so "refactor it" can be checked against the right
+// file before it's said. The preview starts at the region OpenCode's last
+// `edit` of the file touched, found by searching the file for the edit's new
+// text, or at the top of the file when there's no edit to find.
+
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::transport::ToolEvent;
+
+/// Files larger than this aren't previewed.
+pub const MAX_FILE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// `[preview]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PreviewConfig {
+    /// Show the preview pane for File entries.
+    pub enabled: bool,
+    /// Lines of the file to show.
+    pub lines: usize,
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lines: 6,
+        }
+    }
+}
+
+/// The latest change OpenCode made to a file.
+#[derive(Debug, Clone, PartialEq)]
+struct Change {
+    /// Counts changes, so a preview can tell it is stale.
+    serial: u64,
+    /// The new text of an `edit`; `None` for a whole-file `write`.
+    text: Option<String>,
+}
+
+/// The latest `edit` or `write` of each file, from completed tool calls.
+#[derive(Debug, Clone, Default)]
+pub struct LastEdits {
+    changes: HashMap<PathBuf, Change>,
+    serial: u64,
+}
+
+impl LastEdits {
+    /// Remember the change `event` made, if it is a completed edit or write.
+    pub fn record(&mut self, event: &ToolEvent) {
+        if event.state != "completed" {
+            return;
+        }
+        let text = match event.tool.as_str() {
+            "edit" => event.input["newString"].as_str().map(str::to_string),
+            "write" => None,
+            _ => return,
+        };
+        let Some(path) = event.input["filePath"].as_str() else {
+            return;
+        };
+        self.serial += 1;
+        let change = Change {
+            serial: self.serial,
+            text,
+        };
+        self.changes.insert(PathBuf::from(path), change);
+    }
+}
+
+/// The lines shown for one file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preview {
+    pub path: PathBuf,
+    /// Serial of the change the preview was loaded after.
+    serial: u64,
+    /// Line numbers (1-based) and text.
+    pub lines: Vec<(usize, String)>,
+    /// Line numbers the last edit touched.
+    pub edited: Option<RangeInclusive<usize>>,
+    /// Why the file couldn't be shown.
+    pub error: Option<String>,
+}
+
+impl Preview {
+    /// Read up to `count` lines of `path`, around its last edit in `edits`.
+    pub fn load(path: &Path, edits: &LastEdits, count: usize) -> Self {
+        let change = edits.changes.get(path);
+        let mut preview = Self {
+            path: path.to_path_buf(),
+            serial: change.map_or(0, |c| c.serial),
+            lines: Vec::new(),
+            edited: None,
+            error: None,
+        };
+        let text = match read_text(path) {
+            Ok(text) => text,
+            Err(e) => {
+                preview.error = Some(e);
+                return preview;
+            }
+        };
+        let edit = change.and_then(|c| c.text.as_deref());
+        preview.edited = edit.and_then(|edit| find_region(&text, edit));
+        // One line of context above the edit
+        let first = preview
+            .edited
+            .as_ref()
+            .map_or(1, |r| r.start().saturating_sub(1).max(1));
+        preview.lines = text
+            .lines()
+            .enumerate()
+            .skip(first - 1)
+            .take(count)
+            .map(|(i, line)| (i + 1, line.replace('\t', "    ")))
+            .collect();
+        preview
+    }
+
+    /// Whether this preview still shows `path` as of its last change.
+    pub fn is_current(&self, path: &Path, edits: &LastEdits) -> bool {
+        self.path == path && self.serial == edits.changes.get(path).map_or(0, |c| c.serial)
+    }
+}
+
+fn read_text(path: &Path) -> Result<String, String> {
+    let size = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size > MAX_FILE_BYTES {
+        return Err(format!("too large to preview ({} KB)", size / 1024));
+    }
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    if bytes.contains(&0) {
+        return Err("binary file".into());
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Line numbers (1-based) of the first place `edit` appears in `text`.
+fn find_region(text: &str, edit: &str) -> Option<RangeInclusive<usize>> {
+    let edit = edit.trim_matches('\n');
+    if edit.trim().is_empty() {
+        return None;
+    }
+    let at = text.find(edit)?;
+    let start = text[..at].matches('\n').count() + 1;
+    Some(start..=start + edit.matches('\n').count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(path: &Path, new_string: &str) -> ToolEvent {
+        ToolEvent {
+            call_id: None,
+            session_id: None,
+            message_id: None,
+            tool: "edit".into(),
+            input: serde_json::json!({ "filePath": path, "newString": new_string }),
+            state: "completed".into(),
+            metadata: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_find_region() {
+        let text = "fn a() {}\n\nfn b() {\n    todo!()\n}\n";
+        assert_eq!(find_region(text, "fn b() {\n    todo!()"), Some(3..=4));
+        assert_eq!(find_region(text, "\nfn a() {}\n"), Some(1..=1));
+        assert_eq!(find_region(text, "fn c()"), None);
+        assert_eq!(find_region(text, "  \n"), None);
+    }
+
+    #[test]
+    fn test_preview_follows_last_edit() {
+        let path = std::env::temp_dir().join(format!("conch_preview_{}.rs", std::process::id()));
+        let text: String = (1..=20).map(|i| format!("line {i}\n")).collect();
+        std::fs::write(&path, text).unwrap();
+
+        let mut edits = LastEdits::default();
+        let preview = Preview::load(&path, &edits, 3);
+        assert_eq!(preview.lines[0], (1, "line 1".to_string()));
+        assert_eq!(preview.lines.len(), 3);
+        assert!(preview.is_current(&path, &edits));
+
+        edits.record(&edit(&path, "line 10\nline 11"));
+        assert!(!preview.is_current(&path, &edits));
+        let preview = Preview::load(&path, &edits, 3);
+        assert_eq!(preview.edited, Some(10..=11));
+        let numbers: Vec<usize> = preview.lines.iter().map(|(n, _)| *n).collect();
+        assert_eq!(numbers, vec![9, 10, 11]);
+        std::fs::remove_file(&path).ok();
+
+        let missing = Preview::load(Path::new("/nonexistent/file.rs"), &edits, 3);
+        assert!(missing.error.is_some());
+        assert!(missing.lines.is_empty());
+    }
+}
@@ -0,0 +1,40 @@
+// Profiling Module - Flamegraphs of where conch spends its time
+//
+// The hot paths (handing audio from the device callback to the buffers,
+// RMS for the waveform, drawing a frame, transcribing, parsing SSE events)
+// are instrumented with `tracing` spans. Without a subscriber they cost a
+// check of a cached flag; built with the `profiling` feature, `--profile`
+// records them with `tracing-flame` as folded stacks, one per thread, that
+// `inferno-flamegraph` (or `flamegraph.pl`) turns into an SVG.
+
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+
+/// Flushes the recorded spans to the file when dropped, so keep it alive
+/// until conch exits.
+pub struct Profile {
+    #[cfg(feature = "profiling")]
+    _guard: tracing_flame::FlushGuard<std::io::BufWriter<std::fs::File>>,
+}
+
+/// Record every span to `path` as folded stacks, until the returned
+/// [`Profile`] is dropped.
+#[cfg(feature = "profiling")]
+pub fn start(path: &Path) -> Result<Profile> {
+    use tracing_subscriber::layer::SubscriberExt as _;
+
+    let (layer, guard) = tracing_flame::FlameLayer::with_file(path)
+        .map_err(|e| anyhow!("Can't write a profile to {}: {e}", path.display()))?;
+    let subscriber = tracing_subscriber::registry().with(layer.with_empty_samples(false));
+    tracing::subscriber::set_global_default(subscriber)
+        .map_err(|e| anyhow!("Profiling already started: {e}"))?;
+    Ok(Profile { _guard: guard })
+}
+
+#[cfg(not(feature = "profiling"))]
+pub fn start(_path: &Path) -> Result<Profile> {
+    Err(anyhow!(
+        "conch was built without profiling (rebuild with --features profiling)"
+    ))
+}
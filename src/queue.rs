@@ -0,0 +1,150 @@
+// Queue Module - Recordings waiting to be transcribed
+//
+// Finished recordings go to the transcription thread one after another, so
+// while Whisper works on one, the next ones wait their turn. Each job has a
+// ticket shared between the TUI and the thread: the TUI lists the jobs still
+// waiting and can cancel one, and the thread skips a cancelled job when it
+// gets to it. A job the thread has started can't be cancelled, since Whisper
+// can't be interrupted mid-run.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+
+const QUEUED: u8 = 0;
+const RUNNING: u8 = 1;
+const DONE: u8 = 2;
+const CANCELLED: u8 = 3;
+
+/// A job's place in the queue, shared with the thread that runs it.
+#[derive(Debug, Clone)]
+pub struct Ticket {
+    pub id: u64,
+    state: Arc<AtomicU8>,
+}
+
+impl Ticket {
+    /// Take the job up, unless it was cancelled. The job counts as running
+    /// until the returned guard is dropped.
+    pub fn start(&self) -> Option<Running> {
+        self.state
+            .compare_exchange(QUEUED, RUNNING, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| Running(Arc::clone(&self.state)))
+    }
+
+    /// Whether the job is still waiting its turn.
+    pub fn is_waiting(&self) -> bool {
+        self.state.load(Ordering::Acquire) == QUEUED
+    }
+
+    fn is_finished(&self) -> bool {
+        matches!(self.state.load(Ordering::Acquire), DONE | CANCELLED)
+    }
+}
+
+/// A job being transcribed; it's done once this is dropped.
+#[derive(Debug)]
+pub struct Running(Arc<AtomicU8>);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.0.store(DONE, Ordering::Release);
+    }
+}
+
+/// A job handed to the transcription thread, with what it's for.
+#[derive(Debug, Clone)]
+pub struct QueuedJob<K> {
+    pub ticket: Ticket,
+    pub kind: K,
+    /// Length of the recording.
+    pub audio: Duration,
+}
+
+/// The jobs handed to the transcription thread, oldest first.
+#[derive(Debug)]
+pub struct TranscriptionQueue<K> {
+    jobs: Vec<QueuedJob<K>>,
+    next_id: u64,
+}
+
+impl<K> Default for TranscriptionQueue<K> {
+    fn default() -> Self {
+        Self {
+            jobs: Vec::new(),
+            next_id: 1,
+        }
+    }
+}
+
+impl<K> TranscriptionQueue<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a job for `audio` of recording, returning the ticket to send
+    /// along with it.
+    pub fn push(&mut self, kind: K, audio: Duration) -> Ticket {
+        self.jobs.retain(|job| !job.ticket.is_finished());
+        let ticket = Ticket {
+            id: self.next_id,
+            state: Arc::new(AtomicU8::new(QUEUED)),
+        };
+        self.next_id += 1;
+        self.jobs.push(QueuedJob {
+            ticket: ticket.clone(),
+            kind,
+            audio,
+        });
+        ticket
+    }
+
+    /// Jobs the thread hasn't started yet, oldest first.
+    pub fn waiting(&self) -> impl Iterator<Item = &QueuedJob<K>> {
+        self.jobs.iter().filter(|job| job.ticket.is_waiting())
+    }
+
+    /// Cancel job `id` if it's still waiting, returning it.
+    pub fn cancel(&mut self, id: u64) -> Option<QueuedJob<K>> {
+        let index = self.jobs.iter().position(|job| job.ticket.id == id)?;
+        self.jobs[index]
+            .ticket
+            .state
+            .compare_exchange(QUEUED, CANCELLED, Ordering::AcqRel, Ordering::Acquire)
+            .ok()?;
+        Some(self.jobs.remove(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waiting_jobs_can_be_cancelled() {
+        let mut queue = TranscriptionQueue::new();
+        let first = queue.push("prompt", Duration::from_secs(3));
+        let second = queue.push("answer", Duration::from_secs(1));
+        let third = queue.push("prompt", Duration::from_secs(5));
+        assert_eq!(queue.waiting().count(), 3);
+
+        // The thread takes up the first; it can't be cancelled any more
+        let running = first.start().unwrap();
+        let waiting: Vec<u64> = queue.waiting().map(|job| job.ticket.id).collect();
+        assert_eq!(waiting, vec![second.id, third.id]);
+        assert!(queue.cancel(first.id).is_none());
+
+        let cancelled = queue.cancel(second.id).unwrap();
+        assert_eq!(cancelled.kind, "answer");
+        assert!(queue.cancel(second.id).is_none());
+        drop(running);
+
+        // The thread skips the cancelled job and runs the next
+        assert!(second.start().is_none());
+        assert!(third.start().is_some());
+        assert_eq!(queue.waiting().count(), 0);
+        queue.push("prompt", Duration::from_secs(2));
+        assert_eq!(queue.jobs.len(), 1);
+    }
+}
@@ -0,0 +1,121 @@
+// Reload Module - Watches conch.toml and applies edits without a restart
+//
+// The config file is checked about once a second. When it changes, the new
+// config is parsed; if it is valid, sections that are only read as they are
+// used (send gating, snippets, tool rules, limits, ...) take effect at once.
+// Sections that set up devices or long-lived workers at startup are reported
+// so the TUI can ask for a restart.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::Result;
+
+use crate::config::Config;
+
+/// How often the config file is checked for changes.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What identifies a version of the file: modification time and length.
+type Stamp = Option<(SystemTime, u64)>;
+
+/// Polls the config file for changes.
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    stamp: Stamp,
+    last_check: Instant,
+}
+
+impl ConfigWatcher {
+    /// Watch `path`, taking its current contents as already loaded.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            stamp: stamp(&path),
+            path,
+            last_check: Instant::now(),
+        }
+    }
+
+    /// The reloaded config if the file changed since the last check, or why
+    /// it couldn't be loaded. Checks at most every [`CHECK_INTERVAL`].
+    pub fn poll(&mut self, now: Instant) -> Option<Result<Config>> {
+        if now.duration_since(self.last_check) < CHECK_INTERVAL {
+            return None;
+        }
+        self.last_check = now;
+        let current = stamp(&self.path);
+        if current == self.stamp {
+            return None;
+        }
+        self.stamp = current;
+        Some(Config::load(&self.path))
+    }
+}
+
+fn stamp(path: &Path) -> Stamp {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// Changed sections that only take effect after a restart, as written in
+/// the config file.
+pub fn restart_sections(old: &Config, new: &Config) -> Vec<&'static str> {
+    [
+        ("[audio]", old.audio != new.audio),
+        ("[models]", old.models != new.models),
+        ("[wake]", old.wake != new.wake),
+        ("[tts]", old.tts != new.tts),
+        ("[[hooks]]", old.hooks != new.hooks),
+        ("[git]", old.git != new.git),
+        ("[trigger]", old.trigger != new.trigger),
+        ("[control]", old.control != new.control),
+        ("[vocabulary]", old.vocabulary != new.vocabulary),
+        ("[stt] server", old.stt.server != new.stt.server),
+        (
+            "[stt] speech gate",
+            old.stt.speech_gate() != new.stt.speech_gate(),
+        ),
+        ("[connection]", old.connection != new.connection),
+    ]
+    .into_iter()
+    .filter_map(|(section, changed)| changed.then_some(section))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restart_sections() {
+        let old = Config::default();
+        let new = Config::from_toml_str(
+            "[audio]\ndevice = \"USB\"\n[send]\nmin_confidence = 0.5\n[wake]\nenabled = true",
+        )
+        .unwrap();
+        assert_eq!(restart_sections(&old, &new), vec!["[audio]", "[wake]"]);
+        assert!(restart_sections(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_poll_sees_changes() {
+        let path = std::env::temp_dir().join(format!("conch_reload_{}.toml", std::process::id()));
+        std::fs::write(&path, "[send]\nauto = false\n").unwrap();
+        let start = Instant::now();
+        let mut watcher = ConfigWatcher::new(&path);
+        assert!(watcher.poll(start + CHECK_INTERVAL).is_none());
+
+        std::fs::write(&path, "[send]\nauto = true\ncancel_secs = 2.0\n").unwrap();
+        // Not checked again before the interval is up
+        assert!(watcher.poll(start + CHECK_INTERVAL).is_none());
+        let config = watcher.poll(start + CHECK_INTERVAL * 2).unwrap().unwrap();
+        assert!(config.send.auto);
+        assert!(watcher.poll(start + CHECK_INTERVAL * 3).is_none());
+
+        std::fs::write(&path, "[send]\nauto = \"maybe\"\n").unwrap();
+        assert!(watcher.poll(start + CHECK_INTERVAL * 4).unwrap().is_err());
+        std::fs::remove_file(&path).ok();
+    }
+}
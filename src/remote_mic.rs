@@ -0,0 +1,249 @@
+// Remote Mic Module - Recording from a microphone on another machine
+//
+// When OpenCode and the repository live on a dev server, the TUI runs there
+// too, over SSH, but the microphone is on the laptop. `conch mic` runs on the
+// laptop and streams its mic to the TUI over TCP as 16 kHz 16-bit PCM, after
+// a short greeting line; with `[audio] listen` set, the TUI takes its audio
+// from that connection instead of opening a device. One forwarder is heard
+// at a time. While none is connected the source is silent, and the forwarder
+// keeps reconnecting when the connection drops.
+
+use std::io::{ErrorKind, Read as _, Write as _};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+
+use crate::audio::{self, AudioConfig, AudioSource, SampleSink, resample};
+use crate::fake_audio::FakeSource;
+use crate::remote_stt::{SAMPLE_RATE, decode_pcm, encode_pcm};
+
+/// Where `conch mic` streams to unless told otherwise.
+pub const DEFAULT_ADDR: &str = "127.0.0.1:4101";
+/// First line of every stream, so a stray connection isn't heard as audio.
+const GREETING: &[u8] = b"conch-mic 1\n";
+/// How often the listener checks for a connection, or for being dropped.
+const POLL: Duration = Duration::from_millis(100);
+/// Wait between attempts to reach the TUI.
+const RETRY: Duration = Duration::from_secs(2);
+/// Chunks of audio the forwarder holds while the connection is slow;
+/// newer ones are dropped beyond this.
+const BACKLOG: usize = 100;
+
+/// Audio streamed in by `conch mic`.
+pub struct NetSource {
+    listener: Option<TcpListener>,
+    addr: SocketAddr,
+    connected: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl NetSource {
+    /// Listen on `addr` for a `conch mic` to connect.
+    pub fn listen(addr: &str) -> Result<Self> {
+        let listener =
+            TcpListener::bind(addr).map_err(|e| anyhow!("Can't listen on {addr}: {e}"))?;
+        Ok(Self {
+            addr: listener.local_addr()?,
+            listener: Some(listener),
+            connected: Arc::default(),
+            stop: Arc::default(),
+            worker: None,
+        })
+    }
+
+    /// Where the source is listening.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl AudioSource for NetSource {
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn start(&mut self, sink: SampleSink) -> Result<()> {
+        let listener = self
+            .listener
+            .take()
+            .ok_or_else(|| anyhow!("The remote mic is already started"))?;
+        listener.set_nonblocking(true)?;
+        let connected = Arc::clone(&self.connected);
+        let stop = Arc::clone(&self.stop);
+        self.worker = Some(std::thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        // A dropped or stray connection just ends; the next
+                        // one is waited for
+                        let _ = receive(stream, &sink, &connected, &stop);
+                        connected.store(false, Ordering::Relaxed);
+                    }
+                    Err(_) => std::thread::sleep(POLL),
+                }
+            }
+        }));
+        Ok(())
+    }
+
+    fn unavailable(&self) -> Option<String> {
+        (!self.connected.load(Ordering::Relaxed)).then(|| {
+            format!(
+                "No mic connected \u{2014} run `conch mic --to {}` where the mic is",
+                self.addr
+            )
+        })
+    }
+}
+
+impl Drop for NetSource {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Pass the audio of one `conch mic` connection to `sink` until it ends.
+fn receive(
+    mut stream: TcpStream,
+    sink: &SampleSink,
+    connected: &AtomicBool,
+    stop: &AtomicBool,
+) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(RETRY))?;
+    let mut greeting = [0; GREETING.len()];
+    stream.read_exact(&mut greeting)?;
+    if greeting != GREETING {
+        return Err(anyhow!("not a conch mic"));
+    }
+    connected.store(true, Ordering::Relaxed);
+    stream.set_read_timeout(Some(POLL))?;
+    let mut buf = [0; 4096];
+    // A sample split between two reads
+    let mut odd = None;
+    while !stop.load(Ordering::Relaxed) {
+        let n = match stream.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let mut bytes: Vec<u8> = odd.take().into_iter().collect();
+        bytes.extend_from_slice(&buf[..n]);
+        if bytes.len() % 2 == 1 {
+            odd = bytes.pop();
+        }
+        sink.push(decode_pcm(&bytes));
+    }
+    Ok(())
+}
+
+/// `conch mic [--to <addr>] [--fake-audio <spec>]`: stream the mic chosen
+/// in `config`, or a stand-in, to a conch TUI elsewhere.
+pub fn run(args: &[String], config: &AudioConfig) -> Result<()> {
+    let flag = |flag: &str| {
+        args.windows(2)
+            .find_map(|w| (w[0] == flag).then_some(w[1].as_str()))
+    };
+    let to = flag("--to").unwrap_or(DEFAULT_ADDR);
+    let source: Box<dyn AudioSource> = match flag("--fake-audio") {
+        Some(spec) => Box::new(FakeSource::parse(spec)?),
+        None => audio::open_mics(config)?,
+    };
+    eprintln!("Recording at {}Hz", source.sample_rate());
+    forward(source, to)
+}
+
+/// Stream `source` to the conch TUI listening at `addr`, reconnecting
+/// whenever the connection drops. Returns only if the source stops.
+pub fn forward(mut source: Box<dyn AudioSource>, addr: &str) -> Result<()> {
+    let rate = source.sample_rate();
+    let (chunks, received) = mpsc::sync_channel(BACKLOG);
+    let sink = SampleSink::forward(move |samples| {
+        let samples: Vec<f32> = samples.collect();
+        let _ = chunks.try_send(encode_pcm(&resample(&samples, rate, SAMPLE_RATE)));
+    });
+    source.start(sink.on_error(|e| eprintln!("Audio stream error: {e}")))?;
+    let mut reported = false;
+    loop {
+        match TcpStream::connect(addr) {
+            Ok(mut stream) => {
+                // What was recorded while disconnected is stale
+                while received.try_recv().is_ok() {}
+                eprintln!("Streaming the mic to {addr}");
+                reported = false;
+                match send(&mut stream, &received) {
+                    Ok(()) => return Err(anyhow!("The mic stopped delivering audio")),
+                    Err(e) => eprintln!("Lost {addr}: {e}; reconnecting..."),
+                }
+            }
+            Err(e) if !reported => {
+                eprintln!(
+                    "Can't reach {addr} yet ({e}); retrying every {}s",
+                    RETRY.as_secs()
+                );
+                reported = true;
+            }
+            Err(_) => {}
+        }
+        std::thread::sleep(RETRY);
+    }
+}
+
+/// Send chunks of audio until the connection fails (an error) or the
+/// source stops (`Ok`).
+fn send(stream: &mut TcpStream, received: &mpsc::Receiver<Vec<u8>>) -> Result<()> {
+    stream.set_nodelay(true)?;
+    stream.write_all(GREETING)?;
+    while let Ok(chunk) = received.recv() {
+        stream.write_all(&chunk)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::AudioCapture;
+
+    #[test]
+    fn test_mic_streams_to_listener() {
+        let source = NetSource::listen("127.0.0.1:0").unwrap();
+        let addr = source.local_addr().to_string();
+        assert!(source.unavailable().unwrap().contains(&addr));
+        let audio = AudioCapture::with_source(Box::new(source)).unwrap();
+        assert_eq!(audio.sample_rate(), SAMPLE_RATE);
+
+        // Something other than conch mic is ignored
+        let mut stray = TcpStream::connect(&addr).unwrap();
+        stray.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+
+        let to = addr.clone();
+        std::thread::spawn(move || forward(Box::new(FakeSource::tone(440.0)), &to));
+        let mut connected = false;
+        for _ in 0..50 {
+            std::thread::sleep(Duration::from_millis(100));
+            if audio.unavailable().is_none() {
+                connected = true;
+                break;
+            }
+        }
+        assert!(connected, "the forwarder connects");
+        audio.start_recording();
+        std::thread::sleep(Duration::from_millis(300));
+        let samples = audio.stop_recording();
+        assert!(samples.len() > 1600, "got {} samples", samples.len());
+        assert!(
+            samples.iter().any(|s| s.abs() > 0.2),
+            "the tone comes through"
+        );
+    }
+}
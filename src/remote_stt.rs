@@ -0,0 +1,389 @@
+// Remote STT Module - Transcribing on another machine
+//
+// `conch serve-stt` loads a Whisper model and answers transcription requests
+// over HTTP, so a thin client (a Raspberry Pi, an old laptop) can record
+// locally and leave the transcribing to a machine with a GPU. With `[stt]
+// server` set, conch loads no model: each recording goes to the server as
+// 16 kHz 16-bit PCM behind a line of JSON naming the language and Whisper
+// prompt, and the transcript comes back as JSON. Both ends use blocking
+// sockets and one request per connection, like the transcriber thread that
+// calls them.
+
+use std::io::{BufRead as _, BufReader, Read as _, Write as _};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs as _};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::audio::resample;
+use crate::models::{self, ModelsConfig};
+use crate::stt::{SpeechGate, Transcriber, Transcript};
+
+/// Where `conch serve-stt` listens unless told otherwise.
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:4100";
+/// Rate the audio is sent at: Whisper's own.
+pub const SAMPLE_RATE: u32 = 16000;
+/// Largest request the server reads, about 17 minutes of audio.
+const MAX_BODY_BYTES: usize = 32 * 1024 * 1024;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a transcription may take, a large model on a long dictation
+/// included.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How a recording is to be transcribed, sent ahead of its audio.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Request {
+    /// A Whisper language code, or "auto".
+    pub language: String,
+    /// Whisper's initial prompt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// A short spoken answer, primed with `prompt` and cut off early.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub answer: bool,
+}
+
+/// A connection to a `conch serve-stt` server.
+#[derive(Debug, Clone)]
+pub struct SttClient {
+    /// `host:port` of the server.
+    addr: String,
+    model: String,
+    english_only: bool,
+}
+
+impl SttClient {
+    /// Connect to the server at `url` (`http://host:port`, or just
+    /// `host:port`) and ask which model it runs.
+    pub fn connect(url: &str) -> Result<Self> {
+        if url.starts_with("https://") {
+            return Err(anyhow!(
+                "The transcription server speaks plain http; tunnel it over SSH to encrypt it"
+            ));
+        }
+        let addr = url.strip_prefix("http://").unwrap_or(url);
+        let mut client = Self {
+            addr: addr.trim_end_matches('/').to_string(),
+            model: String::new(),
+            english_only: false,
+        };
+        let health = client
+            .request("GET /health", &[])
+            .map_err(|e| anyhow!("Transcription server at {url}: {e}"))?;
+        client.model = health["model"].as_str().unwrap_or_default().to_string();
+        client.english_only = health["english_only"].as_bool().unwrap_or(false);
+        Ok(client)
+    }
+
+    /// Name of the model the server transcribes with.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Whether the server's model only understands English.
+    pub fn english_only(&self) -> bool {
+        self.english_only
+    }
+
+    /// Have the server transcribe `samples`, recorded at `sample_rate` Hz.
+    pub fn transcribe(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        request: &Request,
+    ) -> Result<Transcript> {
+        let samples = resample(samples, sample_rate, SAMPLE_RATE);
+        let mut body = serde_json::to_vec(request)?;
+        body.push(b'\n');
+        body.extend(encode_pcm(&samples));
+        let reply = self
+            .request("POST /transcribe", &body)
+            .map_err(|e| anyhow!("Transcription server: {e}"))?;
+        Ok(Transcript {
+            text: reply["text"].as_str().unwrap_or_default().to_string(),
+            confidence: reply["confidence"].as_f64().unwrap_or(1.0) as f32,
+        })
+    }
+
+    /// Send `request` ("METHOD /path") with `body` and read the JSON reply.
+    fn request(&self, request: &str, body: &[u8]) -> Result<Value> {
+        let addr = self
+            .addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| anyhow!("can't resolve {}", self.addr))?;
+        let mut stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+        stream.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
+        let head = format!(
+            "{request} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.addr,
+            body.len()
+        );
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(body)?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response)?;
+
+        let split = response
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .ok_or_else(|| anyhow!("not an HTTP response"))?;
+        let head = String::from_utf8_lossy(&response[..split]);
+        let status = head.split_whitespace().nth(1).unwrap_or_default();
+        let reply: Value = serde_json::from_slice(&response[split + 4..]).unwrap_or_default();
+        if status != "200" {
+            let error = reply["error"].as_str().unwrap_or("no reason given");
+            return Err(anyhow!("HTTP {status}: {error}"));
+        }
+        Ok(reply)
+    }
+}
+
+/// A transcription server: a model shared by the connections to it.
+pub struct SttServer {
+    listener: TcpListener,
+    transcriber: Arc<Transcriber>,
+    model: String,
+}
+
+impl SttServer {
+    /// Listen on `addr` for requests to transcribe with `transcriber`,
+    /// reported to clients as `model`.
+    pub fn bind(addr: &str, transcriber: Arc<Transcriber>, model: &str) -> Result<Self> {
+        let listener =
+            TcpListener::bind(addr).map_err(|e| anyhow!("Can't listen on {addr}: {e}"))?;
+        Ok(Self {
+            listener,
+            transcriber,
+            model: model.to_string(),
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Answer requests, each connection on its own thread, until the
+    /// listener fails.
+    pub fn run(self) -> Result<()> {
+        let server = Arc::new(self);
+        loop {
+            let (stream, peer) = server.listener.accept()?;
+            let server = Arc::clone(&server);
+            std::thread::spawn(move || {
+                if let Err(e) = server.serve(stream, peer) {
+                    eprintln!("{peer}: {e}");
+                }
+            });
+        }
+    }
+
+    /// Answer one request. Every response closes the connection.
+    fn serve(&self, stream: TcpStream, peer: SocketAddr) -> Result<()> {
+        stream.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header)?;
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':')
+                && name.eq_ignore_ascii_case("content-length")
+            {
+                length = value.trim().parse().unwrap_or(0);
+            }
+        }
+        let mut words = request_line.split_whitespace();
+        let (method, path) = (words.next().unwrap_or(""), words.next().unwrap_or(""));
+        let (status, body) = if length > MAX_BODY_BYTES {
+            error("413 Payload Too Large", "recording too long")
+        } else {
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body)?;
+            self.respond(method, path, &body, peer)
+        };
+
+        let body = body.to_string();
+        let head = format!(
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+        let mut stream = reader.into_inner();
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(body.as_bytes())?;
+        Ok(())
+    }
+
+    fn respond(
+        &self,
+        method: &str,
+        path: &str,
+        body: &[u8],
+        peer: SocketAddr,
+    ) -> (&'static str, Value) {
+        match (method, path) {
+            ("GET", "/health") => (
+                "200 OK",
+                json!({ "model": self.model, "english_only": self.transcriber.english_only() }),
+            ),
+            ("POST", "/transcribe") => match self.transcribe(body) {
+                Ok((transcript, secs, started)) => {
+                    eprintln!(
+                        "{peer}: {secs:.1}s of audio in {} ms",
+                        started.elapsed().as_millis()
+                    );
+                    let Transcript { text, confidence } = transcript;
+                    ("200 OK", json!({ "text": text, "confidence": confidence }))
+                }
+                Err(e) => error("500 Internal Server Error", &e.to_string()),
+            },
+            _ => error("404 Not Found", "not found"),
+        }
+    }
+
+    /// Transcribe a request body, returning the transcript, the length of
+    /// the audio and when transcribing started.
+    fn transcribe(&self, body: &[u8]) -> Result<(Transcript, f32, Instant)> {
+        let split = body
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| anyhow!("no request line"))?;
+        let request: Request =
+            serde_json::from_slice(&body[..split]).map_err(|e| anyhow!("bad request line: {e}"))?;
+        let samples = decode_pcm(&body[split + 1..]);
+        let secs = samples.len() as f32 / SAMPLE_RATE as f32;
+        let started = Instant::now();
+        let (language, prompt) = (&request.language, request.prompt.as_deref());
+        let transcript = match prompt {
+            Some(prompt) if request.answer => {
+                self.transcriber
+                    .transcribe_answer(&samples, SAMPLE_RATE, language, prompt)?
+            }
+            Some(prompt) => {
+                self.transcriber
+                    .transcribe_primed(&samples, SAMPLE_RATE, language, prompt)?
+            }
+            None => self
+                .transcriber
+                .transcribe_in(&samples, SAMPLE_RATE, language)?,
+        };
+        Ok((transcript, secs, started))
+    }
+}
+
+fn error(status: &'static str, message: &str) -> (&'static str, Value) {
+    (status, json!({ "error": message }))
+}
+
+/// `conch serve-stt [model] [--listen <addr>]`: load a model, picked like
+/// the TUI's out of `models`, and transcribe for conch clients on other
+/// machines behind `gate`.
+pub fn serve(args: &[String], models: &ModelsConfig, gate: SpeechGate) -> Result<()> {
+    let listen = match args.windows(2).find(|w| w[0] == "--listen") {
+        Some(w) => w[1].as_str(),
+        None => DEFAULT_LISTEN,
+    };
+    let path = models.chosen(args.first().map(String::as_str));
+    eprintln!("Loading Whisper model from '{}'...", path.display());
+    let transcriber = Transcriber::new(&path.to_string_lossy())?;
+    let transcriber = Arc::new(transcriber.with_gate(gate));
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let server = SttServer::bind(listen, transcriber, models::model_name(&file_name))?;
+    let addr = server.local_addr()?;
+    eprintln!("Transcribing for conch clients on http://{addr}");
+    if !addr.ip().is_loopback() {
+        eprintln!("Warning: there's no authentication; anyone who can reach this port can use it");
+    }
+    server.run()
+}
+
+/// Samples as 16-bit little-endian PCM, half the size of the floats.
+pub fn encode_pcm(samples: &[f32]) -> Vec<u8> {
+    samples
+        .iter()
+        .flat_map(|s| ((s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes())
+        .collect()
+}
+
+/// Samples from 16-bit little-endian PCM.
+pub fn decode_pcm(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / i16::MAX as f32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start(transcriber: Transcriber) -> String {
+        let server = SttServer::bind("127.0.0.1:0", Arc::new(transcriber), "base.en").unwrap();
+        let addr = server.local_addr().unwrap();
+        std::thread::spawn(move || server.run());
+        format!("http://{addr}")
+    }
+
+    #[test]
+    fn test_serve_missing_model() {
+        let args = ["/nonexistent/ggml-base.en.bin".to_string()];
+        let err = serve(&args, &ModelsConfig::default(), SpeechGate::default()).unwrap_err();
+        assert!(err.to_string().contains("/nonexistent/ggml-base.en.bin"));
+    }
+
+    #[test]
+    fn test_pcm_round_trip() {
+        let samples = [0.0, 0.5, -0.5, 1.0, -1.0, 2.0];
+        let decoded = decode_pcm(&encode_pcm(&samples));
+        assert_eq!(decoded.len(), samples.len());
+        for (a, b) in decoded.iter().zip([0.0, 0.5, -0.5, 1.0, -1.0, 1.0]) {
+            assert!((a - b).abs() < 1e-4, "{a} vs {b}");
+        }
+    }
+
+    #[test]
+    fn test_transcribe_remotely() {
+        let url = start(Transcriber::fixed("open the main file"));
+        let client = SttClient::connect(&url).unwrap();
+        assert_eq!(client.model(), "base.en");
+        assert!(!client.english_only());
+
+        let request = Request {
+            language: "en".into(),
+            prompt: Some("main.rs".into()),
+            answer: false,
+        };
+        let transcript = client.transcribe(&[0.1; 4800], 48000, &request).unwrap();
+        assert_eq!(transcript.text, "open the main file");
+        assert_eq!(transcript.confidence, 1.0);
+
+        // Silence is nothing to transcribe, as with a local model
+        let transcript = client.transcribe(&[], 48000, &request).unwrap();
+        assert_eq!(transcript.text, "");
+    }
+
+    #[test]
+    fn test_connect_errors() {
+        let url = start(Transcriber::fixed("hello"));
+        let client = SttClient::connect(&url).unwrap();
+        let err = client.request("GET /nowhere", &[]).unwrap_err();
+        assert!(err.to_string().contains("404"), "{err}");
+        let err = client.request("POST /transcribe", b"no line").unwrap_err();
+        assert!(err.to_string().contains("no request line"), "{err}");
+
+        assert!(SttClient::connect("https://gpu-box:4100").is_err());
+        let closed = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        assert!(SttClient::connect(&closed.to_string()).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+// Replay Module - Captured SSE event logs, for development without a server
+//
+// `--capture-events <file>` writes every SSE data line conch receives to a
+// JSONL file, one `{"t": <seconds>, "data": <event>}` per line, timed from
+// the first event. `--replay <file>` feeds such a log back through the
+// parser and the TUI at its original pace (or faster), so focus and
+// activity behavior can be reproduced without OpenCode running. Logs of bare
+// event objects, one per line, replay too, all at once.
+
+use std::fs::OpenOptions;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+
+/// One captured event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayEvent {
+    /// Seconds since the first captured event.
+    pub t: f64,
+    /// The SSE data line.
+    pub data: String,
+}
+
+/// A captured log, ready to replay.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub events: Vec<ReplayEvent>,
+    /// Playback speed: 2.0 is twice as fast, 0 skips the waits.
+    pub speed: f64,
+}
+
+impl Replay {
+    /// Load a captured log from `path`.
+    pub fn load(path: &Path, speed: f64) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read event log '{}': {}", path.display(), e))?;
+        let events = parse_log(&text)
+            .map_err(|e| anyhow!("Invalid event log '{}': {}", path.display(), e))?;
+        Ok(Self { events, speed })
+    }
+
+    /// Each event with how long to wait before it, at the replay speed.
+    pub fn schedule(&self) -> impl Iterator<Item = (Duration, &str)> {
+        let mut previous = 0.0;
+        self.events.iter().map(move |event| {
+            let gap = (event.t - previous).max(0.0);
+            previous = event.t;
+            let wait = if self.speed > 0.0 {
+                Duration::from_secs_f64(gap / self.speed)
+            } else {
+                Duration::ZERO
+            };
+            (wait, event.data.as_str())
+        })
+    }
+}
+
+/// Parse a log of captured lines or bare event objects. Blank lines are
+/// skipped.
+pub fn parse_log(text: &str) -> Result<Vec<ReplayEvent>> {
+    let mut events = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let value: serde_json::Value =
+            serde_json::from_str(line).map_err(|e| anyhow!("line {}: {}", i + 1, e))?;
+        let event = match (value.get("t").and_then(|t| t.as_f64()), value.get("data")) {
+            (Some(t), Some(data)) => ReplayEvent {
+                t,
+                data: data
+                    .as_str()
+                    .map_or_else(|| data.to_string(), str::to_string),
+            },
+            _ => ReplayEvent {
+                t: events.last().map_or(0.0, |e: &ReplayEvent| e.t),
+                data: line.to_string(),
+            },
+        };
+        events.push(event);
+    }
+    Ok(events)
+}
+
+/// Appends received events to a log that [`Replay::load`] reads back.
+#[derive(Debug)]
+pub struct Capture {
+    path: PathBuf,
+    start: Option<Instant>,
+}
+
+impl Capture {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            start: None,
+        }
+    }
+
+    /// Append one SSE data line, received at `at`.
+    pub fn record(&mut self, data: &str, at: Instant) -> Result<()> {
+        let start = *self.start.get_or_insert(at);
+        // Stored as JSON when it is, so the log stays readable
+        let data = serde_json::from_str::<serde_json::Value>(data)
+            .unwrap_or_else(|_| serde_json::Value::String(data.to_string()));
+        let line = serde_json::json!({
+            "t": at.duration_since(start).as_secs_f64(),
+            "data": data,
+        });
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| anyhow!("Failed to open '{}': {}", self.path.display(), e))?;
+        writeln!(file, "{line}")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_round_trip() {
+        let path = std::env::temp_dir().join(format!("conch_capture_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut capture = Capture::new(&path);
+        let start = Instant::now();
+        capture
+            .record(r#"{"type":"server.connected","properties":{}}"#, start)
+            .unwrap();
+        capture
+            .record(
+                r#"{"type":"session.status","properties":{"sessionID":"s1"}}"#,
+                start + Duration::from_millis(1500),
+            )
+            .unwrap();
+
+        let replay = Replay::load(&path, 1.0).unwrap();
+        assert_eq!(replay.events.len(), 2);
+        assert_eq!(replay.events[1].t, 1.5);
+        let data: serde_json::Value = serde_json::from_str(&replay.events[1].data).unwrap();
+        assert_eq!(data["properties"]["sessionID"], "s1");
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_schedule_speed() {
+        let events =
+            parse_log("{\"t\": 0, \"data\": \"a\"}\n{\"t\": 2.0, \"data\": \"b\"}\n").unwrap();
+        let replay = Replay { events, speed: 4.0 };
+        let waits: Vec<Duration> = replay.schedule().map(|(wait, _)| wait).collect();
+        assert_eq!(waits, vec![Duration::ZERO, Duration::from_millis(500)]);
+
+        let instant = Replay {
+            speed: 0.0,
+            ..replay
+        };
+        assert!(instant.schedule().all(|(wait, _)| wait.is_zero()));
+    }
+
+    #[test]
+    fn test_bare_events_and_errors() {
+        let events =
+            parse_log("{\"type\":\"server.connected\"}\n\n{\"type\":\"server.heartbeat\"}")
+                .unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].data, "{\"type\":\"server.heartbeat\"}");
+        assert_eq!(events[1].t, 0.0);
+
+        let err = parse_log("{\"type\":\"x\"}\nnot json").unwrap_err();
+        assert!(err.to_string().starts_with("line 2:"));
+    }
+}
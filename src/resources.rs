@@ -0,0 +1,311 @@
+// Resources Module - conch's own CPU and memory use
+//
+// The diagnostics view answers "is conch draining my battery?" and "what
+// did the bigger model cost?". Once a second while it's open, the CPU time
+// of each of conch's threads and the resident memory are read from /proc,
+// and the threads are grouped by name into the parts of conch they belong
+// to: the render loop on the main thread, Whisper, captions, the network
+// tasks. Whisper's worker threads only live for one transcription, so their
+// time shows up as threads that have since ended. Elsewhere than Linux
+// nothing is measured.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// How often usage is measured while the view is open.
+pub const INTERVAL: Duration = Duration::from_secs(1);
+/// Clock ticks per second of the CPU times in /proc, 100 on every
+/// architecture Linux runs conch on.
+const TICKS_PER_SEC: u64 = 100;
+
+/// CPU time and memory at one moment.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    /// CPU ticks of the whole process, ended threads included.
+    pub process_ticks: u64,
+    /// CPU ticks and number of live threads, by thread name.
+    pub threads: BTreeMap<String, (u64, usize)>,
+    /// Resident memory, in bytes.
+    pub rss_bytes: u64,
+}
+
+impl Snapshot {
+    /// The current usage of this process, on Linux.
+    pub fn read() -> Option<Self> {
+        let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+        let (_, process_ticks) = parse_stat(&stat)?;
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let mut threads: BTreeMap<String, (u64, usize)> = BTreeMap::new();
+        for task in std::fs::read_dir("/proc/self/task").ok()?.flatten() {
+            let Ok(stat) = std::fs::read_to_string(task.path().join("stat")) else {
+                continue;
+            };
+            if let Some((name, ticks)) = parse_stat(&stat) {
+                let entry = threads.entry(name).or_default();
+                entry.0 += ticks;
+                entry.1 += 1;
+            }
+        }
+        Some(Self {
+            process_ticks,
+            threads,
+            rss_bytes: parse_rss(&status)?,
+        })
+    }
+}
+
+/// CPU use of one part of conch, over the last interval.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Part {
+    pub name: String,
+    /// Percent of one core.
+    pub cpu: f32,
+    /// Live threads.
+    pub threads: usize,
+}
+
+/// Usage over the last interval.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Usage {
+    /// Percent of one core, for the whole process.
+    pub cpu: f32,
+    pub rss_bytes: u64,
+    /// The busiest first. Threads that ended during the interval are a
+    /// part of their own.
+    pub parts: Vec<Part>,
+    /// Frames drawn per second.
+    pub frames_per_sec: f32,
+}
+
+impl Usage {
+    /// Usage between snapshots `old` and `new`, `elapsed` apart, with
+    /// `frames` drawn in between.
+    pub fn between(old: &Snapshot, new: &Snapshot, elapsed: Duration, frames: u64) -> Self {
+        let secs = elapsed.as_secs_f32().max(f32::EPSILON);
+        let percent = |ticks: u64| ticks as f32 * 100.0 / TICKS_PER_SEC as f32 / secs;
+        let mut parts: BTreeMap<&str, (u64, usize)> = BTreeMap::new();
+        let mut accounted = 0;
+        for (thread, &(ticks, count)) in &new.threads {
+            let before = old.threads.get(thread).map_or(0, |&(ticks, _)| ticks);
+            let ticks = ticks.saturating_sub(before);
+            accounted += ticks;
+            let part = parts.entry(part_name(thread)).or_default();
+            part.0 += ticks;
+            part.1 += count;
+        }
+        let total = new.process_ticks.saturating_sub(old.process_ticks);
+        let ended = total.saturating_sub(accounted);
+        if ended > 0 {
+            parts.insert("Threads since ended (Whisper's workers)", (ended, 0));
+        }
+        let mut parts: Vec<Part> = parts
+            .into_iter()
+            .map(|(name, (ticks, threads))| Part {
+                name: name.to_string(),
+                cpu: percent(ticks),
+                threads,
+            })
+            .collect();
+        parts.sort_by(|a, b| b.cpu.total_cmp(&a.cpu));
+        Self {
+            cpu: percent(total),
+            rss_bytes: new.rss_bytes,
+            parts,
+            frames_per_sec: frames as f32 / secs,
+        }
+    }
+}
+
+/// The part of conch a thread named `thread` belongs to.
+fn part_name(thread: &str) -> &str {
+    match thread {
+        "conch" => "Main thread (render loop, keys)",
+        "whisper" => "Whisper",
+        "captions" => "Live captions",
+        t if t.starts_with("tokio") => "Network and events (tokio)",
+        t if ["cpal", "alsa", "pulse", "jack", "pipewire"]
+            .iter()
+            .any(|audio| t.contains(audio)) =>
+        {
+            "Audio capture"
+        }
+        other => other,
+    }
+}
+
+/// Measures usage every [`INTERVAL`] and keeps the latest.
+#[derive(Debug, Clone, Default)]
+pub struct ResourceMonitor {
+    last: Option<(Instant, Snapshot, u64)>,
+    usage: Option<Usage>,
+    /// Audio length and transcription time of the last recording.
+    transcription: Option<(Duration, Duration)>,
+}
+
+impl ResourceMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Measure, if [`INTERVAL`] has passed, with `frames` drawn since
+    /// start. Returns whether there's new usage to show.
+    pub fn tick(&mut self, now: Instant, frames: u64) -> bool {
+        if self
+            .last
+            .as_ref()
+            .is_some_and(|(at, _, _)| now.duration_since(*at) < INTERVAL)
+        {
+            return false;
+        }
+        match Snapshot::read() {
+            Some(snapshot) => self.update(now, snapshot, frames),
+            None => false,
+        }
+    }
+
+    /// Take `snapshot`, taken at `now`, as the latest.
+    pub fn update(&mut self, now: Instant, snapshot: Snapshot, frames: u64) -> bool {
+        let previous = self.last.replace((now, snapshot, frames));
+        let (Some((at, old, old_frames)), Some((_, new, _))) = (previous, &self.last) else {
+            return false;
+        };
+        let elapsed = now.duration_since(at);
+        self.usage = Some(Usage::between(&old, new, elapsed, frames - old_frames));
+        true
+    }
+
+    /// Forget the measurements, to start over when the view opens.
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.usage = None;
+    }
+
+    /// Usage over the last interval, once two measurements were taken.
+    pub fn usage(&self) -> Option<&Usage> {
+        self.usage.as_ref()
+    }
+
+    /// `audio` of a recording was transcribed in `took`.
+    pub fn transcribed(&mut self, audio: Duration, took: Duration) {
+        self.transcription = Some((audio, took));
+    }
+
+    /// Audio length and transcription time of the last recording.
+    pub fn transcription(&self) -> Option<(Duration, Duration)> {
+        self.transcription
+    }
+}
+
+/// Thread name and CPU ticks (user and system) of a /proc `stat` line.
+fn parse_stat(stat: &str) -> Option<(String, u64)> {
+    // The name is in parentheses and may hold spaces or parentheses itself
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let name = stat.get(open + 1..close)?.to_string();
+    // Fields after the name start at the state, field 3; utime and stime
+    // are fields 14 and 15
+    let fields: Vec<&str> = stat.get(close + 1..)?.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some((name, utime + stime))
+}
+
+/// Resident memory from /proc `status`, in bytes.
+fn parse_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// `bytes` in MB, to one decimal.
+pub fn format_mb(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(process_ticks: u64, threads: &[(&str, u64, usize)]) -> Snapshot {
+        Snapshot {
+            process_ticks,
+            threads: threads
+                .iter()
+                .map(|&(name, ticks, count)| (name.to_string(), (ticks, count)))
+                .collect(),
+            rss_bytes: 200 * 1024 * 1024,
+        }
+    }
+
+    #[test]
+    fn test_parse_proc() {
+        let stat = "4242 (tokio-runtime-w) S 1 4242 4242 0 -1 4194560 1234 0 0 0 37 5 0 0 20 0 8 0";
+        assert_eq!(parse_stat(stat), Some(("tokio-runtime-w".to_string(), 42)));
+        let odd = "7 (a (b) c) R 1 7 7 0 -1 0 0 0 0 0 1 2 0 0";
+        assert_eq!(parse_stat(odd), Some(("a (b) c".to_string(), 3)));
+        assert_eq!(parse_stat("garbage"), None);
+
+        let status = "Name:\tconch\nVmPeak:\t  900 kB\nVmRSS:\t  2048 kB\n";
+        assert_eq!(parse_rss(status), Some(2 * 1024 * 1024));
+        assert_eq!(format_mb(2 * 1024 * 1024), "2.0 MB");
+    }
+
+    #[test]
+    fn test_usage_by_part() {
+        let old = snapshot(100, &[("conch", 40, 1), ("tokio-runtime-w", 10, 4)]);
+        let new = snapshot(
+            250,
+            &[
+                ("conch", 45, 1),
+                ("tokio-runtime-w", 15, 4),
+                ("whisper", 20, 1),
+            ],
+        );
+        let usage = Usage::between(&old, &new, Duration::from_secs(2), 8);
+        // 150 ticks in 2 s is 75% of a core
+        assert!((usage.cpu - 75.0).abs() < 0.01);
+        assert_eq!(usage.frames_per_sec, 4.0);
+        let parts: Vec<(&str, f32, usize)> = usage
+            .parts
+            .iter()
+            .map(|p| (p.name.as_str(), p.cpu, p.threads))
+            .collect();
+        assert_eq!(
+            parts,
+            vec![
+                ("Threads since ended (Whisper's workers)", 60.0, 0),
+                ("Whisper", 10.0, 1),
+                ("Main thread (render loop, keys)", 2.5, 1),
+                ("Network and events (tokio)", 2.5, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_monitor_needs_two_measurements() {
+        let mut monitor = ResourceMonitor::new();
+        let t0 = Instant::now();
+        assert!(!monitor.update(t0, snapshot(0, &[]), 0));
+        assert!(monitor.usage().is_none());
+        assert!(monitor.update(t0 + INTERVAL, snapshot(10, &[("conch", 10, 1)]), 3));
+        assert!((monitor.usage().unwrap().cpu - 10.0).abs() < 0.01);
+        // Too soon to measure again
+        assert!(!monitor.tick(t0 + INTERVAL, 4));
+        monitor.reset();
+        assert!(monitor.usage().is_none());
+
+        monitor.transcribed(Duration::from_secs(4), Duration::from_secs(1));
+        assert_eq!(
+            monitor.transcription(),
+            Some((Duration::from_secs(4), Duration::from_secs(1)))
+        );
+    }
+
+    #[test]
+    fn test_read_self() {
+        if let Some(snapshot) = Snapshot::read() {
+            assert!(snapshot.rss_bytes > 0);
+            assert!(!snapshot.threads.is_empty());
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// Rewrite Module - What a transcript goes through before it's a prompt
+//
+// Spoken numbers are written as digits first ("port forty ninety six" is
+// port 4096), then casing commands make identifiers ("camel case fetch
+// user" is `fetchUser`), then names and homophones are corrected against the
+// focus stack and the project vocabulary ("mane dot rs" is `main.rs`). Every
+// change is kept, whichever step made it, so the pending prompt shows what
+// was heard and each change can be put back.
+
+use crate::casing;
+use crate::homophones::{self, Corrected};
+use crate::numbers;
+use crate::vocabulary::Vocabulary;
+
+/// The rewrites a transcript goes through.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rewrites<'a> {
+    /// Language whose number words are written as digits; `None` leaves
+    /// them as words.
+    pub numbers: Option<&'a str>,
+    /// Vocabularies names are corrected from, looked up in order; `None`
+    /// leaves names as heard.
+    pub vocabularies: Option<&'a [&'a Vocabulary]>,
+}
+
+impl Rewrites<'_> {
+    /// `heard` rewritten, with the changes made to it.
+    pub fn apply(&self, heard: &str) -> Corrected {
+        let text = match self.numbers {
+            Some(language) => numbers::apply(heard, language),
+            None => heard.to_string(),
+        };
+        let text = casing::apply(&text);
+        let corrected = Corrected::between(heard, &text);
+        match self.vocabularies {
+            Some(vocabularies) => corrected.then(homophones::correct(&text, vocabularies)),
+            None => corrected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> Vocabulary {
+        Vocabulary::new(vec!["main.rs".into()], vec!["AudioCapture".into()])
+    }
+
+    fn shown(corrected: &Corrected) -> Vec<(&str, &str)> {
+        corrected
+            .corrections
+            .iter()
+            .map(|c| (c.original.as_str(), &corrected.text[c.range.clone()]))
+            .collect()
+    }
+
+    #[test]
+    fn test_every_step_is_shown() {
+        let project = project();
+        let rewrites = Rewrites {
+            numbers: Some("en"),
+            vocabularies: Some(&[&project]),
+        };
+        let heard = "Right mane dot rs with camel case fetch user, on port forty ninety six.";
+        let mut fixed = rewrites.apply(heard);
+        assert_eq!(fixed.text, "Write main.rs with fetchUser, on port 4096.");
+        assert_eq!(
+            shown(&fixed),
+            vec![
+                ("Right", "Write"),
+                ("mane dot rs", "main.rs"),
+                ("camel case fetch user", "fetchUser"),
+                ("forty ninety six", "4096")
+            ]
+        );
+        while fixed.revert_last().is_some() {}
+        assert_eq!(fixed.text, heard);
+    }
+
+    #[test]
+    fn test_steps_turned_off() {
+        let heard = "write mane dot rs on port forty ninety six";
+        let fixed = Rewrites::default().apply(heard);
+        assert_eq!(fixed.text, heard);
+        assert!(fixed.corrections.is_empty());
+
+        let project = project();
+        let names = Rewrites {
+            vocabularies: Some(&[&project]),
+            ..Rewrites::default()
+        };
+        let fixed = names.apply(heard);
+        assert_eq!(fixed.text, "write main.rs on port forty ninety six");
+        assert_eq!(shown(&fixed), vec![("mane dot rs", "main.rs")]);
+    }
+}
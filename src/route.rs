@@ -0,0 +1,233 @@
+// Route Module - Where a confirmed transcript goes
+//
+// Most transcripts are prompts for OpenCode, but not all: a thought worth
+// keeping, a sentence for a chat window, a commit message for the
+// clipboard. While a prompt is pending, a number key or a spoken target
+// ("send to notes") picks where it goes instead of OpenCode. Typing into
+// another app and copying are delegated to the platform's tools, like the
+// clipboard; notes are appended to a Markdown file, one item per note.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+
+use crate::clipboard;
+use crate::paths;
+use crate::send::normalize_words;
+
+/// Where a transcript goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// A prompt for OpenCode.
+    Prompt,
+    /// The system clipboard.
+    Clipboard,
+    /// Typed into the focused window, as if from the keyboard.
+    Type,
+    /// Appended to the notes file.
+    Notes,
+}
+
+/// Every target, in the order of their number keys.
+pub const TARGETS: [Target; 4] = [
+    Target::Prompt,
+    Target::Clipboard,
+    Target::Type,
+    Target::Notes,
+];
+
+/// Whole transcripts naming a target, already normalized.
+const SHORT: &[(&str, Target)] = &[
+    ("copy it", Target::Clipboard),
+    ("copy that", Target::Clipboard),
+    ("type it", Target::Type),
+    ("type it out", Target::Type),
+    ("type that", Target::Type),
+    ("note it", Target::Notes),
+    ("note that", Target::Notes),
+];
+/// Verbs in front of a target's name: "send it to the clipboard".
+const VERBS: &[&str] = &[
+    "send it to",
+    "send to",
+    "add it to",
+    "add to",
+    "put it in",
+    "save it to",
+    "save to",
+];
+/// Names of the targets, after a verb and an optional "the" or "my".
+const NAMES: &[(&str, Target)] = &[
+    ("opencode", Target::Prompt),
+    ("open code", Target::Prompt),
+    ("clipboard", Target::Clipboard),
+    ("app", Target::Type),
+    ("window", Target::Type),
+    ("notes", Target::Notes),
+    ("note", Target::Notes),
+];
+
+impl Target {
+    /// The number key that picks the target.
+    pub fn key(self) -> char {
+        match self {
+            Target::Prompt => '1',
+            Target::Clipboard => '2',
+            Target::Type => '3',
+            Target::Notes => '4',
+        }
+    }
+
+    /// The target number key `c` picks.
+    pub fn from_key(c: char) -> Option<Target> {
+        TARGETS.into_iter().find(|t| t.key() == c)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Target::Prompt => "OpenCode",
+            Target::Clipboard => "Clipboard",
+            Target::Type => "Type",
+            Target::Notes => "Notes",
+        }
+    }
+}
+
+/// `[route]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RouteConfig {
+    /// Markdown file notes are appended to.
+    pub notes: PathBuf,
+    /// Shell command that types its stdin into the focused window. Unset
+    /// picks `wtype` under Wayland and `xdotool` under X11.
+    pub type_command: Option<String>,
+}
+
+impl Default for RouteConfig {
+    fn default() -> Self {
+        Self {
+            notes: paths::data_dir().join("notes.md"),
+            type_command: None,
+        }
+    }
+}
+
+impl RouteConfig {
+    /// Type commands to try, in order, for the current platform and session.
+    pub fn type_commands(&self) -> Vec<&str> {
+        if let Some(command) = &self.type_command {
+            return vec![command.as_str()];
+        }
+        let mut commands = Vec::new();
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            commands.push("wtype -");
+        }
+        if std::env::var_os("DISPLAY").is_some() {
+            commands.push("xdotool type --clearmodifiers --file -");
+        }
+        commands
+    }
+}
+
+/// The target `transcript` names, if it is nothing but a target.
+pub fn spoken(transcript: &str) -> Option<Target> {
+    let words = normalize_words(transcript);
+    if let Some(&(_, target)) = SHORT.iter().find(|(s, _)| *s == words) {
+        return Some(target);
+    }
+    VERBS.iter().find_map(|verb| {
+        let rest = words.strip_prefix(verb)?.strip_prefix(' ')?;
+        let name = ["the ", "my "]
+            .iter()
+            .find_map(|article| rest.strip_prefix(article))
+            .unwrap_or(rest);
+        NAMES.iter().find(|(n, _)| *n == name).map(|&(_, t)| t)
+    })
+}
+
+/// Type `text` into the focused window with the first of `commands` that
+/// works.
+pub fn type_text(commands: &[&str], text: &str) -> Result<()> {
+    if commands.is_empty() {
+        return Err(anyhow!(
+            "No typing tool for this session (set type_command under [route])"
+        ));
+    }
+    let mut errors = Vec::new();
+    for command in commands {
+        match clipboard::pipe_to(command, text) {
+            Ok(()) => return Ok(()),
+            Err(e) => errors.push(format!("{}: {}", command, e)),
+        }
+    }
+    Err(anyhow!("No typing tool worked ({})", errors.join("; ")))
+}
+
+/// Append `text` to the notes file at `path` as a list item, creating the
+/// file and its directory if needed.
+pub fn append_note(path: &Path, text: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    // Later lines of a note are indented to stay in its item
+    let item = text.trim().lines().collect::<Vec<_>>().join("\n  ");
+    writeln!(file, "- {item}")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spoken_targets() {
+        assert_eq!(spoken("Send to notes."), Some(Target::Notes));
+        assert_eq!(spoken("send it to my notes"), Some(Target::Notes));
+        assert_eq!(spoken("Copy it."), Some(Target::Clipboard));
+        assert_eq!(spoken("send it to the clipboard"), Some(Target::Clipboard));
+        assert_eq!(spoken("Type it out"), Some(Target::Type));
+        assert_eq!(spoken("send to the app"), Some(Target::Type));
+        assert_eq!(spoken("send to OpenCode"), Some(Target::Prompt));
+        assert_eq!(spoken("send to notes and the clipboard"), None);
+        assert_eq!(spoken("add tests to the parser"), None);
+        assert_eq!(spoken(""), None);
+    }
+
+    #[test]
+    fn test_number_keys() {
+        for target in TARGETS {
+            assert_eq!(Target::from_key(target.key()), Some(target));
+        }
+        assert_eq!(Target::from_key('5'), None);
+    }
+
+    #[test]
+    fn test_append_note() {
+        let dir = std::env::temp_dir().join(format!("conch_notes_{}", std::process::id()));
+        let path = dir.join("notes.md");
+        append_note(&path, "Look into the reconnect backoff.").unwrap();
+        append_note(&path, "First line\nsecond line\n").unwrap();
+        let notes = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            notes,
+            "- Look into the reconnect backoff.\n- First line\n  second line\n"
+        );
+    }
+
+    #[test]
+    fn test_type_text_needs_a_tool() {
+        let err = type_text(&[], "hello").unwrap_err();
+        assert!(err.to_string().contains("type_command"));
+        assert!(type_text(&["cat > /dev/null"], "hello").is_ok());
+        assert!(type_text(&["false"], "hello").is_err());
+    }
+}
@@ -0,0 +1,202 @@
+// Send Module - Decides whether a transcript can go to OpenCode without confirmation
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::session::RetryPolicy;
+
+/// Keywords that always need a manual Enter, even with auto-send on.
+const DEFAULT_REVIEW_KEYWORDS: &[&str] = &[
+    "delete",
+    "remove",
+    "rm",
+    "drop",
+    "wipe",
+    "force push",
+    "reset hard",
+    "overwrite",
+];
+
+/// `[send]` section: confirmation behavior for transcripts.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SendConfig {
+    /// Send transcripts without waiting for Enter. Toggle at runtime with `a`.
+    pub auto: bool,
+    /// With `auto`, wait this many seconds before sending so Backspace can
+    /// still cancel. 0 sends immediately.
+    pub cancel_secs: f32,
+    /// With `auto`, transcripts whose mean token probability is below this
+    /// wait for Enter instead. 0 disables the check.
+    pub min_confidence: f32,
+    /// With `auto`, transcripts mentioning any of these words or phrases wait
+    /// for Enter. Matched case-insensitively on whole words, ignoring punctuation.
+    pub review_keywords: Vec<String>,
+    /// Times to retry a send that failed because OpenCode couldn't be
+    /// reached, timed out or sat behind a failing proxy. 0 never retries.
+    pub retries: u32,
+    /// Milliseconds before the first retry, doubled for each one after it.
+    pub retry_backoff_ms: u64,
+}
+
+impl Default for SendConfig {
+    fn default() -> Self {
+        Self {
+            auto: false,
+            cancel_secs: 0.0,
+            min_confidence: 0.0,
+            review_keywords: DEFAULT_REVIEW_KEYWORDS
+                .iter()
+                .map(|k| k.to_string())
+                .collect(),
+            retries: 3,
+            retry_backoff_ms: 500,
+        }
+    }
+}
+
+/// Why an auto-send transcript was held for manual confirmation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReviewReason {
+    /// Whisper's confidence fell below `min_confidence`.
+    LowConfidence(f32),
+    /// The transcript mentions a review keyword.
+    Keyword(String),
+    /// The transcript is only phrases Whisper makes up from silence.
+    Hallucination,
+}
+
+impl std::fmt::Display for ReviewReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReviewReason::LowConfidence(c) => write!(f, "low confidence ({:.0}%)", c * 100.0),
+            ReviewReason::Keyword(k) => write!(f, "mentions \"{}\"", k),
+            ReviewReason::Hallucination => write!(f, "likely made up by Whisper"),
+        }
+    }
+}
+
+impl SendConfig {
+    /// How failed sends are retried.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            retries: self.retries,
+            backoff: Duration::from_millis(self.retry_backoff_ms),
+        }
+    }
+
+    /// Check whether an auto-send transcript should wait for confirmation.
+    /// Keywords are checked first since they matter regardless of confidence.
+    pub fn review_reason(&self, text: &str, confidence: f32) -> Option<ReviewReason> {
+        self.review_reason_with(text, confidence, &[])
+    }
+
+    /// Like [`review_reason`](Self::review_reason), also holding transcripts
+    /// that mention one of `extra_keywords` (the session language's own).
+    pub fn review_reason_with(
+        &self,
+        text: &str,
+        confidence: f32,
+        extra_keywords: &[&str],
+    ) -> Option<ReviewReason> {
+        let words = format!(" {} ", normalize_words(text));
+        if let Some(keyword) = self
+            .review_keywords
+            .iter()
+            .map(String::as_str)
+            .chain(extra_keywords.iter().copied())
+            .find(|k| words.contains(&format!(" {} ", normalize_words(k))))
+        {
+            return Some(ReviewReason::Keyword(keyword.to_string()));
+        }
+        if confidence < self.min_confidence {
+            return Some(ReviewReason::LowConfidence(confidence));
+        }
+        None
+    }
+}
+
+/// Lowercase and collapse everything but letters and digits to single spaces.
+pub(crate) fn normalize_words(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gated(min_confidence: f32) -> SendConfig {
+        SendConfig {
+            auto: true,
+            min_confidence,
+            ..SendConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_confident_transcript_passes() {
+        assert_eq!(gated(0.7).review_reason("open the readme", 0.92), None);
+    }
+
+    #[test]
+    fn test_low_confidence_needs_review() {
+        assert_eq!(
+            gated(0.7).review_reason("open the readme", 0.55),
+            Some(ReviewReason::LowConfidence(0.55))
+        );
+    }
+
+    #[test]
+    fn test_zero_threshold_disables_confidence_check() {
+        assert_eq!(gated(0.0).review_reason("open the readme", 0.01), None);
+    }
+
+    #[test]
+    fn test_destructive_keyword_needs_review() {
+        let reason = gated(0.0).review_reason("Delete the old migrations.", 0.99);
+        assert_eq!(reason, Some(ReviewReason::Keyword("delete".into())));
+    }
+
+    #[test]
+    fn test_keyword_phrase_ignores_punctuation() {
+        let reason = gated(0.0).review_reason("then force-push to main", 0.99);
+        assert_eq!(reason, Some(ReviewReason::Keyword("force push".into())));
+    }
+
+    #[test]
+    fn test_keyword_matches_whole_words_only() {
+        // "rm" must not match inside "confirm" or "format"
+        assert_eq!(gated(0.0).review_reason("confirm the format", 0.99), None);
+    }
+
+    #[test]
+    fn test_custom_keywords_replace_defaults() {
+        let config = SendConfig {
+            review_keywords: vec!["deploy".into()],
+            ..gated(0.0)
+        };
+        assert!(config.review_reason("delete it", 0.99).is_none());
+        assert!(config.review_reason("Deploy now", 0.99).is_some());
+    }
+
+    #[test]
+    fn test_extra_keywords() {
+        let reason = gated(0.0).review_reason_with("Borra la rama vieja", 0.99, &["borra"]);
+        assert_eq!(reason, Some(ReviewReason::Keyword("borra".into())));
+        assert_eq!(gated(0.0).review_reason("Borra la rama vieja", 0.99), None);
+    }
+
+    #[test]
+    fn test_reason_display() {
+        assert_eq!(ReviewReason::LowConfidence(0.55).to_string(), "low confidence (55%)");
+        assert_eq!(
+            ReviewReason::Keyword("drop".into()).to_string(),
+            "mentions \"drop\""
+        );
+    }
+}
@@ -0,0 +1,530 @@
+// Session Module - One task owns the OpenCode client and the current session
+//
+// Everything that talks to OpenCode goes through a cloneable `SessionHandle`,
+// which forwards commands to the owning task over a channel. Commands are
+// queued when the method is called (not when the returned future is first
+// polled) and handled one at a time, so a session switch can't race a prompt
+// queued before it, and prompts reach OpenCode in the order the user
+// confirmed them.
+//
+// Retries of a failed send run in the caller's task, one command per
+// attempt, so the backoff never holds up other commands; `SendOrder` keeps
+// the prompts in order meanwhile.
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::transport::{self, OpenCodeClient, SessionInfo, SessionMessage};
+
+/// How a prompt whose send failed for a passing reason is retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt.
+    pub retries: u32,
+    /// Wait before the first retry, doubled for each one after it.
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Give up after the first attempt.
+    pub const NONE: RetryPolicy = RetryPolicy {
+        retries: 0,
+        backoff: Duration::ZERO,
+    };
+
+    /// Wait before retry number `retry` (1-based).
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.backoff * 2u32.saturating_pow(retry.saturating_sub(1))
+    }
+}
+
+/// Where a prompt goes: the session and agent current when it was queued,
+/// kept across its retries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTarget {
+    pub session_id: String,
+    pub agent: Option<String>,
+}
+
+/// Turns for sending prompts, handed out in the order they are taken.
+#[derive(Debug, Default)]
+pub struct SendOrder {
+    last: Option<oneshot::Receiver<()>>,
+}
+
+impl SendOrder {
+    /// Take the next turn: it starts once every turn taken before it ended.
+    pub fn take_turn(&mut self) -> SendTurn {
+        let (done, next) = oneshot::channel();
+        SendTurn {
+            previous: self.last.replace(next),
+            _done: done,
+        }
+    }
+}
+
+/// A turn from [`SendOrder`], ending when dropped.
+#[derive(Debug)]
+pub struct SendTurn {
+    previous: Option<oneshot::Receiver<()>>,
+    _done: oneshot::Sender<()>,
+}
+
+impl SendTurn {
+    /// Wait for the turns taken before this one to end.
+    pub async fn wait(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            // Closed without a value when the previous turn is dropped
+            let _ = previous.await;
+        }
+    }
+}
+
+/// Requests handled by the session task.
+enum Command {
+    HealthCheck(oneshot::Sender<Result<bool>>),
+    ListSessions(oneshot::Sender<Result<Vec<SessionInfo>>>),
+    GetSession {
+        session_id: String,
+        reply: oneshot::Sender<Result<Option<SessionInfo>>>,
+    },
+    CreateSession {
+        directory: Option<PathBuf>,
+        reply: oneshot::Sender<Result<SessionInfo>>,
+    },
+    SetSession(String),
+    ClearSession,
+    SetAgent(Option<String>),
+    SessionId(oneshot::Sender<Result<Option<String>>>),
+    PromptTarget(oneshot::Sender<Result<PromptTarget>>),
+    SendPrompt {
+        text: String,
+        target: Option<PromptTarget>,
+        reply: oneshot::Sender<Result<String>>,
+    },
+    ListMessages {
+        session_id: Option<String>,
+        reply: oneshot::Sender<Result<Vec<SessionMessage>>>,
+    },
+    Subscribe(oneshot::Sender<Result<reqwest::Response>>),
+}
+
+/// Cloneable handle to the task that owns the OpenCode client.
+#[derive(Clone)]
+pub struct SessionHandle {
+    tx: mpsc::UnboundedSender<Command>,
+}
+
+impl SessionHandle {
+    /// Move `client` into a new task and return a handle to it. Must be
+    /// called inside a tokio runtime. The task exits when every handle is
+    /// dropped.
+    pub fn spawn(client: OpenCodeClient) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(run(client, rx));
+        Self { tx }
+    }
+
+    /// Queue `command` built around a reply channel now; the returned
+    /// future waits for the answer.
+    fn request<T, F>(&self, command: F) -> impl Future<Output = Result<T>> + use<T, F>
+    where
+        F: FnOnce(oneshot::Sender<Result<T>>) -> Command,
+    {
+        let (reply, rx) = oneshot::channel();
+        let queued = self.tx.send(command(reply)).is_ok();
+        async move {
+            if !queued {
+                return Err(anyhow!("OpenCode session task stopped"));
+            }
+            rx.await
+                .map_err(|_| anyhow!("OpenCode session task stopped"))?
+        }
+    }
+
+    /// Health check: GET /global/health
+    pub fn health_check(&self) -> impl Future<Output = Result<bool>> + use<> {
+        self.request(Command::HealthCheck)
+    }
+
+    /// List sessions: GET /session
+    pub fn list_sessions(&self) -> impl Future<Output = Result<Vec<SessionInfo>>> + use<> {
+        self.request(Command::ListSessions)
+    }
+
+    /// Fetch session `session_id`, `None` if it doesn't exist: GET
+    /// /session/{id}
+    pub fn get_session(
+        &self,
+        session_id: String,
+    ) -> impl Future<Output = Result<Option<SessionInfo>>> + use<> {
+        self.request(|reply| Command::GetSession { session_id, reply })
+    }
+
+    /// Create a session working in `directory` and make it current.
+    pub fn create_session(
+        &self,
+        directory: Option<PathBuf>,
+    ) -> impl Future<Output = Result<SessionInfo>> + use<> {
+        self.request(|reply| Command::CreateSession { directory, reply })
+    }
+
+    /// Make `id` the session prompts go to. Prompts queued before this
+    /// still go to the previous session.
+    pub fn set_session(&self, id: impl Into<String>) {
+        let _ = self.tx.send(Command::SetSession(id.into()));
+    }
+
+    /// Leave no session current: prompts fail until one is set again.
+    pub fn clear_session(&self) {
+        let _ = self.tx.send(Command::ClearSession);
+    }
+
+    /// Run prompts queued after this with `agent`, or OpenCode's default
+    /// when `None`.
+    pub fn set_agent(&self, agent: Option<String>) {
+        let _ = self.tx.send(Command::SetAgent(agent));
+    }
+
+    /// The current session, if any.
+    pub fn session_id(&self) -> impl Future<Output = Option<String>> + use<> {
+        let id = self.request(Command::SessionId);
+        async move { id.await.ok().flatten() }
+    }
+
+    /// Where a prompt queued now would go; fails without a session.
+    pub fn prompt_target(&self) -> impl Future<Output = Result<PromptTarget>> + use<> {
+        self.request(Command::PromptTarget)
+    }
+
+    /// Send a prompt to the current session, returning the session it went to.
+    pub fn send_prompt(&self, text: String) -> impl Future<Output = Result<String>> + use<> {
+        self.request(|reply| Command::SendPrompt {
+            text,
+            target: None,
+            reply,
+        })
+    }
+
+    /// Send a prompt to `target` once, whatever is current by then.
+    pub fn send_prompt_to(
+        &self,
+        target: PromptTarget,
+        text: String,
+    ) -> impl Future<Output = Result<String>> + use<> {
+        self.request(|reply| Command::SendPrompt {
+            text,
+            target: Some(target),
+            reply,
+        })
+    }
+
+    /// Messages of `session_id`, or of the current session when `None`.
+    pub fn list_messages(
+        &self,
+        session_id: Option<String>,
+    ) -> impl Future<Output = Result<Vec<SessionMessage>>> + use<> {
+        self.request(|reply| Command::ListMessages { session_id, reply })
+    }
+
+    /// Open the SSE event stream: GET /event
+    pub fn subscribe_events(&self) -> impl Future<Output = Result<reqwest::Response>> + use<> {
+        self.request(Command::Subscribe)
+    }
+}
+
+/// The session task: handle commands in order until every handle is gone.
+async fn run(mut client: OpenCodeClient, mut rx: mpsc::UnboundedReceiver<Command>) {
+    while let Some(command) = rx.recv().await {
+        match command {
+            Command::HealthCheck(reply) => {
+                let _ = reply.send(client.health_check().await);
+            }
+            Command::ListSessions(reply) => {
+                let _ = reply.send(client.list_sessions().await);
+            }
+            Command::GetSession { session_id, reply } => {
+                let _ = reply.send(client.get_session(&session_id).await);
+            }
+            Command::CreateSession { directory, reply } => {
+                let _ = reply.send(client.create_session(directory.as_deref()).await);
+            }
+            Command::SetSession(id) => client.set_session(id),
+            Command::ClearSession => client.clear_session(),
+            Command::SetAgent(agent) => client.set_agent(agent),
+            Command::SessionId(reply) => {
+                let _ = reply.send(Ok(client.session_id().map(str::to_string)));
+            }
+            Command::PromptTarget(reply) => {
+                let _ = reply.send(prompt_target(&client));
+            }
+            Command::SendPrompt {
+                text,
+                target,
+                reply,
+            } => {
+                let result = match target.map_or_else(|| prompt_target(&client), Ok) {
+                    Ok(target) => client
+                        .send_prompt_to(&target.session_id, target.agent.as_deref(), &text)
+                        .await
+                        .map(|()| target.session_id),
+                    Err(e) => Err(e),
+                };
+                let _ = reply.send(result);
+            }
+            Command::ListMessages { session_id, reply } => {
+                let result = match session_id.or_else(|| client.session_id().map(str::to_string)) {
+                    Some(id) => client.list_session_messages(&id).await,
+                    None => Err(anyhow!("no session")),
+                };
+                let _ = reply.send(result);
+            }
+            Command::Subscribe(reply) => {
+                let _ = reply.send(client.subscribe_events().await);
+            }
+        }
+    }
+}
+
+/// Where a prompt sent by `client` now would go.
+fn prompt_target(client: &OpenCodeClient) -> Result<PromptTarget> {
+    let session_id = client.session_id().ok_or_else(|| anyhow!("no session"))?;
+    Ok(PromptTarget {
+        session_id: session_id.to_string(),
+        agent: client.agent().map(str::to_string),
+    })
+}
+
+/// Send `text` to `target` through `handle`, one command per attempt,
+/// retrying connection failures, timeouts and gateway errors per `retry` and
+/// calling `on_retry` before each retry. Returns the session it went to.
+pub async fn send_with_retry(
+    handle: &SessionHandle,
+    target: PromptTarget,
+    text: &str,
+    retry: RetryPolicy,
+    mut on_retry: impl FnMut(u32),
+) -> Result<String> {
+    let mut attempt = 0;
+    loop {
+        let sent = handle.send_prompt_to(target.clone(), text.to_string());
+        match sent.await {
+            Err(e) if attempt < retry.retries && transport::is_retryable(&e) => {
+                attempt += 1;
+                on_retry(attempt);
+                tokio::time::sleep(retry.delay(attempt)).await;
+            }
+            Err(e) if attempt > 0 => return Err(anyhow!("{e} (after {attempt} retries)")),
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead as _, BufReader, Read as _, Write as _};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// Minimal HTTP server recording "METHOD /path" for every request and
+    /// answering `{"id": "ses_new"}` to everything.
+    fn fake_server() -> (String, Arc<Mutex<Vec<String>>>) {
+        failing_server(Vec::new())
+    }
+
+    /// [`fake_server`] answering the first requests with the error
+    /// `statuses`, one each.
+    fn failing_server(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let mut statuses = statuses.into_iter();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(v) = header.to_ascii_lowercase().strip_prefix("content-length:") {
+                        length = v.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let mut parts = request_line.split_whitespace();
+                log.lock().unwrap().push(format!(
+                    "{} {}",
+                    parts.next().unwrap_or(""),
+                    parts.next().unwrap_or("")
+                ));
+                if let Some(status) = statuses.next() {
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 {status} Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    );
+                    continue;
+                }
+                let body = r#"{"id":"ses_new"}"#;
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+            }
+        });
+        (url, seen)
+    }
+
+    #[tokio::test]
+    async fn test_prompt_without_session_fails() {
+        let handle = SessionHandle::spawn(OpenCodeClient::new("http://127.0.0.1:9"));
+        let err = handle.send_prompt("hi".into()).await.unwrap_err();
+        assert!(err.to_string().contains("no session"));
+        assert_eq!(handle.session_id().await, None);
+
+        // A deleted session is cleared, not kept as a target
+        handle.set_session("ses_gone");
+        handle.clear_session();
+        assert!(handle.send_prompt("hi".into()).await.is_err());
+        assert_eq!(handle.session_id().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_session_switch_applies_in_order() {
+        let (url, seen) = fake_server();
+        let handle = SessionHandle::spawn(OpenCodeClient::new(&url));
+        handle.set_session("ses_a");
+        let first = handle.send_prompt("one".into());
+        handle.set_session("ses_b");
+        let second = handle.send_prompt("two".into());
+        // Both prompts were queued before either completed; each goes to
+        // the session that was current when it was queued.
+        let (first, second) = tokio::join!(first, second);
+        assert_eq!(first.unwrap(), "ses_a");
+        assert_eq!(second.unwrap(), "ses_b");
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                "POST /session/ses_a/prompt_async",
+                "POST /session/ses_b/prompt_async"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retryable_sends_are_retried() {
+        let retry = RetryPolicy {
+            retries: 2,
+            backoff: Duration::from_millis(1),
+        };
+        let (url, seen) = failing_server(vec![502, 503]);
+        let handle = SessionHandle::spawn(OpenCodeClient::new(&url));
+        handle.set_session("ses_a");
+        let target = handle.prompt_target().await.unwrap();
+        // Retries stay with the session the prompt was queued for
+        handle.set_session("ses_b");
+        let mut retries = Vec::new();
+        let sent = send_with_retry(&handle, target.clone(), "one", retry, |attempt| {
+            retries.push(attempt)
+        });
+        assert_eq!(sent.await.unwrap(), "ses_a");
+        assert_eq!(retries, vec![1, 2]);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec!["POST /session/ses_a/prompt_async"; 3]
+        );
+
+        // Out of retries, and errors that aren't passing
+        let (url, _) = failing_server(vec![502, 502, 502]);
+        let handle = SessionHandle::spawn(OpenCodeClient::new(&url));
+        let sent = send_with_retry(&handle, target.clone(), "two", retry, |_| {});
+        let err = sent.await.unwrap_err();
+        assert!(err.to_string().contains("502"), "{err}");
+        assert!(err.to_string().ends_with("(after 2 retries)"), "{err}");
+        let (url, seen) = failing_server(vec![500]);
+        let handle = SessionHandle::spawn(OpenCodeClient::new(&url));
+        let sent = send_with_retry(&handle, target, "three", retry, |_| {});
+        assert!(sent.await.is_err());
+        assert_eq!(seen.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_backoff_leaves_session_task_free() {
+        let retry = RetryPolicy {
+            retries: 1,
+            backoff: Duration::from_secs(60),
+        };
+        let (url, seen) = failing_server(vec![502]);
+        let handle = SessionHandle::spawn(OpenCodeClient::new(&url));
+        handle.set_session("ses_a");
+        let target = handle.prompt_target().await.unwrap();
+        let (retrying, mut retried) = mpsc::unbounded_channel();
+        let sender = handle.clone();
+        tokio::spawn(async move {
+            let on_retry = |attempt| {
+                let _ = retrying.send(attempt);
+            };
+            send_with_retry(&sender, target, "one", retry, on_retry).await
+        });
+        assert_eq!(retried.recv().await, Some(1));
+        // Answered while the send waits out its backoff
+        let session = handle.get_session("ses_a".into()).await.unwrap();
+        assert_eq!(session.unwrap().id, "ses_new");
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec!["POST /session/ses_a/prompt_async", "GET /session/ses_a"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_turns_in_order() {
+        let mut order = SendOrder::default();
+        let mut first = order.take_turn();
+        let mut second = order.take_turn();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        let later = tokio::spawn(async move {
+            second.wait().await;
+            log.lock().unwrap().push("second");
+        });
+        first.wait().await;
+        tokio::task::yield_now().await;
+        seen.lock().unwrap().push("first");
+        drop(first);
+        later.await.unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec!["first", "second"]);
+    }
+
+    #[test]
+    fn test_retry_backoff_doubles() {
+        let retry = RetryPolicy {
+            retries: 3,
+            backoff: Duration::from_millis(500),
+        };
+        assert_eq!(retry.delay(1), Duration::from_millis(500));
+        assert_eq!(retry.delay(3), Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_create_session_becomes_current() {
+        let (url, _) = fake_server();
+        let handle = SessionHandle::spawn(OpenCodeClient::new(&url));
+        let session = handle.create_session(None).await.unwrap();
+        assert_eq!(session.id, "ses_new");
+        assert_eq!(handle.session_id().await.as_deref(), Some("ses_new"));
+    }
+}
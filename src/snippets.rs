@@ -0,0 +1,371 @@
+// Snippets Module - Named prompt templates with focus placeholders
+//
+// Snippets live in the `[snippets]` table of conch.toml, name to template:
+//
+//     [snippets]
+//     review = "Review {file} for bugs and style issues"
+//
+// They are triggered by saying "run snippet review" or picked from the
+// command palette. Placeholders are filled from the focus stack when the
+// snippet is used, so "{file}" is whatever file OpenCode last touched.
+//
+// Any other placeholder is a parameter, filled by voice: with
+//
+//     fix-tests = "Fix the failing tests in the {module} module"
+//
+// saying "template fix tests in transport" fills `{module}` with
+// "transport". The words after the snippet's name are the values, in the
+// order the parameters first appear; a connecting word ("in", "for",
+// "to", ...) in front of a value is dropped, and with several parameters
+// each one after the first starts a new value.
+
+use std::collections::BTreeMap;
+
+use anyhow::{Result, anyhow};
+
+use crate::focus::{FocusEntry, FocusState};
+use crate::send::normalize_words;
+
+/// `[snippets]` table: snippet name to prompt template.
+pub type Snippets = BTreeMap<String, String>;
+
+/// Spoken prefixes that trigger a snippet, already normalized.
+const VOICE_TRIGGERS: &[&str] = &[
+    "run snippet",
+    "use snippet",
+    "use template",
+    "snippet",
+    "template",
+];
+/// Placeholders filled from the focus stack rather than by voice.
+const FOCUS_PLACEHOLDERS: &[&str] = &["focus", "file", "directory", "dir", "branch", "commit"];
+/// Words that may introduce a spoken value.
+const CONNECTORS: &[&str] = &[
+    "in", "on", "for", "to", "from", "with", "of", "at", "about", "into", "and",
+];
+
+/// Fill the placeholders of `template` from `focus`:
+///
+/// - `{focus}` — the focus entry under the pointer
+/// - `{file}`, `{directory}` (or `{dir}`), `{branch}`, `{commit}` — the
+///   most recent entry of that type
+///
+/// A placeholder with nothing to fill it is an error; the others take a
+/// spoken value, which only [`from_voice`] has.
+pub fn expand(template: &str, focus: &FocusState) -> Result<String> {
+    expand_with(template, focus, &BTreeMap::new())
+}
+
+/// [`expand`], with `values` for the parameters: placeholder name to the
+/// words spoken for it.
+pub fn expand_with(
+    template: &str,
+    focus: &FocusState,
+    values: &BTreeMap<String, String>,
+) -> Result<String> {
+    let (file, dir, branch, commit) = focus.recent_by_type();
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let Some(close) = after.find('}') else {
+            // A lone brace is just text
+            out.push_str(&rest[open..]);
+            return Ok(out);
+        };
+        let name = &after[..close];
+        let entry: Option<&FocusEntry> = match name {
+            "focus" => focus.current_entry(),
+            "file" => file,
+            "directory" | "dir" => dir,
+            "branch" => branch,
+            "commit" => commit,
+            _ => {
+                let value = values
+                    .get(name)
+                    .ok_or_else(|| anyhow!("no value spoken for {{{}}}", name))?;
+                out.push_str(value);
+                rest = &after[close + 1..];
+                continue;
+            }
+        };
+        let entry = entry.ok_or_else(|| anyhow!("nothing in focus for {{{}}}", name))?;
+        out.push_str(&entry.value_str());
+        rest = &after[close + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// The parameters of `template`: its placeholders not filled from the
+/// focus stack, in the order they first appear.
+pub fn parameters(template: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let after = &rest[open + 1..];
+        let Some(close) = after.find('}') else {
+            break;
+        };
+        let name = &after[..close];
+        if !FOCUS_PLACEHOLDERS.contains(&name) && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+        rest = &after[close + 1..];
+    }
+    names
+}
+
+/// The snippet name spoken in `transcript`, if it is a snippet trigger
+/// ("Run snippet review." gives "review"), with any values spoken after it.
+/// Normalized to lowercase words.
+pub fn voice_trigger(transcript: &str) -> Option<String> {
+    let tokens: Vec<&str> = transcript.split_whitespace().collect();
+    let spoken = normalize_words(&after_trigger(&tokens)?.join(" "));
+    (!spoken.is_empty()).then_some(spoken)
+}
+
+/// The words of `tokens` after a snippet trigger, as spoken.
+fn after_trigger<'a, 'b>(tokens: &'b [&'a str]) -> Option<&'b [&'a str]> {
+    VOICE_TRIGGERS
+        .iter()
+        .find_map(|trigger| strip_words(tokens, trigger).filter(|rest| !rest.is_empty()))
+}
+
+/// `tokens` after the normalized `words` they start with, if they do.
+fn strip_words<'a, 'b>(tokens: &'b [&'a str], words: &str) -> Option<&'b [&'a str]> {
+    let mut heard = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let word = normalize_words(token);
+        if word.is_empty() {
+            continue;
+        }
+        if !heard.is_empty() {
+            heard.push(' ');
+        }
+        heard.push_str(&word);
+        if heard == words {
+            return Some(&tokens[i + 1..]);
+        }
+        if !words.starts_with(&format!("{heard} ")) {
+            return None;
+        }
+    }
+    None
+}
+
+/// The values for `parameters` in the words spoken after a snippet's
+/// name, keeping what Whisper wrote but the sentence's end.
+fn spoken_values(tokens: &[&str], parameters: &[String]) -> Result<BTreeMap<String, String>> {
+    let connector = |token: &&str| CONNECTORS.contains(&normalize_words(token).as_str());
+    let mut parts: Vec<Vec<&str>> = Vec::new();
+    for token in tokens {
+        // One parameter takes every word, connecting or not
+        let starts_value = parts.is_empty() || (parameters.len() > 1 && connector(token));
+        if starts_value {
+            parts.push(Vec::new());
+            if connector(token) {
+                continue;
+            }
+        }
+        if let Some(part) = parts.last_mut() {
+            part.push(token);
+        }
+    }
+    let values: Vec<String> = parts
+        .iter()
+        .map(|part| {
+            part.join(" ")
+                .trim_end_matches(['.', ',', '!', '?', ';', ':'])
+                .to_string()
+        })
+        .filter(|value| !value.is_empty())
+        .collect();
+    if values.len() != parameters.len() {
+        let wanted: Vec<String> = parameters.iter().map(|p| format!("{{{p}}}")).collect();
+        if wanted.is_empty() {
+            return Err(anyhow!("takes no values, heard \"{}\"", tokens.join(" ")));
+        }
+        return Err(anyhow!(
+            "needs {} spoken, heard {} of them",
+            wanted.join(", "),
+            values.len()
+        ));
+    }
+    Ok(parameters.iter().cloned().zip(values).collect())
+}
+
+/// Look up a snippet by a spoken name. Names match ignoring case and
+/// punctuation, so "code review" finds `code-review`.
+pub fn find<'a>(snippets: &'a Snippets, spoken: &str) -> Option<(&'a str, &'a str)> {
+    let spoken = normalize_words(spoken);
+    snippets
+        .iter()
+        .find(|(name, _)| normalize_words(name) == spoken)
+        .map(|(name, template)| (name.as_str(), template.as_str()))
+}
+
+/// If `transcript` triggers a snippet, the expanded prompt, or why it
+/// couldn't be used. `None` for ordinary transcripts.
+pub fn from_voice(
+    snippets: &Snippets,
+    focus: &FocusState,
+    transcript: &str,
+) -> Option<Result<String>> {
+    let tokens: Vec<&str> = transcript.split_whitespace().collect();
+    let rest = after_trigger(&tokens)?;
+    // The longest name the words start with, so `fix` doesn't shadow `fix-tests`
+    let found = snippets
+        .iter()
+        .filter_map(|(name, template)| {
+            let spoken = normalize_words(name);
+            let values = strip_words(rest, &spoken)?;
+            Some((spoken.len(), name, template, values))
+        })
+        .max_by_key(|(len, ..)| *len);
+    let Some((_, name, template, values)) = found else {
+        return Some(Err(anyhow!(
+            "no snippet named '{}'",
+            normalize_words(&rest.join(" "))
+        )));
+    };
+    Some(
+        spoken_values(values, &parameters(template))
+            .and_then(|values| expand_with(template, focus, &values))
+            .map_err(|e| anyhow!("snippet '{}': {}", name, e)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn focus() -> FocusState {
+        let mut focus = FocusState::new();
+        focus.append(FocusEntry::Directory(PathBuf::from("src")));
+        focus.append(FocusEntry::Branch("main".into()));
+        focus.append(FocusEntry::File(PathBuf::from("src/main.rs")));
+        focus
+    }
+
+    fn snippets() -> Snippets {
+        Snippets::from([
+            ("review".to_string(), "Review {file} for bugs".to_string()),
+            (
+                "code-review".to_string(),
+                "Review {focus} on {branch}".to_string(),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_expand_placeholders() {
+        assert_eq!(
+            expand("Review {file} in {dir} on {branch}", &focus()).unwrap(),
+            "Review src/main.rs in src on main"
+        );
+        assert_eq!(
+            expand("No placeholders", &focus()).unwrap(),
+            "No placeholders"
+        );
+        assert_eq!(expand("Odd { brace", &focus()).unwrap(), "Odd { brace");
+    }
+
+    #[test]
+    fn test_expand_errors() {
+        let err = expand("Show {commit}", &focus()).unwrap_err();
+        assert!(err.to_string().contains("nothing in focus for {commit}"));
+        let err = expand("Hi {name}", &focus()).unwrap_err();
+        assert!(err.to_string().contains("no value spoken for {name}"));
+        assert!(expand("{focus}", &FocusState::new()).is_err());
+    }
+
+    #[test]
+    fn test_voice_trigger() {
+        assert_eq!(voice_trigger("Run snippet review."), Some("review".into()));
+        assert_eq!(
+            voice_trigger("snippet code review"),
+            Some("code review".into())
+        );
+        assert_eq!(voice_trigger("run the snippets"), None);
+        assert_eq!(voice_trigger("run snippet"), None);
+        assert_eq!(voice_trigger("open the config file"), None);
+    }
+
+    #[test]
+    fn test_find_ignores_punctuation() {
+        let snippets = snippets();
+        assert_eq!(find(&snippets, "Code review").unwrap().0, "code-review");
+        assert_eq!(find(&snippets, "review").unwrap().0, "review");
+        assert!(find(&snippets, "refactor").is_none());
+    }
+
+    #[test]
+    fn test_from_voice() {
+        let snippets = snippets();
+        let focus = focus();
+        assert_eq!(
+            from_voice(&snippets, &focus, "Run snippet code review.")
+                .unwrap()
+                .unwrap(),
+            "Review src/main.rs on main"
+        );
+        let err = from_voice(&snippets, &focus, "run snippet refactor")
+            .unwrap()
+            .unwrap_err();
+        assert!(err.to_string().contains("no snippet named 'refactor'"));
+        assert!(from_voice(&snippets, &focus, "fix the tests").is_none());
+    }
+
+    #[test]
+    fn test_parameters() {
+        assert_eq!(
+            parameters("Move {symbol} from {file} to {target}, then {symbol}"),
+            vec!["symbol", "target"]
+        );
+        assert!(parameters("Review {file} on {branch}").is_empty());
+    }
+
+    #[test]
+    fn test_spoken_parameters() {
+        let snippets = Snippets::from([
+            (
+                "fix-tests".to_string(),
+                "Fix the failing tests in the {module} module".to_string(),
+            ),
+            ("fix".to_string(), "Fix {file}".to_string()),
+            (
+                "move".to_string(),
+                "Move {symbol} from {file} to {target}".to_string(),
+            ),
+        ]);
+        let focus = focus();
+        let voice = |transcript| from_voice(&snippets, &focus, transcript).unwrap();
+        assert_eq!(
+            voice("Template fix tests in transport.").unwrap(),
+            "Fix the failing tests in the transport module"
+        );
+        // One parameter takes connecting words past the first
+        assert_eq!(
+            voice("template fix tests for remote_stt.rs and mock").unwrap(),
+            "Fix the failing tests in the remote_stt.rs and mock module"
+        );
+        assert_eq!(voice("use template fix").unwrap(), "Fix src/main.rs");
+        assert_eq!(
+            voice("template move parse args to the cli module").unwrap(),
+            "Move parse args from src/main.rs to the cli module"
+        );
+
+        let err = voice("template fix tests").unwrap_err().to_string();
+        assert!(err.contains("snippet 'fix-tests': needs {module} spoken"));
+        let err = voice("template move parse args").unwrap_err().to_string();
+        assert!(err.contains("needs {symbol}, {target} spoken, heard 1"));
+        let err = voice("template fix it now").unwrap_err().to_string();
+        assert!(err.contains("snippet 'fix': takes no values"));
+        // Without a voice, the palette can't fill parameters
+        let err = expand("Fix {module}", &focus).unwrap_err().to_string();
+        assert!(err.contains("no value spoken for {module}"));
+    }
+}
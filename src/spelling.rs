@@ -0,0 +1,221 @@
+// Spelling Module - Turns spelled-out speech into literal characters
+//
+// Whisper mangles identifiers, flags, and hashes, so they can be spelled
+// instead: "spell victor echo charlie" or "spell v-e-c" gives "vec". Letters
+// come from the NATO alphabet or as single letters, digits from their names,
+// and symbols from words like "dash" and "underscore". "capital" uppercases
+// the next letter. Spelling mode treats every transcript this way until
+// "stop spelling".
+
+use anyhow::{Result, anyhow};
+
+use crate::send::normalize_words;
+
+/// NATO alphabet, plus common alternative spellings Whisper produces.
+const NATO: &[(&str, char)] = &[
+    ("alfa", 'a'),
+    ("alpha", 'a'),
+    ("bravo", 'b'),
+    ("charlie", 'c'),
+    ("delta", 'd'),
+    ("echo", 'e'),
+    ("foxtrot", 'f'),
+    ("golf", 'g'),
+    ("hotel", 'h'),
+    ("india", 'i'),
+    ("juliet", 'j'),
+    ("juliett", 'j'),
+    ("kilo", 'k'),
+    ("lima", 'l'),
+    ("mike", 'm'),
+    ("november", 'n'),
+    ("oscar", 'o'),
+    ("papa", 'p'),
+    ("quebec", 'q'),
+    ("romeo", 'r'),
+    ("sierra", 's'),
+    ("tango", 't'),
+    ("uniform", 'u'),
+    ("victor", 'v'),
+    ("whiskey", 'w'),
+    ("whisky", 'w'),
+    ("x-ray", 'x'),
+    ("xray", 'x'),
+    ("yankee", 'y'),
+    ("zulu", 'z'),
+];
+
+const DIGITS: &[(&str, char)] = &[
+    ("zero", '0'),
+    ("one", '1'),
+    ("two", '2'),
+    ("three", '3'),
+    ("four", '4'),
+    ("five", '5'),
+    ("six", '6'),
+    ("seven", '7'),
+    ("eight", '8'),
+    ("nine", '9'),
+];
+
+const SYMBOLS: &[(&str, char)] = &[
+    ("dash", '-'),
+    ("hyphen", '-'),
+    ("minus", '-'),
+    ("underscore", '_'),
+    ("dot", '.'),
+    ("period", '.'),
+    ("point", '.'),
+    ("slash", '/'),
+    ("backslash", '\\'),
+    ("colon", ':'),
+    ("at", '@'),
+    ("hash", '#'),
+    ("equals", '='),
+    ("plus", '+'),
+    ("tilde", '~'),
+    ("space", ' '),
+];
+
+/// Words that uppercase the next letter.
+const CAPITAL: &[&str] = &["capital", "cap", "uppercase", "upper"];
+
+/// Phrases that turn spelling mode on or off, already normalized.
+const START_PHRASES: &[&str] = &["spelling mode", "start spelling", "spelling on"];
+const STOP_PHRASES: &[&str] = &[
+    "stop spelling",
+    "end spelling",
+    "done spelling",
+    "exit spelling",
+    "spelling off",
+];
+
+/// A spelling voice command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Enter spelling mode.
+    Start,
+    /// Leave spelling mode.
+    Stop,
+    /// "spell ..." spelled out, as literal characters.
+    Spell(String),
+}
+
+/// The spelling command `transcript` is, if any. "spell ..." only counts
+/// when the rest spells cleanly, so "spell check the readme" stays a prompt.
+pub fn command(transcript: &str) -> Option<Command> {
+    let words = normalize_words(transcript);
+    if START_PHRASES.contains(&words.as_str()) {
+        return Some(Command::Start);
+    }
+    if STOP_PHRASES.contains(&words.as_str()) {
+        return Some(Command::Stop);
+    }
+    let trimmed = transcript.trim();
+    let (first, rest) = trimmed.split_once(char::is_whitespace)?;
+    if !first.eq_ignore_ascii_case("spell") {
+        return None;
+    }
+    spell(rest).ok().map(Command::Spell)
+}
+
+/// Convert spelled-out words to the characters they stand for.
+pub fn spell(text: &str) -> Result<String> {
+    let mut out = String::new();
+    let mut capital = false;
+    for token in text.split(|c: char| c.is_whitespace() || c == ',') {
+        // Whisper ends sentences with punctuation nobody dictated
+        let token = token.trim_end_matches(['.', '!', '?']).to_lowercase();
+        if token.is_empty() {
+            continue;
+        }
+        if CAPITAL.contains(&token.as_str()) {
+            capital = true;
+            continue;
+        }
+        let chars = spell_token(&token).ok_or_else(|| anyhow!("can't spell '{}'", token))?;
+        for c in chars {
+            if capital && c.is_alphabetic() {
+                out.extend(c.to_uppercase());
+                capital = false;
+            } else {
+                out.push(c);
+            }
+        }
+    }
+    if out.is_empty() {
+        return Err(anyhow!("nothing to spell"));
+    }
+    Ok(out)
+}
+
+/// Characters for one lowercase token.
+fn spell_token(token: &str) -> Option<Vec<char>> {
+    let named = NATO
+        .iter()
+        .chain(DIGITS)
+        .chain(SYMBOLS)
+        .find(|(word, _)| *word == token);
+    if let Some((_, c)) = named {
+        return Some(vec![*c]);
+    }
+    // Single letters and digit runs: "v", "42"
+    if token.chars().count() == 1 && token.chars().all(char::is_alphanumeric)
+        || token.chars().all(|c| c.is_ascii_digit())
+    {
+        return Some(token.chars().collect());
+    }
+    // Hyphenated letters: "v-e-c"
+    let parts: Vec<&str> = token.split('-').collect();
+    if parts.len() > 1 && parts.iter().all(|p| p.chars().count() == 1) {
+        return Some(parts.iter().flat_map(|p| p.chars()).collect());
+    }
+    // Symbols Whisper already wrote out: "--", "/"
+    if token.chars().all(|c| c.is_ascii_punctuation()) {
+        return Some(token.chars().collect());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spell_nato_and_letters() {
+        assert_eq!(spell("Victor Echo Charlie.").unwrap(), "vec");
+        assert_eq!(spell("v-e-c").unwrap(), "vec");
+        assert_eq!(spell("V, E, C").unwrap(), "vec");
+        assert_eq!(spell("x-ray yankee zulu").unwrap(), "xyz");
+    }
+
+    #[test]
+    fn test_spell_digits_symbols_and_capitals() {
+        assert_eq!(spell("dash dash victor").unwrap(), "--v");
+        assert_eq!(spell("-- victor").unwrap(), "--v");
+        assert_eq!(
+            spell("capital alpha bravo underscore one two").unwrap(),
+            "Ab_12"
+        );
+        assert_eq!(spell("delta 4 foxtrot nine").unwrap(), "d4f9");
+        assert_eq!(spell("2024").unwrap(), "2024");
+        assert_eq!(
+            spell("banana").unwrap_err().to_string(),
+            "can't spell 'banana'"
+        );
+        assert!(spell("").is_err());
+    }
+
+    #[test]
+    fn test_command() {
+        assert_eq!(command("Spelling mode."), Some(Command::Start));
+        assert_eq!(command("stop spelling"), Some(Command::Stop));
+        assert_eq!(
+            command("Spell foxtrot oscar oscar."),
+            Some(Command::Spell("foo".into()))
+        );
+        // Not a clean spelling, so an ordinary prompt
+        assert_eq!(command("spell check the readme"), None);
+        assert_eq!(command("fix the spelling"), None);
+    }
+}
@@ -0,0 +1,181 @@
+// Stats Module - What a session added up to
+//
+// The stats view sums up the session in front: the prompts sent and the
+// words in them, how long transcribing and answering took on average, the
+// tools OpenCode called and the files it changed. Prompts, words and
+// response times come from the session's transcripts; tool calls and
+// transcription times aren't kept anywhere else, so they are counted here
+// as they happen. Each call is counted once, however many updates it gets.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::time::Duration;
+
+use crate::history::{HistoryEntry, TranscriptStatus};
+use crate::transport::ToolEvent;
+
+/// Tools whose `filePath` input is a file they changed.
+const FILE_TOOLS: &[&str] = &["edit", "write", "multiedit", "patch"];
+
+/// Tool calls and transcription times of one session.
+#[derive(Debug, Clone, Default)]
+pub struct SessionStats {
+    transcriptions: u32,
+    transcribing: Duration,
+    /// Calls already counted, by call ID.
+    calls: HashSet<String>,
+    tools: BTreeMap<String, usize>,
+    files: BTreeSet<String>,
+}
+
+/// The numbers the stats view shows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub prompts: usize,
+    pub words: usize,
+    /// Average time to transcribe a recording, once there was one.
+    pub transcription: Option<Duration>,
+    /// Average time from sending a prompt to OpenCode going idle.
+    pub response: Option<Duration>,
+    /// Calls per tool, most called first.
+    pub tools: Vec<(String, usize)>,
+    /// Files changed, in path order.
+    pub files: Vec<String>,
+}
+
+impl Summary {
+    /// Tool calls of every kind.
+    pub fn calls(&self) -> usize {
+        self.tools.iter().map(|(_, n)| n).sum()
+    }
+}
+
+impl SessionStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A recording was transcribed in `took`.
+    pub fn transcribed(&mut self, took: Duration) {
+        self.transcriptions += 1;
+        self.transcribing += took;
+    }
+
+    /// Count the call `event` is about, the first time it's seen, and the
+    /// file it changed once it completes.
+    pub fn tool(&mut self, event: &ToolEvent) {
+        let first = match &event.call_id {
+            Some(id) => self.calls.insert(id.clone()),
+            // Without an ID the updates can't be told apart: count the end
+            None => matches!(event.state.as_str(), "completed" | "error"),
+        };
+        if first {
+            *self.tools.entry(event.tool.clone()).or_default() += 1;
+        }
+        if event.state == "completed"
+            && FILE_TOOLS.contains(&event.tool.as_str())
+            && let Some(path) = event.input["filePath"].as_str()
+        {
+            self.files.insert(path.to_string());
+        }
+    }
+
+    /// The stats of the session whose transcripts are `transcripts`.
+    pub fn summary(&self, transcripts: &[HistoryEntry]) -> Summary {
+        let sent: Vec<&HistoryEntry> = transcripts
+            .iter()
+            .filter(|e| {
+                matches!(
+                    e.status,
+                    TranscriptStatus::Sent | TranscriptStatus::Answered | TranscriptStatus::Failed
+                )
+            })
+            .collect();
+        let words = sent.iter().map(|e| e.text.split_whitespace().count()).sum();
+        let response_secs: Vec<f64> = sent.iter().filter_map(|e| e.response_secs).collect();
+        let response = (!response_secs.is_empty()).then(|| {
+            Duration::from_secs_f64(response_secs.iter().sum::<f64>() / response_secs.len() as f64)
+        });
+        let transcription =
+            (self.transcriptions > 0).then(|| self.transcribing / self.transcriptions);
+        let mut tools: Vec<(String, usize)> =
+            self.tools.iter().map(|(t, n)| (t.clone(), *n)).collect();
+        tools.sort_by_key(|(_, n)| Reverse(*n));
+        Summary {
+            prompts: sent.len(),
+            words,
+            transcription,
+            response,
+            tools,
+            files: self.files.iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: Option<&str>, tool: &str, state: &str, path: &str) -> ToolEvent {
+        ToolEvent {
+            call_id: id.map(String::from),
+            session_id: None,
+            message_id: None,
+            tool: tool.into(),
+            input: serde_json::json!({ "filePath": path }),
+            state: state.into(),
+            metadata: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_calls_counted_once() {
+        let mut stats = SessionStats::new();
+        for state in ["pending", "running", "completed"] {
+            stats.tool(&event(Some("c1"), "edit", state, "src/main.rs"));
+        }
+        stats.tool(&event(Some("c2"), "read", "completed", "src/lib.rs"));
+        stats.tool(&event(Some("c3"), "edit", "error", "src/lib.rs"));
+        stats.tool(&event(None, "bash", "running", ""));
+        stats.tool(&event(None, "bash", "completed", ""));
+        stats.tool(&event(Some("c4"), "write", "completed", "README.md"));
+
+        let summary = stats.summary(&[]);
+        assert_eq!(
+            summary.tools,
+            vec![
+                ("edit".to_string(), 2),
+                ("bash".to_string(), 1),
+                ("read".to_string(), 1),
+                ("write".to_string(), 1),
+            ]
+        );
+        assert_eq!(summary.calls(), 5);
+        // Reads and failed edits change nothing
+        assert_eq!(summary.files, vec!["README.md", "src/main.rs"]);
+    }
+
+    #[test]
+    fn test_summary_of_transcripts() {
+        let mut stats = SessionStats::new();
+        assert_eq!(stats.summary(&[]).transcription, None);
+        stats.transcribed(Duration::from_millis(400));
+        stats.transcribed(Duration::from_millis(800));
+
+        let mut fast = HistoryEntry::new("run the tests", None);
+        fast.status = TranscriptStatus::Answered;
+        fast.response_secs = Some(4.0);
+        let mut slow = HistoryEntry::new("now fix the failing one", None);
+        slow.status = TranscriptStatus::Answered;
+        slow.response_secs = Some(8.0);
+        let mut waiting = HistoryEntry::new("and commit", None);
+        waiting.status = TranscriptStatus::Sent;
+        let discarded = HistoryEntry::new("never mind", None);
+
+        let summary = stats.summary(&[fast, slow, waiting, discarded]);
+        assert_eq!(summary.prompts, 3);
+        assert_eq!(summary.words, 10);
+        assert_eq!(summary.transcription, Some(Duration::from_millis(600)));
+        assert_eq!(summary.response, Some(Duration::from_secs(6)));
+    }
+}
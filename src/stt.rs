@@ -1,16 +1,133 @@
 // STT Module - Takes audio buffer, returns transcript via whisper-rs
+//
+// Given silence, Whisper doesn't say nothing: it makes something up, most
+// often "Thank you." or "you". So a recording is only transcribed if it has
+// some speech in it, judged by its level in short frames, and Whisper drops
+// segments it thinks more likely silence than speech by itself.
+
+use std::path::Path;
 
 use anyhow::{Result, anyhow};
+use serde::Deserialize;
 use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
 
 use crate::audio::resample;
+use crate::models::ModelInfo;
+use crate::remote_stt::{Request, SttClient};
+use crate::viz::rms;
+
+/// A transcript along with Whisper's confidence in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transcript {
+    pub text: String,
+    /// Mean probability of the text tokens, 0.0–1.0. 1.0 when there were none.
+    pub confidence: f32,
+}
+
+/// `[stt]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SttConfig {
+    /// Language new sessions are transcribed in: a Whisper code ("es"), a
+    /// name ("Spanish"), or "auto" to let Whisper detect it.
+    pub language: String,
+    /// The more accurate model a pending prompt's recording is transcribed
+    /// again with on request: a name or path.
+    pub retry_model: String,
+    /// A `conch serve-stt` server to transcribe on instead of loading a
+    /// model, e.g. "http://gpu-box:4100".
+    pub server: Option<String>,
+    /// RMS level at and above which a frame of a recording counts as
+    /// speech. Recordings without enough of it aren't transcribed; 0 turns
+    /// the check off.
+    pub speech_rms: f32,
+    /// Whisper's no-speech probability above which it drops a segment it
+    /// isn't sure of, 0.0–1.0. Lower drops more.
+    pub no_speech_threshold: f32,
+}
+
+impl Default for SttConfig {
+    fn default() -> Self {
+        Self {
+            language: "en".into(),
+            retry_model: "large-v3-turbo".into(),
+            server: None,
+            speech_rms: 0.01,
+            no_speech_threshold: 0.6,
+        }
+    }
+}
+
+impl SttConfig {
+    pub fn speech_gate(&self) -> SpeechGate {
+        SpeechGate {
+            speech_rms: self.speech_rms,
+            no_speech_threshold: self.no_speech_threshold,
+        }
+    }
+}
+
+/// When a recording counts as silence, before and during inference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeechGate {
+    /// See [`SttConfig::speech_rms`].
+    pub speech_rms: f32,
+    /// See [`SttConfig::no_speech_threshold`].
+    pub no_speech_threshold: f32,
+}
+
+impl Default for SpeechGate {
+    fn default() -> Self {
+        SttConfig::default().speech_gate()
+    }
+}
+
+impl SpeechGate {
+    /// Whether `samples` have at least [`MIN_SPEECH_SECS`] of frames at or
+    /// above the speech level.
+    pub fn hears_speech(&self, samples: &[f32], sample_rate: u32) -> bool {
+        if self.speech_rms <= 0.0 {
+            return !samples.is_empty();
+        }
+        let frame = (sample_rate as usize / FRAMES_PER_SEC).max(1);
+        let needed = (MIN_SPEECH_SECS * FRAMES_PER_SEC as f32).round() as usize;
+        let voiced = samples
+            .chunks(frame)
+            .filter(|f| rms(f) >= self.speech_rms)
+            .count();
+        voiced >= needed.max(1)
+    }
+}
+
+/// Token limit when transcribing a short answer.
+const ANSWER_MAX_TOKENS: i32 = 8;
+/// Frames a second the speech level is measured in (20 ms each).
+const FRAMES_PER_SEC: usize = 50;
+/// Least speech, in seconds, for a recording to be transcribed: a cough or
+/// a click is shorter.
+pub const MIN_SPEECH_SECS: f32 = 0.1;
 
 /// Wraps whisper-rs to provide local speech-to-text transcription.
 ///
 /// Loads a Whisper model from disk and transcribes f32 PCM audio buffers.
 /// The model file (e.g. `ggml-base.en.bin`) must be downloaded separately.
+///
+/// It's shared by the threads transcribing recordings and captions. That's
+/// safe because whisper-rs makes `WhisperContext` `Send` and `Sync`: the
+/// loaded model is read-only, and each transcription runs in a
+/// `WhisperState` of its own.
 pub struct Transcriber {
-    ctx: WhisperContext,
+    engine: Engine,
+    english_only: bool,
+    gate: SpeechGate,
+}
+
+enum Engine {
+    Whisper(WhisperContext),
+    /// Hears the same text in every recording.
+    Fixed(String),
+    /// Sends every recording to a transcription server.
+    Remote(SttClient),
 }
 
 impl Transcriber {
@@ -21,7 +138,51 @@ impl Transcriber {
     pub fn new(model_path: &str) -> Result<Self> {
         let ctx = WhisperContext::new_with_params(model_path, WhisperContextParameters::default())
             .map_err(|e| anyhow!("Failed to load Whisper model from '{}': {}", model_path, e))?;
-        Ok(Self { ctx })
+        let english_only = Path::new(model_path)
+            .file_name()
+            .and_then(|name| ModelInfo::parse(&name.to_string_lossy()))
+            .is_some_and(|info| info.english_only);
+        Ok(Self {
+            engine: Engine::Whisper(ctx),
+            english_only,
+            gate: SpeechGate::default(),
+        })
+    }
+
+    /// A transcriber that hears `text` in any recording with audio in it,
+    /// for running without a model (`--mock`).
+    pub fn fixed(text: impl Into<String>) -> Self {
+        Self {
+            engine: Engine::Fixed(text.into()),
+            english_only: false,
+            gate: SpeechGate::default(),
+        }
+    }
+
+    /// A transcriber that has the `conch serve-stt` server at `url` do the
+    /// work.
+    pub fn remote(url: &str) -> Result<Self> {
+        let client = SttClient::connect(url)?;
+        Ok(Self {
+            english_only: client.english_only(),
+            engine: Engine::Remote(client),
+            gate: SpeechGate::default(),
+        })
+    }
+
+    /// Judge silence with `gate` instead of the default one.
+    pub fn with_gate(mut self, gate: SpeechGate) -> Self {
+        self.gate = gate;
+        self
+    }
+
+    pub fn gate(&self) -> SpeechGate {
+        self.gate
+    }
+
+    /// Whether the model only understands English (a `.en` model).
+    pub fn english_only(&self) -> bool {
+        self.english_only
     }
 
     /// Transcribe an audio buffer to text.
@@ -30,10 +191,93 @@ impl Transcriber {
     /// The audio is resampled to 16kHz if needed before running Whisper.
     /// Returns the transcribed text, trimmed of whitespace.
     pub fn transcribe(&self, samples: &[f32], sample_rate: u32) -> Result<String> {
-        if samples.is_empty() {
-            return Ok(String::new());
+        Ok(self.transcribe_with_confidence(samples, sample_rate)?.text)
+    }
+
+    /// Like [`transcribe`](Self::transcribe), but also reports the mean
+    /// token probability so callers can decide whether to trust the text.
+    pub fn transcribe_with_confidence(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+    ) -> Result<Transcript> {
+        self.transcribe_in(samples, sample_rate, "en")
+    }
+
+    /// Like [`transcribe_with_confidence`](Self::transcribe_with_confidence),
+    /// telling Whisper to expect `language` (a Whisper code, or "auto" to
+    /// detect it). English-only models ignore the hint.
+    pub fn transcribe_in(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        language: &str,
+    ) -> Result<Transcript> {
+        self.run(samples, sample_rate, language, None, None)
+    }
+
+    /// Like [`transcribe_in`](Self::transcribe_in), with Whisper primed
+    /// with `prompt`, e.g. the project's file names and symbols, so it
+    /// writes them the way the project does.
+    pub fn transcribe_primed(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        language: &str,
+        prompt: &str,
+    ) -> Result<Transcript> {
+        self.run(samples, sample_rate, language, Some(prompt), None)
+    }
+
+    /// Transcribe a short spoken answer. Whisper is primed with `vocabulary`
+    /// (e.g. "Yes. No. Cancel.") and stops after a few tokens, which keeps
+    /// one-word answers fast.
+    pub fn transcribe_answer(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        language: &str,
+        vocabulary: &str,
+    ) -> Result<Transcript> {
+        let max_tokens = Some(ANSWER_MAX_TOKENS);
+        self.run(samples, sample_rate, language, Some(vocabulary), max_tokens)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(samples = samples.len()))]
+    fn run(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        language: &str,
+        prompt: Option<&str>,
+        max_tokens: Option<i32>,
+    ) -> Result<Transcript> {
+        // Skips loading the audio into Whisper, or sending it to a server
+        if !self.gate.hears_speech(samples, sample_rate) {
+            return Ok(Transcript {
+                text: String::new(),
+                confidence: 1.0,
+            });
         }
 
+        let ctx = match &self.engine {
+            Engine::Whisper(ctx) => ctx,
+            Engine::Fixed(text) => {
+                return Ok(Transcript {
+                    text: text.clone(),
+                    confidence: 1.0,
+                });
+            }
+            Engine::Remote(client) => {
+                let request = Request {
+                    language: language.to_string(),
+                    prompt: prompt.map(str::to_string),
+                    answer: max_tokens.is_some(),
+                };
+                return client.transcribe(samples, sample_rate, &request);
+            }
+        };
+
         // Resample to 16kHz (Whisper's expected rate) if necessary
         let samples_16k = if sample_rate != 16000 {
             resample(samples, sample_rate, 16000)
@@ -41,13 +285,12 @@ impl Transcriber {
             samples.to_vec()
         };
 
-        let mut state = self
-            .ctx
+        let mut state = ctx
             .create_state()
             .map_err(|e| anyhow!("Failed to create Whisper state: {}", e))?;
 
         let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
-        params.set_language(Some("en"));
+        params.set_language(Some(language));
         params.set_print_special(false);
         params.set_print_progress(false);
         params.set_print_realtime(false);
@@ -55,6 +298,13 @@ impl Transcriber {
         // Optimize for short utterances
         params.set_single_segment(true);
         params.set_no_timestamps(true);
+        params.set_no_speech_thold(self.gate.no_speech_threshold);
+        if let Some(prompt) = prompt {
+            params.set_initial_prompt(prompt);
+        }
+        if let Some(max_tokens) = max_tokens {
+            params.set_max_tokens(max_tokens);
+        }
 
         state
             .full(params, &samples_16k)
@@ -64,27 +314,58 @@ impl Transcriber {
             .full_n_segments()
             .map_err(|e| anyhow!("Failed to get segment count: {}", e))?;
 
+        // Special tokens (timestamps, end-of-text, ...) sort after end-of-text
+        let eot = ctx.token_eot();
         let mut text = String::new();
+        let mut probs = Vec::new();
         for i in 0..num_segments {
             let segment = state
                 .full_get_segment_text(i)
                 .map_err(|e| anyhow!("Failed to get segment {} text: {}", i, e))?;
             text.push_str(&segment);
+
+            let n_tokens = state
+                .full_n_tokens(i)
+                .map_err(|e| anyhow!("Failed to get segment {} token count: {}", i, e))?;
+            for t in 0..n_tokens {
+                let id = state
+                    .full_get_token_id(i, t)
+                    .map_err(|e| anyhow!("Failed to get token {} id: {}", t, e))?;
+                if id >= eot {
+                    continue;
+                }
+                let p = state
+                    .full_get_token_prob(i, t)
+                    .map_err(|e| anyhow!("Failed to get token {} probability: {}", t, e))?;
+                probs.push(p);
+            }
         }
 
-        Ok(text.trim().to_string())
+        Ok(Transcript {
+            text: text.trim().to_string(),
+            confidence: mean_probability(&probs),
+        })
     }
 }
 
-// WhisperContext is thread-safe for creating states (each state is independent).
-// This allows sharing the Transcriber across threads.
-unsafe impl Send for Transcriber {}
-unsafe impl Sync for Transcriber {}
+/// Mean of token probabilities; 1.0 for no tokens so empty output isn't "unsure".
+pub fn mean_probability(probs: &[f32]) -> f32 {
+    if probs.is_empty() {
+        return 1.0;
+    }
+    probs.iter().sum::<f32>() / probs.len() as f32
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_transcriber_is_shareable() {
+        fn shareable<T: Send + Sync>() {}
+        shareable::<Transcriber>();
+    }
+
     #[test]
     fn test_model_missing_error() {
         let result = Transcriber::new("/nonexistent/model.bin");
@@ -106,6 +387,45 @@ mod tests {
         assert!(samples.is_empty());
     }
 
+    #[test]
+    fn test_mean_probability() {
+        assert_eq!(mean_probability(&[]), 1.0);
+        assert!((mean_probability(&[0.9, 0.5, 0.7]) - 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fixed_transcriber() {
+        let transcriber = Transcriber::fixed("open the main file");
+        let heard = transcriber.transcribe_in(&[0.1; 1600], 16000, "en").unwrap();
+        assert_eq!(heard.text, "open the main file");
+        assert_eq!(transcriber.transcribe(&[], 16000).unwrap(), "");
+    }
+
+    #[test]
+    fn test_silence_is_not_transcribed() {
+        let transcriber = Transcriber::fixed("Thank you.");
+        let gate = transcriber.gate();
+        assert!(!gate.hears_speech(&[0.0; 16000], 16000));
+        assert!(!gate.hears_speech(&[0.005; 16000], 16000));
+        // A click is too short to be speech
+        let mut click = vec![0.0; 16000];
+        click[..480].fill(0.5);
+        assert!(!gate.hears_speech(&click, 16000));
+        let heard = transcriber.transcribe_in(&click, 16000, "en").unwrap();
+        assert_eq!(heard.text, "");
+
+        let mut word = vec![0.0; 48000];
+        word[9600..14400].fill(0.05);
+        assert!(gate.hears_speech(&word, 48000));
+        // Off, anything with samples goes to Whisper
+        let off = SpeechGate {
+            speech_rms: 0.0,
+            ..gate
+        };
+        assert!(off.hears_speech(&[0.0; 16000], 16000));
+        assert!(!off.hears_speech(&[], 16000));
+    }
+
     #[test]
     fn test_audio_buffer_format_validation() {
         // Whisper expects 16kHz mono f32. Verify our resampler handles common rates.
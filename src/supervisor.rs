@@ -0,0 +1,367 @@
+// Supervisor Module - Background tasks that can't die unnoticed
+//
+// conch does its network work on tokio tasks (the SSE loop, sending a
+// prompt, fetching a reply) and transcribes on threads of its own. One that
+// panics or gives up used to just vanish: events stopped arriving or
+// transcripts stopped coming, with nothing on screen to say why. The
+// supervisor keeps them all in one JoinSet that the TUI reaps every frame.
+// A task that panicked or failed is reported; a supervised loop is started
+// again, waiting longer before each restart, up to MAX_RESTARTS times.
+// Threads are joined by a blocking task of the set, so they're reaped the
+// same way.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio::task::{Id, JoinSet};
+
+/// Restarts of a supervised loop before it's given up.
+pub const MAX_RESTARTS: u32 = 3;
+/// Wait before the first restart; each next one waits twice as long.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// How a task ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Exit {
+    Finished,
+    /// It returned an error.
+    Failed(String),
+    /// It panicked, with this message.
+    Panicked(String),
+}
+
+/// What the supervisor did about a task that ended abnormally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Restart {
+    /// It runs once; it's gone.
+    No,
+    /// Started again after this long.
+    In(Duration),
+    /// Restarted [`MAX_RESTARTS`] times already; it's gone.
+    GaveUp,
+}
+
+/// A task that panicked or failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub name: &'static str,
+    pub exit: Exit,
+    pub restart: Restart,
+}
+
+type BoxFuture = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+
+/// How to start a supervised loop again.
+#[derive(Clone)]
+enum Make {
+    Task(Arc<dyn Fn() -> BoxFuture + Send + Sync>),
+    Thread(Arc<dyn Fn() -> std::io::Result<JoinHandle<()>> + Send + Sync>),
+}
+
+struct Task {
+    name: &'static str,
+    make: Option<Make>,
+    restarts: u32,
+}
+
+struct Inner {
+    set: JoinSet<Exit>,
+    tasks: HashMap<Id, Task>,
+    restart_delay: Duration,
+}
+
+/// The background tasks and threads of the TUI. Cloning it gives another
+/// handle on the same set.
+#[derive(Clone)]
+pub struct Supervisor {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                set: JoinSet::new(),
+                tasks: HashMap::new(),
+                restart_delay: RESTART_DELAY,
+            })),
+        }
+    }
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `task` once, reporting it if it fails or panics.
+    pub fn spawn(
+        &self,
+        name: &'static str,
+        task: impl Future<Output = Result<()>> + Send + 'static,
+    ) {
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.set.spawn(outcome(task)).id();
+        inner.tasks.insert(id, Task::once(name));
+    }
+
+    /// Run blocking `work` once on tokio's blocking threads, reporting it
+    /// if it panics.
+    pub fn spawn_blocking(&self, name: &'static str, work: impl FnOnce() + Send + 'static) {
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner
+            .set
+            .spawn_blocking(move || {
+                work();
+                Exit::Finished
+            })
+            .id();
+        inner.tasks.insert(id, Task::once(name));
+    }
+
+    /// Run the loop `make` returns, and again from `make` whenever it fails
+    /// or panics.
+    pub fn supervise<F>(&self, name: &'static str, make: impl Fn() -> F + Send + Sync + 'static)
+    where
+        F: Future<Output = Result<()>> + Send + 'static,
+    {
+        let make = Make::Task(Arc::new(move || Box::pin(make()) as BoxFuture));
+        self.inner
+            .lock()
+            .unwrap()
+            .start(Task::supervised(name, make), Duration::ZERO);
+    }
+
+    /// Run the thread `spawn` starts, and start another whenever it panics.
+    pub fn supervise_thread(
+        &self,
+        name: &'static str,
+        spawn: impl Fn() -> std::io::Result<JoinHandle<()>> + Send + Sync + 'static,
+    ) {
+        let make = Make::Thread(Arc::new(spawn));
+        self.inner
+            .lock()
+            .unwrap()
+            .start(Task::supervised(name, make), Duration::ZERO);
+    }
+
+    /// Collect the tasks that have ended, restarting supervised loops that
+    /// failed or panicked, and report those. Never blocks.
+    pub fn reap(&self) -> Vec<Report> {
+        let mut inner = self.inner.lock().unwrap();
+        let mut reports = Vec::new();
+        while let Some(joined) = inner.set.try_join_next_with_id() {
+            let (id, exit) = match joined {
+                Ok((id, exit)) => (id, exit),
+                Err(e) if e.is_panic() => (e.id(), Exit::Panicked(panic_message(e.into_panic()))),
+                // Aborted
+                Err(e) => {
+                    inner.tasks.remove(&e.id());
+                    continue;
+                }
+            };
+            let Some(mut task) = inner.tasks.remove(&id) else {
+                continue;
+            };
+            if exit == Exit::Finished {
+                continue;
+            }
+            let restart = match task.make {
+                None => Restart::No,
+                Some(_) if task.restarts >= MAX_RESTARTS => Restart::GaveUp,
+                Some(_) => {
+                    let delay = inner.restart_delay * 2u32.pow(task.restarts);
+                    task.restarts += 1;
+                    let name = task.name;
+                    inner.start(task, delay);
+                    reports.push(Report {
+                        name,
+                        exit,
+                        restart: Restart::In(delay),
+                    });
+                    continue;
+                }
+            };
+            reports.push(Report {
+                name: task.name,
+                exit,
+                restart,
+            });
+        }
+        reports
+    }
+
+    #[cfg(test)]
+    fn with_restart_delay(delay: Duration) -> Self {
+        let supervisor = Self::new();
+        supervisor.inner.lock().unwrap().restart_delay = delay;
+        supervisor
+    }
+}
+
+impl Task {
+    fn once(name: &'static str) -> Self {
+        Self {
+            name,
+            make: None,
+            restarts: 0,
+        }
+    }
+
+    fn supervised(name: &'static str, make: Make) -> Self {
+        Self {
+            name,
+            make: Some(make),
+            restarts: 0,
+        }
+    }
+}
+
+impl Inner {
+    /// Start supervised `task` after `delay`, zero unless it's a restart.
+    fn start(&mut self, task: Task, delay: Duration) {
+        let id = match task.make.clone() {
+            Some(Make::Task(make)) => {
+                let future = make();
+                self.set.spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    outcome(future).await
+                })
+            }
+            Some(Make::Thread(spawn)) => self.set.spawn_blocking(move || {
+                std::thread::sleep(delay);
+                match spawn() {
+                    Ok(thread) => match thread.join() {
+                        Ok(()) => Exit::Finished,
+                        Err(panic) => Exit::Panicked(panic_message(panic)),
+                    },
+                    Err(e) => Exit::Failed(format!("thread not started: {e}")),
+                }
+            }),
+            None => return,
+        }
+        .id();
+        self.tasks.insert(id, task);
+    }
+}
+
+/// How `task` ends, unless it panics.
+async fn outcome(task: impl Future<Output = Result<()>>) -> Exit {
+    match task.await {
+        Ok(()) => Exit::Finished,
+        Err(e) => Exit::Failed(format!("{e:#}")),
+    }
+}
+
+/// The message a panic was raised with.
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => match panic.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use anyhow::anyhow;
+
+    /// Reap until `n` reports came in.
+    async fn reports(supervisor: &Supervisor, n: usize) -> Vec<Report> {
+        let mut reports = Vec::new();
+        for _ in 0..200 {
+            reports.extend(supervisor.reap());
+            if reports.len() >= n {
+                return reports;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("only {} of {n} reports", reports.len());
+    }
+
+    #[tokio::test]
+    async fn test_ended_tasks_are_reported() {
+        let supervisor = Supervisor::new();
+        supervisor.spawn("fine", async { Ok(()) });
+        supervisor.spawn("send", async { Err(anyhow!("connection refused")) });
+        supervisor.spawn_blocking("vocabulary", || panic!("boom"));
+        let mut reports = reports(&supervisor, 2).await;
+        reports.sort_by_key(|r| r.name);
+        assert_eq!(
+            reports,
+            vec![
+                Report {
+                    name: "send",
+                    exit: Exit::Failed("connection refused".into()),
+                    restart: Restart::No,
+                },
+                Report {
+                    name: "vocabulary",
+                    exit: Exit::Panicked("boom".into()),
+                    restart: Restart::No,
+                },
+            ]
+        );
+        assert!(supervisor.reap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_supervised_loops_restart() {
+        let supervisor = Supervisor::with_restart_delay(Duration::from_millis(1));
+        let runs = Arc::new(AtomicU32::new(0));
+        let counted = Arc::clone(&runs);
+        supervisor.supervise("events", move || {
+            let run = counted.fetch_add(1, Ordering::SeqCst) + 1;
+            async move { Err(anyhow!("run {run} failed")) }
+        });
+        let reports = reports(&supervisor, MAX_RESTARTS as usize + 1).await;
+        let restarts: Vec<Restart> = reports.iter().map(|r| r.restart).collect();
+        let ms = Duration::from_millis;
+        assert_eq!(
+            restarts,
+            vec![
+                Restart::In(ms(1)),
+                Restart::In(ms(2)),
+                Restart::In(ms(4)),
+                Restart::GaveUp
+            ]
+        );
+        assert_eq!(reports[3].exit, Exit::Failed("run 4 failed".into()));
+        assert_eq!(runs.load(Ordering::SeqCst), MAX_RESTARTS + 1);
+    }
+
+    #[tokio::test]
+    async fn test_supervised_threads_restart() {
+        let supervisor = Supervisor::with_restart_delay(Duration::from_millis(1));
+        let runs = Arc::new(AtomicU32::new(0));
+        let counted = Arc::clone(&runs);
+        supervisor.supervise_thread("whisper", move || {
+            let counted = Arc::clone(&counted);
+            std::thread::Builder::new().spawn(move || {
+                // Panics once, then runs to the end
+                if counted.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("model crashed");
+                }
+            })
+        });
+        let reports = reports(&supervisor, 1).await;
+        assert_eq!(reports[0].exit, Exit::Panicked("model crashed".into()));
+        assert_eq!(reports[0].restart, Restart::In(Duration::from_millis(1)));
+        while runs.load(Ordering::SeqCst) < 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(supervisor.reap().is_empty());
+    }
+}
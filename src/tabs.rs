@@ -0,0 +1,227 @@
+// Tabs Module - Several OpenCode sessions attached at once
+//
+// Each tab keeps the per-session state: transcripts, focus stack, tool
+// activity, spoken language, last reply, conversation, stats, and whether OpenCode is busy. The event stream carries every
+// session's events, so each event is routed by its session ID to the tab it
+// belongs to.
+
+use std::path::PathBuf;
+use std::time::Instant;
+
+use crate::activity::ActivityFeed;
+use crate::focus::FocusState;
+use crate::history::HistoryEntry;
+use crate::language::Language;
+use crate::stats::SessionStats;
+use crate::timeline::Timeline;
+
+/// State of one attached session.
+#[derive(Default)]
+pub struct SessionTab {
+    pub session_id: String,
+    pub slug: Option<String>,
+    /// Directory the session was looked up or created for, when known.
+    pub directory: Option<PathBuf>,
+    pub transcripts: Vec<HistoryEntry>,
+    pub focus: FocusState,
+    pub activity: ActivityFeed,
+    pub busy: bool,
+    /// When OpenCode last became busy, while it is.
+    pub busy_since: Option<Instant>,
+    /// Language the session is dictated in; `None` for the configured one.
+    pub language: Option<&'static Language>,
+    /// Text of the assistant's last reply, once fetched.
+    pub last_reply: Option<String>,
+    /// Prompts, tool calls and replies, for the conversation view.
+    pub timeline: Timeline,
+    /// Tool calls and transcription times, for the stats view.
+    pub stats: SessionStats,
+}
+
+impl SessionTab {
+    pub fn new(session_id: impl Into<String>, slug: Option<String>) -> Self {
+        Self {
+            session_id: session_id.into(),
+            slug,
+            ..Self::default()
+        }
+    }
+
+    /// Short name for the tab bar: the slug, else the start of the ID.
+    pub fn title(&self) -> String {
+        match &self.slug {
+            Some(slug) => slug.clone(),
+            None => self.session_id.chars().take(12).collect(),
+        }
+    }
+}
+
+/// Where an event for a session should go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// The tab in front (also events that name no session).
+    Active,
+    /// A tab in the background, by index.
+    Background(usize),
+    /// A session no tab is attached to.
+    Unattached,
+}
+
+/// The open tabs, in order, and which one is in front.
+#[derive(Default)]
+pub struct TabSet {
+    tabs: Vec<SessionTab>,
+    active: usize,
+}
+
+impl TabSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tabs.is_empty()
+    }
+
+    /// Index of the tab in front.
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// Tabs in order.
+    pub fn tabs(&self) -> &[SessionTab] {
+        &self.tabs
+    }
+
+    pub fn tabs_mut(&mut self) -> &mut [SessionTab] {
+        &mut self.tabs
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut SessionTab> {
+        self.tabs.get_mut(index)
+    }
+
+    /// Index of the tab attached to `session_id`.
+    pub fn position(&self, session_id: &str) -> Option<usize> {
+        self.tabs.iter().position(|t| t.session_id == session_id)
+    }
+
+    /// Route an event for `session_id`. Before any tab is open, and for
+    /// events without a session, everything goes to the front.
+    pub fn route(&self, session_id: Option<&str>) -> Route {
+        let Some(id) = session_id else {
+            return Route::Active;
+        };
+        if self.tabs.is_empty() {
+            return Route::Active;
+        }
+        match self.position(id) {
+            Some(i) if i == self.active => Route::Active,
+            Some(i) => Route::Background(i),
+            None => Route::Unattached,
+        }
+    }
+
+    /// Append a tab, returning its index. The first tab becomes active.
+    pub fn add(&mut self, tab: SessionTab) -> usize {
+        self.tabs.push(tab);
+        self.tabs.len() - 1
+    }
+
+    /// Bring tab `index` to the front.
+    pub fn set_active(&mut self, index: usize) {
+        if index < self.tabs.len() {
+            self.active = index;
+        }
+    }
+
+    /// Index of the tab after (`step` 1) or before (`step` -1) the active
+    /// one, wrapping around.
+    pub fn cycle(&self, step: isize) -> usize {
+        if self.tabs.is_empty() {
+            return 0;
+        }
+        let len = self.tabs.len() as isize;
+        (self.active as isize + step).rem_euclid(len) as usize
+    }
+
+    /// Remove tab `index` and return it. The tab after it (or the new last
+    /// tab) becomes active if it was the active one.
+    pub fn remove(&mut self, index: usize) -> Option<SessionTab> {
+        if index >= self.tabs.len() {
+            return None;
+        }
+        let tab = self.tabs.remove(index);
+        if index < self.active || self.active >= self.tabs.len() {
+            self.active = self.active.saturating_sub(1);
+        }
+        Some(tab)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tabs(ids: &[&str]) -> TabSet {
+        let mut set = TabSet::new();
+        for id in ids {
+            set.add(SessionTab::new(*id, None));
+        }
+        set
+    }
+
+    #[test]
+    fn test_route_by_session() {
+        let mut set = tabs(&["ses_a", "ses_b"]);
+        assert_eq!(set.route(Some("ses_a")), Route::Active);
+        assert_eq!(set.route(Some("ses_b")), Route::Background(1));
+        assert_eq!(set.route(Some("ses_other")), Route::Unattached);
+        assert_eq!(set.route(None), Route::Active);
+        set.set_active(1);
+        assert_eq!(set.route(Some("ses_a")), Route::Background(0));
+        assert_eq!(set.route(Some("ses_b")), Route::Active);
+    }
+
+    #[test]
+    fn test_route_before_any_tab() {
+        assert_eq!(TabSet::new().route(Some("ses_a")), Route::Active);
+    }
+
+    #[test]
+    fn test_cycle_wraps() {
+        let mut set = tabs(&["a", "b", "c"]);
+        assert_eq!(set.cycle(1), 1);
+        assert_eq!(set.cycle(-1), 2);
+        set.set_active(2);
+        assert_eq!(set.cycle(1), 0);
+    }
+
+    #[test]
+    fn test_remove_keeps_active_valid() {
+        let mut set = tabs(&["a", "b", "c"]);
+        set.set_active(2);
+        set.remove(2);
+        assert_eq!(set.active(), 1);
+        set.remove(0);
+        assert_eq!(set.active(), 0);
+        assert_eq!(set.tabs()[0].session_id, "b");
+        assert!(set.remove(5).is_none());
+    }
+
+    #[test]
+    fn test_title() {
+        assert_eq!(
+            SessionTab::new("ses_0123456789abcdef", None).title(),
+            "ses_01234567"
+        );
+        assert_eq!(
+            SessionTab::new("ses_x", Some("brave-otter".into())).title(),
+            "brave-otter"
+        );
+    }
+}
@@ -219,6 +219,77 @@ pub mod assertions {
     }
 }
 
+#[cfg(test)]
+pub mod snapshots {
+    //! Plain-text snapshot assertions for rendered TUI buffers
+
+    use std::path::PathBuf;
+
+    use ratatui::buffer::Buffer;
+
+    /// Environment variable that makes `assert_snapshot` (re)write snapshot files.
+    pub const UPDATE_ENV: &str = "UPDATE_SNAPSHOTS";
+
+    /// Flatten a buffer into one line per row, ignoring styles.
+    ///
+    /// Cells hidden behind multi-width symbols (emoji) are skipped so each
+    /// row reads the way it appears on screen. Trailing spaces are trimmed.
+    pub fn buffer_to_string(buf: &Buffer) -> String {
+        let area = buf.area;
+        let mut out = String::new();
+        for y in area.top()..area.bottom() {
+            let mut line = String::new();
+            let mut skip = 0usize;
+            for x in area.left()..area.right() {
+                if skip > 0 {
+                    skip -= 1;
+                    continue;
+                }
+                let symbol = buf[(x, y)].symbol();
+                line.push_str(symbol);
+                skip = unicode_width::UnicodeWidthStr::width(symbol).saturating_sub(1);
+            }
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Path of a named snapshot file under `src/snapshots/`.
+    pub fn snapshot_path(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src")
+            .join("snapshots")
+            .join(format!("{}.snap", name))
+    }
+
+    /// Assert `actual` matches the stored snapshot `name`.
+    ///
+    /// Run with `UPDATE_SNAPSHOTS=1` to create or overwrite the snapshot
+    /// after an intentional layout change.
+    pub fn assert_snapshot(name: &str, actual: &str) {
+        let path = snapshot_path(name);
+        if std::env::var_os(UPDATE_ENV).is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, actual).unwrap();
+            return;
+        }
+        let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!(
+                "Missing snapshot {:?}; run with {}=1 to create it",
+                path, UPDATE_ENV
+            )
+        });
+        assert!(
+            expected == actual,
+            "Snapshot '{}' does not match.\n--- expected\n{}--- actual\n{}",
+            name,
+            expected,
+            actual
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
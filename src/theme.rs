@@ -0,0 +1,211 @@
+// Theme Module - The TUI's colors, named by what they mean
+//
+// The TUI never draws with a color literal, only with a [`Theme`] role:
+// `accent` for key hints and the pending prompt, `success`, `warning` and
+// `error` for statuses, and so on. Besides the default palette there are
+// two built-in ones: `high-contrast` (bright colors, nothing dim) and
+// `deuteranopia` (the Okabe–Ito colors, blue and orange in place of green
+// and red, which stay apart for red-green colorblind users). `[theme]`
+// picks one and can override single roles.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow};
+use ratatui::style::Color;
+use serde::Deserialize;
+
+/// `[theme]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// Built-in palette: "default", "high-contrast" or "deuteranopia".
+    pub name: String,
+    /// Colors replacing the palette's, by role (`[theme.colors]`): a name
+    /// ("yellow", "light-blue"), "#rrggbb", or a 0–255 palette index.
+    pub colors: BTreeMap<String, String>,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            name: "default".into(),
+            colors: BTreeMap::new(),
+        }
+    }
+}
+
+/// The color for each role in the TUI.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    /// Key hints, titles, the focus pointer and the pending prompt.
+    pub accent: Color,
+    /// Transcripts and other body text.
+    pub text: Color,
+    /// Placeholders, ages, details and the idle waveform.
+    pub muted: Color,
+    /// The idle status line and info notices.
+    pub info: Color,
+    /// Connected, completed, handled.
+    pub success: Color,
+    /// Busy, running, and prompts held for review.
+    pub warning: Color,
+    /// Recording, failures, disconnected.
+    pub error: Color,
+    /// MCP and custom tools, spelling, answering.
+    pub special: Color,
+    /// The waveform while recording.
+    pub waveform: Color,
+    /// Text drawn on a `warning` background.
+    pub on_warning: Color,
+}
+
+/// Built-in palette names, as `[theme] name` takes them.
+pub const NAMES: [&str; 3] = ["default", "high-contrast", "deuteranopia"];
+
+impl Theme {
+    pub const DEFAULT: Theme = Theme {
+        accent: Color::Cyan,
+        text: Color::White,
+        muted: Color::DarkGray,
+        info: Color::Gray,
+        success: Color::Green,
+        warning: Color::Yellow,
+        error: Color::Red,
+        special: Color::Magenta,
+        waveform: Color::Cyan,
+        on_warning: Color::Black,
+    };
+
+    /// Bright colors only, for washed-out screens and low vision.
+    pub const HIGH_CONTRAST: Theme = Theme {
+        accent: Color::LightCyan,
+        text: Color::White,
+        muted: Color::Gray,
+        info: Color::White,
+        success: Color::LightGreen,
+        warning: Color::LightYellow,
+        error: Color::LightRed,
+        special: Color::LightMagenta,
+        waveform: Color::White,
+        on_warning: Color::Black,
+    };
+
+    /// Okabe–Ito colors, distinct with red-green color blindness.
+    pub const DEUTERANOPIA: Theme = Theme {
+        accent: Color::Rgb(86, 180, 233),
+        text: Color::White,
+        muted: Color::Gray,
+        info: Color::Gray,
+        success: Color::Rgb(0, 114, 178),
+        warning: Color::Rgb(240, 228, 66),
+        error: Color::Rgb(213, 94, 0),
+        special: Color::Rgb(204, 121, 167),
+        waveform: Color::Rgb(86, 180, 233),
+        on_warning: Color::Black,
+    };
+
+    /// A built-in palette by name.
+    pub fn named(name: &str) -> Option<Theme> {
+        match name {
+            "default" => Some(Self::DEFAULT),
+            "high-contrast" => Some(Self::HIGH_CONTRAST),
+            "deuteranopia" => Some(Self::DEUTERANOPIA),
+            _ => None,
+        }
+    }
+
+    /// The palette `config` names, with its color overrides applied.
+    pub fn from_config(config: &ThemeConfig) -> Result<Theme> {
+        let mut theme = Self::named(&config.name).ok_or_else(|| {
+            anyhow!(
+                "unknown theme '{}' (built-in themes: {})",
+                config.name,
+                NAMES.join(", ")
+            )
+        })?;
+        for (role, value) in &config.colors {
+            let slot = theme
+                .role_mut(role)
+                .ok_or_else(|| anyhow!("unknown color role '{role}'"))?;
+            *slot = Color::from_str(value)
+                .map_err(|_| anyhow!("invalid color '{value}' for {role}"))?;
+        }
+        Ok(theme)
+    }
+
+    fn role_mut(&mut self, role: &str) -> Option<&mut Color> {
+        Some(match role {
+            "accent" => &mut self.accent,
+            "text" => &mut self.text,
+            "muted" => &mut self.muted,
+            "info" => &mut self.info,
+            "success" => &mut self.success,
+            "warning" => &mut self.warning,
+            "error" => &mut self.error,
+            "special" => &mut self.special,
+            "waveform" => &mut self.waveform,
+            "on_warning" => &mut self.on_warning,
+            _ => return None,
+        })
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_themes() {
+        for name in NAMES {
+            assert!(Theme::named(name).is_some(), "{name}");
+        }
+        let theme = Theme::from_config(&ThemeConfig::default()).unwrap();
+        assert_eq!(theme, Theme::DEFAULT);
+        // Statuses never rely on telling red from green
+        let safe = Theme::DEUTERANOPIA;
+        assert!(![safe.success, safe.error].contains(&Color::Green));
+    }
+
+    #[test]
+    fn test_color_overrides() {
+        let config = ThemeConfig {
+            name: "deuteranopia".into(),
+            colors: BTreeMap::from([
+                ("accent".into(), "light-yellow".into()),
+                ("error".into(), "#ff00ff".into()),
+            ]),
+        };
+        let theme = Theme::from_config(&config).unwrap();
+        assert_eq!(theme.accent, Color::LightYellow);
+        assert_eq!(theme.error, Color::Rgb(255, 0, 255));
+        assert_eq!(theme.success, Theme::DEUTERANOPIA.success);
+    }
+
+    #[test]
+    fn test_invalid_theme() {
+        let unknown = ThemeConfig {
+            name: "solarized".into(),
+            ..ThemeConfig::default()
+        };
+        let err = Theme::from_config(&unknown).unwrap_err().to_string();
+        assert!(err.contains("high-contrast"), "{err}");
+
+        let bad_role = ThemeConfig {
+            colors: BTreeMap::from([("border".into(), "red".into())]),
+            ..ThemeConfig::default()
+        };
+        assert!(Theme::from_config(&bad_role).is_err());
+        let bad_color = ThemeConfig {
+            colors: BTreeMap::from([("accent".into(), "reddish".into())]),
+            ..ThemeConfig::default()
+        };
+        assert!(Theme::from_config(&bad_color).is_err());
+    }
+}
@@ -0,0 +1,285 @@
+// Timeline Module - The session as one conversation
+//
+// The transcript list shows what was said, not what came of it. The
+// conversation view interleaves the prompts sent, the tool calls OpenCode
+// made for them and the assistant's replies, oldest first. Only the calls
+// that change something or fail are kept: reads, searches and listings
+// would drown the rest. A reply longer than FOLD_LINES is folded to its
+// first lines; any entry can be unfolded and folded again.
+
+use std::collections::VecDeque;
+
+use crate::activity::{Activity, ActivityStatus};
+use crate::dictation::wrap;
+use crate::history::{HistoryEntry, TranscriptStatus};
+
+/// Entries kept; the oldest go first.
+pub const LIMIT: usize = 200;
+/// Lines a folded entry shows.
+pub const FOLD_LINES: usize = 4;
+/// Tools that only look around, left out unless they fail.
+const LOOKUPS: &[&str] = &[
+    "read",
+    "glob",
+    "grep",
+    "list",
+    "todoread",
+    "todowrite",
+    "webfetch",
+];
+
+/// One step of the conversation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Turn {
+    /// A prompt sent, and the transcript it was, for its outcome.
+    Prompt {
+        transcript_id: Option<u64>,
+        text: String,
+    },
+    /// A tool call OpenCode made, at its latest status.
+    Tool(Activity),
+    /// The assistant's reply once OpenCode went idle.
+    Reply(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub turn: Turn,
+    pub folded: bool,
+}
+
+impl Entry {
+    /// The entry's text wrapped at `width`, cut to [`FOLD_LINES`] while
+    /// folded, and how many lines were cut.
+    pub fn lines(&self, width: usize) -> (Vec<String>, usize) {
+        let mut lines = match &self.turn {
+            Turn::Prompt { text, .. } | Turn::Reply(text) => wrap(text.trim(), width.max(1)),
+            Turn::Tool(activity) => vec![activity.to_string()],
+        };
+        if !self.folded || lines.len() <= FOLD_LINES {
+            return (lines, 0);
+        }
+        let hidden = lines.len() - FOLD_LINES;
+        lines.truncate(FOLD_LINES);
+        (lines, hidden)
+    }
+}
+
+/// A session's conversation, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct Timeline {
+    entries: VecDeque<Entry>,
+    /// The entry picked to fold or unfold; `None` follows the newest.
+    selected: Option<usize>,
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The prompts sent among `transcripts`, stored before this run. Their
+    /// replies weren't kept.
+    pub fn from_history(transcripts: &[HistoryEntry]) -> Self {
+        let mut timeline = Self::new();
+        for entry in transcripts {
+            if matches!(
+                entry.status,
+                TranscriptStatus::Sent | TranscriptStatus::Answered | TranscriptStatus::Failed
+            ) {
+                timeline.prompt(Some(entry.id), &entry.text);
+            }
+        }
+        timeline
+    }
+
+    /// Prompt `text` was sent, as transcript `transcript_id`.
+    pub fn prompt(&mut self, transcript_id: Option<u64>, text: &str) {
+        self.push(Turn::Prompt {
+            transcript_id,
+            text: text.to_string(),
+        });
+    }
+
+    /// A tool call was made or moved on, replacing the earlier entry of the
+    /// same call.
+    pub fn tool(&mut self, activity: &Activity) {
+        let existing = activity.call_id.as_ref().and_then(|id| {
+            self.entries
+                .iter_mut()
+                .rev()
+                .find(|e| matches!(&e.turn, Turn::Tool(a) if a.call_id.as_ref() == Some(id)))
+        });
+        if let Some(entry) = existing {
+            entry.turn = Turn::Tool(activity.clone());
+            return;
+        }
+        let lookup = LOOKUPS.contains(&activity.tool.as_str());
+        if lookup && activity.status != ActivityStatus::Failed {
+            return;
+        }
+        self.push(Turn::Tool(activity.clone()));
+    }
+
+    /// The assistant replied `text`. The same reply fetched again is
+    /// ignored.
+    pub fn reply(&mut self, text: &str) {
+        let last_reply = self.entries.iter().rev().find_map(|e| match &e.turn {
+            Turn::Reply(reply) => Some(reply),
+            _ => None,
+        });
+        if last_reply.is_some_and(|reply| reply == text) {
+            return;
+        }
+        self.push(Turn::Reply(text.to_string()));
+    }
+
+    /// Drop the tool calls of message `message_id`, which was removed.
+    pub fn remove_message(&mut self, message_id: &str) {
+        self.entries.retain(
+            |e| !matches!(&e.turn, Turn::Tool(a) if a.message_id.as_deref() == Some(message_id)),
+        );
+        self.selected = self.selected.filter(|&i| i < self.entries.len());
+    }
+
+    fn push(&mut self, turn: Turn) {
+        if self.entries.len() == LIMIT {
+            self.entries.pop_front();
+            self.selected = self.selected.and_then(|i| i.checked_sub(1));
+        }
+        let folded = matches!(turn, Turn::Reply(_));
+        self.entries.push_back(Entry { turn, folded });
+    }
+
+    /// Entries, oldest first.
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &Entry> + ExactSizeIterator {
+        self.entries.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entry picked, if one was.
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Pick the entry before the picked one, or the newest.
+    pub fn select_previous(&mut self) {
+        let last = self.entries.len().checked_sub(1);
+        self.selected = match self.selected {
+            Some(i) => Some(i.saturating_sub(1)),
+            None => last,
+        };
+    }
+
+    /// Pick the entry after the picked one; past the newest, follow the
+    /// newest again.
+    pub fn select_next(&mut self) {
+        self.selected = self
+            .selected
+            .map(|i| i + 1)
+            .filter(|&i| i < self.entries.len());
+    }
+
+    /// Fold or unfold the picked entry, else the newest reply.
+    pub fn toggle_fold(&mut self) {
+        let index = self.selected.or_else(|| {
+            self.entries
+                .iter()
+                .rposition(|e| matches!(e.turn, Turn::Reply(_)))
+        });
+        if let Some(entry) = index.and_then(|i| self.entries.get_mut(i)) {
+            entry.folded = !entry.folded;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::ToolEvent;
+
+    fn call(id: &str, tool: &str, state: &str) -> Activity {
+        let event = ToolEvent {
+            call_id: Some(id.into()),
+            session_id: None,
+            message_id: Some("msg-1".into()),
+            tool: tool.into(),
+            input: serde_json::json!({ "filePath": "src/lib.rs" }),
+            state: state.into(),
+            metadata: serde_json::Value::Null,
+        };
+        Activity::from_event(&event, &[])
+    }
+
+    #[test]
+    fn test_turns_interleave() {
+        let mut timeline = Timeline::new();
+        timeline.prompt(Some(1), "fix the parser");
+        timeline.tool(&call("c1", "read", "completed"));
+        timeline.tool(&call("c2", "edit", "running"));
+        timeline.tool(&call("c3", "grep", "error"));
+        timeline.tool(&call("c2", "edit", "completed"));
+        timeline.reply("Fixed it.");
+        timeline.reply("Fixed it.");
+
+        let turns: Vec<String> = timeline
+            .entries()
+            .map(|e| match &e.turn {
+                Turn::Prompt { text, .. } => format!("prompt {text}"),
+                Turn::Tool(a) => format!("tool {a}"),
+                Turn::Reply(text) => format!("reply {text}"),
+            })
+            .collect();
+        assert_eq!(
+            turns,
+            vec![
+                "prompt fix the parser",
+                "tool edit src/lib.rs \u{2713}",
+                "tool grep src/lib.rs \u{2717} failed",
+                "reply Fixed it.",
+            ]
+        );
+
+        timeline.remove_message("msg-1");
+        assert_eq!(timeline.entries().len(), 2);
+    }
+
+    #[test]
+    fn test_long_replies_fold() {
+        let mut timeline = Timeline::new();
+        timeline.prompt(None, "explain the cache");
+        let reply: Vec<String> = (1..=6).map(|i| format!("line {i}")).collect();
+        timeline.reply(&reply.join("\n"));
+
+        let entry = timeline.entries().last().unwrap();
+        let (lines, hidden) = entry.lines(40);
+        assert_eq!(lines.len(), FOLD_LINES);
+        assert_eq!(hidden, 2);
+        // Short entries have nothing to fold
+        assert_eq!(timeline.entries().next().unwrap().lines(40).1, 0);
+
+        // Without a pick, the newest reply folds and unfolds
+        timeline.toggle_fold();
+        assert_eq!(timeline.entries().last().unwrap().lines(40), (reply, 0));
+        timeline.select_previous();
+        assert_eq!(timeline.selected(), Some(1));
+        timeline.select_previous();
+        timeline.select_previous();
+        assert_eq!(timeline.selected(), Some(0));
+        timeline.select_next();
+        timeline.select_next();
+        assert_eq!(timeline.selected(), None);
+    }
+
+    #[test]
+    fn test_from_history() {
+        let mut sent = HistoryEntry::new("run the tests", None);
+        sent.status = TranscriptStatus::Answered;
+        let discarded = HistoryEntry::new("never mind", None);
+        let timeline = Timeline::from_history(&[sent, discarded]);
+        assert_eq!(timeline.entries().len(), 1);
+    }
+}
@@ -0,0 +1,309 @@
+// Tools Module - Display and focus rules for MCP and custom tools
+//
+// OpenCode's built-in tools (read, edit, bash, ...) are understood natively.
+// Anything else, typically MCP tools named `<server>_<tool>`, is shown
+// generically unless a `[[tools]]` rule in conch.toml says how to label it,
+// which input to show, and whether it contributes a focus entry:
+//
+//     [[tools]]
+//     name = "github_*"
+//     label = "github"
+//     target = "repo"
+//     focus = "directory"
+//
+// The built-in tools' focus entries come from the same kind of rules, in
+// `BUILTIN_RULES`, after the configured ones. Only `bash` is special: its
+// command line is parsed for `cd`, `git checkout` and the like.
+
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use serde::Deserialize;
+
+use crate::focus::{self, FocusEntry};
+use crate::transport::ToolEvent;
+
+/// Tools OpenCode ships with.
+pub const BUILTIN_TOOLS: &[&str] = &[
+    "read",
+    "write",
+    "edit",
+    "patch",
+    "bash",
+    "list",
+    "glob",
+    "grep",
+    "webfetch",
+    "task",
+    "todoread",
+    "todowrite",
+];
+
+/// Focus rules for OpenCode's own tools, tried after the configured ones.
+pub static BUILTIN_RULES: LazyLock<Vec<ToolRule>> = LazyLock::new(|| {
+    let rule = |name: &str, focus, key: &str| ToolRule {
+        name: name.into(),
+        label: None,
+        target: None,
+        focus: Some(focus),
+        focus_key: Some(key.into()),
+    };
+    vec![
+        // All file tools use "filePath" in OpenCode's SDK
+        rule("read", FocusKind::File, "filePath"),
+        rule("write", FocusKind::File, "filePath"),
+        rule("edit", FocusKind::File, "filePath"),
+        rule("list", FocusKind::Directory, "path"),
+    ]
+});
+
+/// Focus entry type a tool rule can contribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FocusKind {
+    File,
+    Directory,
+    Branch,
+    Commit,
+}
+
+impl FocusKind {
+    pub fn entry(self, value: &str) -> FocusEntry {
+        match self {
+            FocusKind::File => FocusEntry::File(PathBuf::from(value)),
+            FocusKind::Directory => FocusEntry::Directory(PathBuf::from(value)),
+            FocusKind::Branch => FocusEntry::Branch(value.to_string()),
+            FocusKind::Commit => FocusEntry::Commit(value.to_string()),
+        }
+    }
+}
+
+/// A `[[tools]]` table: how to show and use a non-built-in tool.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ToolRule {
+    /// Tool name; a trailing `*` matches any suffix (`github_*`).
+    pub name: String,
+    /// Shown in the activity feed instead of the tool name.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Input field shown as the call's target; see [`input_str`] for paths.
+    #[serde(default)]
+    pub target: Option<String>,
+    /// Focus entry type a completed call contributes.
+    #[serde(default)]
+    pub focus: Option<FocusKind>,
+    /// Input field holding the focus value, also a path; defaults to
+    /// `target`.
+    #[serde(default)]
+    pub focus_key: Option<String>,
+}
+
+impl ToolRule {
+    pub fn matches(&self, tool: &str) -> bool {
+        match self.name.strip_suffix('*') {
+            Some(prefix) => tool.starts_with(prefix),
+            None => tool == self.name,
+        }
+    }
+}
+
+/// Whether `tool` is one of OpenCode's own tools.
+pub fn is_builtin(tool: &str) -> bool {
+    BUILTIN_TOOLS.contains(&tool)
+}
+
+/// The first rule matching `tool`, if any.
+pub fn rule_for<'a>(rules: &'a [ToolRule], tool: &str) -> Option<&'a ToolRule> {
+    rules.iter().find(|rule| rule.matches(tool))
+}
+
+/// A string input field of `event`. `key` may be a dotted path into nested
+/// objects and arrays, such as `pr.head.ref` or `files.0.path`.
+pub fn input_str<'a>(event: &'a ToolEvent, key: &str) -> Option<&'a str> {
+    let value = match event.input.get(key) {
+        Some(value) => value,
+        None => key.split('.').try_fold(&event.input, |value, segment| {
+            match segment.parse::<usize>() {
+                Ok(index) if value.is_array() => value.get(index),
+                _ => value.get(segment),
+            }
+        })?,
+    };
+    value.as_str()
+}
+
+/// Focus entry contributed by a completed call: from the first of `rules`,
+/// then [`BUILTIN_RULES`], that matches the tool and has a `focus` type.
+pub fn map_focus(rules: &[ToolRule], event: &ToolEvent) -> Option<FocusEntry> {
+    if event.state != "completed" {
+        return None;
+    }
+    let rule = rules
+        .iter()
+        .chain(BUILTIN_RULES.iter())
+        .find(|rule| rule.focus.is_some() && rule.matches(&event.tool));
+    let Some(rule) = rule else {
+        return match event.tool.as_str() {
+            "bash" => focus::parse_bash_command(input_str(event, "command")?),
+            _ => None,
+        };
+    };
+    let kind = rule.focus?;
+    let key = rule.focus_key.as_ref().or(rule.target.as_ref())?;
+    let value = input_str(event, key)?.trim();
+    (!value.is_empty()).then(|| kind.entry(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str) -> ToolRule {
+        ToolRule {
+            name: name.into(),
+            label: None,
+            target: None,
+            focus: None,
+            focus_key: None,
+        }
+    }
+
+    fn event(tool: &str, state: &str, input: serde_json::Value) -> ToolEvent {
+        ToolEvent {
+            call_id: None,
+            session_id: None,
+            message_id: None,
+            tool: tool.into(),
+            input,
+            state: state.into(),
+            metadata: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_rule_matching() {
+        assert!(rule("github_*").matches("github_create_issue"));
+        assert!(!rule("github_*").matches("gitlab_create_issue"));
+        assert!(rule("jira_search").matches("jira_search"));
+        assert!(!rule("jira_search").matches("jira_search_all"));
+        let rules = vec![rule("github_get_file"), rule("github_*")];
+        assert_eq!(
+            rule_for(&rules, "github_get_file").unwrap().name,
+            "github_get_file"
+        );
+        assert_eq!(rule_for(&rules, "github_list").unwrap().name, "github_*");
+        assert!(rule_for(&rules, "read").is_none());
+    }
+
+    #[test]
+    fn test_builtin() {
+        assert!(is_builtin("bash"));
+        assert!(!is_builtin("github_create_issue"));
+    }
+
+    #[test]
+    fn test_map_focus() {
+        let rules = vec![ToolRule {
+            target: Some("path".into()),
+            focus: Some(FocusKind::File),
+            ..rule("fs_*")
+        }];
+        let input = serde_json::json!({"path": "docs/api.md"});
+        assert_eq!(
+            map_focus(&rules, &event("fs_read", "completed", input.clone())),
+            Some(FocusEntry::File(PathBuf::from("docs/api.md")))
+        );
+        assert_eq!(
+            map_focus(&rules, &event("fs_read", "running", input.clone())),
+            None
+        );
+        assert_eq!(map_focus(&rules, &event("other", "completed", input)), None);
+    }
+
+    #[test]
+    fn test_map_focus_key_overrides_target() {
+        let rules = vec![ToolRule {
+            target: Some("title".into()),
+            focus: Some(FocusKind::Branch),
+            focus_key: Some("head".into()),
+            ..rule("github_create_pr")
+        }];
+        let input = serde_json::json!({"title": "Fix it", "head": "fix/it"});
+        assert_eq!(
+            map_focus(&rules, &event("github_create_pr", "completed", input)),
+            Some(FocusEntry::Branch("fix/it".into()))
+        );
+        // Without a focus type nothing is contributed
+        let rules = vec![ToolRule {
+            target: Some("title".into()),
+            ..rule("github_*")
+        }];
+        let input = serde_json::json!({"title": "x"});
+        assert_eq!(
+            map_focus(&rules, &event("github_x", "completed", input)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_focus_key_paths() {
+        let rules = vec![
+            ToolRule {
+                focus: Some(FocusKind::Branch),
+                focus_key: Some("pr.head.ref".into()),
+                ..rule("open_pr")
+            },
+            ToolRule {
+                focus: Some(FocusKind::File),
+                focus_key: Some("files.1.path".into()),
+                ..rule("db_query")
+            },
+        ];
+        let input = serde_json::json!({"pr": {"head": {"ref": "fix/cache"}}});
+        assert_eq!(
+            map_focus(&rules, &event("open_pr", "completed", input)),
+            Some(FocusEntry::Branch("fix/cache".into()))
+        );
+        let input = serde_json::json!({"files": [{"path": "a.sql"}, {"path": "b.sql"}]});
+        assert_eq!(
+            map_focus(&rules, &event("db_query", "completed", input)),
+            Some(FocusEntry::File(PathBuf::from("b.sql")))
+        );
+        // A key with a dot in it is looked up as is first
+        let input = serde_json::json!({"a.b": "dotted"});
+        assert_eq!(
+            input_str(&event("x", "completed", input), "a.b"),
+            Some("dotted")
+        );
+    }
+
+    #[test]
+    fn test_builtin_rules_and_overrides() {
+        let input = serde_json::json!({"filePath": "src/lib.rs", "path": "src"});
+        assert_eq!(
+            map_focus(&[], &event("read", "completed", input.clone())),
+            Some(FocusEntry::File(PathBuf::from("src/lib.rs")))
+        );
+        // A configured rule wins over the built-in one
+        let rules = vec![ToolRule {
+            focus: Some(FocusKind::Directory),
+            focus_key: Some("path".into()),
+            ..rule("read")
+        }];
+        assert_eq!(
+            map_focus(&rules, &event("read", "completed", input.clone())),
+            Some(FocusEntry::Directory(PathBuf::from("src")))
+        );
+        // A rule without a focus type leaves the built-in one alone
+        let rules = vec![rule("read")];
+        assert_eq!(
+            map_focus(&rules, &event("read", "completed", input)),
+            Some(FocusEntry::File(PathBuf::from("src/lib.rs")))
+        );
+        let input = serde_json::json!({"command": "git switch main"});
+        assert_eq!(
+            map_focus(&[], &event("bash", "completed", input)),
+            Some(FocusEntry::Branch("main".into()))
+        );
+    }
+}
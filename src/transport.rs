@@ -1,8 +1,14 @@
 // Transport Module - HTTP/SSE communication with OpenCode server via reqwest
 
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 
+/// Heartbeat intervals the event stream may miss before it counts as idle.
+pub const MISSED_HEARTBEATS: u32 = 3;
+
 /// Connection status for the OpenCode server.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConnectionStatus {
@@ -14,11 +20,26 @@ pub enum ConnectionStatus {
 /// A tool execution event parsed from SSE.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolEvent {
+    /// Identifies the call across its state updates, when OpenCode sends one.
+    #[serde(default)]
+    pub call_id: Option<String>,
+    /// Session the call belongs to, when OpenCode sends it.
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Message the call is part of, when OpenCode sends it.
+    #[serde(default)]
+    pub message_id: Option<String>,
     pub tool: String,
     pub input: serde_json::Value,
     pub state: String,
+    /// What the tool reported back, e.g. the unified `diff` of an edit.
+    #[serde(default)]
+    pub metadata: serde_json::Value,
 }
 
+/// Title of the sessions conch creates, telling them apart from typed ones.
+pub const SESSION_TITLE: &str = "Voice session";
+
 /// Session info from OpenCode.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionInfo {
@@ -28,23 +49,98 @@ pub struct SessionInfo {
     pub title: Option<String>,
 }
 
+/// One part of a stored session message. Parts we don't export (files,
+/// step markers, snapshots, ...) are dropped when parsing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum MessagePart {
+    Text { text: String },
+    Tool(ToolEvent),
+}
+
+/// A message from a session's history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionMessage {
+    pub id: String,
+    /// `"user"` or `"assistant"`.
+    pub role: String,
+    /// Creation time in milliseconds since the Unix epoch.
+    pub created: Option<i64>,
+    pub parts: Vec<MessagePart>,
+}
+
 /// Events emitted from the SSE stream to the TUI.
 #[derive(Debug, Clone)]
 pub enum ServerEvent {
     Connected,
     SessionStatus { session_id: String, busy: bool },
     Tool(ToolEvent),
+    /// A session was deleted, e.g. from another client.
+    SessionDeleted { session_id: String },
+    /// A message was removed from a session, e.g. by an undo.
+    MessageRemoved {
+        session_id: String,
+        message_id: String,
+    },
+    /// OpenCode failed at something, e.g. the provider rejected a request
+    /// or a run was aborted. Not every error names a session.
+    Error {
+        session_id: Option<String>,
+        message: String,
+    },
     Heartbeat,
 }
 
+impl ServerEvent {
+    /// The session an event belongs to, if it names one.
+    pub fn session_id(&self) -> Option<&str> {
+        match self {
+            ServerEvent::SessionStatus { session_id, .. } => Some(session_id),
+            ServerEvent::Tool(te) => te.session_id.as_deref(),
+            ServerEvent::SessionDeleted { session_id }
+            | ServerEvent::MessageRemoved { session_id, .. } => Some(session_id),
+            ServerEvent::Error { session_id, .. } => session_id.as_deref(),
+            ServerEvent::Connected | ServerEvent::Heartbeat => None,
+        }
+    }
+}
+
+/// OpenCode answered a prompt with an error status.
+#[derive(Debug)]
+pub struct SendStatusError {
+    pub status: reqwest::StatusCode,
+    pub body: String,
+}
+
+impl std::fmt::Display for SendStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "send prompt failed ({}): {}", self.status, self.body)
+    }
+}
+
+impl std::error::Error for SendStatusError {}
+
+/// Whether a failed send is worth trying again: OpenCode couldn't be
+/// reached or timed out, or a proxy in front of it failed (502-504).
+pub fn is_retryable(err: &anyhow::Error) -> bool {
+    if let Some(e) = err.downcast_ref::<reqwest::Error>() {
+        return e.is_connect() || e.is_timeout();
+    }
+    err.downcast_ref::<SendStatusError>()
+        .is_some_and(|e| matches!(e.status.as_u16(), 502..=504))
+}
+
 /// HTTP + SSE client for OpenCode.
 pub struct OpenCodeClient {
     base_url: String,
     http: reqwest::Client,
     session_id: Option<String>,
+    /// Agent prompts are run with; `None` leaves it to OpenCode.
+    agent: Option<String>,
 }
 
 impl OpenCodeClient {
+    /// Create a client for the server at `base_url` (trailing slash optional).
     pub fn new(base_url: &str) -> Self {
         let http = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(30))
@@ -54,13 +150,20 @@ impl OpenCodeClient {
             base_url: base_url.trim_end_matches('/').to_string(),
             http,
             session_id: None,
+            agent: None,
         }
     }
 
+    /// The session prompts are sent to, if one has been set or created.
     pub fn session_id(&self) -> Option<&str> {
         self.session_id.as_deref()
     }
 
+    /// The agent prompts are run with, `None` for OpenCode's default.
+    pub fn agent(&self) -> Option<&str> {
+        self.agent.as_deref()
+    }
+
     /// Health check: GET /global/health
     pub async fn health_check(&self) -> Result<bool> {
         let url = format!("{}/global/health", self.base_url);
@@ -87,20 +190,25 @@ impl OpenCodeClient {
         Ok(sessions)
     }
 
-    /// Create a new session: POST /session
-    pub async fn create_session(&mut self) -> Result<String> {
+    /// Create a new session working in `directory`: POST /session. It
+    /// becomes current once the response is checked.
+    pub async fn create_session(&mut self, directory: Option<&Path>) -> Result<SessionInfo> {
         let url = format!("{}/session", self.base_url);
-        let resp = self.http.post(&url).json(&serde_json::json!({})).send().await?;
+        let mut request = self
+            .http
+            .post(&url)
+            .json(&serde_json::json!({ "title": SESSION_TITLE }));
+        if let Some(dir) = directory {
+            request = request.query(&[("directory", dir.to_string_lossy())]);
+        }
+        let resp = request.send().await?;
         if !resp.status().is_success() {
             return Err(anyhow!("create session failed: {}", resp.status()));
         }
         let body: serde_json::Value = resp.json().await?;
-        let id = body["id"]
-            .as_str()
-            .ok_or_else(|| anyhow!("no session id in response"))?
-            .to_string();
-        self.session_id = Some(id.clone());
-        Ok(id)
+        let session = created_session(body, directory)?;
+        self.session_id = Some(session.id.clone());
+        Ok(session)
     }
 
     /// Use an existing session ID.
@@ -108,25 +216,79 @@ impl OpenCodeClient {
         self.session_id = Some(id);
     }
 
+    /// Forget the current session, e.g. because it was deleted.
+    pub fn clear_session(&mut self) {
+        self.session_id = None;
+    }
+
+    /// Run later prompts with `agent`, or OpenCode's default when `None`.
+    pub fn set_agent(&mut self, agent: Option<String>) {
+        self.agent = agent;
+    }
+
     /// Send a prompt: POST /session/{id}/prompt_async
     pub async fn send_prompt(&self, text: &str) -> Result<()> {
         let session_id = self
             .session_id
-            .as_ref()
+            .as_deref()
             .ok_or_else(|| anyhow!("no session set"))?;
-        let url = format!("{}/session/{}/prompt_async", self.base_url, session_id);
-        let body = serde_json::json!({
-            "parts": [{"type": "text", "text": text}]
-        });
+        self.send_prompt_to(session_id, self.agent.as_deref(), text)
+            .await
+    }
+
+    /// Send a prompt to `session_id`, run with `agent` rather than the
+    /// current one.
+    pub async fn send_prompt_to(
+        &self,
+        session_id: &str,
+        agent: Option<&str>,
+        text: &str,
+    ) -> Result<()> {
+        let url = format!("{}{}", self.base_url, prompt_path(session_id));
+        let body = prompt_body(text, agent);
         let resp = self.http.post(&url).json(&body).send().await?;
         if !resp.status().is_success() {
             let status = resp.status();
-            let text = resp.text().await.unwrap_or_default();
-            return Err(anyhow!("send prompt failed ({}): {}", status, text));
+            let body = resp.text().await.unwrap_or_default();
+            return Err(SendStatusError { status, body }.into());
         }
         Ok(())
     }
 
+    /// Fetch one session: GET /session/{id}. `None` when OpenCode has no
+    /// such session.
+    pub async fn get_session(&self, session_id: &str) -> Result<Option<SessionInfo>> {
+        let url = format!("{}/session/{}", self.base_url, session_id);
+        let resp = self.http.get(&url).send().await?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            return Err(anyhow!("get session failed: {}", resp.status()));
+        }
+        Ok(Some(resp.json().await?))
+    }
+
+    /// Fetch the current session's messages: GET /session/{id}/message
+    pub async fn list_messages(&self) -> Result<Vec<SessionMessage>> {
+        let session_id = self
+            .session_id
+            .as_ref()
+            .ok_or_else(|| anyhow!("no session set"))?;
+        self.list_session_messages(session_id).await
+    }
+
+    /// Fetch any session's messages: GET /session/{id}/message
+    pub async fn list_session_messages(&self, session_id: &str) -> Result<Vec<SessionMessage>> {
+        let url = format!("{}/session/{}/message", self.base_url, session_id);
+        let resp = self.http.get(&url).send().await?;
+        if !resp.status().is_success() {
+            return Err(anyhow!("list messages failed: {}", resp.status()));
+        }
+        let body: serde_json::Value = resp.json().await?;
+        Ok(parse_session_messages(&body))
+    }
+
     /// Subscribe to SSE events: GET /event
     /// Returns a response whose body can be streamed line by line.
     pub async fn subscribe_events(
@@ -150,6 +312,7 @@ impl OpenCodeClient {
 /// Parse a single SSE `data:` JSON line into a `ServerEvent`, if relevant.
 ///
 /// Returns `None` for events we don't care about (heartbeats return Some for keep-alive tracking).
+#[tracing::instrument(level = "trace", skip_all)]
 pub fn parse_sse_event(json_str: &str) -> Option<ServerEvent> {
     let v: serde_json::Value = serde_json::from_str(json_str).ok()?;
     let event_type = v["type"].as_str()?;
@@ -164,30 +327,277 @@ pub fn parse_sse_event(json_str: &str) -> Option<ServerEvent> {
             Some(ServerEvent::SessionStatus { session_id, busy })
         }
         "message.part.updated" => {
-            let part = &v["properties"]["part"];
-            if part["type"].as_str()? != "tool" {
+            parse_tool_part(&v["properties"]["part"]).map(ServerEvent::Tool)
+        }
+        "session.deleted" => {
+            let session_id = v["properties"]["info"]["id"].as_str()?.to_string();
+            Some(ServerEvent::SessionDeleted { session_id })
+        }
+        "message.removed" => {
+            let props = &v["properties"];
+            Some(ServerEvent::MessageRemoved {
+                session_id: props["sessionID"].as_str()?.to_string(),
+                message_id: props["messageID"].as_str()?.to_string(),
+            })
+        }
+        "session.error" => {
+            let props = &v["properties"];
+            Some(ServerEvent::Error {
+                session_id: props["sessionID"].as_str().map(str::to_string),
+                message: describe_error(&props["error"]),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// One line for an OpenCode error object (`{"name": ..., "data": {...}}`):
+/// what went wrong, then the provider's own message if there is one.
+fn describe_error(error: &serde_json::Value) -> String {
+    let data = &error["data"];
+    let what = match error["name"].as_str() {
+        Some("MessageAbortedError") => "Run aborted".to_string(),
+        Some("MessageOutputLengthError") => "Reply cut off at the output limit".to_string(),
+        Some("ProviderAuthError") => match data["providerID"].as_str() {
+            Some(provider) => format!("Authentication with {provider} failed"),
+            None => "Provider authentication failed".to_string(),
+        },
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => "Unknown error".to_string(),
+    };
+    match data["message"].as_str().map(str::trim) {
+        // "Run aborted" needs no "Aborted" after it
+        Some(message)
+            if !message.is_empty()
+                && !what.to_lowercase().contains(&message.to_lowercase()) =>
+        {
+            format!("{what}: {message}")
+        }
+        _ => what,
+    }
+}
+
+/// Parse a `part` object of type `tool` into a `ToolEvent`.
+fn parse_tool_part(part: &serde_json::Value) -> Option<ToolEvent> {
+    if part["type"].as_str()? != "tool" {
+        return None;
+    }
+    // Tool name is at part.tool (not part.toolName)
+    let tool = part["tool"].as_str().unwrap_or("").to_string();
+    // State is an object: part.state.status is the status string,
+    // part.state.input holds the tool input
+    let state_obj = &part["state"];
+    let state = state_obj["status"].as_str().unwrap_or("unknown").to_string();
+    let input = state_obj
+        .get("input")
+        .cloned()
+        .unwrap_or(serde_json::Value::Null);
+    let metadata = state_obj
+        .get("metadata")
+        .cloned()
+        .unwrap_or(serde_json::Value::Null);
+    let call_id = part["callID"]
+        .as_str()
+        .or_else(|| part["id"].as_str())
+        .map(str::to_string);
+    let session_id = part["sessionID"].as_str().map(str::to_string);
+    let message_id = part["messageID"].as_str().map(str::to_string);
+    Some(ToolEvent {
+        call_id,
+        session_id,
+        message_id,
+        tool,
+        input,
+        state,
+        metadata,
+    })
+}
+
+/// Parse a `part` object into a `MessagePart`. Synthetic text parts (added by
+/// OpenCode, not typed by anyone) and empty text are skipped.
+pub fn parse_message_part(part: &serde_json::Value) -> Option<MessagePart> {
+    match part["type"].as_str()? {
+        "text" => {
+            if part["synthetic"].as_bool() == Some(true) {
+                return None;
+            }
+            let text = part["text"].as_str()?.trim();
+            if text.is_empty() {
                 return None;
             }
-            // Tool name is at part.tool (not part.toolName)
-            let tool = part["tool"].as_str().unwrap_or("").to_string();
-            // State is an object: part.state.status is the status string,
-            // part.state.input holds the tool input
-            let state_obj = &part["state"];
-            let state = state_obj["status"].as_str().unwrap_or("unknown").to_string();
-            let input = state_obj
-                .get("input")
-                .cloned()
-                .unwrap_or(serde_json::Value::Null);
-            Some(ServerEvent::Tool(ToolEvent {
-                tool,
-                input,
-                state,
-            }))
+            Some(MessagePart::Text {
+                text: text.to_string(),
+            })
         }
+        "tool" => parse_tool_part(part).map(MessagePart::Tool),
         _ => None,
     }
 }
 
+/// Parse the body of GET /session/{id}/message: an array of
+/// `{ info: { id, role, time: { created } }, parts: [...] }`.
+pub fn parse_session_messages(body: &serde_json::Value) -> Vec<SessionMessage> {
+    let Some(arr) = body.as_array() else {
+        return Vec::new();
+    };
+    arr.iter()
+        .filter_map(|m| {
+            let info = &m["info"];
+            Some(SessionMessage {
+                id: info["id"].as_str()?.to_string(),
+                role: info["role"].as_str()?.to_string(),
+                created: info["time"]["created"].as_i64(),
+                parts: m["parts"]
+                    .as_array()
+                    .map(|parts| parts.iter().filter_map(parse_message_part).collect())
+                    .unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// The session a create request for `directory` was answered with, unless
+/// the answer has no ID or the session works somewhere else.
+pub fn created_session(body: serde_json::Value, directory: Option<&Path>) -> Result<SessionInfo> {
+    let session: SessionInfo = serde_json::from_value(body)
+        .map_err(|e| anyhow!("unexpected create session response: {e}"))?;
+    if session.id.trim().is_empty() {
+        return Err(anyhow!("no session id in response"));
+    }
+    if let (Some(wanted), Some(got)) = (directory, session.directory.as_deref())
+        && Path::new(got) != wanted
+    {
+        return Err(anyhow!(
+            "session created in {got}, not {}",
+            wanted.display()
+        ));
+    }
+    Ok(session)
+}
+
+/// The session working in `dir`, if any.
+pub fn directory_session<'a>(sessions: &'a [SessionInfo], dir: &Path) -> Option<&'a SessionInfo> {
+    sessions
+        .iter()
+        .find(|s| s.directory.as_deref().map(Path::new) == Some(dir))
+}
+
+/// Path prompts for session `session_id` are POSTed to.
+pub fn prompt_path(session_id: &str) -> String {
+    format!("/session/{}/prompt_async", session_id)
+}
+
+/// Body of a prompt request: the text as one part, and the agent to run
+/// it with if one was picked.
+pub fn prompt_body(text: &str, agent: Option<&str>) -> serde_json::Value {
+    let mut body = serde_json::json!({
+        "parts": [{"type": "text", "text": text}]
+    });
+    if let Some(agent) = agent {
+        body["agent"] = agent.into();
+    }
+    body
+}
+
+/// `[connection]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ConnectionConfig {
+    /// Seconds the event stream may stay silent before conch reconnects.
+    /// Stretched to [`MISSED_HEARTBEATS`] heartbeat intervals when OpenCode
+    /// heartbeats less often.
+    pub idle_timeout_secs: u64,
+    /// Project directory sessions are looked up for. Unset uses the
+    /// directory conch starts in; `--dir` overrides it.
+    pub directory: Option<PathBuf>,
+}
+
+impl Default for ConnectionConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout_secs: 60,
+            directory: None,
+        }
+    }
+}
+
+/// Heartbeats on the event stream: how long it may stay silent, from the
+/// configured idle timeout and the longest heartbeat interval seen so far,
+/// and whether one is overdue. Kept across reconnects, so what was learned
+/// about the server isn't lost.
+#[derive(Debug, Clone)]
+pub struct Heartbeats {
+    minimum: Duration,
+    connected_at: Option<Instant>,
+    last_heartbeat: Option<Instant>,
+    interval: Option<Duration>,
+    connections: u32,
+}
+
+impl Heartbeats {
+    pub fn new(config: &ConnectionConfig) -> Self {
+        Self {
+            minimum: Duration::from_secs(config.idle_timeout_secs.max(1)),
+            connected_at: None,
+            last_heartbeat: None,
+            interval: None,
+            connections: 0,
+        }
+    }
+
+    /// The stream (re)connected at `now`.
+    pub fn connected(&mut self, now: Instant) {
+        self.connected_at = Some(now);
+        self.last_heartbeat = None;
+        self.connections += 1;
+    }
+
+    /// A heartbeat arrived at `now`. Only the gaps between two heartbeats
+    /// are intervals; the first one after connecting may come at any time.
+    pub fn heartbeat(&mut self, now: Instant) {
+        if let Some(last) = self.last_heartbeat {
+            let gap = now.saturating_duration_since(last);
+            self.interval = Some(self.interval.map_or(gap, |interval| interval.max(gap)));
+        }
+        self.last_heartbeat = Some(now);
+    }
+
+    /// The longest heartbeat interval seen.
+    pub fn interval(&self) -> Option<Duration> {
+        self.interval
+    }
+
+    /// How long ago the last heartbeat came, if one came since connecting.
+    pub fn since_heartbeat(&self, now: Instant) -> Option<Duration> {
+        self.last_heartbeat
+            .map(|last| now.saturating_duration_since(last))
+    }
+
+    /// Whether a heartbeat should have come by `now`: twice the interval
+    /// (or, until it's known, a [`MISSED_HEARTBEATS`] share of the idle
+    /// timeout) has passed without one since the last one, or since
+    /// connecting.
+    pub fn overdue(&self, now: Instant) -> bool {
+        let Some(since) = self.last_heartbeat.or(self.connected_at) else {
+            return false;
+        };
+        let expected = self.interval.unwrap_or(self.minimum / MISSED_HEARTBEATS);
+        now.saturating_duration_since(since) > expected * 2
+    }
+
+    /// Times the stream connected again after the first.
+    pub fn reconnects(&self) -> u32 {
+        self.connections.saturating_sub(1)
+    }
+
+    /// How long to wait for data before reconnecting.
+    pub fn timeout(&self) -> Duration {
+        self.interval.map_or(self.minimum, |interval| {
+            self.minimum.max(interval * MISSED_HEARTBEATS)
+        })
+    }
+}
+
 /// Extract SSE data lines from a chunk of bytes.
 /// SSE format: lines starting with "data: " followed by JSON, separated by blank lines.
 pub fn extract_sse_data_lines(text: &str) -> Vec<&str> {
@@ -232,6 +642,8 @@ mod tests {
                 assert_eq!(te.tool, "read");
                 assert_eq!(te.input["filePath"], "src/main.rs");
                 assert_eq!(te.state, "completed");
+                assert_eq!(te.call_id.as_deref(), Some("p1"));
+                assert_eq!(te.session_id.as_deref(), Some("s1"));
             }
             _ => panic!("expected Tool event"),
         }
@@ -301,6 +713,54 @@ mod tests {
         assert!(lines[1].contains("server.heartbeat"));
     }
 
+    #[test]
+    fn test_idle_timeout_follows_heartbeats() {
+        let config = ConnectionConfig::default();
+        let mut heartbeats = Heartbeats::new(&config);
+        assert_eq!(heartbeats.timeout(), Duration::from_secs(60));
+
+        // Frequent heartbeats never shorten the configured timeout
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+        heartbeats.connected(t0);
+        heartbeats.heartbeat(at(5));
+        heartbeats.heartbeat(at(15));
+        assert_eq!(heartbeats.timeout(), Duration::from_secs(60));
+
+        // Sparse ones stretch it to a few intervals, across reconnects
+        heartbeats.heartbeat(at(45));
+        assert_eq!(heartbeats.interval(), Some(Duration::from_secs(30)));
+        assert_eq!(heartbeats.timeout(), Duration::from_secs(90));
+        heartbeats.connected(at(100));
+        heartbeats.heartbeat(at(101));
+        assert_eq!(heartbeats.timeout(), Duration::from_secs(90));
+        assert_eq!(heartbeats.reconnects(), 1);
+    }
+
+    #[test]
+    fn test_heartbeat_overdue() {
+        let mut heartbeats = Heartbeats::new(&ConnectionConfig::default());
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+        assert!(!heartbeats.overdue(at(1000)));
+
+        // Until the interval is known, a third of the idle timeout is expected
+        heartbeats.connected(t0);
+        assert_eq!(heartbeats.since_heartbeat(at(5)), None);
+        assert!(!heartbeats.overdue(at(40)));
+        assert!(heartbeats.overdue(at(41)));
+
+        heartbeats.heartbeat(at(41));
+        heartbeats.heartbeat(at(46));
+        assert_eq!(
+            heartbeats.since_heartbeat(at(50)),
+            Some(Duration::from_secs(4))
+        );
+        assert!(!heartbeats.overdue(at(56)));
+        assert!(heartbeats.overdue(at(57)));
+        assert_eq!(heartbeats.reconnects(), 0);
+    }
+
     #[test]
     fn test_extract_sse_data_lines_no_space() {
         let chunk = "data:{\"type\":\"server.heartbeat\",\"properties\":{}}\n\n";
@@ -372,6 +832,52 @@ mod tests {
         assert!(parse_sse_event(json).is_none());
     }
 
+    #[test]
+    fn test_session_deleted_and_message_removed() {
+        let json = r#"{"type":"session.deleted","properties":{"info":{"id":"ses_abc","title":"x"}}}"#;
+        match parse_sse_event(json).unwrap() {
+            ServerEvent::SessionDeleted { session_id } => assert_eq!(session_id, "ses_abc"),
+            other => panic!("expected SessionDeleted, got {other:?}"),
+        }
+        let json = r#"{"type":"message.removed","properties":{"sessionID":"ses_abc","messageID":"msg_1"}}"#;
+        let event = parse_sse_event(json).unwrap();
+        assert_eq!(event.session_id(), Some("ses_abc"));
+        match event {
+            ServerEvent::MessageRemoved { message_id, .. } => assert_eq!(message_id, "msg_1"),
+            other => panic!("expected MessageRemoved, got {other:?}"),
+        }
+        let json = r#"{"type":"message.removed","properties":{"sessionID":"ses_abc"}}"#;
+        assert!(parse_sse_event(json).is_none());
+    }
+
+    #[test]
+    fn test_session_error() {
+        let message = |json: &str| match parse_sse_event(json).unwrap() {
+            ServerEvent::Error {
+                session_id,
+                message,
+            } => (session_id, message),
+            other => panic!("expected Error, got {other:?}"),
+        };
+        let json = r#"{"type":"session.error","properties":{"sessionID":"ses_abc","error":{"name":"ProviderAuthError","data":{"providerID":"anthropic","message":"invalid x-api-key"}}}}"#;
+        assert_eq!(
+            message(json),
+            (
+                Some("ses_abc".to_string()),
+                "Authentication with anthropic failed: invalid x-api-key".to_string()
+            )
+        );
+        let json = r#"{"type":"session.error","properties":{"sessionID":"ses_abc","error":{"name":"MessageAbortedError","data":{"message":"Aborted"}}}}"#;
+        assert_eq!(message(json).1, "Run aborted");
+        let json = r#"{"type":"session.error","properties":{"error":{"name":"UnknownError","data":{"message":"rate limited"}}}}"#;
+        assert_eq!(
+            message(json),
+            (None, "UnknownError: rate limited".to_string())
+        );
+        let json = r#"{"type":"session.error","properties":{}}"#;
+        assert_eq!(message(json).1, "Unknown error");
+    }
+
     #[test]
     fn test_message_updated_ignored() {
         let json = r#"{"type":"message.updated","properties":{"info":{"id":"msg_abc"}}}"#;
@@ -447,11 +953,12 @@ mod tests {
 
     #[test]
     fn test_prompt_json_structure() {
-        let body = serde_json::json!({
-            "parts": [{"type": "text", "text": "hello world"}]
-        });
+        let body = prompt_body("hello world", None);
         assert_eq!(body["parts"][0]["type"], "text");
         assert_eq!(body["parts"][0]["text"], "hello world");
+        assert!(body.get("agent").is_none());
+        let body = prompt_body("hello world", Some("plan"));
+        assert_eq!(body["agent"], "plan");
     }
 
     // ===== OpenCodeClient Construction Tests =====
@@ -476,6 +983,43 @@ mod tests {
         assert_eq!(client.session_id(), Some("ses_abc123"));
     }
 
+    #[test]
+    fn test_directory_session() {
+        let session = |id: &str, directory: Option<&str>| SessionInfo {
+            id: id.into(),
+            slug: None,
+            directory: directory.map(str::to_string),
+            title: None,
+        };
+        let sessions = [
+            session("ses_a", None),
+            session("ses_b", Some("/work/conch")),
+        ];
+        let found = directory_session(&sessions, Path::new("/work/conch"));
+        assert_eq!(found.map(|s| s.id.as_str()), Some("ses_b"));
+        assert!(directory_session(&sessions, Path::new("/work")).is_none());
+    }
+
+    #[test]
+    fn test_created_session_checked() {
+        let dir = Path::new("/work/conch");
+        let body = serde_json::json!({ "id": "ses_a", "slug": "fox", "directory": "/work/conch" });
+        let session = created_session(body, Some(dir)).unwrap();
+        assert_eq!(session.id, "ses_a");
+        assert_eq!(session.slug.as_deref(), Some("fox"));
+        // Servers that don't say where the session works are taken at their word
+        assert!(created_session(serde_json::json!({ "id": "ses_a" }), Some(dir)).is_ok());
+
+        let elsewhere = serde_json::json!({ "id": "ses_a", "directory": "/" });
+        let err = created_session(elsewhere, Some(dir)).unwrap_err();
+        assert_eq!(err.to_string(), "session created in /, not /work/conch");
+        let err = created_session(serde_json::json!({ "id": "" }), None).unwrap_err();
+        assert_eq!(err.to_string(), "no session id in response");
+        let err = created_session(serde_json::json!({ "error": "busy" }), None).unwrap_err();
+        let message = err.to_string();
+        assert!(message.starts_with("unexpected create session response"));
+    }
+
     // ===== Connection Status Tests =====
 
     #[test]
@@ -491,9 +1035,13 @@ mod tests {
     #[test]
     fn test_tool_event_serialization() {
         let te = ToolEvent {
+            call_id: None,
+            session_id: None,
+            message_id: None,
             tool: "read".into(),
             input: serde_json::json!({"path": "src/main.rs"}),
             state: "completed".into(),
+            metadata: serde_json::Value::Null,
         };
         let json = serde_json::to_string(&te).unwrap();
         let parsed: ToolEvent = serde_json::from_str(&json).unwrap();
@@ -523,6 +1071,50 @@ mod tests {
         assert!(parse_sse_event(json).is_none());
     }
 
+    // ===== Session Message Tests =====
+
+    #[test]
+    fn test_parse_session_messages() {
+        let body = serde_json::json!([
+            {
+                "info": {"id": "msg_1", "role": "user", "time": {"created": 1000}},
+                "parts": [{"type": "text", "text": "list the files"}]
+            },
+            {
+                "info": {"id": "msg_2", "role": "assistant", "time": {"created": 2000}},
+                "parts": [
+                    {"type": "step-start"},
+                    {"type": "tool", "tool": "bash", "state": {"status": "completed", "input": {"command": "ls"}}},
+                    {"type": "text", "text": "Here they are."}
+                ]
+            }
+        ]);
+        let messages = parse_session_messages(&body);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, "user");
+        assert_eq!(messages[0].created, Some(1000));
+        assert_eq!(messages[1].parts.len(), 2);
+        match &messages[1].parts[0] {
+            MessagePart::Tool(te) => assert_eq!(te.input["command"], "ls"),
+            other => panic!("expected tool part, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_message_part_skips_synthetic_and_empty() {
+        let synthetic = serde_json::json!({"type": "text", "text": "ctx", "synthetic": true});
+        let empty = serde_json::json!({"type": "text", "text": "  "});
+        let file = serde_json::json!({"type": "file", "url": "file:///x"});
+        assert!(parse_message_part(&synthetic).is_none());
+        assert!(parse_message_part(&empty).is_none());
+        assert!(parse_message_part(&file).is_none());
+    }
+
+    #[test]
+    fn test_parse_session_messages_non_array() {
+        assert!(parse_session_messages(&serde_json::json!({"error": "x"})).is_empty());
+    }
+
     // ===== Network-dependent tests (ignored by default) =====
 
     #[tokio::test]
@@ -546,8 +1138,8 @@ mod tests {
     #[ignore]
     async fn test_create_session_live() {
         let mut client = OpenCodeClient::new("http://127.0.0.1:4096");
-        let id = client.create_session().await.unwrap();
-        assert!(!id.is_empty());
+        let session = client.create_session(None).await.unwrap();
+        assert!(!session.id.is_empty());
         assert!(client.session_id().is_some());
     }
 
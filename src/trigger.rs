@@ -0,0 +1,292 @@
+// Trigger Module - Push-to-talk from a MIDI controller or HID foot pedal
+//
+// A pedal press works like Space: it starts a recording, and the next press
+// stops it. In "hold" mode the recording lasts only while the pedal is down.
+// Pedals that type a key are already covered by the keyboard; this is for
+// MIDI sustain pedals and pads (built with `--features midi`) and HID pedals
+// that send raw reports (built with `--features pedal`).
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+
+/// MIDI sustain pedal controller, used when neither `note` nor `cc` is set.
+pub const SUSTAIN_CC: u8 = 64;
+
+/// What a pedal press does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TriggerMode {
+    /// Each press starts or stops a recording, like Space.
+    #[default]
+    Toggle,
+    /// Record while the pedal is held down.
+    Hold,
+}
+
+impl TriggerMode {
+    /// Whether `event` starts or stops a recording, given whether one is
+    /// running.
+    pub fn toggles(self, event: TriggerEvent, recording: bool) -> bool {
+        match (self, event) {
+            (TriggerMode::Toggle, TriggerEvent::Pressed) => true,
+            (TriggerMode::Toggle, TriggerEvent::Released) => false,
+            (TriggerMode::Hold, TriggerEvent::Pressed) => !recording,
+            (TriggerMode::Hold, TriggerEvent::Released) => recording,
+        }
+    }
+}
+
+/// `[trigger]` section of the config file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TriggerConfig {
+    /// "toggle" or "hold".
+    pub mode: TriggerMode,
+    /// A MIDI controller (`[trigger.midi]`).
+    pub midi: Option<MidiTrigger>,
+    /// An HID foot pedal (`[trigger.hid]`).
+    pub hid: Option<HidTrigger>,
+}
+
+/// The pedal going down or coming back up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerEvent {
+    Pressed,
+    Released,
+}
+
+/// `[trigger.midi]`: which MIDI message is the pedal.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct MidiTrigger {
+    /// Part of the input port's name; the first port when unset.
+    pub port: Option<String>,
+    /// A note (pads, keys). Note on presses, note off releases.
+    pub note: Option<u8>,
+    /// A controller (pedals). Values of 64 and up press, lower release.
+    /// Defaults to the sustain pedal, CC 64.
+    pub cc: Option<u8>,
+    /// MIDI channel, 1-16; any channel when unset.
+    pub channel: Option<u8>,
+}
+
+impl MidiTrigger {
+    /// What `message` does to the pedal, if it is the configured one.
+    pub fn event(&self, message: &[u8]) -> Option<TriggerEvent> {
+        let (&status, data) = message.split_first()?;
+        let (kind, channel) = (status & 0xf0, status & 0x0f);
+        if self.channel.is_some_and(|c| c.wrapping_sub(1) != channel) {
+            return None;
+        }
+        let [number, value] = *data else {
+            return None;
+        };
+        let pressed = match (kind, self.note) {
+            (0x90, Some(note)) if note == number => value > 0,
+            (0x80, Some(note)) if note == number => false,
+            (0xb0, None) if self.cc.unwrap_or(SUSTAIN_CC) == number => value >= 64,
+            _ => return None,
+        };
+        Some(if pressed {
+            TriggerEvent::Pressed
+        } else {
+            TriggerEvent::Released
+        })
+    }
+}
+
+/// `[trigger.hid]`: which device is the pedal, and where in its reports.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct HidTrigger {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// Byte of the input report holding the pedal state.
+    pub byte: usize,
+    /// Bits of that byte that mean "pressed", e.g. 2 for the middle pedal
+    /// of a three-pedal board.
+    pub mask: u8,
+}
+
+impl Default for HidTrigger {
+    fn default() -> Self {
+        Self {
+            vendor_id: 0,
+            product_id: 0,
+            byte: 0,
+            mask: 0xff,
+        }
+    }
+}
+
+impl HidTrigger {
+    /// Whether `report` has the pedal down.
+    pub fn is_pressed(&self, report: &[u8]) -> bool {
+        report.get(self.byte).is_some_and(|b| b & self.mask != 0)
+    }
+}
+
+/// Listen to the MIDI input in `config`, passing pedal presses to
+/// `on_event` from a background thread.
+#[cfg(feature = "midi")]
+pub fn spawn_midi(
+    config: &MidiTrigger,
+    mut on_event: impl FnMut(TriggerEvent) + Send + 'static,
+) -> Result<()> {
+    use midir::MidiInput;
+
+    let input = MidiInput::new("conch").map_err(|e| anyhow!("MIDI unavailable: {e}"))?;
+    let ports = input.ports();
+    let port = ports
+        .iter()
+        .find(|port| match (&config.port, input.port_name(port)) {
+            (None, _) => true,
+            (Some(wanted), Ok(name)) => name.to_lowercase().contains(&wanted.to_lowercase()),
+            (Some(_), Err(_)) => false,
+        })
+        .ok_or_else(|| match &config.port {
+            Some(wanted) => anyhow!("No MIDI input port matching '{wanted}'"),
+            None => anyhow!("No MIDI input ports"),
+        })?
+        .clone();
+    let config = config.clone();
+    let connection = input
+        .connect(
+            &port,
+            "conch-trigger",
+            move |_, message, _| {
+                if let Some(event) = config.event(message) {
+                    on_event(event);
+                }
+            },
+            (),
+        )
+        .map_err(|e| anyhow!("Failed to open MIDI input: {e}"))?;
+    // Messages arrive for as long as the connection lives
+    std::thread::spawn(move || {
+        let _connection = connection;
+        loop {
+            std::thread::park();
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(feature = "midi"))]
+pub fn spawn_midi(
+    _config: &MidiTrigger,
+    _on_event: impl FnMut(TriggerEvent) + Send + 'static,
+) -> Result<()> {
+    Err(anyhow!(
+        "conch was built without MIDI support (rebuild with --features midi)"
+    ))
+}
+
+/// Read the HID pedal in `config`, passing presses to `on_event` from a
+/// background thread. A read error after it opened goes to `on_error`
+/// and stops it.
+#[cfg(feature = "pedal")]
+pub fn spawn_hid(
+    config: &HidTrigger,
+    mut on_event: impl FnMut(TriggerEvent) + Send + 'static,
+    on_error: fn(&str),
+) -> Result<()> {
+    use hidapi::HidApi;
+
+    let api = HidApi::new().map_err(|e| anyhow!("HID unavailable: {e}"))?;
+    let device = api.open(config.vendor_id, config.product_id).map_err(|e| {
+        anyhow!(
+            "Failed to open HID pedal {:04x}:{:04x}: {e}",
+            config.vendor_id,
+            config.product_id
+        )
+    })?;
+    let config = config.clone();
+    std::thread::spawn(move || {
+        let mut report = [0u8; 64];
+        let mut pressed = false;
+        loop {
+            let len = match device.read(&mut report) {
+                Ok(len) => len,
+                Err(e) => {
+                    on_error(&format!("HID pedal: {e}"));
+                    return;
+                }
+            };
+            let now = config.is_pressed(&report[..len]);
+            if now != pressed {
+                pressed = now;
+                on_event(if now {
+                    TriggerEvent::Pressed
+                } else {
+                    TriggerEvent::Released
+                });
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(feature = "pedal"))]
+pub fn spawn_hid(
+    _config: &HidTrigger,
+    _on_event: impl FnMut(TriggerEvent) + Send + 'static,
+    _on_error: fn(&str),
+) -> Result<()> {
+    Err(anyhow!(
+        "conch was built without HID pedal support (rebuild with --features pedal)"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_midi_sustain_pedal() {
+        let pedal = MidiTrigger::default();
+        assert_eq!(pedal.event(&[0xb0, 64, 127]), Some(TriggerEvent::Pressed));
+        assert_eq!(pedal.event(&[0xb3, 64, 0]), Some(TriggerEvent::Released));
+        assert_eq!(pedal.event(&[0xb0, 1, 127]), None, "mod wheel");
+        assert_eq!(pedal.event(&[0x90, 64, 127]), None);
+        assert_eq!(pedal.event(&[0xf8]), None, "clock");
+    }
+
+    #[test]
+    fn test_midi_note_and_channel() {
+        let pad = MidiTrigger {
+            note: Some(36),
+            channel: Some(10),
+            ..MidiTrigger::default()
+        };
+        assert_eq!(pad.event(&[0x99, 36, 100]), Some(TriggerEvent::Pressed));
+        // Note on with velocity 0 is a note off
+        assert_eq!(pad.event(&[0x99, 36, 0]), Some(TriggerEvent::Released));
+        assert_eq!(pad.event(&[0x89, 36, 64]), Some(TriggerEvent::Released));
+        assert_eq!(pad.event(&[0x90, 36, 100]), None, "channel 1");
+        assert_eq!(pad.event(&[0x99, 37, 100]), None);
+    }
+
+    #[test]
+    fn test_hid_report() {
+        let middle = HidTrigger {
+            mask: 0b010,
+            ..HidTrigger::default()
+        };
+        assert!(middle.is_pressed(&[0b010, 0]));
+        assert!(middle.is_pressed(&[0b111, 0]));
+        assert!(!middle.is_pressed(&[0b101, 0]));
+        assert!(!middle.is_pressed(&[]));
+    }
+
+    #[test]
+    fn test_modes() {
+        use TriggerEvent::{Pressed, Released};
+        assert!(TriggerMode::Toggle.toggles(Pressed, true));
+        assert!(!TriggerMode::Toggle.toggles(Released, true));
+        assert!(TriggerMode::Hold.toggles(Pressed, false));
+        assert!(!TriggerMode::Hold.toggles(Pressed, true));
+        assert!(TriggerMode::Hold.toggles(Released, true));
+        assert!(!TriggerMode::Hold.toggles(Released, false));
+    }
+}
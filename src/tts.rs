@@ -0,0 +1,229 @@
+// TTS Module - Reads assistant replies aloud through an external speech command
+//
+// Speech is delegated to whatever the user has installed (`say`, `espeak-ng`,
+// a piper pipeline, ...). The reply text goes to the command on stdin.
+
+use std::io::Write as _;
+use std::process::{Child, Command, Stdio};
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+
+/// `[tts]` section of the config file. Off unless explicitly enabled.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TtsConfig {
+    /// Read the assistant's final reply aloud when OpenCode goes idle.
+    pub enabled: bool,
+    /// Speech command run via `sh -c`, text on stdin. Defaults to `say` on
+    /// macOS and `espeak-ng` elsewhere.
+    pub command: Option<String>,
+    /// Replies are cut at a sentence boundary near this many characters.
+    pub max_chars: usize,
+}
+
+impl Default for TtsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            command: None,
+            max_chars: 600,
+        }
+    }
+}
+
+impl TtsConfig {
+    /// The configured command, or the platform default.
+    pub fn command(&self) -> &str {
+        self.command.as_deref().unwrap_or(default_command())
+    }
+}
+
+/// Speech command used when none is configured.
+pub fn default_command() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "say"
+    } else {
+        "espeak-ng"
+    }
+}
+
+/// Runs the speech command, one utterance at a time.
+pub struct Speaker {
+    command: String,
+    child: Option<Child>,
+}
+
+impl Speaker {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            child: None,
+        }
+    }
+
+    /// Speak `text`, interrupting anything already playing.
+    pub fn speak(&mut self, text: &str) -> Result<()> {
+        self.stop();
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        // Own process group, so stop() also reaches the rest of a pipeline
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        let mut child = command
+            .spawn()
+            .map_err(|e| anyhow!("Failed to start TTS command '{}': {}", self.command, e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(text.as_bytes());
+        }
+        self.child = Some(child);
+        Ok(())
+    }
+
+    /// Whether speech is still playing.
+    pub fn is_speaking(&mut self) -> bool {
+        match &mut self.child {
+            Some(child) => match child.try_wait() {
+                Ok(None) => true,
+                _ => {
+                    self.child = None;
+                    false
+                }
+            },
+            None => false,
+        }
+    }
+
+    /// Cut off any speech in progress.
+    pub fn stop(&mut self) {
+        let Some(mut child) = self.child.take() else {
+            return;
+        };
+        #[cfg(unix)]
+        {
+            let _ = Command::new("kill")
+                .args(["-TERM", "--", &format!("-{}", child.id())])
+                .stderr(Stdio::null())
+                .status();
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+impl Drop for Speaker {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Turn a Markdown reply into something worth listening to: code blocks are
+/// summarized, inline markup is dropped, and long replies are cut at the
+/// last sentence that fits in `max_chars`.
+pub fn speakable_text(reply: &str, max_chars: usize) -> String {
+    let mut lines = Vec::new();
+    let mut in_code = false;
+    for line in reply.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            if !in_code {
+                lines.push("(code block)".to_string());
+            }
+            in_code = !in_code;
+            continue;
+        }
+        if in_code || trimmed.is_empty() {
+            continue;
+        }
+        let trimmed = trimmed.trim_start_matches(['#', '>', ' ']);
+        let trimmed = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+            .unwrap_or(trimmed);
+        let cleaned: String = trimmed.chars().filter(|c| !matches!(c, '`' | '*' | '_')).collect();
+        lines.push(cleaned);
+    }
+    let text = lines.join(" ");
+
+    if text.chars().count() <= max_chars {
+        return text;
+    }
+    let cut: String = text.chars().take(max_chars).collect();
+    match cut.rfind(['.', '!', '?']) {
+        Some(end) if end > 0 => cut[..=end].to_string(),
+        _ => format!("{}...", cut.trim_end()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_is_off() {
+        let config = TtsConfig::default();
+        assert!(!config.enabled);
+        assert_eq!(config.command(), default_command());
+    }
+
+    #[test]
+    fn test_speakable_strips_markup() {
+        let reply = "## Done\n\nI updated `main.rs` and **removed** the unused import.";
+        assert_eq!(
+            speakable_text(reply, 500),
+            "Done I updated main.rs and removed the unused import."
+        );
+    }
+
+    #[test]
+    fn test_speakable_summarizes_code_blocks() {
+        let reply = "Run this:\n```bash\ncargo test\n```\nThen check the output.";
+        assert_eq!(
+            speakable_text(reply, 500),
+            "Run this: (code block) Then check the output."
+        );
+    }
+
+    #[test]
+    fn test_speakable_list_items() {
+        assert_eq!(speakable_text("- one\n* two", 500), "one two");
+    }
+
+    #[test]
+    fn test_speakable_truncates_at_sentence() {
+        let reply = "First sentence here. Second sentence is much longer than the limit allows.";
+        assert_eq!(speakable_text(reply, 40), "First sentence here.");
+    }
+
+    #[test]
+    fn test_speakable_truncates_without_sentence() {
+        assert_eq!(speakable_text("abcdefghij", 5), "abcde...");
+    }
+
+    #[test]
+    fn test_speaker_runs_command_with_stdin() {
+        let out = std::env::temp_dir().join(format!("conch_tts_{}.txt", std::process::id()));
+        let mut speaker = Speaker::new(format!("cat > '{}'", out.display()));
+        speaker.speak("hello there").unwrap();
+        while speaker.is_speaking() {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let written = std::fs::read_to_string(&out).unwrap();
+        let _ = std::fs::remove_file(&out);
+        assert_eq!(written, "hello there");
+    }
+
+    #[test]
+    fn test_speaker_stop_interrupts() {
+        let mut speaker = Speaker::new("sleep 5");
+        speaker.speak("ignored").unwrap();
+        assert!(speaker.is_speaking());
+        speaker.stop();
+        assert!(!speaker.is_speaking());
+    }
+}
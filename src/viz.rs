@@ -7,6 +7,9 @@ use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::style::{Color, Style};
 use ratatui::widgets::Widget;
+use serde::Deserialize;
+
+use crate::glyphs::Charset;
 
 /// Convert a 4-row x 2-column dot grid to a braille Unicode character.
 ///
@@ -50,6 +53,31 @@ fn dots_to_braille(dots: [[bool; 2]; 4]) -> char {
     char::from_u32(0x2800 + bits as u32).unwrap_or(' ')
 }
 
+/// Convert a cell's dots to a block element, filling each half of the cell
+/// that has any dot set.
+fn dots_to_block(dots: [[bool; 2]; 4]) -> char {
+    let top = dots[..2].iter().flatten().any(|&d| d);
+    let bottom = dots[2..].iter().flatten().any(|&d| d);
+    match (top, bottom) {
+        (true, true) => '\u{2588}',
+        (true, false) => '\u{2580}',
+        (false, true) => '\u{2584}',
+        (false, false) => ' ',
+    }
+}
+
+/// Convert a cell's dots to ASCII: `|` where a bar fills the cell, `-` for
+/// the center line and bar tips.
+fn dots_to_ascii(dots: [[bool; 2]; 4]) -> char {
+    let top = dots[..2].iter().flatten().any(|&d| d);
+    let bottom = dots[2..].iter().flatten().any(|&d| d);
+    match (top, bottom) {
+        (true, true) => '|',
+        (false, false) => ' ',
+        _ => '-',
+    }
+}
+
 /// A canvas of dot-pixels that maps to braille characters.
 ///
 /// Each terminal cell is 2 dots wide and 4 dots tall.
@@ -98,6 +126,11 @@ impl BrailleCanvas {
 
     /// Convert the dot canvas to a grid of braille characters.
     fn to_braille_grid(&self) -> Vec<Vec<char>> {
+        self.to_grid(dots_to_braille)
+    }
+
+    /// Convert the dot canvas to a grid of characters, one per 2x4 dots.
+    fn to_grid(&self, cell: fn([[bool; 2]; 4]) -> char) -> Vec<Vec<char>> {
         let cols = self.width / 2;
         let rows = self.height / 4;
         let mut grid = vec![vec![' '; cols]; rows];
@@ -111,7 +144,7 @@ impl BrailleCanvas {
                     [self.get_dot(px, py + 2), self.get_dot(px + 1, py + 2)],
                     [self.get_dot(px, py + 3), self.get_dot(px + 1, py + 3)],
                 ];
-                grid[row][col] = dots_to_braille(dots);
+                grid[row][col] = cell(dots);
             }
         }
         grid
@@ -142,34 +175,101 @@ fn render_waveform_to_canvas(bars: &[f32], canvas: &mut BrailleCanvas) {
 
 /// Waveform display color.
 const WAVEFORM_COLOR: Color = Color::Cyan;
+/// Color of the flat line drawn while idle.
+const IDLE_COLOR: Color = Color::DarkGray;
+
+/// Factor one press of `[` or `]` changes the full-scale level by.
+pub const SENSITIVITY_STEP: f32 = 1.25;
+/// Range the full-scale level is kept within by live adjustment.
+const FULL_SCALE_RANGE: (f32, f32) = (0.002, 1.0);
+
+/// `[waveform]` section of the config file: how loud the mic has to be to
+/// fill the display.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct WaveformConfig {
+    /// RMS level drawn at full height. Lower it for quiet laptop mics, raise
+    /// it for hot condenser mics.
+    pub full_scale: f32,
+    /// Scaled levels below this are drawn as silence.
+    pub noise_floor: f32,
+}
+
+impl Default for WaveformConfig {
+    fn default() -> Self {
+        Self {
+            full_scale: 0.04,
+            noise_floor: 0.001,
+        }
+    }
+}
+
+impl WaveformConfig {
+    /// Scale RMS levels to bar heights in 0.0..=1.0.
+    pub fn scale(&self, rms: &[f32]) -> Vec<f32> {
+        let mut bars = rms.to_vec();
+        self.scale_in_place(&mut bars);
+        bars
+    }
+
+    /// [`scale`](Self::scale) RMS `levels` where they are, for the render
+    /// loop's reused buffer.
+    pub fn scale_in_place(&self, levels: &mut [f32]) {
+        for v in levels {
+            let level = (*v / self.full_scale).clamp(0.0, 1.0);
+            *v = if level < self.noise_floor { 0.0 } else { level };
+        }
+    }
+
+    /// Let quieter audio fill the display (`]`).
+    pub fn more_sensitive(&mut self) {
+        self.full_scale = (self.full_scale / SENSITIVITY_STEP).max(FULL_SCALE_RANGE.0);
+    }
+
+    /// Need louder audio to fill the display (`[`).
+    pub fn less_sensitive(&mut self) {
+        self.full_scale = (self.full_scale * SENSITIVITY_STEP).min(FULL_SCALE_RANGE.1);
+    }
+}
 
 /// Compute RMS energy for each of `num_windows` equal-sized chunks of samples.
 ///
 /// Returns a Vec of RMS values, one per window. If samples is empty or
 /// num_windows is 0, returns an empty/zero-filled vec.
 pub fn compute_rms_windows(samples: &[f32], num_windows: usize) -> Vec<f32> {
-    if samples.is_empty() || num_windows == 0 {
-        return vec![0.0; num_windows];
-    }
+    let mut rms = vec![0.0; num_windows];
+    compute_rms_windows_into(samples, &mut rms);
+    rms
+}
 
-    let chunk_size = samples.len() / num_windows;
+/// [`compute_rms_windows`] into `out`, one window per element, without
+/// allocating. All zeros when there are fewer samples than windows.
+#[tracing::instrument(level = "trace", skip_all)]
+pub fn compute_rms_windows_into(samples: &[f32], out: &mut [f32]) {
+    let num_windows = out.len();
+    let chunk_size = samples.len() / num_windows.max(1);
     if chunk_size == 0 {
-        return vec![0.0; num_windows];
+        out.fill(0.0);
+        return;
+    }
+    for (i, v) in out.iter_mut().enumerate() {
+        let start = i * chunk_size;
+        let end = if i == num_windows - 1 {
+            samples.len()
+        } else {
+            start + chunk_size
+        };
+        *v = rms(&samples[start..end]);
     }
+}
 
-    (0..num_windows)
-        .map(|i| {
-            let start = i * chunk_size;
-            let end = if i == num_windows - 1 {
-                samples.len()
-            } else {
-                start + chunk_size
-            };
-            let chunk = &samples[start..end];
-            let sum_sq: f32 = chunk.iter().map(|&s| s * s).sum();
-            (sum_sq / chunk.len() as f32).sqrt()
-        })
-        .collect()
+/// RMS energy of `samples`, 0.0 when there are none.
+pub fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum_sq: f32 = samples.iter().map(|&s| s * s).sum();
+    (sum_sq / samples.len() as f32).sqrt()
 }
 
 /// Normalize magnitudes to 0.0..=1.0 range with a noise floor threshold.
@@ -230,12 +330,48 @@ impl WaveformData {
 
 /// A ratatui widget that renders a waveform amplitude display.
 pub struct WaveformWidget<'a> {
-    data: &'a WaveformData,
+    bars: &'a [f32],
+    color: Color,
+    idle_color: Color,
+    charset: Charset,
 }
 
 impl<'a> WaveformWidget<'a> {
+    /// Create a widget that renders `data`.
     pub fn new(data: &'a WaveformData) -> Self {
-        Self { data }
+        Self::from_bars(&data.bars)
+    }
+
+    /// Create a widget that renders `bars`, normalized amplitudes one per
+    /// column, without copying them into a [`WaveformData`].
+    pub fn from_bars(bars: &'a [f32]) -> Self {
+        Self {
+            bars,
+            color: WAVEFORM_COLOR,
+            idle_color: IDLE_COLOR,
+            charset: Charset::Full,
+        }
+    }
+
+    /// Draw in braille (`Full`), block elements (`Basic`) or ASCII.
+    pub fn charset(mut self, charset: Charset) -> Self {
+        self.charset = charset;
+        self
+    }
+
+    fn grid(&self, canvas: &BrailleCanvas) -> Vec<Vec<char>> {
+        match self.charset {
+            Charset::Full => canvas.to_braille_grid(),
+            Charset::Basic => canvas.to_grid(dots_to_block),
+            Charset::Ascii => canvas.to_grid(dots_to_ascii),
+        }
+    }
+
+    /// Draw the waveform in `color` and the idle line in `idle_color`.
+    pub fn colors(mut self, color: Color, idle_color: Color) -> Self {
+        self.color = color;
+        self.idle_color = idle_color;
+        self
     }
 }
 
@@ -249,44 +385,46 @@ impl Widget for WaveformWidget<'_> {
         let waveform_rows = area.height as usize;
 
         // Silence / idle: render thin center line using braille dots
-        if self.data.bars.is_empty() {
+        if self.bars.is_empty() {
             let mut canvas = BrailleCanvas::new(waveform_cols, waveform_rows);
             let center_y = canvas.height / 2;
             for x in 0..canvas.width {
                 canvas.set_dot(x, center_y);
             }
-            let grid = canvas.to_braille_grid();
+            let grid = self.grid(&canvas);
             for (row_idx, row) in grid.iter().enumerate() {
                 let s: String = row.iter().collect();
                 buf.set_string(
                     area.x,
                     area.y + row_idx as u16,
                     &s,
-                    Style::default().fg(Color::DarkGray),
+                    Style::default().fg(self.idle_color),
                 );
             }
             return;
         }
 
         // Resample bars to fit the available width
-        let bars = if self.data.bars.len() == waveform_cols {
-            self.data.bars.clone()
+        let resampled: Vec<f32>;
+        let bars = if self.bars.len() == waveform_cols {
+            self.bars
         } else {
-            let ratio = self.data.bars.len() as f32 / waveform_cols as f32;
-            (0..waveform_cols)
+            let ratio = self.bars.len() as f32 / waveform_cols as f32;
+            resampled = (0..waveform_cols)
                 .map(|i| {
                     let src = (i as f32 * ratio) as usize;
-                    self.data.bars[src.min(self.data.bars.len() - 1)]
+                    self.bars[src.min(self.bars.len() - 1)]
                 })
-                .collect()
+                .collect();
+            &resampled
         };
 
         // Render braille waveform
         let mut canvas = BrailleCanvas::new(waveform_cols, waveform_rows);
-        render_waveform_to_canvas(&bars, &mut canvas);
-        let grid = canvas.to_braille_grid();
+        render_waveform_to_canvas(bars, &mut canvas);
+        let grid = self.grid(&canvas);
 
-        let style = Style::default().fg(WAVEFORM_COLOR);
+        let style = Style::default().fg(self.color);
         for (row_idx, row) in grid.iter().enumerate() {
             let s: String = row.iter().collect();
             buf.set_string(
@@ -331,6 +469,26 @@ mod tests {
         assert_eq!(dots_to_braille(dots), '\u{28B8}');
     }
 
+    #[test]
+    fn test_block_and_ascii_cells() {
+        let top = [[true, false], [false, false], [false, false], [false, false]];
+        assert_eq!(dots_to_block(top), '\u{2580}');
+        assert_eq!(dots_to_block([[true; 2]; 4]), '\u{2588}');
+        assert_eq!(dots_to_ascii(top), '-');
+        assert_eq!(dots_to_ascii([[false, true]; 4]), '|');
+
+        let data = WaveformData {
+            bars: vec![1.0, 0.0],
+        };
+        let area = Rect::new(0, 0, 2, 2);
+        let mut buf = Buffer::empty(area);
+        WaveformWidget::new(&data)
+            .charset(Charset::Ascii)
+            .render(area, &mut buf);
+        assert!(buf.content().iter().all(|c| c.symbol().is_ascii()));
+        assert_eq!(buf[(0, 0)].symbol(), "|");
+    }
+
     // --- BrailleCanvas tests ---
 
     #[test]
@@ -441,6 +599,18 @@ mod tests {
         assert!(rms.is_empty());
     }
 
+    #[test]
+    fn test_compute_rms_windows_into() {
+        let mut out = [9.0; 2];
+        compute_rms_windows_into(&[3.0, -3.0, 0.0, 0.0], &mut out);
+        assert_eq!(out, [3.0, 0.0]);
+        // Too few samples for a window each clears what was there
+        out = [9.0; 2];
+        compute_rms_windows_into(&[1.0], &mut out);
+        assert_eq!(out, [0.0, 0.0]);
+        assert_eq!(rms(&[]), 0.0);
+    }
+
     #[test]
     fn test_compute_rms_windows_sine() {
         // RMS of a sine wave with amplitude A is A/sqrt(2)
@@ -526,4 +696,35 @@ mod tests {
             assert_eq!(data.bars.len(), 20);
         }
     }
+
+    // --- Sensitivity tests ---
+
+    #[test]
+    fn test_waveform_scale() {
+        let config = WaveformConfig::default();
+        let bars = config.scale(&[0.0, 0.00002, 0.02, 0.04, 0.5]);
+        assert_eq!(bars, vec![0.0, 0.0, 0.5, 1.0, 1.0]);
+        let mut levels = [0.02, 0.5];
+        config.scale_in_place(&mut levels);
+        assert_eq!(levels, [0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_sensitivity_adjustment() {
+        let mut config = WaveformConfig::default();
+        config.more_sensitive();
+        assert!((config.full_scale - 0.032).abs() < 1e-6);
+        // A quiet signal now fills more of the display
+        assert!(config.scale(&[0.016])[0] > 0.45);
+        config.less_sensitive();
+        assert!((config.full_scale - 0.04).abs() < 1e-6);
+        for _ in 0..100 {
+            config.more_sensitive();
+        }
+        assert_eq!(config.full_scale, 0.002);
+        for _ in 0..100 {
+            config.less_sensitive();
+        }
+        assert_eq!(config.full_scale, 1.0);
+    }
 }
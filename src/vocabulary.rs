@@ -0,0 +1,314 @@
+// Vocabulary Module - File names and symbols of the project being worked on
+//
+// Whisper has never heard of `spawn_transcriber`, so it writes "spawn
+// transcriber". The vocabulary is built in the background from the files git
+// tracks in the directory the session works in: their names, and the
+// identifiers in them that look like code (snake_case or camelCase), the most
+// used first. The names prime Whisper for prompts and dictation, and let
+// spoken forms like "audio capture" be looked up as `AudioCapture`. It is
+// built again when the focus moves to another directory.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+
+use crate::send::normalize_words;
+
+/// Files larger than this aren't read for symbols.
+pub const MAX_FILE_BYTES: u64 = 256 * 1024;
+/// Files read for symbols, at most; the rest only add their names.
+pub const MAX_FILES: usize = 2000;
+/// Length of the Whisper prompt, at most. Whisper only looks at the last
+/// couple of hundred tokens of it.
+pub const PROMPT_CHARS: usize = 600;
+/// Shortest and longest identifier taken as a symbol.
+const SYMBOL_LEN: std::ops::RangeInclusive<usize> = 4..=40;
+
+/// `[vocabulary]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct VocabularyConfig {
+    /// Build the vocabulary and prime Whisper with it.
+    pub enabled: bool,
+    /// Names and symbols kept, at most.
+    pub max_terms: usize,
+    /// Fix homophones and spoken names of project terms in transcripts.
+    pub correct: bool,
+}
+
+impl Default for VocabularyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_terms: 300,
+            correct: true,
+        }
+    }
+}
+
+/// File names and symbols of a project, the most relevant first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Vocabulary {
+    /// Names of the tracked files, shallowest first.
+    pub files: Vec<String>,
+    /// Identifiers used in them, the most used first.
+    pub symbols: Vec<String>,
+    /// Spoken form of each term, squashed, to its term.
+    spoken: HashMap<String, String>,
+}
+
+impl Vocabulary {
+    /// Collect the vocabulary of the files git tracks under `dir`, keeping
+    /// `max_terms` terms: up to a third file names, the rest symbols.
+    pub fn harvest(dir: &Path, max_terms: usize) -> Result<Self> {
+        let output = Command::new("git")
+            .arg("--no-optional-locks")
+            .arg("-C")
+            .arg(dir)
+            .args(["ls-files", "-z"])
+            .output()
+            .map_err(|e| anyhow!("Failed to run git: {e}"))?;
+        if !output.status.success() {
+            return Err(anyhow!("{} isn't in a git repository", dir.display()));
+        }
+        let listing = String::from_utf8_lossy(&output.stdout);
+        let mut paths: Vec<&str> = listing.split('\0').filter(|p| !p.is_empty()).collect();
+        paths.sort_by_key(|p| p.matches('/').count());
+
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let mut texts = Vec::new();
+        for path in paths.iter().take(MAX_FILES) {
+            if let Some(text) = read_text(&dir.join(path)) {
+                texts.push(text);
+            }
+        }
+        for text in &texts {
+            for symbol in symbols(text) {
+                *counts.entry(symbol).or_default() += 1;
+            }
+        }
+        let mut symbols: Vec<(&str, usize)> = counts.into_iter().collect();
+        symbols.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+        let mut files: Vec<String> = Vec::new();
+        for path in &paths {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            if files.len() < max_terms / 3 && !files.iter().any(|f| f == name) {
+                files.push(name.to_string());
+            }
+        }
+        let symbols = symbols
+            .into_iter()
+            .take(max_terms - files.len())
+            .map(|(s, _)| s.to_string())
+            .collect();
+        Ok(Self::new(files, symbols))
+    }
+
+    /// A vocabulary of `files` and `symbols`, in order of relevance.
+    pub fn new(files: Vec<String>, symbols: Vec<String>) -> Self {
+        let mut spoken = HashMap::new();
+        for term in files.iter().chain(&symbols) {
+            for key in spoken_keys(term) {
+                spoken.entry(key).or_insert_with(|| term.clone());
+            }
+        }
+        Self {
+            files,
+            symbols,
+            spoken,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.symbols.is_empty()
+    }
+
+    /// Whisper's initial prompt: the most relevant names and symbols,
+    /// comma separated, within [`PROMPT_CHARS`].
+    pub fn prompt(&self) -> String {
+        let mut prompt = String::new();
+        // Alternate, so a big project's file names don't crowd out its symbols
+        let mut files = self.files.iter();
+        let mut symbols = self.symbols.iter();
+        loop {
+            let (file, symbol) = (files.next(), symbols.next());
+            if file.is_none() && symbol.is_none() {
+                return prompt;
+            }
+            for term in file.into_iter().chain(symbol) {
+                if prompt.len() + term.len() + 2 > PROMPT_CHARS {
+                    return prompt;
+                }
+                if !prompt.is_empty() {
+                    prompt.push_str(", ");
+                }
+                prompt.push_str(term);
+            }
+        }
+    }
+
+    /// The term `spoken` names, ignoring case, spaces and separators:
+    /// "audio capture" is `AudioCapture`, "main dot rs" is `main.rs`.
+    pub fn resolve(&self, spoken: &str) -> Option<&str> {
+        let key: String = normalize_words(spoken).split(' ').collect();
+        self.spoken.get(&key).map(String::as_str)
+    }
+}
+
+/// The text of `path`, unless it is too large or binary.
+fn read_text(path: &Path) -> Option<String> {
+    if std::fs::metadata(path).ok()?.len() > MAX_FILE_BYTES {
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    if bytes.contains(&0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Identifiers in `text` that look like code rather than prose.
+fn symbols(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .map(|word| word.trim_matches('_'))
+        .filter(|word| is_symbol(word))
+}
+
+/// Whether `word` is snake_case or has an inner capital (camelCase,
+/// PascalCase), and is a reasonable length.
+fn is_symbol(word: &str) -> bool {
+    if !SYMBOL_LEN.contains(&word.len()) || word.starts_with(|c: char| c.is_ascii_digit()) {
+        return false;
+    }
+    let snake = word.contains('_') && word.chars().any(|c| c.is_ascii_alphabetic());
+    let camel = word
+        .as_bytes()
+        .windows(2)
+        .any(|w| w[0].is_ascii_lowercase() && w[1].is_ascii_uppercase());
+    snake || camel
+}
+
+/// The words `term` is said as: `AudioCapture` is "audio capture",
+/// `main.rs` is "main dot rs".
+fn spoken_words(term: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut previous = None;
+    for c in term.chars() {
+        let lower_to_upper = previous.is_some_and(|p: char| p.is_lowercase()) && c.is_uppercase();
+        if !c.is_alphanumeric() || lower_to_upper {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            if c == '.' {
+                words.push("dot".to_string());
+            }
+        }
+        if c.is_alphanumeric() {
+            word.extend(c.to_lowercase());
+        }
+        previous = Some(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Lookup keys of `term`: its spoken words run together, with and without
+/// "dot", since Whisper may write "main.rs" or "main dot rs".
+fn spoken_keys(term: &str) -> Vec<String> {
+    let words = spoken_words(term);
+    let with_dot: String = words.concat();
+    let without: String = words
+        .iter()
+        .filter(|w| *w != "dot")
+        .map(String::as_str)
+        .collect();
+    if with_dot == without {
+        vec![with_dot]
+    } else {
+        vec![with_dot, without]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbols() {
+        let text = "fn spawn_transcriber(audio: &AudioCapture) { let x_y = lowercase; Prose }";
+        let found: Vec<&str> = symbols(text).collect();
+        assert_eq!(found, vec!["spawn_transcriber", "AudioCapture"]);
+        assert!(is_symbol("MAX_FILE_BYTES"));
+        assert!(is_symbol("sampleRate"));
+        assert!(!is_symbol("__"));
+        assert!(!is_symbol("2_000"));
+    }
+
+    #[test]
+    fn test_resolve_spoken_forms() {
+        let vocabulary = Vocabulary::new(
+            vec!["main.rs".into(), "Cargo.toml".into()],
+            vec!["AudioCapture".into(), "spawn_transcriber".into()],
+        );
+        assert_eq!(vocabulary.resolve("audio capture"), Some("AudioCapture"));
+        assert_eq!(
+            vocabulary.resolve("Spawn transcriber."),
+            Some("spawn_transcriber")
+        );
+        assert_eq!(vocabulary.resolve("main dot rs"), Some("main.rs"));
+        assert_eq!(vocabulary.resolve("cargo.toml"), Some("Cargo.toml"));
+        assert_eq!(vocabulary.resolve("audio"), None);
+    }
+
+    #[test]
+    fn test_prompt_alternates_within_limit() {
+        let vocabulary = Vocabulary::new(
+            vec!["main.rs".into(), "lib.rs".into()],
+            vec!["AudioCapture".into()],
+        );
+        assert_eq!(vocabulary.prompt(), "main.rs, AudioCapture, lib.rs");
+
+        let many = (0..200).map(|i| format!("symbol_{i}")).collect();
+        let prompt = Vocabulary::new(Vec::new(), many).prompt();
+        assert!(prompt.len() <= PROMPT_CHARS);
+        assert!(prompt.starts_with("symbol_0, symbol_1"));
+    }
+
+    #[test]
+    fn test_harvest_tracked_files() {
+        let dir = std::env::temp_dir().join(format!("conch_vocabulary_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(
+            dir.join("src/audio.rs"),
+            "struct AudioCapture; AudioCapture; ring_buffer",
+        )
+        .unwrap();
+        std::fs::write(dir.join("notes.txt"), "untracked_symbol").unwrap();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(&dir)
+                .args(args)
+                .output()
+                .unwrap()
+        };
+        if !git(&["init", "-q"]).status.success() {
+            return;
+        }
+        git(&["add", "src"]);
+
+        let vocabulary = Vocabulary::harvest(&dir, 30).unwrap();
+        assert_eq!(vocabulary.files, vec!["audio.rs"]);
+        assert_eq!(vocabulary.symbols, vec!["AudioCapture", "ring_buffer"]);
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(Vocabulary::harvest(Path::new("/"), 30).is_err());
+    }
+}
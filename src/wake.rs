@@ -0,0 +1,570 @@
+// Wake Module - Always-listening wake word ("hey conch") via a small keyword model
+//
+// The keyword model is a handful of enrolled recordings reduced to log band
+// energies. Detection is an energy gate followed by DTW against the enrolled
+// templates, so it runs cheaply on the monitor buffer without touching Whisper.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::audio::{AudioCapture, AudioConfig, resample};
+use crate::paths;
+
+/// Sample rate features are computed at.
+const FEATURE_RATE: u32 = 16_000;
+/// 25ms analysis frame at 16kHz.
+const FRAME_LEN: usize = 400;
+/// 10ms hop between frames at 16kHz.
+const HOP_LEN: usize = 160;
+/// Number of log-energy bands per frame.
+pub const N_BANDS: usize = 16;
+/// Frame RMS above which a frame counts as speech.
+const SPEECH_RMS: f32 = 0.01;
+/// Quiet frames required after speech before the utterance is judged (200ms).
+const TAIL_FRAMES: usize = 20;
+/// Accepted ratio between a candidate's length and the enrolled length.
+const LENGTH_RATIO: (f32, f32) = (0.6, 1.6);
+/// Band power floor relative to the loudest band in a frame (40dB).
+const DYNAMIC_RANGE: f32 = 1e-4;
+/// Calibrated threshold = worst distance between enrolled takes times this margin.
+const THRESHOLD_MARGIN: f32 = 1.5;
+
+/// Seconds of recent audio the monitor keeps for detection.
+pub const WAKE_WINDOW_SECS: u32 = 2;
+/// Seconds recorded per enrollment take.
+pub const ENROLL_TAKE_SECS: u32 = 2;
+/// Number of takes recorded by `--enroll-wake`.
+pub const ENROLL_TAKES: usize = 3;
+
+/// `[wake]` section of the config file. Off unless explicitly enabled.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct WakeConfig {
+    /// Keep the mic open and listen for the wake word while idle.
+    pub enabled: bool,
+    /// Phrase shown in prompts and the hot-mic indicator.
+    pub phrase: String,
+    /// Keyword model written by `conch --enroll-wake`.
+    pub model: PathBuf,
+    /// Overrides the threshold calibrated at enrollment. Lower is stricter.
+    pub threshold: Option<f32>,
+}
+
+impl Default for WakeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            phrase: "hey conch".into(),
+            model: paths::wake_model_file(),
+            threshold: None,
+        }
+    }
+}
+
+/// Per-frame log band energies.
+pub type Features = Vec<[f32; N_BANDS]>;
+
+/// Enrolled keyword templates, saved as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeywordModel {
+    pub phrase: String,
+    /// Match threshold calibrated from the spread between enrolled takes.
+    pub threshold: f32,
+    pub templates: Vec<Features>,
+}
+
+impl KeywordModel {
+    /// Build a model from enrollment takes of the same phrase.
+    ///
+    /// Each take is trimmed to its voiced region; at least two takes with
+    /// speech are needed to calibrate the threshold.
+    pub fn enroll(phrase: &str, takes: &[Vec<f32>], sample_rate: u32) -> Result<Self> {
+        let mut templates = Vec::new();
+        for (i, take) in takes.iter().enumerate() {
+            let samples = resample(take, sample_rate, FEATURE_RATE);
+            let Some((start, end)) = voiced_range(&frame_rms(&samples)) else {
+                return Err(anyhow!("No speech detected in take {}", i + 1));
+            };
+            templates.push(extract_features(
+                &samples[start * HOP_LEN..(end * HOP_LEN + FRAME_LEN).min(samples.len())],
+            ));
+        }
+        if templates.len() < 2 {
+            return Err(anyhow!("Need at least 2 takes to enroll a wake word"));
+        }
+
+        let mut worst: f32 = 0.0;
+        for i in 0..templates.len() {
+            for j in i + 1..templates.len() {
+                worst = worst.max(dtw_distance(&templates[i], &templates[j]));
+            }
+        }
+
+        Ok(Self {
+            phrase: phrase.to_string(),
+            threshold: worst * THRESHOLD_MARGIN,
+            templates,
+        })
+    }
+
+    /// Load a model written by [`KeywordModel::save`].
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read wake model '{}': {}", path.display(), e))?;
+        serde_json::from_str(&text)
+            .map_err(|e| anyhow!("Invalid wake model '{}': {}", path.display(), e))
+    }
+
+    /// Write the model as JSON.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string(self)?)
+            .map_err(|e| anyhow!("Failed to write wake model '{}': {}", path.display(), e))
+    }
+
+    /// Mean template length in frames.
+    fn mean_len(&self) -> f32 {
+        let total: usize = self.templates.iter().map(Vec::len).sum();
+        total as f32 / self.templates.len().max(1) as f32
+    }
+}
+
+/// `conch --enroll-wake`: record [`ENROLL_TAKES`] takes of the wake phrase
+/// from the mic chosen in `audio` and write the keyword model.
+pub fn enroll(wake: &WakeConfig, audio: &AudioConfig) -> Result<()> {
+    let audio = AudioCapture::with_config(audio)?;
+    eprintln!(
+        "Enrolling wake word \"{}\": {} takes of {}s each.",
+        wake.phrase, ENROLL_TAKES, ENROLL_TAKE_SECS
+    );
+    let mut takes = Vec::with_capacity(ENROLL_TAKES);
+    for i in 1..=ENROLL_TAKES {
+        eprintln!(
+            "Take {}/{}: say \"{}\" now...",
+            i, ENROLL_TAKES, wake.phrase
+        );
+        audio.start_recording();
+        std::thread::sleep(Duration::from_secs(ENROLL_TAKE_SECS as u64));
+        takes.push(audio.stop_recording());
+        std::thread::sleep(Duration::from_millis(500));
+    }
+
+    let model = KeywordModel::enroll(&wake.phrase, &takes, audio.sample_rate())?;
+    model.save(&wake.model)?;
+    eprintln!(
+        "Saved wake model to '{}' (threshold {:.3}).",
+        wake.model.display(),
+        model.threshold
+    );
+    eprintln!("Turn it on with `enabled = true` under [wake] in conch.toml.");
+    Ok(())
+}
+
+/// Outcome of checking the monitor window for the wake word.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WakeCheck {
+    /// Nothing above the speech gate.
+    Quiet,
+    /// Speech is still going; check again later.
+    Listening,
+    /// A complete utterance that isn't the wake word. `distance` is set when
+    /// it got as far as template matching.
+    Rejected { distance: Option<f32> },
+    /// The wake word was spoken.
+    Detected { distance: f32 },
+}
+
+impl WakeCheck {
+    /// Whether the utterance is finished and the window can be discarded.
+    pub fn is_final(&self) -> bool {
+        matches!(self, WakeCheck::Rejected { .. } | WakeCheck::Detected { .. })
+    }
+}
+
+/// Matches recent audio against a [`KeywordModel`].
+pub struct WakeDetector {
+    model: KeywordModel,
+    threshold: f32,
+}
+
+impl WakeDetector {
+    /// Use `threshold` instead of the model's calibrated one when given.
+    pub fn new(model: KeywordModel, threshold: Option<f32>) -> Self {
+        let threshold = threshold.unwrap_or(model.threshold);
+        Self { model, threshold }
+    }
+
+    /// The phrase this detector listens for.
+    pub fn phrase(&self) -> &str {
+        &self.model.phrase
+    }
+
+    /// Check a window of mono audio at `sample_rate` for the wake word.
+    ///
+    /// Only a complete utterance — quiet at both ends of the window — is
+    /// matched, so callers should discard the window once the result
+    /// [`is_final`](WakeCheck::is_final).
+    pub fn check(&self, samples: &[f32], sample_rate: u32) -> WakeCheck {
+        let samples = resample(samples, sample_rate, FEATURE_RATE);
+        let rms = frame_rms(&samples);
+        let Some((start, end)) = voiced_range(&rms) else {
+            return WakeCheck::Quiet;
+        };
+        if rms.len() - end < TAIL_FRAMES {
+            return WakeCheck::Listening;
+        }
+        if start == 0 {
+            // Started before the window; we only have the tail of it.
+            return WakeCheck::Rejected { distance: None };
+        }
+
+        let ratio = (end - start) as f32 / self.model.mean_len();
+        if ratio < LENGTH_RATIO.0 || ratio > LENGTH_RATIO.1 {
+            return WakeCheck::Rejected { distance: None };
+        }
+
+        let candidate = extract_features(&samples[start * HOP_LEN..end * HOP_LEN + FRAME_LEN]);
+        let distance = self
+            .model
+            .templates
+            .iter()
+            .map(|t| dtw_distance(&candidate, t))
+            .fold(f32::INFINITY, f32::min);
+        if distance <= self.threshold {
+            WakeCheck::Detected { distance }
+        } else {
+            WakeCheck::Rejected {
+                distance: Some(distance),
+            }
+        }
+    }
+}
+
+/// A [`WakeDetector`] on the capture's monitor window, opened while the TUI
+/// would take a recording and checked every [`CHECK_INTERVAL`].
+pub struct HotMic {
+    detector: WakeDetector,
+    listening: bool,
+    last_check: Instant,
+}
+
+/// How often the monitor window is checked for the wake word.
+pub const CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+impl HotMic {
+    pub fn new(detector: WakeDetector) -> Self {
+        Self {
+            detector,
+            listening: false,
+            last_check: Instant::now(),
+        }
+    }
+
+    pub fn phrase(&self) -> &str {
+        self.detector.phrase()
+    }
+
+    /// Whether the mic is open listening for the wake word.
+    pub fn is_listening(&self) -> bool {
+        self.listening
+    }
+
+    /// Open or close the monitor on `audio` as `listen` says, then check
+    /// it if it's been [`CHECK_INTERVAL`] since the last check. A judged
+    /// utterance is cleared from the window, and the wake word closes the
+    /// mic for the recording it starts. `None` when nothing was checked.
+    pub fn poll(&mut self, audio: &AudioCapture, listen: bool, now: Instant) -> Option<WakeCheck> {
+        if listen != self.listening {
+            audio.set_monitoring(listen);
+            self.listening = listen;
+        }
+        if !listen || now.duration_since(self.last_check) < CHECK_INTERVAL {
+            return None;
+        }
+        self.last_check = now;
+        let check = self
+            .detector
+            .check(&audio.read_monitor(), audio.sample_rate());
+        if check.is_final() {
+            audio.clear_monitor();
+        }
+        if let WakeCheck::Detected { .. } = check {
+            audio.set_monitoring(false);
+            self.listening = false;
+        }
+        Some(check)
+    }
+}
+
+/// RMS of each analysis frame.
+fn frame_rms(samples: &[f32]) -> Vec<f32> {
+    if samples.len() < FRAME_LEN {
+        return Vec::new();
+    }
+    (0..=(samples.len() - FRAME_LEN) / HOP_LEN)
+        .map(|i| {
+            let frame = &samples[i * HOP_LEN..i * HOP_LEN + FRAME_LEN];
+            (frame.iter().map(|s| s * s).sum::<f32>() / FRAME_LEN as f32).sqrt()
+        })
+        .collect()
+}
+
+/// First and last (exclusive) frame above the speech gate.
+fn voiced_range(rms: &[f32]) -> Option<(usize, usize)> {
+    let start = rms.iter().position(|&r| r > SPEECH_RMS)?;
+    let end = rms.iter().rposition(|&r| r > SPEECH_RMS)? + 1;
+    Some((start, end))
+}
+
+/// Band center frequencies, evenly spaced on the mel scale from 200Hz to 4kHz.
+fn band_centers() -> [f32; N_BANDS] {
+    let mel = |f: f32| 2595.0 * (1.0 + f / 700.0).log10();
+    let hz = |m: f32| 700.0 * (10f32.powf(m / 2595.0) - 1.0);
+    let (lo, hi) = (mel(200.0), mel(4000.0));
+    let mut centers = [0.0; N_BANDS];
+    for (i, c) in centers.iter_mut().enumerate() {
+        *c = hz(lo + (hi - lo) * i as f32 / (N_BANDS - 1) as f32);
+    }
+    centers
+}
+
+/// Power at `freq` in a windowed frame (Goertzel).
+fn goertzel_power(frame: &[f32], freq: f32) -> f32 {
+    let coeff = 2.0 * (2.0 * std::f32::consts::PI * freq / FEATURE_RATE as f32).cos();
+    let (mut s1, mut s2) = (0.0f32, 0.0f32);
+    for &x in frame {
+        let s = x + coeff * s1 - s2;
+        s2 = s1;
+        s1 = s;
+    }
+    s1 * s1 + s2 * s2 - coeff * s1 * s2
+}
+
+/// Log band energies per frame, mean-normalized per band so input gain
+/// doesn't affect matching. Each band sums three bins around its center to
+/// avoid falling between voice harmonics.
+pub fn extract_features(samples: &[f32]) -> Features {
+    let centers = band_centers();
+    let window: Vec<f32> = (0..FRAME_LEN)
+        .map(|i| {
+            0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / (FRAME_LEN - 1) as f32).cos()
+        })
+        .collect();
+
+    let mut frames: Features = Vec::new();
+    let mut windowed = vec![0.0f32; FRAME_LEN];
+    let mut pos = 0;
+    while pos + FRAME_LEN <= samples.len() {
+        for (w, (s, h)) in windowed
+            .iter_mut()
+            .zip(samples[pos..pos + FRAME_LEN].iter().zip(&window))
+        {
+            *w = s * h;
+        }
+        let mut bands = [0.0f32; N_BANDS];
+        for (band, &center) in bands.iter_mut().zip(&centers) {
+            let spread = center * 0.08;
+            *band = [center - spread, center, center + spread]
+                .iter()
+                .map(|&f| goertzel_power(&windowed, f))
+                .sum();
+        }
+        // Clamp to 40dB below the loudest band so leakage noise doesn't dominate
+        let floor = bands.iter().cloned().fold(1e-10, f32::max) * DYNAMIC_RANGE;
+        for band in bands.iter_mut() {
+            *band = band.max(floor).ln();
+        }
+        frames.push(bands);
+        pos += HOP_LEN;
+    }
+
+    if !frames.is_empty() {
+        for b in 0..N_BANDS {
+            let mean = frames.iter().map(|f| f[b]).sum::<f32>() / frames.len() as f32;
+            for f in frames.iter_mut() {
+                f[b] -= mean;
+            }
+        }
+    }
+    frames
+}
+
+/// Dynamic time warping distance between two feature sequences, normalized
+/// by path length so short and long utterances compare on the same scale.
+pub fn dtw_distance(a: &Features, b: &Features) -> f32 {
+    if a.is_empty() || b.is_empty() {
+        return f32::INFINITY;
+    }
+    let frame_dist = |x: &[f32; N_BANDS], y: &[f32; N_BANDS]| {
+        let sq: f32 = x.iter().zip(y).map(|(p, q)| (p - q) * (p - q)).sum();
+        (sq / N_BANDS as f32).sqrt()
+    };
+
+    let m = b.len();
+    let mut prev = vec![f32::INFINITY; m + 1];
+    let mut curr = vec![f32::INFINITY; m + 1];
+    prev[0] = 0.0;
+    for x in a {
+        curr[0] = f32::INFINITY;
+        for j in 1..=m {
+            let best = prev[j].min(curr[j - 1]).min(prev[j - 1]);
+            curr[j] = frame_dist(x, &b[j - 1]) + best;
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[m] / (a.len() + m) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A synthetic "word": two tones back to back, padded with silence.
+    fn word(first_hz: f32, second_hz: f32, amplitude: f32, stretch: f32) -> Vec<f32> {
+        let rate = FEATURE_RATE as f32;
+        let pad = vec![0.0; (0.3 * rate) as usize];
+        let seg = (0.25 * rate * stretch) as usize;
+        let tone = |hz: f32, n: usize| {
+            (0..n).map(move |i| amplitude * (2.0 * std::f32::consts::PI * hz * i as f32 / rate).sin())
+        };
+        let mut out = pad.clone();
+        out.extend(tone(first_hz, seg));
+        out.extend(tone(second_hz, seg));
+        out.extend(pad);
+        out
+    }
+
+    fn model() -> KeywordModel {
+        let takes = vec![
+            word(500.0, 1500.0, 0.3, 1.0),
+            word(500.0, 1500.0, 0.5, 1.1),
+            word(510.0, 1480.0, 0.4, 0.9),
+        ];
+        KeywordModel::enroll("hey conch", &takes, FEATURE_RATE).unwrap()
+    }
+
+    // ===== Features =====
+
+    #[test]
+    fn test_features_are_gain_invariant() {
+        // Voiced region only, as enrollment and detection trim silence first
+        let pad = (0.3 * FEATURE_RATE as f32) as usize;
+        let voiced = |w: Vec<f32>| extract_features(&w[pad..w.len() - pad]);
+        let quiet = voiced(word(500.0, 1500.0, 0.1, 1.0));
+        let loud = voiced(word(500.0, 1500.0, 0.8, 1.0));
+        let other = voiced(word(1500.0, 500.0, 0.8, 1.0));
+        assert!(dtw_distance(&quiet, &loud) < 0.1 * dtw_distance(&quiet, &other));
+    }
+
+    #[test]
+    fn test_dtw_identical_is_zero() {
+        let f = extract_features(&word(500.0, 1500.0, 0.3, 1.0));
+        assert_eq!(dtw_distance(&f, &f), 0.0);
+    }
+
+    #[test]
+    fn test_dtw_empty_is_infinite() {
+        let f = extract_features(&word(500.0, 1500.0, 0.3, 1.0));
+        assert!(dtw_distance(&f, &Vec::new()).is_infinite());
+    }
+
+    // ===== Enrollment =====
+
+    #[test]
+    fn test_enroll_calibrates_threshold() {
+        let m = model();
+        assert_eq!(m.templates.len(), 3);
+        assert!(m.threshold > 0.0);
+    }
+
+    #[test]
+    fn test_enroll_rejects_silent_take() {
+        let takes = vec![word(500.0, 1500.0, 0.3, 1.0), vec![0.0; 16000]];
+        assert!(KeywordModel::enroll("hey conch", &takes, FEATURE_RATE).is_err());
+    }
+
+    #[test]
+    fn test_model_roundtrip() {
+        let m = model();
+        let path = std::env::temp_dir().join(format!("conch_wake_{}.json", std::process::id()));
+        m.save(&path).unwrap();
+        let loaded = KeywordModel::load(&path);
+        let _ = std::fs::remove_file(&path);
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.phrase, "hey conch");
+        assert_eq!(loaded.templates, m.templates);
+    }
+
+    // ===== Detection =====
+
+    #[test]
+    fn test_detects_enrolled_word() {
+        let detector = WakeDetector::new(model(), None);
+        let check = detector.check(&word(505.0, 1490.0, 0.6, 1.05), FEATURE_RATE);
+        assert!(matches!(check, WakeCheck::Detected { .. }), "{check:?}");
+    }
+
+    #[test]
+    fn test_detects_at_device_rate() {
+        let detector = WakeDetector::new(model(), None);
+        let samples = resample(&word(500.0, 1500.0, 0.4, 1.0), FEATURE_RATE, 48_000);
+        let check = detector.check(&samples, 48_000);
+        assert!(matches!(check, WakeCheck::Detected { .. }), "{check:?}");
+    }
+
+    #[test]
+    fn test_rejects_other_word() {
+        let detector = WakeDetector::new(model(), None);
+        let check = detector.check(&word(1500.0, 500.0, 0.4, 1.0), FEATURE_RATE);
+        assert!(matches!(check, WakeCheck::Rejected { distance: Some(_) }), "{check:?}");
+    }
+
+    #[test]
+    fn test_rejects_wrong_length() {
+        let detector = WakeDetector::new(model(), None);
+        let check = detector.check(&word(500.0, 1500.0, 0.4, 3.0), FEATURE_RATE);
+        assert_eq!(check, WakeCheck::Rejected { distance: None });
+    }
+
+    #[test]
+    fn test_silence_is_quiet() {
+        let detector = WakeDetector::new(model(), None);
+        assert_eq!(detector.check(&vec![0.0; 32000], FEATURE_RATE), WakeCheck::Quiet);
+    }
+
+    #[test]
+    fn test_ongoing_speech_is_listening() {
+        let detector = WakeDetector::new(model(), None);
+        let mut samples = word(500.0, 1500.0, 0.4, 1.0);
+        samples.truncate(samples.len() - (0.25 * FEATURE_RATE as f32) as usize);
+        let check = detector.check(&samples, FEATURE_RATE);
+        assert_eq!(check, WakeCheck::Listening);
+        assert!(!check.is_final());
+    }
+
+    #[test]
+    fn test_hot_mic_checks_while_listening() {
+        let source = crate::fake_audio::FakeSource::new(vec![0.0; 16000], 16000);
+        let audio = AudioCapture::with_source(Box::new(source)).unwrap();
+        let mut hot_mic = HotMic::new(WakeDetector::new(model(), None));
+        let later = Instant::now() + CHECK_INTERVAL;
+        assert_eq!(hot_mic.poll(&audio, false, later), None);
+        assert!(!hot_mic.is_listening());
+
+        assert_eq!(hot_mic.poll(&audio, true, later), Some(WakeCheck::Quiet));
+        assert!(hot_mic.is_listening());
+        // Not again until the interval has passed
+        assert_eq!(hot_mic.poll(&audio, true, later), None);
+        let check = hot_mic.poll(&audio, true, later + CHECK_INTERVAL);
+        assert_eq!(check, Some(WakeCheck::Quiet));
+    }
+
+    #[test]
+    fn test_threshold_override() {
+        let detector = WakeDetector::new(model(), Some(0.0));
+        let check = detector.check(&word(505.0, 1490.0, 0.6, 1.05), FEATURE_RATE);
+        assert!(matches!(check, WakeCheck::Rejected { .. }));
+    }
+}
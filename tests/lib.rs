@@ -3,9 +3,22 @@
 
 #[cfg(test)]
 mod integration {
+    use std::path::PathBuf;
+
+    use conch::audio::{AudioCapture, RecordingState, RingBuffer, resample};
+    use conch::focus::{FocusEntry, FocusState, map_tool_event};
+    use conch::stt::Transcriber;
+    use conch::transport::{
+        OpenCodeClient, ServerEvent, ToolEvent, extract_sse_data_lines, parse_sse_event,
+    };
+    use conch::viz::{WaveformData, WaveformWidget, compute_rms_windows};
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
     // ===== Cross-Module Communication Tests =====
 
     #[test]
+    #[ignore = "needs model/audio device"]
     fn test_audio_to_stt_pipeline() {
         // Test: Audio module output can be consumed by STT module
         // Plan requirement: Module interface compatibility
@@ -16,10 +29,19 @@ mod integration {
     fn test_audio_to_viz_pipeline() {
         // Test: Audio module output can be consumed by Viz module
         // Plan requirement: FFT reads from ring buffer
-        todo!("Create ring buffer, read chunks for FFT, verify interface works");
+        let mut buf = RingBuffer::new(16000);
+        let tone: Vec<f32> = (0..8000)
+            .map(|i| (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 16000.0).sin())
+            .collect();
+        buf.write(&tone);
+        let recent = buf.read_last(1600);
+        let data = WaveformData::from_samples(&recent, 40, 0.001);
+        assert_eq!(data.bars.len(), 40);
+        assert!(data.bars.iter().any(|&v| v > 0.0));
     }
 
     #[test]
+    #[ignore = "needs model/audio device"]
     fn test_stt_to_transport_pipeline() {
         // Test: STT transcript can be sent via transport module
         // Plan requirement: Transcript sending
@@ -30,14 +52,29 @@ mod integration {
     fn test_transport_to_focus_pipeline() {
         // Test: Transport events can be processed by focus module
         // Plan requirement: SSE events update focus
-        todo!("Receive SSE event, parse, update focus, verify flow");
+        let json = r#"{"type":"message.part.updated","properties":{"part":{"type":"tool","tool":"read","state":{"status":"completed","input":{"filePath":"src/main.rs"}}}}}"#;
+        let Some(ServerEvent::Tool(event)) = parse_sse_event(json) else {
+            panic!("expected a tool event");
+        };
+        let mut focus = FocusState::new();
+        focus.append(map_tool_event(&event).unwrap());
+        assert_eq!(
+            focus.current_entry(),
+            Some(&FocusEntry::File(PathBuf::from("src/main.rs")))
+        );
     }
 
     #[test]
     fn test_focus_to_transport_pipeline() {
         // Test: Focus context can be serialized and sent via transport
         // Plan requirement: Context included in prompts
-        todo!("Get focus context, serialize, include in HTTP request");
+        let mut focus = FocusState::new();
+        focus.append(FocusEntry::Directory(PathBuf::from("src")));
+        let context = focus.to_context_json();
+        assert_eq!(context["current_focus"]["type"], "directory");
+        let prompt = format!("{}\nshow me what's in here", focus.to_context_string().unwrap());
+        let body = serde_json::json!({ "parts": [{ "type": "text", "text": prompt }] });
+        assert!(body["parts"][0]["text"].as_str().unwrap().contains("src"));
     }
 
     // ===== Public API Tests =====
@@ -46,35 +83,50 @@ mod integration {
     fn test_audio_module_public_api() {
         // Test: Audio module exposes expected public interface
         // Plan requirement: Clean module boundaries
-        todo!("Verify RingBuffer, RecordingState, and audio capture functions are public");
+        let mut buf = RingBuffer::new(4);
+        buf.write(&[0.1, 0.2]);
+        assert_eq!(buf.read_all(), vec![0.1, 0.2]);
+        assert_eq!(RecordingState::Idle, RecordingState::Idle);
+        let _new: fn() -> anyhow::Result<AudioCapture> = AudioCapture::new;
+        assert_eq!(resample(&[0.0; 480], 48000, 16000).len(), 160);
     }
 
     #[test]
     fn test_stt_module_public_api() {
         // Test: STT module exposes transcription function
         // Plan requirement: Simple transcription API
-        todo!("Verify transcribe(audio_buffer) -> String function exists");
+        let _transcribe: fn(&Transcriber, &[f32], u32) -> anyhow::Result<String> =
+            Transcriber::transcribe;
     }
 
     #[test]
     fn test_viz_module_public_api() {
-        // Test: Viz module exposes FFT and rendering functions
+        // Test: Viz module exposes waveform computation and rendering
         // Plan requirement: Visualization interface
-        todo!("Verify compute_fft and render_spectrogram functions are public");
+        assert_eq!(compute_rms_windows(&[1.0; 100], 5), vec![1.0; 5]);
+        let data = WaveformData::empty();
+        let _widget = WaveformWidget::new(&data);
     }
 
     #[test]
     fn test_focus_module_public_api() {
         // Test: Focus module exposes FocusEntry enum and history management
         // Plan requirement: Focus API
-        todo!("Verify FocusEntry, FocusHistory, and mapping functions are public");
+        let mut focus = FocusState::new();
+        focus.append(FocusEntry::Branch("main".into()));
+        focus.toggle_follow_mode();
+        assert_eq!(focus.len(), 1);
+        let _map: fn(&ToolEvent) -> Option<FocusEntry> = map_tool_event;
     }
 
     #[test]
     fn test_transport_module_public_api() {
         // Test: Transport module exposes client and event types
         // Plan requirement: OpenCode communication API
-        todo!("Verify OpenCodeClient, send_prompt, subscribe_events functions are public");
+        let mut client = OpenCodeClient::new("http://127.0.0.1:4096");
+        client.set_session("ses_abc".into());
+        assert_eq!(client.session_id(), Some("ses_abc"));
+        assert_eq!(extract_sse_data_lines("data: {}\n\n"), vec!["{}"]);
     }
 
     // ===== Type Compatibility Tests =====
@@ -83,21 +135,42 @@ mod integration {
     fn test_pcm_buffer_type_compatibility() {
         // Test: Audio PCM buffer type is compatible across modules
         // Plan requirement: Vec<f32> or similar shared type
-        todo!("Verify audio buffer type works for both STT and FFT");
+        let mut buf = RingBuffer::new(48000);
+        buf.write(&vec![0.25; 48000]);
+        let pcm: Vec<f32> = buf.read_all();
+        assert_eq!(resample(&pcm, 48000, 16000).len(), 16000);
+        assert_eq!(compute_rms_windows(&pcm, 10).len(), 10);
     }
 
     #[test]
     fn test_focus_entry_serialization_compatibility() {
         // Test: FocusEntry can be serialized for transport
         // Plan requirement: Serde compatibility
-        todo!("Verify FocusEntry implements Serialize/Deserialize");
+        let entries = vec![
+            FocusEntry::File(PathBuf::from("src/main.rs")),
+            FocusEntry::Directory(PathBuf::from("src")),
+            FocusEntry::Branch("main".into()),
+            FocusEntry::Commit("abc1234".into()),
+        ];
+        let json = serde_json::to_string(&entries).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"type":"file","value":"src/main.rs"},{"type":"directory","value":"src"},{"type":"branch","value":"main"},{"type":"commit","value":"abc1234"}]"#
+        );
+        let back: Vec<FocusEntry> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, entries);
     }
 
     #[test]
     fn test_event_type_deserialization() {
         // Test: SSE event JSON can be deserialized to Rust types
         // Plan requirement: Event parsing
-        todo!("Deserialize tool.execute event JSON to Rust struct");
+        let event: ToolEvent = serde_json::from_str(
+            r#"{"tool":"bash","input":{"command":"cd src"},"state":"completed"}"#,
+        )
+        .unwrap();
+        assert_eq!(event.tool, "bash");
+        assert_eq!(event.input["command"], "cd src");
     }
 
     // ===== Error Propagation Tests =====
@@ -106,21 +179,32 @@ mod integration {
     fn test_error_types_across_modules() {
         // Test: Error types are compatible and composable
         // Plan requirement: Error handling
-        todo!("Verify module errors can be converted/wrapped for propagation");
+        fn load_pipeline() -> anyhow::Result<Transcriber> {
+            let transcriber = Transcriber::new("/nonexistent/model.bin")?;
+            Ok(transcriber)
+        }
+        let err = load_pipeline()
+            .map_err(|e| e.context("starting conch"))
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "starting conch");
+        assert!(format!("{:#}", err).contains("Failed to load Whisper model"));
     }
 
     #[test]
     fn test_stt_error_propagation() {
         // Test: STT errors propagate to caller correctly
         // Plan requirement: Error handling
-        todo!("Trigger STT error, verify it's returned as Result::Err");
+        assert!(Transcriber::new("/nonexistent/model.bin").is_err());
     }
 
-    #[test]
-    fn test_transport_error_propagation() {
+    #[tokio::test]
+    async fn test_transport_error_propagation() {
         // Test: Transport errors propagate correctly
         // Plan requirement: Network error handling
-        todo!("Trigger network error, verify it's returned as Result::Err");
+        let client = OpenCodeClient::new("http://127.0.0.1:4096");
+        let err = client.send_prompt("hello").await.unwrap_err();
+        assert!(err.to_string().contains("no session set"));
     }
 
     // ===== Thread Safety Tests =====
@@ -129,21 +213,21 @@ mod integration {
     fn test_ring_buffer_send_sync() {
         // Test: RingBuffer can be shared across threads
         // Plan requirement: Concurrent audio capture and reading
-        todo!("Verify RingBuffer implements Send + Sync");
+        assert_send_sync::<RingBuffer>();
     }
 
     #[test]
     fn test_focus_history_send_sync() {
         // Test: FocusHistory can be shared across threads
         // Plan requirement: Async architecture
-        todo!("Verify FocusHistory implements Send + Sync");
+        assert_send_sync::<FocusState>();
     }
 
     #[test]
     fn test_whisper_model_thread_safety() {
         // Test: Whisper model can be used safely in async context
         // Plan requirement: Tokio integration
-        todo!("Verify Whisper wrapper is thread-safe or can be Arc<Mutex<>>ed");
+        assert_send_sync::<Transcriber>();
     }
 
     // ===== Build and Dependency Tests =====
@@ -165,6 +249,7 @@ mod integration {
     // ===== Module Initialization Tests =====
 
     #[test]
+    #[ignore = "needs model/audio device"]
     fn test_audio_module_initialization() {
         // Test: Audio module can be initialized
         // Plan requirement: Module setup
@@ -172,6 +257,7 @@ mod integration {
     }
 
     #[test]
+    #[ignore = "needs model/audio device"]
     fn test_stt_module_initialization() {
         // Test: STT module can load Whisper model
         // Plan requirement: "The user downloads it once"
@@ -179,6 +265,7 @@ mod integration {
     }
 
     #[test]
+    #[ignore = "needs model/audio device"]
     fn test_transport_module_initialization() {
         // Test: Transport module can connect to OpenCode
         // Plan requirement: OpenCode connectivity
@@ -189,13 +276,23 @@ mod integration {
     fn test_focus_module_initialization() {
         // Test: Focus module can be initialized (possibly with log file)
         // Plan requirement: Focus setup
-        todo!("Initialize focus module and verify empty or loaded state");
+        assert!(FocusState::new().is_empty());
+        let events = vec![ToolEvent {
+            call_id: None,
+            session_id: None,
+            message_id: None,
+            tool: "list".into(),
+            input: serde_json::json!({"path": "src"}),
+            state: "completed".into(),
+            metadata: serde_json::Value::Null,
+        }];
+        assert_eq!(FocusState::rebuild_from_events(&events).len(), 1);
     }
 
     #[test]
     fn test_viz_module_initialization() {
         // Test: Viz module can be initialized
         // Plan requirement: FFT setup
-        todo!("Initialize FFT planner and verify success");
+        assert!(WaveformData::empty().bars.is_empty());
     }
 }